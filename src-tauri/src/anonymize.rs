@@ -0,0 +1,197 @@
+//! 恩兔的“打码笔”（分享工作汇报前先把隐私涂掉）
+//!
+//! 主人想把日志发给别人帮忙看问题时，里面往往夹着公网地址、网卡 MAC、小组暗号甚至密语。
+//! `anonymize_logs()` 会把这些东西换成占位符再交出去：
+//! - 公网 IPv4/IPv6 → `<ip-1>`、`<ip-2>`……（私网/回环/链路本地地址对排错有用，保留原样）
+//! - MAC 地址 → `<mac-1>`、`<mac-2>`……
+//! - 暗号、密语、总部主机名 → `<community>`、`<key>`、`<supernode>`
+//!
+//! 同一个值在同一次导出里总是换成同一个占位符，看日志的人仍然能对上“是不是同一台机器”。
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::config::ProfileStore;
+use crate::n2n_process;
+
+/// 需要按原文涂掉的敏感词
+#[derive(Debug, Clone, Default)]
+pub struct Secrets {
+    /// (原文, 占位符)
+    words: Vec<(String, &'static str)>,
+}
+
+impl Secrets {
+    /// 从指示簿里收集全部方案的暗号、密语、管理口令和总部主机名
+    pub fn from_store(store: &ProfileStore) -> Self {
+        let mut secrets = Self::default();
+        for profile in &store.profiles {
+            let config = &profile.config;
+            secrets.add(&config.encryption_key, "<key>");
+            secrets.add(&config.community, "<community>");
+            secrets.add(config.management_password.as_deref().unwrap_or_default(), "<password>");
+            secrets.add(n2n_process::supernode_host(&config.supernode), "<supernode>");
+        }
+        for entry in &store.supernodes {
+            secrets.add(n2n_process::supernode_host(&entry.address), "<supernode>");
+        }
+        // 长的先替换，免得短词把长词拆坏
+        secrets.words.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        secrets
+    }
+
+    fn add(&mut self, word: &str, placeholder: &'static str) {
+        let word = word.trim();
+        // 太短的词（比如单个字母）替换起来误伤太多，跳过
+        if word.chars().count() < 3 || self.words.iter().any(|(w, _)| w == word) {
+            return;
+        }
+        self.words.push((word.to_string(), placeholder));
+    }
+}
+
+/// 给一批日志行打码
+pub fn anonymize_logs(lines: &[String], secrets: &Secrets) -> Vec<String> {
+    let mut aliases = Aliases::default();
+    lines
+        .iter()
+        .map(|line| {
+            let mut line = line.clone();
+            for (word, placeholder) in &secrets.words {
+                line = line.replace(word.as_str(), placeholder);
+            }
+            mask_addresses(&line, &mut aliases)
+        })
+        .collect()
+}
+
+/// 同一个地址总是换成同一个占位符
+#[derive(Default)]
+struct Aliases {
+    ips: HashMap<String, usize>,
+    macs: HashMap<String, usize>,
+}
+
+impl Aliases {
+    fn ip(&mut self, ip: &str) -> String {
+        let next = self.ips.len() + 1;
+        format!("<ip-{}>", self.ips.entry(ip.to_string()).or_insert(next))
+    }
+
+    fn mac(&mut self, mac: &str) -> String {
+        let next = self.macs.len() + 1;
+        format!("<mac-{}>", self.macs.entry(mac.to_ascii_lowercase()).or_insert(next))
+    }
+}
+
+/// 把一行里的公网地址和 MAC 换成占位符
+fn mask_addresses(line: &str, aliases: &mut Aliases) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_hexdigit() || c == ':') {
+        out.push_str(&rest[..start]);
+        let token_len = rest[start..]
+            .find(|c: char| !(c.is_ascii_hexdigit() || c == ':' || c == '.' || c == '-'))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + token_len];
+        out.push_str(&mask_token(token, aliases));
+        rest = &rest[start + token_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 认一认这一段是不是地址（末尾的标点和 `:端口` 原样留下）
+fn mask_token(token: &str, aliases: &mut Aliases) -> String {
+    if is_mac(token) {
+        return aliases.mac(token);
+    }
+
+    let trimmed = token.trim_end_matches(['.', ':', '-']);
+    let tail = &token[trimmed.len()..];
+
+    if let Ok(ip) = trimmed.parse::<Ipv6Addr>() {
+        return if is_public_v6(&ip) {
+            format!("{}{}", aliases.ip(trimmed), tail)
+        } else {
+            token.to_string()
+        };
+    }
+
+    // IPv4 后面可能紧跟着 :端口
+    let (host, port) = match trimmed.split_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => (host, Some(port)),
+        _ => (trimmed, None),
+    };
+    match host.parse::<Ipv4Addr>() {
+        Ok(ip) if is_public_v4(&ip) => {
+            let mut masked = aliases.ip(host);
+            if let Some(port) = port {
+                masked.push(':');
+                masked.push_str(port);
+            }
+            masked.push_str(tail);
+            masked
+        }
+        _ => token.to_string(),
+    }
+}
+
+/// `aa:bb:cc:dd:ee:ff` 或 `aa-bb-cc-dd-ee-ff`
+fn is_mac(token: &str) -> bool {
+    let sep = if token.contains(':') { ':' } else { '-' };
+    let parts: Vec<&str> = token.split(sep).collect();
+    parts.len() == 6 && parts.iter().all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    // 100.64.0.0/10 是运营商级 NAT 地址，同样不算公网；
+    // 0.x 和 224 以上是保留/组播段（255.255.255.0 这种子网掩码也落在这里）
+    let cgnat = a == 100 && (64..128).contains(&b);
+    !(a == 0
+        || a >= 224
+        || ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || cgnat)
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    let unique_local = (first & 0xfe00) == 0xfc00;
+    let link_local = (first & 0xffc0) == 0xfe80;
+    !(ip.is_loopback() || ip.is_unspecified() || ip.is_multicast() || unique_local || link_local)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::N2NConfig;
+
+    #[test]
+    fn test_anonymize_logs() {
+        let store = ProfileStore::from_single(N2NConfig {
+            supernode: "sn.example.com:7777".to_string(),
+            community: "family".to_string(),
+            encryption_key: "s3cret-key".to_string(),
+            ..Default::default()
+        });
+        let secrets = Secrets::from_store(&store);
+        let lines = vec![
+            "[OUT] 15/Mar/2024 10:00:00 supernode sn.example.com resolved to 203.0.113.7:7777.".to_string(),
+            "[OUT] created local tap device IP: 10.0.0.2, Mask: 255.255.255.0, MAC: 0A:1B:2C:3D:4E:5F".to_string(),
+            "[INFO] args: -c family -k s3cret-key, peer 203.0.113.7 via 2001:db8::1 and fe80::1".to_string(),
+        ];
+        assert_eq!(
+            anonymize_logs(&lines, &secrets),
+            vec![
+                "[OUT] 15/Mar/2024 10:00:00 supernode <supernode> resolved to <ip-1>:7777.",
+                "[OUT] created local tap device IP: 10.0.0.2, Mask: 255.255.255.0, MAC: <mac-1>",
+                "[INFO] args: -c <community> -k <key>, peer <ip-1> via <ip-2> and fe80::1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! 恩兔的身份卡（版本、构建指纹、运行环境）
+//!
+//! 主人来报问题时，最先要问的总是“哪个版本、哪个系统、edge 是哪一版”。以前工作台只能自己猜
+//! （前端拿不到 Rust 和 edge 的版本），这里由后端一次填好：
+//! - 恩兔的版本、构建时的 git 提交、编译用的 Rust 版本（由 build.rs 记下）和 Tauri 版本；
+//! - 实际会用的 edge（当前方案填的、或者打包附带的）路径和版本；
+//! - 系统名称和版本、CPU 架构、系统的网页引擎版本。
+//!
+//! “关于恩兔”里显示它，排查时整张复制给维护者。
+use serde::Serialize;
+
+use crate::n2n_process;
+
+/// 构建时拿不到的信息显示成这个
+const UNKNOWN: &str = "unknown";
+
+/// 恩兔的身份卡
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    /// 恩兔的版本
+    pub version: String,
+    /// 构建时的 git 提交（不是从仓库构建时为 unknown）
+    pub git_hash: String,
+    /// 调试版还是发布版
+    pub build_profile: String,
+    pub tauri_version: String,
+    pub rust_version: String,
+    /// 实际会用的 edge
+    pub edge_path: String,
+    /// edge 的版本（问不出来时为 None）
+    pub edge_version: Option<String>,
+    /// 系统（linux / windows / macos ...）
+    pub os: String,
+    /// 系统的发行版和版本（读不出来时为 None）
+    pub os_version: Option<String>,
+    /// CPU 架构
+    pub arch: String,
+    /// 系统网页引擎的版本（WebView2 / WebKitGTK / WKWebView，读不出来时为 None）
+    pub webview_version: Option<String>,
+}
+
+/// 填写身份卡（会调用 `edge -h`，可能要等一两秒，别放在界面线程里）
+pub fn collect(edge_path: &str, tauri_version: &str, webview_version: Option<String>) -> AppInfo {
+    AppInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("N2N_MAID_GIT_HASH").unwrap_or(UNKNOWN).to_string(),
+        build_profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_string(),
+        tauri_version: tauri_version.to_string(),
+        rust_version: option_env!("N2N_MAID_RUSTC_VERSION").unwrap_or(UNKNOWN).to_string(),
+        edge_path: edge_path.to_string(),
+        edge_version: n2n_process::detect_edge_version(edge_path),
+        os: std::env::consts::OS.to_string(),
+        os_version: os_version(),
+        arch: std::env::consts::ARCH.to_string(),
+        webview_version,
+    }
+}
+
+impl AppInfo {
+    /// 一行写完的身份卡（放在导出的工作汇报开头）
+    pub fn summary(&self) -> String {
+        format!(
+            "N2N Maid {} ({}, {}) · Tauri {} · {} · edge {} · {} {} ({}) · webview {}",
+            self.version,
+            self.git_hash,
+            self.build_profile,
+            self.tauri_version,
+            self.rust_version,
+            self.edge_version.as_deref().unwrap_or(UNKNOWN),
+            self.os,
+            self.arch,
+            self.os_version.as_deref().unwrap_or(UNKNOWN),
+            self.webview_version.as_deref().unwrap_or(UNKNOWN),
+        )
+    }
+}
+
+/// 从 `/etc/os-release` 里读出发行版名字（优先 PRETTY_NAME）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_os_release(content: &str) -> Option<String> {
+    let value = |key: &str| {
+        content.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?.trim().trim_matches('"');
+            (!value.is_empty()).then(|| value.to_string())
+        })
+    };
+    value("PRETTY_NAME").or_else(|| {
+        let name = value("NAME")?;
+        Some(match value("VERSION_ID") {
+            Some(version) => format!("{} {}", name, version),
+            None => name,
+        })
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> Option<String> {
+    let content = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+        .ok()?;
+    parse_os_release(&content)
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    let output = std::process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then(|| format!("macOS {}", version))
+}
+
+/// Windows：`ver` 念出来的是 “Microsoft Windows [Version 10.0.22631.3007]”
+#[cfg(target_os = "windows")]
+fn os_version() -> Option<String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("cmd")
+        .args(["/C", "ver"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn os_version() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_os_release_and_collect() {
+        let debian = "NAME=\"Debian GNU/Linux\"\nVERSION_ID=\"12\"\nPRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\n";
+        assert_eq!(parse_os_release(debian).as_deref(), Some("Debian GNU/Linux 12 (bookworm)"));
+        assert_eq!(parse_os_release("NAME=Alpine\nVERSION_ID=3.19\n").as_deref(), Some("Alpine 3.19"));
+        assert_eq!(parse_os_release("PRETTY_NAME=\"\"\n"), None);
+
+        let info = collect("/no/such/edge", "2.0.0", None);
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.edge_version, None);
+        assert_eq!(info.os, std::env::consts::OS);
+        assert!(!info.git_hash.is_empty() && !info.rust_version.is_empty());
+        assert!(info.summary().starts_with(&format!("N2N Maid {} (", info.version)));
+    }
+}
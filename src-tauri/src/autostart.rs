@@ -0,0 +1,147 @@
+//! 开机自启（登录系统后自动打开恩兔）
+//!
+//! 各个系统登记“登录后启动”的地方不一样：
+//! - Linux：`~/.config/autostart/` 里的 `.desktop` 文件（XDG Autostart，GNOME/KDE/Xfce 都认）；
+//! - Windows：当前用户的 `HKCU\Software\Microsoft\Windows\CurrentVersion\Run`；
+//! - macOS：`~/Library/LaunchAgents/` 里的 plist（登录时由 launchd 拉起来）。
+//!
+//! 都只登记当前用户、不需要管理员权限。登记的是恩兔现在这个可执行文件的路径，挪了位置要重新打开一次开关。
+use anyhow::{Context, Result};
+
+/// 登记用的名字
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+const ENTRY_NAME: &str = "n2n-maid";
+
+/// 恩兔现在这个可执行文件
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows", target_os = "macos")), allow(dead_code))]
+fn current_exe() -> Result<String> {
+    let exe = std::env::current_exe().context("找不到恩兔自己的可执行文件")?;
+    Ok(exe.display().to_string())
+}
+
+/// XDG Autostart 的 `.desktop` 内容（路径里有空格时要加引号）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn desktop_entry(exe: &str) -> String {
+    let exec = if exe.contains(' ') { format!("\"{}\"", exe) } else { exe.to_string() };
+    format!(
+        "[Desktop Entry]\nType=Application\nName=N2N Maid\nExec={}\nX-GNOME-Autostart-enabled=true\nNoDisplay=true\n",
+        exec
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn desktop_file() -> Result<std::path::PathBuf> {
+    Ok(dirs::config_dir()
+        .context("无法获取配置目录")?
+        .join("autostart")
+        .join(format!("{}.desktop", ENTRY_NAME)))
+}
+
+/// 现在登记了没有（这个平台不支持时为 None）
+#[cfg(target_os = "linux")]
+pub fn is_enabled() -> Option<bool> {
+    Some(desktop_file().ok()?.exists())
+}
+
+/// 登记或撤掉开机自启
+#[cfg(target_os = "linux")]
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    let path = desktop_file()?;
+    if !enabled {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).context("撤掉开机自启失败"),
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, desktop_entry(&current_exe()?)).context("登记开机自启失败")
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[cfg(target_os = "windows")]
+pub fn is_enabled() -> Option<bool> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    let run = winreg::RegKey::predef(HKEY_CURRENT_USER).open_subkey(RUN_KEY).ok()?;
+    Some(run.get_value::<String, _>(ENTRY_NAME).is_ok())
+}
+
+#[cfg(target_os = "windows")]
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    let (run, _) = winreg::RegKey::predef(HKEY_CURRENT_USER)
+        .create_subkey(RUN_KEY)
+        .context("打开注册表的 Run 键失败")?;
+    if enabled {
+        run.set_value(ENTRY_NAME, &format!("\"{}\"", current_exe()?))
+            .context("登记开机自启失败")
+    } else {
+        match run.delete_value(ENTRY_NAME) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).context("撤掉开机自启失败"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent() -> Result<std::path::PathBuf> {
+    Ok(dirs::home_dir()
+        .context("找不到主人的家目录")?
+        .join("Library/LaunchAgents/com.n2n.maid.plist"))
+}
+
+#[cfg(target_os = "macos")]
+pub fn is_enabled() -> Option<bool> {
+    Some(launch_agent().ok()?.exists())
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_enabled(enabled: bool) -> Result<()> {
+    let path = launch_agent()?;
+    if !enabled {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).context("撤掉开机自启失败"),
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let plist = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\"><dict>\n\
+         <key>Label</key><string>com.n2n.maid</string>\n\
+         <key>ProgramArguments</key><array><string>{}</string></array>\n\
+         <key>RunAtLoad</key><true/>\n\
+         </dict></plist>\n",
+        current_exe()?.replace('&', "&amp;").replace('<', "&lt;")
+    );
+    std::fs::write(&path, plist).context("登记开机自启失败")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub fn is_enabled() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub fn set_enabled(_enabled: bool) -> Result<()> {
+    anyhow::bail!("这个平台上恩兔还不会开机自启")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_quotes_paths_with_spaces() {
+        let entry = desktop_entry("/opt/N2N Maid/n2n-maid");
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Exec=\"/opt/N2N Maid/n2n-maid\"\n"));
+        assert!(desktop_entry("/usr/bin/n2n-maid").contains("Exec=/usr/bin/n2n-maid\n"));
+    }
+}
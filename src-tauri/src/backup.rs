@@ -0,0 +1,184 @@
+//! 指示簿的备份柜（自动留存带时间戳的副本）
+//!
+//! 多份方案、地址簿、钉扎的公钥……这么复杂的指示簿，手滑改坏一次或者文件写坏一次就很心疼。
+//! 恩兔每次改写指示簿之前，会把旧版本收进配置目录下的 `backups/` 里：
+//! - 文件名是 `config-<Unix 秒>.toml`，时间戳同时也是恢复时用的编号；
+//! - 两次备份至少间隔 `interval_minutes`，内容没变就不重复备份；
+//! - 最多保留 `keep` 份，超过 `retention_days` 天的旧备份会被清掉（最新的一份永远留着）。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 备份策略（记在指示簿里）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupPolicy {
+    /// 最多保留几份（0 表示不自动备份）
+    pub keep: usize,
+    /// 两次自动备份的最短间隔（分钟）
+    pub interval_minutes: u64,
+    /// 备份最多保留几天（0 表示不按时间清理）
+    pub retention_days: u64,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            keep: 10,
+            interval_minutes: 10,
+            retention_days: 30,
+        }
+    }
+}
+
+/// 一份备份的概要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// 备份时间（Unix 秒，也是恢复时用的编号）
+    pub timestamp: u64,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+fn backup_dir(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .map(|dir| dir.join("backups"))
+        .unwrap_or_else(|| PathBuf::from("backups"))
+}
+
+fn backup_path(config_path: &Path, timestamp: u64) -> PathBuf {
+    backup_dir(config_path).join(format!("config-{}.toml", timestamp))
+}
+
+/// 列出全部备份（新的在前）
+pub fn list(config_path: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = fs::read_dir(backup_dir(config_path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<BackupInfo> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = name.strip_prefix("config-")?.strip_suffix(".toml")?.parse().ok()?;
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(BackupInfo { timestamp, size })
+        })
+        .collect();
+    backups.sort_by_key(|b| std::cmp::Reverse(b.timestamp));
+    backups
+}
+
+/// 读出某一份备份的内容
+pub fn read(config_path: &Path, timestamp: u64) -> Result<String> {
+    fs::read_to_string(backup_path(config_path, timestamp))
+        .with_context(|| format!("找不到时间为 {} 的备份", timestamp))
+}
+
+/// 改写指示簿之前调用：按策略决定要不要把当前文件收进备份柜
+pub fn maybe_backup(config_path: &Path, policy: &BackupPolicy, now: u64) -> Result<Option<u64>> {
+    if policy.keep == 0 || !config_path.exists() {
+        return Ok(None);
+    }
+    if let Some(latest) = list(config_path).first() {
+        if now.saturating_sub(latest.timestamp) < policy.interval_minutes * 60 {
+            return Ok(None);
+        }
+        let current = fs::read_to_string(config_path).context("读取配置文件失败")?;
+        if read(config_path, latest.timestamp).ok().as_deref() == Some(current.as_str()) {
+            return Ok(None);
+        }
+    }
+    force_backup(config_path, now)?;
+    prune(config_path, policy, now);
+    Ok(Some(now))
+}
+
+/// 不管策略，立刻备份一份（恢复备份前先保住当前版本）
+pub fn force_backup(config_path: &Path, now: u64) -> Result<()> {
+    if !config_path.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(backup_dir(config_path)).context("创建备份目录失败")?;
+    fs::copy(config_path, backup_path(config_path, now)).context("备份配置文件失败")?;
+    Ok(())
+}
+
+/// 把柜子里的每一份备份换个写法重新存好（指示簿加密、换密码、取消加密时用）；
+/// 换不了的那份留着也没用，直接扔掉。返回扔了几份
+pub fn rewrite_all(config_path: &Path, mut convert: impl FnMut(String) -> Result<String>) -> usize {
+    let mut dropped = 0;
+    for backup in list(config_path) {
+        let path = backup_path(config_path, backup.timestamp);
+        let rewritten = read(config_path, backup.timestamp)
+            .and_then(&mut convert)
+            .and_then(|content| fs::write(&path, content).context("改写备份失败"));
+        if let Err(e) = rewritten {
+            log::warn!("备份 {} 没法改写，扔掉了：{:#}", backup.timestamp, e);
+            if fs::remove_file(&path).is_ok() {
+                dropped += 1;
+            }
+        }
+    }
+    dropped
+}
+
+/// 按数量和天数清理旧备份（最新的一份永远留着）
+fn prune(config_path: &Path, policy: &BackupPolicy, now: u64) {
+    let max_age = policy.retention_days * 24 * 60 * 60;
+    for (idx, backup) in list(config_path).iter().enumerate() {
+        let too_many = idx >= policy.keep.max(1);
+        let too_old = idx > 0 && max_age > 0 && now.saturating_sub(backup.timestamp) > max_age;
+        if too_many || too_old {
+            if let Err(e) = fs::remove_file(backup_path(config_path, backup.timestamp)) {
+                log::warn!("清理旧备份失败：{}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_backups_are_throttled_and_pruned() {
+        let dir = TempDir::new("backup");
+        let path = dir.join("config.toml");
+        let policy = BackupPolicy {
+            keep: 2,
+            interval_minutes: 10,
+            retention_days: 1,
+        };
+
+        fs::write(&path, "v1").unwrap();
+        assert_eq!(maybe_backup(&path, &policy, 1_000).unwrap(), Some(1_000));
+        fs::write(&path, "v2").unwrap();
+        // 间隔太短，不备份
+        assert_eq!(maybe_backup(&path, &policy, 1_100).unwrap(), None);
+        assert_eq!(maybe_backup(&path, &policy, 2_000).unwrap(), Some(2_000));
+        // 内容没变，不备份
+        assert_eq!(maybe_backup(&path, &policy, 3_000).unwrap(), None);
+        fs::write(&path, "v3").unwrap();
+        assert_eq!(maybe_backup(&path, &policy, 3_000).unwrap(), Some(3_000));
+
+        let backups: Vec<u64> = list(&path).iter().map(|b| b.timestamp).collect();
+        assert_eq!(backups, vec![3_000, 2_000]);
+        assert_eq!(read(&path, 2_000).unwrap(), "v2");
+
+        // 过了保留期，只留下最新的一份
+        fs::write(&path, "v4").unwrap();
+        maybe_backup(&path, &policy, 3_000 + 2 * 24 * 60 * 60).unwrap();
+        assert_eq!(list(&path).len(), 1);
+
+        // 整柜改写：改不了的扔掉
+        let latest = list(&path)[0].timestamp;
+        assert_eq!(rewrite_all(&path, |content| Ok(content.to_uppercase())), 0);
+        assert_eq!(read(&path, latest).unwrap(), "V4");
+        assert_eq!(rewrite_all(&path, |_| anyhow::bail!("打不开")), 1);
+        assert!(list(&path).is_empty());
+    }
+}
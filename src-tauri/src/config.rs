@@ -3,7 +3,146 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::backup::{self, BackupInfo, BackupPolicy};
+use crate::config_sync::{self, SyncReport};
+use crate::config_watch;
+use crate::edge_options::{self, EdgeOption};
+use crate::key_pin::{self, KeyPinCheck};
+use crate::key_rotation::KeyRotation;
+use crate::network_aware::NetworkRule;
+use crate::overrides::ConfigOverrides;
+use crate::port_forward::PortForward;
+use crate::port_knock::KnockStep;
+use crate::routes::Route;
+use crate::wake::WakeRule;
+use crate::portable::{FileFormat, ProfileBundle};
+use crate::preferences::ThemePreference;
+use crate::secret_store;
+use crate::vault::{self, VaultKey};
+
+/// 工作汇报的转递范围（哪些 edge 输出要递到日志面板）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogForwardFilter {
+    /// 全部递上（默认）
+    #[default]
+    All,
+    /// 只递警告和错误
+    WarningsAndErrors,
+    /// 只递连接相关的大事（建网卡、注册总部、已识别的故障）
+    ConnectionEvents,
+}
+
+/// edge 退出后要不要自动重新建立通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconnectPolicy {
+    /// 从不自动重连（默认，按流量计费的笔记本适合这样）
+    #[default]
+    Never,
+    /// 只在 edge 出错退出时重连
+    OnFailure,
+    /// 只要不是主人叫停，edge 退出就重连（无人值守的站点网关适合这样）
+    Always,
+}
+
+/// 数据加密方式（edge 的 `-A<n>`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cipher {
+    /// 交给 edge 决定（默认，有密语时 n2n 3.x 用 AES）
+    #[default]
+    Default,
+    /// 不加密（`-A1`，只在可信的网络里用）
+    Null,
+    /// Twofish（`-A2`）
+    Twofish,
+    /// AES（`-A3`）
+    Aes,
+    /// ChaCha20（`-A4`，没有 AES 硬件加速的小设备上更快）
+    Chacha20,
+    /// Speck-CTR（`-A5`）
+    Speck,
+}
+
+impl Cipher {
+    /// 所有明确的加密方式（不含“交给 edge 决定”）
+    pub const EXPLICIT: [Cipher; 5] = [Cipher::Null, Cipher::Twofish, Cipher::Aes, Cipher::Chacha20, Cipher::Speck];
+
+    /// 交给 edge 的开关（交给 edge 决定时没有）
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            Cipher::Default => None,
+            Cipher::Null => Some("-A1"),
+            Cipher::Twofish => Some("-A2"),
+            Cipher::Aes => Some("-A3"),
+            Cipher::Chacha20 => Some("-A4"),
+            Cipher::Speck => Some("-A5"),
+        }
+    }
+
+    /// 从开关认出加密方式（`-A3` → AES）
+    pub fn from_flag(flag: &str) -> Option<Cipher> {
+        Cipher::EXPLICIT.into_iter().find(|cipher| cipher.flag() == Some(flag))
+    }
+
+    /// 除了不加密以外都要有密语
+    pub fn needs_key(self) -> bool {
+        !matches!(self, Cipher::Default | Cipher::Null)
+    }
+}
+
+/// 数据压缩方式（edge 的 `-z<n>`）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    /// 不压缩（默认）
+    #[default]
+    None,
+    /// LZO（`-z1`，省 CPU）
+    Lzo,
+    /// Zstandard（`-z2`，压得更小；edge 要带 zstd 编译）
+    Zstd,
+}
+
+impl Compression {
+    /// 交给 edge 的开关（不压缩时没有）
+    pub fn flag(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Lzo => Some("-z1"),
+            Compression::Zstd => Some("-z2"),
+        }
+    }
+
+    /// 从开关认出压缩方式（`-z2` → zstd）
+    pub fn from_flag(flag: &str) -> Option<Compression> {
+        [Compression::Lzo, Compression::Zstd].into_iter().find(|c| c.flag() == Some(flag))
+    }
+}
+
+fn default_reconnect_max_attempts() -> u32 {
+    5
+}
+
+fn default_reconnect_backoff_max_secs() -> u64 {
+    60
+}
+
+fn default_stop_timeout_secs() -> u64 {
+    10
+}
+
+fn default_status_snapshot_interval_secs() -> u64 {
+    30
+}
+
+fn default_supernode_recheck_secs() -> u64 {
+    300
+}
 
 /// 工作指示清单结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,20 +153,191 @@ pub struct N2NConfig {
     pub community: String,
     /// 我的工号（edge 名称）
     pub username: String,
-    /// 保密密语
+    /// 保密密语（内存里总是明文；写进指示簿时交给系统保险箱，这里留空）
     pub encryption_key: String,
+    /// 密语在系统保险箱里的取件单（`keyring:<账户>`，读指示簿时自动换回密语）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption_key_ref: Option<String>,
     /// 地址分配方式（"dhcp" 或手动指定）
     pub ip_mode: String,
     /// 指定地址（仅当 ip_mode 为手动时使用）
     pub static_ip: Option<String>,
-    /// 特殊指令
+    /// 旧版的特殊指令（一整行，按空格切开）：读进来时转成 advanced_options，不再写回
+    #[serde(default, skip_serializing)]
     pub extra_args: Option<String>,
+    /// 路由表（经由虚拟网里的同伴去往别的网段，建立通道时翻译成 `-n`）
+    #[serde(default)]
+    pub routes: Vec<Route>,
+    /// 中转节点：edge 带上 `-r`，连上后再打开系统的 IP 转发，让这台机器替同伴转发流量（当网关用）
+    #[serde(default)]
+    pub packet_forwarding: bool,
+    /// 数据加密方式（`-A<n>`，默认交给 edge 决定）
+    #[serde(default)]
+    pub cipher: Cipher,
+    /// 包头加密（`-H`，连小组暗号也藏起来；总部得用固定的小组清单）
+    #[serde(default)]
+    pub header_encryption: bool,
+    /// 数据压缩（`-z<n>`，上行慢的线路上用）
+    #[serde(default)]
+    pub compression: Compression,
+    /// 高级选项（交给 edge 的额外开关，每条单独记下开关、值和是否启用）
+    #[serde(default)]
+    pub advanced_options: Vec<EdgeOption>,
     /// 工具箱路径（edge 二进制文件）
     pub edge_path: Option<String>,
     /// 设备名称（TAP 网卡）
     pub tap_device: Option<String>,
     /// 通道宽度（MTU 设置）
     pub mtu: Option<u16>,
+    /// 固定 edge 的本机 UDP 端口（`-p`，留空由 edge 随便挑；防火墙只放行固定端口时用）
+    #[serde(default)]
+    pub local_port: Option<u16>,
+    /// 向总部和同伴报到（打洞保活）的间隔（`-i`，秒；留空用 edge 默认的 20 秒）：NAT 很快就忘掉映射时调短
+    #[serde(default)]
+    pub register_interval_secs: Option<u32>,
+    /// 指定虚拟网卡的 MAC（`-m`，留空由 edge 随机挑；克隆虚拟机撞了 MAC 时用）
+    #[serde(default)]
+    pub mac_address: Option<String>,
+    /// edge 管理口（`-t`，留空用默认的 5644；同一台机器上开第二只 edge 时要错开）
+    #[serde(default)]
+    pub management_port: Option<u16>,
+    /// edge 管理口的门禁口令（`--management-password`，留空用 edge 的默认口令）
+    #[serde(default)]
+    pub management_password: Option<String>,
+    /// 工作汇报转递范围（-v -v -v 很啰嗦时可以只看重点）
+    #[serde(default)]
+    pub log_forward: LogForwardFilter,
+    /// 旧版记在方案里的工作台配色：现在记在偏好本里（见 preferences.rs），第一次读偏好时搬过去，不再写回
+    #[serde(default, skip_serializing)]
+    pub theme: Option<ThemePreference>,
+    /// 引用地址簿里的总部（设置后 supernode 以地址簿为准）
+    #[serde(default)]
+    pub supernode_id: Option<String>,
+    /// 按域名找总部：每次建立通道前查 `_n2n._udp.<域名>` 的 SRV 记录（设置后 supernode 以 DNS 为准）
+    #[serde(default)]
+    pub supernode_dns: Option<String>,
+    /// 重要同伴（虚拟网内的地址）：连上后先去打个招呼，让 ARP/邻居缓存提前备好（留空则不打招呼）
+    #[serde(default)]
+    pub important_peers: Vec<String>,
+    /// 把大事（建立通道、意外退出、出错）另外抄一份到系统日志（Windows 事件查看器 / Linux journald）
+    #[serde(default)]
+    pub system_log: bool,
+    /// 连上后请 NetworkManager 把虚拟网卡显示成已连接（仅 Linux，GNOME/KDE 的网络面板里能看到 VPN 在用）
+    #[serde(default)]
+    pub desktop_vpn_hint: bool,
+    /// 断线重连策略（从不 / 出错时 / 总是）
+    #[serde(default)]
+    pub reconnect_policy: ReconnectPolicy,
+    /// 连续重连最多几次（0 表示不限次数），连上之后重新计数
+    #[serde(default = "default_reconnect_max_attempts")]
+    pub reconnect_max_attempts: u32,
+    /// 重连等待时间的上限（秒）：每次失败等待时间翻倍，但不超过这个值
+    #[serde(default = "default_reconnect_backoff_max_secs")]
+    pub reconnect_backoff_max_secs: u64,
+    /// 给 edge 上锁（仅 Linux）：收窄环境变量、不继承多余的文件、root 下设置 no_new_privs
+    #[serde(default)]
+    pub edge_hardening: bool,
+    /// 建好网卡后 edge 切换到的用户（用户名或 `uid[:gid]`，仅 Linux，需打开 edge_hardening）
+    #[serde(default)]
+    pub edge_run_as: Option<String>,
+    /// edge 指纹白名单（SHA-256）：填了就只放行指纹在名单里的 edge，没填就记住第一次见到的指纹
+    #[serde(default)]
+    pub edge_sha256: Vec<String>,
+    /// 不拦指纹对不上的 edge（只记日志，主人自己编译、经常换 edge 时用）
+    #[serde(default)]
+    pub edge_integrity_bypass: bool,
+    /// 恩兔启动后自动用这份方案建立通道
+    #[serde(default)]
+    pub auto_connect: bool,
+    /// 传话筒规则（通道连上后在本机和同伴之间转发 TCP 端口）
+    #[serde(default)]
+    pub port_forwards: Vec<PortForward>,
+    /// 唤醒规则（按时用网络唤醒叫醒同伴，并确认它上线）
+    #[serde(default)]
+    pub wake_rules: Vec<WakeRule>,
+    /// 敲门步骤（总部藏在端口敲门/单包授权后面时，edge 每次出门前先照着敲一遍），见 port_knock.rs
+    #[serde(default)]
+    pub port_knock: Vec<KnockStep>,
+    /// 温柔停止后最多等 edge 多久（秒），超时就强制清理（断开、退出恩兔、换方案重连都按它来）
+    #[serde(default = "default_stop_timeout_secs")]
+    pub stop_timeout_secs: u64,
+    /// edge 走后虚拟网卡上还挂着地址/路由时，顺手重置网卡（主要是 Windows 的 TAP，免得下次撞地址）
+    #[serde(default)]
+    pub tap_reset_on_teardown: bool,
+    /// 连着时虚拟网卡不见了、被停用或者地址掉了，直接重新出门让 edge 重新建网卡（不打开的话只提醒主人）
+    #[serde(default)]
+    pub tap_restart_on_loss: bool,
+    /// 定期把状态写进这个文件，给外面的脚本/监控程序读（`.prom` 结尾写 Prometheus 文本格式，其余写 JSON；留空不写）
+    #[serde(default)]
+    pub status_snapshot_path: Option<String>,
+    /// 状态快照多久写一次（秒）
+    #[serde(default = "default_status_snapshot_interval_secs")]
+    pub status_snapshot_interval_secs: u64,
+    /// 连着的时候多久重新查一次总部域名（秒，0 表示不查）：动态域名换了 IP 就重新建立通道
+    #[serde(default = "default_supernode_recheck_secs")]
+    pub supernode_recheck_secs: u64,
+    /// 备用总部（host:port）：正在用的总部一直摸不到时，恩兔按顺序一家一家换过去，不用主人挨个手动试
+    #[serde(default)]
+    pub supernode_failover: Vec<String>,
+    /// 网关模式：无人值守的站点网关（开机建立通道、总是重连、替同伴转发、大事写进系统日志），见 server_mode.rs
+    #[serde(default)]
+    pub server_mode: bool,
+}
+
+impl N2NConfig {
+    /// 把旧版的 extra_args 拆成高级选项（接在已有选项后面），
+    /// 再把写在高级选项里的 `-A<n>`、`-H`、`-z<n>`、`-r`、`-t`、`-i`、`--management-password` 搬到专门的设置里（它们现在由基本设置管着）
+    pub fn upgrade_extra_args(&mut self) {
+        if let Some(extra_args) = self.extra_args.take() {
+            self.advanced_options.extend(edge_options::from_legacy(&extra_args));
+        }
+        let (cipher, header_encryption, compression, packet_forwarding, management_port, management_password, register_interval) = (
+            &mut self.cipher,
+            &mut self.header_encryption,
+            &mut self.compression,
+            &mut self.packet_forwarding,
+            &mut self.management_port,
+            &mut self.management_password,
+            &mut self.register_interval_secs,
+        );
+        self.advanced_options.retain(|option| {
+            let flag = option.flag.trim();
+            if let Some(found) = Cipher::from_flag(flag) {
+                if option.enabled {
+                    *cipher = found;
+                }
+                false
+            } else if let Some(found) = Compression::from_flag(flag) {
+                if option.enabled {
+                    *compression = found;
+                }
+                false
+            } else if flag == "-H" {
+                *header_encryption |= option.enabled;
+                false
+            } else if flag == "-r" {
+                *packet_forwarding |= option.enabled;
+                false
+            } else if flag == "-t" {
+                if option.enabled {
+                    *management_port = option.value.as_deref().and_then(|v| v.trim().parse().ok());
+                }
+                false
+            } else if flag == "-i" {
+                if option.enabled {
+                    *register_interval = option.value.as_deref().and_then(|v| v.trim().parse().ok());
+                }
+                false
+            } else if flag == "--management-password" {
+                if option.enabled {
+                    *management_password = option.value.clone().filter(|v| !v.is_empty());
+                }
+                false
+            } else {
+                true
+            }
+        });
+    }
 }
 
 impl Default for N2NConfig {
@@ -37,19 +347,469 @@ impl Default for N2NConfig {
             community: String::new(),
             username: String::new(),
             encryption_key: String::new(),
+            encryption_key_ref: None,
             ip_mode: "dhcp".to_string(),
             static_ip: None,
             extra_args: None,
+            cipher: Cipher::Default,
+            header_encryption: false,
+            compression: Compression::None,
+            advanced_options: Vec::new(),
+            routes: Vec::new(),
+            packet_forwarding: false,
             edge_path: None,
             tap_device: None,
             mtu: Some(1290),
+            local_port: None,
+            register_interval_secs: None,
+            mac_address: None,
+            management_port: None,
+            management_password: None,
+            log_forward: LogForwardFilter::All,
+            theme: None,
+            supernode_id: None,
+            supernode_dns: None,
+            important_peers: Vec::new(),
+            system_log: false,
+            desktop_vpn_hint: false,
+            reconnect_policy: ReconnectPolicy::Never,
+            reconnect_max_attempts: default_reconnect_max_attempts(),
+            reconnect_backoff_max_secs: default_reconnect_backoff_max_secs(),
+            edge_hardening: false,
+            edge_run_as: None,
+            edge_sha256: Vec::new(),
+            edge_integrity_bypass: false,
+            auto_connect: false,
+            port_forwards: Vec::new(),
+            wake_rules: Vec::new(),
+            port_knock: Vec::new(),
+            stop_timeout_secs: default_stop_timeout_secs(),
+            tap_reset_on_teardown: false,
+            tap_restart_on_loss: false,
+            status_snapshot_path: None,
+            status_snapshot_interval_secs: default_status_snapshot_interval_secs(),
+            supernode_recheck_secs: default_supernode_recheck_secs(),
+            supernode_failover: Vec::new(),
+            server_mode: false,
+        }
+    }
+}
+
+/// 默认工作方案的名字（旧版单份指示迁移过来时也用它）
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// 一份有名字的工作方案（profile）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// 方案名字（在指示簿里唯一）
+    pub name: String,
+    /// 这份方案的工作指示
+    pub config: N2NConfig,
+    /// 管理员下发的方案（邀请链接/预置文件导入）：主人可以直接连接，
+    /// 但总部、暗号、密语这几项要先解锁才能改
+    #[serde(default)]
+    pub read_only: bool,
+    /// 记下的总部公钥（`-P`，第一次建立通道时记住，之后每次核对）
+    #[serde(default)]
+    pub supernode_key_pin: Option<String>,
+    /// 备注、标签和时间戳（只给主人自己看，不交给 edge）
+    #[serde(default)]
+    pub meta: ProfileMeta,
+    /// 约好了时间要换的新密语（到点后换上）
+    #[serde(default)]
+    pub key_rotation: Option<KeyRotation>,
+}
+
+/// 方案的备注信息（管着很多小组时记下负责人、用途、到期时间之类）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileMeta {
+    /// 随手记（自由文本）
+    #[serde(default)]
+    pub notes: String,
+    /// 标签（例如“客户 A”“临时”）
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 创建时间（Unix 秒；有这个功能之前就在的方案为 None）
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    /// 最后修改时间（改指示或改备注都算）
+    #[serde(default)]
+    pub modified_at: Option<u64>,
+}
+
+impl ProfileMeta {
+    /// 刚建好的方案
+    fn created_now() -> Self {
+        let now = crate::n2n_process::unix_now_seconds();
+        Self {
+            created_at: Some(now),
+            modified_at: Some(now),
+            ..Default::default()
+        }
+    }
+
+    fn touch(&mut self) {
+        self.modified_at = Some(crate::n2n_process::unix_now_seconds());
+    }
+}
+
+/// 整理标签：去掉首尾空白和空标签，重复的只留一个
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !out.iter().any(|t| t == tag) {
+            out.push(tag.to_string());
         }
     }
+    out
+}
+
+impl Profile {
+    /// 只读方案里被锁住的几项有没有被改动
+    fn locked_fields_changed(&self, config: &N2NConfig) -> bool {
+        self.config.supernode != config.supernode
+            || self.config.supernode_id != config.supernode_id
+            || self.config.supernode_dns != config.supernode_dns
+            || self.config.community != config.community
+            || self.config.encryption_key != config.encryption_key
+    }
+}
+
+/// 总部地址簿里的一条记录（多份方案可以共用同一个总部）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SupernodeEntry {
+    /// 记录编号（方案通过它引用总部）
+    #[serde(default)]
+    pub id: String,
+    /// 给主人看的名字
+    pub name: String,
+    /// 总部地址（host:port）
+    pub address: String,
+    /// 备注
+    #[serde(default)]
+    pub notes: String,
+    /// 最近一次测得的往返延迟（毫秒）
+    pub last_rtt_ms: Option<f64>,
+    /// 最近一次测量时间（Unix 秒）
+    pub last_measured: Option<u64>,
+}
+
+/// 指示簿的完整内容：多份工作方案 + 当前正在用的那一份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileStore {
+    /// 当前使用的方案名字
+    pub active_profile: String,
+    /// 全部工作方案
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    /// 总部地址簿（和方案分开存放）
+    #[serde(default)]
+    pub supernodes: Vec<SupernodeEntry>,
+    /// 自动备份策略
+    #[serde(default)]
+    pub backup: BackupPolicy,
+    /// 认路对照表（连着哪个网络就换用哪份方案）
+    #[serde(default)]
+    pub network_rules: Vec<NetworkRule>,
+    /// 上次和同步文件夹对账的时间（判断两边是不是都改过）
+    #[serde(default)]
+    pub synced_at: Option<u64>,
+}
+
+impl Default for ProfileStore {
+    fn default() -> Self {
+        Self::from_single(N2NConfig::default())
+    }
+}
+
+impl ProfileStore {
+    /// 把一份单独的指示包装成只有“Default”方案的指示簿
+    pub fn from_single(config: N2NConfig) -> Self {
+        Self {
+            active_profile: DEFAULT_PROFILE_NAME.to_string(),
+            profiles: vec![Profile {
+                name: DEFAULT_PROFILE_NAME.to_string(),
+                config,
+                read_only: false,
+                supernode_key_pin: None,
+                meta: ProfileMeta::created_now(),
+                key_rotation: None,
+            }],
+            supernodes: Vec::new(),
+            backup: BackupPolicy::default(),
+            network_rules: Vec::new(),
+            synced_at: None,
+        }
+    }
+
+    /// 解析指示簿内容（顺便认出旧版的单份指示，返回值的第二项表示是否为旧格式）
+    pub fn parse(content: &str) -> Result<(Self, bool)> {
+        let value: toml::Value = toml::from_str(content).context("解析配置文件失败")?;
+        // 旧版 config.toml 直接把 supernode 等字段写在最外层
+        if value.get("supernode").is_some() {
+            let mut legacy: N2NConfig = value.try_into().context("解析旧版配置文件失败")?;
+            legacy.upgrade_extra_args();
+            return Ok((Self::from_single(legacy), true));
+        }
+        let mut store: ProfileStore = value.try_into().context("解析配置文件失败")?;
+        store.normalize();
+        Ok((store, false))
+    }
+
+    /// 保证至少有一份方案，且当前方案确实存在
+    fn normalize(&mut self) {
+        if self.profiles.is_empty() {
+            *self = Self::default();
+            return;
+        }
+        if self.get(&self.active_profile).is_none() {
+            self.active_profile = self.profiles[0].name.clone();
+        }
+        for profile in &mut self.profiles {
+            profile.config.upgrade_extra_args();
+        }
+        self.resolve_supernode_refs();
+    }
+
+    /// 写盘前：把各方案的密语收进系统保险箱，只留下取件单（保险箱用不了就照旧明文保存）
+    fn seal_secrets(&self, secrets: &secret_store::Cache) -> ProfileStore {
+        let mut sealed = self.clone();
+        for profile in &mut sealed.profiles {
+            let config = &mut profile.config;
+            let account = secret_store::profile_account(&profile.name);
+            seal_secret(secrets, &mut config.encryption_key, &mut config.encryption_key_ref, &account, &profile.name);
+            if let Some(rotation) = profile.key_rotation.as_mut() {
+                let account = secret_store::rotation_account(&profile.name);
+                seal_secret(secrets, &mut rotation.new_key, &mut rotation.new_key_ref, &account, &profile.name);
+            }
+        }
+        sealed
+    }
+
+    /// 读盘后：按取件单把密语从系统保险箱取回来
+    fn unseal_secrets(&mut self, secrets: &secret_store::Cache) {
+        for profile in &mut self.profiles {
+            let config = &mut profile.config;
+            let account = secret_store::profile_account(&profile.name);
+            unseal_secret(secrets, &mut config.encryption_key, config.encryption_key_ref.as_deref(), &account, &profile.name);
+            if let Some(rotation) = profile.key_rotation.as_mut() {
+                let account = secret_store::rotation_account(&profile.name);
+                unseal_secret(secrets, &mut rotation.new_key, rotation.new_key_ref.as_deref(), &account, &profile.name);
+            }
+        }
+    }
+
+    /// 还有没有明文躺在指示簿里的密语（需要搬进保险箱）
+    fn has_plaintext_secrets(&self) -> bool {
+        self.profiles.iter().any(|p| {
+            (!p.config.encryption_key.is_empty() && p.config.encryption_key_ref.is_none())
+                || p.key_rotation
+                    .as_ref()
+                    .is_some_and(|r| !r.new_key.is_empty() && r.new_key_ref.is_none())
+        })
+    }
+
+    /// 把到点的新密语换上，返回换过密语的方案名字
+    pub fn apply_due_key_rotations(&mut self, now: u64) -> Vec<String> {
+        let mut rotated = Vec::new();
+        for profile in &mut self.profiles {
+            let Some(rotation) = profile.key_rotation.take_if(|r| r.is_due(now)) else {
+                continue;
+            };
+            profile.config.encryption_key = rotation.new_key;
+            profile.meta.touch();
+            rotated.push(profile.name.clone());
+        }
+        rotated
+    }
+
+    /// 按地址簿刷新各方案的总部地址（地址簿改一次，引用它的方案全部跟着变）
+    pub(crate) fn resolve_supernode_refs(&mut self) {
+        for profile in &mut self.profiles {
+            let Some(ref id) = profile.config.supernode_id else { continue };
+            match self.supernodes.iter().find(|s| &s.id == id) {
+                Some(entry) => profile.config.supernode = entry.address.clone(),
+                // 引用的记录已经不在了：保留最后一次的地址，只断开引用
+                None => profile.config.supernode_id = None,
+            }
+        }
+    }
+
+    /// 写入（或新建）一条总部记录，返回它的编号
+    pub fn upsert_supernode(&mut self, mut entry: SupernodeEntry) -> String {
+        if entry.id.is_empty() {
+            entry.id = new_supernode_id(&self.supernodes);
+        }
+        let id = entry.id.clone();
+        match self.supernodes.iter_mut().find(|s| s.id == id) {
+            Some(existing) => *existing = entry,
+            None => self.supernodes.push(entry),
+        }
+        self.resolve_supernode_refs();
+        id
+    }
+
+    /// 从地址簿删掉一条记录（引用它的方案保留当前地址）
+    pub fn remove_supernode(&mut self, id: &str) -> bool {
+        let before = self.supernodes.len();
+        self.supernodes.retain(|s| s.id != id);
+        self.resolve_supernode_refs();
+        self.supernodes.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles.iter_mut().find(|p| p.name == name)
+    }
+
+    /// 当前方案的指示
+    pub fn active_config(&self) -> N2NConfig {
+        self.get(&self.active_profile)
+            .map(|p| p.config.clone())
+            .unwrap_or_default()
+    }
+
+    /// 写入（或新建）一份方案（只读方案不许改总部、暗号和密语）
+    pub fn upsert(&mut self, name: &str, mut config: N2NConfig) -> Result<()> {
+        config.upgrade_extra_args();
+        match self.get_mut(name) {
+            Some(profile) if profile.read_only && profile.locked_fields_changed(&config) => {
+                anyhow::bail!("“{}”是管理员下发的只读方案，要先解锁才能修改总部、暗号和密语哦", name);
+            }
+            Some(profile) => {
+                // 每次建立通道都会存一遍，内容没变就不算修改
+                if serde_json::to_value(&profile.config).ok() != serde_json::to_value(&config).ok() {
+                    profile.meta.touch();
+                }
+                profile.config = config;
+            }
+            None => self.profiles.push(Profile {
+                name: name.to_string(),
+                config,
+                read_only: false,
+                supernode_key_pin: None,
+                meta: ProfileMeta::created_now(),
+                key_rotation: None,
+            }),
+        }
+        self.resolve_supernode_refs();
+        Ok(())
+    }
+}
+
+/// 和同步文件夹对账：先把别的机器的改动并进来，再把整套方案写上去
+fn sync_with(dir: &Path, store: &mut ProfileStore) -> Result<SyncReport> {
+    let now = crate::n2n_process::unix_now_seconds();
+    let report = match config_sync::read(dir)? {
+        Some(remote) => config_sync::merge(store, &remote),
+        None => SyncReport::default(),
+    };
+    let host = crate::node_name::host_name().unwrap_or_default();
+    config_sync::write(dir, &config_sync::snapshot(store, &host, now))?;
+    store.synced_at = Some(now);
+    if !report.is_empty() {
+        log::info!("和同步文件夹对了账：拉下 {:?}，冲突另存 {:?}", report.pulled, report.conflicts);
+    }
+    Ok(report)
+}
+
+/// 写盘前把一条密语收进保险箱的某一格，只留下取件单（清空了的话那一格也扔掉）
+fn seal_secret(
+    secrets: &secret_store::Cache,
+    secret: &mut String,
+    secret_ref: &mut Option<String>,
+    account: &str,
+    profile: &str,
+) {
+    if secret.is_empty() {
+        if secret_ref.take().is_some() {
+            if let Err(e) = secrets.delete(account) {
+                log::warn!("{:#}", e);
+            }
+        }
+        return;
+    }
+    match secrets.store(account, secret) {
+        Ok(()) => {
+            secret.clear();
+            *secret_ref = Some(secret_store::reference(account));
+        }
+        Err(e) => {
+            log::warn!("{:#}；方案“{}”的密语只能明文保存了", e, profile);
+            *secret_ref = None;
+        }
+    }
+}
+
+/// 读盘后按取件单把一条密语取回来
+fn unseal_secret(secrets: &secret_store::Cache, secret: &mut String, secret_ref: Option<&str>, account: &str, profile: &str) {
+    let Some(found) = secret_ref.and_then(secret_store::parse_reference) else {
+        return;
+    };
+    // 只认自己那一格：别的方案（比如导入的文件里写了别人的取件单）不能借走这份密语
+    if found != account {
+        log::warn!("方案“{}”的取件单指向别的保险箱格子，已忽略", profile);
+        return;
+    }
+    match secrets.load(account) {
+        Ok(Some(value)) => *secret = value,
+        Ok(None) => log::warn!("系统保险箱里找不到方案“{}”的密语，请重新填写", profile),
+        Err(e) => log::warn!("{:#}", e),
+    }
+}
+
+/// 给新的总部记录起个不重复的编号
+fn new_supernode_id(existing: &[SupernodeEntry]) -> String {
+    let mut n = existing.len() + 1;
+    loop {
+        let id = format!("sn-{}", n);
+        if !existing.iter().any(|s| s.id == id) {
+            return id;
+        }
+        n += 1;
+    }
+}
+
+/// 给前端看的方案概要
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub active: bool,
+    pub supernode: String,
+    pub community: String,
+    pub read_only: bool,
+    #[serde(flatten)]
+    pub meta: ProfileMeta,
+    /// 约好换密语的时间（Unix 秒；没约时为 None）
+    pub key_rotation_at: Option<u64>,
+}
+
+/// 指示簿的加密状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ConfigEncryption {
+    /// 指示簿是否加密保存
+    pub encrypted: bool,
+    /// 是否还没用密码打开（打开之前读写指示簿都会得到 error_config_locked）
+    pub locked: bool,
 }
 
 /// 指示簿管理器
 pub struct ConfigManager {
     config_path: PathBuf,
+    /// 加密指示簿的钥匙（解锁后才有，只放在内存里）
+    vault: Option<VaultKey>,
+    /// 环境变量/启动参数给的外部指示（读的时候叠加，存的时候不写进去）
+    overrides: ConfigOverrides,
+    /// 磁盘上指示簿的指纹（恩兔自己写进去的，或者已经通知过的）
+    seen: AtomicU64,
+    /// 同步文件夹（主人在偏好里选的；没选时为 None）
+    sync_dir: Option<PathBuf>,
+    /// 从系统保险箱取过、放过的密语（每读一次指示簿都去敲保险箱太打扰了）
+    secrets: secret_store::Cache,
 }
 
 impl ConfigManager {
@@ -64,32 +824,616 @@ impl ConfigManager {
         
         let config_path = config_dir.join("config.toml");
         
-        Ok(Self { config_path })
+        Ok(Self::at(config_path))
     }
 
-    /// 翻看指示簿（加载配置）
-    pub fn load(&self) -> Result<N2NConfig> {
-        if !self.config_path.exists() {
-            return Ok(N2NConfig::default());
+    /// 指定指示簿的位置
+    pub fn at(config_path: PathBuf) -> Self {
+        let seen = fs::read(&config_path).map(|content| config_watch::fingerprint(&content)).unwrap_or(0);
+        Self {
+            config_path,
+            vault: None,
+            overrides: ConfigOverrides::default(),
+            seen: AtomicU64::new(seen),
+            sync_dir: None,
+            secrets: secret_store::Cache::default(),
         }
+    }
+
+    /// 指示簿的位置
+    pub fn path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// 指示簿是不是被别人（主人的编辑器）改过了；问过一次之后就算知道了
+    pub fn take_external_change(&self) -> bool {
+        let current = fs::read(&self.config_path).map(|content| config_watch::fingerprint(&content)).unwrap_or(0);
+        self.seen.swap(current, Ordering::SeqCst) != current
+    }
+
+    /// 把原文写进指示簿，顺手记下指纹（自己写的不算外面改的）
+    fn write_file(&self, content: &str) -> Result<()> {
+        fs::write(&self.config_path, content).context("写入配置文件失败")?;
+        self.seen.store(config_watch::fingerprint(content.as_bytes()), Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// 换同步文件夹（None 表示不同步）
+    pub fn set_sync_dir(&mut self, dir: Option<PathBuf>) {
+        self.sync_dir = dir;
+    }
+
+    /// 记下外部指示（启动时调用一次）
+    pub fn set_overrides(&mut self, overrides: ConfigOverrides) {
+        self.overrides = overrides;
+    }
 
+    /// 被外部指示覆盖的字段
+    pub fn overridden_fields(&self) -> Vec<String> {
+        self.overrides.fields()
+    }
+
+    /// 读出指示簿原文（加密的话用内存里的钥匙打开）
+    fn read_content(&self) -> Result<String> {
         let content = fs::read_to_string(&self.config_path)
             .context("读取配置文件失败")?;
-        
-        let config: N2NConfig = toml::from_str(&content)
-            .context("解析配置文件失败")?;
-        
-        Ok(config)
+        self.decode(content)
     }
 
-    /// 记下主人的指示（保存配置）
-    pub fn save(&self, config: &N2NConfig) -> Result<()> {
-        let content = toml::to_string_pretty(config)
+    /// 原文如果是加密的，就用内存里的钥匙打开
+    fn decode(&self, content: String) -> Result<String> {
+        if !vault::is_sealed(&content) {
+            return Ok(content);
+        }
+        match self.vault {
+            Some(ref key) => key.open(&content),
+            None => anyhow::bail!(vault::ERROR_LOCKED),
+        }
+    }
+
+    /// 写回指示簿原文（开启了加密就先封起来）
+    fn write_content(&self, content: &str) -> Result<()> {
+        let content = match self.vault {
+            Some(ref key) => key.seal(content)?,
+            None if self.sealed_on_disk() => anyhow::bail!(vault::ERROR_LOCKED),
+            None => content.to_string(),
+        };
+        self.write_file(&content)
+    }
+
+    fn sealed_on_disk(&self) -> bool {
+        fs::read_to_string(&self.config_path).is_ok_and(|content| vault::is_sealed(&content))
+    }
+
+    /// 指示簿现在是否加密、是否已经打开
+    pub fn encryption(&self) -> ConfigEncryption {
+        let sealed = self.sealed_on_disk();
+        ConfigEncryption {
+            encrypted: sealed || self.vault.is_some(),
+            locked: sealed && self.vault.is_none(),
+        }
+    }
+
+    /// 用主人的密码打开加密的指示簿（钥匙记在内存里，直到锁上或者退出）
+    pub fn unlock(&mut self, password: &str) -> Result<()> {
+        let content = fs::read_to_string(&self.config_path)
+            .context("读取配置文件失败")?;
+        if !vault::is_sealed(&content) {
+            anyhow::bail!("指示簿没有加密，不需要解锁");
+        }
+        let (key, plaintext) = VaultKey::unlock(&content, password)?;
+        ProfileStore::parse(&plaintext).context("解开的指示簿已经损坏")?;
+        self.vault = Some(key);
+        log::info!("指示簿已用密码打开");
+        Ok(())
+    }
+
+    /// 锁上指示簿：忘掉内存里的钥匙（正在工作的 edge 不受影响）
+    pub fn lock(&mut self) {
+        if self.vault.take().is_some() {
+            log::info!("指示簿已锁上");
+        }
+    }
+
+    /// 用密码加密保存指示簿；已经加密（并且打开了）时就是换密码
+    pub fn set_password(&mut self, password: &str) -> Result<()> {
+        // 同步文件夹里那份是明文：开着同步就加密，等于没加
+        if self.sync_dir.is_some() {
+            anyhow::bail!(config_sync::ERROR_ENCRYPTED);
+        }
+        let content = if self.config_path.exists() {
+            self.read_content()?
+        } else {
+            toml::to_string_pretty(&ProfileStore::default().seal_secrets(&self.secrets))
+                .context("序列化配置失败")?
+        };
+        let key = VaultKey::generate(password)?;
+        self.write_file(&key.seal(&content)?)?;
+        // 备份柜里的旧版本（明文的，或者旧钥匙封的）也用新钥匙重新封好，不留明文
+        let dropped = backup::rewrite_all(&self.config_path, |backup| key.seal(&self.decode(backup)?));
+        self.vault = Some(key);
+        if dropped > 0 {
+            log::warn!("有 {} 份打不开的旧备份已经扔掉", dropped);
+        }
+        log::info!("指示簿已加密保存（备份柜里的旧版本也一起封好了）");
+        Ok(())
+    }
+
+    /// 取消加密：核对密码后把指示簿改回明文保存
+    pub fn remove_password(&mut self, password: &str) -> Result<()> {
+        let content = fs::read_to_string(&self.config_path)
+            .context("读取配置文件失败")?;
+        if !vault::is_sealed(&content) {
+            self.vault = None;
+            return Ok(());
+        }
+        let (key, plaintext) = VaultKey::unlock(&content, password)?;
+        self.write_file(&plaintext)?;
+        // 备份柜也改回明文，不然没了钥匙就再也恢复不了
+        let dropped = backup::rewrite_all(&self.config_path, |backup| {
+            if vault::is_sealed(&backup) {
+                key.open(&backup)
+            } else {
+                Ok(backup)
+            }
+        });
+        if dropped > 0 {
+            log::warn!("有 {} 份打不开的旧备份已经扔掉", dropped);
+        }
+        self.vault = None;
+        log::warn!("指示簿已改回明文保存");
+        Ok(())
+    }
+
+    /// 把旧版的单份指示搬进方案指示簿（启动时调用一次）
+    /// - 原文件先原样备份成 `config.legacy.toml`，万一出岔子主人还能找回来；
+    /// - 全部字段原封不动地放进名为“Default”的方案里。
+    ///
+    /// 返回是否真的做了迁移
+    pub fn migrate_legacy(&self) -> Result<bool> {
+        if !self.config_path.exists() {
+            return Ok(false);
+        }
+        let content = fs::read_to_string(&self.config_path)
+            .context("读取配置文件失败")?;
+        // 加密的指示簿一定是新版写的，不用迁移
+        if vault::is_sealed(&content) {
+            return Ok(false);
+        }
+        let (store, legacy) = ProfileStore::parse(&content)?;
+        if !legacy {
+            return Ok(false);
+        }
+
+        let backup_path = self.config_path.with_file_name("config.legacy.toml");
+        fs::write(&backup_path, &content)
+            .context("备份旧版配置文件失败")?;
+        self.save_store(&store)?;
+        log::info!(
+            "恩兔把旧版指示簿搬进了“{}”方案（原件备份在 {}）",
+            DEFAULT_PROFILE_NAME,
+            backup_path.display()
+        );
+        Ok(true)
+    }
+
+    /// 翻开整本指示簿（全部方案）
+    pub fn load_store(&self) -> Result<ProfileStore> {
+        if !self.config_path.exists() {
+            return Ok(ProfileStore::default());
+        }
+
+        let content = self.read_content()?;
+        let (mut store, _) = ProfileStore::parse(&content)?;
+        store.unseal_secrets(&self.secrets);
+        Ok(store)
+    }
+
+    /// 把还明文躺在指示簿里的密语搬进系统保险箱（启动时调用一次），返回是否搬过
+    pub fn migrate_secrets(&self) -> Result<bool> {
+        // 还没解锁就先不搬，反正别人也读不到里面的内容
+        if self.encryption().locked {
+            return Ok(false);
+        }
+        let store = self.load_store()?;
+        if !store.has_plaintext_secrets() {
+            return Ok(false);
+        }
+        self.save_store(&store)?;
+        let sealed = !self.load_raw_store()?.has_plaintext_secrets();
+        if sealed {
+            log::info!("恩兔把密语都收进了系统保险箱（更早的备份里可能还留着明文，可以按需清理 backups 目录）");
+        }
+        Ok(sealed)
+    }
+
+    /// 原样读出指示簿（不去保险箱取密语）
+    fn load_raw_store(&self) -> Result<ProfileStore> {
+        let content = self.read_content()?;
+        Ok(ProfileStore::parse(&content)?.0)
+    }
+
+    /// 整本指示簿写回磁盘（选了同步文件夹的话先和它对一次账）
+    pub fn save_store(&self, store: &ProfileStore) -> Result<()> {
+        let Some(dir) = self.sync_target() else {
+            return self.write_store(store);
+        };
+        let mut store = store.clone();
+        // 同步文件夹一时不可用（网盘没挂上之类）不耽误本机保存
+        if let Err(e) = sync_with(&dir, &mut store) {
+            log::warn!("和同步文件夹对账失败：{:#}", e);
+        }
+        self.write_store(&store)
+    }
+
+    /// 现在就和同步文件夹对一次账（启动时、同步文件夹里的那份被别的机器改了时），返回拉下来了什么
+    pub fn sync_now(&self) -> Result<SyncReport> {
+        if self.sync_dir.is_some() && self.encryption().encrypted {
+            anyhow::bail!(config_sync::ERROR_ENCRYPTED);
+        }
+        let Some(dir) = self.sync_target() else {
+            return Ok(SyncReport::default());
+        };
+        let mut store = self.load_store()?;
+        let report = sync_with(&dir, &mut store)?;
+        if !report.is_empty() {
+            self.write_store(&store)?;
+        }
+        Ok(report)
+    }
+
+    /// 要对账的同步文件夹；指示簿加了密码就不同步（同步文件夹里只能放明文）
+    fn sync_target(&self) -> Option<PathBuf> {
+        let dir = self.sync_dir.clone()?;
+        if self.encryption().encrypted {
+            log::warn!("指示簿加密了，不往同步文件夹里写明文方案");
+            return None;
+        }
+        Some(dir)
+    }
+
+    /// 指示簿写回磁盘（改写之前按策略把旧版本收进备份柜）
+    fn write_store(&self, store: &ProfileStore) -> Result<()> {
+        let content = toml::to_string_pretty(&store.seal_secrets(&self.secrets))
             .context("序列化配置失败")?;
+
+        let now = crate::n2n_process::unix_now_seconds();
+        if let Err(e) = backup::maybe_backup(&self.config_path, &store.backup, now) {
+            log::warn!("自动备份指示簿失败：{}", e);
+        }
         
-        fs::write(&self.config_path, content)
-            .context("写入配置文件失败")?;
-        
+        self.write_content(&content)
+    }
+
+    /// 翻看备份柜（新的在前）
+    pub fn list_backups(&self) -> Vec<BackupInfo> {
+        backup::list(&self.config_path)
+    }
+
+    /// 用某一份备份替换当前指示簿（替换前先把当前版本也备份一份）
+    pub fn restore_backup(&self, timestamp: u64) -> Result<()> {
+        let content = self.decode(backup::read(&self.config_path, timestamp)?)?;
+        ProfileStore::parse(&content).context("这份备份已经损坏，不能用来恢复")?;
+        backup::force_backup(&self.config_path, crate::n2n_process::unix_now_seconds())?;
+        self.write_content(&content)?;
+        log::info!("恩兔把指示簿恢复到了 {} 的备份", timestamp);
+        Ok(())
+    }
+
+    /// 翻看指示簿（加载当前方案的配置）
+    pub fn load(&self) -> Result<N2NConfig> {
+        Ok(self.overrides.apply(&self.load_store()?.active_config()))
+    }
+
+    /// 记下主人的指示（保存到当前方案）
+    pub fn save(&self, config: &N2NConfig) -> Result<()> {
+        let mut store = self.load_store()?;
+        let active = store.active_profile.clone();
+        let config = self.without_overrides(&store, &active, config);
+        store.upsert(&active, config)?;
+        self.save_store(&store)
+    }
+
+    /// 存之前把外部指示覆盖的字段换回指示簿里原来的值（新方案就用默认值）
+    fn without_overrides(&self, store: &ProfileStore, name: &str, config: &N2NConfig) -> N2NConfig {
+        match store.get(name) {
+            Some(profile) => self.overrides.restore(config, &profile.config),
+            None => self.overrides.restore(config, &N2NConfig::default()),
+        }
+    }
+
+    /// 列出全部方案
+    pub fn list_profiles(&self) -> Result<Vec<ProfileSummary>> {
+        let store = self.load_store()?;
+        Ok(store
+            .profiles
+            .iter()
+            .map(|p| ProfileSummary {
+                name: p.name.clone(),
+                active: p.name == store.active_profile,
+                supernode: p.config.supernode.clone(),
+                community: p.config.community.clone(),
+                read_only: p.read_only,
+                meta: p.meta.clone(),
+                key_rotation_at: p.key_rotation.as_ref().map(|r| r.switch_at),
+            })
+            .collect())
+    }
+
+    /// 改一份方案的备注和标签（只读方案也可以改，这些只给主人自己看）
+    pub fn set_profile_meta(&self, name: &str, notes: &str, tags: Vec<String>) -> Result<()> {
+        let mut store = self.load_store()?;
+        let profile = store
+            .get_mut(name)
+            .with_context(|| format!("找不到名为“{}”的方案", name))?;
+        profile.meta.notes = notes.trim().to_string();
+        profile.meta.tags = normalize_tags(tags);
+        profile.meta.touch();
+        self.save_store(&store)
+    }
+
+    /// 换用另一份方案，返回它的配置
+    pub fn switch_profile(&self, name: &str) -> Result<N2NConfig> {
+        let mut store = self.load_store()?;
+        let config = store
+            .get(name)
+            .map(|p| p.config.clone())
+            .with_context(|| format!("找不到名为“{}”的方案", name))?;
+        store.active_profile = name.to_string();
+        self.save_store(&store)?;
+        Ok(self.overrides.apply(&config))
+    }
+
+    /// 读出另一份方案的配置（不换用它，也不叠加外部指示；副通道用）
+    pub fn load_profile(&self, name: &str) -> Result<N2NConfig> {
+        self.load_store()?
+            .get(name)
+            .map(|p| p.config.clone())
+            .with_context(|| format!("找不到名为“{}”的方案", name))
+    }
+
+    /// 保存（或新建）一份方案，不改变当前方案
+    pub fn save_profile(&self, name: &str, config: &N2NConfig) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("方案名字不能为空");
+        }
+        let mut store = self.load_store()?;
+        let config = self.without_overrides(&store, name, config);
+        store.upsert(name, config)?;
+        self.save_store(&store)
+    }
+
+    /// 收下一份导入的方案（邀请链接/预置文件），默认标成只读；同名方案会被整份替换
+    pub fn import_profile(&self, name: &str, mut config: N2NConfig) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("方案名字不能为空");
+        }
+        let mut store = self.load_store()?;
+        config.upgrade_extra_args();
+        store.profiles.retain(|p| p.name != name);
+        store.profiles.push(Profile {
+            name: name.to_string(),
+            config,
+            read_only: true,
+            supernode_key_pin: None,
+            meta: ProfileMeta::created_now(),
+            key_rotation: None,
+        });
+        store.resolve_supernode_refs();
+        self.save_store(&store)
+    }
+
+    /// 把当前方案（或全部方案）导出成便携的方案文件，返回导出了几份
+    pub fn export_profiles(&self, path: &Path, all: bool) -> Result<usize> {
+        let store = self.load_store()?;
+        let bundle = ProfileBundle::new(
+            store
+                .profiles
+                .into_iter()
+                .filter(|p| all || p.name == store.active_profile)
+                .map(|p| (p.name, p.config)),
+        );
+        let content = bundle.encode(FileFormat::from_path(path))?;
+        fs::write(path, content).with_context(|| format!("写入方案文件 {} 失败", path.display()))?;
+        log::info!("恩兔把 {} 份方案导出到了 {}", bundle.profiles.len(), path.display());
+        Ok(bundle.profiles.len())
+    }
+
+    /// 从方案文件导入（导入的方案和管理员下发的一样是只读的；重名时自动换个名字），返回导入后的名字
+    pub fn import_profiles(&self, path: &Path) -> Result<Vec<String>> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取方案文件 {} 失败", path.display()))?;
+        let fallback_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Imported".to_string());
+        let bundle = ProfileBundle::decode(&content, FileFormat::from_path(path), &fallback_name)?;
+
+        let mut store = self.load_store()?;
+        let mut names = Vec::new();
+        for mut profile in bundle.profiles {
+            profile.config.upgrade_extra_args();
+            let base = profile.name.trim().to_string();
+            let mut name = base.clone();
+            let mut n = 2;
+            while store.get(&name).is_some() {
+                name = format!("{} ({})", base, n);
+                n += 1;
+            }
+            store.profiles.push(Profile {
+                name: name.clone(),
+                config: profile.config,
+                read_only: true,
+                supernode_key_pin: None,
+                meta: ProfileMeta::created_now(),
+                key_rotation: None,
+            });
+            names.push(name);
+        }
+        self.save_store(&store)?;
+        log::info!("恩兔从 {} 导入了方案：{}", path.display(), names.join("、"));
+        Ok(names)
+    }
+
+    /// 核对当前方案的总部公钥（第一次见到时顺手记下）
+    pub fn verify_supernode_key(&self, presented: Option<&str>) -> Result<KeyPinCheck> {
+        let mut store = self.load_store()?;
+        let active = store.active_profile.clone();
+        let profile = store
+            .get_mut(&active)
+            .with_context(|| format!("找不到名为“{}”的方案", active))?;
+        let check = key_pin::verify(profile.supernode_key_pin.as_deref(), presented);
+        if let KeyPinCheck::FirstUse { ref key } = check {
+            profile.supernode_key_pin = Some(key.clone());
+            log::info!("方案“{}”记下了总部公钥：{}", active, key);
+            self.save_store(&store)?;
+        }
+        Ok(check)
+    }
+
+    /// 主人确认新公钥可信：改为记住当前方案里的公钥
+    pub fn trust_supernode_key(&self) -> Result<()> {
+        let mut store = self.load_store()?;
+        let active = store.active_profile.clone();
+        let profile = store
+            .get_mut(&active)
+            .with_context(|| format!("找不到名为“{}”的方案", active))?;
+        profile.supernode_key_pin = key_pin::presented_key(&profile.config.advanced_options);
+        log::warn!("主人确认信任方案“{}”的新总部公钥：{:?}", active, profile.supernode_key_pin);
+        self.save_store(&store)
+    }
+
+    /// 约好一份方案换密语的时间：新密语先记下，到点才换上（只读方案也可以约，换密语不算主人改动）
+    pub fn schedule_key_rotation(&self, name: &str, new_key: &str, switch_at: u64) -> Result<()> {
+        let mut store = self.load_store()?;
+        let profile = store
+            .get_mut(name)
+            .with_context(|| format!("找不到名为“{}”的方案", name))?;
+        profile.key_rotation = Some(KeyRotation::new(new_key, switch_at));
+        profile.meta.touch();
+        self.save_store(&store)
+    }
+
+    /// 不换了：扔掉约好的新密语
+    pub fn cancel_key_rotation(&self, name: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        let profile = store
+            .get_mut(name)
+            .with_context(|| format!("找不到名为“{}”的方案", name))?;
+        if profile.key_rotation.take().is_none() {
+            return Ok(());
+        }
+        profile.meta.touch();
+        self.save_store(&store)?;
+        if let Err(e) = self.secrets.delete(&secret_store::rotation_account(name)) {
+            log::warn!("{:#}", e);
+        }
+        Ok(())
+    }
+
+    /// 换上到点的新密语，返回换过密语的方案名字
+    pub fn apply_due_key_rotations(&self, now: u64) -> Result<Vec<String>> {
+        let mut store = self.load_store()?;
+        let rotated = store.apply_due_key_rotations(now);
+        if rotated.is_empty() {
+            return Ok(rotated);
+        }
+        self.save_store(&store)?;
+        for name in &rotated {
+            if let Err(e) = self.secrets.delete(&secret_store::rotation_account(name)) {
+                log::warn!("{:#}", e);
+            }
+        }
+        Ok(rotated)
+    }
+
+    /// 解锁只读方案（给懂行的主人用），之后就能随意修改了
+    pub fn unlock_profile(&self, name: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        let profile = store
+            .get_mut(name)
+            .with_context(|| format!("找不到名为“{}”的方案", name))?;
+        if !profile.read_only {
+            return Ok(());
+        }
+        profile.read_only = false;
+        log::info!("方案“{}”已解锁，主人可以自由修改了", name);
+        self.save_store(&store)
+    }
+
+    /// 翻看总部地址簿
+    pub fn list_supernodes(&self) -> Result<Vec<SupernodeEntry>> {
+        Ok(self.load_store()?.supernodes)
+    }
+
+    /// 写入（或新建）一条总部记录，返回它的编号
+    pub fn save_supernode(&self, entry: SupernodeEntry) -> Result<String> {
+        if entry.name.trim().is_empty() || entry.address.trim().is_empty() {
+            anyhow::bail!("总部的名字和地址都要填写哦");
+        }
+        let mut store = self.load_store()?;
+        let id = store.upsert_supernode(entry);
+        self.save_store(&store)?;
+        Ok(id)
+    }
+
+    /// 从地址簿删掉一条总部记录
+    pub fn delete_supernode(&self, id: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        if !store.remove_supernode(id) {
+            anyhow::bail!("地址簿里没有编号为“{}”的总部", id);
+        }
+        self.save_store(&store)
+    }
+
+    /// 记下一次延迟测量结果
+    pub fn record_supernode_rtt(&self, id: &str, rtt_ms: Option<f64>, measured_at: u64) -> Result<()> {
+        let mut store = self.load_store()?;
+        let entry = store
+            .supernodes
+            .iter_mut()
+            .find(|s| s.id == id)
+            .with_context(|| format!("地址簿里没有编号为“{}”的总部", id))?;
+        entry.last_rtt_ms = rtt_ms;
+        entry.last_measured = Some(measured_at);
+        self.save_store(&store)
+    }
+
+    /// 翻看认路对照表
+    pub fn network_rules(&self) -> Result<Vec<NetworkRule>> {
+        Ok(self.load_store()?.network_rules)
+    }
+
+    /// 整张替换认路对照表（规则里的方案必须存在）
+    pub fn save_network_rules(&self, rules: Vec<NetworkRule>) -> Result<()> {
+        let mut store = self.load_store()?;
+        if let Some(rule) = rules.iter().find(|rule| store.get(&rule.profile).is_none()) {
+            anyhow::bail!("找不到名为“{}”的方案", rule.profile);
+        }
+        store.network_rules = rules;
+        self.save_store(&store)
+    }
+
+    /// 删除一份方案（至少要留下一份；删掉当前方案时换用第一份）
+    pub fn delete_profile(&self, name: &str) -> Result<()> {
+        let mut store = self.load_store()?;
+        if store.get(name).is_none() {
+            anyhow::bail!("找不到名为“{}”的方案", name);
+        }
+        if store.profiles.len() == 1 {
+            anyhow::bail!("至少要留下一份方案哦");
+        }
+        store.profiles.retain(|p| p.name != name);
+        store.network_rules.retain(|rule| rule.profile != name);
+        store.normalize();
+        self.save_store(&store)?;
+        for account in [secret_store::profile_account(name), secret_store::rotation_account(name)] {
+            if let Err(e) = self.secrets.delete(&account) {
+                log::warn!("{:#}", e);
+            }
+        }
         Ok(())
     }
 }
@@ -97,12 +1441,14 @@ impl ConfigManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::TempDir;
 
     #[test]
     fn test_default_config() {
         let config = N2NConfig::default();
         assert_eq!(config.ip_mode, "dhcp");
         assert_eq!(config.mtu, Some(1290));
+        assert_eq!(config.theme, None);
     }
 
     #[test]
@@ -112,4 +1458,320 @@ mod tests {
         let deserialized: N2NConfig = toml::from_str(&toml_str).unwrap();
         assert_eq!(config.ip_mode, deserialized.ip_mode);
     }
+
+    #[test]
+    fn test_log_forward_defaults_when_missing() {
+        let toml_str = r#"
+supernode = "n2n.example.com:7777"
+community = "c"
+username = ""
+encryption_key = ""
+ip_mode = "dhcp"
+"#;
+        let config: N2NConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.log_forward, LogForwardFilter::All);
+    }
+
+    #[test]
+    fn test_migrate_legacy_config_into_default_profile() {
+        let dir = TempDir::new("config");
+        let path = dir.join("config.toml");
+        let legacy = r#"
+supernode = "n2n.example.com:7777"
+community = "home"
+username = "laptop"
+encryption_key = "secret"
+ip_mode = "static"
+static_ip = "10.0.0.2/24"
+mtu = 1400
+extra_args = "-v -A4 -H -z2 -r -t 5645 -i 10 --management-password 'my pw'"
+"#;
+        fs::write(&path, legacy).unwrap();
+
+        let manager = ConfigManager::at(path.clone());
+        assert!(manager.migrate_legacy().unwrap());
+        assert!(!manager.migrate_legacy().unwrap());
+        assert_eq!(fs::read_to_string(dir.join("config.legacy.toml")).unwrap(), legacy);
+
+        let store = manager.load_store().unwrap();
+        assert_eq!(store.active_profile, DEFAULT_PROFILE_NAME);
+        let config = manager.load().unwrap();
+        assert_eq!(config.supernode, "n2n.example.com:7777");
+        assert_eq!(config.encryption_key, "secret");
+        assert_eq!(config.static_ip.as_deref(), Some("10.0.0.2/24"));
+        assert_eq!(config.mtu, Some(1400));
+        // 加密方式、包头加密、压缩、中转、管理口和报到间隔搬进了专门的设置，高级选项里只剩 -v
+        assert_eq!(config.cipher, Cipher::Chacha20);
+        assert!(config.header_encryption);
+        assert_eq!(config.compression, Compression::Zstd);
+        assert!(config.packet_forwarding);
+        assert_eq!(config.management_port, Some(5645));
+        assert_eq!(config.register_interval_secs, Some(10));
+        assert_eq!(config.management_password.as_deref(), Some("my pw"));
+        assert_eq!(config.advanced_options, vec![EdgeOption::new("-v", None)]);
+        // 密语收进了保险箱，指示簿里只有取件单
+        let raw = fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("\"secret\""));
+        assert!(raw.contains("keyring:profile:Default"));
+
+        manager.save_profile("office", &N2NConfig::default()).unwrap();
+        assert_eq!(manager.switch_profile("office").unwrap().supernode, "");
+        manager.delete_profile("office").unwrap();
+        assert_eq!(manager.load_store().unwrap().active_profile, DEFAULT_PROFILE_NAME);
+        assert!(manager.delete_profile(DEFAULT_PROFILE_NAME).is_err());
+    }
+
+    #[test]
+    fn test_supernode_address_book_updates_referencing_profiles() {
+        let mut store = ProfileStore::default();
+        let id = store.upsert_supernode(SupernodeEntry {
+            name: "home".to_string(),
+            address: "old.example.com:7777".to_string(),
+            ..Default::default()
+        });
+        let config = N2NConfig {
+            supernode_id: Some(id.clone()),
+            ..Default::default()
+        };
+        store.upsert(DEFAULT_PROFILE_NAME, config.clone()).unwrap();
+        store.upsert("office", config).unwrap();
+        assert_eq!(store.active_config().supernode, "old.example.com:7777");
+
+        let mut entry = store.supernodes[0].clone();
+        entry.address = "new.example.com:7777".to_string();
+        store.upsert_supernode(entry);
+        assert!(store.profiles.iter().all(|p| p.config.supernode == "new.example.com:7777"));
+
+        // 删除记录后方案保留最后的地址
+        assert!(store.remove_supernode(&id));
+        assert_eq!(store.active_config().supernode, "new.example.com:7777");
+        assert_eq!(store.active_config().supernode_id, None);
+
+        let round_trip: ProfileStore = toml::from_str(&toml::to_string_pretty(&store).unwrap()).unwrap();
+        assert_eq!(round_trip.profiles.len(), 2);
+    }
+
+    #[test]
+    fn test_imported_profile_is_read_only_until_unlocked() {
+        let dir = TempDir::new("readonly");
+        let manager = ConfigManager::at(dir.join("config.toml"));
+
+        let imported = N2NConfig {
+            supernode: "sn.corp.example:7777".to_string(),
+            community: "corp".to_string(),
+            encryption_key: "admin-secret".to_string(),
+            ..Default::default()
+        };
+        manager.import_profile("corp", imported.clone()).unwrap();
+        assert!(manager.list_profiles().unwrap().iter().any(|p| p.name == "corp" && p.read_only));
+
+        // 不动锁住的几项时照常保存
+        let mut tweaked = imported.clone();
+        tweaked.mtu = Some(1400);
+        manager.save_profile("corp", &tweaked).unwrap();
+
+        tweaked.encryption_key = "guess".to_string();
+        assert!(manager.save_profile("corp", &tweaked).is_err());
+
+        manager.unlock_profile("corp").unwrap();
+        manager.save_profile("corp", &tweaked).unwrap();
+        let store = manager.load_store().unwrap();
+        assert_eq!(store.get("corp").unwrap().config.encryption_key, "guess");
+    }
+
+    #[test]
+    fn test_profile_meta_notes_tags_and_timestamps() {
+        let dir = TempDir::new("meta");
+        let manager = ConfigManager::at(dir.join("config.toml"));
+
+        let config = N2NConfig {
+            community: "lab".to_string(),
+            ..Default::default()
+        };
+        manager.save_profile("lab", &config).unwrap();
+        manager
+            .set_profile_meta("lab", "  owner: Alice, expires 2026-12  ", vec![" client-a ".into(), "client-a".into(), "".into(), "temp".into()])
+            .unwrap();
+        let summary = manager.list_profiles().unwrap().into_iter().find(|p| p.name == "lab").unwrap();
+        assert_eq!(summary.meta.notes, "owner: Alice, expires 2026-12");
+        assert_eq!(summary.meta.tags, vec!["client-a", "temp"]);
+        assert!(summary.meta.created_at.is_some());
+        assert!(manager.set_profile_meta("nope", "", Vec::new()).is_err());
+
+        // 原样再存一遍不算修改，改了指示才算
+        let mut store = manager.load_store().unwrap();
+        store.get_mut("lab").unwrap().meta.modified_at = Some(1);
+        manager.save_store(&store).unwrap();
+        manager.save_profile("lab", &config).unwrap();
+        assert_eq!(manager.load_store().unwrap().get("lab").unwrap().meta.modified_at, Some(1));
+        let changed = N2NConfig {
+            mtu: Some(1400),
+            ..config
+        };
+        manager.save_profile("lab", &changed).unwrap();
+        assert!(manager.load_store().unwrap().get("lab").unwrap().meta.modified_at > Some(1));
+    }
+
+    #[test]
+    fn test_supernode_key_is_pinned_on_first_use() {
+        // 第一次用旧版的 extra_args 写法保存，顺便确认会被拆成高级选项
+        let dir = TempDir::new("keypin");
+        let manager = ConfigManager::at(dir.join("config.toml"));
+        manager
+            .save(&N2NConfig {
+                extra_args: Some("-P good-key".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let saved = manager.load().unwrap();
+        assert_eq!(saved.extra_args, None);
+        assert_eq!(saved.advanced_options, vec![EdgeOption::new("-P", Some("good-key"))]);
+
+        assert_eq!(
+            manager.verify_supernode_key(Some("good-key")).unwrap(),
+            KeyPinCheck::FirstUse { key: "good-key".to_string() }
+        );
+        assert_eq!(manager.verify_supernode_key(Some("good-key")).unwrap(), KeyPinCheck::Match);
+        assert!(matches!(
+            manager.verify_supernode_key(Some("evil-key")).unwrap(),
+            KeyPinCheck::Changed { .. }
+        ));
+
+        // 主人确认后改记方案里的公钥
+        manager
+            .save(&N2NConfig {
+                advanced_options: vec![EdgeOption::new("-P", Some("evil-key"))],
+                ..Default::default()
+            })
+            .unwrap();
+        manager.trust_supernode_key().unwrap();
+        assert_eq!(manager.verify_supernode_key(Some("evil-key")).unwrap(), KeyPinCheck::Match);
+    }
+
+    #[test]
+    fn test_plaintext_keys_move_into_secret_store() {
+        let dir = TempDir::new("secrets");
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            r#"
+active_profile = "home"
+
+[[profiles]]
+name = "home"
+
+[profiles.config]
+supernode = "n2n.example.com:7777"
+community = "home"
+username = ""
+encryption_key = "plain"
+ip_mode = "dhcp"
+
+[[profiles]]
+name = "guest"
+
+[profiles.config]
+supernode = "evil.example.com:7777"
+community = "guest"
+username = ""
+encryption_key = ""
+encryption_key_ref = "keyring:profile:home"
+ip_mode = "dhcp"
+"#,
+        )
+        .unwrap();
+
+        let manager = ConfigManager::at(path.clone());
+        assert!(manager.migrate_secrets().unwrap());
+        assert!(!manager.migrate_secrets().unwrap());
+        assert!(!fs::read_to_string(&path).unwrap().contains("plain"));
+
+        let store = manager.load_store().unwrap();
+        assert_eq!(store.get("home").unwrap().config.encryption_key, "plain");
+        // 别的方案拿着 home 的取件单也取不走密语
+        assert_eq!(store.get("guest").unwrap().config.encryption_key, "");
+
+        // 取过一次就记住了：再翻指示簿、原样存回去都不用再敲保险箱的门
+        let knocks = secret_store::backend::knocks();
+        let store = manager.load_store().unwrap();
+        manager.save_store(&store).unwrap();
+        assert_eq!(manager.load().unwrap().encryption_key, "plain");
+        assert_eq!(secret_store::backend::knocks(), knocks);
+    }
+
+    #[test]
+    fn test_encrypted_config_needs_password() {
+        let dir = TempDir::new("vault");
+        let path = dir.join("config.toml");
+        let mut manager = ConfigManager::at(path.clone());
+        manager
+            .save(&N2NConfig {
+                community: "secret-club".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // 开着同步不让加密（同步文件夹里那份是明文）
+        let sync = dir.join("sync");
+        fs::create_dir_all(&sync).unwrap();
+        manager.set_sync_dir(Some(sync.clone()));
+        assert_eq!(manager.set_password("correct horse").err().unwrap().to_string(), config_sync::ERROR_ENCRYPTED);
+        manager.set_sync_dir(None);
+
+        // 加密前留下的明文备份也一起封上
+        backup::force_backup(&path, 1).unwrap();
+        manager.set_password("correct horse").unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret-club"));
+        assert!(vault::is_sealed(&backup::read(&path, 1).unwrap()));
+        manager.set_password("battery staple").unwrap();
+        manager.set_password("correct horse").unwrap();
+        manager.restore_backup(1).unwrap();
+        manager.save(&N2NConfig { mtu: Some(1400), ..manager.load().unwrap() }).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("secret-club"));
+
+        // 加密之后就算硬塞一个同步文件夹，也不会往里写明文
+        manager.set_sync_dir(Some(sync.clone()));
+        manager.save(&manager.load().unwrap()).unwrap();
+        assert!(!sync.join(config_sync::FILE_NAME).exists());
+        assert_eq!(manager.sync_now().err().unwrap().to_string(), config_sync::ERROR_ENCRYPTED);
+        manager.set_sync_dir(None);
+
+        // 重新启动的恩兔只知道上了锁
+        let mut restarted = ConfigManager::at(path.clone());
+        assert!(!restarted.migrate_legacy().unwrap());
+        assert_eq!(restarted.encryption(), ConfigEncryption { encrypted: true, locked: true });
+        assert_eq!(restarted.load().err().unwrap().to_string(), vault::ERROR_LOCKED);
+        assert!(restarted.save(&N2NConfig::default()).is_err());
+        assert_eq!(restarted.unlock("wrong").err().unwrap().to_string(), vault::ERROR_WRONG_PASSWORD);
+
+        restarted.unlock("correct horse").unwrap();
+        let config = restarted.load().unwrap();
+        assert_eq!((config.community.as_str(), config.mtu), ("secret-club", Some(1400)));
+
+        restarted.lock();
+        assert!(restarted.encryption().locked);
+        restarted.remove_password("correct horse").unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("secret-club"));
+        assert!(backup::read(&path, 1).unwrap().contains("secret-club"));
+        assert_eq!(restarted.encryption(), ConfigEncryption { encrypted: false, locked: false });
+    }
+
+    #[test]
+    fn test_external_edits_are_told_apart_from_own_writes() {
+        let dir = TempDir::new("watch");
+        let path = dir.join("config.toml");
+        let manager = ConfigManager::at(path.clone());
+        assert!(!manager.take_external_change());
+
+        // 恩兔自己存的不算
+        manager.save(&N2NConfig::default()).unwrap();
+        assert!(!manager.take_external_change());
+
+        // 主人用编辑器改的算，而且只报一次
+        let edited = fs::read_to_string(&path).unwrap().replace("mtu = 1290", "mtu = 1400");
+        fs::write(&path, edited).unwrap();
+        assert!(manager.take_external_change());
+        assert!(!manager.take_external_change());
+        assert_eq!(manager.load().unwrap().mtu, Some(1400));
+    }
 }
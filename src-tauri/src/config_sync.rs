@@ -0,0 +1,278 @@
+//! 同步文件夹（方案跟着主人走）
+//!
+//! 主人在好几台机器上用恩兔时，以前只能导出、拷过去、再导入。现在可以在偏好里选一个同步文件夹
+//! （Dropbox、Syncthing、网盘客户端同步的目录都行），恩兔在里面放一份 `n2n-maid-profiles.toml`：
+//! - 每次保存指示簿时，先把文件夹里别的机器的改动并进来，再把整套方案写上去；
+//! - 启动时、文件夹里的那份被别的机器改了时，也对一次账；
+//! - 两边在上次对账之后都改了同一份方案，就算冲突：本机的留着，对面的另存成“方案名 (机器名)”，交给主人挑；
+//! - 密语（和约好要换的新密语）不进同步文件夹，那里往往是云盘；新机器上第一次用要再填一遍；
+//! - edge 路径、完整性校验、网卡名这些只在本机有意义的设置也不同步，换成对面的版本时本机的留着；
+//! - 删除不会同步（免得一台机器手滑，几台机器一起丢方案）；
+//! - 指示簿加了密码就不同步：同步文件夹里那份只能是明文（每台机器的钥匙都不一样），
+//!   所以加密和同步只能二选一，要加密先关掉同步，开着加密也选不了同步文件夹。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::config::{N2NConfig, Profile, ProfileStore, SupernodeEntry};
+
+/// 同步文件夹里那份文件的名字
+pub const FILE_NAME: &str = "n2n-maid-profiles.toml";
+
+/// 指示簿加密了，不能往同步文件夹里写明文
+pub const ERROR_ENCRYPTED: &str = "error_sync_config_encrypted";
+
+/// 同步文件夹里的那份方案
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncFile {
+    /// 最后是哪台机器写的
+    #[serde(default)]
+    pub written_by: String,
+    /// 最后写入时间（Unix 秒）
+    #[serde(default)]
+    pub written_at: u64,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub supernodes: Vec<SupernodeEntry>,
+}
+
+/// 一次对账从别的机器拉下来的东西
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    /// 新来的、或者换成对面版本的方案
+    pub pulled: Vec<String>,
+    /// 冲突时另存下来的对面版本
+    pub conflicts: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.pulled.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+/// 能放进同步文件夹的样子（去掉密语和保险箱的取件单，还有只在本机有意义的设置）
+fn portable(profile: &Profile) -> Profile {
+    let mut profile = profile.clone();
+    profile.config.encryption_key.clear();
+    profile.config.encryption_key_ref = None;
+    profile.key_rotation = None;
+    take_local(&mut profile.config, &mut N2NConfig::default());
+    profile
+}
+
+/// 把只在本机有意义的设置从 `from` 搬到 `into`（`from` 里的清空）
+/// - edge 路径、校验和、放行开关、代跑用户：换台机器就不是同一个程序了，
+///   跟着同步过去等于让别的机器绕开完整性检查；
+/// - 网卡名、状态快照路径、管理口令：各台机器各管各的。
+fn take_local(from: &mut N2NConfig, into: &mut N2NConfig) {
+    into.edge_path = from.edge_path.take();
+    into.edge_sha256 = std::mem::take(&mut from.edge_sha256);
+    into.edge_integrity_bypass = std::mem::take(&mut from.edge_integrity_bypass);
+    into.edge_run_as = from.edge_run_as.take();
+    into.tap_device = from.tap_device.take();
+    into.status_snapshot_path = from.status_snapshot_path.take();
+    into.management_password = from.management_password.take();
+}
+
+/// 给整本指示簿拍一张能放进同步文件夹的快照
+pub fn snapshot(store: &ProfileStore, written_by: &str, written_at: u64) -> SyncFile {
+    SyncFile {
+        written_by: written_by.to_string(),
+        written_at,
+        profiles: store.profiles.iter().map(portable).collect(),
+        supernodes: store.supernodes.clone(),
+    }
+}
+
+/// 两份方案的内容一样不一样（按序列化结果比，密语不算）
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// 读出同步文件夹里的那份（还没有时为 None）
+pub fn read(dir: &Path) -> Result<Option<SyncFile>> {
+    let path = dir.join(FILE_NAME);
+    match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content)
+            .with_context(|| format!("同步文件夹里的 {} 已经损坏", path.display()))
+            .map(Some),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("读取 {} 失败", path.display())),
+    }
+}
+
+/// 写进同步文件夹（内容和里面那份一样就不动它，免得同步软件来回搬）；返回是否真的写了
+pub fn write(dir: &Path, file: &SyncFile) -> Result<bool> {
+    if let Some(existing) = read(dir).ok().flatten() {
+        if same(&(&existing.profiles, &existing.supernodes), &(&file.profiles, &file.supernodes)) {
+            return Ok(false);
+        }
+    }
+    let content = toml::to_string_pretty(file).context("序列化同步文件失败")?;
+    // 先写临时文件再改名：同步软件不会搬走写了一半的文件
+    let path = dir.join(FILE_NAME);
+    let tmp = dir.join(format!(".{}.tmp", FILE_NAME));
+    fs::write(&tmp, content).with_context(|| format!("写入 {} 失败", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("写入 {} 失败", path.display()))?;
+    Ok(true)
+}
+
+/// 把同步文件夹里的改动并进本机的指示簿（以 `synced_at` 为上次对账的时间）
+pub fn merge(store: &mut ProfileStore, remote: &SyncFile) -> SyncReport {
+    let base = store.synced_at.unwrap_or(0);
+    let mut report = SyncReport::default();
+    for theirs in &remote.profiles {
+        let Some(ours) = store.profiles.iter_mut().find(|p| p.name == theirs.name) else {
+            store.profiles.push(portable(theirs));
+            report.pulled.push(theirs.name.clone());
+            continue;
+        };
+        if same(&portable(ours), &portable(theirs)) {
+            continue;
+        }
+        let our_time = ours.meta.modified_at.unwrap_or(0);
+        let their_time = theirs.meta.modified_at.unwrap_or(0);
+        if our_time > base && their_time > base {
+            // 两边都改过：本机的留着，对面的另存一份
+            let host = if remote.written_by.is_empty() { "sync" } else { remote.written_by.as_str() };
+            let base_name = format!("{} ({})", theirs.name, host);
+            // 上次已经另存过同样的一份（还没来得及推上去）就不再存了
+            if store
+                .profiles
+                .iter()
+                .any(|p| p.name.starts_with(&base_name) && same(&portable(p).config, &theirs.config))
+            {
+                continue;
+            }
+            let mut name = base_name.clone();
+            let mut n = 2;
+            while store.get(&name).is_some() {
+                name = format!("{} {}", base_name, n);
+                n += 1;
+            }
+            let mut copy = portable(theirs);
+            copy.name = name.clone();
+            store.profiles.push(copy);
+            report.conflicts.push(name);
+        } else if their_time > our_time {
+            // 只有对面改过：换成对面的，本机的密语和本机设置留下
+            let mut taken = portable(theirs);
+            taken.config.encryption_key = std::mem::take(&mut ours.config.encryption_key);
+            taken.config.encryption_key_ref = ours.config.encryption_key_ref.take();
+            taken.key_rotation = ours.key_rotation.take();
+            take_local(&mut ours.config, &mut taken.config);
+            *ours = taken;
+            report.pulled.push(theirs.name.clone());
+        }
+    }
+    for entry in &remote.supernodes {
+        if !store.supernodes.iter().any(|s| s.id == entry.id) {
+            store.supernodes.push(entry.clone());
+        }
+    }
+    if !report.is_empty() {
+        store.resolve_supernode_refs();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_pulls_changes_and_detects_conflicts() {
+        let profile = |name: &str, community: &str, modified: u64| {
+            let mut store = ProfileStore::from_single(N2NConfig {
+                community: community.to_string(),
+                encryption_key: "local-secret".to_string(),
+                ..Default::default()
+            });
+            let mut p = store.profiles.remove(0);
+            p.name = name.to_string();
+            p.meta.modified_at = Some(modified);
+            p
+        };
+        let mut store = ProfileStore::from_single(N2NConfig::default());
+        store.profiles = vec![profile("home", "home", 100), profile("office", "office", 100), profile("lab", "lab", 300)];
+        store.synced_at = Some(200);
+
+        let remote = SyncFile {
+            written_by: "laptop".to_string(),
+            written_at: 400,
+            profiles: vec![
+                portable(&profile("home", "home-v2", 350)),
+                portable(&profile("office", "office", 100)),
+                portable(&profile("lab", "lab-v2", 320)),
+                portable(&profile("cafe", "cafe", 380)),
+            ],
+            supernodes: Vec::new(),
+        };
+        // 快照里不带密语
+        assert!(snapshot(&store, "desk", 0).profiles.iter().all(|p| p.config.encryption_key.is_empty()));
+
+        let report = merge(&mut store, &remote);
+        assert_eq!(report.pulled, vec!["home", "cafe"]);
+        assert_eq!(report.conflicts, vec!["lab (laptop)"]);
+
+        // 只有对面改过的换成对面的，本机的密语还在
+        let home = store.get("home").unwrap();
+        assert_eq!((home.config.community.as_str(), home.config.encryption_key.as_str()), ("home-v2", "local-secret"));
+        // 两边都改过的：本机的留着，对面的另存
+        assert_eq!(store.get("lab").unwrap().config.community, "lab");
+        assert_eq!(store.get("lab (laptop)").unwrap().config.community, "lab-v2");
+        assert!(store.get("cafe").unwrap().config.encryption_key.is_empty());
+
+        // 再对一次账：冲突副本已经在了，不会越存越多
+        assert!(merge(&mut store, &remote).conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_local_settings_stay_local() {
+        let mut store = ProfileStore::from_single(N2NConfig {
+            community: "home".to_string(),
+            edge_path: Some("/opt/n2n/edge".to_string()),
+            edge_sha256: vec!["ab".repeat(32)],
+            edge_integrity_bypass: true,
+            edge_run_as: Some("n2n".to_string()),
+            tap_device: Some("n2n0".to_string()),
+            status_snapshot_path: Some("/run/n2n-maid.json".to_string()),
+            management_password: Some("hunter2".to_string()),
+            ..Default::default()
+        });
+        store.profiles[0].meta.modified_at = Some(100);
+        store.synced_at = Some(200);
+
+        // 快照里不带本机设置
+        let file = snapshot(&store, "desk", 0);
+        let config = &file.profiles[0].config;
+        assert_eq!(config.edge_path, None);
+        assert!(config.edge_sha256.is_empty());
+        assert!(!config.edge_integrity_bypass);
+        assert_eq!(config.edge_run_as, None);
+        assert_eq!(config.tap_device, None);
+        assert_eq!(config.status_snapshot_path, None);
+        assert_eq!(config.management_password, None);
+
+        // 对面带着自己的 edge 路径改了方案：换成对面的，本机的 edge 路径和放行开关留下
+        let mut theirs = store.profiles[0].clone();
+        theirs.config.community = "home-v2".to_string();
+        theirs.config.edge_path = Some("/tmp/evil-edge".to_string());
+        theirs.config.edge_integrity_bypass = false;
+        theirs.meta.modified_at = Some(300);
+        let remote = SyncFile {
+            written_by: "laptop".to_string(),
+            profiles: vec![theirs],
+            ..Default::default()
+        };
+        assert_eq!(merge(&mut store, &remote).pulled.len(), 1);
+        let config = &store.profiles[0].config;
+        assert_eq!(config.community, "home-v2");
+        assert_eq!(config.edge_path.as_deref(), Some("/opt/n2n/edge"));
+        assert!(config.edge_integrity_bypass);
+        assert_eq!(config.management_password.as_deref(), Some("hunter2"));
+    }
+}
@@ -0,0 +1,79 @@
+//! 盯着指示簿（主人直接用编辑器改了 `config.toml` 时马上知道）
+//!
+//! 懂行的主人喜欢直接改 `config.toml`，以前改完要重启恩兔才看得到。现在恩兔请系统的文件通知盯着它：
+//! - 很多编辑器是“写临时文件再改名”，所以盯的是所在目录，只看名字对得上的动静；
+//! - 一次保存常常有好几下动静，稍等片刻并成一次；
+//! - 恩兔自己写进去的不算（按内容的指纹认，见 `ConfigManager::take_external_change`）。
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::OsStr;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// 同一次保存里的几下动静在这段时间内并成一次
+const SETTLE: Duration = Duration::from_millis(300);
+
+/// 文件内容的指纹（认出是不是恩兔自己写的）
+pub fn fingerprint(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 这下动静是不是冲着指示簿来的（光是被读一下不算）
+fn touches(event: &notify::Event, file_name: &OsStr) -> bool {
+    !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|p| p.file_name() == Some(file_name))
+}
+
+/// 开始盯着指示簿，有动静时（在小工人线程里）调用 `on_change`
+pub fn watch(path: &Path, on_change: impl Fn() + Send + 'static) -> Result<()> {
+    let dir = path.parent().context("指示簿没有所在的目录")?.to_path_buf();
+    let file_name = path.file_name().context("指示簿没有文件名")?.to_os_string();
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+        Ok(event) if touches(&event, &file_name) => {
+            let _ = tx.send(());
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("文件通知出错：{}", e),
+    })
+    .context("创建文件监视器失败")?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("盯不住 {}", dir.display()))?;
+
+    thread::spawn(move || {
+        // 监视器要一直拿在手里，放下就不再有通知了
+        let _watcher = watcher;
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(SETTLE).is_ok() {}
+            on_change();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, ModifyKind};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_touches_only_the_config_file() {
+        let name = OsStr::new("config.toml");
+        let event = |kind, path: &str| notify::Event::new(kind).add_path(PathBuf::from(path));
+
+        assert!(touches(&event(EventKind::Modify(ModifyKind::Any), "/home/a/.config/n2n-maid/config.toml"), name));
+        assert!(touches(&event(EventKind::Create(notify::event::CreateKind::File), "/x/config.toml"), name));
+        assert!(!touches(&event(EventKind::Access(AccessKind::Any), "/x/config.toml"), name));
+        assert!(!touches(&event(EventKind::Modify(ModifyKind::Any), "/x/config.toml.swp"), name));
+
+        assert_eq!(fingerprint(b"a = 1"), fingerprint(b"a = 1"));
+        assert_ne!(fingerprint(b"a = 1"), fingerprint(b"a = 2"));
+    }
+}
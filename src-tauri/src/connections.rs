@@ -0,0 +1,183 @@
+//! 同时开好几条通道（比如家里的小组和客户的小组一起连着）
+//!
+//! 主通道还是 `AppState.process`：跟着当前方案走，托盘、遥控口、传话筒和各种巡查小工人都只看它。
+//! 其余方案可以各开一条“副通道”，按方案名登记在 `ConnectionManager` 里：
+//! - 每条副通道是一只独立的 `N2NProcess`：自己的状态、自己的汇报本、自己的虚拟网卡；
+//! - 开工前对照已经开着的通道，网卡名、管理口、本机端口撞了就不让开（两只 edge 抢同一个口只会互相挤掉）；
+//! - 副通道的方案原样使用，不叠加命令行/环境变量的外部指示（那些是给主通道的）。
+//!
+//! 登记簿的锁只用来翻一眼（`with` / `map`）和取出一只手：开工、收工要等 edge 好几秒，
+//! 都在锁外面做（克隆出来的手被丢掉时不会收拾 edge，只有登记簿里那一只会）。
+//! 开工前的对照和开工本身由另一把 `starting` 锁排队，免得两条新通道同时对照、一起抢到同一块网卡。
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::config::N2NConfig;
+use crate::events::EventSender;
+use crate::log_buffer::{self, LogReceiver};
+use crate::n2n_process::{ConnectionStatus, N2NProcess, DEFAULT_MGMT_PORT};
+
+/// 一条副通道
+struct Connection {
+    process: N2NProcess,
+    /// 这条通道自己的汇报本
+    logs: LogReceiver,
+}
+
+/// 副通道登记簿（按方案名）
+pub struct ConnectionManager {
+    connections: Mutex<HashMap<String, Connection>>,
+    /// 开工排队（对照完到 edge 出门之间不让别的副通道插进来）
+    starting: Mutex<()>,
+    /// 副通道的事件和主通道走同一个小喇叭（恩兔说的话、工作总结照样记下来）
+    event_tx: Option<EventSender>,
+}
+
+/// 这条通道实际占用的虚拟网卡名（没填时 Linux 下 edge 用 edge0，其它系统由 edge 自己挑）
+fn tap_of(config: &N2NConfig) -> Option<String> {
+    let tap = config.tap_device.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    #[cfg(target_os = "linux")]
+    let tap = tap.or(Some("edge0"));
+    tap.map(str::to_string)
+}
+
+/// 新通道和已经开着的通道有没有抢同一样东西（有的话返回错误码）
+/// - 两边都没指定网卡时也算撞：edge 多半会挑中同一块
+pub fn clash(config: &N2NConfig, running: &[N2NConfig]) -> Option<&'static str> {
+    let mgmt_port = |c: &N2NConfig| c.management_port.unwrap_or(DEFAULT_MGMT_PORT);
+    for other in running {
+        if tap_of(config) == tap_of(other) {
+            return Some("error_connection_tap_clash");
+        }
+        if mgmt_port(config) == mgmt_port(other) {
+            return Some("error_connection_mgmt_port_clash");
+        }
+        if config.local_port.is_some() && config.local_port == other.local_port {
+            return Some("error_connection_local_port_clash");
+        }
+    }
+    None
+}
+
+impl ConnectionManager {
+    pub fn new(event_tx: Option<EventSender>) -> Self {
+        Self {
+            connections: Mutex::new(HashMap::new()),
+            starting: Mutex::new(()),
+            event_tx,
+        }
+    }
+
+    /// 用方案 `id` 的指示开一条副通道（`primary` 是主通道正在用的指示，一起拿来对照）
+    pub fn connect(&self, id: &str, config: &N2NConfig, primary: Option<N2NConfig>) -> Result<()> {
+        let _starting = self.starting.lock().unwrap();
+        let process = {
+            let mut connections = self.connections.lock().unwrap();
+            let running: Vec<N2NConfig> = primary
+                .into_iter()
+                .chain(
+                    connections
+                        .iter()
+                        .filter(|(other, _)| other.as_str() != id)
+                        .filter_map(|(_, c)| c.process.running_config()),
+                )
+                .collect();
+            if let Some(code) = clash(config, &running) {
+                anyhow::bail!(code);
+            }
+
+            let connection = connections.entry(id.to_string()).or_insert_with(|| {
+                let (log_tx, logs) = log_buffer::channel(log_buffer::DEFAULT_CAPACITY);
+                let mut process = N2NProcess::new();
+                process.set_log_sender(log_tx);
+                if let Some(ref tx) = self.event_tx {
+                    process.set_event_sender(tx.clone());
+                }
+                Connection { process, logs }
+            });
+            connection.process.clone()
+        };
+        log::info!("恩兔给方案“{}”另开一条通道（小组 {}）", id, config.community);
+        process.start(config)
+    }
+
+    /// 收起方案 `id` 的副通道（登记留着，汇报本还能看）
+    pub fn disconnect(&self, id: &str) -> Result<()> {
+        self.with(id, N2NProcess::clone)
+            .with_context(|| format!("方案“{}”没有开着的副通道", id))?
+            .stop()
+    }
+
+    /// 借方案 `id` 的副通道看一眼（没开过时为 None）
+    pub fn with<R>(&self, id: &str, f: impl FnOnce(&N2NProcess) -> R) -> Option<R> {
+        self.connections.lock().unwrap().get(id).map(|c| f(&c.process))
+    }
+
+    /// 挨个看一眼全部副通道（按方案名排好）
+    pub fn map<R>(&self, f: impl Fn(&str, &N2NProcess) -> R) -> Vec<R> {
+        let connections = self.connections.lock().unwrap();
+        let mut ids: Vec<&String> = connections.keys().collect();
+        ids.sort();
+        ids.into_iter().map(|id| f(id, &connections[id].process)).collect()
+    }
+
+    /// 取走方案 `id` 的副通道汇报
+    pub fn drain_logs(&self, id: &str) -> Option<Vec<String>> {
+        self.connections.lock().unwrap().get(id).map(|c| c.logs.drain())
+    }
+
+    /// 有没有副通道的 edge 还在跑
+    pub fn any_running(&self) -> bool {
+        self.map(|_, process| process.is_running()).into_iter().any(|running| running)
+    }
+
+    /// 还开着的副通道正在用的指示（给主通道开工前对照）
+    pub fn running_configs(&self) -> Vec<N2NConfig> {
+        self.map(|_, process| process.running_config()).into_iter().flatten().collect()
+    }
+
+    /// 恩兔下班：副通道全部收起（等它们走，不听话的强制清理）
+    pub fn stop_all(&self) {
+        let processes: Vec<(String, N2NProcess)> = self.map(|id, process| (id.to_string(), process.clone()));
+        for (id, process) in processes {
+            if matches!(process.status(), ConnectionStatus::Disconnected | ConnectionStatus::Error(..)) {
+                continue;
+            }
+            if let Err(e) = process.stop() {
+                log::warn!("收起方案“{}”的副通道失败：{}", id, e);
+            }
+            process.wait_stopped_or_force();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clash() {
+        let home = N2NConfig {
+            tap_device: Some("n2n-home".to_string()),
+            management_port: Some(5644),
+            local_port: Some(50000),
+            ..N2NConfig::default()
+        };
+        let client = N2NConfig {
+            tap_device: Some("n2n-client".to_string()),
+            management_port: Some(5645),
+            ..N2NConfig::default()
+        };
+        assert_eq!(clash(&client, std::slice::from_ref(&home)), None);
+        assert_eq!(clash(&client, &[]), None);
+
+        let same_tap = N2NConfig { tap_device: Some("n2n-home".to_string()), ..client.clone() };
+        assert_eq!(clash(&same_tap, std::slice::from_ref(&home)), Some("error_connection_tap_clash"));
+        // 管理口没填就是默认的 5644
+        let default_mgmt = N2NConfig { management_port: None, ..client.clone() };
+        assert_eq!(clash(&default_mgmt, std::slice::from_ref(&home)), Some("error_connection_mgmt_port_clash"));
+        let same_port = N2NConfig { local_port: Some(50000), ..client };
+        assert_eq!(clash(&same_port, &[home]), Some("error_connection_local_port_clash"));
+    }
+}
@@ -0,0 +1,51 @@
+//! Windows 上温柔地请 edge 下班（就像主人在它的黑框里按了 Ctrl+Break）
+//!
+//! Windows 没有 SIGINT，以前用 `taskkill /PID` 请 edge 下班：它只会给窗口发 WM_CLOSE，
+//! 可 edge 是收起黑框悄悄跑的，根本没有窗口，taskkill 多半只回一句“只能强制终止”，
+//! edge 来不及跟总部道别就被强制清理了。现在：
+//! - edge 出门时单独成一个进程组（`CREATE_NEW_PROCESS_GROUP`），之后可以只对它一个发控制台事件；
+//! - 要它下班时，恩兔临时挂到 edge 的控制台上（`AttachConsole`），对它的进程组发 `CTRL_BREAK_EVENT`
+//!   （`GenerateConsoleCtrlEvent`），edge 的控制台处理函数会让它跟总部道别、收拾好网卡再走；
+//! - 发完就从它的控制台上下来。一个进程同一时间只能挂一个控制台，所以整个过程排队进行。
+//!
+//! 接手来的 edge 不是恩兔带出门的，不一定单独成组：对它发事件可能连带主人终端里的其他程序，所以不用这一招。
+use anyhow::Result;
+use std::sync::Mutex;
+use windows_sys::Win32::System::Console::{
+    AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, ATTACH_PARENT_PROCESS, CTRL_BREAK_EVENT,
+};
+
+/// 带 edge 出门时加上：单独成一个进程组，之后才能只对它发 CTRL_BREAK
+pub const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+/// 控制台一次只能挂一个：挂上、发事件、下来要一口气做完
+static CONSOLE: Mutex<()> = Mutex::new(());
+
+/// 对进程组 `pid`（出门时带了 `CREATE_NEW_PROCESS_GROUP` 的 edge）发 CTRL_BREAK
+pub fn send_break(pid: u32) -> Result<()> {
+    let _guard = CONSOLE.lock().unwrap_or_else(|e| e.into_inner());
+    // 开发版的恩兔自己挂着控制台：先下来，发完再挂回去
+    let had_console = unsafe { FreeConsole() } != 0;
+    let result = unsafe {
+        if AttachConsole(pid) == 0 {
+            Err(anyhow::anyhow!(
+                "挂到 edge（PID: {}）的控制台失败：{}",
+                pid,
+                std::io::Error::last_os_error()
+            ))
+        } else {
+            let sent = GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid) != 0;
+            let error = std::io::Error::last_os_error();
+            FreeConsole();
+            if sent {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("给 edge（PID: {}）发 CTRL_BREAK 失败：{}", pid, error))
+            }
+        }
+    };
+    if had_console {
+        unsafe { AttachConsole(ATTACH_PARENT_PROCESS) };
+    }
+    result
+}
@@ -0,0 +1,552 @@
+//! 恩兔的遥控口（给脚本/自动化用的本机 HTTP 接口）
+//!
+//! 打开后，恩兔在本机守着一个很小的 HTTP 接口，脚本拿着令牌就能查状态、建立或断开通道：
+//! - `GET /v1/status`：查看状态（“只读”令牌即可）；
+//! - `POST /v1/connect[?profile=方案名]`、`POST /v1/disconnect`：遥控（需要“遥控”令牌）。
+//!
+//! 安全上的几道门：
+//! - 只监听 127.0.0.1；Unix 下还可以改成监听一个只有主人能读写的 Unix socket（Windows 暂不支持命名管道，照旧用 TCP）；
+//! - 每个令牌各有范围（只读 / 遥控），可以单独轮换、吊销，轮换后旧令牌立即失效；
+//! - 设置和令牌存在配置目录的 `control_api.json`（只有主人自己能读），不进指示簿，不跟着备份、导出走。
+//!   每次请求都现读这个文件，所以在工作台里轮换/吊销令牌不用重启。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// 遥控口默认端口（紧挨着 edge 的管理口 5644）
+pub const DEFAULT_PORT: u16 = 5645;
+/// 单个请求的读写超时
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// 请求头最多读多少行（防止被人塞一大堆头拖住）
+const MAX_HEADER_LINES: usize = 64;
+
+/// 令牌的权限范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    /// 只能查看状态
+    Status,
+    /// 查看状态 + 建立/断开通道
+    Control,
+}
+
+impl Scope {
+    /// 这个范围是否覆盖 `required`
+    fn allows(self, required: Scope) -> bool {
+        self == Scope::Control || required == Scope::Status
+    }
+}
+
+/// 遥控口设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ApiSettings {
+    /// 是否打开遥控口（改动后重启恩兔生效）
+    pub enabled: bool,
+    /// 监听的本机端口
+    pub port: u16,
+    /// 改为监听这个 Unix socket（仅 Unix，设置后不再监听 TCP）
+    pub socket_path: Option<String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            socket_path: None,
+        }
+    }
+}
+
+/// 一枚令牌（secret 只在文件里，不会再交给前端）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiToken {
+    id: String,
+    name: String,
+    scope: Scope,
+    secret: String,
+    created_at: u64,
+}
+
+/// 给工作台看的令牌概要
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    pub id: String,
+    pub name: String,
+    pub scope: Scope,
+    pub created_at: u64,
+}
+
+/// 刚生成（或轮换）的令牌：secret 只在这一次交给主人
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssuedToken {
+    pub info: TokenInfo,
+    pub secret: String,
+}
+
+impl From<&ApiToken> for TokenInfo {
+    fn from(token: &ApiToken) -> Self {
+        Self {
+            id: token.id.clone(),
+            name: token.name.clone(),
+            scope: token.scope,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ApiFile {
+    #[serde(default)]
+    settings: ApiSettings,
+    #[serde(default)]
+    tokens: Vec<ApiToken>,
+}
+
+/// 遥控口的设置与令牌本
+#[derive(Debug, Clone)]
+pub struct ControlApiStore {
+    path: PathBuf,
+}
+
+impl ControlApiStore {
+    /// 翻开令牌本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("control_api.json")))
+    }
+
+    /// 指定令牌本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> Result<ApiFile> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).context("遥控口设置文件已损坏"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ApiFile::default()),
+            Err(e) => Err(e).context("读取遥控口设置失败"),
+        }
+    }
+
+    fn save(&self, file: &ApiFile) -> Result<()> {
+        let content = serde_json::to_string_pretty(file).context("序列化遥控口设置失败")?;
+        write_private(&self.path, &content)
+    }
+
+    pub fn settings(&self) -> Result<ApiSettings> {
+        Ok(self.load()?.settings)
+    }
+
+    pub fn save_settings(&self, settings: ApiSettings) -> Result<()> {
+        if settings.port == 0 {
+            anyhow::bail!("遥控口端口不能是 0");
+        }
+        let mut file = self.load()?;
+        file.settings = settings;
+        self.save(&file)
+    }
+
+    pub fn list_tokens(&self) -> Result<Vec<TokenInfo>> {
+        Ok(self.load()?.tokens.iter().map(TokenInfo::from).collect())
+    }
+
+    /// 发一枚新令牌
+    pub fn create_token(&self, name: &str, scope: Scope, now: u64) -> Result<IssuedToken> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("给令牌起个名字吧（比如“备份脚本”）");
+        }
+        let mut file = self.load()?;
+        let token = ApiToken {
+            id: random_hex(6)?,
+            name: name.to_string(),
+            scope,
+            secret: random_hex(32)?,
+            created_at: now,
+        };
+        let issued = IssuedToken {
+            info: TokenInfo::from(&token),
+            secret: token.secret.clone(),
+        };
+        file.tokens.push(token);
+        self.save(&file)?;
+        Ok(issued)
+    }
+
+    /// 轮换令牌：换一个新的 secret，旧的立即失效
+    pub fn rotate_token(&self, id: &str, now: u64) -> Result<IssuedToken> {
+        let mut file = self.load()?;
+        let token = file
+            .tokens
+            .iter_mut()
+            .find(|t| t.id == id)
+            .with_context(|| format!("没有编号为“{}”的令牌", id))?;
+        token.secret = random_hex(32)?;
+        token.created_at = now;
+        let issued = IssuedToken {
+            info: TokenInfo::from(&*token),
+            secret: token.secret.clone(),
+        };
+        self.save(&file)?;
+        Ok(issued)
+    }
+
+    /// 吊销令牌
+    pub fn revoke_token(&self, id: &str) -> Result<()> {
+        let mut file = self.load()?;
+        let before = file.tokens.len();
+        file.tokens.retain(|t| t.id != id);
+        if file.tokens.len() == before {
+            anyhow::bail!("没有编号为“{}”的令牌", id);
+        }
+        self.save(&file)
+    }
+
+    /// 核对请求带来的令牌，返回令牌的名字（用于日志）
+    fn authorize(&self, secret: Option<&str>, required: Scope) -> std::result::Result<String, Reply> {
+        let secret = secret.ok_or_else(|| Reply::error(401, "缺少令牌（Authorization: Bearer <token>）"))?;
+        let file = self.load().map_err(|e| Reply::error(500, &e.to_string()))?;
+        let token = file
+            .tokens
+            .iter()
+            .find(|t| constant_time_eq(t.secret.as_bytes(), secret.as_bytes()))
+            .ok_or_else(|| Reply::error(401, "令牌无效或已被吊销"))?;
+        if !token.scope.allows(required) {
+            return Err(Reply::error(403, "这枚令牌只能查看状态"));
+        }
+        Ok(token.name.clone())
+    }
+}
+
+/// 遥控口能办的事
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Status,
+    Connect { profile: Option<String> },
+    Disconnect,
+}
+
+impl Endpoint {
+    fn required_scope(&self) -> Scope {
+        match self {
+            Endpoint::Status => Scope::Status,
+            Endpoint::Connect { .. } | Endpoint::Disconnect => Scope::Control,
+        }
+    }
+
+    /// 按请求方法和路径找到对应的事
+    fn route(method: &str, target: &str) -> std::result::Result<Self, Reply> {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let endpoint = match path {
+            "/v1/status" => Endpoint::Status,
+            "/v1/connect" => Endpoint::Connect {
+                profile: query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("profile="))
+                    .map(percent_decode)
+                    .filter(|p| !p.is_empty()),
+            },
+            "/v1/disconnect" => Endpoint::Disconnect,
+            _ => return Err(Reply::error(404, "没有这个接口")),
+        };
+        let expected = if endpoint == Endpoint::Status { "GET" } else { "POST" };
+        if method != expected {
+            return Err(Reply::error(405, &format!("这个接口请用 {}", expected)));
+        }
+        Ok(endpoint)
+    }
+}
+
+/// 一份 HTTP 回复
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Reply {
+    fn ok(body: serde_json::Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }),
+        }
+    }
+
+    fn write_to(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        };
+        let body = self.body.to_string();
+        write!(
+            out,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )?;
+        out.flush()
+    }
+}
+
+/// 打开遥控口：每个请求交给 `handler` 处理（令牌和范围已经核对过）
+pub fn serve<F>(settings: &ApiSettings, store: ControlApiStore, handler: F) -> Result<()>
+where
+    F: Fn(Endpoint) -> std::result::Result<serde_json::Value, String> + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+
+    #[cfg(unix)]
+    if let Some(socket_path) = settings.socket_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixListener;
+
+        // 上次没收拾的 socket 文件会挡住 bind
+        let _ = fs::remove_file(socket_path);
+        let listener = UnixListener::bind(socket_path).with_context(|| format!("无法监听 {}", socket_path))?;
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o600))?;
+        log::info!("遥控口已打开：unix:{}", socket_path);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+                let store = store.clone();
+                let handler = Arc::clone(&handler);
+                thread::spawn(move || handle_connection(&stream, &mut &stream, &store, handler.as_ref()));
+            }
+        });
+        return Ok(());
+    }
+
+    #[cfg(not(unix))]
+    if settings.socket_path.as_deref().is_some_and(|p| !p.trim().is_empty()) {
+        log::warn!("Windows 下暂不支持命名管道，遥控口照旧监听 127.0.0.1:{}", settings.port);
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))
+        .with_context(|| format!("无法监听 127.0.0.1:{}", settings.port))?;
+    log::info!("遥控口已打开：http://127.0.0.1:{}", settings.port);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+            let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+            let store = store.clone();
+            let handler = Arc::clone(&handler);
+            thread::spawn(move || handle_connection(&stream, &mut &stream, &store, handler.as_ref()));
+        }
+    });
+    Ok(())
+}
+
+/// 处理一个连接上的一次请求
+fn handle_connection<F>(input: impl Read, output: &mut impl Write, store: &ControlApiStore, handler: &F)
+where
+    F: Fn(Endpoint) -> std::result::Result<serde_json::Value, String>,
+{
+    let reply = match read_request(BufReader::new(input)) {
+        Ok((method, target, bearer)) => dispatch(&method, &target, bearer.as_deref(), store, handler),
+        Err(e) => Reply::error(400, &e.to_string()),
+    };
+    if let Err(e) = reply.write_to(output) {
+        log::debug!("遥控口回复没送出去：{}", e);
+    }
+}
+
+fn dispatch<F>(method: &str, target: &str, bearer: Option<&str>, store: &ControlApiStore, handler: &F) -> Reply
+where
+    F: Fn(Endpoint) -> std::result::Result<serde_json::Value, String>,
+{
+    let endpoint = match Endpoint::route(method, target) {
+        Ok(endpoint) => endpoint,
+        Err(reply) => return reply,
+    };
+    let token_name = match store.authorize(bearer, endpoint.required_scope()) {
+        Ok(name) => name,
+        Err(reply) => {
+            log::warn!("遥控口拒绝了一次请求：{} {}（{}）", method, target, reply.status);
+            return reply;
+        }
+    };
+    if endpoint != Endpoint::Status {
+        log::info!("遥控口：令牌“{}”请求 {:?}", token_name, endpoint);
+    }
+    match handler(endpoint) {
+        Ok(body) => Reply::ok(body),
+        Err(e) => Reply::error(500, &e),
+    }
+}
+
+/// 读请求行和请求头，返回（方法, 路径, Bearer 令牌）
+fn read_request(mut reader: impl BufRead) -> Result<(String, String, Option<String>)> {
+    let mut line = String::new();
+    reader.read_line(&mut line).context("读取请求失败")?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().context("请求行不完整")?.to_string();
+    let target = parts.next().context("请求行不完整")?.to_string();
+
+    let mut bearer = None;
+    for _ in 0..MAX_HEADER_LINES {
+        line.clear();
+        if reader.read_line(&mut line).context("读取请求头失败")? == 0 {
+            break;
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            return Ok((method, target, bearer));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                bearer = value.trim().strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+        }
+    }
+    anyhow::bail!("请求头不完整或太长")
+}
+
+/// 逐字节比较（不因为前几位对上就提前返回，免得被人按耗时猜令牌）
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn random_hex(bytes: usize) -> Result<String> {
+    let mut buf = vec![0u8; bytes];
+    getrandom::getrandom(&mut buf).map_err(|e| anyhow::anyhow!("生成随机令牌失败：{}", e))?;
+    Ok(buf.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 只认 `%XX` 的简单解码（方案名里有空格或中文时用得上）
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .context("写入遥控口设置失败")?;
+    // `mode` 只管新建的文件：以前留下的（或者被人改宽了的）在写令牌之前先收紧
+    file.set_permissions(fs::Permissions::from_mode(0o600)).context("收紧遥控口设置的权限失败")?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).context("写入遥控口设置失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn request(store: &ControlApiStore, raw: &str) -> (u16, serde_json::Value) {
+        let mut out = Vec::new();
+        handle_connection(raw.as_bytes(), &mut out, store, &|endpoint| match endpoint {
+            Endpoint::Status => Ok(serde_json::json!({ "status": "connected" })),
+            Endpoint::Connect { profile } => Ok(serde_json::json!({ "profile": profile })),
+            Endpoint::Disconnect => Err("还没连上".to_string()),
+        });
+        let text = String::from_utf8(out).unwrap();
+        let status = text[9..12].parse().unwrap();
+        let body = text.split("\r\n\r\n").nth(1).unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_tokens_scopes_and_rotation() {
+        let dir = TempDir::new("control-api");
+        let path = dir.join("control_api.json");
+        let store = ControlApiStore::at(path.clone());
+
+        let reader = store.create_token("监控", Scope::Status, 1).unwrap();
+        let admin = store.create_token("脚本", Scope::Control, 2).unwrap();
+        assert_eq!(store.list_tokens().unwrap().len(), 2);
+
+        let get = |secret: &str| format!("GET /v1/status HTTP/1.1\r\nAuthorization: Bearer {}\r\n\r\n", secret);
+        let post = |target: &str, secret: &str| {
+            format!("POST {} HTTP/1.1\r\nHost: x\r\nauthorization: Bearer {}\r\n\r\n", target, secret)
+        };
+
+        assert_eq!(request(&store, "GET /v1/status HTTP/1.1\r\n\r\n").0, 401);
+        assert_eq!(request(&store, &get(&reader.secret)).0, 200);
+        assert_eq!(request(&store, &post("/v1/disconnect", &reader.secret)).0, 403);
+        assert_eq!(request(&store, &post("/v1/status", &admin.secret)).0, 405);
+        assert_eq!(request(&store, &get("nope")).0, 401);
+
+        let (status, body) = request(&store, &post("/v1/connect?profile=%E5%8A%9E%E5%85%AC%E5%AE%A4", &admin.secret));
+        assert_eq!(status, 200);
+        assert_eq!(body["profile"], "办公室");
+        assert_eq!(request(&store, &post("/v1/disconnect", &admin.secret)).0, 500);
+
+        // 轮换后旧令牌立即失效
+        let rotated = store.rotate_token(&admin.info.id, 3).unwrap();
+        assert_eq!(request(&store, &get(&admin.secret)).0, 401);
+        assert_eq!(request(&store, &get(&rotated.secret)).0, 200);
+
+        store.revoke_token(&reader.info.id).unwrap();
+        assert_eq!(request(&store, &get(&reader.secret)).0, 401);
+        assert!(store.revoke_token(&reader.info.id).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_existing_file_becomes_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new("control-api-mode");
+        let path = dir.join("control_api.json");
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        ControlApiStore::at(path.clone()).create_token("监控", Scope::Status, 1).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,77 @@
+//! 桌面上的“VPN 已连接”提示（主要是 Linux）
+//!
+//! 以前只有恩兔自己的托盘提示文字会变，而 Linux 上的托盘走 StatusNotifier/AppIndicator，
+//! 根本不显示提示文字，桌面上看不出通道是不是通着。现在：
+//! - 托盘图标旁边挂一个短标签（AppIndicator 的 label / StatusNotifier 的 title），连上时显示 `VPN`；
+//! - 主人打开 `desktop_vpn_hint` 时，连上后再请 NetworkManager 把虚拟网卡当成“外部配置的连接”显示出来，
+//!   GNOME / KDE 的网络面板里就能看到它处于已连接（NetworkManager 不会改网卡上的地址和路由）。
+//!
+//! 没有 NetworkManager、或者没有权限时只在日志里记一句，不影响通道。
+use crate::n2n_process::ConnectionStatus;
+
+/// 托盘图标旁边的短标签（没连上时不显示）
+pub fn indicator_label(status: &ConnectionStatus) -> Option<&'static str> {
+    match status {
+        ConnectionStatus::Connected(_) => Some("VPN"),
+        ConnectionStatus::Connecting | ConnectionStatus::Degraded(_) | ConnectionStatus::Reconnecting(_) => Some("VPN…"),
+        _ => None,
+    }
+}
+
+/// 从 `nmcli -t -f GENERAL.STATE device show <dev>` 的输出里看网卡是不是没人管
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn is_unmanaged(nmcli_output: &str) -> bool {
+    nmcli_output
+        .lines()
+        .filter_map(|line| line.strip_prefix("GENERAL.STATE:"))
+        .any(|state| state.contains("unmanaged"))
+}
+
+/// 请 NetworkManager 把虚拟网卡显示成已连接（只接管显示，不改地址和路由）
+#[cfg(target_os = "linux")]
+pub fn hint_network_manager(device: &str) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use std::process::Command;
+
+    let output = Command::new("nmcli")
+        .args(["-t", "-f", "GENERAL.STATE", "device", "show", device])
+        .output()
+        .context("找不到 nmcli（没有装 NetworkManager？）")?;
+    if !output.status.success() {
+        anyhow::bail!("NetworkManager 不认识网卡 {}：{}", device, String::from_utf8_lossy(&output.stderr).trim());
+    }
+    if !is_unmanaged(&String::from_utf8_lossy(&output.stdout)) {
+        // 已经在 NetworkManager 的名单上了（显示为“已连接（外部）”）
+        return Ok(());
+    }
+    let status = Command::new("nmcli")
+        .args(["device", "set", device, "managed", "yes"])
+        .status()
+        .context("运行 nmcli 失败")?;
+    if !status.success() {
+        anyhow::bail!("nmcli device set {} managed yes 失败（{}）", device, status);
+    }
+    log::info!("已请 NetworkManager 显示虚拟网卡 {}（外部连接）", device);
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn hint_network_manager(_device: &str) -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicator_label_and_nmcli_state() {
+        assert_eq!(indicator_label(&ConnectionStatus::Connected(None)), Some("VPN"));
+        assert_eq!(indicator_label(&ConnectionStatus::Disconnected), None);
+        assert_eq!(indicator_label(&ConnectionStatus::Error("x".to_string(), None)), None);
+
+        assert!(is_unmanaged("GENERAL.STATE:10 (unmanaged)\n"));
+        assert!(!is_unmanaged("GENERAL.STATE:100 (connected (externally))\n"));
+        assert!(!is_unmanaged(""));
+    }
+}
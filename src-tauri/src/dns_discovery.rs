@@ -0,0 +1,170 @@
+//! 按域名找总部（DNS SRV / TXT）
+//!
+//! 管着一整队机器的管理员换总部时，以前只能挨个改方案、重新发邀请。现在方案里可以只填一个域名
+//! （`supernode_dns`），每次建立通道前恩兔去 DNS 里问：
+//! - `_n2n._udp.<域名>` 的 SRV 记录：总部的 host 和端口（优先级数字小的先用，同级里权重大的先用）；
+//! - `_n2n.<域名>` 的 TXT 记录：`community=<暗号>`，方案里没填暗号时拿来用（密语绝不放进 DNS）。
+//!
+//! 管理员改一下 DNS，大家下次建立通道（包括自动重连）时就换到新总部了。
+//!
+//! 连着的时候也会每隔 `supernode_recheck_secs` 重新查一次总部地址（`supernode_addrs`），动态域名换了 IP
+//! 就重新建立通道；不然 edge 会一直对着已经没人的旧 IP 喊，直到主人发现。
+use anyhow::{Context, Result};
+use hickory_resolver::Resolver;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+use crate::config::N2NConfig;
+
+/// 一条 SRV 记录（优先级、权重、目标主机、端口）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvTarget {
+    pub priority: u16,
+    pub weight: u16,
+    pub host: String,
+    pub port: u16,
+}
+
+/// 从域名里查到的总部信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Discovered {
+    /// 总部地址（host:port）
+    pub supernode: String,
+    /// TXT 记录里给的暗号
+    pub community: Option<String>,
+}
+
+/// 挑出最该用的那条 SRV 记录（`.` 表示“这里没有这项服务”，不算）
+fn pick_srv(mut targets: Vec<SrvTarget>) -> Option<String> {
+    targets.retain(|t| !t.host.is_empty() && t.host != "." && t.port != 0);
+    targets.sort_by(|a, b| a.priority.cmp(&b.priority).then(b.weight.cmp(&a.weight)));
+    targets
+        .first()
+        .map(|t| format!("{}:{}", t.host.trim_end_matches('.'), t.port))
+}
+
+/// 从 TXT 记录里读出 `community=...`
+fn parse_txt<'a>(records: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    records.into_iter().find_map(|record| {
+        let (key, value) = record.split_once('=')?;
+        let value = value.trim();
+        (key.trim().eq_ignore_ascii_case("community") && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// 去 DNS 里问这个域名的总部
+pub fn discover(domain: &str) -> Result<Discovered> {
+    let domain = domain.trim().trim_end_matches('.');
+    let resolver = Resolver::from_system_conf().context("读取系统的 DNS 设置失败")?;
+
+    let srv_name = format!("_n2n._udp.{}.", domain);
+    let targets = resolver
+        .srv_lookup(srv_name.as_str())
+        .with_context(|| format!("查不到 {} 的 SRV 记录", srv_name))?
+        .iter()
+        .map(|srv| SrvTarget {
+            priority: srv.priority(),
+            weight: srv.weight(),
+            host: srv.target().to_utf8(),
+            port: srv.port(),
+        })
+        .collect();
+    let supernode = pick_srv(targets).with_context(|| format!("{} 的 SRV 记录里没有可用的总部", srv_name))?;
+
+    // TXT 只是锦上添花，查不到不要紧
+    let txt_name = format!("_n2n.{}.", domain);
+    let community = match resolver.txt_lookup(txt_name.as_str()) {
+        Ok(lookup) => {
+            let records: Vec<String> = lookup
+                .iter()
+                .map(|txt| {
+                    txt.txt_data()
+                        .iter()
+                        .map(|part| String::from_utf8_lossy(part).into_owned())
+                        .collect::<String>()
+                })
+                .collect();
+            parse_txt(records.iter().map(String::as_str))
+        }
+        Err(e) => {
+            log::debug!("没有 {} 的 TXT 记录：{}", txt_name, e);
+            None
+        }
+    };
+    Ok(Discovered { supernode, community })
+}
+
+/// 方案填了总部域名的话，查出总部（和暗号）填进去；没填就原样返回
+pub fn resolve(config: &N2NConfig) -> Result<Option<N2NConfig>> {
+    let Some(domain) = config.supernode_dns.as_deref().map(str::trim).filter(|d| !d.is_empty()) else {
+        return Ok(None);
+    };
+    let found = discover(domain)?;
+    log::info!("按域名 {} 找到了总部 {}", domain, found.supernode);
+    let mut resolved = config.clone();
+    resolved.supernode = found.supernode;
+    if resolved.community.trim().is_empty() {
+        if let Some(community) = found.community {
+            log::info!("方案里没填暗号，用 DNS 里给的“{}”", community);
+            resolved.community = community;
+        }
+    }
+    Ok(Some(resolved))
+}
+
+/// 总部现在的地址（按域名找总部的先查 SRV；总部直接写成 IP 的不用查，返回 None）
+pub fn supernode_addrs(config: &N2NConfig) -> Result<Option<Vec<SocketAddr>>> {
+    let supernode = match resolve(config)? {
+        Some(resolved) => resolved.supernode,
+        None => config.supernode.trim().to_string(),
+    };
+    let host = supernode.rsplit_once(':').map_or(supernode.as_str(), |(host, _)| host);
+    if host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>().is_ok() {
+        return Ok(None);
+    }
+    let mut addrs: Vec<SocketAddr> = supernode
+        .to_socket_addrs()
+        .with_context(|| format!("查不到总部 {} 的地址", supernode))?
+        .collect();
+    addrs.sort();
+    addrs.dedup();
+    Ok(Some(addrs))
+}
+
+/// 总部是不是搬家了：新查到的地址里一个旧的都没有（轮询的域名每次只给一部分地址，不算搬家；查不到也不算）
+pub fn moved(before: &[SocketAddr], now: &[SocketAddr]) -> bool {
+    !now.is_empty() && !before.is_empty() && !now.iter().any(|addr| before.contains(addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_srv_and_parse_txt() {
+        let srv = |priority, weight, host: &str, port| SrvTarget {
+            priority,
+            weight,
+            host: host.to_string(),
+            port,
+        };
+        let picked = pick_srv(vec![
+            srv(20, 100, "backup.example.com.", 7777),
+            srv(10, 10, "light.example.com.", 7777),
+            srv(10, 60, "main.example.com.", 7654),
+        ]);
+        assert_eq!(picked.as_deref(), Some("main.example.com:7654"));
+        assert_eq!(pick_srv(vec![srv(0, 0, ".", 0)]), None);
+
+        assert_eq!(parse_txt(["v=spf1 -all", "Community = office "]).as_deref(), Some("office"));
+        assert_eq!(parse_txt(["community="]), None);
+
+        let addr = |s: &str| s.parse::<SocketAddr>().unwrap();
+        let before = [addr("198.51.100.1:7777"), addr("198.51.100.2:7777")];
+        assert!(!moved(&before, &[addr("198.51.100.2:7777")]));
+        assert!(moved(&before, &[addr("203.0.113.9:7777")]));
+        assert!(!moved(&before, &[]));
+        // 直接写成 IP 的总部不用查
+        let literal = N2NConfig { supernode: "[2001:db8::1]:7777".to_string(), ..Default::default() };
+        assert_eq!(supernode_addrs(&literal).unwrap(), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! edge 会些什么（从 `edge -h` 的说明书里看出来）
+//!
+//! 不同版本、不同编译选项的 edge 会的本事不一样：2.8 以前没有 ChaCha20、Speck 和包头加密，
+//! 没带 OpenSSL 编译的 2.8 也不会 ChaCha20，没带 zstd 编译的也不会 `-z2`。把 edge 不会的开关交给它，
+//! 它只会嘀咕一句然后照旧用默认的，主人以为加了密、压了缩，其实没有。所以恩兔先读一遍说明书：
+//! - 说明书里列了加密/压缩方式的名字，就以列出来的为准；
+//! - 没列（老版本的说明书比较简略）就按版本号推断；版本号也认不出来时不拦着，交给 edge 自己去试。
+//!
+//! 读一次说明书要启动一次 edge，所以按路径和文件修改时间记住结果（开门时和换了 edge 路径后先问好）。
+//!
+//! 拼参数时也照着说明书来，不再按系统瞎猜：
+//! - 说明书里没有 `-f`（部分 Windows 版）就不传前台运行；
+//! - 2.8 以前只有一个不带编号的 `-A`（就是 AES），没有 `-A1`～`-A5`。
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::config::{Cipher, Compression};
+use crate::n2n_process;
+
+/// 这只 edge 会的本事
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeCaps {
+    /// 版本号（认不出来时为 None）
+    pub version: Option<String>,
+    /// 大版本（2 或 3；认不出来时为 None）
+    pub generation: Option<u32>,
+    /// 是否认得 `-f`（前台运行）
+    pub foreground: bool,
+    /// 加密方式是不是按 `-A<n>` 编号选（2.8 以前只有一个 `-A`）
+    pub cipher_levels: bool,
+    /// 支持的加密方式（不含“交给 edge 决定”，那个总是可以的）
+    pub ciphers: Vec<Cipher>,
+    /// 是否支持包头加密（`-H`）
+    pub header_encryption: bool,
+    /// 支持的压缩方式（不含“不压缩”）
+    pub compressions: Vec<Compression>,
+}
+
+impl EdgeCaps {
+    pub fn supports_cipher(&self, cipher: Cipher) -> bool {
+        cipher == Cipher::Default || self.ciphers.contains(&cipher)
+    }
+
+    pub fn supports_compression(&self, compression: Compression) -> bool {
+        compression == Compression::None || self.compressions.contains(&compression)
+    }
+
+    /// 选这种加密方式要传的开关（老 edge 的 AES 是不带编号的 `-A`）
+    pub fn cipher_flag(&self, cipher: Cipher) -> Option<&'static str> {
+        if cipher == Cipher::Aes && !self.cipher_levels {
+            return Some("-A");
+        }
+        cipher.flag()
+    }
+}
+
+/// 说明书里有没有列出这个开关（`-f`、`[-H]`、`-A | ...` 这样单独出现的才算）
+fn lists_flag(help: &str, flag: &str) -> bool {
+    help.split_whitespace()
+        .any(|word| word.trim_matches(|c: char| "[]|,".contains(c)) == flag)
+}
+
+/// 版本号的前两段（`3.1.1` → (3, 1)）
+fn version_pair(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// 读说明书（认不出版本、也没列加密方式时返回 None，表示不知道）
+pub fn parse(help: &str) -> Option<EdgeCaps> {
+    let version = n2n_process::parse_edge_version(help);
+    let lower = help.to_ascii_lowercase();
+    let named = [
+        (Cipher::Twofish, "twofish"),
+        (Cipher::Aes, "aes"),
+        (Cipher::Chacha20, "chacha20"),
+        (Cipher::Speck, "speck"),
+    ];
+
+    // 2.4 的说明书里也提到 AES，但那时只有一个不带编号的 `-A`，所以要认准 `-A<n>` 的写法
+    let numbered = (1..=5).any(|n| help.contains(&format!("-A{}", n)));
+    let ciphers: Vec<Cipher> = if numbered && named.iter().any(|(_, name)| lower.contains(name)) {
+        std::iter::once(Cipher::Null)
+            .chain(named.iter().filter(|(_, name)| lower.contains(name)).map(|(cipher, _)| *cipher))
+            .collect()
+    } else if lists_flag(help, "-A") && lower.contains("aes") {
+        // 老 edge：不带编号的 `-A` 就是 AES
+        vec![Cipher::Aes]
+    } else {
+        // `-A<n>` 是 2.8 才有的写法
+        match version_pair(version.as_deref()?) {
+            Some(v) if v >= (2, 8) => Cipher::EXPLICIT.to_vec(),
+            _ => Vec::new(),
+        }
+    };
+    // zstd 是可选的编译选项，只有说明书里写了才算数
+    let compressions = if help.contains("-z1") || help.contains("-z2") {
+        std::iter::once(Compression::Lzo)
+            .chain(lower.contains("zstd").then_some(Compression::Zstd))
+            .collect()
+    } else {
+        match version.as_deref().and_then(version_pair) {
+            Some(v) if v >= (2, 8) => vec![Compression::Lzo],
+            _ => Vec::new(),
+        }
+    };
+    let header_encryption = help.contains("-H") || version.as_deref().and_then(version_pair) >= Some((2, 8));
+
+    Some(EdgeCaps {
+        generation: version.as_deref().and_then(version_pair).map(|(major, _)| major),
+        foreground: lists_flag(help, "-f"),
+        cipher_levels: numbered,
+        version,
+        ciphers,
+        header_encryption,
+        compressions,
+    })
+}
+
+type CacheKey = (String, Option<SystemTime>);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, Option<EdgeCaps>>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, Option<EdgeCaps>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 问一问这只 edge 会些什么（同一个文件只问一次；问不出来返回 None）
+pub fn detect(edge_path: &str) -> Option<EdgeCaps> {
+    let modified = std::fs::metadata(edge_path).and_then(|m| m.modified()).ok();
+    let key = (edge_path.to_string(), modified);
+    if let Some(caps) = cache().lock().unwrap().get(&key) {
+        return caps.clone();
+    }
+    let caps = n2n_process::edge_help(edge_path).and_then(|help| parse(&help));
+    cache().lock().unwrap().insert(key, caps.clone());
+    caps
+}
+
+/// 这只 edge 的底细（给设置页和“关于恩兔”看）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeInfo {
+    /// 实际会用的 edge 路径
+    pub path: String,
+    /// 文件在不在
+    pub found: bool,
+    /// 会些什么（说明书读不懂时为 None）
+    pub caps: Option<EdgeCaps>,
+}
+
+/// 查一查这只 edge 的底细（有缓存）
+pub fn info(edge_path: &str) -> EdgeInfo {
+    EdgeInfo {
+        path: edge_path.to_string(),
+        found: std::path::Path::new(edge_path).exists(),
+        caps: detect(edge_path),
+    }
+}
+
+/// 在后台先把说明书读好（开门时、换了 edge 路径时），省得第一次建立通道时再等
+pub fn warm(edge_path: String) {
+    std::thread::spawn(move || match detect(&edge_path) {
+        Some(caps) => log::info!("edge {} 的版本：{}", edge_path, caps.version.as_deref().unwrap_or("?")),
+        None => log::debug!("读不懂 edge {} 的说明书", edge_path),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_capabilities_from_help() {
+        let v3 = "Welcome to n2n v.3.1.1.r1040.4c1c7d3 for Linux\n\
+                  -A1 = none, -A2 = Twofish, -A3 = AES (default), -A4 = ChaCha20, -A5 = Speck-CTR\n\
+                  -H  | use header encryption\n\
+                  -z1 = lzo1x, -z2 = zstd\n";
+        let caps = parse(v3).unwrap();
+        assert_eq!(caps.version.as_deref(), Some("3.1.1.r1040.4c1c7d3"));
+        assert_eq!(caps.ciphers, Cipher::EXPLICIT.to_vec());
+        assert!(caps.header_encryption);
+        assert!(caps.supports_compression(Compression::Zstd));
+        assert_eq!(caps.generation, Some(3));
+        assert!(!caps.foreground);
+        assert_eq!(caps.cipher_flag(Cipher::Aes), Some("-A3"));
+
+        // 没带 OpenSSL、zstd 编译的 2.8：说明书里没有 ChaCha20 和 zstd
+        let no_openssl = "Welcome to n2n v2.8.0 for Linux\n-A2 = Twofish, -A3 = AES, -A5 = Speck-CTR\n-H\n-z1 = lzo1x\n";
+        let caps = parse(no_openssl).unwrap();
+        assert!(!caps.supports_cipher(Cipher::Chacha20));
+        assert!(caps.supports_cipher(Cipher::Speck));
+        assert!(caps.supports_cipher(Cipher::Default));
+        assert!(caps.supports_compression(Compression::Lzo));
+        assert!(!caps.supports_compression(Compression::Zstd));
+
+        // 说明书太简略时按版本号推断
+        let old = parse("Welcome to n2n v2.4.0 for Linux\n-f | do not fork\n-A | use AES CBC for encryption\n").unwrap();
+        assert_eq!(old.ciphers, vec![Cipher::Aes]);
+        assert_eq!(old.cipher_flag(Cipher::Aes), Some("-A"));
+        assert!(!old.supports_cipher(Cipher::Twofish));
+        assert!(old.foreground);
+        assert_eq!(old.generation, Some(2));
+        assert!(!old.header_encryption);
+        assert!(old.compressions.is_empty());
+        assert!(parse("Welcome to n2n v3.0.0 for Windows").unwrap().supports_cipher(Cipher::Speck));
+        assert_eq!(parse("usage: edge ..."), None);
+    }
+}
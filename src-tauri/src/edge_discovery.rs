@@ -0,0 +1,203 @@
+//! 替主人找找工具箱（机器上都装了哪些 edge）
+//!
+//! 没填 edge 路径时恩兔只会默默去找 `bin/edge`，主人用包管理器装在 `/usr/sbin/edge`、
+//! 或者装在 Program Files 里的，就只会看到“找不到工具箱”。这里把常见的地方都翻一遍：
+//! - `PATH` 里的每个目录；
+//! - 恩兔自己的资源目录（打包时附带的 edge）；
+//! - 常见的安装位置（发行版软件包、Homebrew、Program Files）。
+//!
+//! 每个找到的都问一下版本号、看一眼文件头认出是给哪种 CPU 的，交给主人挑一个填进 `edge_path`。
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::n2n_process;
+
+/// edge 可执行文件的名字
+#[cfg(target_os = "windows")]
+const EDGE_NAME: &str = "edge.exe";
+#[cfg(not(target_os = "windows"))]
+const EDGE_NAME: &str = "edge";
+
+/// 从哪里找到的
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeSource {
+    /// `PATH` 里
+    Path,
+    /// 恩兔自己的资源目录
+    Bundled,
+    /// 常见的安装位置
+    Common,
+}
+
+/// 一个找到的 edge
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeCandidate {
+    pub path: String,
+    pub source: EdgeSource,
+    /// 版本号（问不出来时为 None）
+    pub version: Option<String>,
+    /// 给哪种 CPU 的（x86_64 / aarch64 / universal ...，认不出来时为 None）
+    pub arch: Option<String>,
+}
+
+/// 常见的安装位置
+fn common_locations() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+            .iter()
+            .filter_map(|var| std::env::var_os(var))
+            .flat_map(|dir| {
+                let dir = PathBuf::from(dir);
+                [dir.join("n2n").join(EDGE_NAME), dir.join("n2n").join("bin").join(EDGE_NAME)]
+            })
+            .collect()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        [
+            "/usr/sbin",
+            "/usr/bin",
+            "/usr/local/sbin",
+            "/usr/local/bin",
+            "/opt/homebrew/sbin",
+            "/opt/homebrew/bin",
+            "/opt/n2n/sbin",
+            "/opt/n2n/bin",
+        ]
+        .iter()
+        .map(|dir| Path::new(dir).join(EDGE_NAME))
+        .collect()
+    }
+}
+
+/// 按顺序列出要看的位置（同一个文件只留第一次出现的）
+fn candidate_paths(path_var: Option<&std::ffi::OsStr>, resource_dir: Option<&Path>) -> Vec<(PathBuf, EdgeSource)> {
+    let from_path = path_var
+        .map(|p| std::env::split_paths(p).map(|dir| dir.join(EDGE_NAME)).collect::<Vec<_>>())
+        .unwrap_or_default();
+    let bundled = resource_dir
+        .map(|dir| vec![dir.join(EDGE_NAME), dir.join("bin").join(EDGE_NAME)])
+        .unwrap_or_default();
+
+    let mut seen = HashSet::new();
+    from_path
+        .into_iter()
+        .map(|p| (p, EdgeSource::Path))
+        .chain(bundled.into_iter().map(|p| (p, EdgeSource::Bundled)))
+        .chain(common_locations().into_iter().map(|p| (p, EdgeSource::Common)))
+        .filter(|(p, _)| p.is_file())
+        .filter(|(p, _)| seen.insert(std::fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+        .collect()
+}
+
+/// 看文件头认出是给哪种 CPU 的（ELF / PE / Mach-O）
+fn binary_arch(header: &[u8]) -> Option<&'static str> {
+    let u16_at = |offset: usize, little: bool| {
+        let bytes: [u8; 2] = header.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    };
+    let u32_at = |offset: usize, little: bool| {
+        let bytes: [u8; 4] = header.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    };
+
+    if header.starts_with(b"\x7fELF") {
+        let little = header.get(5) == Some(&1);
+        return match u16_at(18, little)? {
+            0x03 => Some("x86"),
+            0x3e => Some("x86_64"),
+            0x28 => Some("arm"),
+            0xb7 => Some("aarch64"),
+            0x08 => Some("mips"),
+            0xf3 => Some("riscv"),
+            _ => None,
+        };
+    }
+    if header.starts_with(b"MZ") {
+        let pe = u32_at(0x3c, true)? as usize;
+        if header.get(pe..pe + 4)? != b"PE\0\0" {
+            return None;
+        }
+        return match u16_at(pe + 4, true)? {
+            0x014c => Some("x86"),
+            0x8664 => Some("x86_64"),
+            0x01c4 => Some("arm"),
+            0xaa64 => Some("aarch64"),
+            _ => None,
+        };
+    }
+    match u32_at(0, false)? {
+        0xcafe_babe => Some("universal"),
+        0xcffa_edfe => match u32_at(4, true)? {
+            0x0100_0007 => Some("x86_64"),
+            0x0100_000c => Some("aarch64"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 读文件头认 CPU（PE 的头可能稍微靠后，多读一点）
+fn file_arch(path: &Path) -> Option<String> {
+    let mut header = Vec::with_capacity(4096);
+    std::fs::File::open(path).ok()?.take(4096).read_to_end(&mut header).ok()?;
+    binary_arch(&header).map(str::to_string)
+}
+
+/// 把能找的地方都找一遍
+pub fn discover(resource_dir: Option<&Path>) -> Vec<EdgeCandidate> {
+    let path_var = std::env::var_os("PATH");
+    candidate_paths(path_var.as_deref(), resource_dir)
+        .into_iter()
+        .map(|(path, source)| {
+            let display = path.display().to_string();
+            EdgeCandidate {
+                version: n2n_process::detect_edge_version(&display),
+                arch: file_arch(&path),
+                path: display,
+                source,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_binary_arch_and_candidates() {
+        let mut elf = vec![0u8; 64];
+        elf[..4].copy_from_slice(b"\x7fELF");
+        elf[5] = 1;
+        elf[18] = 0xb7;
+        assert_eq!(binary_arch(&elf), Some("aarch64"));
+
+        let mut pe = vec![0u8; 0x90];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x80;
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        pe[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+        assert_eq!(binary_arch(&pe), Some("x86_64"));
+
+        assert_eq!(binary_arch(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2]), Some("universal"));
+        assert_eq!(binary_arch(b"#!/bin/sh\n"), None);
+        assert_eq!(binary_arch(b"MZ"), None);
+
+        // PATH 里和资源目录里是同一个文件时只留一个，不存在的不列
+        let dir = TempDir::new("edge-discovery");
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("bin").join(EDGE_NAME), b"").unwrap();
+        let path_var = std::env::join_paths([dir.join("bin"), dir.join("missing")]).unwrap();
+        let found = candidate_paths(Some(&path_var), Some(&dir));
+        let ours: Vec<_> = found.iter().filter(|(p, _)| p.starts_with(&dir)).collect();
+        assert_eq!(ours.len(), 1);
+        assert_eq!(ours[0].1, EdgeSource::Path);
+    }
+}
@@ -0,0 +1,299 @@
+//! 核对 edge 的指纹（防止工具箱被人悄悄掉包）
+//!
+//! edge 带着 cap_net_admin（甚至直接以 root 身份）在跑，谁把它换成别的程序，谁就拿到了这些权限。
+//! 所以恩兔每次放 edge 出门前都先算一遍它的 SHA-256：
+//! - 主人在 `edge_sha256` 里填了指纹白名单的话，只放行名单里的 edge；
+//! - 没填白名单就“第一次见面就记住”：每个 edge 路径的指纹记在配置目录的 `edge_fingerprints.toml`，
+//!   之后指纹变了就拒绝出门，并把新旧指纹都告诉主人；主人确认是自己升级的，用 `trust_edge_binary` 重新记下；
+//! - 主人自己编译、经常换 edge 的话，可以打开 `edge_integrity_bypass`：对不上时只记日志，照常出门
+//!   （这时也不会把对不上的指纹记下来，关掉 bypass 后照样拦得住）；
+//! - 指纹算不出来、指纹本读不了时宁可不出门（同样，打开 bypass 时只记日志）。
+//!
+//! 核对放在 `N2NProcess::start_inner` 里、授予 capabilities 和真正放 edge 出门之前，
+//! 自动重连、暂停后恢复、换总部、网卡坏了重新出门都要过这一关；之后都用核对过的那个绝对路径。
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 核对结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    /// 在白名单里
+    Listed,
+    /// 填了白名单，却不在名单里！
+    NotListed { actual: String },
+    /// 第一次见到这个 edge（该记下来了）
+    FirstSeen { actual: String },
+    /// 和记下的指纹一致
+    Unchanged,
+    /// 指纹变了！
+    Changed { previous: String, actual: String },
+}
+
+impl IntegrityCheck {
+    /// 对不上、该拦下来时的错误码
+    pub fn error_code(&self) -> Option<&'static str> {
+        match self {
+            IntegrityCheck::NotListed { .. } => Some("error_edge_not_allowlisted"),
+            IntegrityCheck::Changed { .. } => Some("error_edge_binary_changed"),
+            _ => None,
+        }
+    }
+}
+
+/// 拦下 edge 时递给界面的说明（`edge-binary-rejected` 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeBinaryAlert {
+    pub code: &'static str,
+    pub path: String,
+    /// 上次记下的指纹（不在白名单里时没有）
+    pub previous: Option<String>,
+    pub actual: String,
+}
+
+/// 拦下 edge 的原因（`alert` 只在指纹对不上时有，核对不了时没有）
+#[derive(Debug, Clone)]
+pub struct Rejected {
+    pub code: &'static str,
+    pub alert: Option<EdgeBinaryAlert>,
+}
+
+/// 出门前核对一遍 `edge_path`（见模块说明）：放行时返回核对过的那个文件（绝对路径），该拦下时返回原因
+/// - 授予 capabilities、出门前检查和真正放 edge 出门都要用返回的路径，
+///   不然核对的是 `PATH` 里找到的这个、跑起来的却可能是另一个；
+/// - 找不到 edge 就直接拦下（`error_edge_path_missing`）。
+pub fn guard(edge_path: &str, allowlist: &[String], bypass: bool) -> std::result::Result<PathBuf, Rejected> {
+    guard_with(FingerprintBook::new(), edge_path, allowlist, bypass)
+}
+
+/// `guard` 的本体（指纹本由调用方翻开，方便测试）
+fn guard_with(
+    book: Result<FingerprintBook>,
+    edge_path: &str,
+    allowlist: &[String],
+    bypass: bool,
+) -> std::result::Result<PathBuf, Rejected> {
+    let Some(path) = resolve(edge_path) else {
+        log::error!("找不到 edge（{}），已拒绝建立通道", edge_path);
+        return Err(Rejected {
+            code: "error_edge_path_missing",
+            alert: None,
+        });
+    };
+    let checked = book.and_then(|book| {
+        let actual = sha256_file(&path)?;
+        let recorded = book.get(&path)?;
+        Ok((book, actual, recorded))
+    });
+    let (book, actual, recorded) = match checked {
+        Ok(checked) => checked,
+        Err(e) if bypass => {
+            log::warn!("核对 edge（{}）的指纹失败：{:#}，主人吩咐过不拦，照常出门", path.display(), e);
+            return Ok(path);
+        }
+        Err(e) => {
+            log::error!("核对 edge（{}）的指纹失败：{:#}，已拒绝建立通道", path.display(), e);
+            return Err(Rejected {
+                code: "error_edge_integrity_unverifiable",
+                alert: None,
+            });
+        }
+    };
+
+    let check = verify(allowlist, recorded.as_deref(), &actual);
+    if let Some(code) = check.error_code() {
+        if bypass {
+            log::warn!("edge（{}）的指纹对不上（{:?}），主人吩咐过不拦，照常出门", path.display(), check);
+            return Ok(path);
+        }
+        log::error!("edge（{}）的指纹对不上（{:?}），已拒绝建立通道", path.display(), check);
+        let previous = match check {
+            IntegrityCheck::Changed { previous, .. } => Some(previous),
+            _ => None,
+        };
+        return Err(Rejected {
+            code,
+            alert: Some(EdgeBinaryAlert {
+                code,
+                path: path.to_string_lossy().to_string(),
+                previous,
+                actual,
+            }),
+        });
+    }
+    // 只记第一次见到的、和白名单对得上的（对不上又被放行的绝不记）
+    let trusted = matches!(check, IntegrityCheck::FirstSeen { .. } | IntegrityCheck::Listed);
+    if trusted && recorded.as_deref() != Some(actual.as_str()) {
+        log::info!("记下 edge（{}）的指纹：{}", path.display(), actual);
+        if let Err(e) = book.record(&path, &actual) {
+            log::warn!("{:#}", e);
+        }
+    }
+    Ok(path)
+}
+
+/// 拿白名单和记下的指纹对一对这次算出来的指纹
+pub fn verify(allowlist: &[String], recorded: Option<&str>, actual: &str) -> IntegrityCheck {
+    if !allowlist.is_empty() {
+        return if allowlist.iter().any(|hash| normalize(hash) == actual) {
+            IntegrityCheck::Listed
+        } else {
+            IntegrityCheck::NotListed { actual: actual.to_string() }
+        };
+    }
+    match recorded {
+        None => IntegrityCheck::FirstSeen { actual: actual.to_string() },
+        Some(previous) if previous == actual => IntegrityCheck::Unchanged,
+        Some(previous) => IntegrityCheck::Changed {
+            previous: previous.to_string(),
+            actual: actual.to_string(),
+        },
+    }
+}
+
+/// 白名单里的指纹统一成小写、去掉空白（也认 `sha256:` 前缀）
+fn normalize(hash: &str) -> String {
+    let hash = hash.trim().to_ascii_lowercase();
+    hash.strip_prefix("sha256:").map(str::to_string).unwrap_or(hash)
+}
+
+/// 白名单里的一条是不是像样的 SHA-256（64 位十六进制）
+pub fn is_sha256(hash: &str) -> bool {
+    let hash = normalize(hash);
+    hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// 找到 edge 真正的文件（相对路径、PATH 里的名字都换成绝对路径）；找不到就交给出门时报错
+pub fn resolve(edge_path: &str) -> Option<PathBuf> {
+    let path = Path::new(edge_path);
+    let found = if path.exists() { path.to_path_buf() } else { which::which(edge_path).ok()? };
+    fs::canonicalize(found).ok()
+}
+
+/// 算文件的 SHA-256（小写十六进制）
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("打开 {} 失败", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).with_context(|| format!("读取 {} 失败", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 指纹本：每个 edge 路径第一次见到时的指纹
+pub struct FingerprintBook {
+    path: PathBuf,
+}
+
+impl FingerprintBook {
+    /// 翻开指纹本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("edge_fingerprints.toml")))
+    }
+
+    /// 指定指纹本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => toml::from_str(&content).context("指纹本已经损坏"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e).context("读取指纹本失败"),
+        }
+    }
+
+    /// 这个 edge 记下的指纹
+    pub fn get(&self, edge: &Path) -> Result<Option<String>> {
+        Ok(self.load()?.remove(edge.to_string_lossy().as_ref()))
+    }
+
+    /// 记下（或改记）这个 edge 的指纹
+    pub fn record(&self, edge: &Path, sha256: &str) -> Result<()> {
+        let mut book = self.load()?;
+        book.insert(edge.to_string_lossy().to_string(), sha256.to_string());
+        let content = toml::to_string_pretty(&book).context("序列化指纹本失败")?;
+        fs::write(&self.path, content).context("写入指纹本失败")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_verify_and_record() {
+        let dir = TempDir::new("edge-integrity");
+        let edge = dir.join("edge");
+        fs::write(&edge, b"abc").unwrap();
+        // 指纹本按解析后的绝对路径记
+        let edge = fs::canonicalize(&edge).unwrap();
+        let edge_str = edge.to_str().unwrap();
+        let actual = sha256_file(&edge).unwrap();
+        assert_eq!(actual, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+        // 白名单优先：大小写、前缀都认
+        let listed = vec![format!("  SHA256:{}", actual.to_uppercase())];
+        assert_eq!(verify(&listed, Some("old"), &actual), IntegrityCheck::Listed);
+        assert!(is_sha256(&listed[0]));
+        assert!(!is_sha256("abc"));
+        let other = vec!["00".repeat(32)];
+        let check = verify(&other, None, &actual);
+        assert_eq!(check.error_code(), Some("error_edge_not_allowlisted"));
+
+        // 没有白名单：第一次见面就记住，之后对得上才放行
+        let book = FingerprintBook::at(dir.join("edge_fingerprints.toml"));
+        assert_eq!(book.get(&edge).unwrap(), None);
+        assert_eq!(verify(&[], None, &actual), IntegrityCheck::FirstSeen { actual: actual.clone() });
+        book.record(&edge, &actual).unwrap();
+        let recorded = book.get(&edge).unwrap();
+        assert_eq!(verify(&[], recorded.as_deref(), &actual), IntegrityCheck::Unchanged);
+
+        fs::write(&edge, b"not edge").unwrap();
+        let swapped = sha256_file(&edge).unwrap();
+        let check = verify(&[], recorded.as_deref(), &swapped);
+        assert_eq!(check, IntegrityCheck::Changed { previous: actual.clone(), actual: swapped.clone() });
+        assert_eq!(check.error_code(), Some("error_edge_binary_changed"));
+
+        // 出门前的那一关：掉包的拦下；bypass 放行但不记，关掉 bypass 后照样拦得住
+        let open = || Ok(FingerprintBook::at(dir.join("edge_fingerprints.toml")));
+        let missing = guard_with(open(), dir.join("missing").to_str().unwrap(), &[], true).unwrap_err();
+        assert_eq!(missing.code, "error_edge_path_missing");
+        let rejected = guard_with(open(), edge_str, &[], false).unwrap_err();
+        assert_eq!(rejected.code, "error_edge_binary_changed");
+        assert_eq!(rejected.alert.unwrap().previous.as_deref(), Some(actual.as_str()));
+        assert!(guard_with(open(), edge_str, &[], true).is_ok());
+        assert_eq!(book.get(&edge).unwrap().as_deref(), Some(actual.as_str()));
+        assert!(guard_with(open(), edge_str, &[], false).is_err());
+        // 白名单里的新指纹算数，记下来；放行时交回核对过的那个文件
+        let verified = guard_with(open(), edge_str, std::slice::from_ref(&swapped), false).unwrap();
+        assert_eq!(verified, edge);
+        assert_eq!(book.get(&edge).unwrap(), Some(swapped));
+
+        // 指纹本坏了：宁可不出门（bypass 时照常出门）
+        fs::write(dir.join("edge_fingerprints.toml"), "not = [toml").unwrap();
+        let rejected = guard_with(open(), edge_str, &[], false).unwrap_err();
+        assert_eq!(rejected.code, "error_edge_integrity_unverifiable");
+        assert!(rejected.alert.is_none());
+        assert!(guard_with(open(), edge_str, &[], true).is_ok());
+        assert!(guard_with(Err(anyhow::anyhow!("无法获取配置目录")), edge_str, &[], false).is_err());
+
+        assert_eq!(resolve(edge_str), Some(edge.clone()));
+        assert_eq!(resolve(dir.join("missing").to_str().unwrap()), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! 高级选项（一条一条交给 edge 的开关，取代以前按空格切开的 `extra_args`）
+//!
+//! 以前的“奇妙指令”是一整行字，按空格切开后交给 edge：值里带空格就被切坏，
+//! 拼错的开关也原样递过去，edge 只会在日志里嘀咕一句 unknown option。现在每条选项单独记下开关、值和是否启用：
+//! - 保存前对照 edge 认识的开关检查一遍（不认识、少了值、多了值、和基本设置重复都会指出来）；
+//! - 每个值作为一个独立的参数交给 edge，带空格也不会被切开；
+//! - 旧指示簿里的 `extra_args` 读进来时按 shell 的引号规则拆成选项，之后不再写回。
+use serde::{Deserialize, Serialize};
+
+/// 一条高级选项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeOption {
+    /// 开关名（例如 `-E`、`-n`、`-S1`）
+    pub flag: String,
+    /// 开关后面跟的值（不需要值的开关留空）
+    #[serde(default)]
+    pub value: Option<String>,
+    /// 是否交给 edge（先关掉试试，不用删掉重填）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl EdgeOption {
+    pub fn new(flag: &str, value: Option<&str>) -> Self {
+        Self {
+            flag: flag.to_string(),
+            value: value.map(str::to_string),
+            enabled: true,
+        }
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.value.as_deref().filter(|v| !v.is_empty())
+    }
+}
+
+/// edge 认识、并且允许主人在高级选项里使用的开关（`true` 表示后面要跟一个值）
+const KNOWN_FLAGS: &[(&str, bool)] = &[
+    ("-L", true),
+    ("-R", true),
+    ("-x", true),
+    ("-T", true),
+    ("-n", true),
+    ("-e", true),
+    ("-J", true),
+    ("-P", true),
+    ("-u", true),
+    ("-g", true),
+    ("-E", false),
+    ("-D", false),
+    ("-v", false),
+    ("-S1", false),
+    ("-S2", false),
+];
+
+/// 基本设置里已经管着的开关（写在高级选项里会和它们打架）
+const MANAGED_FLAGS: &[&str] = &[
+    "-c", "-l", "-k", "-a", "-d", "-m", "-p", "-M", "-I", "-f", "-H", "-A1", "-A2", "-A3", "-A4", "-A5", "-z1", "-z2", "-r",
+    "-t", "-i", "--management-password",
+];
+
+/// 开关是否需要值（不认识的开关返回 None）
+fn takes_value(flag: &str) -> Option<bool> {
+    KNOWN_FLAGS.iter().find(|(known, _)| *known == flag).map(|(_, value)| *value)
+}
+
+/// 检查高级选项，有问题时返回第一个问题的 `error_` 编号
+pub fn check(options: &[EdgeOption]) -> Option<&'static str> {
+    options.iter().filter(|o| o.enabled).find_map(|option| {
+        let flag = option.flag.trim();
+        if MANAGED_FLAGS.contains(&flag) {
+            return Some("error_edge_option_managed");
+        }
+        match takes_value(flag) {
+            None => Some("error_edge_option_unknown"),
+            Some(true) if option.value().is_none() => Some("error_edge_option_needs_value"),
+            Some(false) if option.value().is_some() => Some("error_edge_option_no_value"),
+            _ => None,
+        }
+    })
+}
+
+/// 按顺序展开成 edge 的参数（没启用的跳过，值原样作为一个参数）
+pub fn to_args(options: &[EdgeOption]) -> Vec<String> {
+    let mut args = Vec::new();
+    for option in options.iter().filter(|o| o.enabled && !o.flag.trim().is_empty()) {
+        args.push(option.flag.trim().to_string());
+        if let Some(value) = option.value() {
+            args.push(value.to_string());
+        }
+    }
+    args
+}
+
+/// 启用的选项里某个开关的值（例如 `-P` 的总部公钥）
+pub fn value_of<'a>(options: &'a [EdgeOption], flag: &str) -> Option<&'a str> {
+    options
+        .iter()
+        .filter(|o| o.enabled && o.flag.trim() == flag)
+        .find_map(EdgeOption::value)
+}
+
+/// 把旧版的一整行 `extra_args` 拆成选项（认得单双引号和反斜杠；`-Pkey` 这种粘在一起的写法也会拆开）
+pub fn from_legacy(extra_args: &str) -> Vec<EdgeOption> {
+    let mut options: Vec<EdgeOption> = Vec::new();
+    let mut tokens = split_words(extra_args).into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let (flag, glued) = match takes_value(&token) {
+            Some(_) => (token.clone(), None),
+            None => match token.get(..2).filter(|head| takes_value(head) == Some(true)) {
+                Some(head) if token.starts_with('-') && !token.starts_with("--") => {
+                    (head.to_string(), Some(token[2..].to_string()))
+                }
+                _ => (token.clone(), None),
+            },
+        };
+        let wants_value = match takes_value(&flag) {
+            Some(value) => value,
+            // 不认识的开关：后面紧跟着的不像开关，就当它的值
+            None => tokens.peek().is_some_and(|next| !next.starts_with('-')),
+        };
+        let value = glued.or_else(|| if wants_value { tokens.next() } else { None });
+        options.push(EdgeOption {
+            flag,
+            value,
+            enabled: true,
+        });
+    }
+    options
+}
+
+/// 按 shell 的规矩切词（单引号里原样保留，双引号里认反斜杠转义）
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                current.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => current.extend(chars.next()),
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                current.extend(chars.next());
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_args_become_checked_options() {
+        let options = from_legacy(r#"-v -E -Pgood-key -J "my pw" -n '192.168.2.0/24:10.0.0.2'"#);
+        assert_eq!(
+            options,
+            vec![
+                EdgeOption::new("-v", None),
+                EdgeOption::new("-E", None),
+                EdgeOption::new("-P", Some("good-key")),
+                EdgeOption::new("-J", Some("my pw")),
+                EdgeOption::new("-n", Some("192.168.2.0/24:10.0.0.2")),
+            ]
+        );
+        assert_eq!(check(&options), None);
+        assert_eq!(value_of(&options, "-P"), Some("good-key"));
+        assert_eq!(
+            to_args(&options),
+            vec!["-v", "-E", "-P", "good-key", "-J", "my pw", "-n", "192.168.2.0/24:10.0.0.2"]
+        );
+
+        let mut disabled = EdgeOption::new("-E", None);
+        disabled.enabled = false;
+        assert!(to_args(&[disabled]).is_empty());
+
+        assert_eq!(check(&[EdgeOption::new("-Q", None)]), Some("error_edge_option_unknown"));
+        assert_eq!(check(&[EdgeOption::new("-n", None)]), Some("error_edge_option_needs_value"));
+        assert_eq!(check(&[EdgeOption::new("-E", Some("x"))]), Some("error_edge_option_no_value"));
+        assert_eq!(check(&[EdgeOption::new("-c", Some("x"))]), Some("error_edge_option_managed"));
+        assert_eq!(check(&[EdgeOption::new("-t", Some("5645"))]), Some("error_edge_option_managed"));
+        assert_eq!(from_legacy("--weird thing")[0].value.as_deref(), Some("thing"));
+    }
+}
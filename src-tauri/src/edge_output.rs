@@ -0,0 +1,171 @@
+//! 工作录音（每次工作把 edge 的原始输出单独存成一个文件）
+//!
+//! 工作汇报面板和应用日志都是滚动的，断线几个小时后再去查，当时的输出早就被挤掉了。
+//! 现在每次工作（从建立通道到彻底结束，暂停和重连都算同一次）都会把 edge 的 stdout/stderr 原样录到
+//! 配置目录下的 `sessions/edge-<开始时间>.log`，工作日志本里记着文件路径，想复盘哪次断线就打开哪一份：
+//! - 每行前面标上 `[OUT]` / `[ERR]`，每轮 edge 启动时先写一行分隔；
+//! - 单个文件最多 10 MiB，写满后留一句说明就不再往里写；
+//! - 最多留 30 份、总共不超过 100 MiB，开新的一份前把最旧的撕掉。
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 单个录音文件的上限（字节）
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// 最多留几份录音
+const MAX_FILES: usize = 30;
+/// 全部录音加起来的上限（字节）
+const MAX_TOTAL_BYTES: u64 = 100 * 1024 * 1024;
+
+/// 一次工作的录音（stdout、stderr 两根管子共用同一份）
+#[derive(Debug, Clone)]
+pub struct SessionOutput {
+    path: PathBuf,
+    recorder: Arc<Mutex<Recorder>>,
+}
+
+#[derive(Debug)]
+struct Recorder {
+    file: File,
+    written: u64,
+    full: bool,
+}
+
+/// 录音放在哪个目录
+fn sessions_dir() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .context("无法获取配置目录")?
+        .join("n2n-maid")
+        .join("sessions"))
+}
+
+impl SessionOutput {
+    /// 开一份新录音（失败时只记一笔，不耽误建立通道）
+    pub fn create(started_at: u64) -> Option<Self> {
+        match sessions_dir().and_then(|dir| Self::create_in(&dir, started_at)) {
+            Ok(output) => Some(output),
+            Err(e) => {
+                log::warn!("开不了工作录音：{:#}", e);
+                None
+            }
+        }
+    }
+
+    /// 在指定目录开一份新录音
+    pub fn create_in(dir: &Path, started_at: u64) -> Result<Self> {
+        fs::create_dir_all(dir).context("创建录音目录失败")?;
+        prune(dir, MAX_FILES - 1, MAX_TOTAL_BYTES);
+        let path = dir.join(format!("edge-{}.log", started_at));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("打开录音文件 {} 失败", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            recorder: Arc::new(Mutex::new(Recorder {
+                file,
+                written,
+                full: false,
+            })),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 新一轮 edge 开工（暂停后继续、重连时都会再写一行）
+    pub fn begin_run(&self, at: u64, edge_path: &str) {
+        self.write(&format!("===== {} edge 开工：{}", at, edge_path));
+    }
+
+    /// 录下一行输出（`tag` 是 OUT / ERR）
+    pub fn record(&self, tag: &str, line: &str) {
+        self.write(&format!("[{}] {}", tag, line));
+    }
+
+    fn write(&self, line: &str) {
+        let mut recorder = self.recorder.lock().unwrap();
+        if recorder.full {
+            return;
+        }
+        let len = line.len() as u64 + 1;
+        let result = if recorder.written + len > MAX_FILE_BYTES {
+            recorder.full = true;
+            writeln!(recorder.file, "===== 录音写满 {} 字节了，之后的输出不再记录", MAX_FILE_BYTES)
+        } else {
+            recorder.written += len;
+            writeln!(recorder.file, "{}", line)
+        };
+        if let Err(e) = result {
+            log::debug!("写工作录音失败：{}", e);
+            recorder.full = true;
+        }
+    }
+}
+
+/// 撕掉最旧的录音，直到不超过 `keep_files` 份、`keep_bytes` 字节
+fn prune(dir: &Path, keep_files: usize, keep_bytes: u64) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(u64, PathBuf, u64)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let started_at = name.strip_prefix("edge-")?.strip_suffix(".log")?.parse().ok()?;
+            let size = entry.metadata().ok()?.len();
+            Some((started_at, entry.path(), size))
+        })
+        .collect();
+    files.sort_by_key(|(started_at, _, _)| *started_at);
+
+    let mut total: u64 = files.iter().map(|(_, _, size)| size).sum();
+    let mut count = files.len();
+    for (_, path, size) in files {
+        if count <= keep_files && total <= keep_bytes {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => log::debug!("撕掉了旧的工作录音 {}", path.display()),
+            Err(e) => log::debug!("删除旧录音 {} 失败：{}", path.display(), e),
+        }
+        count -= 1;
+        total = total.saturating_sub(size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_session_output_records_and_prunes() {
+        let dir = TempDir::new("sessions");
+
+        let output = SessionOutput::create_in(&dir, 100).unwrap();
+        output.begin_run(100, "/usr/sbin/edge");
+        output.clone().record("OUT", "edge <<< ================ >>> edge");
+        output.record("ERR", "WARNING: something");
+        let content = fs::read_to_string(output.path()).unwrap();
+        assert!(content.contains("100 edge 开工：/usr/sbin/edge"));
+        assert!(content.contains("[OUT] edge <<<"));
+        assert!(content.contains("[ERR] WARNING: something"));
+
+        for started_at in [200, 300, 400] {
+            fs::write(dir.join(format!("edge-{}.log", started_at)), "x").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "keep me").unwrap();
+        prune(&dir, 2, u64::MAX);
+        let mut left: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["edge-300.log", "edge-400.log", "notes.txt"]);
+    }
+}
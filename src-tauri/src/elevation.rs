@@ -0,0 +1,169 @@
+//! 权限体检（恩兔现在是不是管理员、到底需不需要当管理员）
+//!
+//! 目前 Windows 包带着 `requireAdministrator` 清单，每次启动都要弹 UAC。
+//! 想安全地去掉它，得先弄清楚“当前网卡后端到底离不离得开管理员权限”：
+//! - TAP-Windows：edge 要用 netsh 给网卡配地址，离不开管理员；
+//! - Wintun：创建适配器本身就要管理员；
+//! - Linux：root 或者 edge 二进制带齐 capabilities 都可以。
+//!
+//! 这里只负责体检和给建议（建议用 i18n 编号表示），不会替主人改任何东西。
+use serde::Serialize;
+
+use crate::config::N2NConfig;
+use crate::n2n_process::N2NProcess;
+
+/// 网卡后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetBackend {
+    /// Windows TAP-Windows（tap0901）
+    Tap,
+    /// Windows Wintun（edge 旁边放着 wintun.dll）
+    Wintun,
+    /// Linux：edge 二进制带 capabilities
+    LinuxCapabilities,
+    /// 整个程序以 root 身份运行
+    Root,
+    /// 认不出来（比如 Windows 上两种驱动都没装）
+    Unknown,
+}
+
+/// 权限体检报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevationReport {
+    /// 当前进程是否以管理员/root 身份运行
+    pub elevated: bool,
+    /// 按当前后端，建立通道是否需要管理员/root
+    pub required: bool,
+    /// 识别到的网卡后端
+    pub backend: NetBackend,
+    /// 给主人的建议（i18n 编号）
+    pub advice: Vec<String>,
+}
+
+/// 对照当前配置做一次权限体检
+pub fn check(config: &N2NConfig) -> ElevationReport {
+    let edge_path = config
+        .edge_path
+        .clone()
+        .unwrap_or_else(N2NProcess::default_edge_path);
+    let elevated = is_elevated();
+    let (backend, required) = detect_backend(&edge_path, elevated);
+    ElevationReport {
+        elevated,
+        required,
+        backend,
+        advice: advise(elevated, required, backend),
+    }
+}
+
+/// 根据体检结果给出建议
+fn advise(elevated: bool, required: bool, backend: NetBackend) -> Vec<String> {
+    let mut advice = Vec::new();
+    match (elevated, required) {
+        (false, true) => advice.push(
+            match backend {
+                NetBackend::LinuxCapabilities => "elevation_caps_missing",
+                _ => "elevation_missing",
+            }
+            .to_string(),
+        ),
+        (true, false) => advice.push("elevation_can_drop".to_string()),
+        (true, true) => advice.push("elevation_required_by_backend".to_string()),
+        (false, false) => {}
+    }
+    if backend == NetBackend::Unknown {
+        advice.push("elevation_backend_unknown".to_string());
+    }
+    advice
+}
+
+#[cfg(target_os = "windows")]
+fn detect_backend(edge_path: &str, _elevated: bool) -> (NetBackend, bool) {
+    let edge_dir = std::path::Path::new(edge_path)
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_default();
+    if edge_dir.join("wintun.dll").exists() {
+        return (NetBackend::Wintun, true);
+    }
+    if crate::windows_ready::is_tap_windows_installed().unwrap_or(false) {
+        return (NetBackend::Tap, true);
+    }
+    // 两种驱动都没装：不管怎样都连不上，先按需要管理员处理
+    (NetBackend::Unknown, true)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_backend(edge_path: &str, elevated: bool) -> (NetBackend, bool) {
+    let has_caps = crate::n2n_process::resolve_edge_path_for_caps(edge_path)
+        .map(|p| crate::n2n_process::edge_has_capabilities(&p))
+        .unwrap_or(false);
+    if has_caps {
+        return (NetBackend::LinuxCapabilities, false);
+    }
+    if elevated {
+        return (NetBackend::Root, true);
+    }
+    // 没有 capabilities：连接时会通过 pkexec 申请
+    (NetBackend::LinuxCapabilities, true)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn detect_backend(_edge_path: &str, elevated: bool) -> (NetBackend, bool) {
+    (if elevated { NetBackend::Root } else { NetBackend::Unknown }, true)
+}
+
+/// 当前进程是否以管理员身份运行（看进程令牌的 TokenElevation）
+#[cfg(target_os = "windows")]
+fn is_elevated() -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut len = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut TOKEN_ELEVATION as *mut _,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        );
+        CloseHandle(token);
+        ok != 0 && elevation.TokenIsElevated != 0
+    }
+}
+
+/// 当前进程是否以 root 身份运行
+#[cfg(unix)]
+fn is_elevated() -> bool {
+    nix::unistd::Uid::effective().is_root()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advise_matrix() {
+        assert_eq!(advise(false, true, NetBackend::Tap), vec!["elevation_missing"]);
+        assert_eq!(
+            advise(false, true, NetBackend::LinuxCapabilities),
+            vec!["elevation_caps_missing"]
+        );
+        assert_eq!(advise(true, false, NetBackend::LinuxCapabilities), vec!["elevation_can_drop"]);
+        assert_eq!(advise(true, true, NetBackend::Wintun), vec!["elevation_required_by_backend"]);
+        assert!(advise(false, false, NetBackend::LinuxCapabilities).is_empty());
+        assert_eq!(
+            advise(true, true, NetBackend::Unknown),
+            vec!["elevation_required_by_backend", "elevation_backend_unknown"]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! 恩兔的“小喇叭”（后端主动通知前端的事件）
+//!
+//! `N2NProcess` 本身不认识 Tauri（方便单独测试），所以它只把事件塞进一条 mpsc 通道；
+//! `main.rs` 里的转发小工人再把事件按名字 `emit` 给前端。
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::edge_integrity::EdgeBinaryAlert;
+
+/// 事件发送端（交给 N2NProcess 等后台模块使用）
+pub type EventSender = mpsc::UnboundedSender<ProcessEvent>;
+/// 事件接收端（由 main.rs 的转发小工人持有）
+pub type EventReceiver = mpsc::UnboundedReceiver<ProcessEvent>;
+
+/// edge 吐出的一条“不致命但值得主人知道”的提醒
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeWarning {
+    /// 已识别的提醒编号（例如 warning_community_truncated），未识别时为 None
+    pub code: Option<String>,
+    /// edge 原始的提醒内容
+    pub message: String,
+    /// 记录时间（Unix 秒）
+    pub at: u64,
+}
+
+/// 一次工作（从建立通道到彻底结束）的总结，暂停/恢复不算结束
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    /// 开始时间（Unix 秒）
+    pub started_at: u64,
+    /// 结束时间（Unix 秒）
+    pub ended_at: u64,
+    /// 持续时间（秒）
+    pub duration_secs: u64,
+    pub supernode: String,
+    pub community: String,
+    /// 数据包收发计数（来自管理口 packetstats）
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+    /// 网卡收发字节数（平台支持时才有）
+    pub tx_bytes: Option<u64>,
+    pub rx_bytes: Option<u64>,
+    /// 本次工作中重新连接的次数
+    pub reconnect_count: u32,
+    /// 本次工作中暂停的次数
+    pub pause_count: u32,
+    /// 结束原因：user_stop / force_stop / edge_exited
+    pub reason: String,
+    /// 意外结束时的错误线索（error_ 编号或原始片段）
+    pub error: Option<String>,
+    /// 最近一次 edge 意外摔倒的原因码（见 exit_reason；开着自动重连时摔倒后又爬起来的也记着，旧记录里没有）
+    #[serde(default)]
+    pub exit_reason: Option<String>,
+    /// 本次工作的 edge 原始输出录音（旧记录里没有）
+    #[serde(default)]
+    pub output_file: Option<String>,
+}
+
+/// 恩兔想对主人说的话的轻重
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// 恩兔对主人说的一句话（连上了、歇一会儿、重连中、小提示……）
+///
+/// 和原始日志分开走：日志面板只放 edge 的原话，这些话由前端按 `id` 翻译后画成聊天气泡。
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssistantMessage {
+    /// 话术编号（前端 i18n 的 key，例如 maid_connected）
+    pub id: String,
+    pub severity: Severity,
+    /// 话术里的占位参数（例如 {"ip": "10.0.0.2"}）
+    pub params: serde_json::Map<String, serde_json::Value>,
+    /// 说这句话的时间（Unix 秒）
+    pub at: u64,
+}
+
+impl AssistantMessage {
+    pub fn new(id: &str, severity: Severity) -> Self {
+        Self {
+            id: id.to_string(),
+            severity,
+            params: serde_json::Map::new(),
+            at: crate::n2n_process::unix_now_seconds(),
+        }
+    }
+
+    /// 补一个占位参数
+    pub fn with(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.params.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+/// 后端要广播的事件
+#[derive(Debug, Clone)]
+pub enum ProcessEvent {
+    /// edge 的非致命提醒（WARNING 行）
+    Warning(EdgeWarning),
+    /// 本次工作结束的总结
+    SessionEnded(SessionSummary),
+    /// 恩兔对主人说的话
+    Assistant(AssistantMessage),
+    /// edge 的指纹对不上，拦下没放出门（见 edge_integrity.rs）
+    EdgeBinaryRejected(EdgeBinaryAlert),
+}
+
+impl ProcessEvent {
+    /// 前端 `listen()` 用的事件名
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcessEvent::Warning(_) => "warning",
+            ProcessEvent::SessionEnded(_) => "session-ended",
+            ProcessEvent::Assistant(_) => "assistant-message",
+            ProcessEvent::EdgeBinaryRejected(_) => "edge-binary-rejected",
+        }
+    }
+
+    /// 事件内容（JSON）
+    pub fn payload(&self) -> serde_json::Value {
+        match self {
+            ProcessEvent::Warning(w) => serde_json::to_value(w).unwrap_or(serde_json::Value::Null),
+            ProcessEvent::SessionEnded(s) => serde_json::to_value(s).unwrap_or(serde_json::Value::Null),
+            ProcessEvent::Assistant(m) => serde_json::to_value(m).unwrap_or(serde_json::Value::Null),
+            ProcessEvent::EdgeBinaryRejected(a) => serde_json::to_value(a).unwrap_or(serde_json::Value::Null),
+        }
+    }
+}
+
+/// 让恩兔说一句话（没有接上小喇叭时就不说）
+pub fn say(tx: Option<&EventSender>, message: AssistantMessage) {
+    if let Some(tx) = tx {
+        let _ = tx.send(ProcessEvent::Assistant(message));
+    }
+}
+
+/// 新建一条事件通道
+pub fn channel() -> (EventSender, EventReceiver) {
+    mpsc::unbounded_channel()
+}
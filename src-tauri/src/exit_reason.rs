@@ -0,0 +1,181 @@
+//! edge 摔倒的原因（给 `ConnectionStatus::Error` 配一个稳定的、程序也看得懂的原因码）
+//!
+//! 以前 edge 意外退出只留下一句“进程意外退出”，主人得自己去翻原始日志。现在看护员收尾时
+//! 把退出状态和最后几行输出对一对，归到下面这张表里（原因码只增不改，界面、遥控口都可以放心按它分支）：
+//! - 被信号放倒的：SIGKILL/SIGTERM/SIGINT/SIGHUP 是 `exit_killed`（内存不够被系统收走、被别的程序清理），
+//!   SIGSEGV 之类（Windows 上是 0xC0000005 这类异常码）是 `exit_crashed`；
+//! - 输出里有线索的：参数不认识是 `exit_bad_args`、虚拟网卡打不开是 `exit_tap_failed`、
+//!   权限不够是 `exit_permission_denied`、认证没过是 `exit_auth_failed`（越靠后的输出越算数）；
+//! - 都没有线索时只看退出码：0 是 `exit_clean`，其余是 `exit_failed`；
+//!   连退出码都不知道（被别人先收走了、接手来的 edge）是 `exit_unknown`。
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+
+/// 最多记住 edge 最后几行输出
+const TAIL_LINES: usize = 20;
+
+/// edge 退出的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// 参数不认识、缺值（多半是高级选项写错了，或者 edge 版本太老）
+    BadArgs,
+    /// 虚拟网卡打不开（被占用、驱动没装、没有 /dev/net/tun）
+    TapFailed,
+    /// 权限不够
+    PermissionDenied,
+    /// 认证没过（密语、用户名口令不对）
+    AuthFailed,
+    /// 被信号放倒了
+    Killed,
+    /// 自己摔坏了（段错误之类）
+    Crashed,
+    /// 退出码是 0，却不是主人让它走的
+    Clean,
+    /// 退出码不是 0，也没留下线索
+    Failed,
+    /// 连退出码都不知道
+    Unknown,
+}
+
+impl ExitReason {
+    /// 稳定的原因码
+    pub fn code(self) -> &'static str {
+        match self {
+            ExitReason::BadArgs => "exit_bad_args",
+            ExitReason::TapFailed => "exit_tap_failed",
+            ExitReason::PermissionDenied => "exit_permission_denied",
+            ExitReason::AuthFailed => "exit_auth_failed",
+            ExitReason::Killed => "exit_killed",
+            ExitReason::Crashed => "exit_crashed",
+            ExitReason::Clean => "exit_clean",
+            ExitReason::Failed => "exit_failed",
+            ExitReason::Unknown => "exit_unknown",
+        }
+    }
+}
+
+/// edge 最后几行输出（stdout、stderr 混在一起，按先后排）
+#[derive(Debug, Default)]
+pub struct Tail(VecDeque<String>);
+
+impl Tail {
+    /// 记下一行（太多了就挤掉最早的）
+    pub fn push(&mut self, line: &str) {
+        if self.0.len() == TAIL_LINES {
+            self.0.pop_front();
+        }
+        self.0.push_back(line.to_string());
+    }
+
+    /// 新的一班开工：忘掉上一班的
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// 对照退出状态和记下的输出找原因
+    pub fn classify(&self, status: Option<ExitStatus>) -> ExitReason {
+        classify(status, self.0.iter().map(String::as_str))
+    }
+}
+
+/// 对照退出状态和最后几行输出（按先后排）找原因
+pub fn classify<'a>(status: Option<ExitStatus>, tail: impl DoubleEndedIterator<Item = &'a str>) -> ExitReason {
+    if let Some(reason) = status.and_then(by_signal) {
+        return reason;
+    }
+    if let Some(reason) = tail.rev().find_map(by_line) {
+        return reason;
+    }
+    match status {
+        Some(status) if status.success() => ExitReason::Clean,
+        Some(_) => ExitReason::Failed,
+        None => ExitReason::Unknown,
+    }
+}
+
+/// 被信号放倒、或者（Windows 上）带着异常码退出的
+#[cfg(unix)]
+fn by_signal(status: ExitStatus) -> Option<ExitReason> {
+    use nix::sys::signal::Signal;
+    use std::os::unix::process::ExitStatusExt;
+
+    let signal = Signal::try_from(status.signal()?).ok()?;
+    Some(match signal {
+        Signal::SIGKILL | Signal::SIGTERM | Signal::SIGINT | Signal::SIGHUP => ExitReason::Killed,
+        _ => ExitReason::Crashed,
+    })
+}
+
+#[cfg(windows)]
+fn by_signal(status: ExitStatus) -> Option<ExitReason> {
+    // NTSTATUS 的错误码（最高两位都是 1），比如 0xC0000005 访问冲突
+    let code = status.code()? as u32;
+    (code & 0xC000_0000 == 0xC000_0000).then_some(ExitReason::Crashed)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn by_signal(_status: ExitStatus) -> Option<ExitReason> {
+    None
+}
+
+/// 一行输出里有没有线索
+fn by_line(line: &str) -> Option<ExitReason> {
+    let l = line.to_ascii_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| l.contains(w));
+
+    if has(&["unrecognized option", "invalid option", "unknown option", "option requires an argument", "usage: edge"]) {
+        return Some(ExitReason::BadArgs);
+    }
+    if has(&["tunsetiff", "/dev/net/tun", "tuntap", "tap device", "tap adapter", "tap-windows", "tap_open"])
+        && has(&["error", "fail", "cannot", "unable", "busy", "not found", "no such"])
+    {
+        return Some(ExitReason::TapFailed);
+    }
+    if has(&["operation not permitted", "permission denied", "eperm"]) {
+        return Some(ExitReason::PermissionDenied);
+    }
+    if has(&["authentication error", "auth failed", "authentication failed"]) {
+        return Some(ExitReason::AuthFailed);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let exit = |code: i32| Some(ExitStatus::from_raw(code << 8));
+        let signal = |sig: i32| Some(ExitStatus::from_raw(sig));
+        let mut tail = Tail::default();
+
+        assert_eq!(tail.classify(None), ExitReason::Unknown);
+        assert_eq!(tail.classify(exit(0)), ExitReason::Clean);
+        assert_eq!(tail.classify(exit(1)), ExitReason::Failed);
+        assert_eq!(tail.classify(signal(9)).code(), "exit_killed");
+        assert_eq!(tail.classify(signal(11)), ExitReason::Crashed);
+
+        tail.push("03/Jul/2025 10:00:00 [edge.c:1234] ERROR: ioctl() [Device or resource busy][-1]: TUNSETIFF");
+        tail.push("03/Jul/2025 10:00:00 [edge.c:1240] ERROR: failed in tuntap_open");
+        assert_eq!(tail.classify(exit(1)), ExitReason::TapFailed);
+        // 信号比输出更说明问题
+        assert_eq!(tail.classify(signal(9)), ExitReason::Killed);
+        // 越靠后的输出越算数
+        tail.push("edge: unrecognized option '--frobnicate'");
+        assert_eq!(tail.classify(exit(1)).code(), "exit_bad_args");
+
+        for i in 0..TAIL_LINES {
+            tail.push(&format!("line {}", i));
+        }
+        assert_eq!(tail.classify(exit(2)), ExitReason::Failed);
+        tail.push("ERROR: tuntap ioctl(TUNSETIFF, IFF_TAP) error: Operation not permitted");
+        assert_eq!(tail.classify(None), ExitReason::TapFailed);
+        tail.clear();
+        tail.push("[edge_utils.c:2345] WARNING: Authentication error. MAC or IP address already in use");
+        assert_eq!(tail.classify(exit(1)), ExitReason::AuthFailed);
+    }
+}
@@ -0,0 +1,107 @@
+//! 备用总部轮换（正在用的总部摸不到时，换下一家试试）
+//!
+//! 以前总部挂了，主人只能自己把备用地址一个个填进去、一个个点连接。现在方案里可以写一串 `supernode_failover`：
+//! - 这一班的名单是“主总部 + 备用总部”，按顺序排好、去掉重复的；
+//! - 巡查小工人发现 edge 一直摸不到当前总部（`error_supernode_unreachable`），就换名单里的下一家重新出门；
+//! - 换过去时 edge 干脆出不了门，也接着试下一家；一家都出不了门，这次工作就按出错收尾（不会卡在暂停上）；
+//! - 换过去之后连上了，就把这一家记进本次工作的账本（历史记录里能看到最后是哪家总部接待的）；
+//! - 名单走到头了就不再换，只提醒主人一次，错误照常摆出来；主人重新建立通道时名单从头来过。
+
+/// 巡查这一轮该怎么做
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// 换到下一家
+    Next(String),
+    /// 名单里的都试过了
+    Exhausted,
+}
+
+/// 这一班的总部名单，和换到了哪一家
+#[derive(Debug, Clone, Default)]
+pub struct Failover {
+    candidates: Vec<String>,
+    current: usize,
+    /// 换过去了，还没确认连上
+    pending: bool,
+    /// 走到头的提醒已经说过了
+    exhausted: bool,
+}
+
+impl Failover {
+    /// 按主总部和备用总部排好名单（去掉空的、重复的）
+    pub fn new(primary: &str, backups: &[String]) -> Self {
+        let mut candidates: Vec<String> = Vec::new();
+        for address in std::iter::once(primary).chain(backups.iter().map(String::as_str)) {
+            let address = address.trim();
+            if !address.is_empty() && !candidates.iter().any(|c| c == address) {
+                candidates.push(address.to_string());
+            }
+        }
+        Self {
+            candidates,
+            ..Default::default()
+        }
+    }
+
+    /// 有没有备用的可以换（只有一家就不用轮换）
+    pub fn is_enabled(&self) -> bool {
+        self.candidates.len() > 1
+    }
+
+    /// 当前这家摸不到了：换下一家；走到头时提醒一次，之后就不再动了
+    pub fn advance(&mut self) -> Option<Step> {
+        if self.current + 1 < self.candidates.len() {
+            self.current += 1;
+            self.pending = true;
+            return Some(Step::Next(self.candidates[self.current].clone()));
+        }
+        self.pending = false;
+        if !self.is_enabled() || self.exhausted {
+            return None;
+        }
+        self.exhausted = true;
+        Some(Step::Exhausted)
+    }
+
+    /// 连上了：换过去的这一家算接待成功（每换一次只报一次）
+    pub fn settle(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.pending) {
+            return None;
+        }
+        Some(self.candidates[self.current].clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failover_walks_the_list_once() {
+        let backups = vec![
+            " b.example.com:7777 ".to_string(),
+            "a.example.com:7777".to_string(),
+            String::new(),
+            "c.example.com:7777".to_string(),
+        ];
+        let mut failover = Failover::new("a.example.com:7777", &backups);
+        assert!(failover.is_enabled());
+        // 还没换过：连上了也不用报
+        assert_eq!(failover.settle(), None);
+
+        assert_eq!(failover.advance(), Some(Step::Next("b.example.com:7777".to_string())));
+        assert_eq!(failover.settle(), Some("b.example.com:7777".to_string()));
+        assert_eq!(failover.settle(), None);
+
+        assert_eq!(failover.advance(), Some(Step::Next("c.example.com:7777".to_string())));
+        assert_eq!(failover.advance(), Some(Step::Exhausted));
+        assert_eq!(failover.settle(), None);
+        assert_eq!(failover.advance(), None);
+
+        // 没有备用总部：什么都不做
+        let mut single = Failover::new("a.example.com:7777", &["a.example.com:7777".to_string()]);
+        assert!(!single.is_enabled());
+        assert_eq!(single.advance(), None);
+        assert!(!Failover::default().is_enabled());
+    }
+}
@@ -0,0 +1,130 @@
+//! 恩兔的工作日志本（历次会话的总结）
+//!
+//! 每次工作彻底结束时，`session-ended` 事件里的 `SessionSummary` 会按行追加到
+//! `history.jsonl`（一行一条 JSON），方便以后翻看“哪天连了多久、为什么断开”。
+//! 本子只保留最近的 `MAX_HISTORY_ENTRIES` 条，写满了就撕掉最旧的几页。
+//!
+//! 同一种本子也用来记别的流水账（比如定时唤醒同伴的结果记在 `wake-history.jsonl`）。
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+
+use crate::events::SessionSummary;
+
+/// 最多保留的会话总结条数
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// 工作日志本（默认记会话总结）
+pub struct HistoryStore<T = SessionSummary> {
+    path: PathBuf,
+    entries: PhantomData<T>,
+}
+
+impl HistoryStore<SessionSummary> {
+    /// 翻开工作日志本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        Self::named("history.jsonl")
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Clone> HistoryStore<T> {
+    /// 翻开配置目录里指定名字的流水账
+    pub fn named(file_name: &str) -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join(file_name)))
+    }
+
+    /// 指定日志本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            entries: PhantomData,
+        }
+    }
+
+    /// 记下一条
+    pub fn append(&self, summary: &T) -> Result<()> {
+        let mut entries = self.load_all();
+        entries.push(summary.clone());
+        if entries.len() > MAX_HISTORY_ENTRIES {
+            let excess = entries.len() - MAX_HISTORY_ENTRIES;
+            entries.drain(..excess);
+        }
+
+        let mut content = String::new();
+        for entry in &entries {
+            content.push_str(&serde_json::to_string(entry).context("序列化日志条目失败")?);
+            content.push('\n');
+        }
+        fs::write(&self.path, content).context("写入工作日志失败")?;
+        Ok(())
+    }
+
+    /// 翻看最近的几次工作（新的在前）
+    pub fn recent(&self, limit: usize) -> Vec<T> {
+        let mut entries = self.load_all();
+        entries.reverse();
+        entries.truncate(limit);
+        entries
+    }
+
+    /// 读出整本日志（坏掉的行直接跳过，不让一页污渍毁掉整本）
+    fn load_all(&self) -> Vec<T> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return Vec::new();
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn summary(started_at: u64) -> SessionSummary {
+        SessionSummary {
+            started_at,
+            ended_at: started_at + 10,
+            duration_secs: 10,
+            supernode: "sn.example.com:7777".to_string(),
+            community: "home".to_string(),
+            tx_packets: 0,
+            rx_packets: 0,
+            tx_bytes: None,
+            rx_bytes: None,
+            reconnect_count: 0,
+            pause_count: 0,
+            reason: "user_stop".to_string(),
+            error: None,
+            exit_reason: None,
+            output_file: None,
+        }
+    }
+
+    #[test]
+    fn test_history_append_and_recent() {
+        let dir = TempDir::new("history");
+        let path = dir.join("history.jsonl");
+        let store: HistoryStore = HistoryStore::at(path.clone());
+
+        store.append(&summary(1)).unwrap();
+        store.append(&summary(2)).unwrap();
+        fs::write(&path, format!("{}not json\n", fs::read_to_string(&path).unwrap())).unwrap();
+        store.append(&summary(3)).unwrap();
+
+        let recent = store.recent(2);
+        assert_eq!(recent.iter().map(|s| s.started_at).collect::<Vec<_>>(), vec![3, 2]);
+        assert_eq!(store.recent(10).len(), 3);
+    }
+}
@@ -0,0 +1,152 @@
+//! 中转节点（让这台机器替同伴转发流量，当小组的网关）
+//!
+//! 以前要把一台机器变成网关得分好几步：在高级选项里加 `-r`，再去系统里打开 IP 转发
+//! （Linux 的 sysctl、Windows 的 netsh），断开以后还得记得改回去。现在打开 `packet_forwarding` 后：
+//! - edge 带上 `-r`（收下不是发给自己的包，交给系统转发）；
+//! - 通道连上后打开虚拟网卡的 IP 转发，断开时恢复原样（本来就开着的不去动它）。
+//!
+//! Linux 的内核按“包从哪块网卡进来”决定转不转发，只开虚拟网卡的话回程的包过不来，
+//! 所以这里打开的是总开关 `net.ipv4.ip_forward`（会影响所有网卡）。
+use anyhow::Result;
+use std::sync::Mutex;
+
+use crate::tap_adapter::TapAdapter;
+
+/// 当前替哪块网卡开着转发
+struct Enabled {
+    adapter: TapAdapter,
+    /// 是不是恩兔打开的（本来就开着的，收工时不关）
+    changed: bool,
+}
+
+/// 转发开关的管家
+#[derive(Default)]
+pub struct Forwarding {
+    enabled: Mutex<Option<Enabled>>,
+}
+
+impl Forwarding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按通道状态调整：`Some(网卡)` 表示该当网关，`None` 表示恢复原样
+    pub fn sync(&self, desired: Option<&TapAdapter>) {
+        let mut enabled = self.enabled.lock().unwrap();
+        if enabled.as_ref().map(|e| &e.adapter) == desired {
+            return;
+        }
+        if let Some(previous) = enabled.take() {
+            if previous.changed {
+                match set_enabled(&previous.adapter, false) {
+                    Ok(()) => log::info!("已关掉 {} 的 IP 转发（恢复原样）", previous.adapter.friendly_name),
+                    Err(e) => log::warn!("关掉 {} 的 IP 转发失败：{:#}", previous.adapter.friendly_name, e),
+                }
+            }
+        }
+        let Some(adapter) = desired else {
+            return;
+        };
+        // 打不开也记下来，免得每轮都弹一次授权框
+        let changed = match is_enabled(adapter) {
+            Ok(true) => false,
+            _ => match set_enabled(adapter, true) {
+                Ok(()) => {
+                    log::info!("已打开 {} 的 IP 转发，这台机器现在替同伴转发流量", adapter.friendly_name);
+                    true
+                }
+                Err(e) => {
+                    log::warn!("打开 {} 的 IP 转发失败（edge 的 -r 照样生效，但系统不会转发）：{:#}", adapter.friendly_name, e);
+                    false
+                }
+            },
+        };
+        *enabled = Some(Enabled {
+            adapter: adapter.clone(),
+            changed,
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+const IP_FORWARD: &str = "/proc/sys/net/ipv4/ip_forward";
+
+#[cfg(target_os = "linux")]
+fn is_enabled(_adapter: &TapAdapter) -> Result<bool> {
+    Ok(std::fs::read_to_string(IP_FORWARD)?.trim() == "1")
+}
+
+#[cfg(target_os = "linux")]
+fn set_enabled(_adapter: &TapAdapter, on: bool) -> Result<()> {
+    if std::fs::write(IP_FORWARD, if on { "1" } else { "0" }).is_ok() {
+        return Ok(());
+    }
+    log::info!("直接修改 ip_forward 失败，请小帮手代劳");
+    crate::priv_helper::call(&crate::priv_helper::Request::IpForward { enabled: on })
+}
+
+/// Windows：`netsh interface ipv4 show interface <编号>` 里的 `Forwarding` 那一行
+#[cfg(target_os = "windows")]
+fn is_enabled(adapter: &TapAdapter) -> Result<bool> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = std::process::Command::new("netsh")
+        .args(["interface", "ipv4", "show", "interface", adapter.if_index.to_string().as_str()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()?;
+    parse_netsh_forwarding(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| anyhow::anyhow!("netsh 的输出里没找到 Forwarding"))
+}
+
+#[cfg(target_os = "windows")]
+fn set_enabled(adapter: &TapAdapter, on: bool) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let forwarding = if on { "forwarding=enabled" } else { "forwarding=disabled" };
+    let status = std::process::Command::new("netsh")
+        .args(["interface", "ipv4", "set", "interface", adapter.if_index.to_string().as_str(), forwarding])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("netsh 设置 {} 的 {} 失败（{:?}）", adapter.friendly_name, forwarding, status.code());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn is_enabled(_adapter: &TapAdapter) -> Result<bool> {
+    Ok(false)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn set_enabled(_adapter: &TapAdapter, _on: bool) -> Result<()> {
+    anyhow::bail!("这个平台上恩兔还不会打开 IP 转发")
+}
+
+/// 读出 `Forwarding                         : enabled` 这一行（netsh 的字段名不随系统语言变）
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn parse_netsh_forwarding(output: &str) -> Option<bool> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "Forwarding").then(|| value.trim().eq_ignore_ascii_case("enabled"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_netsh_forwarding() {
+        let output = "\nInterface Ethernet 2 Parameters\n----------------------------------------------\n\
+                      IfLuid                             : ethernet_32772\n\
+                      IfIndex                            : 17\n\
+                      Forwarding                         : disabled\n\
+                      Advertising                        : disabled\n";
+        assert_eq!(parse_netsh_forwarding(output), Some(false));
+        assert_eq!(parse_netsh_forwarding("Forwarding : enabled"), Some(true));
+        assert_eq!(parse_netsh_forwarding("nothing here"), None);
+    }
+}
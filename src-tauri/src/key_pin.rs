@@ -0,0 +1,67 @@
+//! 总部公钥钉扎（防止有人冒充总部）
+//!
+//! n2n v3 的用户名/密码认证里，edge 靠 `-P <总部公钥>` 认出真正的总部。
+//! 如果有人把这把公钥悄悄换掉（比如发来一条篡改过的邀请链接），edge 就会把口令交给冒牌总部。
+//! 所以恩兔对每份方案采取“第一次见面就记住”的做法：
+//! - 第一次带着 `-P` 建立通道时，把公钥记在方案上；
+//! - 之后每次建立通道都核对一遍，对不上就拒绝出门，并大声提醒主人；
+//! - 主人确认新公钥可信后，可以用 `trust_supernode_key` 重新记下。
+use crate::edge_options::{self, EdgeOption};
+
+/// 核对结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPinCheck {
+    /// 没有使用带公钥的认证，不需要核对
+    NotAuthenticated,
+    /// 第一次见到这把公钥（已经记下来了）
+    FirstUse { key: String },
+    /// 和记下的公钥一致
+    Match,
+    /// 公钥变了！
+    Changed { pinned: String, presented: String },
+}
+
+/// 从高级选项里找出 `-P` 给的总部公钥
+pub fn presented_key(options: &[EdgeOption]) -> Option<String> {
+    edge_options::value_of(options, "-P").map(str::to_string)
+}
+
+/// 拿记下的公钥和这次要用的公钥对一对
+pub fn verify(pinned: Option<&str>, presented: Option<&str>) -> KeyPinCheck {
+    match (pinned, presented) {
+        (_, None) => KeyPinCheck::NotAuthenticated,
+        (None, Some(key)) => KeyPinCheck::FirstUse { key: key.to_string() },
+        (Some(pinned), Some(key)) if pinned == key => KeyPinCheck::Match,
+        (Some(pinned), Some(key)) => KeyPinCheck::Changed {
+            pinned: pinned.to_string(),
+            presented: key.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presented_key_and_verify() {
+        assert_eq!(
+            presented_key(&edge_options::from_legacy("-v -P opIyaWhWjKLJSNOHNpKnGmelhHWRqkmY5pAx7lbDHp4")).as_deref(),
+            Some("opIyaWhWjKLJSNOHNpKnGmelhHWRqkmY5pAx7lbDHp4")
+        );
+        assert_eq!(presented_key(&edge_options::from_legacy("-Pabc -v")).as_deref(), Some("abc"));
+        assert_eq!(presented_key(&edge_options::from_legacy("-v -r")), None);
+        assert_eq!(presented_key(&[]), None);
+
+        assert_eq!(verify(Some("abc"), None), KeyPinCheck::NotAuthenticated);
+        assert_eq!(verify(None, Some("abc")), KeyPinCheck::FirstUse { key: "abc".to_string() });
+        assert_eq!(verify(Some("abc"), Some("abc")), KeyPinCheck::Match);
+        assert_eq!(
+            verify(Some("abc"), Some("evil")),
+            KeyPinCheck::Changed {
+                pinned: "abc".to_string(),
+                presented: "evil".to_string()
+            }
+        );
+    }
+}
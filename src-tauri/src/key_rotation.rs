@@ -0,0 +1,78 @@
+//! 换密语（和小伙伴们约好时间一起换）
+//!
+//! 密语用久了总该换一换，可小组里的人只要有一个先换了，他就和大家断了线。以前只能在群里喊一声
+//! “今晚十点一起改”，再一个个手动去改。现在换密语分三步：
+//! 1. 恩兔生成一条新密语，记在方案上，约好换用的时间（到点之前照旧用旧密语）；
+//! 2. 把带着新密语和换用时间的邀请链接发给小伙伴，他们收下时也只是记下，不会马上换；
+//! 3. 到点后各自的恩兔换上新密语，通道正开着的话用新密语重新建立。
+//!
+//! 新密语和旧密语一样，存盘时收进系统保险箱。
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// 生成的密语长度
+const KEY_LEN: usize = 24;
+/// 生成密语用的字符（去掉了容易看错的 0/O、1/l/I）
+const KEY_ALPHABET: &[u8] = b"abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// 约好了要换的新密语
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRotation {
+    /// 新密语（收进系统保险箱后这里留空）
+    #[serde(default)]
+    pub new_key: String,
+    /// 新密语在系统保险箱里的取件单
+    #[serde(default)]
+    pub new_key_ref: Option<String>,
+    /// 换用新密语的时间（Unix 秒）
+    pub switch_at: u64,
+}
+
+impl KeyRotation {
+    pub fn new(new_key: &str, switch_at: u64) -> Self {
+        Self {
+            new_key: new_key.to_string(),
+            new_key_ref: None,
+            switch_at,
+        }
+    }
+
+    /// 到点了没有
+    pub fn is_due(&self, now: u64) -> bool {
+        now >= self.switch_at
+    }
+}
+
+/// 生成一条新密语
+pub fn generate_key() -> Result<String> {
+    // 256 不是字符数的整数倍，落在尾巴上的字节不要，每个字符的概率才一样
+    let limit = 256 - 256 % KEY_ALPHABET.len();
+    let mut key = String::with_capacity(KEY_LEN);
+    let mut byte = [0u8; 1];
+    while key.len() < KEY_LEN {
+        getrandom::getrandom(&mut byte).map_err(|e| anyhow::anyhow!("生成新密语失败：{}", e))?;
+        let value = byte[0] as usize;
+        if value < limit {
+            key.push(KEY_ALPHABET[value % KEY_ALPHABET.len()] as char);
+        }
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_key_and_due_time() {
+        let a = generate_key().unwrap();
+        let b = generate_key().unwrap();
+        assert_eq!(a.len(), KEY_LEN);
+        assert_ne!(a, b);
+        assert!(a.bytes().all(|c| KEY_ALPHABET.contains(&c)));
+
+        let rotation = KeyRotation::new(&a, 1_000);
+        assert!(!rotation.is_due(999));
+        assert!(rotation.is_due(1_000));
+    }
+}
@@ -0,0 +1,108 @@
+//! 本机 UDP 端口（edge 和总部、同伴说话用的那个口子，`-p`）
+//!
+//! 默认由 edge 随便挑一个端口，防火墙只放行固定端口的环境就连不上了。主人可以在指示里固定 `local_port`：
+//! - 建立通道前先试着占一下这个端口，已经被别人占了就直接告诉主人（`error_local_port_in_use`），不等 edge 摔倒；
+//! - 工作台上显示 edge 实际在用的端口：Linux 从 `/proc/<pid>/fd` 对上 `/proc/net/udp`，Windows 查 `GetExtendedUdpTable`；
+//!   查不到（edge 换了身份、平台不支持）时显示指示里固定的端口。
+use std::net::{Ipv4Addr, UdpSocket};
+
+/// 这个端口现在能不能用（试着占一下，马上放开）
+pub fn is_free(port: u16) -> bool {
+    UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok()
+}
+
+/// edge（`pid`）正在用的 UDP 端口（跳过 `skip`，也就是管理口）
+#[cfg(target_os = "linux")]
+pub fn bound_port(pid: u32, skip: u16) -> Option<u16> {
+    let inodes: Vec<u64> = std::fs::read_dir(format!("/proc/{}/fd", pid))
+        .ok()?
+        .filter_map(|entry| std::fs::read_link(entry.ok()?.path()).ok())
+        .filter_map(|link| {
+            let link = link.to_string_lossy().into_owned();
+            link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect();
+    ["/proc/net/udp", "/proc/net/udp6"].iter().find_map(|table| {
+        let table = std::fs::read_to_string(table).ok()?;
+        parse_proc_udp(&table)
+            .into_iter()
+            .find(|(port, inode)| *port != skip && inodes.contains(inode))
+            .map(|(port, _)| port)
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn bound_port(pid: u32, skip: u16) -> Option<u16> {
+    use windows_sys::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedUdpTable, MIB_UDPROW_OWNER_PID, MIB_UDPTABLE_OWNER_PID, UDP_TABLE_OWNER_PID,
+    };
+    use windows_sys::Win32::Networking::WinSock::AF_INET;
+
+    // 结构体要求 4 字节对齐，所以用 u32 数组当缓冲区
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u32>;
+    loop {
+        buffer = vec![0u32; (size as usize).div_ceil(4)];
+        let ret = unsafe {
+            GetExtendedUdpTable(buffer.as_mut_ptr().cast(), &mut size, 0, AF_INET as u32, UDP_TABLE_OWNER_PID, 0)
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_INSUFFICIENT_BUFFER => continue,
+            other => {
+                log::debug!("GetExtendedUdpTable 失败：{}", other);
+                return None;
+            }
+        }
+    }
+
+    let table = unsafe { &*(buffer.as_ptr() as *const MIB_UDPTABLE_OWNER_PID) };
+    let rows: &[MIB_UDPROW_OWNER_PID] =
+        unsafe { std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) };
+    rows.iter()
+        .filter(|row| row.dwOwningPid == pid)
+        .map(|row| u16::from_be(row.dwLocalPort as u16))
+        .find(|port| *port != skip)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn bound_port(_pid: u32, _skip: u16) -> Option<u16> {
+    None
+}
+
+/// `/proc/net/udp` 里每个套接字的（本机端口，inode）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_proc_udp(table: &str) -> Vec<(u16, u64)> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let port = u16::from_str_radix(fields.get(1)?.rsplit_once(':')?.1, 16).ok()?;
+            let inode = fields.get(9)?.parse().ok()?;
+            Some((port, inode))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_udp_and_probe() {
+        let table = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  283: 0100007F:160C 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 41234 2 0000000000000000 0
+  512: 00000000:1C8E 00000000:0000 07 00000000:00000000 00:00000000 00000000  1000        0 41235 2 0000000000000000 0
+";
+        assert_eq!(parse_proc_udp(table), vec![(5644, 41234), (7310, 41235)]);
+        assert!(parse_proc_udp("").is_empty());
+
+        let taken = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        assert!(!is_free(taken.local_addr().unwrap().port()));
+        #[cfg(target_os = "linux")]
+        assert!(bound_port(std::process::id(), 0).is_some());
+    }
+}
@@ -0,0 +1,130 @@
+//! 工作汇报本（有页数上限的日志通道）
+//!
+//! 日志面板每半秒来取一次汇报，但窗口关着好几天、edge 又开着 `-v -v -v` 的时候，
+//! 没人取走的汇报会一直堆在内存里。所以汇报本只留最近的 `capacity` 条：
+//! - 写满之后再来新的，就撕掉最旧的一条（新的汇报更有用）；
+//! - 撕掉了多少条记在 `dropped` 上，下次取汇报时先递上一句提醒，诊断信息里也能看到累计数。
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 汇报本默认能记多少条
+pub const DEFAULT_CAPACITY: usize = 5000;
+
+struct LogQueue {
+    lines: Mutex<VecDeque<String>>,
+    capacity: usize,
+    /// 累计撕掉的条数
+    dropped: AtomicU64,
+    /// 上次取汇报之后撕掉的条数（取的时候清零）
+    dropped_since_drain: AtomicU64,
+}
+
+/// 往汇报本里写（可以随便克隆给各个小工人）
+#[derive(Clone)]
+pub struct LogSender {
+    queue: Arc<LogQueue>,
+}
+
+/// 从汇报本里取（日志面板用）
+pub struct LogReceiver {
+    queue: Arc<LogQueue>,
+}
+
+/// 汇报本的使用情况（诊断用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStats {
+    /// 还没被取走的条数
+    pub buffered: usize,
+    pub capacity: usize,
+    /// 累计因为写满而撕掉的条数
+    pub dropped: u64,
+}
+
+/// 准备一本最多记 `capacity` 条的汇报本
+pub fn channel(capacity: usize) -> (LogSender, LogReceiver) {
+    let queue = Arc::new(LogQueue {
+        lines: Mutex::new(VecDeque::new()),
+        capacity: capacity.max(1),
+        dropped: AtomicU64::new(0),
+        dropped_since_drain: AtomicU64::new(0),
+    });
+    (
+        LogSender {
+            queue: Arc::clone(&queue),
+        },
+        LogReceiver { queue },
+    )
+}
+
+impl LogSender {
+    /// 记一条汇报（写满时撕掉最旧的一条）
+    pub fn send(&self, line: String) {
+        let mut lines = self.queue.lines.lock().unwrap();
+        if lines.len() >= self.queue.capacity {
+            lines.pop_front();
+            self.queue.dropped.fetch_add(1, Ordering::Relaxed);
+            self.queue.dropped_since_drain.fetch_add(1, Ordering::Relaxed);
+        }
+        lines.push_back(line);
+    }
+}
+
+impl LogReceiver {
+    /// 取走全部汇报；期间撕掉过旧汇报的话，先递上一句提醒
+    pub fn drain(&self) -> Vec<String> {
+        let mut lines = self.queue.lines.lock().unwrap();
+        let dropped = self.queue.dropped_since_drain.swap(0, Ordering::Relaxed);
+        let mut logs = Vec::with_capacity(lines.len() + 1);
+        if dropped > 0 {
+            logs.push(format!(
+                "[WARN] 汇报本写满了，恩兔撕掉了 {} 条较早的汇报（只保留最近 {} 条）",
+                dropped, self.queue.capacity
+            ));
+        }
+        logs.extend(lines.drain(..));
+        logs
+    }
+
+    /// 汇报本的使用情况
+    pub fn stats(&self) -> LogStats {
+        LogStats {
+            buffered: self.queue.lines.lock().unwrap().len(),
+            capacity: self.queue.capacity,
+            dropped: self.queue.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drops_oldest_when_full() {
+        let (tx, rx) = channel(3);
+        for i in 0..5 {
+            tx.send(format!("line {}", i));
+        }
+        assert_eq!(
+            rx.stats(),
+            LogStats {
+                buffered: 3,
+                capacity: 3,
+                dropped: 2
+            }
+        );
+
+        let logs = rx.drain();
+        assert_eq!(logs.len(), 4);
+        assert!(logs[0].starts_with("[WARN]") && logs[0].contains('2'));
+        assert_eq!(&logs[1..], ["line 2", "line 3", "line 4"]);
+
+        // 提醒只递一次，累计数保留
+        tx.send("line 5".to_string());
+        assert_eq!(rx.drain(), vec!["line 5".to_string()]);
+        assert_eq!(rx.stats().dropped, 2);
+    }
+}
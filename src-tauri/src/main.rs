@@ -1,131 +1,1644 @@
 // 防止 Windows 发布版额外蹦出黑框框（恩兔想把工作台保持干净整洁）
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod anonymize;
+mod app_info;
+mod autostart;
+mod backup;
 mod config;
+mod config_sync;
+mod config_watch;
+mod connections;
+mod control_api;
+mod desktop_indicator;
+mod dns_discovery;
+mod edge_caps;
+mod edge_discovery;
+mod edge_integrity;
+mod edge_options;
+mod edge_output;
+mod elevation;
+mod events;
+mod exit_reason;
+mod failover;
+mod history;
+mod ip_forward;
+mod key_pin;
+mod key_rotation;
+mod local_port;
+mod log_buffer;
+mod messages;
+mod mgmt;
 mod n2n_process;
+mod nat_check;
+mod network_aware;
+mod node_name;
+mod operation;
+mod orphans;
+mod peer_bulk;
+mod overrides;
+mod port_forward;
+mod port_knock;
+mod portable;
+mod preflight;
+mod preferences;
+mod process_group;
+mod reaper;
+mod roster;
+mod routes;
+mod safe_mode;
+mod sandbox;
+mod secret_store;
+mod server_mode;
+mod session_state;
+mod share_link;
+mod shutdown;
+mod single_instance;
+mod startup;
+mod status_snapshot;
+mod supernode;
+mod system_log;
+mod tap_adapter;
+mod templates;
+mod theme;
 mod tray;
+mod validation;
+mod vault;
+mod vpn_conflict;
+mod watchdog;
+mod wake;
 
 // Windows 专属开机体检（TAP/UAC 等“高频痛点”）
 #[cfg(target_os = "windows")]
 mod windows_ready;
+// Windows 下用 CTRL_BREAK 请 edge 下班
+#[cfg(target_os = "windows")]
+mod console_ctrl;
+// Linux 下以 root 身份常驻的小帮手（断开、清理网卡不用每次都授权）
+#[cfg(target_os = "linux")]
+mod priv_helper;
+// 测试用的小工具（临时目录之类）
+#[cfg(test)]
+mod test_support;
 
-use config::{ConfigManager, N2NConfig};
+use config::{ConfigEncryption, ConfigManager, N2NConfig, ProfileSummary, SupernodeEntry};
+use connections::ConnectionManager;
+use control_api::{ApiSettings, ControlApiStore, IssuedToken, Scope};
+use events::{AssistantMessage, EdgeWarning, ProcessEvent, SessionSummary, Severity};
+use exit_reason::ExitReason;
+use history::HistoryStore;
 use n2n_process::{ConnectionStatus, N2NProcess, PeerNodeInfo};
+use preferences::{AppPreferences, PreferencesStore};
+use supernode::SupernodeStats;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::Emitter;
 use tauri::Manager;
 use tauri::State;
-use tokio::sync::mpsc;
 #[cfg(target_os = "windows")]
 use tauri::path::BaseDirectory;
 
+/// 工作状态没变化时，交班记录多久刷新一次“最后确认时间”（秒）
+const SESSION_RECORD_REFRESH_SECS: u64 = 30;
+
+/// 认路小工人多久看一次网络（秒）
+const NETWORK_CHECK_SECS: u64 = 10;
+
+/// 唤醒闹钟多久看一次表（秒，要小于一分钟才不会错过整点）
+const WAKE_CHECK_SECS: u64 = 20;
+
+/// 换密语小工人多久看一次表（秒）
+const KEY_ROTATION_CHECK_SECS: u64 = 30;
+
+/// 总部地址巡查小工人多久醒一次（秒；真正重新查的间隔看方案里的 supernode_recheck_secs）
+const SUPERNODE_RECHECK_TICK_SECS: u64 = 15;
+
 /// 恩兔酱的工作台状态
 struct AppState {
     /// N2N 进程管理器（恩兔的工作记录）
     process: Arc<Mutex<N2NProcess>>,
+    /// 按别的方案另开的副通道（见 connections.rs）
+    connections: Arc<ConnectionManager>,
     /// 配置管理器（主人的指示簿）
     config_manager: Arc<Mutex<ConfigManager>>,
     /// 日志接收器（工作汇报通道）
-    log_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<String>>>>,
+    log_rx: Arc<Mutex<Option<log_buffer::LogReceiver>>>,
+    /// 工作日志本（历次会话总结）
+    history: Arc<Mutex<HistoryStore>>,
+    /// 唤醒流水账（每次叫醒同伴的结果）
+    wake_history: Arc<Mutex<HistoryStore<wake::WakeRecord>>>,
+    /// 这次上岗进了安全模式（主人确认没事后清掉）
+    safe_mode: Arc<Mutex<Option<safe_mode::SafeMode>>>,
+    /// 上岗登记本（记着接连意外退出了几次）
+    launch_guard: Option<Arc<safe_mode::LaunchGuard>>,
+    /// 开门体检单（启动后台填写完成前为 None）
+    startup_report: Arc<Mutex<Option<startup::StartupReport>>>,
+    /// 传话筒（通道连着时转发 TCP 端口）
+    port_forwarder: Arc<port_forward::PortForwarder>,
+    /// 中转节点的 IP 转发开关（通道连着时打开，断开后恢复原样）
+    ip_forwarding: Arc<ip_forward::Forwarding>,
+    /// 还没交给前端确认的邀请链接（点链接打开恩兔时，界面可能还没准备好）
+    pending_deep_link: Arc<Mutex<Option<String>>>,
+    /// 恩兔自己的偏好（和工作指示分开保存）
+    preferences: Arc<Mutex<AppPreferences>>,
+    /// 主人从托盘选了“退出”（这时即使开着“关窗口躲进托盘”也要真的收工）
+    quitting: Arc<AtomicBool>,
 }
 
 /// 获取主人的指示（读取配置）
 #[tauri::command]
-async fn get_config(state: State<'_, AppState>) -> Result<N2NConfig, String> {
-    let manager = state.config_manager.lock().unwrap();
-    manager.load().map_err(|e| e.to_string())
+async fn get_config(app: tauri::AppHandle) -> Result<N2NConfig, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.load().map_err(|e| e.to_string())
+    })
+    .await
 }
 
 /// 记下主人的指示（保存配置）
 #[tauri::command]
-async fn save_config(config: N2NConfig, state: State<'_, AppState>) -> Result<(), String> {
-    let manager = state.config_manager.lock().unwrap();
-    manager.save(&config).map_err(|e| e.to_string())
+async fn save_config(config: N2NConfig, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |app, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.save(&config).map_err(|e| e.to_string())?;
+        // 换了 edge 的话先把它的说明书读好
+        edge_caps::warm(effective_edge_path(app, &config));
+        Ok(())
+    })
+    .await
 }
 
-/// 开始打扫通道（启动 N2N 连接）
+/// 列出全部工作方案
 #[tauri::command]
-async fn connect(config: N2NConfig, state: State<'_, AppState>, app: tauri::AppHandle) -> Result<(), String> {
-    let process = state.process.lock().unwrap();
-    
-    // 先保存配置
-    let config = {
-        // Windows 打包模式下：优先使用资源目录里的 edge.exe（避免工作目录变化导致找不到 bin/edge.exe）
-        #[cfg(target_os = "windows")]
-        {
-            let mut config = config;
-            if config.edge_path.is_none() {
-                if let Ok(p) = app.path().resolve("edge.exe", BaseDirectory::Resource) {
-                    if p.exists() {
-                        config.edge_path = Some(p.to_string_lossy().to_string());
-                    }
-                }
-                if config.edge_path.is_none() {
-                    if let Ok(p) = app.path().resolve("bin/edge.exe", BaseDirectory::Resource) {
-                        if p.exists() {
-                            config.edge_path = Some(p.to_string_lossy().to_string());
-                        }
-                    }
-                }
+async fn list_profiles(app: tauri::AppHandle) -> Result<Vec<ProfileSummary>, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.list_profiles().map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 改一份方案的备注和标签
+#[tauri::command]
+async fn set_profile_meta(name: String, notes: String, tags: Vec<String>, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.set_profile_meta(&name, &notes, tags).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 换用另一份工作方案（返回它的配置）
+#[tauri::command]
+async fn switch_profile(name: String, app: tauri::AppHandle) -> Result<N2NConfig, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.switch_profile(&name).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 保存（或新建）一份工作方案
+#[tauri::command]
+async fn save_profile(name: String, config: N2NConfig, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.save_profile(&name, &config).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 收下一份导入的方案（邀请链接/预置文件），默认为只读
+///
+/// 换密语的邀请（带 `switch_at`）遇上同名方案时不整份替换，只记下新密语，到点再换。
+#[tauri::command]
+async fn import_profile(
+    name: String,
+    config: N2NConfig,
+    switch_at: Option<u64>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        if let Some(switch_at) = switch_at.filter(|at| *at > n2n_process::unix_now_seconds()) {
+            let exists = manager.load_store().map_err(|e| e.to_string())?.get(&name).is_some();
+            if exists {
+                return manager
+                    .schedule_key_rotation(&name, &config.encryption_key, switch_at)
+                    .map_err(|e| e.to_string());
             }
-            config
         }
-        #[cfg(not(target_os = "windows"))]
-        {
-            config
+        manager.import_profile(&name, config).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 把当前方案（`all` 时为全部方案）导出成方案文件（.json 为 JSON，其余为 TOML）
+#[tauri::command]
+async fn export_config(path: String, all: Option<bool>, app: tauri::AppHandle) -> Result<usize, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager
+            .export_profiles(std::path::Path::new(&path), all.unwrap_or(false))
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 从方案文件导入方案（检查不通过就整份拒收），返回导入后的方案名字
+#[tauri::command]
+async fn import_config(path: String, app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager
+            .import_profiles(std::path::Path::new(&path))
+            .map_err(|e| format!("{:#}", e))
+    })
+    .await
+}
+
+/// 把一份方案（默认当前方案）压成邀请链接，前端可以直接画成二维码
+///
+/// 顺便翻一下这个小组的花名册：邀请里的固定地址已经分给别人时一起告诉前端。
+#[tauri::command]
+async fn encode_share_link(name: Option<String>, app: tauri::AppHandle) -> Result<roster::ShareInvite, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        let store = manager.load_store().map_err(|e| e.to_string())?;
+        let name = name.unwrap_or_else(|| store.active_profile.clone());
+        let profile = store
+            .get(&name)
+            .ok_or_else(|| format!("找不到名为“{}”的方案", name))?;
+        let conflict = roster::RosterStore::new()
+            .and_then(|rosters| rosters.get(&profile.config.community))
+            .ok()
+            .and_then(|roster| roster.invite_conflict(&profile.name, &profile.config).cloned());
+        if let Some(member) = &conflict {
+            log::warn!("邀请“{}”的地址 {} 已经分给了“{}”", profile.name, member.ip, member.name);
+        }
+        Ok(roster::ShareInvite {
+            link: share_link::encode(&profile.name, &profile.config),
+            conflict,
+        })
+    })
+    .await
+}
+
+/// 换密语：生成新密语，约好换用的时间，返回带着新密语的邀请链接（发给小伙伴们）
+#[tauri::command]
+async fn start_key_rotation(
+    name: Option<String>,
+    switch_at: u64,
+    app: tauri::AppHandle,
+) -> Result<roster::ShareInvite, String> {
+    blocking(app, move |_, state| {
+        if switch_at <= n2n_process::unix_now_seconds() {
+            return Err("error_key_rotation_time_passed".to_string());
+        }
+        let manager = state.config_manager.lock().unwrap();
+        let store = manager.load_store().map_err(|e| e.to_string())?;
+        let name = name.unwrap_or_else(|| store.active_profile.clone());
+        let profile = store
+            .get(&name)
+            .ok_or_else(|| format!("找不到名为“{}”的方案", name))?;
+        let new_key = key_rotation::generate_key().map_err(|e| format!("{:#}", e))?;
+        manager
+            .schedule_key_rotation(&name, &new_key, switch_at)
+            .map_err(|e| e.to_string())?;
+        log::info!("方案“{}”约好了换密语（{}）", name, switch_at);
+        Ok(roster::ShareInvite {
+            link: share_link::encode_rotation(&profile.name, &profile.config, &new_key, switch_at),
+            conflict: None,
+        })
+    })
+    .await
+}
+
+/// 不换密语了
+#[tauri::command]
+async fn cancel_key_rotation(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.cancel_key_rotation(&name).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 翻看认路对照表
+#[tauri::command]
+async fn get_network_rules(app: tauri::AppHandle) -> Result<Vec<network_aware::NetworkRule>, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.network_rules().map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 整张保存认路对照表
+#[tauri::command]
+async fn save_network_rules(rules: Vec<network_aware::NetworkRule>, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.save_network_rules(rules).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 恩兔眼里现在的网络（填对照表时参考）
+#[tauri::command]
+async fn get_network_snapshot() -> Result<network_aware::NetworkSnapshot, String> {
+    Ok(network_aware::detect())
+}
+
+/// 翻出某个小组的花名册
+#[tauri::command]
+async fn get_roster(community: String) -> Result<roster::Roster, String> {
+    let store = roster::RosterStore::new().map_err(|e| e.to_string())?;
+    store.get(&community).map_err(|e| e.to_string())
+}
+
+/// 整本保存花名册（地址不合法、不在网段内或者重复时拒收）
+#[tauri::command]
+async fn save_roster(roster: roster::Roster) -> Result<(), String> {
+    let store = roster::RosterStore::new().map_err(|e| e.to_string())?;
+    store.save(roster).map_err(|e| e.to_string())
+}
+
+/// 网段里下一个空着的地址
+#[tauri::command]
+async fn suggest_roster_ip(community: String) -> Result<Option<String>, String> {
+    let store = roster::RosterStore::new().map_err(|e| e.to_string())?;
+    Ok(store.get(&community).map_err(|e| e.to_string())?.next_free())
+}
+
+/// 从花名册文件导入（同一个小组的整本替换）
+#[tauri::command]
+async fn import_roster(path: String) -> Result<roster::Roster, String> {
+    let store = roster::RosterStore::new().map_err(|e| e.to_string())?;
+    store
+        .import(std::path::Path::new(&path))
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// 把某个小组的花名册导出成文件（.json 为 JSON，其余为 TOML）
+#[tauri::command]
+async fn export_roster(community: String, path: String) -> Result<(), String> {
+    let store = roster::RosterStore::new().map_err(|e| e.to_string())?;
+    store
+        .export(&community, std::path::Path::new(&path))
+        .map_err(|e| e.to_string())
+}
+
+/// 把主人粘贴（或扫码得到）的邀请链接还原成方案，交给前端确认后再用 `import_profile` 收下
+#[tauri::command]
+async fn decode_share_link(link: String) -> Result<portable::PortableProfile, String> {
+    share_link::decode(&link).map_err(|e| format!("{:#}", e))
+}
+
+/// 恩兔自带的方案模板
+#[tauri::command]
+async fn list_templates() -> Result<Vec<templates::Template>, String> {
+    Ok(templates::builtin())
+}
+
+/// 把模板盖到主人正在填写的指示上（只是预填，保存前还可以再改）
+#[tauri::command]
+async fn apply_template(id: String, config: N2NConfig) -> Result<N2NConfig, String> {
+    templates::find(&id).map(|t| t.apply(&config)).map_err(|e| e.to_string())
+}
+
+/// 解锁只读方案（管理员下发的方案默认不许改总部、暗号和密语）
+#[tauri::command]
+async fn unlock_profile(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.unlock_profile(&name).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 主人确认总部的新公钥可信（之后按新公钥核对）
+#[tauri::command]
+async fn trust_supernode_key(app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.trust_supernode_key().map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 主人确认 edge 是自己换的：按当前方案的 edge 重新记下指纹
+/// - `sha256` 是提示里给主人看过的指纹，重新算一遍还是它才记（免得确认期间又被换掉）
+#[tauri::command]
+async fn trust_edge_binary(app: tauri::AppHandle, sha256: String) -> Result<(), String> {
+    blocking(app, move |app, state| {
+        let config = state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?;
+        let path = edge_integrity::resolve(&effective_edge_path(app, &config)).ok_or("error_edge_path_missing")?;
+        let actual = edge_integrity::sha256_file(&path).map_err(|e| e.to_string())?;
+        if actual != sha256 {
+            return Err("error_edge_binary_changed".to_string());
         }
+        edge_integrity::FingerprintBook::new()
+            .and_then(|book| book.record(&path, &actual))
+            .map_err(|e| e.to_string())?;
+        log::warn!("主人确认信任 edge（{}）的新指纹：{}", path.display(), actual);
+        Ok(())
+    })
+    .await
+}
+
+/// 翻看指示簿的备份柜
+#[tauri::command]
+async fn list_backups(app: tauri::AppHandle) -> Result<Vec<backup::BackupInfo>, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        Ok(manager.list_backups())
+    })
+    .await
+}
+
+/// 把指示簿恢复到某一份备份（编号为备份时间）
+#[tauri::command]
+async fn restore_backup(timestamp: u64, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.restore_backup(timestamp).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 删除一份工作方案
+#[tauri::command]
+async fn delete_profile(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.delete_profile(&name).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 翻看总部地址簿
+#[tauri::command]
+async fn list_supernodes(app: tauri::AppHandle) -> Result<Vec<SupernodeEntry>, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.list_supernodes().map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 写入（或新建）一条总部记录（返回编号；引用它的方案会一起更新地址）
+#[tauri::command]
+async fn save_supernode(entry: SupernodeEntry, app: tauri::AppHandle) -> Result<String, String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.save_supernode(entry).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 从地址簿删掉一条总部记录
+#[tauri::command]
+async fn delete_supernode(id: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        let manager = state.config_manager.lock().unwrap();
+        manager.delete_supernode(&id).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 量一量到某个总部的距离（ping 一次，结果记进地址簿）
+#[tauri::command]
+async fn measure_supernode_rtt(id: String, app: tauri::AppHandle) -> Result<Option<f64>, String> {
+    blocking(app, move |_, state| {
+        let entry = {
+            let manager = state.config_manager.lock().unwrap();
+            manager
+                .list_supernodes()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|s| s.id == id)
+                .ok_or_else(|| format!("地址簿里没有编号为“{}”的总部", id))?
+        };
+        // ping 的时候不攥着指示簿
+        let host = n2n_process::supernode_host(&entry.address).to_string();
+        let rtt = n2n_process::ping_once(&host, 1500).map_err(|e| e.to_string())?;
+        let manager = state.config_manager.lock().unwrap();
+        manager
+            .record_supernode_rtt(&id, rtt, n2n_process::unix_now_seconds())
+            .map_err(|e| e.to_string())?;
+        Ok(rtt)
+    })
+    .await
+}
+
+/// 开始打扫通道（启动 N2N 连接）
+/// - `connection_id` 是另一份方案的名字时，按那份方案另开一条副通道，主通道不受影响
+#[tauri::command]
+async fn connect(
+    config: Option<N2NConfig>,
+    connection_id: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    blocking(app, move |app, state| {
+        let active = state.config_manager.lock().unwrap().load_store().map(|s| s.active_profile).ok();
+        if let Some(id) = connection_id.filter(|id| Some(id) != active.as_ref()) {
+            return start_side_connection(app, state, &id);
+        }
+        let config = match config {
+            Some(config) => config,
+            None => state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?,
+        };
+        start_connection(app, state, config)
+    })
+    .await
+}
+
+/// 换了设置后重新建立通道（不用前端先断开再连接、和看护员抢）
+/// - `config` 为空时用指示簿里当前的方案
+#[tauri::command]
+async fn reconnect(config: Option<N2NConfig>, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |app, state| {
+        let config = match config {
+            Some(config) => config,
+            None => state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?,
+        };
+        restart_connection(app, state, config)
+    })
+    .await
+}
+
+/// 把要排队拿闸门、等 edge 回话、读写指示簿的活放到阻塞线程池里干（别让 `std::sync::Mutex` 卡住 async 运行时）
+async fn blocking<R: Send + 'static>(
+    app: tauri::AppHandle,
+    f: impl FnOnce(&tauri::AppHandle, &AppState) -> Result<R, String> + Send + 'static,
+) -> Result<R, String> {
+    tauri::async_runtime::spawn_blocking(move || f(&app, &app.state::<AppState>()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 按方案 `id` 另开一条副通道（见 connections.rs）
+fn start_side_connection(app: &tauri::AppHandle, state: &AppState, id: &str) -> Result<(), String> {
+    let config = state.config_manager.lock().unwrap().load_profile(id).map_err(|e| e.to_string())?;
+    let config = with_bundled_edge(app, config);
+    if let Some(error) = validation::validate(&config).first() {
+        return Err(error.code.to_string());
+    }
+    let primary = state.process.lock().unwrap().running_config();
+    state.connections.connect(id, &config, primary).map_err(|e| e.to_string())
+}
+
+/// `connection_id` 指的是已经登记的副通道时返回它（其余都算主通道）
+fn side_connection(state: &AppState, connection_id: Option<String>) -> Option<String> {
+    connection_id.filter(|id| state.connections.with(id, |_| ()).is_some())
+}
+
+/// 列出全部副通道和它们的状态（格式同 get_status，多一个 id）
+#[tauri::command]
+async fn list_connections(state: State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    Ok(state.connections.map(|id, process| {
+        let mut status = status_json(process);
+        status["id"] = serde_json::json!(id);
+        status
+    }))
+}
+
+/// Windows 打包模式下：没填 edge 路径时优先使用资源目录里的 edge.exe（避免工作目录变化导致找不到 bin/edge.exe）
+#[cfg_attr(not(target_os = "windows"), allow(unused_variables))]
+fn with_bundled_edge(app: &tauri::AppHandle, config: N2NConfig) -> N2NConfig {
+    #[cfg(target_os = "windows")]
+    {
+        let mut config = config;
+        if config.edge_path.is_none() {
+            config.edge_path = ["edge.exe", "bin/edge.exe"].iter().find_map(|name| {
+                app.path()
+                    .resolve(name, BaseDirectory::Resource)
+                    .ok()
+                    .filter(|p| p.exists())
+                    .map(|p| p.to_string_lossy().to_string())
+            });
+        }
+        config
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        config
+    }
+}
+
+/// 恩兔的身份卡（版本、构建指纹、edge 版本、运行环境），给“关于恩兔”和排查报告用
+#[tauri::command]
+async fn get_app_info(app: tauri::AppHandle) -> Result<app_info::AppInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || collect_app_info(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按当前方案实际会用的 edge 填身份卡（会调用 `edge -h`，别放在界面线程里）
+fn collect_app_info(app: &tauri::AppHandle) -> app_info::AppInfo {
+    let config = app.state::<AppState>().config_manager.lock().unwrap().load().unwrap_or_default();
+    let edge_path = with_bundled_edge(app, config)
+        .edge_path
+        .unwrap_or_else(N2NProcess::default_edge_path);
+    app_info::collect(&edge_path, tauri::VERSION, tauri::webview_version().ok())
+}
+
+/// 导出和“建立通道”一模一样的 edge 命令行（排查“终端里能连、恩兔连不上”，或者搬到没有界面的机器上）
+#[tauri::command]
+async fn export_command_line(config: N2NConfig, mask_secrets: Option<bool>, app: tauri::AppHandle) -> Result<String, String> {
+    let config = with_bundled_edge(&app, config);
+    n2n_process::edge_command_line(&config, mask_secrets.unwrap_or(true)).map_err(|e| e.to_string())
+}
+
+/// 把网关模式的开机启动服务配置导出到文件夹里（不点名方案时用当前方案），返回装上它要执行的命令
+#[tauri::command]
+async fn export_service_setup(profile: Option<String>, dir: String, state: State<'_, AppState>) -> Result<String, String> {
+    let profile = match profile {
+        Some(name) => name,
+        None => state.config_manager.lock().unwrap().load_store().map_err(|e| e.to_string())?.active_profile,
+    };
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let setup = server_mode::service_setup(&exe.display().to_string(), &profile);
+    let path = Path::new(&dir).join(&setup.file_name);
+    std::fs::write(&path, &setup.content).map_err(|e| format!("写入 {} 失败：{}", path.display(), e))?;
+    Ok(setup.install.replace("{path}", &path.display().to_string()))
+}
+
+/// 只改了通道宽度（MTU）时不用重连：直接改虚拟网卡，并记进当前方案，下次启动 edge 时生效
+#[tauri::command]
+async fn apply_mtu(mtu: u16, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        if !validation::MTU_RANGE.contains(&mtu) {
+            return Err("error_invalid_mtu".to_string());
+        }
+        // 可能要等小帮手（pkexec）点头，别一直攥着进程的锁
+        let process = state.process.lock().unwrap().clone();
+        process.apply_mtu_live(mtu).map_err(|e| e.to_string())?;
+
+        let manager = state.config_manager.lock().unwrap();
+        let mut config = manager.load().map_err(|e| e.to_string())?;
+        config.mtu = Some(mtu);
+        manager.save(&config).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// NAT 侦察：看看隔了几层 NAT（两层 NAT / 运营商级 NAT 时建议打开中转）
+#[tauri::command]
+async fn diagnose_nat() -> Result<nat_check::NatReport, String> {
+    // 要问好几个地方，别占着异步工人
+    tauri::async_runtime::spawn_blocking(nat_check::diagnose)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 随机挑一个硬件编号（MAC），克隆出来的虚拟机撞了 `error_mac_in_use` 时换一个用
+#[tauri::command]
+async fn random_mac_address() -> Result<String, String> {
+    tap_adapter::random_mac().map_err(|e| e.to_string())
+}
+
+/// 马上叫醒一位同伴（要等它上线，结果稍后通过小喇叭和唤醒流水账告诉主人）
+#[tauri::command]
+async fn wake_peer(rule: wake::WakeRule, app: tauri::AppHandle) -> Result<(), String> {
+    if let Some(code) = wake::check(std::slice::from_ref(&rule)) {
+        return Err(code.to_string());
+    }
+    thread::spawn(move || run_wake(&app, rule, false));
+    Ok(())
+}
+
+/// 唤醒规则全部一起叫（不给规则时用当前方案里的），等大家上线后汇总结果
+#[tauri::command]
+async fn wake_all_peers(
+    rules: Option<Vec<wake::WakeRule>>,
+    app: tauri::AppHandle,
+) -> Result<peer_bulk::BulkReport<wake::WakeRecord>, String> {
+    let state = app.state::<AppState>();
+    let rules = match rules {
+        Some(rules) => rules,
+        None => state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?.wake_rules,
+    };
+    if let Some(code) = wake::check(&rules) {
+        return Err(code.to_string());
+    }
+    let broadcast = match state.process.lock().unwrap().status() {
+        ConnectionStatus::Connected(Some(info)) => wake::broadcast_of(&info.ip, &info.mask),
+        _ => None,
     };
+    // 最多要等 VERIFY_TIMEOUT，别占着异步工人
+    let report = tauri::async_runtime::spawn_blocking(move || peer_bulk::wake_all(&rules, broadcast))
+        .await
+        .map_err(|e| e.to_string())?;
+    for record in &report.results {
+        if let Err(e) = state.wake_history.lock().unwrap().append(record) {
+            log::warn!("唤醒流水账没记上：{}", e);
+        }
+        let _ = app.emit("wake-finished", record);
+    }
+    Ok(report)
+}
+
+/// 翻看唤醒流水账（新的在前）
+#[tauri::command]
+async fn get_wake_history(limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<wake::WakeRecord>, String> {
+    Ok(state.wake_history.lock().unwrap().recent(limit.unwrap_or(20)))
+}
+
+/// 建立通道的实际流程（前端按钮和启动参数 `--connect` 共用）
+fn start_connection(app: &tauri::AppHandle, state: &AppState, config: N2NConfig) -> Result<(), String> {
+    open_connection(app, state, config, false)
+}
+
+/// 用新的指示重新建立通道（开着的先收起，停下、等它走、再出门一次做完，见 `N2NProcess::reconnect`）
+fn restart_connection(app: &tauri::AppHandle, state: &AppState, config: N2NConfig) -> Result<(), String> {
+    open_connection(app, state, config, true)
+}
+
+/// 出门前的各项准备和检查；`restart` 为 true 时旧通道还开着也照样出门
+fn open_connection(app: &tauri::AppHandle, state: &AppState, config: N2NConfig, restart: bool) -> Result<(), String> {
+    // 拿一只手出来就放开 AppState.process：出门要等好几秒，别让状态查询、托盘跟着卡住（排队由闸门管）
+    let process = state.process.lock().unwrap().clone();
+
+    // 先保存配置
+    let config = with_bundled_edge(app, config);
 
     let manager = state.config_manager.lock().unwrap();
     manager.save(&config).map_err(|e| e.to_string())?;
+
+    // v3 认证：核对总部公钥，对不上就别把口令交出去
+    let presented = key_pin::presented_key(&config.advanced_options);
+    match manager.verify_supernode_key(presented.as_deref()) {
+        Ok(key_pin::KeyPinCheck::Changed { pinned, presented }) => {
+            log::error!("总部公钥变了！记下的是 {}，这次给的是 {}，已拒绝建立通道", pinned, presented);
+            let _ = app.emit(
+                "supernode-key-changed",
+                serde_json::json!({ "pinned": pinned, "presented": presented }),
+            );
+            return Err("error_supernode_key_changed".to_string());
+        }
+        Ok(_) => {}
+        Err(e) => log::warn!("核对总部公钥失败：{}", e),
+    }
     drop(manager);
+
+    // 网关模式：在主人的指示上叠加无人值守需要的几项（保存的还是主人自己的设置）
+    let config = server_mode::effective(&config);
+
+    // 安全模式：edge 的输出全部递到日志面板，方便找出摔倒的原因（只影响这一次，不改方案）
+    let mut config = config;
+    if state.safe_mode.lock().unwrap().is_some() {
+        config.log_forward = config::LogForwardFilter::All;
+    }
+
+    // 副通道占着同一块网卡或同一个口的话，两只 edge 只会互相挤掉
+    if let Some(code) = connections::clash(&config, &state.connections.running_configs()) {
+        return Err(code.to_string());
+    }
+
+    // 权限不够的话先给主人递张提示条（edge 多半会因为权限摔倒）
+    let report = elevation::check(&config);
+    if report.required && !report.elevated {
+        log::warn!("当前没有管理员权限，而 {:?} 后端需要它", report.backend);
+        let _ = app.emit("elevation-advice", &report);
+    }
+
+    // 别的 VPN 占着同一片网段的话，连上了也多半不通：先跟主人说清楚是谁、在哪一段
+    for conflict in vpn_conflict::check(&config) {
+        log::warn!(
+            "{} 的网卡 {} 占着 {}，和小组的 {} 重叠",
+            conflict.vpn.label(),
+            conflict.interface,
+            conflict.network,
+            conflict.ours
+        );
+        let _ = app.emit(
+            "assistant-message",
+            AssistantMessage::new("maid_vpn_conflict", Severity::Warning)
+                .with("vpn", conflict.vpn.label())
+                .with("interface", conflict.interface)
+                .with("network", conflict.network)
+                .with("ours", conflict.ours),
+        );
+    }
     
     // 启动连接
-    process.start(&config).map_err(|e| e.to_string())?;
+    let started = if restart { process.reconnect(&config) } else { process.start(&config) };
+    if let Err(e) = started {
+        let e = e.to_string();
+        report_system_event(
+            state,
+            system_log::EVENT_CONNECT_FAILED,
+            system_log::Severity::Error,
+            format!("建立通道失败：{}", e),
+            "error",
+            None,
+        );
+        return Err(e);
+    }
+    report_system_event(
+        state,
+        system_log::EVENT_CONNECT,
+        system_log::Severity::Info,
+        format!("开始建立通道（总部 {}，小组 {}）", config.supernode, config.community),
+        "connecting",
+        None,
+    );
     
     // 更新托盘状态
     let status = process.status();
-    let _ = tray::update_tray_menu(&app, &status);
+    let _ = tray::update_tray_menu(app, &status);
     
     Ok(())
 }
 
+/// 启动时按当前方案的 auto_connect 自动建立通道，结果通过 `auto-connect` 事件告诉前端
+fn auto_connect(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let config = match state.config_manager.lock().unwrap().load() {
+        Ok(config) if config.auto_connect || config.server_mode => config,
+        Ok(_) => return,
+        Err(e) => {
+            log::warn!("读取指示簿失败，跳过自动建立通道：{}", e);
+            return;
+        }
+    };
+
+    log::info!("主人吩咐过开门就上班，恩兔这就去建立通道～");
+    let error = start_connection(app, &state, config).err();
+    if let Some(ref e) = error {
+        log::warn!("自动建立通道失败：{}", e);
+    }
+    let status = status_json(&state.process.lock().unwrap());
+    let _ = app.emit("auto-connect", serde_json::json!({ "status": status, "error": error }));
+}
+
+/// 交班小工人：状态有变化时马上记，没变化时也隔一会儿刷新一下“最后确认时间”
+fn keep_session_record(process: N2NProcess, store: session_state::SessionStateStore) {
+    // 上一页已经在启动时读过了，从干净的一页开始
+    if let Err(e) = store.clear() {
+        log::debug!("{}", e);
+    }
+    let mut last: Option<session_state::SessionRecord> = None;
+    loop {
+        let now = n2n_process::unix_now_seconds();
+        match process.session_record(now) {
+            Some(record) => {
+                let due = last.as_ref().is_none_or(|prev| {
+                    !prev.same_state(&record) || now.saturating_sub(prev.updated_at) >= SESSION_RECORD_REFRESH_SECS
+                });
+                if due {
+                    if let Err(e) = store.save(&record) {
+                        log::debug!("{}", e);
+                    }
+                    last = Some(record);
+                }
+            }
+            None => {
+                if last.take().is_some() {
+                    if let Err(e) = store.clear() {
+                        log::debug!("{}", e);
+                    }
+                }
+            }
+        }
+        thread::sleep(Duration::from_secs(2));
+    }
+}
+
+/// 传话筒小工人：通道连上就按规则开张，断开、暂停或出错就收起来
+fn keep_port_forwards(process: N2NProcess, forwarder: Arc<port_forward::PortForwarder>) {
+    loop {
+        forwarder.sync(process.active_port_forwards().as_deref());
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// 中转小工人：当中转节点的通道连上就打开 IP 转发，断开后恢复原样
+fn keep_ip_forwarding(process: N2NProcess, forwarding: Arc<ip_forward::Forwarding>) {
+    loop {
+        forwarding.sync(process.forwarding_adapter().as_ref());
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// 巡查小工人：定期核对状态和 edge 的实际情况，卡住了就自己修好并告诉主人；顺便看一眼虚拟网卡还好不好
+fn keep_watch(app: tauri::AppHandle, process: N2NProcess) {
+    let mut dog = watchdog::Watchdog::new();
+    loop {
+        thread::sleep(watchdog::CHECK_INTERVAL);
+        // 在 systemd 下跑的话报个平安（恩兔卡死了 systemd 会把它重新拉起来）
+        server_mode::notify("WATCHDOG=1");
+        // 主人临时叫停了自动重连（总部在维护）：巡查也先歇着
+        if process.reconnect_suspended_until().is_some() {
+            continue;
+        }
+        process.check_tap();
+        if let Some(address) = process.check_failover() {
+            report_system_event(
+                &app.state::<AppState>(),
+                system_log::EVENT_CONNECT,
+                system_log::Severity::Info,
+                format!("换到备用总部 {}", address),
+                "connected",
+                None,
+            );
+            let _ = tray::update_tray_menu(&app, &process.status());
+        }
+        let Some(issue) = dog.inspect(&process.observe(), Instant::now()) else {
+            continue;
+        };
+        log::warn!("巡查发现状态对不上：{:?}", issue);
+        if process.repair(issue) {
+            let _ = app.emit("state-repaired", issue);
+            let _ = tray::update_tray_menu(&app, &process.status());
+        }
+    }
+}
+
+/// 桌面提示小工人：状态一变就更新托盘（连上、掉线不一定是主人点出来的），
+/// 主人打开了 desktop_vpn_hint 的话，连上后再请 NetworkManager 把虚拟网卡显示出来（每块网卡只请一次）
+fn keep_desktop_indicator(app: tauri::AppHandle, process: N2NProcess) {
+    let mut last_status: Option<ConnectionStatus> = None;
+    let mut hinted: Option<String> = None;
+    loop {
+        // 按推断出来的状态更新托盘：总部登记丢了、网卡坏了（降级）也要让主人在托盘上看出来
+        let status = process.derived_status();
+        if last_status.as_ref() != Some(&status) {
+            let _ = tray::update_tray_menu(&app, &status);
+            last_status = Some(status.clone());
+        }
+        match status {
+            ConnectionStatus::Connected(_) if hinted.is_none() => {
+                // 网卡还没认出来就下一轮再看；开关在这次连接里只看一次
+                if let Some(adapter) = process.tap_adapter_snapshot() {
+                    let enabled = app
+                        .state::<AppState>()
+                        .config_manager
+                        .lock()
+                        .unwrap()
+                        .load()
+                        .is_ok_and(|c| c.desktop_vpn_hint);
+                    if enabled {
+                        if let Err(e) = desktop_indicator::hint_network_manager(&adapter.friendly_name) {
+                            log::warn!("没能让桌面显示 VPN 已连接：{:#}", e);
+                        }
+                    }
+                    hinted = Some(adapter.friendly_name);
+                }
+            }
+            ConnectionStatus::Connected(_) | ConnectionStatus::Degraded(_) => {}
+            _ => hinted = None,
+        }
+        thread::sleep(Duration::from_secs(2));
+    }
+}
+
+/// 快照小工人：主人填了状态快照文件的话，定期把状态写进去（写不进去只在原因变了时唠叨一次）
+fn keep_status_snapshot(config_manager: Arc<Mutex<ConfigManager>>, process: N2NProcess) {
+    let mut last_error: Option<String> = None;
+    loop {
+        let config = config_manager.lock().unwrap().load().ok();
+        let interval = config
+            .as_ref()
+            .map_or_else(|| N2NConfig::default().status_snapshot_interval_secs, |c| c.status_snapshot_interval_secs)
+            .clamp(*validation::SNAPSHOT_INTERVAL_RANGE.start(), *validation::SNAPSHOT_INTERVAL_RANGE.end());
+        let path = config
+            .and_then(|c| c.status_snapshot_path)
+            .filter(|p| !p.trim().is_empty());
+        if let Some(path) = path {
+            let status = status_json(&process);
+            let result = status_snapshot::write(std::path::Path::new(path.trim()), &status, n2n_process::unix_now_seconds());
+            match result {
+                Ok(()) => last_error = None,
+                Err(e) => {
+                    let message = format!("{:#}", e);
+                    if last_error.as_deref() != Some(message.as_str()) {
+                        log::warn!("{}", message);
+                        last_error = Some(message);
+                    }
+                }
+            }
+        }
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+/// 唤醒闹钟小工人：到点按当前方案里的规则叫醒同伴
+fn keep_wake_schedule(app: tauri::AppHandle) {
+    let mut schedule = wake::Schedule::new();
+    loop {
+        thread::sleep(Duration::from_secs(WAKE_CHECK_SECS));
+        let Some((day, minute)) = wake::local_clock() else {
+            continue;
+        };
+        let rules = match app.state::<AppState>().config_manager.lock().unwrap().load() {
+            Ok(config) => config.wake_rules,
+            Err(_) => continue,
+        };
+        for rule in schedule.due(&rules, day, minute) {
+            let handle = app.clone();
+            thread::spawn(move || run_wake(&handle, rule, true));
+        }
+    }
+}
+
+/// 叫醒一位同伴并等它上线，结果记进唤醒流水账，再告诉主人一声
+fn run_wake(app: &tauri::AppHandle, rule: wake::WakeRule, scheduled: bool) {
+    let state = app.state::<AppState>();
+    let broadcast = match state.process.lock().unwrap().status() {
+        ConnectionStatus::Connected(Some(info)) => wake::broadcast_of(&info.ip, &info.mask),
+        _ => None,
+    };
+    let label = if rule.name.trim().is_empty() { rule.peer_ip.clone() } else { rule.name.clone() };
+    let _ = app.emit(
+        "assistant-message",
+        AssistantMessage::new("maid_wake_sent", Severity::Info).with("name", label.as_str()),
+    );
+
+    let record = wake::wake_and_verify(&rule, broadcast, scheduled);
+    if let Err(e) = state.wake_history.lock().unwrap().append(&record) {
+        log::warn!("唤醒流水账没记上：{}", e);
+    }
+    let message = match (&record.error, record.online_after_secs) {
+        (Some(error), _) => AssistantMessage::new("maid_wake_failed", Severity::Error)
+            .with("name", label.as_str())
+            .with("error", error.as_str()),
+        (None, Some(secs)) => AssistantMessage::new("maid_wake_online", Severity::Success)
+            .with("name", label.as_str())
+            .with("secs", secs),
+        (None, None) => AssistantMessage::new("maid_wake_no_answer", Severity::Warning)
+            .with("name", label.as_str())
+            .with("minutes", wake::VERIFY_TIMEOUT.as_secs() / 60),
+    };
+    let _ = app.emit("assistant-message", message);
+    let _ = app.emit("wake-finished", &record);
+}
+
+/// 认路小工人：网络一变（换了 Wi-Fi、插拔网线）就按对照表换方案
+fn keep_network_profile(app: tauri::AppHandle) {
+    // 启动时的网络由 auto_connect 之前那一次处理，这里只管之后的变化
+    let mut last = network_aware::detect();
+    loop {
+        thread::sleep(Duration::from_secs(NETWORK_CHECK_SECS));
+        let network = network_aware::detect();
+        if network != last {
+            log::debug!("网络变了：{:?}", network);
+            follow_network(&app, &network);
+            last = network;
+        }
+    }
+}
+
+/// 按认路对照表换用方案；通道正开着的话，用新方案重新建立
+fn follow_network(app: &tauri::AppHandle, network: &network_aware::NetworkSnapshot) {
+    let state = app.state::<AppState>();
+    let (profile, config) = {
+        let manager = state.config_manager.lock().unwrap();
+        // 指示簿上着锁时读不到对照表，就先不管
+        let Ok(store) = manager.load_store() else {
+            return;
+        };
+        let Some(rule) = network_aware::pick(&store.network_rules, network) else {
+            return;
+        };
+        if rule.profile == store.active_profile {
+            return;
+        }
+        match manager.switch_profile(&rule.profile) {
+            Ok(config) => (rule.profile.clone(), config),
+            Err(e) => {
+                log::warn!("按网络换用方案“{}”失败：{}", rule.profile, e);
+                return;
+            }
+        }
+    };
+
+    log::info!("主人换到了网络“{}”，恩兔换用方案“{}”", network.describe(), profile);
+    let _ = app.emit("profile-switched", &profile);
+    let _ = app.emit(
+        "assistant-message",
+        AssistantMessage::new("maid_network_switched", Severity::Info)
+            .with("network", network.describe())
+            .with("profile", profile),
+    );
+
+    restart_if_running(app, config);
+}
+
+/// 通道正开着的话，收起旧通道、用新的指示重新建立
+fn restart_if_running(app: &tauri::AppHandle, config: N2NConfig) {
+    let state = app.state::<AppState>();
+    let running = !matches!(
+        state.process.lock().unwrap().status(),
+        ConnectionStatus::Disconnected | ConnectionStatus::Error(..)
+    );
+    if !running {
+        return;
+    }
+    if let Err(e) = restart_connection(app, &state, config) {
+        log::warn!("重新建立通道失败：{}", e);
+    }
+}
+
+/// 总部地址巡查小工人：连着的时候每隔 `supernode_recheck_secs` 重新查一次总部域名，换了 IP 就重新建立通道
+fn keep_supernode_address(app: tauri::AppHandle) {
+    // 这次通道的总部地址，和上次查的时间（断开或重新建立后重新记）
+    let mut baseline: Option<(Vec<std::net::SocketAddr>, Instant)> = None;
+    loop {
+        thread::sleep(Duration::from_secs(SUPERNODE_RECHECK_TICK_SECS));
+        let state = app.state::<AppState>();
+        if !matches!(state.process.lock().unwrap().status(), ConnectionStatus::Connected(_)) {
+            baseline = None;
+            continue;
+        }
+        let Ok(config) = state.config_manager.lock().unwrap().load() else {
+            continue;
+        };
+        let interval = Duration::from_secs(server_mode::effective(&config).supernode_recheck_secs);
+        if interval.is_zero() || baseline.as_ref().is_some_and(|(_, at)| at.elapsed() < interval) {
+            continue;
+        }
+        let addrs = match dns_discovery::supernode_addrs(&config) {
+            Ok(Some(addrs)) => addrs,
+            Ok(None) => continue,
+            Err(e) => {
+                // 一时查不到（DNS 打瞌睡）不算搬家，下一轮再查
+                log::debug!("重新查总部地址失败：{:#}", e);
+                continue;
+            }
+        };
+        match baseline.take() {
+            Some((before, _)) if dns_discovery::moved(&before, &addrs) => {
+                log::warn!("总部的地址从 {:?} 换成了 {:?}，重新建立通道", before, addrs);
+                let _ = app.emit(
+                    "assistant-message",
+                    AssistantMessage::new("maid_supernode_moved", Severity::Warning).with("address", addrs[0].to_string()),
+                );
+                restart_if_running(&app, config);
+            }
+            _ => baseline = Some((addrs, Instant::now())),
+        }
+    }
+}
+
+/// 指示簿被主人直接改过：确认还看得懂，刷新后台跟着指示走的东西，再请前端重新读一遍
+fn on_config_file_changed(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let loaded = {
+        let manager = state.config_manager.lock().unwrap();
+        if !manager.take_external_change() {
+            return;
+        }
+        manager.load()
+    };
+    match loaded {
+        Ok(_) => {
+            log::info!("指示簿在外面被改过了，恩兔重新读了一遍");
+            let status = state.process.lock().unwrap().status();
+            let _ = tray::update_tray_menu(app, &status);
+            let _ = app.emit("config-changed", ());
+            let _ = app.emit("assistant-message", AssistantMessage::new("maid_config_reloaded", Severity::Info));
+        }
+        // 加密的指示簿还没解锁：等主人解锁时自然会读到新内容
+        Err(e) if e.to_string() == vault::ERROR_LOCKED => {}
+        Err(e) => {
+            log::warn!("指示簿在外面被改过了，但恩兔看不懂：{:#}", e);
+            let _ = app.emit(
+                "assistant-message",
+                AssistantMessage::new("maid_config_reload_failed", Severity::Warning).with("error", format!("{:#}", e)),
+            );
+        }
+    }
+}
+
+/// 盯着同步文件夹里的那份方案：别的机器改了就对一次账
+fn watch_sync_folder(app: &tauri::AppHandle, dir: PathBuf) {
+    let handle = app.clone();
+    let file = dir.join(config_sync::FILE_NAME);
+    if let Err(e) = config_watch::watch(&file, move || on_sync_file_changed(&handle, &dir)) {
+        log::warn!("盯不住同步文件夹（别的机器的改动要等下次保存或重启时才并进来）：{:#}", e);
+    }
+}
+
+/// 同步文件夹里的那份被改过了（可能是别的机器，也可能是恩兔自己刚推上去的）
+fn on_sync_file_changed(app: &tauri::AppHandle, dir: &Path) {
+    let state = app.state::<AppState>();
+    // 主人已经换了文件夹：旧的监视器看到的动静不算数
+    if state.preferences.lock().unwrap().sync_dir.as_deref().map(Path::new) != Some(dir) {
+        return;
+    }
+    let result = state.config_manager.lock().unwrap().sync_now();
+    match result {
+        Ok(report) => announce_sync(app, &report),
+        Err(e) if e.to_string() == vault::ERROR_LOCKED || e.to_string() == config_sync::ERROR_ENCRYPTED => {}
+        Err(e) => log::warn!("和同步文件夹对账失败：{:#}", e),
+    }
+}
+
+/// 从同步文件夹拉下了东西：刷新工作台和托盘，告诉主人
+fn announce_sync(app: &tauri::AppHandle, report: &config_sync::SyncReport) {
+    if report.is_empty() {
+        return;
+    }
+    let status = app.state::<AppState>().process.lock().unwrap().status();
+    let _ = tray::update_tray_menu(app, &status);
+    let _ = app.emit("config-changed", ());
+    if !report.pulled.is_empty() {
+        let _ = app.emit(
+            "assistant-message",
+            AssistantMessage::new("maid_sync_pulled", Severity::Info).with("profiles", report.pulled.join(", ")),
+        );
+    }
+    if !report.conflicts.is_empty() {
+        let _ = app.emit(
+            "assistant-message",
+            AssistantMessage::new("maid_sync_conflict", Severity::Warning).with("profiles", report.conflicts.join(", ")),
+        );
+    }
+}
+
+/// 换密语小工人：到了约好的时间换上新密语，当前方案正连着的话用新密语重新建立
+fn keep_key_rotation(app: tauri::AppHandle) {
+    loop {
+        let state = app.state::<AppState>();
+        let (rotated, active) = {
+            let manager = state.config_manager.lock().unwrap();
+            // 指示簿上着锁时读不到，解锁后再换
+            match manager.apply_due_key_rotations(n2n_process::unix_now_seconds()) {
+                Ok(rotated) if !rotated.is_empty() => (rotated, manager.load_store().map(|s| s.active_profile).ok()),
+                _ => (Vec::new(), None),
+            }
+        };
+        for profile in &rotated {
+            log::info!("到点了，方案“{}”换上了新密语", profile);
+            let _ = app.emit(
+                "assistant-message",
+                AssistantMessage::new("maid_key_rotated", Severity::Info).with("profile", profile.clone()),
+            );
+        }
+        if let Some(active) = active.filter(|a| rotated.contains(a)) {
+            let _ = app.emit("profile-switched", &active);
+            let config = state.config_manager.lock().unwrap().load();
+            match config {
+                Ok(config) => restart_if_running(&app, config),
+                Err(e) => log::warn!("换上新密语后读不到指示：{}", e),
+            }
+        }
+        thread::sleep(Duration::from_secs(KEY_ROTATION_CHECK_SECS));
+    }
+}
+
+/// 主人打开了“写入系统日志”时，把大事抄一份过去
+fn report_system_event(
+    state: &AppState,
+    id: u32,
+    severity: system_log::Severity,
+    message: String,
+    status: &'static str,
+    error_code: Option<String>,
+) {
+    let store = match state.config_manager.lock().unwrap().load_store() {
+        Ok(store) => store,
+        Err(_) => return,
+    };
+    if !server_mode::effective(&store.active_config()).system_log {
+        return;
+    }
+    system_log::write(&system_log::SystemEvent {
+        id,
+        severity,
+        message,
+        profile: Some(store.active_profile),
+        status,
+        error_code,
+    });
+}
+
+/// 翻看开门体检单（前端错过 startup-report 事件时补看；还没填好时返回 None）
+#[tauri::command]
+async fn get_startup_report(state: State<'_, AppState>) -> Result<Option<startup::StartupReport>, String> {
+    Ok(state.startup_report.lock().unwrap().clone())
+}
+
+/// 这次上岗是不是安全模式（前端错过 safe-mode 事件时补看）
+#[tauri::command]
+async fn get_safe_mode(state: State<'_, AppState>) -> Result<Option<safe_mode::SafeMode>, String> {
+    Ok(state.safe_mode.lock().unwrap().clone())
+}
+
+/// 主人确认没事了：退出安全模式（意外次数清零，想自动连接的话下次打开恩兔就照常）
+#[tauri::command]
+async fn leave_safe_mode(state: State<'_, AppState>) -> Result<(), String> {
+    if let Some(ref guard) = state.launch_guard {
+        guard.reset().map_err(|e| e.to_string())?;
+    }
+    *state.safe_mode.lock().unwrap() = None;
+    Ok(())
+}
+
+/// 机器上没人照看的 edge（和当前方案对得上的排在前面）
+#[tauri::command]
+async fn find_orphan_edges(app: tauri::AppHandle) -> Result<Vec<orphans::OrphanEdge>, String> {
+    tauri::async_runtime::spawn_blocking(move || scan_orphans(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 接手一只没人照看的 edge（要和当前方案对得上），之后就像恩兔自己带出门的一样照看
+#[tauri::command]
+async fn adopt_orphan_edge(pid: u32, app: tauri::AppHandle) -> Result<(), String> {
+    let lookup = app.clone();
+    let orphan = tauri::async_runtime::spawn_blocking(move || find_orphan(&lookup, pid))
+        .await
+        .map_err(|e| e.to_string())??;
+    if !orphan.matches_config {
+        return Err("error_orphan_mismatch".to_string());
+    }
+    let state = app.state::<AppState>();
+    let config = state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?;
+    let process = state.process.lock().unwrap();
+    process
+        .adopt(orphan.pid, orphan.management_port, &config)
+        .map_err(|e| e.to_string())?;
+    let _ = tray::update_tray_menu(&app, &process.status());
+    Ok(())
+}
+
+/// 请一只没人照看的 edge 下班（先从管理口请，没人应再发信号，不走再强制清理）
+#[tauri::command]
+async fn terminate_orphan_edge(pid: u32, app: tauri::AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        find_orphan(&app, pid)?.stop().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 找一找机器上没人照看的 edge（主通道、副通道自己带出门的不算）
+fn scan_orphans(app: &tauri::AppHandle) -> Vec<orphans::OrphanEdge> {
+    let state = app.state::<AppState>();
+    let config = state.config_manager.lock().unwrap().load().unwrap_or_default();
+    let mut own: Vec<u32> = state.process.lock().unwrap().pid().into_iter().collect();
+    own.extend(state.connections.map(|_, process| process.pid()).into_iter().flatten());
+    orphans::scan(&config, &own)
+}
+
+/// 按进程号重新找一遍（只对确实还在、确实是 edge 的进程动手）
+fn find_orphan(app: &tauri::AppHandle, pid: u32) -> Result<orphans::OrphanEdge, String> {
+    scan_orphans(app)
+        .into_iter()
+        .find(|orphan| orphan.pid == pid)
+        .ok_or_else(|| "error_orphan_not_found".to_string())
+}
+
+/// 取走还没确认的邀请链接（取一次就清空，避免同一条链接反复弹窗）
+#[tauri::command]
+async fn take_deep_link(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state.pending_deep_link.lock().unwrap().take())
+}
+
+/// 权限体检：是否已是管理员、当前网卡后端是否需要管理员，以及给主人的建议
+#[tauri::command]
+async fn get_elevation_status(state: State<'_, AppState>) -> Result<elevation::ElevationReport, String> {
+    let config = {
+        let manager = state.config_manager.lock().unwrap();
+        manager.load().map_err(|e| e.to_string())?
+    };
+    Ok(elevation::check(&config))
+}
+
+/// 把后端给出的编号（error_ / warning_ / op_）翻译成人话；不认识的编号原样返回
+#[tauri::command]
+async fn describe_error(code: String, locale: Option<String>) -> Result<String, String> {
+    let locale = messages::Locale::from_tag(locale.as_deref().unwrap_or("zh"));
+    Ok(messages::describe_or_raw(&code, locale))
+}
+
+/// 检查一份指示，返回每个有问题的字段（没问题时为空列表）
+#[tauri::command]
+async fn validate_config(config: N2NConfig, app: tauri::AppHandle) -> Result<Vec<validation::FieldError>, String> {
+    let mut errors = validation::validate(&config);
+    if let Some(caps) = edge_caps_for(&app, &config) {
+        errors.extend(validation::validate_capabilities(&config, &caps));
+    }
+    Ok(errors)
+}
+
+/// 这份指示用的 edge 会些什么（设置页据此把不支持的加密方式标出来；问不出来时为 null）
+#[tauri::command]
+async fn get_edge_capabilities(config: N2NConfig, app: tauri::AppHandle) -> Result<Option<edge_caps::EdgeCaps>, String> {
+    Ok(edge_caps_for(&app, &config))
+}
+
+/// 把机器上找得到的 edge 都列出来（带版本号和 CPU 架构），给主人挑一个填进工具箱路径
+#[tauri::command]
+async fn discover_edge_binaries(app: tauri::AppHandle) -> Result<Vec<edge_discovery::EdgeCandidate>, String> {
+    let resource_dir = app.path().resource_dir().ok();
+    Ok(edge_discovery::discover(resource_dir.as_deref()))
+}
+
+/// 当前方案实际会用的 edge 的底细：路径、版本、大版本、会的开关（会调用 `edge -h`，结果有缓存）
+#[tauri::command]
+async fn get_edge_info(state: State<'_, AppState>, app: tauri::AppHandle) -> Result<edge_caps::EdgeInfo, String> {
+    let config = state.config_manager.lock().unwrap().load().map_err(|e| e.to_string())?;
+    let edge_path = effective_edge_path(&app, &config);
+    tauri::async_runtime::spawn_blocking(move || edge_caps::info(&edge_path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn edge_caps_for(app: &tauri::AppHandle, config: &N2NConfig) -> Option<edge_caps::EdgeCaps> {
+    edge_caps::detect(&effective_edge_path(app, config))
+}
+
+/// 这份指示实际会用的 edge 路径（没填时用打包带的或默认的）
+fn effective_edge_path(app: &tauri::AppHandle, config: &N2NConfig) -> String {
+    with_bundled_edge(app, config.clone())
+        .edge_path
+        .unwrap_or_else(N2NProcess::default_edge_path)
+}
+
+/// 照着启动参数办事（第一次启动时的参数，以及后来的实例转交过来的参数）
+fn handle_launch_args(app: &tauri::AppHandle, args: Vec<String>) {
+    let actions = single_instance::parse_launch_args(&args);
+
+    // 主人又点了一次恩兔：把工作台端到面前（`--headless` 启动的网关只在后台干活）
+    if server_mode::is_headless(&args) {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    } else if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+
+    let state = app.state::<AppState>();
+    for action in actions {
+        match action {
+            single_instance::LaunchAction::Connect { profile } => {
+                let config = {
+                    let manager = state.config_manager.lock().unwrap();
+                    match profile {
+                        Some(name) => manager.switch_profile(&name),
+                        None => manager.load(),
+                    }
+                };
+                let result = config
+                    .map_err(|e| e.to_string())
+                    .and_then(|config| start_connection(app, &state, config));
+                if let Err(e) = result {
+                    log::warn!("按启动参数建立通道失败：{}", e);
+                }
+            }
+            single_instance::LaunchAction::DeepLink(link) => {
+                // 邀请链接的导入交给前端确认（避免主人不知情时被改掉配置）：
+                // 先收着，界面准备好了会来取；已经开着的话喊一声让它马上来取
+                *state.pending_deep_link.lock().unwrap() = Some(link);
+                let _ = app.emit("deep-link", ());
+            }
+        }
+    }
+}
+
 /// 收拾工具休息（断开 N2N 连接）
 #[tauri::command]
-async fn disconnect(state: State<'_, AppState>, app: tauri::AppHandle) -> Result<(), String> {
-    let process = state.process.lock().unwrap();
-    process.stop().map_err(|e| e.to_string())?;
-    
-    // 更新托盘状态
-    let status = process.status();
-    let _ = tray::update_tray_menu(&app, &status);
-    
-    Ok(())
+async fn disconnect(connection_id: Option<String>, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |app, state| {
+        if let Some(id) = side_connection(state, connection_id) {
+            return state.connections.disconnect(&id).map_err(|e| e.to_string());
+        }
+        let process = state.process.lock().unwrap();
+        process.stop().map_err(|e| e.to_string())?;
+
+        // 更新托盘状态
+        let status = process.status();
+        let _ = tray::update_tray_menu(app, &status);
+
+        // edge 迟迟不走的话，过了 stop_timeout_secs 就强制清理
+        let process = process.clone();
+        let app = app.clone();
+        thread::spawn(move || {
+            if process.wait_stopped_or_force() {
+                let _ = tray::update_tray_menu(&app, &process.status());
+            }
+        });
+
+        Ok(())
+    })
+    .await
 }
 
 /// 立即停止工作（强制断开，用于温柔关闭卡住时）
 #[tauri::command]
-async fn disconnect_force(state: State<'_, AppState>, app: tauri::AppHandle) -> Result<(), String> {
-    let process = state.process.lock().unwrap();
-    process.stop_force().map_err(|e| e.to_string())?;
+async fn disconnect_force(app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, |app, state| {
+        let process = state.process.lock().unwrap();
+        process.stop_force().map_err(|e| e.to_string())?;
 
-    // 更新托盘状态
-    let status = process.status();
-    let _ = tray::update_tray_menu(&app, &status);
+        // 更新托盘状态
+        let status = process.status();
+        let _ = tray::update_tray_menu(app, &status);
 
-    Ok(())
+        Ok(())
+    })
+    .await
+}
+
+/// 歇一会儿（暂停连接，保留本次工作的指示与地址）
+#[tauri::command]
+async fn pause(app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, |app, state| {
+        let process = state.process.lock().unwrap();
+        process.pause().map_err(|e| e.to_string())?;
+
+        let status = process.status();
+        let _ = tray::update_tray_menu(app, &status);
+
+        Ok(())
+    })
+    .await
+}
+
+/// 继续打扫（从暂停中恢复连接）
+#[tauri::command]
+async fn resume(app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, |app, state| {
+        let process = state.process.lock().unwrap();
+        process.resume().map_err(|e| e.to_string())?;
+
+        let status = process.status();
+        let _ = tray::update_tray_menu(app, &status);
+
+        Ok(())
+    })
+    .await
+}
+
+/// 换总部：不用断开再连，按原来的指示（尽量保住虚拟地址）朝新的总部重新出门，并记进当前方案
+#[tauri::command]
+async fn switch_supernode(address: String, app: tauri::AppHandle) -> Result<(), String> {
+    let address = address.trim().to_string();
+    if !validation::is_host_port(&address) {
+        return Err("error_invalid_supernode".to_string());
+    }
+    blocking(app, move |app, state| {
+        // 拿一只手出来就放开 AppState.process：等 edge 停稳、重新出门都要好几秒
+        let process = state.process.lock().unwrap().clone();
+        process.pause_for_switch(&address).map_err(|e| e.to_string())?;
+        process.wait_stopped_or_force();
+
+        // 记进当前方案：下次建立通道也去新的总部（地址簿里有这一家的话就引用它）
+        {
+            let manager = state.config_manager.lock().unwrap();
+            let saved = manager.load().and_then(|mut config| {
+                let book = manager.list_supernodes()?;
+                config.supernode = address.clone();
+                config.supernode_id = book.into_iter().find(|s| s.address.trim() == address).map(|s| s.id);
+                config.supernode_dns = None;
+                manager.save(&config)
+            });
+            if let Err(e) = saved {
+                log::warn!("新总部没能记进方案：{}", e);
+            }
+        }
+
+        process.resume_on(&address).map_err(|e| e.to_string())?;
+        report_system_event(
+            state,
+            system_log::EVENT_CONNECT,
+            system_log::Severity::Info,
+            format!("换到总部 {}", address),
+            "connecting",
+            None,
+        );
+        let _ = tray::update_tray_menu(app, &process.status());
+        Ok(())
+    })
+    .await
 }
 
 /// 查看工作状态（获取连接状态）
 #[tauri::command]
-async fn get_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_status(connection_id: Option<String>, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    if let Some(id) = side_connection(&state, connection_id) {
+        return Ok(state.connections.with(&id, status_json).unwrap_or_default());
+    }
     let process = state.process.lock().unwrap();
+    Ok(status_json(&process))
+}
+
+/// 把当前状态整理成给前端（和遥控口）看的 JSON
+fn status_json(process: &N2NProcess) -> serde_json::Value {
     let status = process.derived_status();
     let notice = process.derived_notice();
     
-    let result = match status {
+    let mut result = match status {
         ConnectionStatus::Disconnected => serde_json::json!({
             "status": "disconnected",
             "error": null,
@@ -146,29 +1659,106 @@ async fn get_status(state: State<'_, AppState>) -> Result<serde_json::Value, Str
             "error": null,
             "networkInfo": network_info
         }),
-        ConnectionStatus::Error(msg) => serde_json::json!({
+        ConnectionStatus::Degraded(network_info) => serde_json::json!({
+            "status": "degraded",
+            "error": notice,
+            "networkInfo": network_info
+        }),
+        ConnectionStatus::Paused => serde_json::json!({
+            "status": "paused",
+            "error": null,
+            "networkInfo": null
+        }),
+        ConnectionStatus::Reconnecting(attempt) => serde_json::json!({
+            "status": "reconnecting",
+            "error": notice,
+            "networkInfo": null,
+            "reconnectAttempt": attempt
+        }),
+        ConnectionStatus::Error(msg, reason) => serde_json::json!({
             "status": "error",
             "error": msg,
-            "networkInfo": null
+            "networkInfo": null,
+            // edge 摔倒的原因码（见 exit_reason.rs；出门前就没过关的为 null）
+            "exitReason": reason.map(ExitReason::code)
         }),
     };
-    
-    Ok(result)
+    // 实际生效的加密/压缩方式（edge 还没汇报时为 null）
+    result["transport"] = serde_json::json!(process.transport_snapshot());
+    // 虚拟网卡在系统里的名字和接口编号（方便主人在系统网络设置里找到它）
+    result["tapAdapter"] = serde_json::json!(process.tap_adapter_snapshot());
+    // 实际交给 edge 的工号（主机名整理过之后可能和主人看到的不一样）
+    result["nodeName"] = serde_json::json!(process.node_name());
+    // 自动重连被临时叫停到什么时候（Unix 秒，没叫停为 null）
+    result["reconnectSuspendedUntil"] = serde_json::json!(process.reconnect_suspended_until());
+    // edge 实际在用的本机 UDP 端口（edge 不在时为 null）
+    result["localPort"] = serde_json::json!(process.local_port());
+    // edge 实际在用的报到间隔（秒，edge 不在时为 null）
+    result["registerIntervalSecs"] = serde_json::json!(process.register_interval_secs());
+    // 本次通道加上的路由（没连上时为空）
+    result["routes"] = serde_json::json!(process.active_routes());
+
+    result
+}
+
+/// 临时叫停自动重连和自我巡查（总部维护时用，不改方案；0 表示马上恢复），返回叫停到什么时候
+#[tauri::command]
+async fn suspend_auto_reconnect(duration_secs: u64, state: State<'_, AppState>) -> Result<Option<u64>, String> {
+    let process = state.process.lock().unwrap();
+    Ok(process.suspend_reconnect(Duration::from_secs(duration_secs)))
+}
+
+/// 取消排队中的自动重连（edge 摔倒后正等着重连时），返回是否真的取消了
+#[tauri::command]
+async fn cancel_reconnect(state: State<'_, AppState>, app: tauri::AppHandle) -> Result<bool, String> {
+    let process = state.process.lock().unwrap();
+    let cancelled = process.cancel_pending_reconnect();
+    if cancelled {
+        let _ = tray::update_tray_menu(&app, &process.status());
+    }
+    Ok(cancelled)
 }
 
 /// 获取工作汇报（读取日志）
 #[tauri::command]
-async fn get_logs(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let mut rx_guard = state.log_rx.lock().unwrap();
-    let mut logs = Vec::new();
-    
-    if let Some(rx) = rx_guard.as_mut() {
-        while let Ok(log) = rx.try_recv() {
-            logs.push(log);
-        }
+async fn get_logs(connection_id: Option<String>, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    if let Some(id) = side_connection(&state, connection_id) {
+        return Ok(state.connections.drain_logs(&id).unwrap_or_default());
     }
-    
-    Ok(logs)
+    let rx_guard = state.log_rx.lock().unwrap();
+    Ok(rx_guard.as_ref().map(|rx| rx.drain()).unwrap_or_default())
+}
+
+/// 翻看汇报本的使用情况（积压条数、上限、因为写满撕掉了多少条）
+#[tauri::command]
+async fn get_log_stats(state: State<'_, AppState>) -> Result<Option<log_buffer::LogStats>, String> {
+    let rx_guard = state.log_rx.lock().unwrap();
+    Ok(rx_guard.as_ref().map(|rx| rx.stats()))
+}
+
+/// 点名册上的同伴全体 ping 一下（同时进行，汇总结果）
+#[tauri::command]
+async fn ping_all_peers(state: State<'_, AppState>) -> Result<peer_bulk::BulkReport<peer_bulk::PingOutcome>, String> {
+    let peers = state.process.lock().unwrap().peers_snapshot();
+    tauri::async_runtime::spawn_blocking(move || peer_bulk::ping_all(&peers))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按虚拟地址反查全体同伴在 DNS 里的名字
+#[tauri::command]
+async fn resolve_peer_names(state: State<'_, AppState>) -> Result<peer_bulk::BulkReport<peer_bulk::NameOutcome>, String> {
+    let peers = state.process.lock().unwrap().peers_snapshot();
+    tauri::async_runtime::spawn_blocking(move || peer_bulk::resolve_all(&peers))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 把点名册导出成文件（.csv 写表格，其余写 JSON），返回导出了几位
+#[tauri::command]
+async fn export_peers(path: String, state: State<'_, AppState>) -> Result<usize, String> {
+    let peers = state.process.lock().unwrap().peers_snapshot();
+    peer_bulk::export(&peers, Path::new(&path)).map_err(|e| format!("{:#}", e))
 }
 
 /// 获取同伴节点信息（用于“网络信息”卡片展示）
@@ -178,22 +1768,374 @@ async fn get_peers(state: State<'_, AppState>) -> Result<Vec<PeerNodeInfo>, Stri
     Ok(process.peers_snapshot())
 }
 
+/// edge 的实时统计（收发、报到情况、在岗时长；edge 不在时为 None）
+#[tauri::command]
+async fn get_edge_stats(state: State<'_, AppState>) -> Result<Option<mgmt::EdgeStats>, String> {
+    let Some(target) = state.process.lock().unwrap().stats_target() else {
+        return Ok(None);
+    };
+    // 要敲好几次管理口，别占着异步工人
+    tauri::async_runtime::spawn_blocking(move || target.query())
+        .await
+        .map_err(|e| e.to_string())?
+        .map(Some)
+        .map_err(|e| format!("{:#}", e))
+}
+
+/// 传话筒每条规则的情况（没连上时为空）
+#[tauri::command]
+async fn get_port_forwards(state: State<'_, AppState>) -> Result<Vec<port_forward::ForwardStatus>, String> {
+    Ok(state.port_forwarder.statuses())
+}
+
+/// 翻看提醒小本子（edge 的非致命 WARNING）
+#[tauri::command]
+async fn get_warnings(state: State<'_, AppState>) -> Result<Vec<EdgeWarning>, String> {
+    let process = state.process.lock().unwrap();
+    Ok(process.warnings_snapshot())
+}
+
+/// 查看自家总部的值班记录（本机 supernode 的小组人数与收发包统计）
+#[tauri::command]
+async fn get_supernode_stats(port: Option<u16>, password: Option<String>) -> Result<SupernodeStats, String> {
+    let port = port.unwrap_or(supernode::DEFAULT_SUPERNODE_MGMT_PORT);
+    supernode::query_supernode_stats(port, password.as_deref()).map_err(|e| e.to_string())
+}
+
+/// 翻看工作日志本（最近几次会话的总结，新的在前）
+#[tauri::command]
+async fn get_session_history(limit: Option<usize>, state: State<'_, AppState>) -> Result<Vec<SessionSummary>, String> {
+    let history = state.history.lock().unwrap();
+    Ok(history.recent(limit.unwrap_or(50)))
+}
+
+/// 导出工作汇报（anonymize 为 true 时先打码：公网地址、MAC、暗号、密语），返回保存位置
+#[tauri::command]
+async fn export_logs(lines: Vec<String>, anonymize: bool, state: State<'_, AppState>, app: tauri::AppHandle) -> Result<String, String> {
+    // 开头附上身份卡，维护者不用再追问版本和系统
+    let header = tauri::async_runtime::spawn_blocking(move || collect_app_info(&app).summary())
+        .await
+        .map_err(|e| e.to_string())?;
+    let lines = if anonymize {
+        let store = {
+            let manager = state.config_manager.lock().unwrap();
+            manager.load_store().map_err(|e| e.to_string())?
+        };
+        anonymize::anonymize_logs(&lines, &anonymize::Secrets::from_store(&store))
+    } else {
+        lines
+    };
+
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "找不到可以存放导出文件的目录".to_string())?;
+    let path = dir.join(format!("n2n-maid-logs-{}.txt", n2n_process::unix_now_seconds()));
+    let content = format!("# {}\n{}\n", header, lines.join("\n"));
+    std::fs::write(&path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    log::info!("工作汇报已导出到 {}", path.display());
+    Ok(path.display().to_string())
+}
+
+/// 翻看恩兔自己的偏好
+#[tauri::command]
+async fn get_preferences(state: State<'_, AppState>) -> Result<AppPreferences, String> {
+    Ok(state.preferences.lock().unwrap().clone())
+}
+
+/// 记下恩兔自己的偏好（和工作指示分开保存），马上换上配色、登记开机自启
+#[tauri::command]
+async fn save_preferences(preferences: AppPreferences, state: State<'_, AppState>, app: tauri::AppHandle) -> Result<(), String> {
+    let mut preferences = preferences;
+    preferences.sync_dir = preferences.sync_dir.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if preferences.sync_dir.as_deref().is_some_and(|d| !Path::new(d).is_dir()) {
+        return Err("error_sync_dir_missing".to_string());
+    }
+    // 加密的指示簿不往同步文件夹里放明文
+    if preferences.sync_dir.is_some() && state.config_manager.lock().unwrap().encryption().encrypted {
+        return Err(config_sync::ERROR_ENCRYPTED.to_string());
+    }
+    let previous = state.preferences.lock().unwrap().clone();
+    if preferences.autostart != previous.autostart {
+        autostart::set_enabled(preferences.autostart).map_err(|e| format!("{:#}", e))?;
+    }
+    PreferencesStore::new()
+        .and_then(|store| store.save(&preferences))
+        .map_err(|e| e.to_string())?;
+    if preferences.theme != previous.theme {
+        theme::apply_preference(&app, preferences.theme);
+    }
+    *state.preferences.lock().unwrap() = preferences.clone();
+    if preferences.sync_dir != previous.sync_dir {
+        let dir = preferences.sync_dir.as_ref().map(PathBuf::from);
+        let result = {
+            let mut manager = state.config_manager.lock().unwrap();
+            manager.set_sync_dir(dir.clone());
+            manager.sync_now()
+        };
+        match result {
+            Ok(report) => announce_sync(&app, &report),
+            Err(e) if e.to_string() == vault::ERROR_LOCKED => {}
+            Err(e) => {
+                log::warn!("和同步文件夹对账失败：{:#}", e);
+                let _ = app.emit(
+                    "assistant-message",
+                    AssistantMessage::new("maid_sync_failed", Severity::Warning).with("error", format!("{:#}", e)),
+                );
+            }
+        }
+        if let Some(dir) = dir {
+            watch_sync_folder(&app, dir);
+        }
+    }
+    let _ = app.emit("preferences-changed", &preferences);
+    Ok(())
+}
+
+/// 遥控口的设置和令牌概要（令牌本身不会再给出来）
+#[tauri::command]
+async fn get_control_api() -> Result<serde_json::Value, String> {
+    let store = ControlApiStore::new().map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({
+        "settings": store.settings().map_err(|e| e.to_string())?,
+        "tokens": store.list_tokens().map_err(|e| e.to_string())?,
+    }))
+}
+
+/// 保存遥控口设置（重启恩兔后生效）
+#[tauri::command]
+async fn save_control_api_settings(settings: ApiSettings) -> Result<(), String> {
+    let store = ControlApiStore::new().map_err(|e| e.to_string())?;
+    store.save_settings(settings).map_err(|e| e.to_string())
+}
+
+/// 发一枚新的遥控令牌（secret 只在这次返回）
+#[tauri::command]
+async fn create_api_token(name: String, scope: Scope) -> Result<IssuedToken, String> {
+    let store = ControlApiStore::new().map_err(|e| e.to_string())?;
+    store
+        .create_token(&name, scope, n2n_process::unix_now_seconds())
+        .map_err(|e| e.to_string())
+}
+
+/// 轮换遥控令牌（旧的立即失效）
+#[tauri::command]
+async fn rotate_api_token(id: String) -> Result<IssuedToken, String> {
+    let store = ControlApiStore::new().map_err(|e| e.to_string())?;
+    store
+        .rotate_token(&id, n2n_process::unix_now_seconds())
+        .map_err(|e| e.to_string())
+}
+
+/// 吊销遥控令牌
+#[tauri::command]
+async fn revoke_api_token(id: String) -> Result<(), String> {
+    let store = ControlApiStore::new().map_err(|e| e.to_string())?;
+    store.revoke_token(&id).map_err(|e| e.to_string())
+}
+
+/// 被环境变量/启动参数覆盖的字段（工作台上提示主人这些改了也不会生效）
+#[tauri::command]
+async fn get_overridden_fields(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    Ok(state.config_manager.lock().unwrap().overridden_fields())
+}
+
+/// 指示簿的加密状态（加密了、还没解锁时前端要先请主人输入密码）
+#[tauri::command]
+async fn get_config_encryption(state: State<'_, AppState>) -> Result<ConfigEncryption, String> {
+    Ok(state.config_manager.lock().unwrap().encryption())
+}
+
+/// 用密码打开加密的指示簿
+#[tauri::command]
+async fn unlock_config(password: String, app: tauri::AppHandle) -> Result<(), String> {
+    // Argon2 要揉一会儿钥匙：放到阻塞线程池里
+    blocking(app, move |_, state| {
+        state.config_manager.lock().unwrap().unlock(&password).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 锁上指示簿（忘掉内存里的钥匙）
+#[tauri::command]
+async fn lock_config(state: State<'_, AppState>) -> Result<(), String> {
+    state.config_manager.lock().unwrap().lock();
+    Ok(())
+}
+
+/// 给指示簿设置（或更换）密码，之后加密保存
+#[tauri::command]
+async fn set_config_password(password: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        state.config_manager.lock().unwrap().set_password(&password).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 取消指示簿加密（要核对一次密码）
+#[tauri::command]
+async fn remove_config_password(password: String, app: tauri::AppHandle) -> Result<(), String> {
+    blocking(app, move |_, state| {
+        state.config_manager.lock().unwrap().remove_password(&password).map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// 遥控口收到的请求（令牌已核对）由这里照办
+fn handle_api_request(app: &tauri::AppHandle, endpoint: control_api::Endpoint) -> Result<serde_json::Value, String> {
+    let state = app.state::<AppState>();
+    match endpoint {
+        control_api::Endpoint::Status => Ok(status_json(&state.process.lock().unwrap())),
+        control_api::Endpoint::Connect { profile } => {
+            let config = {
+                let manager = state.config_manager.lock().unwrap();
+                match profile {
+                    Some(name) => manager.switch_profile(&name),
+                    None => manager.load(),
+                }
+            }
+            .map_err(|e| e.to_string())?;
+            start_connection(app, &state, config)?;
+            Ok(status_json(&state.process.lock().unwrap()))
+        }
+        control_api::Endpoint::Disconnect => {
+            let process = state.process.lock().unwrap();
+            process.stop().map_err(|e| e.to_string())?;
+            let _ = tray::update_tray_menu(app, &process.status());
+            let waiter = process.clone();
+            thread::spawn(move || waiter.wait_stopped_or_force());
+            Ok(status_json(&process))
+        }
+    }
+}
+
+/// 工作台现在实际用的配色（"light" / "dark"）
+#[tauri::command]
+async fn get_effective_theme(app: tauri::AppHandle) -> Result<String, String> {
+    Ok(theme::effective_theme(&app).to_string())
+}
+
 fn main() {
-    // 初始化日志系统
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    // 外部指示：环境变量 N2N_MAID_* 和启动参数 --<字段名> 叠加在指示簿上
+    let launch_args: Vec<String> = std::env::args().skip(1).collect();
+    // 被 pkexec 请来当小帮手的话：不碰指示簿和界面，专心守门铃
+    #[cfg(target_os = "linux")]
+    if launch_args.first().map(String::as_str) == Some(priv_helper::HELPER_ARG) {
+        std::process::exit(priv_helper::serve(&launch_args[1..]));
+    }
+    let (config_overrides, override_problems) = overrides::ConfigOverrides::collect(std::env::vars(), &launch_args);
+
+    // 初始化日志系统（主人打开了“写入系统日志”时，Linux 上同时交给 journald）
+    let system_log_enabled = ConfigManager::new()
+        .and_then(|manager| manager.load())
+        .map(|config| config_overrides.apply(&config).system_log)
+        .unwrap_or(false);
+    // 上岗登记本：接连几次没好好收工的话这次进安全模式（日志要在准备时就放到最详细）
+    let launch_guard = safe_mode::LaunchGuard::new().ok().map(Arc::new);
+    let safe_mode_expected = launch_guard.as_ref().and_then(|guard| guard.peek()).is_some();
+    system_log::init_logging(system_log_enabled, safe_mode_expected);
+    for problem in &override_problems {
+        log::warn!("外部指示没有采用：{}", problem);
+    }
+    if !config_overrides.is_empty() {
+        log::info!("外部指示覆盖了这些字段：{}", config_overrides.fields().join(", "));
+    }
+
+    // 先按门铃：已经有恩兔在岗的话，把启动参数交给她就退场
+    let doorbell = match single_instance::claim(&launch_args) {
+        Ok(single_instance::Claim::Forwarded) => {
+            log::info!("已经有恩兔在岗啦，启动参数已转交");
+            return;
+        }
+        Ok(single_instance::Claim::Primary(bell)) => Some(bell),
+        Err(e) => {
+            log::warn!("门铃没装好（不影响使用，只是无法转交后来的启动参数）：{}", e);
+            None
+        }
+    };
+
+    // 门铃抢到了才算真的上岗：在登记本上记一笔
+    let safe_mode = launch_guard.as_ref().and_then(|guard| match guard.begin() {
+        Ok(mode) => mode,
+        Err(e) => {
+            log::warn!("上岗登记没记上：{:#}", e);
+            None
+        }
+    });
+    if let Some(ref mode) = safe_mode {
+        log::warn!(
+            "接连 {} 次没好好收工，这次进安全模式：不自动建立通道，日志放到最详细",
+            mode.abnormal_exits
+        );
+    }
+    let safe_mode_on = safe_mode.is_some();
+    let launch_guard_for_events = launch_guard.clone();
+    let launch_guard_for_exit = launch_guard.clone();
 
     // 创建工作汇报通道
-    let (log_tx, log_rx) = mpsc::unbounded_channel();
+    let (log_tx, log_rx) = log_buffer::channel(log_buffer::DEFAULT_CAPACITY);
+    // 准备小喇叭（后台事件转发给前端）
+    let (event_tx, mut event_rx) = events::channel();
     
     // 唤醒恩兔酱（创建 N2N 进程管理器）
     let mut process = N2NProcess::new();
     process.set_log_sender(log_tx);
+    let side_event_tx = event_tx.clone();
+    process.set_event_sender(event_tx);
     
     // 准备指示簿（创建配置管理器）
-    let config_manager = ConfigManager::new().expect("无法创建配置管理器");
+    let mut config_manager = ConfigManager::new().expect("无法创建配置管理器");
+    config_manager.set_overrides(config_overrides);
+    // 恩兔自己的偏好：旧版记在方案里的配色要赶在下面改写指示簿之前搬过来
+    let mut preferences = PreferencesStore::new()
+        .and_then(|store| {
+            store.load_or_migrate(|| AppPreferences {
+                theme: config_manager
+                    .load_store()
+                    .ok()
+                    .and_then(|store| store.active_config().theme)
+                    .unwrap_or_default(),
+                ..Default::default()
+            })
+        })
+        .unwrap_or_else(|e| {
+            log::warn!("读取偏好本失败，先用默认偏好：{:#}", e);
+            AppPreferences::default()
+        });
+    // 开机自启以系统里的登记为准（主人可能在系统设置里关掉了）
+    if let Some(enabled) = autostart::is_enabled() {
+        preferences.autostart = enabled;
+    }
+    let theme_preference = preferences.theme;
+    // 旧版的单份指示簿：升级后第一次启动时搬进“Default”方案
+    let migration = config_manager.migrate_legacy().map_err(|e| e.to_string());
+    if let Err(ref e) = migration {
+        log::error!("迁移旧版配置失败：{}", e);
+    }
+    // 还明文写在指示簿里的密语：搬进系统保险箱
+    if let Err(e) = config_manager.migrate_secrets() {
+        log::warn!("把密语搬进系统保险箱失败：{:#}", e);
+    }
+    // 同步文件夹：先和别的机器对一次账（加密指示簿还没解锁的话，等下次保存时再对）
+    let sync_dir = preferences.sync_dir.as_ref().map(PathBuf::from);
+    config_manager.set_sync_dir(sync_dir.clone());
+    let startup_sync = match config_manager.sync_now() {
+        Ok(report) => report,
+        Err(e) => {
+            log::warn!("启动时和同步文件夹对账失败：{:#}", e);
+            config_sync::SyncReport::default()
+        }
+    };
+    // 翻一下交班本：上次有没有好好收工（读完之后交班小工人会重新记）
+    let last_session = session_state::SessionStateStore::new().ok().and_then(|store| store.load());
+    if let Some(ref record) = last_session {
+        log::warn!("上次工作没来得及收尾（最后确认于 {}，状态 {}）", record.updated_at, record.status);
+    }
+    // 翻开工作日志本
+    let history = Arc::new(Mutex::new(HistoryStore::new().expect("无法创建工作日志")));
+    let history_for_events = Arc::clone(&history);
 
     tauri::Builder::default()
-        .setup(|app| {
+        .setup(move |app| {
             // Windows 开机体检：缺 TAP 就先提示主人安装，避免后面连接时才摔跤
             #[cfg(target_os = "windows")]
             {
@@ -204,74 +2146,383 @@ fn main() {
 
             // 创建系统托盘
             tray::create_tray(&app.handle())?;
+
+            // 换上主人上次选的配色，并开始留意系统明暗切换
+            app.manage(theme::ThemeState::new(theme_preference));
+            theme::apply_preference(app.handle(), theme_preference);
+            theme::start_os_theme_watcher(app.handle());
+
+            // 守着门铃：后来的实例转交的参数由在岗的恩兔处理
+            if let Some(bell) = doorbell {
+                let handle = app.handle().clone();
+                single_instance::serve(bell, move |args| handle_launch_args(&handle, args));
+            }
+            // 启动参数里已经点名要连哪份方案的话，就不再自动连接
+            let connect_requested = single_instance::parse_launch_args(&launch_args)
+                .iter()
+                .any(|action| matches!(action, single_instance::LaunchAction::Connect { .. }));
+            if !launch_args.is_empty() {
+                handle_launch_args(app.handle(), launch_args);
+            }
+
+            // 主人勾了“打开恩兔就开始工作”：托盘准备好后自动建立通道（可能要等授权弹窗，放到后台去）
+            // 安全模式下不自动建立通道，先让主人看看体检单、改改指示
+            if !connect_requested {
+                let handle = app.handle().clone();
+                thread::spawn(move || {
+                    // 先按现在的网络挑好方案，再决定要不要自动建立通道
+                    follow_network(&handle, &network_aware::detect());
+                    // 上次留下的 edge 还占着网卡/管理口：先请主人决定接手还是请它下班，别让新的 edge 撞上去
+                    let config = handle.state::<AppState>().config_manager.lock().unwrap().load().unwrap_or_default();
+                    let orphans = scan_orphans(&handle);
+                    let blocked = orphans.iter().any(|orphan| orphan.in_the_way(&config));
+                    if !orphans.is_empty() {
+                        log::warn!("机器上有 {} 只没人照看的 edge", orphans.len());
+                        let _ = handle.emit(
+                            "assistant-message",
+                            AssistantMessage::new("maid_orphan_edges", Severity::Warning).with("count", orphans.len()),
+                        );
+                        let _ = handle.emit("orphan-edges", &orphans);
+                    }
+                    if !safe_mode_on && !blocked {
+                        auto_connect(&handle)
+                    }
+                });
+            }
+            if let Some(mode) = app.state::<AppState>().safe_mode.lock().unwrap().clone() {
+                let _ = app.emit(
+                    "assistant-message",
+                    AssistantMessage::new("maid_safe_mode", Severity::Warning).with("count", mode.abnormal_exits),
+                );
+                let _ = app.emit("safe-mode", mode);
+            }
+
+            // 先读一遍 edge 的说明书（拼参数、校验加密方式都要用，别等到建立通道时再问）
+            {
+                let config = app.state::<AppState>().config_manager.lock().unwrap().load().unwrap_or_default();
+                edge_caps::warm(effective_edge_path(app.handle(), &config));
+            }
+
+            // 认路：网络变了就按对照表换方案
+            {
+                let handle = app.handle().clone();
+                thread::spawn(move || keep_network_profile(handle));
+            }
+
+            // 盯着指示簿：主人直接改了 config.toml 时马上读进来
+            {
+                let handle = app.handle().clone();
+                let path = app.state::<AppState>().config_manager.lock().unwrap().path().to_path_buf();
+                if let Err(e) = config_watch::watch(&path, move || on_config_file_changed(&handle)) {
+                    log::warn!("盯不住指示簿（直接改 config.toml 后要重启恩兔才能生效）：{:#}", e);
+                }
+            }
+
+            // 同步文件夹：启动时拉下来的告诉主人，之后别的机器改了也马上并进来
+            announce_sync(app.handle(), &startup_sync);
+            if let Some(dir) = sync_dir.clone() {
+                watch_sync_folder(app.handle(), dir);
+            }
+
+            // 总部用动态域名时：连着的期间定期重新查地址
+            {
+                let handle = app.handle().clone();
+                thread::spawn(move || keep_supernode_address(handle));
+            }
+
+            // 换密语：到了和小伙伴约好的时间换上新密语
+            {
+                let handle = app.handle().clone();
+                thread::spawn(move || keep_key_rotation(handle));
+            }
+
+            // 主人打开了遥控口的话，守着它等脚本来敲门
+            match ControlApiStore::new().and_then(|store| Ok((store.settings()?, store))) {
+                Ok((settings, store)) if settings.enabled => {
+                    let handle = app.handle().clone();
+                    if let Err(e) = control_api::serve(&settings, store, move |endpoint| {
+                        handle_api_request(&handle, endpoint)
+                    }) {
+                        log::warn!("遥控口没打开：{:#}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("读取遥控口设置失败：{:#}", e),
+            }
+
+            // 交班本：工作期间定期记下状态，好好收工后撕掉
+            match session_state::SessionStateStore::new() {
+                Ok(store) => {
+                    let process = app.state::<AppState>().process.lock().unwrap().clone();
+                    thread::spawn(move || keep_session_record(process, store));
+                }
+                Err(e) => log::warn!("交班本打不开（重启后就看不到上次的情况了）：{}", e),
+            }
+
+            // 传话筒：跟着通道状态开张和收起
+            {
+                let state = app.state::<AppState>();
+                let process = state.process.lock().unwrap().clone();
+                let forwarder = Arc::clone(&state.port_forwarder);
+                thread::spawn(move || keep_port_forwards(process, forwarder));
+            }
+
+            // 中转节点：跟着通道状态打开和恢复系统的 IP 转发
+            {
+                let state = app.state::<AppState>();
+                let process = state.process.lock().unwrap().clone();
+                let forwarding = Arc::clone(&state.ip_forwarding);
+                thread::spawn(move || keep_ip_forwarding(process, forwarding));
+            }
+
+            // 巡查：状态机卡住时自己修好，不用主人重启恩兔
+            {
+                let handle = app.handle().clone();
+                let process = app.state::<AppState>().process.lock().unwrap().clone();
+                thread::spawn(move || keep_watch(handle, process));
+            }
+
+            // 桌面提示：托盘跟着状态走，需要时让桌面的网络面板也显示 VPN 已连接
+            {
+                let handle = app.handle().clone();
+                let process = app.state::<AppState>().process.lock().unwrap().clone();
+                thread::spawn(move || keep_desktop_indicator(handle, process));
+            }
+
+            // 唤醒闹钟：到点叫醒同伴（比如备份前的 NAS）
+            {
+                let handle = app.handle().clone();
+                thread::spawn(move || keep_wake_schedule(handle));
+            }
+
+            // 状态快照：给不方便开遥控口的脚本/监控程序读
+            {
+                let state = app.state::<AppState>();
+                let config_manager = Arc::clone(&state.config_manager);
+                let process = state.process.lock().unwrap().clone();
+                thread::spawn(move || keep_status_snapshot(config_manager, process));
+            }
+
+            // 填写开门体检单（要问 edge 版本，放到后台去做），填好后广播给前端
+            {
+                let state = app.state::<AppState>();
+                let config_manager = Arc::clone(&state.config_manager);
+                let startup_report = Arc::clone(&state.startup_report);
+                let handle = app.handle().clone();
+                thread::spawn(move || {
+                    let report = {
+                        let manager = config_manager.lock().unwrap();
+                        startup::collect(&manager, migration, last_session)
+                    };
+                    log::info!("开门体检完成：{:?}", report);
+                    let failed = report.checks.iter().filter(|c| !c.ok).count();
+                    let readiness = if failed == 0 {
+                        AssistantMessage::new("maid_ready", Severity::Success)
+                    } else {
+                        AssistantMessage::new("maid_not_ready", Severity::Warning).with("count", failed)
+                    };
+                    *startup_report.lock().unwrap() = Some(report.clone());
+                    let _ = handle.emit("startup-report", report);
+                    let _ = handle.emit("assistant-message", readiness);
+                });
+            }
+
+            // 小喇叭转发小工人：把后台事件原样广播给前端
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                while let Some(event) = event_rx.recv().await {
+                    // 上岗登记本：edge 意外退出记一次，连上了就清零
+                    if let Some(ref guard) = launch_guard_for_events {
+                        let recorded = match &event {
+                            ProcessEvent::SessionEnded(summary) if summary.reason == "edge_exited" => guard.record_edge_crash(),
+                            ProcessEvent::Assistant(message) if message.id.starts_with("maid_connected") => guard.reset(),
+                            _ => Ok(()),
+                        };
+                        if let Err(e) = recorded {
+                            log::debug!("{:#}", e);
+                        }
+                    }
+                    if let ProcessEvent::SessionEnded(summary) = &event {
+                        if let Err(e) = history_for_events.lock().unwrap().append(summary) {
+                            log::warn!("工作日志没记上：{}", e);
+                        }
+                        let (id, severity, status) = if summary.reason == "edge_exited" {
+                            (system_log::EVENT_UNEXPECTED_EXIT, system_log::Severity::Error, "error")
+                        } else {
+                            (system_log::EVENT_SESSION_ENDED, system_log::Severity::Info, "disconnected")
+                        };
+                        report_system_event(
+                            &handle.state::<AppState>(),
+                            id,
+                            severity,
+                            format!("工作结束（{}），持续 {} 秒", summary.reason, summary.duration_secs),
+                            status,
+                            summary.error.clone(),
+                        );
+                    }
+                    let _ = handle.emit(event.name(), event.payload());
+                }
+            });
             
             log::info!("恩兔酱准备就绪，随时为主人服务！");
+            // 在 systemd 下跑的话（网关模式导出的服务）告诉它恩兔已经就位
+            server_mode::notify("READY=1");
             Ok(())
         })
         .on_window_event(|window, event| {
+            if let tauri::WindowEvent::ThemeChanged(os_theme) = event {
+                theme::on_os_theme_changed(window.app_handle(), *os_theme);
+                return;
+            }
             let tauri::WindowEvent::CloseRequested { api, .. } = event else {
                 return;
             };
 
             let app = window.app_handle();
             let state = app.state::<AppState>();
-            let process = Arc::clone(&state.process);
 
-            // 只有 edge 在工作时才拦截关闭：给主人展示“正在收拾工具”的提示
-            if !process.lock().unwrap().is_running() {
+            // 主人想让恩兔躲进托盘：只藏起窗口，通道照常工作（从托盘选“退出”才真的收工）
+            if state.preferences.lock().unwrap().close_to_tray && !state.quitting.load(Ordering::SeqCst) {
+                api.prevent_close();
+                let _ = window.hide();
                 return;
             }
 
-            api.prevent_close();
-            let _ = window.show();
-            let _ = window.set_focus();
-            let _ = window.emit("app-exit-waiting", ());
-
-            {
-                let proc = process.lock().unwrap();
-                proc.log_info("主人要关门啦，恩兔先把 edge 温柔地收拾好（正在等待退出）…");
-                if let Err(e) = proc.stop() {
-                    proc.log_info(format!("优雅断开失败：{}（将尝试强制停止）", e));
-                    let _ = proc.stop_force();
-                }
+            // 只有 edge 在工作时才拦截关闭：收拾工具交给关门流程（见 shutdown.rs），收拾完它会真的退出
+            if shutdown::may_exit(app) {
+                return;
             }
-
-            let app = app.clone();
-            thread::spawn(move || {
-                // 给 edge 一点时间收尾；如果它不肯走，stop() 内部也会让“强制停止”按钮可用
-                // 这里仅做退出等待，避免主人点击关闭后程序直接消失、留下残影进程
-                let deadline = std::time::Instant::now() + Duration::from_secs(5);
-                loop {
-                    let running = process.lock().unwrap().is_running();
-                    if !running {
-                        break;
-                    }
-                    if std::time::Instant::now() >= deadline {
-                        let proc = process.lock().unwrap();
-                        proc.log_info("等得有点久了…恩兔要改用强制停止来清理残影进程了。");
-                        let _ = proc.stop_force();
-                        break;
-                    }
-                    thread::sleep(Duration::from_millis(200));
-                }
-                app.exit(0);
-            });
+            api.prevent_close();
+            shutdown::begin(app);
         })
         .manage(AppState {
             process: Arc::new(Mutex::new(process)),
+            connections: Arc::new(ConnectionManager::new(Some(side_event_tx))),
             config_manager: Arc::new(Mutex::new(config_manager)),
             log_rx: Arc::new(Mutex::new(Some(log_rx))),
+            history,
+            wake_history: Arc::new(Mutex::new(
+                HistoryStore::named("wake-history.jsonl").expect("无法创建唤醒流水账"),
+            )),
+            startup_report: Arc::new(Mutex::new(None)),
+            safe_mode: Arc::new(Mutex::new(safe_mode)),
+            launch_guard,
+            pending_deep_link: Arc::new(Mutex::new(None)),
+            port_forwarder: Arc::new(port_forward::PortForwarder::new()),
+            ip_forwarding: Arc::new(ip_forward::Forwarding::new()),
+            preferences: Arc::new(Mutex::new(preferences)),
+            quitting: Arc::new(AtomicBool::new(false)),
         })
         .invoke_handler(tauri::generate_handler![
             get_config,
             save_config,
+            list_profiles,
+            set_profile_meta,
+            switch_profile,
+            save_profile,
+            delete_profile,
+            import_profile,
+            export_config,
+            import_config,
+            encode_share_link,
+            get_app_info,
+            export_command_line,
+            export_service_setup,
+            apply_mtu,
+            random_mac_address,
+            diagnose_nat,
+            wake_peer,
+            wake_all_peers,
+            get_wake_history,
+            get_network_rules,
+            save_network_rules,
+            get_network_snapshot,
+            get_roster,
+            save_roster,
+            suggest_roster_ip,
+            import_roster,
+            export_roster,
+            decode_share_link,
+            start_key_rotation,
+            cancel_key_rotation,
+            list_templates,
+            apply_template,
+            unlock_profile,
+            trust_supernode_key,
+            trust_edge_binary,
+            list_backups,
+            restore_backup,
+            list_supernodes,
+            save_supernode,
+            delete_supernode,
+            measure_supernode_rtt,
             connect,
+            reconnect,
             disconnect,
+            list_connections,
             disconnect_force,
+            pause,
+            resume,
+            switch_supernode,
             get_status,
+            suspend_auto_reconnect,
+            cancel_reconnect,
             get_logs,
+            get_log_stats,
             get_peers,
+            ping_all_peers,
+            resolve_peer_names,
+            export_peers,
+            get_edge_stats,
+            get_warnings,
+            get_port_forwards,
+            get_supernode_stats,
+            get_session_history,
+            export_logs,
+            get_elevation_status,
+            get_startup_report,
+            get_safe_mode,
+            leave_safe_mode,
+            find_orphan_edges,
+            adopt_orphan_edge,
+            terminate_orphan_edge,
+            take_deep_link,
+            describe_error,
+            validate_config,
+            get_edge_capabilities,
+            get_edge_info,
+            discover_edge_binaries,
+            get_preferences,
+            save_preferences,
+            get_effective_theme,
+            get_control_api,
+            save_control_api_settings,
+            create_api_token,
+            rotate_api_token,
+            revoke_api_token,
+            get_overridden_fields,
+            get_config_encryption,
+            unlock_config,
+            lock_config,
+            set_config_password,
+            remove_config_password,
         ])
-        .run(tauri::generate_context!())
-        .expect("启动 Tauri 应用失败");
+        .build(tauri::generate_context!())
+        .expect("启动 Tauri 应用失败")
+        .run(move |app, event| match event {
+            // 不管是谁要求退出（最后一个窗口关了、系统要注销），edge 还在工作就先走关门流程
+            tauri::RunEvent::ExitRequested { api, .. } if !shutdown::may_exit(app) => {
+                api.prevent_exit();
+                shutdown::begin(app);
+            }
+            // 好好收工：在上岗登记本上划掉（下次上岗就不算意外退出）
+            tauri::RunEvent::Exit => {
+                if let Some(ref guard) = launch_guard_for_exit {
+                    if let Err(e) = guard.finish() {
+                        log::warn!("{:#}", e);
+                    }
+                }
+            }
+            _ => {}
+        });
 }
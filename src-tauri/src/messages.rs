@@ -0,0 +1,236 @@
+//! 恩兔的话术本（后端自带的 error_ / warning_ / op_ 编号翻译）
+//!
+//! 后端对外只给稳定的编号（例如 `error_mac_in_use`），以前这些编号的意思只写在前端的 i18n 里；
+//! 托盘提示、命令行、系统通知这些不经过前端的地方就只能看到一串编号。
+//! 这里由后端自己保管一份中英文话术，`describe()` 按语言查，查不到就退回另一种语言。
+//!
+//! 备注：前端 `src/i18n.ts` 里同名编号的文案应与这里保持一致，改一边记得改另一边。
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Zh,
+    En,
+}
+
+impl Locale {
+    /// 按 BCP 47 风格的语言标签挑语言（zh、zh-CN、zh_TW 都算中文，其他一律英文）
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("zh") {
+            Locale::Zh
+        } else {
+            Locale::En
+        }
+    }
+
+    fn bundle(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::Zh => ZH,
+            Locale::En => EN,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Locale::Zh => Locale::En,
+            Locale::En => Locale::Zh,
+        }
+    }
+}
+
+/// 查一条编号的话术（当前语言没有就退回另一种语言）
+pub fn describe(code: &str, locale: Locale) -> Option<&'static str> {
+    lookup(code, locale).or_else(|| lookup(code, locale.other()))
+}
+
+/// 查话术，查不到时原样返回编号/原文（方便直接拿去展示）
+pub fn describe_or_raw(text: &str, locale: Locale) -> String {
+    describe(text, locale).map(str::to_string).unwrap_or_else(|| text.to_string())
+}
+
+fn lookup(code: &str, locale: Locale) -> Option<&'static str> {
+    locale
+        .bundle()
+        .iter()
+        .find(|(k, _)| *k == code)
+        .map(|(_, v)| *v)
+}
+
+const ZH: &[(&str, &str)] = &[
+    ("error_mac_in_use", "这个硬件编号已经有人用了，可能是其他设备还在工作，或者总部还没注销旧记录"),
+    ("error_ip_in_use", "这个地址已经有人占了，要不换一个试试？"),
+    ("error_mac_or_ip_in_use", "总部说这台设备的硬件编号/地址还没释放呢（可能上次断开太匆忙）。主人可以先等一小会儿让总部清理，或者点“断开”让恩兔发出 SIGINT 再重新连接。"),
+    ("error_tap_create_failed", "恩兔没权限创建设备，需要主人帮忙授权呢"),
+    ("error_tap_busy", "虚拟网卡被占用了（Device or resource busy）。可能已经有别的 edge 在工作，或者 TAP 名称冲突；请先断开其它连接/重启相关服务，或在设置里换个 TAP 设备名再试。"),
+    ("error_supernode_unreachable", "联系不上总部了，是不是地址写错了，或者网络断了？"),
+    ("error_tap_missing", "虚拟网卡不见了（可能是睡眠唤醒或者驱动出了问题）"),
+    ("error_tap_down", "虚拟网卡被停用了"),
+    ("error_tap_address_lost", "虚拟网卡上的地址掉了"),
+    ("error_auth_failed", "门被锁住了，检查一下暗号和密语对不对吧"),
+    ("error_permission_denied", "权限不够呀，Linux 系统需要给工具箱特殊权限才行"),
+    ("error_edge_exited", "呜呜…恩兔的工具箱突然停工了（edge 意外退出）。请打开“工作汇报”看看最后一条报错线索。"),
+    ("error_wsagetlasterror", "Windows 环境下奇怪的错误发生了，但是似乎不影响使用哦"),
+    ("error_version_mismatch", "恩兔和总部说的不是同一种“方言”（edge 与 supernode 的 n2n 大版本不一致，比如 v2 对 v3）。请让 edge 和 supernode 使用相同的大版本（可以用 edge -h 查看版本），或者换一个匹配的总部地址～"),
+    ("error_no_network", "恩兔找不到任何可以出门的路（没有网络，可能开了飞行模式或者网线松了），等网络恢复再试吧"),
+    ("error_clock_skew", "这台电脑的时间和标准时间差得太多了，开了包头加密（-H）时总部会拒收；请先同步一下系统时间再连接"),
+    ("error_supernode_key_changed", "总部的公钥和上次记下的不一样！可能有人在冒充总部，恩兔已经拒绝出门。请先向管理员确认新公钥是否可信"),
+    ("error_invalid_supernode", "总部地址要写成 host:port 的样子（比如 vpn.example.com:7777，IPv6 写成 [::1]:7777）"),
+    ("error_invalid_supernode_dns", "总部域名只填域名就好（比如 example.com，不带端口），恩兔会去查它的 SRV 记录"),
+    ("error_community_empty", "还没有填写小组暗号哦"),
+    ("error_community_too_long", "小组暗号最多 19 个字节（中文一个字占 3 个字节），再长 edge 会把它截短"),
+    ("error_invalid_static_ip", "指定地址要写成 10.0.0.2 或 10.0.0.2/24 的样子"),
+    ("error_invalid_ip_mode", "地址分配方式只能是自动分配或手动指定"),
+    ("error_invalid_mtu", "通道宽度（MTU）要在 576 到 1500 之间"),
+    ("error_local_port_in_use", "固定的本机端口已经被别的程序占用了，换一个端口或者先关掉占用它的程序吧"),
+    ("error_invalid_register_interval", "报到间隔要在 1 到 3600 秒之间（留空用 edge 默认的 20 秒）"),
+    ("error_invalid_local_port", "本机端口要在 1 到 65535 之间，而且不能和 edge 的管理口（默认 5644）相同"),
+    ("error_invalid_management_port", "管理口要在 1 到 65535 之间"),
+    ("error_invalid_mac", "硬件编号（MAC）要写成 02:ab:cd:ef:12:34 这样，而且不能是组播地址或全 0"),
+    ("error_cipher_needs_key", "选了加密方式就要填保密密语（只有“不加密”可以不填）"),
+    ("error_key_rotation_time_passed", "换密语的时间要约在以后"),
+    ("error_sync_config_encrypted", "加密指示簿和同步文件夹只能二选一：同步文件夹里只能放明文方案。先关掉其中一个吧"),
+    ("error_sync_dir_missing", "找不到这个同步文件夹，先确认它已经建好（网盘客户端已经同步下来）吧"),
+    ("error_cipher_unsupported", "这个版本的 edge 不会这种加密方式，换一种或者换个新一点的 edge 吧"),
+    ("error_header_encryption_unsupported", "这个版本的 edge 不会包头加密（-H 要 2.8 以上）"),
+    ("error_compression_unsupported", "这只 edge 不会这种压缩方式（zstd 要 edge 编译时带上才有），换成 LZO 吧"),
+    ("error_mtu_live_not_connected", "通道连上之后才能直接修改通道宽度；没连上时保存指示就好"),
+    ("error_edge_path_missing", "在这个位置找不到工具箱（edge 可执行文件），检查一下路径吧"),
+    ("error_edge_binary_changed", "工具箱（edge）和上次记下的不一样！可能被人掉包了，恩兔已经拒绝出门。如果是主人自己升级的，确认指纹后再信任它"),
+    ("error_edge_not_allowlisted", "工具箱（edge）的指纹不在白名单里，恩兔已经拒绝出门。请核对 edge 的来源，或者把它的指纹加进白名单"),
+    ("error_edge_integrity_unverifiable", "没能核对工具箱（edge）的指纹（读不了 edge 或者指纹本），恩兔宁可先不出门。检查一下文件权限，或者在设置里打开“指纹对不上也照常出门”"),
+    ("error_invalid_edge_sha256", "edge 指纹白名单里每一条都要是 64 位十六进制的 SHA-256"),
+    ("error_invalid_port_forward", "传话筒规则的监听地址和目标都要写成 host:port"),
+    ("error_invalid_wake_rule", "唤醒规则要写明同伴的 MAC 和虚拟地址，时间写成 22:00 这样（留空只能手动叫）"),
+    ("error_connection_tap_clash", "另一条通道已经在用同一块虚拟网卡了，请给这份方案换一个网卡名（设备名称）"),
+    ("error_connection_mgmt_port_clash", "另一条通道的 edge 已经占着同一个管理口了，请给这份方案换一个管理口"),
+    ("error_connection_local_port_clash", "另一条通道的 edge 已经占着同一个本机端口了，请换一个或留空"),
+    ("error_orphan_not_found", "这只 edge 已经不在了（可能自己下班了）"),
+    ("error_orphan_mismatch", "这只 edge 的小组或总部和当前方案对不上，不能接手，只能请它下班"),
+    ("error_orphan_terminate_failed", "这只 edge 不肯下班（可能需要管理员权限）"),
+    ("error_invalid_port_knock", "敲门步骤要写端口（1 到 65535），等待不超过 10000 毫秒；UDP 内容用 hex: 开头时要是成对的十六进制，TCP 敲门不带内容"),
+    ("error_invalid_route", "路由要写成 IPv4 网段、子网掩码（或前缀长度）和网关，网段里不能带主机位"),
+    ("error_edge_option_managed", "高级选项里有基本设置已经管着的开关（-c、-l、-k、-a 这些），请在基本设置里改"),
+    ("error_edge_option_unknown", "高级选项里有 edge 不认识的开关，检查一下拼写吧"),
+    ("error_edge_option_needs_value", "高级选项里有开关需要填一个值"),
+    ("error_edge_option_no_value", "高级选项里有开关不需要值，请把值清空"),
+    ("error_invalid_stop_timeout", "停止等待时间要在 1 到 300 秒之间"),
+    ("error_invalid_snapshot_interval", "状态快照间隔要在 5 到 3600 秒之间"),
+    ("error_invalid_supernode_recheck", "重新查总部地址的间隔要在 30 到 86400 秒之间（填 0 表示不查）"),
+    ("error_invalid_supernode_failover", "备用总部的每一条都要写成 host:port"),
+    ("error_roster_invalid_subnet", "花名册的网段要写成 CIDR，例如 10.0.0.0/24"),
+    ("error_roster_invalid_ip", "花名册里有成员的地址不是有效的 IPv4 地址"),
+    ("error_roster_ip_outside_subnet", "花名册里有成员的地址不在小组网段内"),
+    ("error_roster_duplicate_ip", "花名册里有两位成员分到了同一个地址"),
+    ("error_config_locked", "指示簿上了锁，请主人先输入密码打开"),
+    ("error_config_wrong_password", "密码不对（或者指示簿被改动过），恩兔打不开"),
+    ("error_config_password_empty", "密码不能是空的哦"),
+    ("warning_community_truncated", "小组暗号太长啦，edge 把它截短了；请确认伙伴们用的是同一个暗号哦"),
+    ("warning_unknown_option", "edge 不认识某个奇妙指令，已经跳过了；可以检查一下高级设置里的参数"),
+    ("warning_mtu", "通道宽度（MTU）好像不太合适，edge 给出了提醒"),
+    ("op_already_running", "N2N 进程已在运行"),
+    ("op_not_running", "N2N 进程未运行"),
+    ("op_already_stopping", "恩兔正在收拾工具呢，请稍等一下（等不及可以选择强制停止）"),
+    ("op_stop_cooling_down", "恩兔刚请 edge 停下，先看看它走不走，过几秒再试吧"),
+    ("op_invalid_transition", "恩兔现在的状态没办法执行这个操作哦"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("error_mac_in_use", "This hardware ID is already taken, maybe another device is using it or head office hasn't cleared the old record"),
+    ("error_ip_in_use", "This address is occupied, shall we try another one?"),
+    ("error_mac_or_ip_in_use", "Head office says your MAC/IP is still marked as in-use (maybe the last session wasn't released yet). You can wait a bit, or click “Take a Break” to send SIGINT and try again."),
+    ("error_tap_create_failed", "N-Too doesn't have permission to create device, Master needs to grant authorization"),
+    ("error_tap_busy", "The virtual adapter is busy (Device or resource busy). Another edge may still be running, or the TAP name conflicts. Disconnect other sessions or change the TAP device name and retry."),
+    ("error_supernode_unreachable", "Can't reach head office, is the address wrong or network disconnected?"),
+    ("error_tap_missing", "The virtual adapter is gone (sleep/resume or a driver problem?)"),
+    ("error_tap_down", "The virtual adapter has been disabled"),
+    ("error_tap_address_lost", "The virtual adapter lost its address"),
+    ("error_auth_failed", "The door is locked, please check if the code and password are correct"),
+    ("error_permission_denied", "Not enough permission, Linux systems need special capabilities for the toolbox"),
+    ("error_edge_exited", "Oops… the toolbox stopped unexpectedly (edge exited). Check the “Work Report” for the last error line."),
+    ("error_wsagetlasterror", "A strange error occurred in Windows environment, but it seems not to affect usage"),
+    ("error_version_mismatch", "N-Too and head office speak different dialects (edge and supernode run different n2n major versions, e.g. v2 vs v3). Use the same major version on both sides (check with edge -h), or pick a matching supernode~"),
+    ("error_no_network", "N-Too can't find any way out (no network; airplane mode or a loose cable?). Try again once the network is back"),
+    ("error_clock_skew", "This computer's clock is too far off; with header encryption (-H) head office will reject the packets. Sync the system time and connect again"),
+    ("error_supernode_key_changed", "The supernode's public key differs from the one remembered last time! Someone may be impersonating head office, so N-Too refused to connect. Check with your administrator before trusting the new key"),
+    ("error_invalid_supernode", "The supernode must look like host:port (e.g. vpn.example.com:7777, or [::1]:7777 for IPv6)"),
+    ("error_invalid_supernode_dns", "Enter just a domain for supernode discovery (e.g. example.com, no port); its SRV records will be looked up"),
+    ("error_community_empty", "The secret code is empty"),
+    ("error_community_too_long", "The secret code can be at most 19 bytes (a Chinese character takes 3); edge would truncate anything longer"),
+    ("error_invalid_static_ip", "The static address must look like 10.0.0.2 or 10.0.0.2/24"),
+    ("error_invalid_ip_mode", "The address mode must be DHCP or static"),
+    ("error_invalid_mtu", "The tunnel width (MTU) must be between 576 and 1500"),
+    ("error_local_port_in_use", "The pinned local port is already used by another program; pick another port or close that program first"),
+    ("error_invalid_register_interval", "The registration interval must be between 1 and 3600 seconds (leave it empty for edge's default of 20)"),
+    ("error_invalid_local_port", "The local port must be between 1 and 65535 and can't be the same as edge's management port (5644 by default)"),
+    ("error_invalid_management_port", "The management port must be between 1 and 65535"),
+    ("error_invalid_mac", "The hardware ID (MAC) must look like 02:ab:cd:ef:12:34 and can't be a multicast or all-zero address"),
+    ("error_cipher_needs_key", "An encryption key is required for this cipher (only \"no encryption\" works without one)"),
+    ("error_key_rotation_time_passed", "The key switch time must be in the future"),
+    ("error_sync_config_encrypted", "Config encryption and the sync folder can't be used together: the sync folder copy can only be plain text. Turn one of them off first"),
+    ("error_sync_dir_missing", "The sync folder doesn't exist; make sure it has been created (and synced by your cloud client) first"),
+    ("error_cipher_unsupported", "This edge build doesn't support that cipher; pick another one or use a newer edge"),
+    ("error_header_encryption_unsupported", "This edge build doesn't support header encryption (-H needs 2.8 or later)"),
+    ("error_compression_unsupported", "This edge build doesn't support that compression (zstd must be compiled in); try LZO instead"),
+    ("error_mtu_live_not_connected", "The tunnel width can only be changed live while connected; otherwise just save the settings"),
+    ("error_edge_path_missing", "No toolbox (edge executable) was found at this path; please check it"),
+    ("error_edge_binary_changed", "The toolbox (edge) differs from the one remembered last time! It may have been replaced, so N-Too refused to connect. If you upgraded it yourself, check the fingerprint before trusting it"),
+    ("error_edge_not_allowlisted", "The toolbox (edge) fingerprint is not on the allow-list, so N-Too refused to connect. Check where this edge came from, or add its fingerprint to the allow-list"),
+    ("error_edge_integrity_unverifiable", "The toolbox (edge) fingerprint could not be checked (edge or the fingerprint book is unreadable), so N-Too refused to connect. Check the file permissions, or turn on \"Start even if the fingerprint does not match\" in the settings"),
+    ("error_invalid_edge_sha256", "Every entry in the edge allow-list must be a 64-digit hexadecimal SHA-256"),
+    ("error_invalid_port_forward", "Port forwarding rules need a listen address and a target in host:port form"),
+    ("error_invalid_wake_rule", "Wake rules need the peer's MAC and virtual address, and a time like 22:00 (leave it empty for manual wake only)"),
+    ("error_connection_tap_clash", "Another tunnel is already using the same virtual adapter; give this profile a different device name"),
+    ("error_connection_mgmt_port_clash", "Another tunnel's edge already uses the same management port; give this profile a different one"),
+    ("error_connection_local_port_clash", "Another tunnel's edge already uses the same local port; pick another or leave it empty"),
+    ("error_orphan_not_found", "That edge is no longer running"),
+    ("error_orphan_mismatch", "That edge's community or supernode doesn't match the current profile; it can only be stopped"),
+    ("error_orphan_terminate_failed", "That edge refused to stop (administrator rights may be needed)"),
+    ("error_invalid_port_knock", "Knock steps need a port (1-65535) and a wait of at most 10000 ms; UDP payloads starting with hex: must be whole hex bytes, and TCP knocks carry no payload"),
+    ("error_invalid_route", "Routes need an IPv4 network, a netmask (or prefix length) and a gateway, and the network must not have host bits set"),
+    ("error_edge_option_managed", "An advanced option duplicates a basic setting (-c, -l, -k, -a, ...); change it in the basic settings instead"),
+    ("error_edge_option_unknown", "An advanced option is not a flag edge knows; check the spelling"),
+    ("error_edge_option_needs_value", "An advanced option needs a value"),
+    ("error_edge_option_no_value", "An advanced option does not take a value; please clear it"),
+    ("error_invalid_stop_timeout", "The stop timeout must be between 1 and 300 seconds"),
+    ("error_invalid_snapshot_interval", "The status snapshot interval must be between 5 and 3600 seconds"),
+    ("error_invalid_supernode_recheck", "The supernode re-check interval must be between 30 and 86400 seconds (0 turns it off)"),
+    ("error_invalid_supernode_failover", "Every backup supernode must be in host:port form"),
+    ("error_roster_invalid_subnet", "The roster subnet must be in CIDR form, e.g. 10.0.0.0/24"),
+    ("error_roster_invalid_ip", "A roster member's address is not a valid IPv4 address"),
+    ("error_roster_ip_outside_subnet", "A roster member's address is outside the community subnet"),
+    ("error_roster_duplicate_ip", "Two roster members share the same address"),
+    ("error_config_locked", "The configuration is encrypted; please enter the password to unlock it"),
+    ("error_config_wrong_password", "Wrong password (or the configuration file was modified)"),
+    ("error_config_password_empty", "The password cannot be empty"),
+    ("warning_community_truncated", "The secret code is too long and edge truncated it; make sure all peers use the same one"),
+    ("warning_unknown_option", "edge didn't recognize one of the special orders and skipped it; check the advanced settings"),
+    ("warning_mtu", "edge complained about the tunnel width (MTU)"),
+    ("op_already_running", "The N2N process is already running"),
+    ("op_not_running", "The N2N process is not running"),
+    ("op_already_stopping", "N-Too is still packing up; please wait a moment (or force stop if you can't wait)"),
+    ("op_stop_cooling_down", "N-Too just asked edge to stop; give it a few seconds before trying again"),
+    ("op_invalid_transition", "N-Too can't do that right now"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundles_cover_the_same_codes() {
+        for (code, _) in ZH {
+            assert!(lookup(code, Locale::En).is_some(), "英文话术缺少 {}", code);
+        }
+        for (code, _) in EN {
+            assert!(lookup(code, Locale::Zh).is_some(), "中文话术缺少 {}", code);
+        }
+    }
+
+    #[test]
+    fn test_describe_picks_locale_and_falls_back_to_raw() {
+        assert_eq!(Locale::from_tag("zh-CN"), Locale::Zh);
+        assert_eq!(Locale::from_tag("en-US"), Locale::En);
+        assert!(describe("error_tap_busy", Locale::En).unwrap().starts_with("The virtual adapter"));
+        assert_eq!(describe_or_raw("edge: something odd", Locale::Zh), "edge: something odd");
+    }
+}
@@ -0,0 +1,424 @@
+//! 敲 edge 的管理口（UDP Management API）
+//!
+//! edge 在本机 UDP 端口（默认 5644，`-t` 可以改）上开着一扇管理口，讲的是一行一问、JSON 作答的方言：
+//! - 恩兔递一张纸条：`r <tag>[:1:<口令>] <方法>`（读）或 `w ...`（写，例如 `stop`）；
+//!   请 edge 下班优先走 `stop`：edge 早就降权了，从这里请比请 root 代发信号、或者 Windows 上的 taskkill 都靠谱；
+//! - edge 回一串带同一个 tag 的包：`begin`、若干 `row`、`end`，出错时是 `error`。
+//!
+//! 本机的 supernode 也讲同一套方言。这里只管传纸条、收回信，
+//! 心跳怎么算“连上了”、同伴名册怎么缓存由 `n2n_process` 决定；
+//! `query_stats` 把几张表（timestamps、packetstats、supernodes）拼成一份实时统计，给状态面板用。
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::n2n_process;
+
+/// 管理口查询超时 - socket 读取超时（毫秒）
+const READ_TIMEOUT_MS: u64 = 200;
+/// 管理口查询超时 - 总等待时间（毫秒）
+const DEADLINE_MS: u64 = 1500;
+/// 管理口的门牌（恩兔只敲本机 127.0.0.1）
+pub(crate) const HOST: &str = "127.0.0.1";
+
+/// tag 自增器（让每次点名都有自己的编号）
+static TAG_COUNTER: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Debug, serde::Deserialize)]
+struct Packet {
+    #[serde(rename = "_tag")]
+    tag: Option<String>,
+    #[serde(rename = "_type")]
+    kind: String,
+    error: Option<String>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// `timestamps` 的那一行
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct TimestampsRow {
+    pub start_time: u64,
+    pub last_super: u64,
+    pub last_p2p: u64,
+}
+
+/// `packetstats` 的一行（transop、p2p、super、super_broadcast ……）
+#[derive(Debug, Clone, serde::Deserialize)]
+pub(crate) struct PacketStatsRow {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub tx_pkt: Option<u64>,
+    pub rx_pkt: Option<u64>,
+}
+
+/// `supernodes` 的一行
+#[derive(Debug, Clone, serde::Deserialize)]
+struct SupernodeRow {
+    sockaddr: Option<String>,
+    current: Option<u64>,
+}
+
+pub(crate) fn next_tag() -> String {
+    let n = TAG_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}", n % 1000)
+}
+
+/// 拼一张纸条（`verb` 是 `r` 读或 `w` 写），返回（tag，纸条）
+fn build_request(verb: char, method: &str, password: Option<&str>) -> (String, String) {
+    let tag = next_tag();
+    let options = match password {
+        Some(pw) if !pw.is_empty() => format!("{tag}:1:{pw}"),
+        _ => tag.clone(),
+    };
+    // 备注：协议要求单行、<=80 bytes；这里 method 都很短
+    (tag, format!("{verb} {options} {method}\n"))
+}
+
+pub(crate) fn send_request(
+    addr: (&str, u16),
+    req: &str,
+    prepare_hint: &'static str,
+    send_hint: &'static str,
+) -> Result<UdpSocket> {
+    let socket = UdpSocket::bind(("127.0.0.1", 0)).context(prepare_hint)?;
+    socket
+        .set_read_timeout(Some(Duration::from_millis(READ_TIMEOUT_MS)))
+        .ok();
+    socket.send_to(req.as_bytes(), addr).context(send_hint)?;
+    Ok(socket)
+}
+
+fn parse_packet(buf: &[u8]) -> Option<Packet> {
+    let mut s = String::from_utf8_lossy(buf).to_string();
+    // edge 回包尾部可能带 \0，让恩兔把它扫掉
+    s = s.trim_matches('\u{0}').trim().to_string();
+    serde_json::from_str(&s).ok()
+}
+
+fn collect_rows_json(socket: &UdpSocket, tag: &str, deadline: Instant) -> Result<Vec<serde_json::Value>> {
+    let mut buf = vec![0u8; 65535];
+    let mut rows: Vec<serde_json::Value> = Vec::new();
+
+    while Instant::now() < deadline {
+        let (n, _) = match socket.recv_from(&mut buf) {
+            Ok(v) => v,
+            Err(e) if should_stop_read(&e) => break,
+            Err(e) => return Err(anyhow::anyhow!("读取 Management API 回信失败：{}", e)),
+        };
+
+        let pkt = match parse_packet(&buf[..n]) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if pkt.tag.as_deref() != Some(tag) {
+            continue;
+        }
+
+        match pkt.kind.as_str() {
+            "error" => {
+                let err = pkt.error.unwrap_or_else(|| "unknown".to_string());
+                return Err(anyhow::anyhow!("Management API 返回错误：{}", err));
+            }
+            "row" => rows.push(serde_json::Value::Object(pkt.extra)),
+            "end" => break,
+            _ => {}
+        }
+    }
+
+    Ok(rows)
+}
+
+/// 等 edge 对这张纸条回 `end`（到点还没回就算没人应：管理口没开、门牌不对或者 edge 卡住了）
+fn wait_end(
+    socket: &UdpSocket,
+    tag: &str,
+    deadline: Instant,
+    read_error_hint: &str,
+    error_prefix: &str,
+) -> Result<()> {
+    let mut buf = vec![0u8; 65535];
+    while Instant::now() < deadline {
+        let (n, _) = match socket.recv_from(&mut buf) {
+            Ok(v) => v,
+            Err(e) if should_stop_read(&e) => continue,
+            Err(e) => return Err(anyhow::anyhow!("{}：{}", read_error_hint, e)),
+        };
+
+        let pkt = match parse_packet(&buf[..n]) {
+            Some(v) => v,
+            None => continue,
+        };
+
+        if pkt.tag.as_deref() != Some(tag) {
+            continue;
+        }
+
+        if pkt.kind == "error" {
+            let err = pkt.error.unwrap_or_else(|| "unknown".to_string());
+            return Err(anyhow::anyhow!("{}：{}", error_prefix, err));
+        }
+        if pkt.kind == "end" {
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!("{}：edge 没有回信", error_prefix)
+}
+
+/// 从管理口请 edge 下班（`w ... stop`，写操作要带口令）：edge 回了 `end` 才算递到了，
+/// 之后它会自己收拾好网卡再走
+pub(crate) fn stop(port: u16, password: Option<&str>) -> Result<()> {
+    let (tag, req) = build_request('w', "stop", password);
+    let deadline = Instant::now() + Duration::from_millis(DEADLINE_MS);
+    let socket = send_request(
+        (HOST, port),
+        &req,
+        "准备 stop 纸条失败",
+        "把 stop 纸条递给 edge（Management API）失败",
+    )?;
+    wait_end(&socket, &tag, deadline, "读取 stop 回信失败", "Management API stop 失败")
+}
+
+pub(crate) fn query_single_row<T>(port: u16, method: &str, password: Option<&str>) -> Result<Option<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let mut rows = query_rows::<T>(port, method, password)?;
+    Ok(rows.pop())
+}
+
+pub(crate) fn query_rows<T>(port: u16, method: &str, password: Option<&str>) -> Result<Vec<T>>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let rows_json = query_rows_json_at((HOST, port), method, password)?;
+    let mut out = Vec::new();
+    for row in rows_json {
+        if let Ok(v) = serde_json::from_value::<T>(row) {
+            out.push(v);
+        }
+    }
+    Ok(out)
+}
+
+/// 管理口上是否已经有一只 edge 在值班（比如上次恩兔摔倒时没来得及带走的 edge）
+pub(crate) fn edge_already_listening(port: u16) -> bool {
+    query_rows_json_at((HOST, port), "timestamps", None).is_ok()
+}
+
+/// 敲指定门牌的管理口（edge 或本机 supernode 都讲同一套 JSON 方言）
+pub(crate) fn query_rows_json_at(
+    addr: (&str, u16),
+    method: &str,
+    password: Option<&str>,
+) -> Result<Vec<serde_json::Value>> {
+    match query_rows_json_once(addr, method, password) {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            // 默认密码是 n2n：如果主人没配置密码且遇到 badauth，就用默认钥匙再试一次
+            if password.is_none() && e.to_string().contains("badauth") {
+                return query_rows_json_once(addr, method, Some("n2n"));
+            }
+            Err(e)
+        }
+    }
+}
+
+/// 通过 Management API 查询多行结果（JSON 格式）
+fn query_rows_json_once(
+    addr: (&str, u16),
+    method: &str,
+    password: Option<&str>,
+) -> Result<Vec<serde_json::Value>> {
+    let (tag, req) = build_request('r', method, password);
+    let deadline = Instant::now() + Duration::from_millis(DEADLINE_MS);
+    let socket = send_request(
+        addr,
+        &req,
+        "准备 Management API 询问纸条失败",
+        "把询问纸条递给 edge（Management API）失败",
+    )?;
+    collect_rows_json(&socket, &tag, deadline)
+}
+
+fn should_stop_read(e: &std::io::Error) -> bool {
+    // WouldBlock/TimedOut：本轮没等到回信，直接结束就好
+    if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut {
+        return true;
+    }
+
+    // Windows 下：UDP 目标端口尚未监听时，可能会抛出 10054（ConnectionReset）。
+    // 这属于“管理口还没准备好”的正常抖动，别拿它吓主人一跳～
+    #[cfg(target_os = "windows")]
+    {
+        if e.kind() == std::io::ErrorKind::ConnectionReset {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// 一类包的收发计数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PacketCounter {
+    /// 哪一类（transop：经过加解密的全部数据包；p2p：直连；super：经总部中转 ……）
+    pub kind: String,
+    pub tx: u64,
+    pub rx: u64,
+}
+
+/// edge 此刻的实时统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EdgeStats {
+    /// edge 开工的时间（Unix 秒）
+    pub started_at: u64,
+    /// 开工了多久（秒）
+    pub uptime_secs: u64,
+    /// 最近一次和总部通上话（Unix 秒，0 表示还没有）
+    pub last_super: u64,
+    /// 最近一次和同伴直连通上话（Unix 秒，0 表示还没有）
+    pub last_p2p: u64,
+    /// 在总部那里报上到了（最近一次通话够新）
+    pub registered: bool,
+    /// edge 正在用的总部（配了多台总部时是选中的那台；旧版 edge 报不出来时为 None）
+    pub supernode: Option<String>,
+    /// 收发的数据包总数（transop 那一行）
+    pub tx_packets: u64,
+    pub rx_packets: u64,
+    /// 虚拟网卡的收发字节数（目前只有 Linux 读得到）
+    pub tx_bytes: Option<u64>,
+    pub rx_bytes: Option<u64>,
+    /// 各类包的明细
+    pub packets: Vec<PacketCounter>,
+}
+
+/// 去问统计要用的东西（从 `N2NProcess` 里抄出来，问的时候不用占着锁）
+#[derive(Debug, Clone)]
+pub struct StatsTarget {
+    pub port: u16,
+    pub password: Option<String>,
+    /// 和总部最近一次通话在这么多秒以内才算“报上到了”
+    pub fresh_secs: u64,
+    /// 读字节数用的网卡名
+    pub tap_device: Option<String>,
+}
+
+impl StatsTarget {
+    /// 敲管理口拼一份统计（要等几百毫秒，别放在界面线程里）
+    pub fn query(&self) -> Result<EdgeStats> {
+        let password = self.password.as_deref();
+        let timestamps = query_single_row::<TimestampsRow>(self.port, "timestamps", password)?
+            .context("edge 的管理口没有回 timestamps")?;
+        // 旧版 edge 没有 packetstats/supernodes 也不影响其它几项
+        let packets = query_rows::<PacketStatsRow>(self.port, "packetstats", password).unwrap_or_default();
+        let supernodes = query_rows::<SupernodeRow>(self.port, "supernodes", password).unwrap_or_default();
+
+        let mut stats = assemble(timestamps, packets, supernodes, n2n_process::unix_now_seconds(), self.fresh_secs);
+        if let Some((tx, rx)) = self.tap_device.as_deref().and_then(n2n_process::read_tap_byte_counters) {
+            stats.tx_bytes = Some(tx);
+            stats.rx_bytes = Some(rx);
+        }
+        Ok(stats)
+    }
+}
+
+fn assemble(
+    timestamps: TimestampsRow,
+    packets: Vec<PacketStatsRow>,
+    supernodes: Vec<SupernodeRow>,
+    now: u64,
+    fresh_secs: u64,
+) -> EdgeStats {
+    let packets: Vec<PacketCounter> = packets
+        .into_iter()
+        .map(|row| PacketCounter {
+            kind: row.kind,
+            tx: row.tx_pkt.unwrap_or(0),
+            rx: row.rx_pkt.unwrap_or(0),
+        })
+        .collect();
+    let (tx_packets, rx_packets) = packets
+        .iter()
+        .find(|p| p.kind == "transop")
+        .map_or((0, 0), |p| (p.tx, p.rx));
+    EdgeStats {
+        started_at: timestamps.start_time,
+        uptime_secs: now.saturating_sub(timestamps.start_time),
+        last_super: timestamps.last_super,
+        last_p2p: timestamps.last_p2p,
+        registered: timestamps.last_super != 0 && now.saturating_sub(timestamps.last_super) <= fresh_secs,
+        supernode: supernodes.into_iter().find(|s| s.current == Some(1)).and_then(|s| s.sockaddr),
+        tx_packets,
+        rx_packets,
+        tx_bytes: None,
+        rx_bytes: None,
+        packets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_assemble_stats() {
+        let (tag, req) = build_request('r', "timestamps", Some("pw"));
+        assert_eq!(req, format!("r {tag}:1:pw timestamps\n"));
+        let (tag, req) = build_request('w', "stop", None);
+        assert_eq!(req, format!("w {tag} stop\n"));
+
+        let pkt = parse_packet(b"{\"_tag\":\"7\",\"_type\":\"row\",\"start_time\":1000,\"last_super\":1090,\"last_p2p\":0}\0")
+            .unwrap();
+        assert_eq!((pkt.tag.as_deref(), pkt.kind.as_str()), (Some("7"), "row"));
+        let timestamps: TimestampsRow = serde_json::from_value(serde_json::Value::Object(pkt.extra)).unwrap();
+
+        let packets = vec![
+            PacketStatsRow { kind: "transop".to_string(), tx_pkt: Some(120), rx_pkt: Some(98) },
+            PacketStatsRow { kind: "p2p".to_string(), tx_pkt: Some(80), rx_pkt: None },
+        ];
+        let supernodes = vec![
+            SupernodeRow { sockaddr: Some("203.0.113.1:7777".to_string()), current: Some(0) },
+            SupernodeRow { sockaddr: Some("203.0.113.2:7777".to_string()), current: Some(1) },
+        ];
+        let stats = assemble(timestamps.clone(), packets, supernodes, 1100, 15);
+        assert_eq!(stats.uptime_secs, 100);
+        assert!(stats.registered);
+        assert_eq!(stats.supernode.as_deref(), Some("203.0.113.2:7777"));
+        assert_eq!((stats.tx_packets, stats.rx_packets), (120, 98));
+        assert_eq!(stats.packets[1], PacketCounter { kind: "p2p".to_string(), tx: 80, rx: 0 });
+
+        // 好久没和总部通话：不算报上到了
+        let stale = assemble(timestamps, Vec::new(), Vec::new(), 1200, 15);
+        assert!(!stale.registered);
+        assert_eq!((stale.tx_packets, stale.supernode), (0, None));
+    }
+
+    #[test]
+    fn test_stop_needs_an_answer() {
+        // 门牌那头有人收纸条却不回信：不能当成已经请下班了，要让调用方改用信号
+        let silent = UdpSocket::bind((HOST, 0)).unwrap();
+        let port = silent.local_addr().unwrap().port();
+        assert!(stop(port, Some("n2n")).is_err());
+
+        // 回了 end 才算递到
+        let edge = UdpSocket::bind((HOST, 0)).unwrap();
+        let port = edge.local_addr().unwrap().port();
+        let responder = std::thread::spawn(move || {
+            let mut buf = [0u8; 256];
+            let (n, from) = edge.recv_from(&mut buf).unwrap();
+            let req = String::from_utf8_lossy(&buf[..n]).to_string();
+            let tag = req.split_whitespace().nth(1).unwrap().split(':').next().unwrap().to_string();
+            edge.send_to(format!("{{\"_tag\":\"{tag}\",\"_type\":\"end\"}}").as_bytes(), from).unwrap();
+            req
+        });
+        stop(port, None).unwrap();
+        assert!(responder.join().unwrap().starts_with("w "));
+    }
+}
@@ -13,8 +13,20 @@
 //! ### 2) 依赖 UDP Management API 的内容（结构化、更稳）
 //! - **连接成功判定（UI 状态优先）**：后台轮询 `timestamps`，用 `last_super/last_p2p` 的“新鲜度”推断是否已连上。
 //!   - 对外体现为 `derived_status()`：即使 stdout 没出现特定关键字，也能在心跳正常时进入 `Connected(...)`。
+//!   - 防抖：已连接后心跳要持续缺席 `LINK_DOWN_HOLD` 才会退回 `Connecting`，单次轮询失败不会让 UI 来回闪。
+//!   - 体检：管理口小工人每轮顺便看 `last_super`，连上过之后超过两个报到间隔没和总部报上到，就算“登记丢了”，
+//!     对外变成 `Degraded`（edge 还活着，只是总部那边已经不认它了），报上到后自动恢复。
+//!   - 网卡巡查：`check_tap()` 每隔几秒看一眼虚拟网卡还在不在、是不是启用着、领到的地址还在不在，
+//!     睡眠唤醒或驱动抽风把网卡弄坏了同样变成 `Degraded`；方案里打开了 `tap_restart_on_loss` 就直接重新出门。
 //! - **同伴点名册**：通过 `edges` 获取同伴列表，并缓存后由 `get_peers` 提供给前端展示。
 //! - **优雅断开（Gracefully exit）**：`stop()` 优先发送 `w ... stop`，失败再回退到信号/系统命令兜底。
+//! - **实时统计**：`stats_target()` 交出门牌和钥匙，由 `mgmt::StatsTarget::query` 拼出收发、报到和在岗时长。
+//! - 传纸条、收回信的细节都在 `mgmt` 模块里。
+//!
+//! ### 听汇报和等退出：一只 edge 一个看护员
+//! edge 由 `tokio::process` 带出门，stdout/stderr 按行异步读，`supervise()` 在专用的看护运行时里
+//! 用 `select!` 同时听两根管子、每秒看一眼退出没有：不用每只 edge 开三条阻塞线程，换班时把上一班的
+//! 看护员 abort 掉就干净收工。
 //!
 //! ### 3) 提示信息的合成策略（derived_notice）
 //! - **优先**：如果 stdio 已提取到明确错误（`last_notice`），就直接提示主人。
@@ -22,18 +34,22 @@
 //! - **最后**：再把 Management API 轮询过程中记录的错误（如 `badauth`）作为调试线索返还。
 //!
 //! ### 4) 认证（management password）
-//! - 如果主人在 `extra_args` 中传入 `--management-password <pw>`，恩兔会自动记下并用于管理口请求。
+//! - 如果主人在设置里填了管理口令（`management_password`，交给 edge 的 `--management-password`），恩兔会自动记下并用于管理口请求。
+//! - 管理口换了门牌（`management_port`，交给 edge 的 `-t`）时，恩兔记下这次实际在用的端口，之后的查询都敲这个端口。
 //! - 若主人未配置密码，部分操作会按文档默认密码 `n2n` 做一次兜底尝试（避免环境差异导致“看得见但用不了”）。
 use anyhow::{Context, Result};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, Ordering};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader};
-use std::net::UdpSocket;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::process::{Command, Stdio};
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::Poll;
 use std::thread;
+use tokio::io::{AsyncBufReadExt, AsyncRead, Lines};
+use tokio::process::{Child, ChildStderr, ChildStdout};
+use tokio::runtime::Runtime;
+use tokio::task::AbortHandle;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tokio::sync::mpsc;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -45,22 +61,41 @@ use nix::sys::signal::{kill, Signal};
 #[cfg(unix)]
 use nix::unistd::Pid;
 
-use crate::config::N2NConfig;
-
-/// Management API stop 操作超时（毫秒）
-const MGMT_STOP_TIMEOUT_MS: u64 = 10000;
-/// Management API 查询超时 - socket 读取超时（毫秒）
-const MGMT_READ_TIMEOUT_MS: u64 = 200;
-/// Management API 查询超时 - 总等待时间（毫秒）
-const MGMT_DEADLINE_MS: u64 = 1500;
-/// Management API 的门牌号（恩兔只敲本机 127.0.0.1:5644）
-const MGMT_ADDR: (&str, u16) = ("127.0.0.1", 5644);
+use crate::config::{LogForwardFilter, N2NConfig, ReconnectPolicy};
+use crate::edge_caps::EdgeCaps;
+use crate::edge_options;
+use crate::edge_output::SessionOutput;
+use crate::exit_reason::{ExitReason, Tail};
+use crate::failover::{self, Failover};
+use crate::events::{self, AssistantMessage, EdgeWarning, EventSender, ProcessEvent, SessionSummary, Severity};
+use crate::log_buffer::LogSender;
+use crate::mgmt;
+use crate::port_forward::PortForward;
+use crate::reaper::{self, ChildState};
+use crate::routes::Route;
+use crate::session_state::{process_alive, SessionRecord};
+use crate::tap_adapter::{self, TapAdapter, TapProblem};
+use crate::operation::{self, OpError, Operation, OperationGate, StopThrottle};
+use crate::watchdog::{Observation, StateIssue};
+
+/// edge 走后等多久再去核对虚拟网卡（系统收回地址和路由需要一点时间）
+const TEARDOWN_SETTLE: Duration = Duration::from_secs(2);
+/// 还没建立过通道时，温柔停止最多等多久（之后按方案的 stop_timeout_secs）
+const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// edge 管理口的默认端口（方案里没填 `management_port` 时）
+pub const DEFAULT_MGMT_PORT: u16 = 5644;
+/// edge 默认的报到间隔（秒，方案里没填 `register_interval_secs` 时）
+pub const DEFAULT_REGISTER_INTERVAL_SECS: u32 = 20;
 /// 判断心跳是否有效的最大时间间隔（秒）
 const HEARTBEAT_MAX_INTERVAL_SECS: u64 = 15;
 /// 判断心跳断联的最大时间间隔（秒）- 用于提示"总部不可达"
 const HEARTBEAT_DISCONNECT_THRESHOLD_SECS: u64 = 30;
 /// edge 启动后等待首次 supernode 连接的超时（秒）
 const EDGE_STARTUP_WAIT_SECS: u64 = 30;
+/// 已连接后心跳要连续缺席多久，才承认“真的断了”（防止状态来回闪）
+const LINK_DOWN_HOLD: Duration = Duration::from_secs(8);
+/// 提醒小本子最多记多少条（太多了主人也看不过来）
+const MAX_WARNINGS: usize = 50;
 
 /// Windows 下创建子进程时不弹黑框（恩兔把黑框悄悄收起来）
 #[cfg(target_os = "windows")]
@@ -74,6 +109,24 @@ pub struct NetworkInfo {
     pub mac: String,
 }
 
+/// edge 实际生效的通道特性（从启动汇报里抄下来，给主人确认安全设置真的生效了）
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransportFeatures {
+    /// 加密方式（例如 AES / ChaCha20 / Twofish / null）
+    pub cipher: Option<String>,
+    /// 压缩方式（例如 lzo1x / zstd / none）
+    pub compression: Option<String>,
+    /// 包头加密是否开启（None 表示 edge 没提）
+    pub header_encryption: Option<bool>,
+}
+
+impl TransportFeatures {
+    fn is_empty(&self) -> bool {
+        self == &TransportFeatures::default()
+    }
+}
+
 /// 同伴节点信息（来自 Management API 的 edges 列表）
 #[derive(Debug, Clone, PartialEq, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -86,8 +139,12 @@ pub struct PeerNodeInfo {
     pub vpn_ip: Option<String>,
     /// 同伴的公网 Socket 地址（例如 1.2.3.4:56789）
     pub public_addr: Option<String>,
-    /// N2N 通道模式（例如 p2p / pSp 等）
+    /// 同伴虚拟网卡的 MAC
+    pub mac: Option<String>,
+    /// N2N 通道模式（edge 的原话，例如 p2p / pSp 等）
     pub mode: Option<String>,
+    /// 直连还是经总部中转（按 mode 归好类）
+    pub link: PeerLink,
     /// edge 最后一次“看见”该同伴的时间戳（Unix 秒）
     pub last_seen: Option<u64>,
     /// 是否为本机（有些版本会返回 local=1 的记录）
@@ -98,6 +155,29 @@ pub struct PeerNodeInfo {
     pub last_ping: Option<u64>,
 }
 
+/// 和同伴之间的通道是怎么走的
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerLink {
+    /// 打通了直连（edges 里的 p2p）
+    Direct,
+    /// 还没打通，经总部中转（edges 里的 pSp）
+    Relayed,
+    /// edge 报了不认识的模式
+    #[default]
+    Unknown,
+}
+
+impl PeerLink {
+    fn from_mode(mode: Option<&str>) -> Self {
+        match mode.map(str::trim) {
+            Some(m) if m.eq_ignore_ascii_case("p2p") => PeerLink::Direct,
+            Some(m) if m.eq_ignore_ascii_case("psp") => PeerLink::Relayed,
+            _ => PeerLink::Unknown,
+        }
+    }
+}
+
 /// 恩兔的工作状态
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConnectionStatus {
@@ -109,32 +189,403 @@ pub enum ConnectionStatus {
     Disconnecting,
     /// 通道已就绪（已连接，包含详情）
     Connected(Option<NetworkInfo>),
-    /// 遇到麻烦了（错误）
-    Error(String),
+    /// edge 还活着，但总部那边的登记丢了、或者虚拟网卡坏了（只由 `derived_status` 给出，保留网卡详情）
+    Degraded(Option<NetworkInfo>),
+    /// 暂时歇一会儿（edge 已停下，但本次工作的记录都还留着，随时可以继续）
+    Paused,
+    /// edge 意外退出了，正等着第几次自动重连（等待期间可以取消）
+    Reconnecting(u32),
+    /// 遇到麻烦了（错误；edge 摔倒时附上原因码，见 `exit_reason`）
+    Error(String, Option<ExitReason>),
+}
+
+/// 暂停时留下的“工作便签”（恢复时按它继续打扫）
+#[derive(Debug, Clone)]
+pub struct PausedSession {
+    /// 暂停前正在使用的指示
+    pub config: N2NConfig,
+    /// 暂停前拿到的网卡信息（用于恢复时尽量保住原来的地址）
+    pub network_info: Option<NetworkInfo>,
+    /// 本次工作开始的时间（Unix 秒，暂停/恢复不会重置）
+    pub session_started_at: u64,
+    /// 本次工作已经暂停过几次
+    pub pause_count: u32,
+}
+
+/// 本次工作的记账本（暂停/恢复不翻页，完全结束时汇总成 `SessionSummary`）
+#[derive(Debug, Clone, Default)]
+struct SessionTracker {
+    started_at: u64,
+    supernode: String,
+    community: String,
+    /// 用来读流量计数的网卡名（Linux 下默认 edge0）
+    tap_device: Option<String>,
+    pause_count: u32,
+    reconnect_count: u32,
+    /// 已经结束的 edge 轮次累计的计数（暂停/重连会开新一轮 edge，计数从 0 重来）
+    done: TrafficCounters,
+    /// 当前这一轮 edge 的最新读数
+    current: TrafficCounters,
+    /// 本次工作的录音（edge 原始输出）
+    output: Option<SessionOutput>,
+    /// 最近一次 edge 意外摔倒的原因
+    exit_reason: Option<ExitReason>,
+}
+
+/// 收发计数（包数来自管理口 packetstats，字节数来自系统网卡统计）
+#[derive(Debug, Clone, Copy, Default)]
+struct TrafficCounters {
+    tx_packets: u64,
+    rx_packets: u64,
+    tx_bytes: Option<u64>,
+    rx_bytes: Option<u64>,
+}
+
+impl TrafficCounters {
+    fn add(self, other: TrafficCounters) -> TrafficCounters {
+        let sum = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0).saturating_add(b.unwrap_or(0))),
+        };
+        TrafficCounters {
+            tx_packets: self.tx_packets.saturating_add(other.tx_packets),
+            rx_packets: self.rx_packets.saturating_add(other.rx_packets),
+            tx_bytes: sum(self.tx_bytes, other.tx_bytes),
+            rx_bytes: sum(self.rx_bytes, other.rx_bytes),
+        }
+    }
+}
+
+impl SessionTracker {
+    fn new(config: &N2NConfig) -> Self {
+        #[cfg(target_os = "linux")]
+        let tap_device = Some(config.tap_device.clone().unwrap_or_else(|| "edge0".to_string()));
+        #[cfg(not(target_os = "linux"))]
+        let tap_device = config.tap_device.clone();
+
+        let started_at = unix_now_seconds();
+        Self {
+            started_at,
+            supernode: config.supernode.clone(),
+            community: config.community.clone(),
+            tap_device,
+            output: SessionOutput::create(started_at),
+            ..Default::default()
+        }
+    }
+
+    /// 这一轮 edge 收工了：把读数并进累计里
+    fn fold_current(&mut self) {
+        self.done = self.done.add(self.current);
+        self.current = TrafficCounters::default();
+    }
+
+    fn summarize(&self, ended_at: u64, reason: &str, error: Option<String>) -> SessionSummary {
+        let total = self.done.add(self.current);
+        SessionSummary {
+            started_at: self.started_at,
+            ended_at,
+            duration_secs: ended_at.saturating_sub(self.started_at),
+            supernode: self.supernode.clone(),
+            community: self.community.clone(),
+            tx_packets: total.tx_packets,
+            rx_packets: total.rx_packets,
+            tx_bytes: total.tx_bytes,
+            rx_bytes: total.rx_bytes,
+            reconnect_count: self.reconnect_count,
+            pause_count: self.pause_count,
+            reason: reason.to_string(),
+            error,
+            exit_reason: self.exit_reason.map(|reason| reason.code().to_string()),
+            output_file: self
+                .output
+                .as_ref()
+                .map(|output| output.path().to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// 工作班次编号：每次启动 edge 换一个新班次，上一班留下的看护员看到编号变了就立刻收工，
+/// 免得快速重连时新旧两班小工人同时改状态
+#[derive(Debug, Default)]
+struct Generation(AtomicU64);
+
+impl Generation {
+    /// 开新的一班，返回新班次的编号
+    fn advance(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// 现在是第几班
+    fn current(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// `mine` 是不是当前这一班
+    fn is_current(&self, mine: u64) -> bool {
+        self.0.load(Ordering::SeqCst) == mine
+    }
+}
+
+/// edge 的看护运行时（听汇报、等退出都在这里，不占 Tauri 的异步线程，也不用每只 edge 开三条线程）
+fn edge_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("edge-supervisor")
+            .enable_all()
+            .build()
+            .expect("无法创建 edge 看护运行时")
+    })
+}
+
+/// 看护员看一眼的结果
+enum Watch {
+    /// edge 还在干活
+    Working,
+    /// 这一班结束了（edge 退出或者换班了），可能还排着一次重连（配置 + 要等多久）
+    Done(Option<Box<(N2NConfig, Duration)>>),
+}
+
+/// 等这一班的 edge 退出（句柄被别人收走了也算）
+/// - 句柄一直留在 `child` 里，停止流程、巡查员照常能用：每次被叫醒时借一下，推一推 `Child::wait` 就放开；
+/// - tokio 把“子进程退出了”的提醒挂在句柄自己身上，临时的 `wait` 推完就丢也不会漏掉。
+fn exited(child: &Mutex<Option<Child>>) -> impl Future<Output = ()> + '_ {
+    std::future::poll_fn(move |cx| {
+        let mut guard = child.lock().unwrap();
+        let Some(child) = guard.as_mut() else {
+            return Poll::Ready(());
+        };
+        let exit = std::pin::pin!(child.wait()).poll(cx);
+        exit.map(|_| ())
+    })
+}
+
+/// 读下一行汇报（管子关了就一直等着，让另一根管子和退出检查接着跑）
+/// - 不是 UTF-8 的行跳过，接着往下读；
+/// - `Lines::next_line` 被 `select!` 打断也不会丢半行。
+async fn next_line<R: AsyncRead + Unpin>(lines: &mut Option<Lines<tokio::io::BufReader<R>>>) -> String {
+    while let Some(reader) = lines.as_mut() {
+        match reader.next_line().await {
+            Ok(Some(line)) => return line,
+            Err(e) if e.kind() == std::io::ErrorKind::InvalidData => continue,
+            _ => *lines = None,
+        }
+    }
+    std::future::pending().await
+}
+
+/// 这一班 edge 的汇报怎么听（看护员手里的一份，stdout/stderr 每来一行就翻一次）
+struct LineReader {
+    status: Arc<Mutex<ConnectionStatus>>,
+    last_notice: Arc<Mutex<Option<String>>>,
+    tail: Arc<Mutex<Tail>>,
+    log_tx: Option<LogSender>,
+    stop_requested: Arc<AtomicBool>,
+    /// 连上了就重新计算重连次数
+    reconnect_attempts: Arc<AtomicU32>,
+    warnings: Arc<Mutex<Vec<EdgeWarning>>>,
+    transport: Arc<Mutex<TransportFeatures>>,
+    tap_network: Arc<Mutex<Option<NetworkInfo>>>,
+    event_tx: Option<EventSender>,
+    output: Option<SessionOutput>,
+    log_filter: LogForwardFilter,
+    important_peers: Vec<String>,
+    /// 这一班抄到的网卡信息
+    network_info: Option<NetworkInfo>,
+    /// 重要同伴打过招呼了没有
+    peers_warmed: bool,
+}
+
+impl LineReader {
+    fn on_stdout(&mut self, line: &str) {
+        log::info!("N2N stdout: {}", line);
+        if let Some(ref output) = self.output {
+            output.record("OUT", line);
+        }
+        self.tail.lock().unwrap().push(line);
+
+        let forward = should_forward_line(self.log_filter, line);
+
+        // 如果主人已经让恩兔“收拾工具”，就别再用 ERROR 把主人吓一跳啦
+        if self.stop_requested.load(Ordering::SeqCst) {
+            if let (true, Some(tx)) = (forward, self.log_tx.as_ref()) {
+                tx.send(format!("[OUT] {}", line));
+            }
+            return;
+        }
+
+        // 提取网卡信息：created local tap device IP: xxx.xxx.xxx.xxx, Mask: 255.255.255.0, MAC: xx:xx:xx:xx:xx:xx
+        if line.contains("created local tap device") {
+            if let Some(info) = parse_network_info(line) {
+                *self.tap_network.lock().unwrap() = Some(info.clone());
+                log::info!("提取到网卡信息：{:?}", info);
+                // 如果已经连接成功了，就把详情也补写进状态里（给主人递上“通道回执单”）
+                let mut status = self.status.lock().unwrap();
+                if matches!(*status, ConnectionStatus::Connected(_)) {
+                    *status = ConnectionStatus::Connected(Some(info.clone()));
+                }
+                self.network_info = Some(info);
+            }
+        }
+
+        // 检测连接成功的关键字
+        // 备注：不同版本 edge 输出不完全一致，这里做兼容匹配
+        if line.contains("edge <<<") || line.contains("[OK] edge <<<") {
+            let was_connected = matches!(
+                std::mem::replace(&mut *self.status.lock().unwrap(), ConnectionStatus::Connected(self.network_info.clone())),
+                ConnectionStatus::Connected(_)
+            );
+            // 连接成功就把“提醒便签”撕掉，避免主人继续担心
+            *self.last_notice.lock().unwrap() = None;
+            self.reconnect_attempts.store(0, Ordering::SeqCst);
+            if !was_connected {
+                let message = match self.network_info {
+                    Some(ref info) => AssistantMessage::new("maid_connected_ip", Severity::Success).with("ip", info.ip.clone()),
+                    None => AssistantMessage::new("maid_connected", Severity::Success),
+                };
+                events::say(self.event_tx.as_ref(), message);
+            }
+
+            if !self.peers_warmed && !self.important_peers.is_empty() {
+                self.peers_warmed = true;
+                warm_up_neighbors(self.important_peers.clone(), self.log_tx.clone());
+            }
+        }
+
+        // 识别常见问题并提示给主人（注意：edge 的 ERROR 可能出现在 stdout）
+        if let Some(notice) = extract_user_facing_notice(line) {
+            set_last_notice_if_changed(&self.last_notice, notice);
+        }
+
+        // 记下 WARNING 行（不改变状态，只是提醒主人）
+        record_edge_warning(&self.warnings, self.event_tx.as_ref(), line);
+        // 抄下启动时协商好的加密/压缩方式
+        parse_transport_line(&mut self.transport.lock().unwrap(), line);
+
+        if let (true, Some(tx)) = (forward, self.log_tx.as_ref()) {
+            tx.send(format!("[OUT] {}", line));
+        }
+    }
+
+    fn on_stderr(&self, line: &str) {
+        log::warn!("N2N stderr: {}", line);
+        if let Some(ref output) = self.output {
+            output.record("ERR", line);
+        }
+        self.tail.lock().unwrap().push(line);
+
+        let forward = should_forward_line(self.log_filter, line);
+
+        if self.stop_requested.load(Ordering::SeqCst) {
+            if let (true, Some(tx)) = (forward, self.log_tx.as_ref()) {
+                tx.send(format!("[ERR] {}", line));
+            }
+            return;
+        }
+
+        // 识别常见问题并提示给主人（stderr 里也会冒出关键 ERROR）
+        if let Some(notice) = extract_user_facing_notice(line) {
+            set_last_notice_if_changed(&self.last_notice, notice);
+        }
+
+        record_edge_warning(&self.warnings, self.event_tx.as_ref(), line);
+        // 抄下启动时协商好的加密/压缩方式
+        parse_transport_line(&mut self.transport.lock().unwrap(), line);
+
+        if let (true, Some(tx)) = (forward, self.log_tx.as_ref()) {
+            tx.send(format!("[ERR] {}", line));
+        }
+    }
 }
 
-/// 恩兔的工作管理器
+/// 恩兔的工作管理器（克隆出来的是同一个管理器的另一只手，内部状态全是共享的）
+#[derive(Clone)]
 pub struct N2NProcess {
+    /// 主人这边有几只手（最后一只放下时才收拾还在跑的 edge；看护员手里的是 None，放下时不算）
+    handles: Option<Arc<()>>,
     /// 工作进程句柄
     child: Arc<Mutex<Option<Child>>>,
+    /// 装着这一班 edge 的 Job Object（关上时它拉起的程序一起清走）
+    #[cfg(windows)]
+    job: Arc<Mutex<Option<crate::process_group::Job>>>,
+    /// 这一班的看护员（换班时叫停上一班的）
+    supervisor: Arc<Mutex<Option<AbortHandle>>>,
+    /// 接手来的 edge 的进程号（不是恩兔带出门的，没有句柄；0 表示没有）
+    adopted: Arc<AtomicU32>,
     /// 当前工作状态
     status: Arc<Mutex<ConnectionStatus>>,
     /// 最近一次“需要主人注意”的提示（不一定致命，可能只是需要等待/检查配置）
     last_notice: Arc<Mutex<Option<String>>>,
+    /// edge 最后几行输出（摔倒时找原因用）
+    tail: Arc<Mutex<Tail>>,
     /// 工作汇报通道
-    log_tx: Option<mpsc::UnboundedSender<String>>,
+    log_tx: Option<LogSender>,
+    /// 小喇叭（把事件递给前端）
+    event_tx: Option<EventSender>,
+    /// 本次工作中 edge 给出的非致命提醒（WARNING 行）
+    warnings: Arc<Mutex<Vec<EdgeWarning>>>,
+    /// 本次工作实际生效的通道特性（加密/压缩/包头加密）
+    transport: Arc<Mutex<TransportFeatures>>,
+    /// 虚拟网卡在系统里的名字和编号（按 MAC 对照一次后记住）
+    tap_adapter: Arc<Mutex<Option<TapAdapter>>>,
+    /// 这一轮 edge 领到的网卡信息（edge 走后按它核对网卡有没有收拾干净）
+    tap_network: Arc<Mutex<Option<NetworkInfo>>>,
+    /// edge 走后网卡上还挂着地址/路由时，要不要顺手重置网卡（按本次工作的方案）
+    tap_reset: Arc<AtomicBool>,
+    /// 本次交给 edge 的工号（`-I`，没填时由主机名整理而来）
+    node_name: Arc<Mutex<Option<String>>>,
+    /// 本次工作的传话筒规则（连上之后才开张）
+    port_forwards: Arc<Mutex<Vec<PortForward>>>,
+    /// 本次指示里固定的本机 UDP 端口（`-p`）
+    local_port: Arc<Mutex<Option<u16>>>,
+    /// 这次通道是否当中转节点（连上后要打开系统的 IP 转发）
+    packet_forwarding: Arc<AtomicBool>,
+    /// 本次交给 edge 的报到间隔（`-i`，秒）
+    register_interval: Arc<AtomicU32>,
+    /// 本次交给 edge 的路由（`-n`）
+    routes: Arc<Mutex<Vec<Route>>>,
+    /// 温柔停止后最多等多久，超时就强制清理（按本次工作的方案）
+    stop_timeout: Arc<Mutex<Duration>>,
     /// 自动重连配置（断线后自动重新打扫）
     auto_reconnect: Arc<Mutex<Option<N2NConfig>>>,
+    /// 正在等待下一次自动重连（主人断开或手动建立通道时取消）
+    reconnect_pending: Arc<AtomicBool>,
+    /// 连上之前已经连续重连了几次
+    reconnect_attempts: Arc<AtomicU32>,
+    /// 临时不自动重连、不自我巡查到什么时候（Unix 秒；总部维护时用，不用改方案）
+    reconnect_suspended_until: Arc<Mutex<Option<u64>>>,
+    /// 当前工作班次（看护员按它判断自己是不是过期了）
+    generation: Arc<Generation>,
 
     /// 是否由主人主动要求停止（用于区分"正常休息"与"意外摔倒"）
     stop_requested: Arc<AtomicBool>,
+    /// 暂停中的工作便签（Some 表示 edge 停下只是“歇一会儿”）
+    paused_session: Arc<Mutex<Option<PausedSession>>>,
+    /// 本次工作的记账本（恢复时沿用，完全结束时汇总并清空）
+    session: Arc<Mutex<Option<SessionTracker>>>,
+    /// 排队闸门：启动/停止/暂停等操作一次只放行一个（看护员收尾时也要排队）
+    ops: Arc<OperationGate>,
+    /// 停止请求的节流阀（主人连点断开时别弹出一串 pkexec 授权框）
+    stop_throttle: Arc<Mutex<StopThrottle>>,
 
     /// Management API 密码（如果主人给 edge 设了门禁，恩兔也要带钥匙）
     mgmt_password: Arc<Mutex<Option<String>>>,
+    /// 这只 edge 实际在用的管理口（启动时按方案记下，之后的查询都敲它）
+    mgmt_port: Arc<AtomicU16>,
     /// Management API 状态缓存（避免 get_status 每次都直接去敲 UDP 门铃）
     mgmt_state: Arc<Mutex<MgmtState>>,
     /// 是否已启动后台“管理口状态刷新”小工人（避免重复开工）
     mgmt_worker_started: Arc<AtomicBool>,
+    /// 连接状态防抖器（derived_status 用）
+    link_debounce: Arc<Mutex<LinkDebounce>>,
+    /// 总部登记体检（管理口小工人每轮更新）
+    registration: Arc<Mutex<RegistrationWatch>>,
+    /// 虚拟网卡巡查（`check_tap` 每轮更新）
+    tap_watch: Arc<Mutex<TapWatch>>,
+    /// 这一班的总部名单（主总部 + 备用总部，`check_failover` 按它轮换）
+    failover: Arc<Mutex<Failover>>,
     /// 同伴节点缓存（定期从 Management API 抄写一份“点名册”）
     peer_cache: Arc<Mutex<Vec<PeerNodeInfo>>>,
     /// 同伴延迟缓存（key 是 vpn_ip）
@@ -147,14 +598,46 @@ impl N2NProcess {
     /// 创建新的进程管理器实例
     pub fn new() -> Self {
         Self {
+            handles: Some(Arc::new(())),
             child: Arc::new(Mutex::new(None)),
+            #[cfg(windows)]
+            job: Arc::new(Mutex::new(None)),
+            supervisor: Arc::new(Mutex::new(None)),
+            adopted: Arc::new(AtomicU32::new(0)),
             status: Arc::new(Mutex::new(ConnectionStatus::Disconnected)),
             last_notice: Arc::new(Mutex::new(None)),
+            tail: Arc::new(Mutex::new(Tail::default())),
             log_tx: None,
+            event_tx: None,
+            warnings: Arc::new(Mutex::new(Vec::new())),
+            transport: Arc::new(Mutex::new(TransportFeatures::default())),
+            tap_adapter: Arc::new(Mutex::new(None)),
+            tap_network: Arc::new(Mutex::new(None)),
+            tap_reset: Arc::new(AtomicBool::new(false)),
+            node_name: Arc::new(Mutex::new(None)),
+            port_forwards: Arc::new(Mutex::new(Vec::new())),
+            local_port: Arc::new(Mutex::new(None)),
+            packet_forwarding: Arc::new(AtomicBool::new(false)),
+            register_interval: Arc::new(AtomicU32::new(DEFAULT_REGISTER_INTERVAL_SECS)),
+            routes: Arc::new(Mutex::new(Vec::new())),
+            stop_timeout: Arc::new(Mutex::new(DEFAULT_STOP_TIMEOUT)),
             auto_reconnect: Arc::new(Mutex::new(None)),
+            reconnect_pending: Arc::new(AtomicBool::new(false)),
+            reconnect_attempts: Arc::new(AtomicU32::new(0)),
+            reconnect_suspended_until: Arc::new(Mutex::new(None)),
+            generation: Arc::new(Generation::default()),
             stop_requested: Arc::new(AtomicBool::new(false)),
+            paused_session: Arc::new(Mutex::new(None)),
+            session: Arc::new(Mutex::new(None)),
+            ops: Arc::new(OperationGate::default()),
+            stop_throttle: Arc::new(Mutex::new(StopThrottle::default())),
             mgmt_password: Arc::new(Mutex::new(None)),
+            mgmt_port: Arc::new(AtomicU16::new(DEFAULT_MGMT_PORT)),
             mgmt_state: Arc::new(Mutex::new(MgmtState::default())),
+            link_debounce: Arc::new(Mutex::new(LinkDebounce::default())),
+            registration: Arc::new(Mutex::new(RegistrationWatch::default())),
+            tap_watch: Arc::new(Mutex::new(TapWatch::default())),
+            failover: Arc::new(Mutex::new(Failover::default())),
             mgmt_worker_started: Arc::new(AtomicBool::new(false)),
             peer_cache: Arc::new(Mutex::new(Vec::new())),
             peer_latency: Arc::new(Mutex::new(HashMap::new())),
@@ -163,7 +646,7 @@ impl N2NProcess {
     }
 
     /// 设置日志发送通道（并第一时间递上自我介绍汇报单）
-    pub fn set_log_sender(&mut self, tx: mpsc::UnboundedSender<String>) {
+    pub fn set_log_sender(&mut self, tx: LogSender) {
         self.log_tx = Some(tx);
         // 第一时间递上"上岗汇报单"，让主人一打开日志面板就知道恩兔已就位
         for line in [
@@ -179,147 +662,356 @@ impl N2NProcess {
         }
     }
 
+    /// 设置事件通道（小喇叭）
+    pub fn set_event_sender(&mut self, tx: EventSender) {
+        self.event_tx = Some(tx);
+    }
+
     /// 给日志面板塞一条“工作汇报”
     fn send_log_line(&self, line: String) {
         if let Some(ref tx) = self.log_tx {
-            let _ = tx.send(line);
+            tx.send(line);
         }
     }
 
-    /// 给主人一个进度提示（不会改变连接状态）
-    pub fn log_info(&self, msg: impl AsRef<str>) {
-        self.send_log_line(format!("[INFO] {}", msg.as_ref()));
+    /// 对主人说一句话（走 assistant-message 事件，不混进原始日志）
+    fn say(&self, message: AssistantMessage) {
+        events::say(self.event_tx.as_ref(), message);
+    }
+
+    /// 在闸门前排队，并确认这次操作在当前状态下是合理的
+    fn begin(&self, op: Operation) -> Result<std::sync::MutexGuard<'_, ()>> {
+        let ticket = self.ops.enter();
+        operation::check_transition(op, &self.status(), self.is_running())?;
+        Ok(ticket)
     }
 
     /// 启动 N2N edge 进程
     pub fn start(&self, config: &N2NConfig) -> Result<()> {
-        // 检查是否已经在运行
+        let _ticket = self.begin(Operation::Start)?;
+        // 主人亲自建立通道：之前排着的自动重连就不用了
+        self.reconnect_pending.store(false, Ordering::SeqCst);
+        self.reconnect_attempts.store(0, Ordering::SeqCst);
+        *self.failover.lock().unwrap() = Failover::new(&config.supernode, &config.supernode_failover);
+        self.start_inner(config)
+    }
+
+    /// 换了指示重新出门：停下 → 等它走 → 按新指示出门，一口气在闸门里做完
+    /// - 中途状态依次是“收拾工具中”“铺设通道中”，别的操作和看护员都插不进来，不会像分两步断开、连接那样互相抢；
+    /// - 温柔停止过了 stop_timeout 还不走就强制清理；
+    /// - 没在工作（待命、出错、暂停中）就直接出门，暂停便签作废。
+    pub fn reconnect(&self, config: &N2NConfig) -> Result<()> {
+        let _ticket = self.begin(Operation::Restart)?;
+        self.reconnect_pending.store(false, Ordering::SeqCst);
+        self.reconnect_attempts.store(0, Ordering::SeqCst);
+        *self.paused_session.lock().unwrap() = None;
+        *self.failover.lock().unwrap() = Failover::new(&config.supernode, &config.supernode_failover);
+
         if self.is_running() {
-            return Err(anyhow::anyhow!("N2N 进程已在运行"));
+            log::info!("恩兔按新的指示重新出门，先请旧的 edge 下班");
+            self.say(AssistantMessage::new("maid_restarting", Severity::Info));
+            self.stop_inner()?;
+            let timeout = self.stop_timeout();
+            if self.wait_exit_in_gate(timeout) {
+                self.send_log_line("[INFO] N2N 进程已断开".to_string());
+            } else {
+                log::warn!("旧的 edge 过了 {} 秒还没收拾完，恩兔只好强制清理", timeout.as_secs());
+                self.say(AssistantMessage::new("maid_force_stop_timeout", Severity::Warning));
+                self.stop_force_inner()?;
+                if self.is_running() {
+                    anyhow::bail!("旧的 edge 怎么也不肯走，没法重新出门");
+                }
+            }
         }
+        finish_session(&self.session, self.event_tx.as_ref(), "restart", None);
+        self.start_inner(config)
+    }
+
+    /// 在闸门里等 edge 走（看护员这时排在闸门外，收尾由调用方来做），到点还在就返回 false
+    fn wait_exit_in_gate(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let gone = {
+                let mut child_guard = self.child.lock().unwrap();
+                match child_guard.as_mut() {
+                    Some(child) => match reaper::poll(child) {
+                        Ok(ChildState::Exited(status)) => {
+                            log::info!("旧的 edge 已退出，状态：{:?}", status);
+                            *child_guard = None;
+                            true
+                        }
+                        Ok(ChildState::Running) => false,
+                        Err(e) => {
+                            log::error!("检查进程状态失败：{}", e);
+                            false
+                        }
+                    },
+                    // 接手来的 edge 没有句柄：只能看进程号还在不在
+                    None => match self.adopted_pid() {
+                        Some(pid) if process_alive(pid) => false,
+                        _ => {
+                            self.adopted.store(0, Ordering::SeqCst);
+                            true
+                        }
+                    },
+                }
+            };
+            if gone {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// 接手一只已经在跑的 edge（恩兔上次摔倒时留下的、或者主人自己启动的；`config` 是和它对得上的方案）
+    /// - 没有它的句柄，也听不到它的汇报：靠进程号看死活，靠管理口（`mgmt_port`）看连接状态、点名和请它下班；
+    /// - 它摔倒了只告诉主人，不自动重连。
+    pub fn adopt(&self, pid: u32, mgmt_port: u16, config: &N2NConfig) -> Result<()> {
+        let _ticket = self.begin(Operation::Start)?;
+        if !process_alive(pid) {
+            anyhow::bail!("edge（PID: {}）已经不在了", pid);
+        }
+        self.stop_requested.store(false, Ordering::SeqCst);
+        self.reconnect_pending.store(false, Ordering::SeqCst);
+        self.reconnect_attempts.store(0, Ordering::SeqCst);
+
+        *self.paused_session.lock().unwrap() = None;
+        *self.session.lock().unwrap() = Some(SessionTracker::new(config));
+        *self.status.lock().unwrap() = ConnectionStatus::Connecting;
+        *self.last_notice.lock().unwrap() = None;
+        self.warnings.lock().unwrap().clear();
+        *self.transport.lock().unwrap() = TransportFeatures::default();
+        *self.tap_adapter.lock().unwrap() = None;
+        *self.tap_network.lock().unwrap() = None;
+        *self.link_debounce.lock().unwrap() = LinkDebounce::default();
+        *self.registration.lock().unwrap() = RegistrationWatch::default();
+        *self.tap_watch.lock().unwrap() = TapWatch::default();
+        *self.node_name.lock().unwrap() = None;
+        *self.local_port.lock().unwrap() = config.local_port;
+        self.register_interval
+            .store(config.register_interval_secs.unwrap_or(DEFAULT_REGISTER_INTERVAL_SECS), Ordering::SeqCst);
+        *self.stop_timeout.lock().unwrap() = Duration::from_secs(config.stop_timeout_secs.max(1));
+        self.tap_reset.store(false, Ordering::SeqCst);
+        *self.mgmt_password.lock().unwrap() = management_password(config).map(str::to_string);
+        self.mgmt_port.store(mgmt_port, Ordering::SeqCst);
+        *self.auto_reconnect.lock().unwrap() = Some(config.clone());
+
+        let generation = self.generation.advance();
+        self.adopted.store(pid, Ordering::SeqCst);
+        log::info!("恩兔接手了已经在跑的 edge（PID: {}，管理口 {}）", pid, mgmt_port);
+
+        self.start_mgmt_worker_if_needed();
+        self.start_peer_worker_if_needed();
+        self.start_adopted_monitor(generation, pid);
+        self.say(AssistantMessage::new("maid_edge_adopted", Severity::Success).with("pid", pid));
+        Ok(())
+    }
+
+    /// 给看护员的一只手（放下时不收拾 edge，不然看护员一收工 edge 就被带走了）
+    fn worker(&self) -> Self {
+        let mut worker = self.clone();
+        worker.handles = None;
+        worker
+    }
+
+    /// 照看接手来的 edge：进程号消失了就收尾（和看护员一样，只是不重连）
+    fn start_adopted_monitor(&self, generation: u64, pid: u32) {
+        let this = self.worker();
+        let task = edge_runtime().spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick.tick().await;
+            loop {
+                tick.tick().await;
+                if tokio::task::block_in_place(|| this.watch_adopted(generation, pid)) {
+                    break;
+                }
+            }
+        });
+        if let Some(old) = self.supervisor.lock().unwrap().replace(task.abort_handle()) {
+            old.abort();
+        }
+    }
+
+    /// 看一眼接手来的 edge，这一班该收尾了就返回 true
+    fn watch_adopted(&self, generation: u64, pid: u32) -> bool {
+        if !self.generation.is_current(generation) {
+            return true;
+        }
+        let _ticket = self.ops.enter();
+        if self.adopted.load(Ordering::SeqCst) != pid {
+            return true;
+        }
+        if process_alive(pid) {
+            return false;
+        }
+        self.adopted.store(0, Ordering::SeqCst);
+
+        if self.stop_requested.load(Ordering::SeqCst) {
+            log::info!("接手的 edge 已退出（优雅断开完成），PID: {}", pid);
+            if let Some(ref tx) = self.log_tx {
+                tx.send("[INFO] N2N 进程已断开".to_string());
+            }
+            let idle = idle_status(&self.paused_session);
+            if idle == ConnectionStatus::Paused {
+                if let Some(t) = self.session.lock().unwrap().as_mut() {
+                    t.fold_current();
+                }
+            } else {
+                finish_session(&self.session, self.event_tx.as_ref(), "user_stop", None);
+            }
+            *self.status.lock().unwrap() = idle;
+            *self.last_notice.lock().unwrap() = None;
+        } else {
+            log::warn!("接手的 edge 意外退出，PID: {}", pid);
+            let msg = self
+                .last_notice
+                .lock()
+                .unwrap()
+                .clone()
+                .unwrap_or_else(|| "error_edge_exited".to_string());
+            *self.auto_reconnect.lock().unwrap() = None;
+            self.say(AssistantMessage::new("maid_edge_exited", Severity::Error).with("reason", msg.clone()));
+            finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", Some(msg.clone()));
+            // 接手来的 edge 没有汇报管子，也拿不到退出码
+            *self.status.lock().unwrap() = ConnectionStatus::Error(msg, Some(ExitReason::Unknown));
+        }
+        true
+    }
 
+    /// 真正的“开工”流程（调用方需已在闸门内）
+    fn start_inner(&self, config: &N2NConfig) -> Result<()> {
         // 本次启动不是“停止流程”的一部分
         self.stop_requested.store(false, Ordering::SeqCst);
 
+        // 方案填了总部域名：每次开工（包括自动重连）都重新去 DNS 里问一遍
+        let discovered = match crate::dns_discovery::resolve(config) {
+            Ok(discovered) => discovered,
+            Err(e) => {
+                let e = e.context("按域名找总部失败");
+                *self.status.lock().unwrap() = ConnectionStatus::Error(format!("{:#}", e), None);
+                return Err(e);
+            }
+        };
+        let config = discovered.as_ref().unwrap_or(config);
+
+        // 主人重新下达指示：之前暂停留下的便签就作废啦
+        *self.paused_session.lock().unwrap() = None;
+        self.session
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| SessionTracker::new(config));
+
         // 更新状态为连接中
         *self.status.lock().unwrap() = ConnectionStatus::Connecting;
-        // 清空上一次的“提醒便签”，避免主人看到过期信息
+        // 清空上一次的“提醒便签”和输出，避免主人看到过期信息
         *self.last_notice.lock().unwrap() = None;
+        self.tail.lock().unwrap().clear();
+        self.warnings.lock().unwrap().clear();
+        *self.transport.lock().unwrap() = TransportFeatures::default();
+        *self.tap_adapter.lock().unwrap() = None;
+        *self.tap_network.lock().unwrap() = None;
+        *self.link_debounce.lock().unwrap() = LinkDebounce::default();
+        *self.registration.lock().unwrap() = RegistrationWatch::default();
+        *self.tap_watch.lock().unwrap() = TapWatch::default();
 
         // 确定 edge 可执行文件路径
         let edge_path = config
             .edge_path
             .as_ref()
             .map(|p| p.to_string())
-            .unwrap_or_else(|| self.get_default_edge_path());
+            .unwrap_or_else(Self::default_edge_path);
 
         // 记录实际使用的 edge 路径（方便调试）
         log::info!("恩兔要打扫通道啦～ edge 可执行文件位置：{}", edge_path);
 
+        // edge 带着网卡权限在跑：被人掉包的话别放它出门（在授予 capabilities 之前核对，掉包的也白拿不到权限）
+        // 之后授予 capabilities、出门都用核对过的这个绝对路径，免得核对的和跑起来的不是同一个文件
+        let edge_path = match crate::edge_integrity::guard(&edge_path, &config.edge_sha256, config.edge_integrity_bypass) {
+            Ok(verified) => verified.to_string_lossy().to_string(),
+            Err(rejected) => {
+                *self.status.lock().unwrap() = ConnectionStatus::Error(rejected.code.to_string(), None);
+                if let (Some(alert), Some(tx)) = (rejected.alert, self.event_tx.as_ref()) {
+                    let _ = tx.send(ProcessEvent::EdgeBinaryRejected(alert));
+                }
+                return Err(anyhow::anyhow!(rejected.code));
+            }
+        };
+
         // Linux 下 edge 通常需要 root/capabilities（创建 TAP、切换权限等）
         // 这里优先尝试为 edge 二进制授予 capabilities，避免用 pkexec 包裹运行导致 stop() 无法精确控制 edge PID
         #[cfg(target_os = "linux")]
-        let edge_path = {
-            let mut edge_path = edge_path;
-            if !nix::unistd::Uid::effective().is_root() {
-                // 解析为绝对路径，避免 setcap/实际启动的二进制不一致
-                edge_path = match resolve_edge_path_for_caps(&edge_path) {
-                    Ok(p) => p,
-                    Err(e) => {
-                        *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string());
-                        return Err(e);
-                    }
-                };
-
-                // 如果用户取消授权或系统缺少依赖，直接中止连接流程，避免后续出现更难理解的 EPERM
-                if let Err(e) = ensure_edge_capabilities(&edge_path) {
-                    let e = anyhow::anyhow!(
-                        "需要管理员授权以配置 edge 权限（KDE 下会弹出授权窗口）。详细错误：{}",
-                        e
-                    );
-                    *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string());
-                    return Err(e);
-                }
+        if !nix::unistd::Uid::effective().is_root() {
+            // 如果用户取消授权或系统缺少依赖，直接中止连接流程，避免后续出现更难理解的 EPERM
+            if let Err(e) = ensure_edge_capabilities(&edge_path) {
+                let e = anyhow::anyhow!(
+                    "需要管理员授权以配置 edge 权限（KDE 下会弹出授权窗口）。详细错误：{}",
+                    e
+                );
+                *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string(), None);
+                return Err(e);
             }
-            edge_path
-        };
+        }
 
         // 校验 supernode 格式（必须是 host:port）
         if !config.supernode.contains(':') {
             let e = anyhow::anyhow!("Supernode 地址格式错误，必须包含端口号（如 vpn.example.com:7777）");
-            *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string());
+            *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string(), None);
             return Err(e);
         }
 
-        // 构建命令参数
-        // -c: 社区名称
-        // -l: supernode 地址（host:port）
-        //
-        // 备注：`-f`（前台运行）在部分 Windows 版本的 edge 中并不存在，会触发
-        // `WARNING: unknown option -f`，所以 Windows 下不再传入该参数。
-        let mut args = vec![
-            "-c".to_string(),
-            config.community.clone(),
-            "-l".to_string(),
-            config.supernode.clone(),
-        ];
+        // 出门前先看一眼：网卡被占、没有网络、时间偏差太大就别让 edge 白跑一圈超时了
+        if let Err(code) = crate::preflight::check(config) {
+            log::warn!("出门前检查没通过：{}", code);
+            *self.status.lock().unwrap() = ConnectionStatus::Error(code.to_string(), None);
+            return Err(anyhow::anyhow!(code));
+        }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            // -f: 前台运行（不 fork 到后台，便于监控）
-            args.insert(0, "-f".to_string());
+        // edge 不会的加密/压缩方式别交给它（它只会嘀咕一句 unknown option，然后悄悄用默认的）
+        let caps = crate::edge_caps::detect(&edge_path);
+        if let Some(ref caps) = caps {
+            if let Some(error) = crate::validation::validate_capabilities(config, caps).first() {
+                log::warn!("edge {:?} 不支持这份指示里的 {}", caps.version, error.field);
+                *self.status.lock().unwrap() = ConnectionStatus::Error(error.code.to_string(), None);
+                return Err(anyhow::anyhow!(error.code));
+            }
         }
 
         // -I: edge 描述/用户名（注意：不是 -n，-n 是路由配置）
         // 需求：配置中可留空，默认使用主机名
-        let node_name = if config.username.trim().is_empty() {
-            get_default_node_name()
-        } else {
-            config.username.clone()
+        let node_name = effective_node_name(config);
+        *self.node_name.lock().unwrap() = Some(node_name.clone());
+        *self.port_forwards.lock().unwrap() = config.port_forwards.clone();
+        *self.routes.lock().unwrap() = config.routes.clone();
+        *self.local_port.lock().unwrap() = config.local_port;
+        self.packet_forwarding.store(config.packet_forwarding, Ordering::SeqCst);
+        self.register_interval
+            .store(config.register_interval_secs.unwrap_or(DEFAULT_REGISTER_INTERVAL_SECS), Ordering::SeqCst);
+        *self.stop_timeout.lock().unwrap() = Duration::from_secs(config.stop_timeout_secs.max(1));
+        self.tap_reset.store(config.tap_reset_on_teardown, Ordering::SeqCst);
+
+        let args = match build_edge_args(config, &node_name, caps.as_ref()) {
+            Ok(args) => args,
+            Err(e) => {
+                *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string(), None);
+                return Err(e);
+            }
         };
-        args.push("-I".to_string());
-        args.push(node_name);
-
-        // 添加加密密钥
-        if !config.encryption_key.is_empty() {
-            args.push("-k".to_string());
-            args.push(config.encryption_key.clone());
-        }
 
-        // IP 地址配置
-        if config.ip_mode == "dhcp" {
-            args.push("-a".to_string());
-            args.push("dhcp:0.0.0.0".to_string());
-        } else if let Some(ref static_ip) = config.static_ip {
-            args.push("-a".to_string());
-            args.push(static_ip.clone());
-        }
-
-        // MTU 设置
-        if let Some(mtu) = config.mtu {
-            args.push("-M".to_string());
-            args.push(mtu.to_string());
-        }
-
-        // TAP 设备名称
-        if let Some(ref tap_device) = config.tap_device {
-            args.push("-d".to_string());
-            args.push(tap_device.clone());
-        }
-
-        // 额外参数
-        if let Some(ref extra_args) = config.extra_args {
-            let extra: Vec<String> = extra_args
-                .split_whitespace()
-                .map(|s| s.to_string())
-                .collect();
-            args.extend(extra);
-        }
+        // 记下这只 edge 的管理口和口令（之后的 Management API 查询都用它们）
+        *self.mgmt_password.lock().unwrap() = management_password(config).map(str::to_string);
+        self.mgmt_port
+            .store(config.management_port.unwrap_or(DEFAULT_MGMT_PORT), Ordering::SeqCst);
 
-        // 如果主人通过 extra_args 给 edge 设置了管理口令，恩兔也悄悄记下来（用于 Management API 查询）
-        {
-            let pw = extract_management_password(config.extra_args.as_deref());
-            *self.mgmt_password.lock().unwrap() = pw;
+        // 总部藏在端口敲门后面：先照着暗号敲一遍，防火墙开了门 edge 才进得去
+        if !config.port_knock.is_empty() {
+            log::info!("先敲总部的门（{} 下）", config.port_knock.len());
+            if let Err(e) = crate::port_knock::knock(supernode_host(&config.supernode), &config.port_knock) {
+                log::warn!("敲门没敲成，edge 照常出门：{:#}", e);
+            }
         }
 
         log::info!("启动 N2N edge: {} {:?}", edge_path, args);
@@ -330,230 +1022,530 @@ impl N2NProcess {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        // Windows 下别让 edge 额外弹出黑框框（恩兔会把工具箱悄悄拿出来干活）
+        // Windows 下别让 edge 额外弹出黑框框（恩兔会把工具箱悄悄拿出来干活）；
+        // 单独成一个进程组，断开时才能只对它发 CTRL_BREAK
         #[cfg(target_os = "windows")]
         {
-            cmd.creation_flags(CREATE_NO_WINDOW);
+            cmd.creation_flags(CREATE_NO_WINDOW | crate::console_ctrl::CREATE_NEW_PROCESS_GROUP);
         }
 
+        // edge 自立门户：它拉起的程序都在它的进程组里，强制清理时一起放倒
+        #[cfg(unix)]
+        crate::process_group::isolate(&mut cmd);
+
         // 在 Linux 上可能需要提权
         #[cfg(target_os = "linux")]
         {
             // 检查是否有 root 权限
-            if !nix::unistd::Uid::effective().is_root() {
+            let is_root = nix::unistd::Uid::effective().is_root();
+            if !is_root {
                 log::warn!("N2N 通常需要 root 权限，当前可能无法正常工作");
             }
+            if config.edge_hardening {
+                log::info!("给 edge 上锁：收窄环境变量、不继承多余文件{}", if is_root { "、no_new_privs" } else { "" });
+                crate::sandbox::harden(&mut cmd, is_root);
+            }
         }
 
-        let mut child = match cmd.spawn().context("启动 N2N edge 进程失败") {
+        // 交给 tokio 带出门（上锁等设置都已经在标准库的 Command 上做好了）
+        let mut cmd = tokio::process::Command::from(cmd);
+        let spawned = {
+            let _runtime = edge_runtime().enter();
+            cmd.spawn()
+        };
+        let mut child = match spawned.context("启动 N2N edge 进程失败") {
             Ok(child) => child,
             Err(e) => {
-                *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string());
+                *self.status.lock().unwrap() = ConnectionStatus::Error(e.to_string(), None);
                 return Err(e);
             }
         };
 
-        // 捕获 stdout 和 stderr
+        // edge 降权后恩兔自己发不动信号、清不动网卡：先向小帮手报个到，之后请它代劳时它才认这只 edge
+        #[cfg(target_os = "linux")]
+        if !nix::unistd::Uid::effective().is_root() {
+            if let Some(pid) = child.id() {
+                crate::priv_helper::claim(pid, config.tap_device.as_deref().unwrap_or("edge0"));
+            }
+        }
+
+        // Windows 没有进程组可以一起放倒：把 edge 装进 Job Object（换下来的旧 Job 关上时，上一班剩下的也一起清走）
+        #[cfg(windows)]
+        {
+            let job = crate::process_group::Job::new().and_then(|job| {
+                if let Some(handle) = child.raw_handle() {
+                    job.assign(handle)?;
+                }
+                Ok(job)
+            });
+            if let Err(e) = &job {
+                log::warn!("{:#}，edge 拉起的程序只能靠 taskkill /T 清理", e);
+            }
+            *self.job.lock().unwrap() = job.ok();
+        }
+
+        // 开新的一班：上一班还没收工的看护员会被叫停
+        let generation = self.generation.advance();
+
+        // 汇报管子交给看护员
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
 
-        let status_clone = Arc::clone(&self.status);
-        let last_notice = Arc::clone(&self.last_notice);
-        let log_tx_clone = self.log_tx.clone();
-        let stop_requested = Arc::clone(&self.stop_requested);
+        // 本次工作的录音（暂停、重连接着往同一份里录）
+        let output = self
+            .session
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|tracker| tracker.output.clone());
+        if let Some(ref output) = output {
+            output.begin_run(unix_now_seconds(), &edge_path);
+        }
 
-        // 启动线程读取输出
-        if let Some(stdout) = stdout {
-            let log_tx = log_tx_clone.clone();
-            let status = Arc::clone(&status_clone);
-            let stop_requested = Arc::clone(&stop_requested);
-            let last_notice = Arc::clone(&last_notice);
-            thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                let mut network_info: Option<NetworkInfo> = None;
-                
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        log::info!("N2N stdout: {}", line);
-
-                        // 如果主人已经让恩兔“收拾工具”，就别再用 ERROR 把主人吓一跳啦
-                        if stop_requested.load(Ordering::SeqCst) {
-                            if let Some(ref tx) = log_tx {
-                                let _ = tx.send(format!("[OUT] {}", line));
-                            }
-                            continue;
-                        }
+        let lines = LineReader {
+            status: Arc::clone(&self.status),
+            last_notice: Arc::clone(&self.last_notice),
+            tail: Arc::clone(&self.tail),
+            log_tx: self.log_tx.clone(),
+            stop_requested: Arc::clone(&self.stop_requested),
+            reconnect_attempts: Arc::clone(&self.reconnect_attempts),
+            warnings: Arc::clone(&self.warnings),
+            transport: Arc::clone(&self.transport),
+            tap_network: Arc::clone(&self.tap_network),
+            event_tx: self.event_tx.clone(),
+            output,
+            // 哪些汇报要递到日志面板（在看护员这里就筛掉，免得 -v -v -v 时把前端淹没）
+            log_filter: config.log_forward,
+            // 连上之后要先打招呼的重要同伴
+            important_peers: config.important_peers.clone(),
+            network_info: None,
+            peers_warmed: false,
+        };
 
-                        // 提取网卡信息：created local tap device IP: xxx.xxx.xxx.xxx, Mask: 255.255.255.0, MAC: xx:xx:xx:xx:xx:xx
-                        if line.contains("created local tap device") {
-                            if let Some(info) = parse_network_info(&line) {
-                                network_info = Some(info);
-                                log::info!("提取到网卡信息：{:?}", network_info);
-                                // 如果已经连接成功了，就把详情也补写进状态里（给主人递上“通道回执单”）
-                                if let Some(ref info) = network_info {
-                                    let current = status.lock().unwrap().clone();
-                                    if matches!(current, ConnectionStatus::Connected(_)) {
-                                        *status.lock().unwrap() = ConnectionStatus::Connected(Some(info.clone()));
-                                    }
-                                }
-                            }
-                        }
+        // 保存子进程句柄（上一班的还没退出的话交给收尸小工，别留下僵尸）
+        if let Some(old) = self.child.lock().unwrap().replace(child) {
+            reaper::abandon(old, edge_runtime().handle());
+        }
+        
+        // 保存配置以支持自动重连
+        *self.auto_reconnect.lock().unwrap() = Some(config.clone());
 
-                        // 检测连接成功的关键字
-                        // 备注：不同版本 edge 输出不完全一致，这里做兼容匹配
-                        if line.contains("edge <<<")
-                            || line.contains("[OK] edge <<<")
-                        {
-                            *status.lock().unwrap() = ConnectionStatus::Connected(network_info.clone());
-                            // 连接成功就把“提醒便签”撕掉，避免主人继续担心
-                            *last_notice.lock().unwrap() = None;
-                        }
+        // 后台启动“管理口状态刷新”小工人（缓存连接状态/时间戳等）
+        self.start_mgmt_worker_if_needed();
 
-                        // 识别常见问题并提示给主人（注意：edge 的 ERROR 可能出现在 stdout）
-                        if let Some(notice) = extract_user_facing_notice(&line) {
-                            set_last_notice_if_changed(&last_notice, notice);
-                        }
+        // 后台启动“同伴点名 + 延迟测量”小工人
+        // - 说明：它只在已连接时工作；断开/退出时会自动收工
+        // - 注意：必须在 child 句柄写入后再启动，否则小工人会误判“没有在工作”而提前收工
+        self.start_peer_worker_if_needed();
+        
+        // 看护员上岗：听汇报、等退出都归它
+        self.supervise(generation, stdout, stderr, lines);
 
-                        // 检测启动警告（仅日志提示，不改变状态）
-                        if line.contains("WARNING")
-                            && (line.contains("failed")
-                                || line.contains("invalid")
-                                || line.contains("malformed"))
-                        {
-                            log::warn!("N2N 启动警告：{}", line);
-                        }
-                        
-                        if let Some(ref tx) = log_tx {
-                            let _ = tx.send(format!("[OUT] {}", line));
+        Ok(())
+    }
+    
+    /// 派一个看护员照看这一班的 edge（跑在 `edge_runtime` 上的一个任务）
+    /// - 同时听 stdout、stderr 两根管子，一边等 edge 退出（不用隔一会儿去看一眼，退出了马上知道）；
+    /// - 新的一班开工时，上一班的看护员直接叫停：它只会停在等汇报、等退出的地方，不会半路留下乱摊子；
+    /// - edge 意外退出、按策略要重连时，把重连交给阻塞线程池去排队。
+    fn supervise(&self, generation: u64, stdout: Option<ChildStdout>, stderr: Option<ChildStderr>, mut lines: LineReader) {
+        let this = self.worker();
+        let task = edge_runtime().spawn(async move {
+            let mut stdout = stdout.map(|pipe| tokio::io::BufReader::new(pipe).lines());
+            let mut stderr = stderr.map(|pipe| tokio::io::BufReader::new(pipe).lines());
+
+            let retry = loop {
+                tokio::select! {
+                    line = next_line(&mut stdout) => lines.on_stdout(&line),
+                    line = next_line(&mut stderr) => lines.on_stderr(&line),
+                    _ = exited(&this.child) => {
+                        // 收尾要排队拿闸门，可能等一会儿：放到阻塞线程池里去等，别占着 edge_runtime
+                        let watcher = this.worker();
+                        match tokio::task::spawn_blocking(move || watcher.watch(generation)).await {
+                            Ok(Watch::Done(retry)) => break retry,
+                            Ok(Watch::Working) => {
+                                // 看不出退没退（查状态出错）：歇一秒再看，别原地打转
+                                tokio::time::sleep(Duration::from_secs(1)).await;
+                            }
+                            Err(e) => {
+                                log::error!("看护员收尾失败：{}", e);
+                                break None;
+                            }
                         }
                     }
                 }
-            });
+            };
+
+            if let Some((config, delay)) = retry.map(|retry| *retry) {
+                tokio::task::spawn_blocking(move || this.reconnect_later(config, delay));
+            }
+        });
+        if let Some(old) = self.supervisor.lock().unwrap().replace(task.abort_handle()) {
+            old.abort();
         }
+    }
 
-        if let Some(stderr) = stderr {
-            let log_tx = log_tx_clone;
-            let stop_requested = Arc::clone(&stop_requested);
-            let last_notice = Arc::clone(&last_notice);
-            thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(line) = line {
-                        log::warn!("N2N stderr: {}", line);
+    /// 看护员等到 edge 退出（或者句柄被收走）后来看一眼：edge 还在干活，还是这一班该收尾了
+    fn watch(&self, generation: u64) -> Watch {
+        // 已经换班了（新的 start 接手了）：这一班到此为止，别重复处理状态
+        if !self.generation.is_current(generation) {
+            log::debug!("第 {} 班的看护员已过期，收工", generation);
+            return Watch::Done(None);
+        }
 
-                        if stop_requested.load(Ordering::SeqCst) {
-                            if let Some(ref tx) = log_tx {
-                                let _ = tx.send(format!("[ERR] {}", line));
-                            }
-                            continue;
-                        }
-                        
-                        // 识别常见问题并提示给主人（stderr 里也会冒出关键 ERROR）
-                        if let Some(notice) = extract_user_facing_notice(&line) {
-                            set_last_notice_if_changed(&last_notice, notice);
-                        }
-                        
-                        if let Some(ref tx) = log_tx {
-                            let _ = tx.send(format!("[ERR] {}", line));
-                        }
-                    }
+        // 收尾时也要排队：避免和主人正在进行的启动/停止操作交错，导致状态与句柄对不上
+        let _ticket = self.ops.enter();
+        let mut child_guard = self.child.lock().unwrap();
+        let Some(child) = child_guard.as_mut() else {
+            // 没有运行中的进程，看护员下班
+            return Watch::Done(None);
+        };
+
+        // 检查进程是否还在运行（被别人先收走了也算退出，不然状态会一直卡着）
+        let exit_status = match reaper::poll(child) {
+            Ok(ChildState::Exited(exit_status)) => exit_status,
+            Ok(ChildState::Running) => return Watch::Working,
+            Err(e) => {
+                log::error!("检查进程状态失败：{}", e);
+                return Watch::Working;
+            }
+        };
+
+        let stopping = self.stop_requested.load(Ordering::SeqCst);
+        let reason = self.tail.lock().unwrap().classify(exit_status);
+        if stopping {
+            log::info!("N2N 进程已退出（优雅断开完成），状态：{:?}", exit_status);
+        } else {
+            log::warn!("N2N 进程意外退出，状态：{:?}，原因：{}", exit_status, reason.code());
+        }
+        if let Some(ref tx) = self.log_tx {
+            if stopping {
+                tx.send("[INFO] N2N 进程已断开".to_string());
+            } else {
+                tx.send(format!("[WARN] N2N 进程意外退出：{:?}（{}）", exit_status, reason.code()));
+            }
+        }
+
+        // 清除子进程句柄
+        *child_guard = None;
+        drop(child_guard);
+        self.verify_teardown();
+
+        // 主人主动断开：回到待命；否则：进程都摔倒了，必须给主人一个“出错了”的交代
+        if stopping {
+            let idle = idle_status(&self.paused_session);
+            if idle == ConnectionStatus::Paused {
+                // 只是歇一会儿：本次工作还没结束，先把这一轮的计数记进账本
+                if let Some(t) = self.session.lock().unwrap().as_mut() {
+                    t.fold_current();
                 }
-            });
+            } else {
+                finish_session(&self.session, self.event_tx.as_ref(), "user_stop", None);
+            }
+            *self.status.lock().unwrap() = idle;
+            *self.last_notice.lock().unwrap() = None;
+            return Watch::Done(None);
+        }
+
+        // 摔倒的原因记进账本：自动重连爬起来了，总结和历史里也看得到这一跤
+        if let Some(t) = self.session.lock().unwrap().as_mut() {
+            t.exit_reason = Some(reason);
+        }
+        let msg = self
+            .last_notice
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| "error_edge_exited".to_string());
+        let attempt = self.reconnect_attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut retry = self.auto_reconnect.lock().unwrap().clone().and_then(|config| {
+            reconnect_delay(&config, exit_status.is_some_and(|s| s.success()), attempt)
+                .map(|delay| (config, with_jitter(delay, random_roll())))
+        });
+        if retry.is_some() && self.reconnect_suspended_until().is_some() {
+            log::info!("主人说过这段时间先别自动重连，恩兔就不重连了");
+            retry = None;
         }
+        if retry.is_some() {
+            // 还要接着干：这一轮的计数先记进账本，状态保持“连接中”
+            if let Some(t) = self.session.lock().unwrap().as_mut() {
+                t.fold_current();
+                t.reconnect_count = t.reconnect_count.saturating_add(1);
+            }
+            self.reconnect_pending.store(true, Ordering::SeqCst);
+            *self.status.lock().unwrap() = ConnectionStatus::Reconnecting(attempt);
+        } else {
+            self.say(AssistantMessage::new("maid_edge_exited", Severity::Error).with("reason", msg.clone()));
+            finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", Some(msg.clone()));
+            *self.status.lock().unwrap() = ConnectionStatus::Error(msg, Some(reason));
+        }
+        Watch::Done(retry.map(Box::new))
+    }
+
+    /// 等一会儿再按原来的指示重新建立通道；启动失败就按策略继续排队，直到次数用完
+    fn reconnect_later(&self, config: N2NConfig, mut delay: Duration) {
+        loop {
+            let attempt = self.reconnect_attempts.load(Ordering::SeqCst);
+            let reason = self.session.lock().unwrap().as_ref().and_then(|t| t.exit_reason).unwrap_or(ExitReason::Unknown);
+            log::warn!(
+                "edge 意外退出（{}），{:.1} 秒后进行第 {} 次重连",
+                reason.code(),
+                delay.as_secs_f32(),
+                attempt
+            );
+            self.say(
+                AssistantMessage::new("maid_reconnecting", Severity::Warning)
+                    .with("delay", delay.as_secs_f32().round() as u64)
+                    .with("attempt", attempt)
+                    .with("reason", reason.code()),
+            );
+            thread::sleep(delay);
+
+            let _ticket = self.ops.enter();
+            // 等待期间主人断开了，或者已经亲自重新建立了通道
+            if !self.reconnect_pending.swap(false, Ordering::SeqCst) || self.is_running() {
+                return;
+            }
+            // 等待期间主人说了先别自动重连：这次工作就到这里
+            if self.reconnect_suspended_until().is_some() {
+                log::info!("自动重连被临时叫停，排队中的重连取消");
+                *self.auto_reconnect.lock().unwrap() = None;
+                self.reconnect_attempts.store(0, Ordering::SeqCst);
+                finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", None);
+                *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+                return;
+            }
+            let error = match self.start_inner(&config) {
+                Ok(()) => return,
+                Err(e) => e.to_string(),
+            };
+            log::warn!("第 {} 次重连失败：{}", attempt, error);
+
+            let attempt = self.reconnect_attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            match reconnect_delay(&config, false, attempt) {
+                Some(next) => {
+                    delay = with_jitter(next, random_roll());
+                    self.reconnect_pending.store(true, Ordering::SeqCst);
+                    *self.status.lock().unwrap() = ConnectionStatus::Reconnecting(attempt);
+                }
+                None => {
+                    // 次数用完了：本次工作到此结束，状态停在 start_inner 留下的错误上
+                    log::warn!("重连次数用完，停下来等主人指示");
+                    self.say(AssistantMessage::new("maid_reconnect_gave_up", Severity::Error).with("attempts", attempt));
+                    finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", Some(error));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// 临时叫停自动重连和自我巡查（`duration` 为 0 表示马上恢复），返回叫停到什么时候
+    pub fn suspend_reconnect(&self, duration: Duration) -> Option<u64> {
+        let until = (!duration.is_zero()).then(|| unix_now_seconds().saturating_add(duration.as_secs().max(1)));
+        *self.reconnect_suspended_until.lock().unwrap() = until;
+        match until {
+            Some(_) => {
+                log::info!("自动重连临时叫停 {} 秒", duration.as_secs());
+                self.say(
+                    AssistantMessage::new("maid_reconnect_suspended", Severity::Info)
+                        .with("minutes", duration.as_secs().div_ceil(60)),
+                );
+            }
+            None => {
+                log::info!("自动重连恢复");
+                self.say(AssistantMessage::new("maid_reconnect_unsuspended", Severity::Info));
+            }
+        }
+        until
+    }
+
+    /// 自动重连被叫停到什么时候（没叫停或者已经到点了为 None）
+    pub fn reconnect_suspended_until(&self) -> Option<u64> {
+        let mut until = self.reconnect_suspended_until.lock().unwrap();
+        if until.is_some_and(|t| t <= unix_now_seconds()) {
+            *until = None;
+        }
+        *until
+    }
+
+    /// 取消排队中的自动重连（主人在等待期间断开，或者在工作台/托盘上点了取消）；返回是否真的取消了
+    pub fn cancel_pending_reconnect(&self) -> bool {
+        let _ticket = self.ops.enter();
+        if self.is_running() || !self.reconnect_pending.swap(false, Ordering::SeqCst) {
+            return false;
+        }
+        *self.auto_reconnect.lock().unwrap() = None;
+        self.reconnect_attempts.store(0, Ordering::SeqCst);
+        finish_session(&self.session, self.event_tx.as_ref(), "user_stop", None);
+        *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+        self.say(AssistantMessage::new("maid_reconnect_cancelled", Severity::Info));
+        true
+    }
+
+    /// 停止 N2N edge 进程（完全断开：暂停便签和本次工作记录一起收走）
+    pub fn stop(&self) -> Result<()> {
+        if self.cancel_pending_reconnect() {
+            return Ok(());
+        }
+        self.throttled_stop(Operation::Stop, || {
+            let _ticket = self.begin(Operation::Stop)?;
+            let was_paused = self.paused_session.lock().unwrap().take().is_some();
+            if was_paused && !self.is_running() {
+                // 暂停中 edge 本来就没在工作，直接回到待命就好（本次工作到此结束）
+                finish_session(&self.session, self.event_tx.as_ref(), "user_stop", None);
+                *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+                return Ok(());
+            }
+            self.stop_inner()
+        })
+    }
+
+    /// 停止请求先过节流阀：同一种停止正在进行、或者刚对这只 edge 出过手时直接说明，不再排队
+    fn throttled_stop(&self, op: Operation, stop: impl FnOnce() -> Result<()>) -> Result<()> {
+        let pid = self.pid();
+        if let Err(e) = self.stop_throttle.lock().unwrap().admit(op, pid, Instant::now()) {
+            log::info!("停止请求被拦下：{}", e);
+            let message = match e {
+                OpError::StopCoolingDown { remaining_secs } => {
+                    AssistantMessage::new("maid_stop_cooling_down", Severity::Info).with("secs", remaining_secs)
+                }
+                _ => AssistantMessage::new("maid_stop_in_progress", Severity::Info),
+            };
+            self.say(message);
+            return Err(e.into());
+        }
+        let result = stop();
+        self.stop_throttle.lock().unwrap().finish(op);
+        result
+    }
+
+    /// 歇一会儿（暂停）：温柔地停下 edge，但把本次工作的指示、地址和计数都记在便签上
+    pub fn pause(&self) -> Result<()> {
+        let _ticket = self.begin(Operation::Pause)?;
+        self.park(AssistantMessage::new("maid_paused", Severity::Info), true)
+    }
+
+    /// 把本次工作记上便签再温柔停下 edge（暂停和换总部共用）
+    /// - `count_pause`：要不要算作主人暂停了一次
+    fn park(&self, message: AssistantMessage, count_pause: bool) -> Result<()> {
+        let config = self
+            .auto_reconnect
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("恩兔现在没有在打扫通道，没法暂停哦"))?;
 
-        // 保存子进程句柄
-        *self.child.lock().unwrap() = Some(child);
-        
-        // 保存配置以支持自动重连
-        *self.auto_reconnect.lock().unwrap() = Some(config.clone());
+        let network_info = match self.status() {
+            ConnectionStatus::Connected(info) => info,
+            _ => None,
+        };
+        let (session_started_at, pause_count) = {
+            let mut session = self.session.lock().unwrap();
+            let t = session.get_or_insert_with(|| SessionTracker::new(&config));
+            if count_pause {
+                t.pause_count = t.pause_count.saturating_add(1);
+            }
+            (t.started_at, t.pause_count)
+        };
 
-        // 后台启动“管理口状态刷新”小工人（缓存连接状态/时间戳等）
-        self.start_mgmt_worker_if_needed();
+        *self.paused_session.lock().unwrap() = Some(PausedSession {
+            config,
+            network_info,
+            session_started_at,
+            pause_count,
+        });
+        self.say(message);
+        self.stop_inner()
+    }
 
-        // 后台启动“同伴点名 + 延迟测量”小工人
-        // - 说明：它只在已连接时工作；断开/退出时会自动收工
-        // - 注意：必须在 child 句柄写入后再启动，否则小工人会误判“没有在工作”而提前收工
-        self.start_peer_worker_if_needed();
-        
-        // 启动进程监控线程
-        let _ = self.start_monitor();
+    /// 继续打扫（恢复）：按暂停时的便签重新启动 edge
+    /// - 如果之前是自动分配地址，就把拿到的地址固定下来再申请一次，尽量让同伴看到的还是同一个地址
+    pub fn resume(&self) -> Result<()> {
+        let _ticket = self.begin(Operation::Resume)?;
+        self.unpark(AssistantMessage::new("maid_resumed", Severity::Info), |_| {})
+    }
+
+    /// 换总部第一步：像暂停一样记下便签（保住地址），温柔停下 edge
+    /// - n2n 的管理口没有“改总部”的命令，只能换一身参数重新出门；调用方等 edge 停稳后再 `resume_on`
+    pub fn pause_for_switch(&self, address: &str) -> Result<()> {
+        let _ticket = self.begin(Operation::SwitchSupernode)?;
+        self.park(
+            AssistantMessage::new("maid_supernode_switching", Severity::Info).with("address", address),
+            false,
+        )
+    }
 
+    /// 换总部第二步：按便签朝新的总部重新出门（没成功的话便签还在，主人可以照旧恢复）
+    pub fn resume_on(&self, address: &str) -> Result<()> {
+        let _ticket = self.begin(Operation::Resume)?;
+        self.resume_on_inner(address)
+    }
+
+    /// 朝新的总部重新出门（调用方需已在闸门内）
+    fn resume_on_inner(&self, address: &str) -> Result<()> {
+        self.unpark(
+            AssistantMessage::new("maid_supernode_switched", Severity::Info).with("address", address),
+            |config| {
+                config.supernode = address.to_string();
+                config.supernode_id = None;
+                config.supernode_dns = None;
+            },
+        )?;
+        if let Some(t) = self.session.lock().unwrap().as_mut() {
+            t.supernode = address.to_string();
+        }
         Ok(())
     }
-    
-    /// 启动进程监控线程（用于自动重连）
-    fn start_monitor(&self) -> Result<()> {
-        let child_clone = Arc::clone(&self.child);
-        let status_clone = Arc::clone(&self.status);
-        let last_notice = Arc::clone(&self.last_notice);
-        let log_tx_clone = self.log_tx.clone();
-        let stop_requested = Arc::clone(&self.stop_requested);
-        
-        thread::spawn(move || {
-            loop {
-            // 断开流程可能较长，这里加快轮询以便 UI 更快感知退出
-            thread::sleep(std::time::Duration::from_secs(1));
-                
-                let mut child_guard = child_clone.lock().unwrap();
-                
-                if let Some(child) = child_guard.as_mut() {
-                    // 检查进程是否还在运行
-                    match child.try_wait() {
-                        Ok(Some(exit_status)) => {
-                            if stop_requested.load(Ordering::SeqCst) {
-                                log::info!("N2N 进程已退出（优雅断开完成），状态：{:?}", exit_status);
-                            } else {
-                                log::warn!("N2N 进程意外退出，状态：{:?}", exit_status);
-                            }
-                            
-                            if let Some(ref tx) = log_tx_clone {
-                                if stop_requested.load(Ordering::SeqCst) {
-                                    let _ = tx.send("[INFO] N2N 进程已断开".to_string());
-                                } else {
-                                    let _ = tx.send(format!("[WARN] N2N 进程意外退出：{:?}", exit_status));
-                                }
-                            }
-                            
-                            // 清除子进程句柄
-                            *child_guard = None;
-                            drop(child_guard);
-
-                            // 主人主动断开：回到待命；否则：进程都摔倒了，必须给主人一个“出错了”的交代
-                            if stop_requested.load(Ordering::SeqCst) {
-                                *status_clone.lock().unwrap() = ConnectionStatus::Disconnected;
-                                *last_notice.lock().unwrap() = None;
-                            } else {
-                                let msg = last_notice
-                                    .lock()
-                                    .unwrap()
-                                    .clone()
-                                    .unwrap_or_else(|| "error_edge_exited".to_string());
-                                *status_clone.lock().unwrap() = ConnectionStatus::Error(msg);
-                            }
-                            break;
-                        }
-                        Ok(None) => {
-                            // 进程仍在运行
-                        }
-                        Err(e) => {
-                            log::error!("检查进程状态失败：{}", e);
-                        }
-                    }
-                } else {
-                    // 没有运行中的进程，退出监控线程
-                    break;
+
+    /// 按暂停便签重新启动 edge（`retarget` 可以在出门前改一改指示）
+    fn unpark(&self, message: AssistantMessage, retarget: impl FnOnce(&mut N2NConfig)) -> Result<()> {
+        let session = self
+            .paused_session
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("没有找到暂停的工作便签，请直接建立通道吧"))?;
+
+        let mut config = session.config.clone();
+        retarget(&mut config);
+        if config.ip_mode == "dhcp" {
+            if let Some(ref info) = session.network_info {
+                if let Some(prefix) = netmask_to_prefix(&info.mask) {
+                    config.ip_mode = "static".to_string();
+                    config.static_ip = Some(format!("{}/{}", info.ip, prefix));
                 }
             }
-        });
+        }
+
+        self.say(message);
+        if let Some(t) = self.session.lock().unwrap().as_mut() {
+            t.reconnect_count = t.reconnect_count.saturating_add(1);
+        }
+        if let Err(e) = self.start_inner(&config) {
+            // 没能继续：把便签放回去，主人还可以再试一次
+            *self.paused_session.lock().unwrap() = Some(session);
+            return Err(e);
+        }
         Ok(())
     }
 
-    /// 停止 N2N edge 进程
-    pub fn stop(&self) -> Result<()> {
+    /// 这一班正在用的指示（暂停中的看便签；没在工作时为 None）
+    pub fn running_config(&self) -> Option<N2NConfig> {
+        let config = self.auto_reconnect.lock().unwrap().clone();
+        config.or_else(|| self.paused_session.lock().unwrap().as_ref().map(|s| s.config.clone()))
+    }
+
+    /// 是否正处于暂停（歇一会儿）状态
+    pub fn is_paused(&self) -> bool {
+        self.paused_session.lock().unwrap().is_some()
+    }
+
+    /// 真正的“收拾工具”流程（断开与暂停共用）
+    fn stop_inner(&self) -> Result<()> {
         // 清除自动重连配置
         *self.auto_reconnect.lock().unwrap() = None;
 
-        // 标记为主动停止，避免读线程把退出误判为错误
+        // 标记为主动停止，避免看护员把退出误判为错误
         self.stop_requested.store(true, Ordering::SeqCst);
         // 主人都叫停了，就别再拿旧的“提醒便签”继续叨叨啦
         *self.last_notice.lock().unwrap() = None;
@@ -561,48 +1553,39 @@ impl N2NProcess {
         // 立刻切换状态，UI 侧可提示用户等待
         *self.status.lock().unwrap() = ConnectionStatus::Disconnecting;
 
-        // 优先用 Management API 的 stop 来“礼貌请离”，避免 Linux 下还得借 pkexec 才能发信号
+        // 优先用 Management API 的 stop 来“礼貌请离”，避免 Linux 下还得请 root 小帮手代发信号、Windows 下 taskkill 请不动
         // - 备注：写操作通常需要认证；如果主人没设置，默认密码是 n2n
-        if self.try_management_stop().is_ok() {
-            // stop 已递出：把缓存收一收，UI 就不会展示旧信息啦
-            self.reset_peer_state();
-            self.reset_mgmt_state();
-            return Ok(());
+        match self.try_management_stop() {
+            Ok(()) => {
+                log::info!("已通过管理口请 edge 下班（端口 {}）", self.management_port());
+                // stop 已递出：把缓存收一收，UI 就不会展示旧信息啦
+                self.reset_peer_state();
+                self.reset_mgmt_state();
+                return Ok(());
+            }
+            Err(e) => log::info!("管理口没能请 edge 下班（{:#}），改用信号", e),
         }
 
         // Management API stop 失败：再走传统 SIGINT 路线兜底
         self.reset_peer_state();
         self.reset_mgmt_state();
 
-        let child_guard = self.child.lock().unwrap();
-        if let Some(child) = child_guard.as_ref() {
-            let pid = child.id() as i32;
+        if let Some(pid) = self.pid() {
+            let pid = pid as i32;
             log::info!("开始优雅停止 N2N edge 进程（SIGINT），PID: {}", pid);
             
             // edge 启动后会 setuid 降权到 nobody，普通用户无法直接发送信号
-            // 需要通过 pkexec/sudo 来发送信号
+            // 需要请常驻小帮手代发（第一次会弹一次授权框，之后就不打扰主人了）
             #[cfg(target_os = "linux")]
             {
                 // 先尝试直接发送（如果是 root 或者 edge 没有降权）
                 let direct_result = kill(Pid::from_raw(pid), Signal::SIGINT);
                 if direct_result.is_err() {
-                    log::info!("直接发送 SIGINT 失败（可能 edge 已降权），尝试通过 pkexec 发送");
-                    // 使用 pkexec kill 发送信号
-                    let status = Command::new("pkexec")
-                        .arg("kill")
-                        .arg("-SIGINT")
-                        .arg(pid.to_string())
-                        .status();
-                    match status {
-                        Ok(s) if s.success() => {
-                            log::info!("通过 pkexec 发送 SIGINT 成功");
-                        }
-                        Ok(s) => {
-                            log::warn!("pkexec kill 返回非零退出码：{:?}", s.code());
-                        }
-                        Err(e) => {
-                            log::error!("执行 pkexec kill 失败：{}", e);
-                        }
+                    log::info!("直接发送 SIGINT 失败（可能 edge 已降权），请小帮手代发");
+                    let request = crate::priv_helper::Request::Signal { pid: pid as u32, force: false, group: false };
+                    match crate::priv_helper::call(&request) {
+                        Ok(()) => log::info!("小帮手已代发 SIGINT"),
+                        Err(e) => log::error!("小帮手代发 SIGINT 失败：{:#}", e),
                     }
                 } else {
                     log::info!("直接发送 SIGINT 成功");
@@ -616,7 +1599,18 @@ impl N2NProcess {
 
             #[cfg(windows)]
             {
-                // Windows 没有 SIGINT，恩兔就改用系统自带的 taskkill 来“轻轻拍一下肩膀”
+                // Windows 没有 SIGINT：恩兔带出门的 edge 单独成组，发 CTRL_BREAK 让它跟总部道别再走
+                let own = self.child.lock().unwrap().is_some();
+                if own {
+                    match crate::console_ctrl::send_break(pid as u32) {
+                        Ok(()) => {
+                            log::info!("已给 edge 发 CTRL_BREAK（PID: {}）", pid);
+                            return Ok(());
+                        }
+                        Err(e) => log::warn!("{:#}，改用 taskkill", e),
+                    }
+                }
+                // 接手来的 edge、或者 CTRL_BREAK 没发出去：用系统自带的 taskkill 来“轻轻拍一下肩膀”
                 // 备注：不加 /F 代表尽量温柔；如果 edge 不听话，主人还可以用“强制断开”
                 let status = Command::new("taskkill")
                     .arg("/PID")
@@ -643,6 +1637,17 @@ impl N2NProcess {
 
     /// 强制停止 N2N edge 进程（SIGKILL），用于优雅退出卡住时兜底
     pub fn stop_force(&self) -> Result<()> {
+        if self.cancel_pending_reconnect() {
+            return Ok(());
+        }
+        self.throttled_stop(Operation::ForceStop, || {
+            let _ticket = self.begin(Operation::ForceStop)?;
+            self.stop_force_inner()
+        })
+    }
+
+    /// 真正的“掸子重击”流程（调用方需已在闸门内）
+    fn stop_force_inner(&self) -> Result<()> {
         // 清除自动重连配置
         *self.auto_reconnect.lock().unwrap() = None;
 
@@ -654,30 +1659,49 @@ impl N2NProcess {
         self.reset_mgmt_state();
 
         let mut child_guard = self.child.lock().unwrap();
-        if let Some(child) = child_guard.as_mut() {
-            let pid = child.id() as i32;
+        let pid = child_guard.as_ref().and_then(Child::id).or_else(|| self.adopted_pid());
+        if let Some(pid) = pid {
+            let pid = pid as i32;
             log::warn!("强制停止 N2N edge 进程（SIGKILL），PID: {}", pid);
-            
+
+            // 恩兔带出门的 edge 自己是组长：连它拉起的程序一起放倒（接手来的只清它本身）
+            #[cfg(unix)]
+            let group_done = child_guard.is_some()
+                && match crate::process_group::kill(pid as u32) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        log::warn!("整组强制清理失败（{:#}），只清理 edge 本身", e);
+                        false
+                    }
+                };
+
             // edge 启动后会 setuid 降权到 nobody，普通用户无法直接发送信号
             #[cfg(target_os = "linux")]
-            {
+            if !group_done {
                 // 先尝试直接发送
                 let direct_result = kill(Pid::from_raw(pid), Signal::SIGKILL);
                 if direct_result.is_err() {
-                    log::info!("直接发送 SIGKILL 失败，尝试通过 pkexec 发送");
-                    let _ = Command::new("pkexec")
-                        .arg("kill")
-                        .arg("-9")
-                        .arg(pid.to_string())
-                        .status();
+                    log::info!("直接发送 SIGKILL 失败，请小帮手代发");
+                    let request = crate::priv_helper::Request::Signal { pid: pid as u32, force: true, group: false };
+                    if let Err(e) = crate::priv_helper::call(&request) {
+                        log::error!("小帮手代发 SIGKILL 失败：{:#}", e);
+                    }
                 }
             }
             
             #[cfg(all(unix, not(target_os = "linux")))]
-            {
+            if !group_done {
                 let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
             }
 
+            // Windows：先把 Job Object 里的一家子一起清走，edge 本身再用 taskkill 兜一下
+            #[cfg(windows)]
+            if let Some(job) = self.job.lock().unwrap().take() {
+                if let Err(e) = job.terminate() {
+                    log::warn!("{:#}", e);
+                }
+            }
+
             #[cfg(windows)]
             {
                 // Windows 上就用“掸子重击”模式：/T 递归清理子进程，/F 强制结束
@@ -702,36 +1726,360 @@ impl N2NProcess {
 
             // 尝试快速回收子进程，避免残留/僵尸
             let deadline = Instant::now() + Duration::from_secs(3);
-            loop {
-                match child.try_wait() {
-                    Ok(Some(_)) => {
-                        *child_guard = None;
-                        break;
-                    }
-                    Ok(None) => {
-                        if Instant::now() >= deadline {
+            match child_guard.as_mut() {
+                Some(child) => loop {
+                    match reaper::poll(child) {
+                        Ok(ChildState::Exited(_)) => {
+                            *child_guard = None;
+                            self.verify_teardown();
+                            break;
+                        }
+                        Ok(ChildState::Running) => {
+                            if Instant::now() >= deadline {
+                                break;
+                            }
+                            thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(e) => {
+                            log::error!("强制停止后检查进程状态失败：{}", e);
                             break;
                         }
-                        thread::sleep(Duration::from_millis(100));
                     }
-                    Err(e) => {
-                        log::error!("强制停止后检查进程状态失败：{}", e);
-                        break;
+                },
+                // 接手来的 edge 没有句柄：只能看进程号还在不在
+                None => {
+                    if crate::orphans::wait_gone(pid as u32, Duration::from_secs(3)) {
+                        self.adopted.store(0, Ordering::SeqCst);
                     }
                 }
             }
 
-            *self.status.lock().unwrap() = ConnectionStatus::Disconnected;
+            let idle = idle_status(&self.paused_session);
+            if idle == ConnectionStatus::Paused {
+                if let Some(t) = self.session.lock().unwrap().as_mut() {
+                    t.fold_current();
+                }
+            } else {
+                finish_session(&self.session, self.event_tx.as_ref(), "force_stop", None);
+            }
+            *self.status.lock().unwrap() = idle;
             Ok(())
         } else {
             Err(anyhow::anyhow!("N2N 进程未运行"))
         }
     }
 
+    /// 温柔停止后最多等多久
+    pub fn stop_timeout(&self) -> Duration {
+        *self.stop_timeout.lock().unwrap()
+    }
+
+    /// 等温柔停止收尾：过了 stop_timeout 还没停下就强制清理，返回是否动用了强制
+    /// （等待期间主人又建立了新通道的话就不管了，免得误伤新的一班）
+    pub fn wait_stopped_or_force(&self) -> bool {
+        let timeout = self.stop_timeout();
+        let deadline = Instant::now() + timeout;
+        let generation = self.generation.current();
+        while self.is_running() && self.generation.is_current(generation) {
+            if Instant::now() >= deadline {
+                log::warn!("edge 过了 {} 秒还没收拾完，恩兔只好强制清理", timeout.as_secs());
+                self.say(AssistantMessage::new("maid_force_stop_timeout", Severity::Warning));
+                if let Err(e) = self.stop_force() {
+                    log::warn!("强制清理失败：{}", e);
+                }
+                return true;
+            }
+            thread::sleep(Duration::from_millis(200));
+        }
+        false
+    }
+
+    /// 给巡查员看一眼：句柄、进程死活、账本状态和重连排队
+    pub fn observe(&self) -> Observation {
+        let (has_child, child_exited) = match (self.child.lock().unwrap().as_mut(), self.adopted_pid()) {
+            (Some(child), _) => (true, matches!(reaper::poll(child), Ok(ChildState::Exited(_)))),
+            (None, Some(pid)) => (true, !process_alive(pid)),
+            (None, None) => (false, false),
+        };
+        Observation {
+            has_child,
+            child_exited,
+            status: self.status(),
+            reconnect_pending: self.reconnect_pending.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 修好巡查员确认的问题（排队后再核对一遍，已经好了就不动），返回是否真的动了手
+    pub fn repair(&self, issue: StateIssue) -> bool {
+        let _ticket = self.ops.enter();
+        let obs = self.observe();
+        let busy = matches!(
+            obs.status,
+            ConnectionStatus::Connecting
+                | ConnectionStatus::Connected(_)
+                | ConnectionStatus::Disconnecting
+                | ConnectionStatus::Reconnecting(_)
+        );
+        match issue {
+            StateIssue::ChildGone if obs.has_child && obs.child_exited => {
+                *self.child.lock().unwrap() = None;
+                self.adopted.store(0, Ordering::SeqCst);
+                self.reset_peer_state();
+                self.reset_mgmt_state();
+                if self.stop_requested.load(Ordering::SeqCst) {
+                    self.settle_idle("user_stop");
+                } else {
+                    let msg = self
+                        .last_notice
+                        .lock()
+                        .unwrap()
+                        .clone()
+                        .unwrap_or_else(|| "error_edge_exited".to_string());
+                    *self.auto_reconnect.lock().unwrap() = None;
+                    finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", Some(msg.clone()));
+                    // 退出码被别人收走了，只能从输出里找线索
+                    let reason = self.tail.lock().unwrap().classify(None);
+                    *self.status.lock().unwrap() = ConnectionStatus::Error(msg, Some(reason));
+                }
+            }
+            StateIssue::NoChild if !obs.has_child && busy && !obs.reconnect_pending => {
+                self.reset_peer_state();
+                self.reset_mgmt_state();
+                self.settle_idle("state_repaired");
+            }
+            StateIssue::StuckDisconnecting if obs.status == ConnectionStatus::Disconnecting => {
+                if obs.has_child {
+                    if let Err(e) = self.stop_force_inner() {
+                        log::warn!("强制清理卡住的 edge 失败：{}", e);
+                    }
+                } else {
+                    self.settle_idle("state_repaired");
+                }
+            }
+            _ => return false,
+        }
+        log::warn!("巡查发现状态对不上（{}），恩兔已经修好了", issue.code());
+        self.say(AssistantMessage::new(&format!("maid_state_{}", issue.code()), Severity::Warning));
+        true
+    }
+
+    /// 巡查一次虚拟网卡（连上之后才看；巡查小工人每隔几秒叫一次）
+    /// - 睡眠唤醒、驱动抽风可能把网卡弄没、停用或者把地址冲掉，edge 自己却浑然不觉；
+    /// - 确认出了毛病就对外变成 `Degraded` 并告诉主人，方案里打开了 `tap_restart_on_loss` 就直接重新出门，
+    ///   让 edge 重新建一块网卡；网卡自己好了就恢复。
+    pub fn check_tap(&self) {
+        if self.stop_requested.load(Ordering::SeqCst)
+            || !self.is_running()
+            || !matches!(self.status(), ConnectionStatus::Connected(_))
+        {
+            return;
+        }
+        let Some(info) = self.tap_network.lock().unwrap().clone() else {
+            return;
+        };
+        let found = tap_adapter::state(&info.mac).problem(&info.ip);
+        let change = self.tap_watch.lock().unwrap().update(found);
+        let adapter = self
+            .tap_adapter
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or_else(|| info.mac.clone(), |a| a.friendly_name.clone());
+        match change {
+            Some(TapChange::Broken(problem)) => {
+                log::warn!("虚拟网卡“{}”出毛病了（{}），edge 自己还没发觉", adapter, problem.code());
+                self.send_log_line(format!("[WARN] 虚拟网卡“{}”出毛病了：{}", adapter, problem.code()));
+                self.say(AssistantMessage::new("maid_tap_broken", Severity::Warning).with("adapter", adapter));
+                let restart = self.auto_reconnect.lock().unwrap().clone().filter(|c| c.tap_restart_on_loss);
+                if let Some(config) = restart {
+                    log::info!("方案里说网卡坏了就重新出门，恩兔请 edge 重新建网卡");
+                    if let Err(e) = self.reconnect(&config) {
+                        log::warn!("网卡坏了之后重新出门失败：{:#}", e);
+                    }
+                }
+            }
+            Some(TapChange::Restored) => {
+                log::info!("虚拟网卡“{}”恢复正常了", adapter);
+                self.send_log_line(format!("[INFO] 虚拟网卡“{}”恢复正常", adapter));
+                // 网卡可能是重建的，编号变了：下次用到时重新对照
+                *self.tap_adapter.lock().unwrap() = None;
+                self.say(AssistantMessage::new("maid_tap_restored", Severity::Success).with("adapter", adapter));
+            }
+            None => {}
+        }
+    }
+
+    /// 巡查一次总部（巡查小工人每隔几秒叫一次）：一直摸不到当前总部时，按方案里的备用总部换下一家重新出门
+    /// - 只在“铺设通道中”看：edge 还在跑，只是迟迟等不到总部回话；名单走到头就只提醒一次，错误照常摆给主人；
+    /// - 换过去的那家连上了，就返回它的地址（本次工作的账本已经记下了，调用方写系统日志、更新托盘）。
+    pub fn check_failover(&self) -> Option<String> {
+        if self.stop_requested.load(Ordering::SeqCst) || !self.is_running() {
+            return None;
+        }
+        let status = self.status();
+        if matches!(status, ConnectionStatus::Connected(_)) {
+            let address = self.failover.lock().unwrap().settle()?;
+            log::info!("备用总部 {} 接待成功", address);
+            self.send_log_line(format!("[INFO] 备用总部 {} 接待成功", address));
+            self.say(AssistantMessage::new("maid_supernode_failover_ok", Severity::Success).with("address", address.as_str()));
+            return Some(address);
+        }
+        if !matches!(status, ConnectionStatus::Connecting)
+            || self.derived_notice().as_deref() != Some("error_supernode_unreachable")
+        {
+            return None;
+        }
+
+        let previous = self.running_config().map(|c| c.supernode).unwrap_or_default();
+        let step = self.failover.lock().unwrap().advance()?;
+        match step {
+            failover::Step::Next(address) => {
+                log::warn!("总部 {} 一直摸不到，恩兔换到备用总部 {}", previous, address);
+                self.send_log_line(format!("[WARN] 总部 {} 一直摸不到，换到备用总部 {}", previous, address));
+                if let Err(e) = self.fail_over_to(&previous, &address) {
+                    log::warn!("换备用总部没有成功：{:#}", e);
+                }
+            }
+            failover::Step::Exhausted => {
+                log::warn!("备用总部都试过了，还是摸不到总部");
+                self.say(AssistantMessage::new("maid_supernode_failover_exhausted", Severity::Warning));
+            }
+        }
+        None
+    }
+
+    /// 换到备用总部：和主人换总部一样先记下便签（保住地址）、等 edge 停稳，再朝新总部出门
+    /// - 朝这一家出不了门，就接着试名单里的下一家；
+    /// - 名单走到头还出不了门：便签作废、本次工作收尾，状态停在出错上（不会卡在一张没人管的暂停便签上）；
+    /// - 中途主人亲自动了手（便签被用掉或者作废了），就交给主人，什么都不收拾。
+    fn fail_over_to(&self, previous: &str, address: &str) -> Result<()> {
+        {
+            let _ticket = self.begin(Operation::SwitchSupernode)?;
+            self.park(
+                AssistantMessage::new("maid_supernode_failover", Severity::Warning)
+                    .with("previous", previous)
+                    .with("address", address),
+                false,
+            )?;
+        }
+        self.wait_stopped_or_force();
+
+        let mut address = address.to_string();
+        loop {
+            // 出门失败时状态会停在“出错”，不能再走 resume_on 的状态检查：直接在闸门里看便签还在不在
+            let _ticket = self.ops.enter();
+            if self.is_running() || !self.is_paused() {
+                anyhow::bail!("主人已经接手了，备用总部就不换了");
+            }
+            let e = match self.resume_on_inner(&address) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            log::warn!("换到备用总部 {} 失败：{:#}", address, e);
+            self.send_log_line(format!("[WARN] 换到备用总部 {} 失败：{:#}", address, e));
+            let next = self.failover.lock().unwrap().advance();
+            match next {
+                Some(failover::Step::Next(next)) => address = next,
+                _ => {
+                    self.abandon_failover(&e);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// 备用总部一家都出不了门：作废暂停便签，本次工作按出错收尾（调用方需已在闸门内）
+    fn abandon_failover(&self, error: &anyhow::Error) {
+        *self.paused_session.lock().unwrap() = None;
+        let msg = match self.status() {
+            ConnectionStatus::Error(msg, _) => msg,
+            _ => error.to_string(),
+        };
+        *self.auto_reconnect.lock().unwrap() = None;
+        self.say(AssistantMessage::new("maid_supernode_failover_exhausted", Severity::Warning));
+        finish_session(&self.session, self.event_tx.as_ref(), "edge_exited", Some(msg.clone()));
+        *self.status.lock().unwrap() = ConnectionStatus::Error(msg, None);
+    }
+
+    /// edge 走后回头看一眼虚拟网卡：地址、路由还挂着的话告诉主人，方案允许时顺手重置网卡
+    /// （稍等一会儿再看，给系统留点收拾的时间；期间又建立了通道就不管了）
+    fn verify_teardown(&self) {
+        let Some(info) = self.tap_network.lock().unwrap().take() else {
+            return;
+        };
+        let cached = self.tap_adapter.lock().unwrap().clone();
+        let reset = self.tap_reset.load(Ordering::SeqCst);
+        let ops = Arc::clone(&self.ops);
+        let child = Arc::clone(&self.child);
+        let generation = Arc::clone(&self.generation);
+        let mine = generation.current();
+        let event_tx = self.event_tx.clone();
+        thread::spawn(move || {
+            thread::sleep(TEARDOWN_SETTLE);
+            let _ticket = ops.enter();
+            if !generation.is_current(mine) || child.lock().unwrap().is_some() {
+                return;
+            }
+            let Some(adapter) = cached.or_else(|| tap_adapter::resolve(&info.mac)) else {
+                return;
+            };
+            let found = tap_adapter::leftover(&adapter);
+            if found.is_empty() {
+                log::debug!("虚拟网卡“{}”已经收拾干净了", adapter.friendly_name);
+                return;
+            }
+            log::warn!("edge 走后虚拟网卡“{}”上还挂着：{}", adapter.friendly_name, found.describe());
+            if !reset {
+                events::say(
+                    event_tx.as_ref(),
+                    AssistantMessage::new("maid_tap_leftover", Severity::Warning)
+                        .with("adapter", adapter.friendly_name.clone())
+                        .with("leftover", found.describe()),
+                );
+                return;
+            }
+            let message = match tap_adapter::reset(&adapter) {
+                Ok(()) => {
+                    log::info!("已重置虚拟网卡“{}”", adapter.friendly_name);
+                    AssistantMessage::new("maid_tap_reset", Severity::Info)
+                }
+                Err(e) => {
+                    log::warn!("重置虚拟网卡“{}”失败：{:#}", adapter.friendly_name, e);
+                    AssistantMessage::new("maid_tap_reset_failed", Severity::Warning).with("error", format!("{:#}", e))
+                }
+            };
+            events::say(event_tx.as_ref(), message.with("adapter", adapter.friendly_name.clone()));
+        });
+    }
+
+    /// 回到空闲状态：有暂停便签就继续歇着，否则本次工作到此结束
+    fn settle_idle(&self, reason: &str) {
+        let idle = idle_status(&self.paused_session);
+        if idle == ConnectionStatus::Paused {
+            if let Some(t) = self.session.lock().unwrap().as_mut() {
+                t.fold_current();
+            }
+        } else {
+            *self.auto_reconnect.lock().unwrap() = None;
+            finish_session(&self.session, self.event_tx.as_ref(), reason, None);
+        }
+        *self.status.lock().unwrap() = idle;
+        *self.last_notice.lock().unwrap() = None;
+    }
+
     /// 检查进程是否在运行
     pub fn is_running(&self) -> bool {
         let child_guard = self.child.lock().unwrap();
-        child_guard.is_some()
+        child_guard.is_some() || self.adopted_pid().is_some()
+    }
+
+    /// 正在工作的 edge 的进程号（自己带出门的，或者接手来的）
+    pub fn pid(&self) -> Option<u32> {
+        self.child.lock().unwrap().as_ref().and_then(Child::id).or_else(|| self.adopted_pid())
+    }
+
+    fn adopted_pid(&self) -> Option<u32> {
+        Some(self.adopted.load(Ordering::SeqCst)).filter(|&pid| pid != 0)
     }
 
     /// 获取当前状态
@@ -739,6 +2087,29 @@ impl N2NProcess {
         self.status.lock().unwrap().clone()
     }
 
+    /// 给交班本准备的一页记录（没有在工作时为 None）
+    pub fn session_record(&self, now: u64) -> Option<SessionRecord> {
+        let (status, virtual_ip) = match self.status() {
+            ConnectionStatus::Connecting => ("connecting", None),
+            ConnectionStatus::Reconnecting(_) => ("reconnecting", None),
+            ConnectionStatus::Connected(info) => ("connected", info.map(|info| info.ip)),
+            ConnectionStatus::Paused => ("paused", None),
+            _ => return None,
+        };
+        let session = self.session.lock().unwrap();
+        let session = session.as_ref()?;
+        let pid = self.pid();
+        Some(SessionRecord {
+            status: status.to_string(),
+            pid,
+            supernode: session.supernode.clone(),
+            community: session.community.clone(),
+            virtual_ip,
+            started_at: session.started_at,
+            updated_at: now,
+        })
+    }
+
     /// 取出最近一次“需要主人注意”的提示
     /// - 说明：这不等价于“致命错误”；有些情况 edge 会继续重试（例如 MAC/IP 未释放）
     pub fn last_notice(&self) -> Option<String> {
@@ -747,11 +2118,15 @@ impl N2NProcess {
 
     /// 尝试给出更“客观”的提示信息：
     /// - 优先使用从 stdout/stderr 抓到的明确错误
+    /// - 其次是网卡巡查确认了的毛病（网卡不见了、被停用、地址掉了）
     /// - 否则用 Management API 的时间戳做保守推断（例如：长时间收不到 supernode 心跳）
     pub fn derived_notice(&self) -> Option<String> {
         if let Some(n) = self.last_notice() {
             return Some(n);
         }
+        if let Some(problem) = self.tap_watch.lock().unwrap().problem {
+            return Some(problem.code().to_string());
+        }
 
         let raw = self.status.lock().unwrap().clone();
         if matches!(
@@ -792,14 +2167,29 @@ impl N2NProcess {
     pub fn derived_status(&self) -> ConnectionStatus {
         let raw = self.status.lock().unwrap().clone();
         match raw {
-            ConnectionStatus::Disconnecting | ConnectionStatus::Disconnected | ConnectionStatus::Error(_) => raw,
+            ConnectionStatus::Disconnecting
+            | ConnectionStatus::Disconnected
+            | ConnectionStatus::Paused
+            | ConnectionStatus::Reconnecting(_)
+            | ConnectionStatus::Degraded(_)
+            | ConnectionStatus::Error(..) => raw,
             ConnectionStatus::Connecting | ConnectionStatus::Connected(_) => {
-                if self.mgmt_is_connected() {
-                    // 保留 stdout 里提取到的网卡信息（如果有），但不把“是否已连接”这件事绑死在 stdout 上
-                    let network_info = match raw {
-                        ConnectionStatus::Connected(info) => info,
-                        _ => None,
-                    };
+                let raw_up = self.mgmt_is_connected();
+                let (up, recovered) = self.link_debounce.lock().unwrap().update(raw_up, Instant::now());
+                if recovered {
+                    // 心跳回来了：之前断联时留下的提醒便签已经过时，撕掉免得下次抖动时又冒出来
+                    *self.last_notice.lock().unwrap() = None;
+                }
+                // 保留 stdout 里提取到的网卡信息（如果有），但不把“是否已连接”这件事绑死在 stdout 上
+                let network_info = match raw {
+                    ConnectionStatus::Connected(info) => info,
+                    _ => None,
+                };
+                let tap_broken = self.tap_watch.lock().unwrap().problem.is_some();
+                if self.registration.lock().unwrap().degraded || tap_broken {
+                    // 同伴之间可能还通着（或者网卡坏了、edge 自己还没发觉）：别再让主人以为一切正常
+                    ConnectionStatus::Degraded(network_info.or_else(|| self.tap_network.lock().unwrap().clone()))
+                } else if up {
                     ConnectionStatus::Connected(network_info)
                 } else {
                     ConnectionStatus::Connecting
@@ -808,6 +2198,107 @@ impl N2NProcess {
         }
     }
 
+    /// 把本次工作的提醒小本子递给主人
+    pub fn warnings_snapshot(&self) -> Vec<EdgeWarning> {
+        self.warnings.lock().unwrap().clone()
+    }
+
+    /// 本次交给 edge 的工号（还没开工过时为 None）
+    pub fn node_name(&self) -> Option<String> {
+        self.node_name.lock().unwrap().clone()
+    }
+
+    /// 通道连着时该开张的传话筒规则（没连上时为 None）
+    pub fn active_port_forwards(&self) -> Option<Vec<PortForward>> {
+        matches!(self.status(), ConnectionStatus::Connected(_)).then(|| self.port_forwards.lock().unwrap().clone())
+    }
+
+    /// 当中转节点时要打开 IP 转发的虚拟网卡（没连上、或者没当中转节点时为 None）
+    pub fn forwarding_adapter(&self) -> Option<TapAdapter> {
+        if !self.packet_forwarding.load(Ordering::SeqCst) {
+            return None;
+        }
+        self.tap_adapter_snapshot()
+    }
+
+    /// 不断开通道，直接把新的通道宽度（MTU）设到虚拟网卡上；之后自动重连时 edge 也按新值启动
+    pub fn apply_mtu_live(&self, mtu: u16) -> Result<()> {
+        let adapter = self
+            .tap_adapter_snapshot()
+            .ok_or_else(|| anyhow::anyhow!("error_mtu_live_not_connected"))?;
+        tap_adapter::set_mtu(&adapter, mtu)?;
+        if let Some(config) = self.auto_reconnect.lock().unwrap().as_mut() {
+            config.mtu = Some(mtu);
+        }
+        log::info!("网卡 {} 的 MTU 已改为 {}（通道没有断开）", adapter.friendly_name, mtu);
+        self.say(AssistantMessage::new("maid_mtu_applied", Severity::Success).with("mtu", mtu));
+        Ok(())
+    }
+
+    /// edge 实际在用的本机 UDP 端口（查不到时按指示里固定的端口；edge 不在时为 None）
+    pub fn local_port(&self) -> Option<u16> {
+        let pid = self.pid()?;
+        crate::local_port::bound_port(pid, self.management_port()).or(*self.local_port.lock().unwrap())
+    }
+
+    /// edge 实际在用的报到间隔（秒，edge 不在时为 None）
+    pub fn register_interval_secs(&self) -> Option<u32> {
+        self.pid()?;
+        Some(self.register_interval.load(Ordering::SeqCst))
+    }
+
+    /// 这只 edge 的管理口（没启动过时是默认的 5644）
+    pub fn management_port(&self) -> u16 {
+        self.mgmt_port.load(Ordering::SeqCst)
+    }
+
+    /// 问实时统计要敲的门牌和钥匙（edge 不在时为 None）
+    pub fn stats_target(&self) -> Option<mgmt::StatsTarget> {
+        self.pid()?;
+        // edge 每个报到间隔才和总部通一次话，隔得久的方案别误报成“没报上到”
+        let interval = u64::from(self.register_interval.load(Ordering::SeqCst));
+        Some(mgmt::StatsTarget {
+            port: self.management_port(),
+            password: self.mgmt_password.lock().unwrap().clone(),
+            fresh_secs: HEARTBEAT_MAX_INTERVAL_SECS.max(interval * 2),
+            tap_device: self.session.lock().unwrap().as_ref().and_then(|t| t.tap_device.clone()),
+        })
+    }
+
+    /// 通道连着时 edge 加上的路由（没连上时为空）
+    pub fn active_routes(&self) -> Vec<Route> {
+        if matches!(self.status(), ConnectionStatus::Connected(_)) {
+            self.routes.lock().unwrap().clone()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// 本次工作实际生效的通道特性（edge 还没汇报时为 None）
+    pub fn transport_snapshot(&self) -> Option<TransportFeatures> {
+        let transport = self.transport.lock().unwrap().clone();
+        (!transport.is_empty()).then_some(transport)
+    }
+
+    /// 虚拟网卡在系统里的名字和编号（连上并拿到 MAC 之后才有；MAC 没变就不重复查）
+    pub fn tap_adapter_snapshot(&self) -> Option<TapAdapter> {
+        let ConnectionStatus::Connected(Some(info)) = self.status() else {
+            return None;
+        };
+        let mut cached = self.tap_adapter.lock().unwrap();
+        let stale = cached
+            .as_ref()
+            .map(|adapter| !adapter.mac.eq_ignore_ascii_case(&info.mac.replace('-', ":")))
+            .unwrap_or(true);
+        if stale {
+            *cached = tap_adapter::resolve(&info.mac);
+            if let Some(ref adapter) = *cached {
+                log::info!("虚拟网卡是“{}”（接口编号 {}）", adapter.friendly_name, adapter.if_index);
+            }
+        }
+        cached.clone()
+    }
+
     /// 把“同伴点名册”递给主人（前端展示用）
     pub fn peers_snapshot(&self) -> Vec<PeerNodeInfo> {
         let peers = self.peer_cache.lock().unwrap().clone();
@@ -828,7 +2319,7 @@ impl N2NProcess {
     }
 
     /// 获取默认的 edge 可执行文件路径
-    fn get_default_edge_path(&self) -> String {
+    pub(crate) fn default_edge_path() -> String {
         #[cfg(target_os = "windows")]
         {
             // Windows 下在程序目录的 bin 子目录查找
@@ -857,6 +2348,9 @@ impl N2NProcess {
     /// 清空管理口状态缓存（断开/停止时调用）
     fn reset_mgmt_state(&self) {
         *self.mgmt_state.lock().unwrap() = MgmtState::default();
+        *self.link_debounce.lock().unwrap() = LinkDebounce::default();
+        *self.registration.lock().unwrap() = RegistrationWatch::default();
+        *self.tap_watch.lock().unwrap() = TapWatch::default();
         self.mgmt_worker_started.store(false, Ordering::SeqCst);
     }
 
@@ -869,20 +2363,7 @@ impl N2NProcess {
             .unwrap()
             .clone()
             .or_else(|| Some("n2n".to_string()));
-
-        let tag = next_mgmt_tag();
-        let options = match pw.as_deref() {
-            Some(p) if !p.is_empty() => format!("{tag}:1:{p}"),
-            _ => tag.clone(),
-        };
-        let req = format!("w {options} stop\n");
-        let socket = send_mgmt_request(
-            &req,
-            "准备 stop 纸条失败",
-            "把 stop 纸条递给 edge（Management API）失败",
-        )?;
-        let deadline = Instant::now() + Duration::from_millis(MGMT_STOP_TIMEOUT_MS);
-        wait_mgmt_end(&socket, &tag, deadline, "读取 stop 回信失败", "Management API stop 失败")
+        mgmt::stop(self.management_port(), pw.as_deref())
     }
 
     /// 启动后台“点名 + 测延迟”小工人（仅一次）
@@ -896,8 +2377,10 @@ impl N2NProcess {
         }
 
         let child = Arc::clone(&self.child);
+        let adopted = Arc::clone(&self.adopted);
         let stop_requested = Arc::clone(&self.stop_requested);
         let mgmt_password = Arc::clone(&self.mgmt_password);
+        let mgmt_port = Arc::clone(&self.mgmt_port);
         let mgmt_state = Arc::clone(&self.mgmt_state);
         let peer_cache = Arc::clone(&self.peer_cache);
         let peer_latency = Arc::clone(&self.peer_latency);
@@ -910,7 +2393,7 @@ impl N2NProcess {
                     break;
                 }
 
-                if child.lock().unwrap().is_none() {
+                if child.lock().unwrap().is_none() && adopted.load(Ordering::SeqCst) == 0 {
                     break;
                 }
 
@@ -920,7 +2403,8 @@ impl N2NProcess {
                 }
 
                 let pw = mgmt_password.lock().unwrap().clone();
-                match query_edges_from_management_api(pw.as_deref()) {
+                let port = mgmt_port.load(Ordering::SeqCst);
+                match query_edges_from_management_api(port, pw.as_deref()) {
                     Ok(mut peers) => {
                         fail_streak = 0;
 
@@ -967,10 +2451,17 @@ impl N2NProcess {
         }
 
         let child = Arc::clone(&self.child);
+        let adopted = Arc::clone(&self.adopted);
         let stop_requested = Arc::clone(&self.stop_requested);
         let mgmt_password = Arc::clone(&self.mgmt_password);
+        let mgmt_port = Arc::clone(&self.mgmt_port);
         let mgmt_state = Arc::clone(&self.mgmt_state);
         let mgmt_worker_started = Arc::clone(&self.mgmt_worker_started);
+        let session = Arc::clone(&self.session);
+        let registration = Arc::clone(&self.registration);
+        let register_interval = Arc::clone(&self.register_interval);
+        let event_tx = self.event_tx.clone();
+        let log_tx = self.log_tx.clone();
 
         thread::spawn(move || {
             let mut fail_streak = 0u32;
@@ -978,17 +2469,19 @@ impl N2NProcess {
                 if stop_requested.load(Ordering::SeqCst) {
                     break;
                 }
-                if child.lock().unwrap().is_none() {
+                if child.lock().unwrap().is_none() && adopted.load(Ordering::SeqCst) == 0 {
                     break;
                 }
 
                 // 读操作理论上不需要密码，但有些动作（如 subscribe/stop）会要求认证；
                 // 这里顺便把已知密码带上，避免环境差异导致读不到状态。
                 let pw = mgmt_password.lock().unwrap().clone();
+                let port = mgmt_port.load(Ordering::SeqCst);
 
-                match query_mgmt_state_snapshot(pw.as_deref()) {
+                match query_mgmt_state_snapshot(port, pw.as_deref()) {
                     Ok(snapshot) => {
                         fail_streak = 0;
+                        record_traffic_sample(&session, snapshot.packets);
                         *mgmt_state.lock().unwrap() = snapshot;
                     }
                     Err(e) => {
@@ -997,6 +2490,36 @@ impl N2NProcess {
                     }
                 }
 
+                // 顺便体检：连上过之后，超过两个报到间隔没和总部报上到就算登记丢了（管理口一直不应也算）
+                let window = HEARTBEAT_DISCONNECT_THRESHOLD_SECS.max(u64::from(register_interval.load(Ordering::SeqCst)) * 2);
+                let registered = mgmt_state.lock().unwrap().is_registered(window, unix_now_seconds());
+                let changed = registration.lock().unwrap().update(registered);
+                if !stop_requested.load(Ordering::SeqCst) {
+                    match changed {
+                        Some(true) => {
+                            log::warn!("edge 还在，但已经 {} 秒没在总部报上到了，通道进入降级状态", window);
+                            if let Some(ref tx) = log_tx {
+                                tx.send("[WARN] 总部那边的登记丢了，恩兔继续盯着，报上到就恢复".to_string());
+                            }
+                            events::say(
+                                event_tx.as_ref(),
+                                AssistantMessage::new("maid_registration_lost", Severity::Warning).with("secs", window),
+                            );
+                        }
+                        Some(false) => {
+                            log::info!("edge 重新在总部报上到了，通道恢复正常");
+                            if let Some(ref tx) = log_tx {
+                                tx.send("[INFO] 重新在总部报上到了".to_string());
+                            }
+                            events::say(
+                                event_tx.as_ref(),
+                                AssistantMessage::new("maid_registration_restored", Severity::Success),
+                            );
+                        }
+                        None => {}
+                    }
+                }
+
                 let sleep_ms = if fail_streak >= 3 { 3000 } else { 1200 };
                 thread::sleep(Duration::from_millis(sleep_ms));
             }
@@ -1007,16 +2530,22 @@ impl N2NProcess {
     }
 }
 
-/// Management API 的 tag 自增器（让每次点名都有自己的编号）
-static MGMT_TAG_COUNTER: AtomicU32 = AtomicU32::new(1);
-
 #[derive(Debug, Clone, Default)]
 struct MgmtState {
-    timestamps: Option<MgmtTimestampsRow>,
+    timestamps: Option<mgmt::TimestampsRow>,
+    /// 数据包收发计数（packetstats 里的 transop 行：tx, rx）
+    packets: Option<(u64, u64)>,
     last_error: Option<String>,
 }
 
 impl MgmtState {
+    /// 最近 `window_secs` 秒内有没有在总部报上到（`now` 是 Unix 秒）
+    fn is_registered(&self, window_secs: u64, now: u64) -> bool {
+        self.timestamps
+            .as_ref()
+            .is_some_and(|ts| ts.last_super != 0 && now.saturating_sub(ts.last_super) <= window_secs)
+    }
+
     fn is_connected(&self) -> bool {
         let Some(ts) = self.timestamps.as_ref() else { return false };
         let now = unix_now_seconds();
@@ -1027,27 +2556,100 @@ impl MgmtState {
     }
 }
 
-#[derive(Debug, serde::Deserialize)]
-struct MgmtPacket {
-    #[serde(rename = "_tag")]
-    tag: Option<String>,
-    #[serde(rename = "_type")]
-    kind: String,
-    error: Option<String>,
-    #[serde(flatten)]
-    extra: serde_json::Map<String, serde_json::Value>,
+/// 连接状态防抖器：连上立刻报喜，断开要等心跳持续缺席一段时间才报忧
+#[derive(Debug, Clone, Default)]
+struct LinkDebounce {
+    /// 对外认定的“已连接”
+    stable_up: bool,
+    /// 已连接期间，心跳从什么时候开始缺席
+    down_since: Option<Instant>,
+}
+
+impl LinkDebounce {
+    /// 喂一次最新的心跳判断，返回（对外认定是否已连接，是否刚刚从断开恢复）
+    fn update(&mut self, raw_up: bool, now: Instant) -> (bool, bool) {
+        if raw_up {
+            self.down_since = None;
+            let recovered = !self.stable_up;
+            self.stable_up = true;
+            return (true, recovered);
+        }
+        if self.stable_up {
+            let since = *self.down_since.get_or_insert(now);
+            if now.duration_since(since) >= LINK_DOWN_HOLD {
+                self.stable_up = false;
+                self.down_since = None;
+            }
+        }
+        (self.stable_up, false)
+    }
+}
+
+/// 总部登记体检：连上过之后登记丢了就进入降级，重新报上到就恢复
+#[derive(Debug, Clone, Default)]
+struct RegistrationWatch {
+    /// 这一班在总部报上到过
+    ever_registered: bool,
+    /// 登记丢了（对外显示 Degraded）
+    degraded: bool,
+}
+
+impl RegistrationWatch {
+    /// 喂一次体检结果，状态有变化时返回 Some（true：刚丢了登记，false：刚恢复）
+    fn update(&mut self, registered: bool) -> Option<bool> {
+        if registered {
+            self.ever_registered = true;
+            return std::mem::take(&mut self.degraded).then_some(false);
+        }
+        if self.ever_registered && !self.degraded {
+            self.degraded = true;
+            return Some(true);
+        }
+        None
+    }
+}
+
+/// 虚拟网卡巡查（一次看到可能只是网卡正在重建，连续两次看到同一个毛病才算数；好了马上恢复）
+#[derive(Debug, Clone, Default)]
+struct TapWatch {
+    /// 上一轮看到的毛病（还没确认）
+    suspect: Option<TapProblem>,
+    /// 确认了的毛病（对外显示 Degraded）
+    problem: Option<TapProblem>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-struct MgmtTimestampsRow {
-    start_time: u64,
-    last_super: u64,
-    last_p2p: u64,
+/// 网卡巡查的结论变了
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TapChange {
+    /// 刚确认网卡出了这个毛病
+    Broken(TapProblem),
+    /// 网卡好了
+    Restored,
+}
+
+impl TapWatch {
+    /// 喂一次巡查结果，结论有变化时返回 Some
+    fn update(&mut self, found: Option<TapProblem>) -> Option<TapChange> {
+        let Some(found) = found else {
+            self.suspect = None;
+            return self.problem.take().map(|_| TapChange::Restored);
+        };
+        if self.problem == Some(found) {
+            return None;
+        }
+        if self.suspect.replace(found) != Some(found) {
+            return None;
+        }
+        self.suspect = None;
+        self.problem = Some(found);
+        Some(TapChange::Broken(found))
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
 struct MgmtEdgeRow {
     mode: Option<String>,
+    macaddr: Option<String>,
     ip4addr: Option<String>,
     sockaddr: Option<String>,
     desc: Option<String>,
@@ -1056,230 +2658,431 @@ struct MgmtEdgeRow {
     local: Option<u64>,
 }
 
-fn unix_now_seconds() -> u64 {
+/// 把这一轮 edge 的最新收发读数记进账本
+fn record_traffic_sample(session: &Arc<Mutex<Option<SessionTracker>>>, packets: Option<(u64, u64)>) {
+    let mut guard = session.lock().unwrap();
+    let Some(t) = guard.as_mut() else { return };
+    if let Some((tx, rx)) = packets {
+        t.current.tx_packets = tx;
+        t.current.rx_packets = rx;
+    }
+    if let Some((tx, rx)) = t.tap_device.as_deref().and_then(read_tap_byte_counters) {
+        t.current.tx_bytes = Some(tx);
+        t.current.rx_bytes = Some(rx);
+    }
+}
+
+/// 读取网卡的收发字节数（目前只有 Linux 能从 sysfs 直接读到）
+#[cfg(target_os = "linux")]
+pub(crate) fn read_tap_byte_counters(dev: &str) -> Option<(u64, u64)> {
+    let read = |name: &str| -> Option<u64> {
+        std::fs::read_to_string(format!("/sys/class/net/{dev}/statistics/{name}"))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    Some((read("tx_bytes")?, read("rx_bytes")?))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn read_tap_byte_counters(_dev: &str) -> Option<(u64, u64)> {
+    None
+}
+
+/// 本次工作彻底结束：汇总账本并通过小喇叭递出 `session-ended`（账本随即清空，重复调用不会重复汇报）
+fn finish_session(
+    session: &Arc<Mutex<Option<SessionTracker>>>,
+    event_tx: Option<&EventSender>,
+    reason: &str,
+    error: Option<String>,
+) {
+    let Some(tracker) = session.lock().unwrap().take() else { return };
+    let summary = tracker.summarize(unix_now_seconds(), reason, error);
+    log::info!(
+        "本次工作结束：持续 {} 秒，原因：{}",
+        summary.duration_secs,
+        summary.reason
+    );
+    if let Some(tx) = event_tx {
+        let _ = tx.send(ProcessEvent::SessionEnded(summary));
+    }
+}
+
+/// 重连等待的起步时间（之后每次翻倍）
+const RECONNECT_BASE_DELAY_SECS: u64 = 2;
+/// 重连等待时间上下浮动的幅度（百分比）：一个总部带着很多同伴时，大家别在同一秒一起冲上去
+const RECONNECT_JITTER_PERCENT: u64 = 20;
+
+/// 按方案的重连策略算出第 `attempt` 次重连前要等多久（None 表示不再重连）
+/// - `exit_ok`：edge 是不是正常退出的（“出错时重连”只管非正常退出）
+fn reconnect_delay(config: &N2NConfig, exit_ok: bool, attempt: u32) -> Option<Duration> {
+    match config.reconnect_policy {
+        ReconnectPolicy::Never => return None,
+        ReconnectPolicy::OnFailure if exit_ok => return None,
+        _ => {}
+    }
+    if config.reconnect_max_attempts > 0 && attempt > config.reconnect_max_attempts {
+        return None;
+    }
+    let backoff = RECONNECT_BASE_DELAY_SECS.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    Some(Duration::from_secs(backoff.min(config.reconnect_backoff_max_secs.max(1))))
+}
+
+/// 给重连等待时间加点抖动（`roll` 是随机数，落在上下 20% 之内）
+fn with_jitter(delay: Duration, roll: u32) -> Duration {
+    let span = RECONNECT_JITTER_PERCENT * 2 + 1;
+    let percent = 100 - RECONNECT_JITTER_PERCENT + u64::from(roll) % span;
+    Duration::from_millis((delay.as_millis() as u64).saturating_mul(percent) / 100)
+}
+
+/// 掷个骰子（系统随机数不可用时就不抖了）
+fn random_roll() -> u32 {
+    let mut buf = [0u8; 4];
+    match getrandom::getrandom(&mut buf) {
+        Ok(()) => u32::from_le_bytes(buf),
+        Err(_) => RECONNECT_JITTER_PERCENT as u32,
+    }
+}
+
+/// edge 停下后该回到哪种“空闲”状态：有暂停便签就是歇一会儿，否则就是待命
+fn idle_status(paused_session: &Arc<Mutex<Option<PausedSession>>>) -> ConnectionStatus {
+    if paused_session.lock().unwrap().is_some() {
+        ConnectionStatus::Paused
+    } else {
+        ConnectionStatus::Disconnected
+    }
+}
+
+/// 把 255.255.255.0 这样的子网面具换算成前缀长度（24）
+fn netmask_to_prefix(mask: &str) -> Option<u8> {
+    let addr: std::net::Ipv4Addr = mask.trim().parse().ok()?;
+    let bits = u32::from(addr);
+    // 合法的子网面具必须是“前面全 1、后面全 0”
+    if bits.leading_ones() + bits.trailing_zeros() != 32 {
+        return None;
+    }
+    Some(bits.leading_ones() as u8)
+}
+
+pub(crate) fn unix_now_seconds() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
         .as_secs()
 }
 
-/// 从 extra_args 中悄悄摸出 management password（如果主人给了的话）
-fn extract_management_password(extra_args: Option<&str>) -> Option<String> {
-    let s = extra_args?;
-    let mut it = s.split_whitespace().peekable();
-    while let Some(tok) = it.next() {
-        if tok == "--management-password" {
-            return it.next().map(|v| v.to_string());
-        }
+/// 实际交给 edge 的工号（没填时由主机名整理而来）
+fn effective_node_name(config: &N2NConfig) -> String {
+    if config.username.trim().is_empty() {
+        crate::node_name::default_node_name()
+    } else {
+        config.username.clone()
     }
-    None
 }
 
-fn next_mgmt_tag() -> String {
-    let n = MGMT_TAG_COUNTER.fetch_add(1, Ordering::Relaxed);
-    format!("{}", n % 1000)
-}
+/// 按方案拼出 edge 的参数（`start` 和“导出命令行”共用同一份）
+/// - `caps`：读过说明书的话照着它选开关；读不懂时按系统猜
+pub(crate) fn build_edge_args(config: &N2NConfig, node_name: &str, caps: Option<&EdgeCaps>) -> Result<Vec<String>> {
+    // 构建命令参数
+    // -c: 社区名称
+    // -l: supernode 地址（host:port）
+    //
+    // 备注：`-f`（前台运行）在部分 Windows 版本的 edge 中并不存在，会触发
+    // `WARNING: unknown option -f`，所以说明书里没有它就不传；读不懂说明书时 Windows 下不传。
+    let mut args = vec![
+        "-c".to_string(),
+        config.community.clone(),
+        "-l".to_string(),
+        config.supernode.clone(),
+    ];
+
+    // -f: 前台运行（不 fork 到后台，便于监控）
+    let foreground = caps.map_or(cfg!(not(target_os = "windows")), |caps| caps.foreground);
+    if foreground {
+        args.insert(0, "-f".to_string());
+    }
+
+    args.push("-I".to_string());
+    args.push(node_name.to_string());
+
+    // 添加加密密钥
+    if !config.encryption_key.is_empty() {
+        args.push("-k".to_string());
+        args.push(config.encryption_key.clone());
+    }
 
-fn build_mgmt_request(method: &str, password: Option<&str>) -> (String, String) {
-    let tag = next_mgmt_tag();
-    let options = match password {
-        Some(pw) if !pw.is_empty() => format!("{tag}:1:{pw}"),
-        _ => tag.clone(),
+    // 加密方式、包头加密和压缩
+    let cipher_flag = match caps {
+        Some(caps) => caps.cipher_flag(config.cipher),
+        None => config.cipher.flag(),
     };
-    // 备注：协议要求单行、<=80 bytes；这里 method 都很短
-    (tag, format!("r {options} {method}\n"))
-}
+    if let Some(flag) = cipher_flag {
+        args.push(flag.to_string());
+    }
+    if config.header_encryption {
+        args.push("-H".to_string());
+    }
+    if let Some(flag) = config.compression.flag() {
+        args.push(flag.to_string());
+    }
 
-fn send_mgmt_request(req: &str, prepare_hint: &'static str, send_hint: &'static str) -> Result<UdpSocket> {
-    let socket = UdpSocket::bind(("127.0.0.1", 0)).context(prepare_hint)?;
-    socket
-        .set_read_timeout(Some(Duration::from_millis(MGMT_READ_TIMEOUT_MS)))
-        .ok();
-    socket.send_to(req.as_bytes(), MGMT_ADDR).context(send_hint)?;
-    Ok(socket)
-}
+    // IP 地址配置
+    if config.ip_mode == "dhcp" {
+        args.push("-a".to_string());
+        args.push("dhcp:0.0.0.0".to_string());
+    } else if let Some(ref static_ip) = config.static_ip {
+        args.push("-a".to_string());
+        args.push(static_ip.clone());
+    }
 
-fn parse_mgmt_packet(buf: &[u8]) -> Option<MgmtPacket> {
-    let mut s = String::from_utf8_lossy(buf).to_string();
-    // edge 回包尾部可能带 \0，让恩兔把它扫掉
-    s = s.trim_matches('\u{0}').trim().to_string();
-    serde_json::from_str(&s).ok()
-}
+    // MTU 设置
+    if let Some(mtu) = config.mtu {
+        args.push("-M".to_string());
+        args.push(mtu.to_string());
+    }
 
-fn collect_mgmt_rows_json(socket: &UdpSocket, tag: &str, deadline: Instant) -> Result<Vec<serde_json::Value>> {
-    let mut buf = vec![0u8; 65535];
-    let mut rows: Vec<serde_json::Value> = Vec::new();
+    // TAP 设备名称
+    if let Some(ref tap_device) = config.tap_device {
+        args.push("-d".to_string());
+        args.push(tap_device.clone());
+    }
 
-    while Instant::now() < deadline {
-        let (n, _) = match socket.recv_from(&mut buf) {
-            Ok(v) => v,
-            Err(e) if should_stop_mgmt_read(&e) => break,
-            Err(e) => return Err(anyhow::anyhow!("读取 Management API 回信失败：{}", e)),
-        };
+    // 固定的本机 UDP 端口
+    if let Some(port) = config.local_port {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
 
-        let pkt = match parse_mgmt_packet(&buf[..n]) {
-            Some(v) => v,
-            None => continue,
-        };
+    // 指定的 MAC（克隆出来的虚拟机 MAC 撞车时用）
+    if let Some(mac) = config.mac_address.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        args.push("-m".to_string());
+        args.push(mac.to_string());
+    }
 
-        if pkt.tag.as_deref() != Some(tag) {
-            continue;
-        }
+    // NAT 很快就忘掉映射时，报到得勤一点
+    if let Some(secs) = config.register_interval_secs {
+        args.push("-i".to_string());
+        args.push(secs.to_string());
+    }
 
-        match pkt.kind.as_str() {
-            "error" => {
-                let err = pkt.error.unwrap_or_else(|| "unknown".to_string());
-                return Err(anyhow::anyhow!("Management API 返回错误：{}", err));
-            }
-            "row" => rows.push(serde_json::Value::Object(pkt.extra)),
-            "end" => break,
-            _ => {}
+    // 管理口换了门牌、设了门禁时告诉 edge
+    if let Some(port) = config.management_port {
+        args.push("-t".to_string());
+        args.push(port.to_string());
+    }
+    if let Some(password) = management_password(config) {
+        args.push("--management-password".to_string());
+        args.push(password.to_string());
+    }
+
+    // 路由：经由同伴去往别的网段
+    args.extend(crate::routes::to_args(&config.routes));
+
+    // 中转节点：收下不是发给自己的包（系统那边的 IP 转发由 ip_forward 跟着通道状态开关）
+    if config.packet_forwarding {
+        args.push("-r".to_string());
+    }
+
+    // 加固模式下让 edge 建好网卡后降权到指定用户
+    #[cfg(target_os = "linux")]
+    if config.edge_hardening {
+        if let Some(user) = config.edge_run_as.as_deref().filter(|u| !u.trim().is_empty()) {
+            args.extend(crate::sandbox::privilege_drop_args(user)?);
         }
     }
 
-    Ok(rows)
-}
+    // 高级选项（每个值都是独立的参数，带空格也不会被切开）
+    args.extend(edge_options::to_args(&config.advanced_options));
 
-fn wait_mgmt_end(
-    socket: &UdpSocket,
-    tag: &str,
-    deadline: Instant,
-    read_error_hint: &str,
-    error_prefix: &str,
-) -> Result<()> {
-    let mut buf = vec![0u8; 65535];
-    while Instant::now() < deadline {
-        let (n, _) = match socket.recv_from(&mut buf) {
-            Ok(v) => v,
-            Err(e) if should_stop_mgmt_read(&e) => break,
-            Err(e) => return Err(anyhow::anyhow!("{}：{}", read_error_hint, e)),
-        };
+    Ok(args)
+}
 
-        let pkt = match parse_mgmt_packet(&buf[..n]) {
-            Some(v) => v,
-            None => continue,
-        };
+/// 主人给管理口设的口令（留空等于没设）
+fn management_password(config: &N2NConfig) -> Option<&str> {
+    config.management_password.as_deref().filter(|pw| !pw.is_empty())
+}
 
-        if pkt.tag.as_deref() != Some(tag) {
-            continue;
+/// 后面跟着秘密的参数（导出命令行时可以打码）
+const SECRET_FLAGS: &[&str] = &["-k", "-J", "--management-password"];
+
+/// 拼出和 `start` 一模一样的 edge 命令行（可以直接贴进终端）
+/// - `mask_secrets`：把密语、口令换成 `******`
+pub fn edge_command_line(config: &N2NConfig, mask_secrets: bool) -> Result<String> {
+    let edge_path = config
+        .edge_path
+        .clone()
+        .unwrap_or_else(N2NProcess::default_edge_path);
+    let caps = crate::edge_caps::detect(&edge_path);
+    let args = build_edge_args(config, &effective_node_name(config), caps.as_ref())?;
+
+    let mut parts = vec![quote_arg(&edge_path)];
+    let mut hide_next = false;
+    for arg in &args {
+        if hide_next {
+            parts.push("******".to_string());
+        } else {
+            parts.push(quote_arg(arg));
         }
+        hide_next = mask_secrets && SECRET_FLAGS.contains(&arg.as_str());
+    }
+    Ok(parts.join(" "))
+}
 
-        if pkt.kind == "error" {
-            let err = pkt.error.unwrap_or_else(|| "unknown".to_string());
-            return Err(anyhow::anyhow!("{}：{}", error_prefix, err));
-        }
-        if pkt.kind == "end" {
-            break;
-        }
+/// 给命令行参数加上引号（只在需要时）
+#[cfg(target_os = "windows")]
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
     }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
 
-    Ok(())
+#[cfg(not(target_os = "windows"))]
+fn quote_arg(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', "'\\''"))
 }
 
-fn query_mgmt_state_snapshot(password: Option<&str>) -> Result<MgmtState> {
-    let timestamps = query_mgmt_single_row::<MgmtTimestampsRow>("timestamps", password)?;
+fn query_mgmt_state_snapshot(port: u16, password: Option<&str>) -> Result<MgmtState> {
+    let timestamps = mgmt::query_single_row::<mgmt::TimestampsRow>(port, "timestamps", password)?;
+    // 收发计数只是锦上添花：旧版 edge 没有 packetstats 也不影响状态判断
+    let packets = mgmt::query_rows::<mgmt::PacketStatsRow>(port, "packetstats", password)
+        .ok()
+        .and_then(|rows| rows.into_iter().find(|r| r.kind == "transop"))
+        .map(|r| (r.tx_pkt.unwrap_or(0), r.rx_pkt.unwrap_or(0)));
 
     Ok(MgmtState {
         timestamps,
+        packets,
         last_error: None,
     })
 }
 
-fn query_mgmt_single_row<T>(method: &str, password: Option<&str>) -> Result<Option<T>>
-where
-    T: serde::de::DeserializeOwned,
-{
-    let mut rows = query_mgmt_rows::<T>(method, password)?;
-    Ok(rows.pop())
+/// 问一问 edge 的版本号（`edge -h` 的欢迎语里有 `n2n v.3.1.1...`）
+pub(crate) fn detect_edge_version(edge_path: &str) -> Option<String> {
+    parse_edge_version(&edge_help(edge_path)?)
 }
 
-fn query_mgmt_rows<T>(method: &str, password: Option<&str>) -> Result<Vec<T>>
-where
-    T: serde::de::DeserializeOwned,
-{
-    let rows_json = query_mgmt_rows_json(method, password)?;
-    let mut out = Vec::new();
-    for row in rows_json {
-        if let Ok(v) = serde_json::from_value::<T>(row) {
-            out.push(v);
-        }
-    }
-    Ok(out)
-}
+/// 让 edge 念一遍说明书（`edge -h` 的全部输出）
+pub(crate) fn edge_help(edge_path: &str) -> Option<String> {
+    let mut cmd = Command::new(edge_path);
+    cmd.arg("-h")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
 
-fn query_mgmt_rows_json(method: &str, password: Option<&str>) -> Result<Vec<serde_json::Value>> {
-    match query_mgmt_rows_json_once(method, password) {
-        Ok(v) => Ok(v),
-        Err(e) => {
-            // 默认密码是 n2n：如果主人没配置密码且遇到 badauth，就用默认钥匙再试一次
-            if password.is_none() && e.to_string().contains("badauth") {
-                return query_mgmt_rows_json_once(method, Some("n2n"));
+    let mut child = cmd.spawn().ok()?;
+    // edge -h 本该立刻退出；万一它赖着不走，恩兔也不会一直等
+    let deadline = Instant::now() + Duration::from_secs(3);
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
             }
-            Err(e)
         }
     }
+    let output = child.wait_with_output().ok()?;
+    Some(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
 }
 
-/// 通过 Management API 查询多行结果（JSON 格式）
-fn query_mgmt_rows_json_once(method: &str, password: Option<&str>) -> Result<Vec<serde_json::Value>> {
-    let (tag, req) = build_mgmt_request(method, password);
-    let deadline = Instant::now() + Duration::from_millis(MGMT_DEADLINE_MS);
-    let socket = send_mgmt_request(
-        &req,
-        "准备 Management API 询问纸条失败",
-        "把询问纸条递给 edge（Management API）失败",
-    )?;
-    collect_mgmt_rows_json(&socket, &tag, deadline)
-}
-
-fn query_edges_from_management_api(password: Option<&str>) -> Result<Vec<PeerNodeInfo>> {
-    match query_edges_from_management_api_once(password) {
-        Ok(v) => Ok(v),
-        Err(e) => {
-            // 默认密码是 n2n：如果主人没配置密码且遇到 badauth，就用默认钥匙再试一次
-            if password.is_none() && e.to_string().contains("badauth") {
-                return query_edges_from_management_api_once(Some("n2n"));
-            }
-            Err(e)
-        }
+/// 从 edge 的欢迎语里抠出版本号
+pub(crate) fn parse_edge_version(text: &str) -> Option<String> {
+    let idx = text.find("n2n v")?;
+    let token = text[idx + "n2n ".len()..].split_whitespace().next()?;
+    let version = token.trim_start_matches('v').trim_start_matches('.');
+    if version.chars().next()?.is_ascii_digit() {
+        Some(version.to_string())
+    } else {
+        None
     }
 }
 
 /// 通过 Management API 查询同伴列表（edges）
-fn query_edges_from_management_api_once(password: Option<&str>) -> Result<Vec<PeerNodeInfo>> {
-    let (tag, req) = build_mgmt_request("edges", password);
-    let deadline = Instant::now() + Duration::from_millis(MGMT_DEADLINE_MS);
-    let socket = send_mgmt_request(
-        &req,
-        "准备 Management API 询问纸条失败",
-        "把询问纸条递给 edge（Management API）失败",
-    )?;
-    let rows = collect_mgmt_rows_json(&socket, &tag, deadline)?;
-
-    let mut peers = Vec::new();
-    for row in rows {
-        let parsed: MgmtEdgeRow = match serde_json::from_value(row) {
-            Ok(v) => v,
-            Err(_) => continue,
-        };
-        let vpn_ip = parsed.ip4addr.as_deref().and_then(|s| s.split('/').next()).map(|s| s.to_string());
-        peers.push(PeerNodeInfo {
-            name: parsed.desc,
-            vpn_addr: parsed.ip4addr,
+fn query_edges_from_management_api(port: u16, password: Option<&str>) -> Result<Vec<PeerNodeInfo>> {
+    let rows = mgmt::query_rows::<MgmtEdgeRow>(port, "edges", password)?;
+    Ok(rows.into_iter().map(PeerNodeInfo::from).collect())
+}
+
+impl From<MgmtEdgeRow> for PeerNodeInfo {
+    fn from(row: MgmtEdgeRow) -> Self {
+        // 还没拿到地址的同伴，edge 报的是空字符串
+        let vpn_addr = row.ip4addr.filter(|a| !a.is_empty());
+        let vpn_ip = vpn_addr.as_deref().and_then(|s| s.split('/').next()).map(|s| s.to_string());
+        PeerNodeInfo {
+            name: row.desc.filter(|d| !d.is_empty()),
+            vpn_addr,
             vpn_ip,
-            public_addr: parsed.sockaddr,
-            mode: parsed.mode,
-            last_seen: parsed.last_seen,
-            is_local: parsed.local.map(|v| v != 0),
+            public_addr: row.sockaddr,
+            mac: row.macaddr.map(|mac| mac.to_ascii_lowercase()),
+            link: PeerLink::from_mode(row.mode.as_deref()),
+            mode: row.mode,
+            last_seen: row.last_seen,
+            is_local: row.local.map(|v| v != 0),
             latency_ms: None,
             last_ping: None,
-        });
+        }
+    }
+}
+
+/// 从总部地址（host:port、[v6]:port 或单独的 host）里取出主机部分
+pub(crate) fn supernode_host(address: &str) -> &str {
+    let address = address.trim();
+    if let Some(rest) = address.strip_prefix('[') {
+        return rest.split(']').next().unwrap_or(rest);
     }
+    match address.rsplit_once(':') {
+        // 只有一个冒号才是 host:port；多个冒号多半是裸 IPv6
+        Some((host, _)) if !host.contains(':') => host,
+        _ => address,
+    }
+}
 
-    Ok(peers)
+/// 给重要同伴挨个打个招呼（各 ping 一次），让系统提前把 ARP/邻居缓存填好，
+/// 免得主人第一次 SSH/RDP 时卡在覆盖网络上慢吞吞的邻居发现里。
+/// 只是热身：对方不回也不算错，结果写进工作汇报就好。
+fn warm_up_neighbors(peers: Vec<String>, log_tx: Option<LogSender>) {
+    thread::spawn(move || {
+        let handles: Vec<_> = peers
+            .into_iter()
+            .map(|peer| peer.trim().to_string())
+            .filter(|peer| !peer.is_empty())
+            .map(|peer| {
+                thread::spawn(move || {
+                    let rtt = ping_once(&peer, 1000).ok().flatten();
+                    (peer, rtt)
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let Ok((peer, rtt)) = handle.join() else { continue };
+            let line = match rtt {
+                Some(ms) => format!("[INFO] 已和重要同伴 {} 打过招呼（{:.1} ms）", peer, ms),
+                None => format!("[INFO] 重要同伴 {} 暂时没有回应，邻居缓存可能还没备好", peer),
+            };
+            log::info!("{}", line);
+            if let Some(ref tx) = log_tx {
+                tx.send(line);
+            }
+        }
+    });
 }
 
-fn ping_once(ip: &str, timeout_ms: u64) -> Result<Option<f64>> {
+pub(crate) fn ping_once(ip: &str, timeout_ms: u64) -> Result<Option<f64>> {
     let mut cmd = Command::new("ping");
     #[cfg(target_os = "windows")]
     {
@@ -1367,24 +3170,6 @@ fn decode_with_code_page(bytes: &[u8], code_page: u32) -> Option<String> {
     String::from_utf16(&wide[..written as usize]).ok()
 }
 
-fn should_stop_mgmt_read(e: &std::io::Error) -> bool {
-    // WouldBlock/TimedOut：本轮没等到回信，直接结束就好
-    if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut {
-        return true;
-    }
-
-    // Windows 下：UDP 目标端口尚未监听时，可能会抛出 10054（ConnectionReset）。
-    // 这属于“管理口还没准备好”的正常抖动，别拿它吓主人一跳～
-    #[cfg(target_os = "windows")]
-    {
-        if e.kind() == std::io::ErrorKind::ConnectionReset {
-            return true;
-        }
-    }
-
-    false
-}
-
 fn parse_ping_latency_ms(s: &str) -> Option<f64> {
     // Linux/macOS: time=12.3 ms / time<1 ms
     // Windows（中英本地化都可能出现）: time=12ms / 时间=12ms
@@ -1414,7 +3199,7 @@ fn parse_ping_latency_ms(s: &str) -> Option<f64> {
 }
 
 #[cfg(target_os = "linux")]
-fn resolve_edge_path_for_caps(edge_path: &str) -> Result<String> {
+pub(crate) fn resolve_edge_path_for_caps(edge_path: &str) -> Result<String> {
     // 如果是显式路径（含 /），优先使用它
     if edge_path.contains('/') {
         return Ok(edge_path.to_string());
@@ -1424,6 +3209,16 @@ fn resolve_edge_path_for_caps(edge_path: &str) -> Result<String> {
     Ok(resolved.to_string_lossy().to_string())
 }
 
+/// edge 二进制是否已经带齐所需 capabilities（getcap 不存在或读不到时视为没有）
+#[cfg(target_os = "linux")]
+pub(crate) fn edge_has_capabilities(edge_path: &str) -> bool {
+    let required = ["cap_net_admin", "cap_net_raw", "cap_setuid", "cap_setgid"];
+    let Ok(getcap) = which::which("getcap") else { return false };
+    let Ok(out) = Command::new(getcap).arg(edge_path).output() else { return false };
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    out.status.success() && required.iter().all(|c| stdout.contains(c))
+}
+
 /// 确保 edge 二进制具备所需 capabilities
 /// - 目标：在非 root 下也能创建 TAP，并能执行 drop privileges 相关系统调用
 /// - 实现：使用 pkexec 运行 setcap（KDE 下由 polkit 弹窗授权）
@@ -1433,26 +3228,14 @@ fn ensure_edge_capabilities(edge_path: &str) -> Result<()> {
     // - cap_net_admin/cap_net_raw：创建/配置 TAP、收发原始包
     // - cap_setuid/cap_setgid：允许 edge 在启动后 drop privileges
     // 备注：不同发行版/edge 版本可能要求略有差异，但这组在实践中更稳
-    let required = ["cap_net_admin", "cap_net_raw", "cap_setuid", "cap_setgid"];
     let cap_spec = "cap_net_admin,cap_net_raw,cap_setuid,cap_setgid+eip";
 
     let pkexec = which::which("pkexec").context("未找到 pkexec，请安装 polkit（KDE 可用 polkit-kde-agent）")?;
     let setcap = which::which("setcap").context("未找到 setcap，请安装 libcap 工具包")?;
 
-    // 如果 getcap 存在且已满足，就直接返回
-    if let Ok(getcap) = which::which("getcap") {
-        let out = Command::new(getcap)
-            .arg(edge_path)
-            .output()
-            .context("执行 getcap 失败")?;
-
-        let stdout = String::from_utf8_lossy(&out.stdout);
-        if out.status.success() {
-            let ok = required.iter().all(|c| stdout.contains(c));
-            if ok {
-                return Ok(());
-            }
-        }
+    // 如果已经满足，就直接返回
+    if edge_has_capabilities(edge_path) {
+        return Ok(());
     }
 
     log::info!("检测到非 root 运行环境，尝试为 edge 自动申请权限（pkexec + setcap）");
@@ -1474,28 +3257,50 @@ impl Drop for N2NProcess {
     fn drop(&mut self) {
         // 进程退出时尽量避免残留子进程
         // 说明：正常点击“断开”会优雅退出；应用退出时这里也尽量先温柔收拾（SIGINT），再兜底强制清理
-        if !self.is_running() {
-            return;
+        // 看护员的手、或者主人这边还有别的手握着（命令里借出去的那只）就不动：edge 可能正在好好干活
+        match self.handles {
+            Some(ref handles) if Arc::strong_count(handles) == 1 && self.is_running() => {}
+            _ => return,
         }
 
         // 先尝试温柔收拾工具（优雅退出）
         let _ = self.stop();
 
         // 稍等一会儿，让 edge 自己把活收尾；如果不听话，再请“掸子重击”出场
+        // 恩兔带出门的 edge 的组号（就是它的进程号），收尾后清它拉起的程序用
+        #[cfg(unix)]
+        let group = self.child.lock().unwrap().as_ref().and_then(Child::id);
         let mut need_force = true;
         {
             let mut child_guard = self.child.lock().unwrap();
             if let Some(child) = child_guard.as_mut() {
-                if let Ok(true) = wait_child_exit(child, Duration::from_millis(MGMT_STOP_TIMEOUT_MS)) {
+                if let Ok(true) = wait_child_exit(child, self.stop_timeout()) {
                     *child_guard = None;
                     need_force = false;
                 }
+            } else if let Some(pid) = self.adopted_pid() {
+                if crate::orphans::wait_gone(pid, self.stop_timeout()) {
+                    self.adopted.store(0, Ordering::SeqCst);
+                    need_force = false;
+                }
             }
         }
 
         if need_force {
             let _ = self.stop_force();
         }
+
+        // edge 走了，它拉起的程序可能还占着虚拟网卡：一家子清一遍再下班
+        #[cfg(unix)]
+        if let Some(pgid) = group {
+            match crate::process_group::kill(pgid) {
+                Ok(true) => log::warn!("edge（PID: {}）走后还有它拉起的程序没走，已一起清理", pgid),
+                Ok(false) => {}
+                Err(e) => log::warn!("{:#}", e),
+            }
+        }
+        #[cfg(windows)]
+        drop(self.job.lock().unwrap().take());
     }
 }
 
@@ -1504,9 +3309,9 @@ impl Drop for N2NProcess {
 fn wait_child_exit(child: &mut Child, timeout: Duration) -> Result<bool> {
     let deadline = Instant::now() + timeout;
     loop {
-        match child.try_wait().context("检查进程状态失败")? {
-            Some(_status) => return Ok(true),
-            None => {
+        match reaper::poll(child).context("检查进程状态失败")? {
+            ChildState::Exited(_) => return Ok(true),
+            ChildState::Running => {
                 if Instant::now() >= deadline {
                     return Ok(false);
                 }
@@ -1530,6 +3335,43 @@ fn parse_network_info(line: &str) -> Option<NetworkInfo> {
     })
 }
 
+/// 从 edge 的启动汇报里认出通道特性（不同版本措辞略有出入，这里只认关键字）
+/// - `using AES cipher.`
+/// - `using compression: lzo1x.`
+/// - `... header encryption ...`（带 disabled/off/no 视为未开启）
+fn parse_transport_line(features: &mut TransportFeatures, line: &str) {
+    let lower = line.to_ascii_lowercase();
+
+    if let Some(idx) = lower.find("using compression:") {
+        let value = line[idx + "using compression:".len()..]
+            .trim()
+            .trim_end_matches('.')
+            .trim();
+        if !value.is_empty() {
+            features.compression = Some(value.to_string());
+        }
+        return;
+    }
+
+    if let (Some(start), Some(end)) = (lower.find("using "), lower.find(" cipher")) {
+        let start = start + "using ".len();
+        if start < end {
+            let value = line[start..end].trim();
+            if !value.is_empty() && !value.contains(' ') {
+                features.cipher = Some(value.to_string());
+            }
+        }
+        return;
+    }
+
+    if lower.contains("header encryption") {
+        let off = ["disabled", " off", "no header", "not "]
+            .iter()
+            .any(|k| lower.contains(k));
+        features.header_encryption = Some(!off);
+    }
+}
+
 /// 从日志行中提取字段值
 fn extract_field<'a>(line: &'a str, field: &str) -> Option<&'a str> {
     let start_idx = line.find(field)? + field.len();
@@ -1540,29 +3382,16 @@ fn extract_field<'a>(line: &'a str, field: &str) -> Option<&'a str> {
     Some(remaining[..end_idx].trim())
 }
 
-/// 获取默认节点标识名称（主机名）
-fn get_default_node_name() -> String {
-    #[cfg(target_os = "windows")]
-    {
-        "n2n-maid".to_string()
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        match nix::unistd::gethostname() {
-            Ok(name) => {
-                let s = name.to_string_lossy().trim().to_string();
-                if s.is_empty() { "n2n-maid".to_string() } else { s }
-            }
-            Err(_) => "n2n-maid".to_string(),
-        }
-    }
-}
-
 /// 从 edge 的输出里提取一个“对主人友好”的提示文案（i18n key 或原始片段）
 fn extract_user_facing_notice(line: &str) -> Option<String> {
     let l = line.to_ascii_lowercase();
 
+    // edge 与 supernode 大版本对不上（v2 edge 碰上 v3 supernode，或反过来）：
+    // 这种情况下 edge 会一直重试，却永远注册不上，是最常见的“配置没错却连不上”
+    if is_version_mismatch_line(&l) {
+        return Some("error_version_mismatch".to_string());
+    }
+
     // TAP 创建被占用：典型表现是 tuntap ioctl + TUNSETIFF + Device or resource busy
     if l.contains("tunsetiff") && (l.contains("device or resource busy") || l.contains("resource busy")) {
         return Some("error_tap_busy".to_string());
@@ -1621,6 +3450,103 @@ fn extract_user_facing_notice(line: &str) -> Option<String> {
     None
 }
 
+/// 判断这行 edge 输出要不要递到日志面板
+/// - 备注：状态判定/提醒识别不受影响，筛掉的只是“给主人看的汇报”
+fn should_forward_line(filter: LogForwardFilter, line: &str) -> bool {
+    match filter {
+        LogForwardFilter::All => true,
+        LogForwardFilter::WarningsAndErrors => {
+            line.contains("WARNING") || extract_user_facing_notice(line).is_some()
+        }
+        LogForwardFilter::ConnectionEvents => {
+            let l = line.to_ascii_lowercase();
+            let is_connection_event = l.contains("created local tap device")
+                || l.contains("edge <<<")
+                || l.contains("supernode")
+                || l.contains("register")
+                || l.contains("p2p")
+                || l.contains("stop");
+            // 已识别的故障（error_ 编号）也算“连接大事”，主人需要看到
+            let is_known_failure = extract_user_facing_notice(line).is_some_and(|n| n.starts_with("error_"));
+            is_connection_event || is_known_failure
+        }
+    }
+}
+
+/// 从 edge 的 WARNING 行里整理出一条提醒（编号 + 内容）
+fn parse_edge_warning(line: &str) -> Option<(Option<String>, String)> {
+    let pos = line.find("WARNING")?;
+    let message = line[pos + "WARNING".len()..]
+        .trim_start_matches(':')
+        .trim()
+        .to_string();
+    if message.is_empty() {
+        return None;
+    }
+
+    let l = message.to_ascii_lowercase();
+    let code = if l.contains("community") && (l.contains("truncat") || l.contains("too long")) {
+        Some("warning_community_truncated")
+    } else if l.contains("unknown option") {
+        Some("warning_unknown_option")
+    } else if l.contains("mtu") {
+        Some("warning_mtu")
+    } else {
+        None
+    };
+
+    Some((code.map(|c| c.to_string()), message))
+}
+
+/// 把提醒记进小本子，并通过小喇叭告诉前端（同样的内容只记一次）
+fn record_edge_warning(warnings: &Arc<Mutex<Vec<EdgeWarning>>>, event_tx: Option<&EventSender>, line: &str) {
+    let Some((code, message)) = parse_edge_warning(line) else { return };
+
+    let warning = {
+        let mut list = warnings.lock().unwrap();
+        if list.iter().any(|w| w.message == message) {
+            return;
+        }
+        let warning = EdgeWarning {
+            code,
+            message,
+            at: unix_now_seconds(),
+        };
+        if list.len() >= MAX_WARNINGS {
+            list.remove(0);
+        }
+        list.push(warning.clone());
+        warning
+    };
+
+    log::warn!("N2N 提醒：{}", warning.message);
+    if let Some(tx) = event_tx {
+        let _ = tx.send(ProcessEvent::Warning(warning));
+    }
+}
+
+/// 识别 n2n 关于协议版本不一致的各种说法（传入的行需已转为小写）
+/// - v3 edge 收到 v2 包：`received packet with unknown protocol version (2)` 一类
+/// - 其他版本：`version mismatch` / `incompatible version` / `wrong version` / `invalid version`
+fn is_version_mismatch_line(l: &str) -> bool {
+    if !l.contains("version") {
+        return false;
+    }
+    [
+        "version mismatch",
+        "unknown protocol version",
+        "unsupported version",
+        "unsupported protocol version",
+        "incompatible version",
+        "wrong version",
+        "invalid version",
+        "bad version",
+    ]
+    .iter()
+    .any(|k| l.contains(k))
+        || l.contains("expected")
+}
+
 fn set_last_notice_if_changed(last_notice: &Arc<Mutex<Option<String>>>, notice: String) {
     let mut guard = last_notice.lock().unwrap();
     let changed = guard.as_deref() != Some(notice.as_str());
@@ -1632,6 +3558,25 @@ fn set_last_notice_if_changed(last_notice: &Arc<Mutex<Option<String>>>, notice:
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::edge_options::EdgeOption;
+
+    #[test]
+    fn test_peer_from_edges_row() {
+        let row = |json: serde_json::Value| PeerNodeInfo::from(serde_json::from_value::<MgmtEdgeRow>(json).unwrap());
+        let direct = row(serde_json::json!({
+            "mode": "p2p", "ip4addr": "10.0.0.5/24", "purgeable": 1, "local": 0,
+            "macaddr": "02:AB:00:10:FE:01", "sockaddr": "203.0.113.7:50123", "desc": "nas", "last_seen": 1700000000
+        }));
+        assert_eq!(direct.link, PeerLink::Direct);
+        assert_eq!(direct.vpn_ip.as_deref(), Some("10.0.0.5"));
+        assert_eq!(direct.mac.as_deref(), Some("02:ab:00:10:fe:01"));
+        assert_eq!((direct.name.as_deref(), direct.is_local), (Some("nas"), Some(false)));
+
+        let relayed = row(serde_json::json!({ "mode": "pSp", "ip4addr": "", "desc": "", "lastseen": 5 }));
+        assert_eq!(relayed.link, PeerLink::Relayed);
+        assert_eq!((relayed.name, relayed.vpn_ip, relayed.last_seen), (None, None, Some(5)));
+        assert_eq!(PeerLink::from_mode(Some("sn")), PeerLink::Unknown);
+    }
 
     #[test]
     fn test_process_creation() {
@@ -1659,10 +3604,237 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_management_password_from_extra_args() {
-        let args = Some("--management-password mypw -v -E");
-        assert_eq!(extract_management_password(args), Some("mypw".to_string()));
-        assert_eq!(extract_management_password(Some("-v -E")), None);
+    fn test_should_forward_line_filters() {
+        let verbose = "[edge.c:1234] sending packet to 10.0.0.3";
+        let warn = "WARNING: unknown option -f";
+        let tap = "created local tap device IP: 10.0.0.2, Mask: 255.255.255.0, MAC: 00:11:22:33:44:55";
+
+        assert!(should_forward_line(LogForwardFilter::All, verbose));
+        assert!(!should_forward_line(LogForwardFilter::WarningsAndErrors, verbose));
+        assert!(should_forward_line(LogForwardFilter::WarningsAndErrors, warn));
+        assert!(!should_forward_line(LogForwardFilter::ConnectionEvents, verbose));
+        assert!(!should_forward_line(LogForwardFilter::ConnectionEvents, warn));
+        assert!(should_forward_line(LogForwardFilter::ConnectionEvents, tap));
+    }
+
+    #[test]
+    fn test_parse_edge_warning() {
+        let line = "12/Jan/2025 10:00:00 [edge.c:321] WARNING: community name too long, truncated to 19 characters";
+        let (code, msg) = parse_edge_warning(line).unwrap();
+        assert_eq!(code.as_deref(), Some("warning_community_truncated"));
+        assert!(msg.starts_with("community name"));
+
+        let (code, _) = parse_edge_warning("WARNING: unknown option -f").unwrap();
+        assert_eq!(code.as_deref(), Some("warning_unknown_option"));
+        assert!(parse_edge_warning("edge started").is_none());
+    }
+
+    #[test]
+    fn test_record_edge_warning_dedupes() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let (tx, mut rx) = crate::events::channel();
+        record_edge_warning(&warnings, Some(&tx), "WARNING: something odd");
+        record_edge_warning(&warnings, Some(&tx), "WARNING: something odd");
+        assert_eq!(warnings.lock().unwrap().len(), 1);
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_edge_command_line_matches_start_args() {
+        let config = N2NConfig {
+            supernode: "sn.example.com:7777".to_string(),
+            community: "home lab".to_string(),
+            username: "laptop".to_string(),
+            encryption_key: "s3cret".to_string(),
+            edge_path: Some("/opt/n2n/edge".to_string()),
+            management_port: Some(5645),
+            management_password: Some("my pw".to_string()),
+            register_interval_secs: Some(10),
+            advanced_options: vec![EdgeOption::new("-v", None)],
+            ..Default::default()
+        };
+        assert_eq!(
+            edge_command_line(&config, false).unwrap(),
+            "/opt/n2n/edge -f -c 'home lab' -l sn.example.com:7777 -I laptop -k s3cret -a dhcp:0.0.0.0 -M 1290 -i 10 -t 5645 --management-password 'my pw' -v"
+        );
+        assert_eq!(
+            edge_command_line(&config, true).unwrap(),
+            "/opt/n2n/edge -f -c 'home lab' -l sn.example.com:7777 -I laptop -k ****** -a dhcp:0.0.0.0 -M 1290 -i 10 -t 5645 --management-password ****** -v"
+        );
+        assert_eq!(quote_arg("it's"), "'it'\\''s'");
+    }
+
+    #[test]
+    fn test_session_summary_accumulates_rounds() {
+        let mut t = SessionTracker {
+            started_at: 100,
+            ..Default::default()
+        };
+        t.current = TrafficCounters {
+            tx_packets: 10,
+            rx_packets: 20,
+            tx_bytes: Some(1000),
+            rx_bytes: None,
+        };
+        t.fold_current();
+        t.current.tx_packets = 5;
+        t.reconnect_count = 1;
+        t.exit_reason = Some(ExitReason::Killed);
+
+        let summary = t.summarize(160, "user_stop", None);
+        assert_eq!(summary.duration_secs, 60);
+        assert_eq!(summary.tx_packets, 15);
+        assert_eq!(summary.rx_packets, 20);
+        assert_eq!(summary.tx_bytes, Some(1000));
+        assert_eq!(summary.rx_bytes, None);
+        assert_eq!(summary.reconnect_count, 1);
+        assert_eq!(summary.exit_reason.as_deref(), Some("exit_killed"));
+    }
+
+    #[test]
+    fn test_finish_session_emits_once() {
+        let session = Arc::new(Mutex::new(Some(SessionTracker::default())));
+        let (tx, mut rx) = crate::events::channel();
+        finish_session(&session, Some(&tx), "force_stop", None);
+        finish_session(&session, Some(&tx), "force_stop", None);
+        assert!(matches!(rx.try_recv(), Ok(ProcessEvent::SessionEnded(_))));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_link_debounce_holds_through_short_gaps() {
+        let mut d = LinkDebounce::default();
+        let t0 = Instant::now();
+        assert_eq!(d.update(false, t0), (false, false));
+        assert_eq!(d.update(true, t0), (true, true));
+        // 一两次心跳缺席不算断开
+        assert_eq!(d.update(false, t0 + Duration::from_secs(1)), (true, false));
+        assert_eq!(d.update(true, t0 + Duration::from_secs(2)), (true, false));
+        // 持续缺席超过阈值才退回 Connecting
+        assert_eq!(d.update(false, t0 + Duration::from_secs(3)), (true, false));
+        assert_eq!(d.update(false, t0 + Duration::from_secs(3) + LINK_DOWN_HOLD), (false, false));
+        assert_eq!(d.update(true, t0 + Duration::from_secs(20)), (true, true));
+    }
+
+    #[test]
+    fn test_registration_watch_degrades_after_losing_supernode() {
+        let ts = |last_super| MgmtState {
+            timestamps: Some(mgmt::TimestampsRow { start_time: 900, last_super, last_p2p: 0 }),
+            ..MgmtState::default()
+        };
+        assert!(ts(970).is_registered(40, 1000));
+        assert!(!ts(950).is_registered(40, 1000));
+        assert!(!ts(0).is_registered(40, 1000));
+        assert!(!MgmtState::default().is_registered(40, 1000));
+
+        let mut w = RegistrationWatch::default();
+        // 还没报上到过：只是在连接中，不算降级
+        assert_eq!(w.update(false), None);
+        assert_eq!(w.update(true), None);
+        assert_eq!(w.update(false), Some(true));
+        assert_eq!(w.update(false), None);
+        assert!(w.degraded);
+        assert_eq!(w.update(true), Some(false));
+        assert!(!w.degraded);
+    }
+
+    #[test]
+    fn test_tap_watch_confirms_twice() {
+        let mut w = TapWatch::default();
+        assert_eq!(w.update(None), None);
+        // 一次看到可能只是网卡正在重建，连续两次才算数
+        assert_eq!(w.update(Some(TapProblem::Missing)), None);
+        assert_eq!(w.update(None), None);
+        assert_eq!(w.update(Some(TapProblem::Missing)), None);
+        assert_eq!(w.update(Some(TapProblem::Missing)), Some(TapChange::Broken(TapProblem::Missing)));
+        assert_eq!(w.update(Some(TapProblem::Missing)), None);
+        assert_eq!(w.problem, Some(TapProblem::Missing));
+
+        // 毛病换了也要再确认一次；好了马上恢复
+        assert_eq!(w.update(Some(TapProblem::AddressLost)), None);
+        assert_eq!(w.update(Some(TapProblem::AddressLost)), Some(TapChange::Broken(TapProblem::AddressLost)));
+        assert_eq!(w.update(None), Some(TapChange::Restored));
+        assert_eq!(w.problem, None);
+        assert_eq!(w.update(None), None);
+    }
+
+    #[test]
+    fn test_supernode_host() {
+        assert_eq!(supernode_host("n2n.example.com:7777"), "n2n.example.com");
+        assert_eq!(supernode_host("[2001:db8::1]:7777"), "2001:db8::1");
+        assert_eq!(supernode_host("2001:db8::1"), "2001:db8::1");
+        assert_eq!(supernode_host(" 10.0.0.1 "), "10.0.0.1");
+    }
+
+    #[test]
+    fn test_parse_edge_version() {
+        assert_eq!(
+            parse_edge_version("Welcome to n2n v.3.1.1.r1040.4c1c7d3 for Linux\n").as_deref(),
+            Some("3.1.1.r1040.4c1c7d3")
+        );
+        assert_eq!(parse_edge_version("Welcome to n2n v2.8.0 for Windows").as_deref(), Some("2.8.0"));
+        assert_eq!(parse_edge_version("usage: edge ..."), None);
+    }
+
+    #[test]
+    fn test_parse_transport_line() {
+        let mut features = TransportFeatures::default();
+        for line in [
+            "15/Mar/2024 10:00:00 [edge_utils.c:  300] using compression: lzo1x.",
+            "15/Mar/2024 10:00:00 [edge_utils.c:  310] using AES cipher.",
+            "15/Mar/2024 10:00:00 [edge.c: 1100] enabled header encryption",
+            "15/Mar/2024 10:00:01 [edge_utils.c: 2000] [OK] edge <<< ================ >>> supernode",
+        ] {
+            parse_transport_line(&mut features, line);
+        }
+        assert_eq!(
+            features,
+            TransportFeatures {
+                cipher: Some("AES".to_string()),
+                compression: Some("lzo1x".to_string()),
+                header_encryption: Some(true),
+            }
+        );
+
+        parse_transport_line(&mut features, "header encryption disabled");
+        assert_eq!(features.header_encryption, Some(false));
+    }
+
+    #[test]
+    fn test_netmask_to_prefix() {
+        assert_eq!(netmask_to_prefix("255.255.255.0"), Some(24));
+        assert_eq!(netmask_to_prefix("255.255.0.0"), Some(16));
+        assert_eq!(netmask_to_prefix("255.0.255.0"), None);
+        assert_eq!(netmask_to_prefix("not-a-mask"), None);
+    }
+
+    #[test]
+    fn test_pause_without_session_fails() {
+        let process = N2NProcess::new();
+        assert!(process.pause().is_err());
+        assert!(process.resume().is_err());
+        assert!(!process.is_paused());
+    }
+
+    #[test]
+    fn test_extract_notice_version_mismatch() {
+        for line in [
+            "WARNING: received packet with unknown protocol version (2): discarded",
+            "ERROR: version mismatch with supernode",
+            "Received packet with invalid version 2 (expected 3)",
+        ] {
+            assert_eq!(
+                extract_user_facing_notice(line).as_deref(),
+                Some("error_version_mismatch"),
+                "{line}"
+            );
+        }
+        assert_ne!(
+            extract_user_facing_notice("edge v3.1.1 started").as_deref(),
+            Some("error_version_mismatch")
+        );
     }
 
     #[test]
@@ -1671,4 +3843,40 @@ mod tests {
         assert_eq!(parse_ping_latency_ms("64 bytes from 1.1.1.1: time<1 ms"), Some(1.0));
         assert_eq!(parse_ping_latency_ms("来自 1.1.1.1 的回复：时间=23ms TTL=64"), Some(23.0));
     }
+
+    #[test]
+    fn test_reconnect_delay_follows_policy() {
+        let mut config = N2NConfig::default();
+        assert_eq!(reconnect_delay(&config, false, 1), None);
+
+        config.reconnect_policy = ReconnectPolicy::OnFailure;
+        config.reconnect_max_attempts = 4;
+        config.reconnect_backoff_max_secs = 10;
+        assert_eq!(reconnect_delay(&config, true, 1), None);
+        assert_eq!(reconnect_delay(&config, false, 1), Some(Duration::from_secs(2)));
+        assert_eq!(reconnect_delay(&config, false, 2), Some(Duration::from_secs(4)));
+        assert_eq!(reconnect_delay(&config, false, 4), Some(Duration::from_secs(10)));
+        assert_eq!(reconnect_delay(&config, false, 5), None);
+
+        config.reconnect_policy = ReconnectPolicy::Always;
+        config.reconnect_max_attempts = 0;
+        assert_eq!(reconnect_delay(&config, true, 100), Some(Duration::from_secs(10)));
+
+        // 抖动落在上下 20% 之内，两头都能取到
+        let delay = Duration::from_secs(10);
+        assert_eq!(with_jitter(delay, 0), Duration::from_secs(8));
+        assert_eq!(with_jitter(delay, 40), Duration::from_secs(12));
+        assert_eq!(with_jitter(delay, 20), delay);
+        assert!((0..1000).map(|_| with_jitter(delay, random_roll())).all(|d| (8..=12).contains(&d.as_secs())));
+    }
+
+    #[test]
+    fn test_generation_marks_old_workers_stale() {
+        let generation = Generation::default();
+        let first = generation.advance();
+        assert!(generation.is_current(first));
+        let second = generation.advance();
+        assert!(!generation.is_current(first));
+        assert!(generation.is_current(second));
+    }
 }
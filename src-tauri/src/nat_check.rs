@@ -0,0 +1,412 @@
+//! NAT 侦察（看看主人家门口隔了几层 NAT，打洞还有没有希望）
+//!
+//! 同伴之间能不能直连，取决于两边各隔着几层 NAT。运营商级 NAT（CGNAT）和家里套了两层路由器的情况下，
+//! 打洞几乎不可能成功，edge 会一直卡在“只能经总部中转”，主人却不知道为什么慢。恩兔这样侦察：
+//! - 用 STUN 问公网上的服务器“你看到我是从哪个地址来的”，得到真正的出口地址；
+//! - 用 NAT-PMP，不行再用 UPnP，问家里的路由器“你的外网口（WAN）地址是多少”；
+//! - 路由器的外网地址落在 `100.64.0.0/10`（运营商级 NAT 专用段），或者和 STUN 看到的出口地址对不上，
+//!   就说明路由器外面还隔着一层 NAT：外网地址是私网地址算“两层 NAT”，否则算“运营商级 NAT”。
+//!
+//! 这两种情况都建议主人打开中转模式（高级选项 `-S1`），别让 edge 白白打洞。
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// 问出口地址的 STUN 服务器（按顺序试）
+const STUN_SERVERS: &[&str] = &["stun.l.google.com:19302", "stun.cloudflare.com:3478"];
+const STUN_MAGIC: u32 = 0x2112_a442;
+/// 每一问最多等多久
+const ASK_TIMEOUT: Duration = Duration::from_millis(1500);
+/// NAT-PMP 在路由器上的端口
+const NAT_PMP_PORT: u16 = 5351;
+const SSDP_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(239, 255, 255, 250), 1900);
+
+/// 侦察结论
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NatVerdict {
+    /// 本机直接就是公网地址
+    Public,
+    /// 只隔着自家路由器一层（打洞通常没问题）
+    Nat,
+    /// 路由器外面还有一层私网（两层 NAT）
+    DoubleNat,
+    /// 运营商级 NAT
+    Cgnat,
+    /// 问不到出口地址（STUN 不通）
+    Unknown,
+}
+
+impl NatVerdict {
+    /// 这种情况下打洞基本没戏，建议走中转
+    pub fn relay_recommended(self) -> bool {
+        matches!(self, NatVerdict::DoubleNat | NatVerdict::Cgnat)
+    }
+}
+
+/// 侦察报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NatReport {
+    /// 本机出门用的地址
+    pub local_ip: Option<Ipv4Addr>,
+    /// 默认网关（家里的路由器）
+    pub gateway: Option<Ipv4Addr>,
+    /// 路由器报告的外网口地址
+    pub router_wan_ip: Option<Ipv4Addr>,
+    /// 外网口地址是怎么问到的（`nat-pmp` / `upnp`）
+    pub router_wan_source: Option<&'static str>,
+    /// STUN 看到的出口地址
+    pub external_ip: Option<Ipv4Addr>,
+    pub verdict: NatVerdict,
+    /// 建议打开中转模式（`-S1`）
+    pub relay_recommended: bool,
+}
+
+/// 100.64.0.0/10（运营商级 NAT 专用段）
+pub fn is_cgnat(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    a == 100 && (64..128).contains(&b)
+}
+
+/// 根据三个地址下结论
+pub fn classify(local: Option<Ipv4Addr>, router_wan: Option<Ipv4Addr>, external: Option<Ipv4Addr>) -> NatVerdict {
+    let Some(external) = external else {
+        return NatVerdict::Unknown;
+    };
+    if local == Some(external) {
+        return NatVerdict::Public;
+    }
+    // 手机热点之类：本机直接拿到运营商级 NAT 的地址
+    if local.is_some_and(is_cgnat) {
+        return NatVerdict::Cgnat;
+    }
+    match router_wan {
+        Some(wan) if is_cgnat(wan) => NatVerdict::Cgnat,
+        Some(wan) if wan != external && wan.is_private() => NatVerdict::DoubleNat,
+        Some(wan) if wan != external => NatVerdict::Cgnat,
+        _ => NatVerdict::Nat,
+    }
+}
+
+/// 侦察一遍（要花几秒，放在后台线程或异步命令里用）
+pub fn diagnose() -> NatReport {
+    let (local_ip, external_ip) = match stun_external() {
+        Ok((local, external)) => (Some(local), Some(external)),
+        Err(e) => {
+            log::warn!("STUN 问不到出口地址：{:#}", e);
+            (None, None)
+        }
+    };
+    let gateway = default_gateway();
+    let (router_wan_ip, router_wan_source) = match gateway.and_then(nat_pmp_external) {
+        Some(ip) => (Some(ip), Some("nat-pmp")),
+        None => match upnp_external() {
+            Some(ip) => (Some(ip), Some("upnp")),
+            None => (None, None),
+        },
+    };
+    let verdict = classify(local_ip, router_wan_ip, external_ip);
+    log::info!(
+        "NAT 侦察：本机 {:?}，网关 {:?}，路由器外网口 {:?}（{}），出口 {:?} → {:?}",
+        local_ip,
+        gateway,
+        router_wan_ip,
+        router_wan_source.unwrap_or("-"),
+        external_ip,
+        verdict
+    );
+    NatReport {
+        local_ip,
+        gateway,
+        router_wan_ip,
+        router_wan_source,
+        external_ip,
+        verdict,
+        relay_recommended: verdict.relay_recommended(),
+    }
+}
+
+/// STUN Binding 请求（RFC 5389）
+fn stun_request(transaction: &[u8; 12]) -> [u8; 20] {
+    let mut request = [0u8; 20];
+    request[..2].copy_from_slice(&0x0001u16.to_be_bytes());
+    request[4..8].copy_from_slice(&STUN_MAGIC.to_be_bytes());
+    request[8..].copy_from_slice(transaction);
+    request
+}
+
+/// 从 STUN 回信里找出对方看到的地址（认 XOR-MAPPED-ADDRESS 和老式的 MAPPED-ADDRESS）
+fn parse_stun_response(reply: &[u8], transaction: &[u8; 12]) -> Option<Ipv4Addr> {
+    if reply.len() < 20 || reply[..2] != [0x01, 0x01] || reply[8..20] != transaction[..] {
+        return None;
+    }
+    let mut rest = &reply[20..];
+    let mut mapped = None;
+    while rest.len() >= 4 {
+        let kind = u16::from_be_bytes([rest[0], rest[1]]);
+        let len = u16::from_be_bytes([rest[2], rest[3]]) as usize;
+        let value = rest.get(4..4 + len)?;
+        if len >= 8 && value[1] == 0x01 {
+            let raw = u32::from_be_bytes([value[4], value[5], value[6], value[7]]);
+            match kind {
+                0x0020 => return Some(Ipv4Addr::from(raw ^ STUN_MAGIC)),
+                0x0001 => mapped = Some(Ipv4Addr::from(raw)),
+                _ => {}
+            }
+        }
+        rest = rest.get(4 + len.div_ceil(4) * 4..).unwrap_or_default();
+    }
+    mapped
+}
+
+/// 问 STUN 服务器，返回（本机出门用的地址，出口地址）
+fn stun_external() -> Result<(Ipv4Addr, Ipv4Addr)> {
+    let mut last_error = anyhow::anyhow!("没有可用的 STUN 服务器");
+    for server in STUN_SERVERS {
+        match stun_ask(server) {
+            Ok(found) => return Ok(found),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn stun_ask(server: &str) -> Result<(Ipv4Addr, Ipv4Addr)> {
+    let addr = server
+        .to_socket_addrs()
+        .with_context(|| format!("解析 {} 失败", server))?
+        .find(SocketAddr::is_ipv4)
+        .with_context(|| format!("{} 没有 IPv4 地址", server))?;
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).context("打开 UDP 套接字失败")?;
+    socket.set_read_timeout(Some(ASK_TIMEOUT))?;
+    socket.connect(addr).with_context(|| format!("连不上 {}", server))?;
+
+    let mut transaction = [0u8; 12];
+    getrandom::getrandom(&mut transaction).map_err(|e| anyhow::anyhow!("生成随机数失败：{}", e))?;
+    socket.send(&stun_request(&transaction))?;
+    let mut reply = [0u8; 512];
+    let n = socket.recv(&mut reply).with_context(|| format!("{} 没有回话", server))?;
+    let external = parse_stun_response(&reply[..n], &transaction).with_context(|| format!("{} 的回信看不懂", server))?;
+    let local = match socket.local_addr()?.ip() {
+        std::net::IpAddr::V4(ip) => ip,
+        other => anyhow::bail!("本机地址不是 IPv4：{}", other),
+    };
+    Ok((local, external))
+}
+
+/// NAT-PMP（RFC 6886）：问路由器的外网口地址
+fn nat_pmp_external(gateway: Ipv4Addr) -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.set_read_timeout(Some(ASK_TIMEOUT)).ok()?;
+    socket.send_to(&[0, 0], (gateway, NAT_PMP_PORT)).ok()?;
+    let mut reply = [0u8; 16];
+    let (n, from) = socket.recv_from(&mut reply).ok()?;
+    parse_nat_pmp(&reply[..n]).filter(|_| from.ip() == gateway)
+}
+
+/// 版本 0、操作码 128、结果 0，最后 4 个字节是外网口地址
+fn parse_nat_pmp(reply: &[u8]) -> Option<Ipv4Addr> {
+    if reply.len() < 12 || reply[0] != 0 || reply[1] != 128 || reply[2..4] != [0, 0] {
+        return None;
+    }
+    Some(Ipv4Addr::new(reply[8], reply[9], reply[10], reply[11]))
+}
+
+/// UPnP IGD：先在局域网里喊一声找到路由器，再照它的说明书问 GetExternalIPAddress
+fn upnp_external() -> Option<Ipv4Addr> {
+    let location = ssdp_discover()?;
+    let (base, _) = split_url(&location)?;
+    let description = http_request(&location, "GET", &[], "")?;
+    let (service, control) = find_wan_service(&description)?;
+    let control = if control.starts_with("http://") {
+        control
+    } else {
+        format!("{}/{}", base, control.trim_start_matches('/'))
+    };
+    let body = format!(
+        "<?xml version=\"1.0\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:GetExternalIPAddress xmlns:u=\"{}\"/></s:Body></s:Envelope>",
+        service
+    );
+    let action = format!("SOAPAction: \"{}#GetExternalIPAddress\"", service);
+    let reply = http_request(
+        &control,
+        "POST",
+        &["Content-Type: text/xml; charset=\"utf-8\"", &action],
+        &body,
+    )?;
+    tag_text(&reply, "NewExternalIPAddress")?.trim().parse().ok()
+}
+
+/// SSDP 搜索，返回路由器说明书的地址
+fn ssdp_discover() -> Option<String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.set_read_timeout(Some(ASK_TIMEOUT)).ok()?;
+    let search = "M-SEARCH * HTTP/1.1\r\n\
+                  HOST: 239.255.255.250:1900\r\n\
+                  MAN: \"ssdp:discover\"\r\n\
+                  MX: 1\r\n\
+                  ST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\n\r\n";
+    socket.send_to(search.as_bytes(), SSDP_ADDR).ok()?;
+    let deadline = Instant::now() + ASK_TIMEOUT;
+    let mut reply = [0u8; 2048];
+    while Instant::now() < deadline {
+        let n = socket.recv(&mut reply).ok()?;
+        let text = String::from_utf8_lossy(&reply[..n]);
+        let location = text.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("location").then(|| value.trim().to_string())
+        });
+        if location.is_some() {
+            return location;
+        }
+    }
+    None
+}
+
+/// 说明书里的 WANIPConnection / WANPPPConnection 服务，返回（服务类型，控制地址）
+fn find_wan_service(description: &str) -> Option<(String, String)> {
+    description.split("<service>").skip(1).find_map(|block| {
+        let service = tag_text(block, "serviceType")?.trim();
+        let control = tag_text(block, "controlURL")?.trim();
+        (service.contains(":WANIPConnection:") || service.contains(":WANPPPConnection:"))
+            .then(|| (service.to_string(), control.to_string()))
+    })
+}
+
+/// `<tag>文字</tag>` 里的文字（只找第一个）
+fn tag_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{}>", tag))? + start;
+    Some(&xml[start..end])
+}
+
+/// `http://host:port/path` → (`http://host:port`, `host:port`)
+fn split_url(url: &str) -> Option<(String, String)> {
+    let rest = url.strip_prefix("http://")?;
+    let host = rest.split('/').next()?.to_string();
+    Some((format!("http://{}", host), host))
+}
+
+/// 最简单的 HTTP/1.0 请求（路由器说明书和 SOAP 用），返回正文
+fn http_request(url: &str, method: &str, headers: &[&str], body: &str) -> Option<String> {
+    let (_, host) = split_url(url)?;
+    let path = url.strip_prefix("http://")?.strip_prefix(host.as_str())?;
+    let path = if path.is_empty() { "/" } else { path };
+    let addr = host.to_socket_addrs().ok()?.next()?;
+    let mut stream = TcpStream::connect_timeout(&addr, ASK_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(ASK_TIMEOUT)).ok()?;
+
+    let mut request = format!("{} {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n", method, path, host);
+    for header in headers {
+        request.push_str(header);
+        request.push_str("\r\n");
+    }
+    request.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+    stream.write_all(request.as_bytes()).ok()?;
+
+    let mut response = Vec::new();
+    let _ = stream.take(256 * 1024).read_to_end(&mut response);
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    head.split_whitespace()
+        .nth(1)
+        .filter(|status| status.starts_with('2'))
+        .map(|_| body.to_string())
+}
+
+/// 默认网关
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    parse_default_gateway(&std::fs::read_to_string("/proc/net/route").ok()?)
+}
+
+#[cfg(target_os = "windows")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    use windows_sys::Win32::Foundation::NO_ERROR;
+    use windows_sys::Win32::NetworkManagement::IpHelper::{GetBestRoute, MIB_IPFORWARDROW};
+
+    // 去 8.8.8.8 走哪条路，下一跳就是默认网关（地址按网络字节序存放）
+    let dest = u32::from_ne_bytes([8, 8, 8, 8]);
+    let mut row: MIB_IPFORWARDROW = unsafe { std::mem::zeroed() };
+    if unsafe { GetBestRoute(dest, 0, &mut row) } != NO_ERROR {
+        return None;
+    }
+    let hop = Ipv4Addr::from(row.dwForwardNextHop.to_ne_bytes());
+    (!hop.is_unspecified()).then_some(hop)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn default_gateway() -> Option<Ipv4Addr> {
+    None
+}
+
+/// `/proc/net/route` 里的默认路由（目标 0.0.0.0、带 RTF_GATEWAY）的网关
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_default_gateway(table: &str) -> Option<Ipv4Addr> {
+    table.lines().skip(1).find_map(|line| {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        let flags = u16::from_str_radix(cols.get(3)?, 16).ok()?;
+        if *cols.get(1)? != "00000000" || flags & 0x2 == 0 {
+            return None;
+        }
+        Some(Ipv4Addr::from(u32::from_str_radix(cols[2], 16).ok()?.to_le_bytes()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nat_classification_and_parsers() {
+        let ip = |s: &str| Some(s.parse::<Ipv4Addr>().unwrap());
+        assert_eq!(classify(ip("203.0.113.5"), None, ip("203.0.113.5")), NatVerdict::Public);
+        assert_eq!(classify(ip("192.168.1.2"), ip("203.0.113.5"), ip("203.0.113.5")), NatVerdict::Nat);
+        assert_eq!(classify(ip("192.168.1.2"), None, ip("203.0.113.5")), NatVerdict::Nat);
+        assert_eq!(classify(ip("192.168.1.2"), ip("100.72.1.9"), ip("203.0.113.5")), NatVerdict::Cgnat);
+        assert_eq!(classify(ip("192.168.1.2"), ip("198.51.100.7"), ip("203.0.113.5")), NatVerdict::Cgnat);
+        assert_eq!(classify(ip("192.168.1.2"), ip("192.168.0.10"), ip("203.0.113.5")), NatVerdict::DoubleNat);
+        assert_eq!(classify(ip("100.80.3.4"), None, ip("203.0.113.5")), NatVerdict::Cgnat);
+        assert_eq!(classify(ip("192.168.1.2"), None, None), NatVerdict::Unknown);
+        assert!(NatVerdict::Cgnat.relay_recommended());
+        assert!(!NatVerdict::Nat.relay_recommended());
+
+        // STUN：XOR-MAPPED-ADDRESS 203.0.113.5:54321
+        let transaction = [7u8; 12];
+        let mut reply = vec![0x01, 0x01, 0x00, 0x0c];
+        reply.extend_from_slice(&STUN_MAGIC.to_be_bytes());
+        reply.extend_from_slice(&transaction);
+        reply.extend_from_slice(&[0x00, 0x20, 0x00, 0x08, 0x00, 0x01]);
+        reply.extend_from_slice(&(54321u16 ^ 0x2112).to_be_bytes());
+        reply.extend_from_slice(&(u32::from(Ipv4Addr::new(203, 0, 113, 5)) ^ STUN_MAGIC).to_be_bytes());
+        assert_eq!(parse_stun_response(&reply, &transaction), ip("203.0.113.5"));
+        assert_eq!(parse_stun_response(&reply, &[0u8; 12]), None);
+        assert_eq!(&stun_request(&transaction)[4..8], &STUN_MAGIC.to_be_bytes());
+
+        assert_eq!(parse_nat_pmp(&[0, 128, 0, 0, 0, 0, 0, 9, 100, 72, 1, 9]), ip("100.72.1.9"));
+        assert_eq!(parse_nat_pmp(&[0, 128, 0, 3, 0, 0, 0, 9, 100, 72, 1, 9]), None);
+
+        let route = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+                     eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+                     eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\n";
+        assert_eq!(parse_default_gateway(route), ip("192.168.1.1"));
+
+        let description = "<root><service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType>\
+                           <controlURL>/l3f</controlURL></service><service>\
+                           <serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType>\
+                           <controlURL>/ctl/IPConn</controlURL></service></root>";
+        assert_eq!(
+            find_wan_service(description),
+            Some(("urn:schemas-upnp-org:service:WANIPConnection:1".to_string(), "/ctl/IPConn".to_string()))
+        );
+        assert_eq!(
+            split_url("http://192.168.1.1:5000/rootDesc.xml"),
+            Some(("http://192.168.1.1:5000".to_string(), "192.168.1.1:5000".to_string()))
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! 认路（看主人现在连着哪个网络，按对照表换用对应的方案）
+//!
+//! 笔记本在公司和家里要连的小组不一样，以前每次都得手动换方案。
+//! 现在在指示簿里写一张对照表（`network_rules`），恩兔会留意网络的变化：
+//! - `ssid`：连着这个 Wi-Fi 时生效；
+//! - `interface`：这块网卡（例如 `eth0`、`以太网`）连着时生效；
+//! - 两项都写时要同时满足，都不写的规则不生效；从上往下，第一条对上的规则说了算。
+//!
+//! 只在网络变化时换一次方案，之后主人手动换回去也不会被马上改掉。
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// 对照表里的一条规则
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkRule {
+    /// Wi-Fi 名称（SSID），留空表示不看
+    #[serde(default)]
+    pub ssid: String,
+    /// 网卡名字，留空表示不看
+    #[serde(default)]
+    pub interface: String,
+    /// 对上时换用的方案名字
+    pub profile: String,
+}
+
+impl NetworkRule {
+    fn matches(&self, network: &NetworkSnapshot) -> bool {
+        let ssid = self.ssid.trim();
+        let interface = self.interface.trim();
+        if ssid.is_empty() && interface.is_empty() {
+            return false;
+        }
+        (ssid.is_empty() || network.ssids.iter().any(|s| s == ssid))
+            && (interface.is_empty() || network.interfaces.iter().any(|i| i.eq_ignore_ascii_case(interface)))
+    }
+}
+
+/// 此刻的网络情况
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkSnapshot {
+    /// 正连着的 Wi-Fi（多块无线网卡时可能有好几个）
+    pub ssids: Vec<String>,
+    /// 连着的网卡（不含回环）
+    pub interfaces: Vec<String>,
+}
+
+impl NetworkSnapshot {
+    /// 给主人看的一句话（优先说 Wi-Fi 名字）
+    pub fn describe(&self) -> String {
+        if self.ssids.is_empty() {
+            self.interfaces.join(", ")
+        } else {
+            self.ssids.join(", ")
+        }
+    }
+}
+
+/// 按对照表找出第一条对上的规则
+pub fn pick<'a>(rules: &'a [NetworkRule], network: &NetworkSnapshot) -> Option<&'a NetworkRule> {
+    rules.iter().find(|rule| rule.matches(network))
+}
+
+/// 看一眼现在的网络
+pub fn detect() -> NetworkSnapshot {
+    let mut network = NetworkSnapshot {
+        ssids: detect_ssids(),
+        interfaces: detect_interfaces(),
+    };
+    network.ssids.sort();
+    network.ssids.dedup();
+    network.interfaces.sort();
+    network
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn detect_ssids() -> Vec<String> {
+    run("netsh", &["wlan", "show", "interfaces"])
+        .map(|out| parse_netsh_ssids(&out))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn detect_ssids() -> Vec<String> {
+    if let Some(out) = run("nmcli", &["-t", "-f", "active,ssid", "dev", "wifi"]) {
+        return parse_nmcli_ssids(&out);
+    }
+    // 没有 NetworkManager 时问 iwgetid（只报第一块无线网卡）
+    run("iwgetid", &["-r"])
+        .map(|out| out.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+        .into_iter()
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn detect_ssids() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn detect_interfaces() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            std::fs::read_to_string(entry.path().join("operstate"))
+                .map(|state| state.trim() == "up")
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name != "lo")
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn detect_interfaces() -> Vec<String> {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        GAA_FLAG_SKIP_UNICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows_sys::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
+
+    /// 回环网卡的类型编号（IF_TYPE_SOFTWARE_LOOPBACK）
+    const IF_TYPE_SOFTWARE_LOOPBACK: u32 = 24;
+
+    let flags = GAA_FLAG_SKIP_UNICAST | GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    // 结构体要求 8 字节对齐，所以用 u64 数组当缓冲区
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64>;
+    loop {
+        buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as u32,
+                flags,
+                std::ptr::null(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                &mut size,
+            )
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_BUFFER_OVERFLOW => continue,
+            other => {
+                log::debug!("GetAdaptersAddresses 失败：{}", other);
+                return Vec::new();
+            }
+        }
+    }
+
+    let mut interfaces = Vec::new();
+    let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !current.is_null() {
+        let adapter = unsafe { &*current };
+        if adapter.OperStatus == IfOperStatusUp && adapter.IfType != IF_TYPE_SOFTWARE_LOOPBACK {
+            let name = unsafe {
+                let mut n = 0;
+                while *adapter.FriendlyName.add(n) != 0 {
+                    n += 1;
+                }
+                String::from_utf16_lossy(std::slice::from_raw_parts(adapter.FriendlyName, n))
+            };
+            interfaces.push(name);
+        }
+        current = adapter.Next;
+    }
+    interfaces
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn detect_interfaces() -> Vec<String> {
+    Vec::new()
+}
+
+/// `netsh wlan show interfaces` 里的 `SSID : xxx` 行（不要 BSSID 行）
+#[cfg_attr(not(any(target_os = "windows", test)), allow(dead_code))]
+fn parse_netsh_ssids(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == "SSID").then(|| value.trim().to_string())
+        })
+        .filter(|ssid| !ssid.is_empty())
+        .collect()
+}
+
+/// `nmcli -t -f active,ssid dev wifi` 里标着 yes 的行（SSID 里的 `:` 会被转义成 `\:`）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_nmcli_ssids(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("yes:"))
+        .map(|ssid| ssid.replace("\\:", ":"))
+        .filter(|ssid| !ssid.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(ssid: &str, interface: &str, profile: &str) -> NetworkRule {
+        NetworkRule {
+            ssid: ssid.to_string(),
+            interface: interface.to_string(),
+            profile: profile.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_detect_output_and_pick_rule() {
+        let netsh = "\
+    Name                   : WLAN
+    State                  : connected
+    SSID                   : Office: 5G
+    BSSID                  : 12:34:56:78:9a:bc
+";
+        assert_eq!(parse_netsh_ssids(netsh), vec!["Office: 5G"]);
+        assert_eq!(parse_nmcli_ssids("no:Neighbour\nyes:Home\\:Net\n"), vec!["Home:Net"]);
+
+        let rules = vec![
+            rule("", "", "Never"),
+            rule("Office: 5G", "", "Office"),
+            rule("", "eth0", "Wired"),
+            rule("Home", "wlan0", "Home"),
+        ];
+        let office = NetworkSnapshot {
+            ssids: vec!["Office: 5G".to_string()],
+            interfaces: vec!["eth0".to_string()],
+        };
+        assert_eq!(pick(&rules, &office).map(|r| r.profile.as_str()), Some("Office"));
+        let wired = NetworkSnapshot {
+            ssids: vec![],
+            interfaces: vec!["ETH0".to_string()],
+        };
+        assert_eq!(pick(&rules, &wired).map(|r| r.profile.as_str()), Some("Wired"));
+        let home_other_card = NetworkSnapshot {
+            ssids: vec!["Home".to_string()],
+            interfaces: vec!["wlan1".to_string()],
+        };
+        assert_eq!(pick(&rules, &home_other_card), None);
+    }
+}
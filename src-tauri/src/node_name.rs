@@ -0,0 +1,149 @@
+//! 默认工号（主人没填 edge 名称时，用主机名给恩兔起一个 edge 认得的名字）
+//!
+//! edge 的 `-I` 描述最多 15 个字节（N2N_DESC_SIZE 是 16，要留一个 `\0`），超出的部分会被硬截断，
+//! 中文主机名还可能被截在半个字符上，在 supernode 和同伴那边显示成乱码。
+//! 中文 Windows 上“张三的电脑”“ＤＥＳＫＴＯＰ－１２３”这样的主机名很常见，所以这里先整理一遍：
+//! - 全角字母数字换成半角，带音调的拉丁字母换成不带音调的（é → e、ß → ss）；
+//! - 其他 edge 不好处理的字符（汉字、空格、符号）去掉，连续的分隔符合并成一个 `-`；
+//! - 截到 15 个字节；整理完什么都不剩时，按原主机名算一个固定的 `maid-xxxxxxxx`，同一台电脑每次都一样。
+use std::borrow::Cow;
+
+/// edge 描述的最大字节数
+pub const NODE_NAME_MAX_LEN: usize = 15;
+
+/// 主机名都拿不到时的名字
+const FALLBACK_NAME: &str = "n2n-maid";
+
+/// 带音调的拉丁字母 → ASCII（只收常见的，查之前先转成小写）
+const LATIN_FOLDS: &[(&str, &str)] = &[
+    ("àáâãäåāăą", "a"),
+    ("çćĉċč", "c"),
+    ("ďđð", "d"),
+    ("èéêëēĕėęě", "e"),
+    ("ĝğġģ", "g"),
+    ("ĥħ", "h"),
+    ("ìíîïĩīĭįı", "i"),
+    ("ĵ", "j"),
+    ("ķ", "k"),
+    ("ĺļľŀł", "l"),
+    ("ñńņňŉ", "n"),
+    ("òóôõöøōŏő", "o"),
+    ("ŕŗř", "r"),
+    ("śŝşš", "s"),
+    ("ţťŧ", "t"),
+    ("ùúûüũūŭůűų", "u"),
+    ("ŵ", "w"),
+    ("ýÿŷ", "y"),
+    ("źżž", "z"),
+    ("ß", "ss"),
+    ("æ", "ae"),
+    ("œ", "oe"),
+    ("þ", "th"),
+];
+
+/// 本机的默认工号（已经整理成 edge 认得的样子）
+pub fn default_node_name() -> String {
+    match host_name() {
+        Some(host) => {
+            let name = sanitize(&host);
+            if name != host {
+                log::info!("主机名“{}”不适合直接当 edge 名称，改用“{}”", host, name);
+            }
+            name
+        }
+        None => FALLBACK_NAME.to_string(),
+    }
+}
+
+/// 本机的主机名（原样，没整理过）
+#[cfg(target_os = "windows")]
+pub fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn host_name() -> Option<String> {
+    nix::unistd::gethostname()
+        .ok()
+        .map(|name| name.to_string_lossy().trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// 把任意主机名整理成 edge 认得的名字（结果一定非空、是 ASCII、不超过 15 个字节）
+pub fn sanitize(host: &str) -> String {
+    let mut name = String::new();
+    for c in host.chars() {
+        match fold(c) {
+            Some(folded) => name.push_str(&folded),
+            // 去掉的字符当作分隔符，免得“李雷-PC”的两段粘在一起
+            None => {
+                if !name.is_empty() && !name.ends_with('-') {
+                    name.push('-');
+                }
+            }
+        }
+    }
+
+    let mut name = name.trim_matches(['-', '.']).to_string();
+    name.truncate(NODE_NAME_MAX_LEN);
+    let name = name.trim_end_matches(['-', '.']);
+    if name.is_empty() {
+        format!("maid-{:08x}", fnv1a(host))
+    } else {
+        name.to_string()
+    }
+}
+
+/// 一个字符能不能留下来（能的话换成什么）
+fn fold(c: char) -> Option<Cow<'static, str>> {
+    // 全角 ASCII（！到～）和全角空格
+    let c = match c as u32 {
+        0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        0x3000 => ' ',
+        _ => c,
+    };
+    if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+        return Some(Cow::Owned(c.to_string()));
+    }
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    let folded = LATIN_FOLDS
+        .iter()
+        .find(|(from, _)| from.contains(lower))
+        .map(|(_, to)| *to)?;
+    if c.is_uppercase() {
+        Some(Cow::Owned(folded.to_ascii_uppercase()))
+    } else {
+        Some(Cow::Borrowed(folded))
+    }
+}
+
+/// 固定不变的小指纹（FNV-1a），同一个主机名每次算出来都一样
+fn fnv1a(value: &str) -> u32 {
+    value.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_host_names() {
+        assert_eq!(sanitize("laptop"), "laptop");
+        assert_eq!(sanitize("ＤＥＳＫＴＯＰ－１２３"), "DESKTOP-123");
+        assert_eq!(sanitize("Zoë's MacBook Pro"), "Zoe-s-MacBook-P");
+        assert_eq!(sanitize("Straße"), "Strasse");
+        assert_eq!(sanitize("李雷-PC"), "PC");
+        assert_eq!(sanitize("very-long-hostname-here"), "very-long-hostn");
+        assert_eq!(sanitize("abcdefghijklmn-x"), "abcdefghijklmn");
+
+        let fallback = sanitize("张三的电脑");
+        assert!(fallback.starts_with("maid-") && fallback.len() <= NODE_NAME_MAX_LEN);
+        assert_eq!(fallback, sanitize("张三的电脑"));
+        assert_ne!(fallback, sanitize("李四的电脑"));
+    }
+}
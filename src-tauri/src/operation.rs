@@ -0,0 +1,272 @@
+//! 工作排班表（连接操作的状态机与排队闸门）
+//!
+//! 主人连点“建立通道 / 休息一下”时，启动、停止、暂停等操作可能互相插队，
+//! 而状态锁和进程句柄锁又是分开的，很容易出现“状态说在工作、其实已经下班”的尴尬。
+//! 这里规定：
+//! - 所有会改变工作状态的操作都要先在 `OperationGate` 前排队，一次只放行一个；
+//! - 放行前先对照当前状态检查这次操作是否合理，不合理就用 `OpError` 明确告诉主人原因；
+//! - 停止请求另外过一道 `StopThrottle`：同一种停止正在进行时不再排队，刚对同一只 edge 出过手的也先冷却一会儿，
+//!   免得主人等得着急连点时弹出一串 pkexec 授权框、或者刷出一堆 taskkill。
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 对同一只 edge 再用同一种手段停止前，至少要隔多久
+pub const STOP_COOLDOWN: Duration = Duration::from_secs(5);
+
+use crate::n2n_process::ConnectionStatus;
+
+/// 会改变工作状态的操作种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// 开始打扫（启动 edge）
+    Start,
+    /// 收拾工具（优雅停止）
+    Stop,
+    /// 掸子重击（强制停止）
+    ForceStop,
+    /// 歇一会儿（暂停）
+    Pause,
+    /// 继续打扫（恢复）
+    Resume,
+    /// 换总部（先像暂停一样停下，再朝新总部出门）
+    SwitchSupernode,
+    /// 重新出门（停下、等它走、按新指示出门，一次做完）
+    Restart,
+}
+
+impl Operation {
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::Start => "开始打扫",
+            Operation::Stop => "收拾工具",
+            Operation::ForceStop => "强制停止",
+            Operation::Pause => "歇一会儿",
+            Operation::Resume => "继续打扫",
+            Operation::SwitchSupernode => "换总部",
+            Operation::Restart => "重新出门",
+        }
+    }
+}
+
+/// 不合理的操作请求（带类型，方便调用方区分处理）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpError {
+    /// edge 已经在工作了，不能重复启动
+    AlreadyRunning,
+    /// edge 没在工作，没什么可停的
+    NotRunning,
+    /// 正在收拾工具中，重复的停止请求被拦下
+    AlreadyStopping,
+    /// 刚对这只 edge 出过手，还在冷却（还要等几秒）
+    StopCoolingDown { remaining_secs: u64 },
+    /// 当前状态下不允许该操作
+    InvalidTransition {
+        op: Operation,
+        from: &'static str,
+    },
+}
+
+impl OpError {
+    /// 稳定的机器可读编号（前端/脚本可以据此判断）
+    pub fn code(&self) -> &'static str {
+        match self {
+            OpError::AlreadyRunning => "op_already_running",
+            OpError::NotRunning => "op_not_running",
+            OpError::AlreadyStopping => "op_already_stopping",
+            OpError::StopCoolingDown { .. } => "op_stop_cooling_down",
+            OpError::InvalidTransition { .. } => "op_invalid_transition",
+        }
+    }
+}
+
+impl fmt::Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpError::AlreadyRunning => write!(f, "N2N 进程已在运行"),
+            OpError::NotRunning => write!(f, "N2N 进程未运行"),
+            OpError::AlreadyStopping => write!(f, "恩兔正在收拾工具呢，请稍等一下（等不及可以选择强制停止）"),
+            OpError::StopCoolingDown { remaining_secs } => {
+                write!(f, "恩兔刚请 edge 停下，先看看它走不走，{} 秒后再试吧", remaining_secs)
+            }
+            OpError::InvalidTransition { op, from } => {
+                write!(f, "恩兔现在是「{}」状态，没办法「{}」哦", from, op.label())
+            }
+        }
+    }
+}
+
+impl std::error::Error for OpError {}
+
+/// 状态的简短名字（用于错误提示）
+pub fn status_label(status: &ConnectionStatus) -> &'static str {
+    match status {
+        ConnectionStatus::Disconnected => "待命",
+        ConnectionStatus::Connecting => "铺设通道中",
+        ConnectionStatus::Disconnecting => "收拾工具中",
+        ConnectionStatus::Connected(_) => "已连接",
+        ConnectionStatus::Degraded(_) => "通道不太通",
+        ConnectionStatus::Paused => "歇一会儿",
+        ConnectionStatus::Reconnecting(_) => "等着重连",
+        ConnectionStatus::Error(..) => "出错",
+    }
+}
+
+/// 对照当前状态检查操作是否合理
+/// - `running`：是否还握着 edge 的进程句柄（状态和句柄偶尔会短暂不同步，以句柄为准）
+pub fn check_transition(op: Operation, status: &ConnectionStatus, running: bool) -> Result<(), OpError> {
+    let invalid = || OpError::InvalidTransition {
+        op,
+        from: status_label(status),
+    };
+    match op {
+        Operation::Start => {
+            if matches!(status, ConnectionStatus::Disconnecting) {
+                return Err(OpError::AlreadyStopping);
+            }
+            if running {
+                return Err(OpError::AlreadyRunning);
+            }
+            Ok(())
+        }
+        Operation::Stop => {
+            if matches!(status, ConnectionStatus::Disconnecting) && running {
+                return Err(OpError::AlreadyStopping);
+            }
+            if !running && !matches!(status, ConnectionStatus::Paused) {
+                return Err(OpError::NotRunning);
+            }
+            Ok(())
+        }
+        Operation::ForceStop => {
+            if !running {
+                return Err(OpError::NotRunning);
+            }
+            Ok(())
+        }
+        // 什么状态都能重新出门，只是正在收拾工具时别去抢
+        Operation::Restart => {
+            if matches!(status, ConnectionStatus::Disconnecting) && running {
+                return Err(OpError::AlreadyStopping);
+            }
+            Ok(())
+        }
+        Operation::Pause | Operation::SwitchSupernode => match status {
+            ConnectionStatus::Connecting | ConnectionStatus::Connected(_) if running => Ok(()),
+            _ => Err(invalid()),
+        },
+        Operation::Resume => match status {
+            ConnectionStatus::Paused if !running => Ok(()),
+            _ => Err(invalid()),
+        },
+    }
+}
+
+/// 停止请求的节流阀（温柔停止和强制停止分开算）
+#[derive(Debug, Default)]
+pub struct StopThrottle {
+    /// 正在进行中的停止
+    in_flight: Vec<Operation>,
+    /// 上一次真正出手：(手段, edge 的 PID, 时间)
+    last: Option<(Operation, u32, Instant)>,
+}
+
+impl StopThrottle {
+    /// 登记一次停止请求，拦下时说明原因
+    /// - `pid`：当前 edge 的 PID（暂停中没有 edge 时为 None，不用冷却）
+    pub fn admit(&mut self, op: Operation, pid: Option<u32>, now: Instant) -> Result<(), OpError> {
+        if self.in_flight.contains(&op) {
+            return Err(OpError::AlreadyStopping);
+        }
+        if let (Some(pid), Some((last_op, last_pid, at))) = (pid, self.last) {
+            let elapsed = now.saturating_duration_since(at);
+            if last_op == op && last_pid == pid && elapsed < STOP_COOLDOWN {
+                let remaining_secs = (STOP_COOLDOWN - elapsed).as_secs_f64().ceil() as u64;
+                return Err(OpError::StopCoolingDown { remaining_secs });
+            }
+        }
+        self.in_flight.push(op);
+        if let Some(pid) = pid {
+            self.last = Some((op, pid, now));
+        }
+        Ok(())
+    }
+
+    /// 这次停止做完了（不管成没成功）
+    pub fn finish(&mut self, op: Operation) {
+        self.in_flight.retain(|o| *o != op);
+    }
+}
+
+/// 排队闸门：一次只放一个会改变状态的操作进去
+#[derive(Default)]
+pub struct OperationGate {
+    lock: Mutex<()>,
+}
+
+impl OperationGate {
+    /// 排队等候入场（阻塞到前一个操作结束为止）
+    pub fn enter(&self) -> MutexGuard<'_, ()> {
+        // 前一个操作就算摔倒（panic）了，闸门本身也还能用
+        self.lock.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_transition_rules() {
+        let connected = ConnectionStatus::Connected(None);
+        assert_eq!(
+            check_transition(Operation::Start, &connected, true),
+            Err(OpError::AlreadyRunning)
+        );
+        assert_eq!(
+            check_transition(Operation::Start, &ConnectionStatus::Disconnecting, true),
+            Err(OpError::AlreadyStopping)
+        );
+        assert!(check_transition(Operation::Start, &ConnectionStatus::Error("x".into(), None), false).is_ok());
+        assert_eq!(
+            check_transition(Operation::Stop, &ConnectionStatus::Disconnected, false),
+            Err(OpError::NotRunning)
+        );
+        assert!(check_transition(Operation::Stop, &ConnectionStatus::Paused, false).is_ok());
+        assert!(check_transition(Operation::ForceStop, &ConnectionStatus::Disconnecting, true).is_ok());
+        assert!(check_transition(Operation::Pause, &connected, true).is_ok());
+        assert!(check_transition(Operation::SwitchSupernode, &connected, true).is_ok());
+        assert!(check_transition(Operation::SwitchSupernode, &ConnectionStatus::Paused, false).is_err());
+        assert!(check_transition(Operation::Restart, &connected, true).is_ok());
+        assert!(check_transition(Operation::Restart, &ConnectionStatus::Paused, false).is_ok());
+        assert_eq!(
+            check_transition(Operation::Restart, &ConnectionStatus::Disconnecting, true),
+            Err(OpError::AlreadyStopping)
+        );
+        assert_eq!(
+            check_transition(Operation::Resume, &connected, true).map_err(|e| e.code()),
+            Err("op_invalid_transition")
+        );
+    }
+
+    #[test]
+    fn test_stop_throttle_blocks_repeats_and_cools_down() {
+        let mut throttle = StopThrottle::default();
+        let t0 = Instant::now();
+        assert!(throttle.admit(Operation::Stop, Some(42), t0).is_ok());
+        // 温柔停止还在进行：重复的请求不排队，强制停止照样放行
+        assert_eq!(throttle.admit(Operation::Stop, Some(42), t0), Err(OpError::AlreadyStopping));
+        assert!(throttle.admit(Operation::ForceStop, Some(42), t0).is_ok());
+        throttle.finish(Operation::ForceStop);
+
+        // 刚对同一只 edge 强制出过手：冷却中
+        assert_eq!(
+            throttle.admit(Operation::ForceStop, Some(42), t0 + Duration::from_millis(1500)),
+            Err(OpError::StopCoolingDown { remaining_secs: 4 })
+        );
+        // 换了一只 edge、或者过了冷却时间就放行
+        assert!(throttle.admit(Operation::ForceStop, Some(43), t0).is_ok());
+        throttle.finish(Operation::ForceStop);
+        assert!(throttle.admit(Operation::ForceStop, Some(43), t0 + STOP_COOLDOWN).is_ok());
+    }
+}
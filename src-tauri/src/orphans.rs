@@ -0,0 +1,307 @@
+//! 没人照看的 edge（恩兔上次摔倒时留下的，或者主人在终端里自己启动的）
+//!
+//! 恩兔意外退出时 edge 不一定跟着走：它还占着虚拟网卡和管理口，下次建立通道时新的 edge 只会报
+//! “网卡被占”“端口被占”这种让人摸不着头脑的错。所以开门时先找一找机器上正在跑的 edge：
+//! - Linux 翻 `/proc/<pid>/cmdline`，macOS 问 `ps`，Windows 问 `Win32_Process`；
+//! - 从命令行里认出小组（`-c`）、总部（`-l`）、管理口（`-t`）和网卡（`-d`），和当前方案对得上的才能接手；
+//! - 交给主人决定：接手（不重启它，恩兔靠进程号和管理口照看），或者请它下班。
+//!
+//! 请它下班时先从它的管理口递 `stop` 纸条（edge 多半已经降权，这样不用麻烦小帮手代发信号），
+//! 管理口没人应才发信号。
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::config::N2NConfig;
+use crate::mgmt;
+use crate::n2n_process::DEFAULT_MGMT_PORT;
+use crate::session_state::process_alive;
+
+/// 一只没人照看的 edge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanEdge {
+    pub pid: u32,
+    /// 小组（`-c`；用配置文件启动时可能认不出来）
+    pub community: Option<String>,
+    /// 总部（`-l`）
+    pub supernode: Option<String>,
+    /// 管理口（`-t`，没写就是默认的 5644）
+    pub management_port: u16,
+    /// 虚拟网卡（`-d`）
+    pub tap_device: Option<String>,
+    /// 管理口令（`--management-password`，请它下班时用；不递给前端）
+    #[serde(skip)]
+    pub management_password: Option<String>,
+    /// 和当前方案对得上（同一个小组、同一个总部），可以接手
+    pub matches_config: bool,
+}
+
+impl OrphanEdge {
+    /// 会不会挡住当前方案的 edge（同一个方案，或者占着同一个管理口/网卡）
+    pub fn in_the_way(&self, config: &N2NConfig) -> bool {
+        let tap = config.tap_device.as_deref().filter(|d| !d.trim().is_empty());
+        self.matches_config
+            || self.management_port == config.management_port.unwrap_or(DEFAULT_MGMT_PORT)
+            || (tap.is_some() && self.tap_device.as_deref() == tap)
+    }
+
+    /// 请这只 edge 下班：先从它的管理口递 stop 纸条（没写口令就用默认的 n2n），没人应再走 `terminate`
+    pub fn stop(&self) -> Result<()> {
+        let password = self.management_password.as_deref().or(Some("n2n"));
+        match mgmt::stop(self.management_port, password) {
+            Ok(()) if wait_gone(self.pid, Duration::from_secs(3)) => {
+                log::info!("没人照看的 edge 从管理口请下班了，PID: {}", self.pid);
+                return Ok(());
+            }
+            Ok(()) => log::info!("edge（PID: {}）答应了下班却还没走，改用信号", self.pid),
+            Err(e) => log::info!("没能从管理口请 edge（PID: {}）下班（{:#}），改用信号", self.pid, e),
+        }
+        terminate(self.pid)
+    }
+}
+
+/// 从命令行认出一只 edge（第一个词是程序本身；不是 edge 时返回 None）
+pub fn parse(pid: u32, args: &[String], config: &N2NConfig) -> Option<OrphanEdge> {
+    let program = args.first()?;
+    let name = Path::new(program.rsplit(['/', '\\']).next()?).file_stem()?.to_str()?;
+    if !name.eq_ignore_ascii_case("edge") {
+        return None;
+    }
+
+    // 开关和值可以分开写（`-c home`），也可以粘在一起（`-chome`）
+    let value_of = |flag: &str| {
+        let mut rest = args[1..].iter();
+        while let Some(arg) = rest.next() {
+            if arg == flag {
+                return rest.next().cloned();
+            }
+            if let Some(glued) = arg.strip_prefix(flag).filter(|v| !v.is_empty() && !arg.starts_with("--")) {
+                return Some(glued.to_string());
+            }
+        }
+        None
+    };
+    let community = value_of("-c");
+    let supernode = value_of("-l");
+    let matches_config = community.as_deref() == Some(config.community.as_str())
+        && supernode.as_deref() == Some(config.supernode.as_str());
+    Some(OrphanEdge {
+        pid,
+        management_port: value_of("-t").and_then(|p| p.parse().ok()).unwrap_or(DEFAULT_MGMT_PORT),
+        tap_device: value_of("-d"),
+        management_password: value_of("--management-password"),
+        community,
+        supernode,
+        matches_config,
+    })
+}
+
+/// 找一找机器上正在跑的 edge（`own` 是恩兔自己带出门的，不算）
+pub fn scan(config: &N2NConfig, own: &[u32]) -> Vec<OrphanEdge> {
+    let mut found: Vec<OrphanEdge> = list_processes()
+        .into_iter()
+        .filter(|(pid, _)| !own.contains(pid) && *pid != std::process::id())
+        .filter_map(|(pid, args)| parse(pid, &args, config))
+        .collect();
+    found.sort_by_key(|edge| (!edge.matches_config, edge.pid));
+    found
+}
+
+/// Linux：每个进程的命令行都在 `/proc/<pid>/cmdline` 里（用 `\0` 隔开）
+#[cfg(target_os = "linux")]
+fn list_processes() -> Vec<(u32, Vec<String>)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let raw = std::fs::read(entry.path().join("cmdline")).ok()?;
+            let args: Vec<String> = raw
+                .split(|&b| b == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect();
+            (!args.is_empty()).then_some((pid, args))
+        })
+        .collect()
+}
+
+/// macOS 等：`ps` 列出的命令行（值里带空格时会被切开，认不出来也不要紧）
+#[cfg(all(unix, not(target_os = "linux")))]
+fn list_processes() -> Vec<(u32, Vec<String>)> {
+    let Ok(output) = std::process::Command::new("ps").args(["-axww", "-o", "pid=,args="]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim_start().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, command.split_whitespace().map(str::to_string).collect()))
+        })
+        .collect()
+}
+
+/// Windows：问 `Win32_Process` 要名叫 edge.exe 的进程（每行“进程号<Tab>命令行”）
+#[cfg(target_os = "windows")]
+fn list_processes() -> Vec<(u32, Vec<String>)> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let script = "Get-CimInstance Win32_Process -Filter \"Name='edge.exe'\" | \
+                  ForEach-Object { \"$($_.ProcessId)`t$($_.CommandLine)\" }";
+    let Ok(output) = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.split_once('\t')?;
+            let mut args = split_windows_command_line(command);
+            // 命令行里的程序名可能没带 .exe、也可能是相对路径：已经按进程名筛过了，统一写成 edge.exe
+            *args.first_mut()? = "edge.exe".to_string();
+            Some((pid.trim().parse().ok()?, args))
+        })
+        .collect()
+}
+
+/// 按 Windows 的规矩切命令行（只认双引号，反斜杠是路径的一部分）
+#[cfg(target_os = "windows")]
+fn split_windows_command_line(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_word = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// 请这只 edge 下班：先温柔地请，3 秒还不走就强制清理
+pub fn terminate(pid: u32) -> Result<()> {
+    log::info!("请没人照看的 edge 下班，PID: {}", pid);
+    signal(pid, false);
+    if wait_gone(pid, Duration::from_secs(3)) {
+        return Ok(());
+    }
+    log::warn!("edge（PID: {}）不肯走，恩兔只好强制清理", pid);
+    signal(pid, true);
+    if wait_gone(pid, Duration::from_secs(3)) {
+        Ok(())
+    } else {
+        anyhow::bail!("error_orphan_terminate_failed")
+    }
+}
+
+/// 等这个进程号消失（到点还在就返回 false）
+pub(crate) fn wait_gone(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while process_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    true
+}
+
+/// 发停止信号（edge 多半是 root 的，直接发不动时请常驻小帮手代发）
+#[cfg(unix)]
+fn signal(pid: u32, force: bool) {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    let sig = if force { Signal::SIGKILL } else { Signal::SIGINT };
+    if kill(Pid::from_raw(pid as i32), sig).is_ok() {
+        return;
+    }
+    #[cfg(target_os = "linux")]
+    if let Err(e) = crate::priv_helper::call(&crate::priv_helper::Request::Signal { pid, force, group: false }) {
+        log::warn!("小帮手代发信号失败：{:#}", e);
+    }
+}
+
+#[cfg(windows)]
+fn signal(pid: u32, force: bool) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let mut cmd = std::process::Command::new("taskkill");
+    if force {
+        cmd.args(["/T", "/F"]);
+    }
+    if let Err(e) = cmd.args(["/PID", &pid.to_string()]).creation_flags(CREATE_NO_WINDOW).status() {
+        log::warn!("Windows taskkill 执行失败：{}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_edge_command_line() {
+        let config = N2NConfig {
+            community: "home".to_string(),
+            supernode: "sn.example.com:7777".to_string(),
+            ..N2NConfig::default()
+        };
+        let args = |line: &str| line.split(' ').map(str::to_string).collect::<Vec<_>>();
+
+        let ours = parse(
+            42,
+            &args("/usr/sbin/edge -f -c home -l sn.example.com:7777 -t5645 -d n2n0 --management-password secret"),
+            &config,
+        )
+        .unwrap();
+        assert!(ours.matches_config);
+        assert!(ours.in_the_way(&config));
+        assert_eq!(ours.management_port, 5645);
+        assert_eq!(ours.tap_device.as_deref(), Some("n2n0"));
+        assert_eq!(ours.management_password.as_deref(), Some("secret"));
+        // 口令只留给自己用，不递给前端
+        assert!(!serde_json::to_string(&ours).unwrap().contains("secret"));
+
+        let other = parse(43, &args("edge.exe -c office -l sn.example.com:7777"), &config).unwrap();
+        assert!(!other.matches_config);
+        assert_eq!(other.management_password, None);
+        assert_eq!(other.management_port, DEFAULT_MGMT_PORT);
+        // 不是同一个方案，但占着默认的管理口
+        assert!(other.in_the_way(&config));
+        assert!(!other.in_the_way(&N2NConfig { management_port: Some(5650), ..config.clone() }));
+
+        // 用配置文件启动的认不出小组，不能接手（但还是列出来，可以请它下班）
+        let from_file = parse(44, &args("edge /etc/n2n/edge.conf"), &config).unwrap();
+        assert_eq!(from_file.community, None);
+        assert!(!from_file.matches_config);
+
+        assert_eq!(parse(45, &args("/usr/bin/supernode -p 7777"), &config), None);
+        assert_eq!(parse(46, &[], &config), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! 外部指示（用环境变量和启动参数临时改写指示簿里的字段）
+//!
+//! 无人值守的站点、信息亭这类部署，指示簿往往由外部统一管理，恩兔只负责照办。
+//! 这里允许在启动时覆盖任何一个 `N2NConfig` 字段：
+//! - 环境变量 `N2N_MAID_<字段名大写>`，例如 `N2N_MAID_SUPERNODE=vpn.example.com:7777`；
+//! - 启动参数 `--<字段名>=<值>` 或 `--<字段名> <值>`，下划线可以写成短横线，例如 `--static-ip 10.0.0.2/24`；
+//!   布尔字段只写 `--auto-connect` 就表示打开；
+//! - 启动参数比环境变量优先；值按字段本来的类型检查，不合格的直接丢弃并记一条警告。
+//!
+//! 覆盖只在读指示簿时叠加上去，保存时这些字段会保留指示簿里原来的值，不会被写进去。
+//! 后来的实例转交过来的参数不会再覆盖（只认第一次启动时的环境变量和参数）。
+use serde_json::{Map, Value};
+
+use crate::config::N2NConfig;
+
+/// 环境变量前缀
+pub const ENV_PREFIX: &str = "N2N_MAID_";
+
+/// 一组外部指示（字段名 → 值，已按字段类型检查过）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverrides {
+    values: Map<String, Value>,
+}
+
+impl ConfigOverrides {
+    /// 从环境变量和启动参数收集外部指示，顺便返回被丢弃的条目（交给日志）
+    pub fn collect(
+        vars: impl IntoIterator<Item = (String, String)>,
+        args: &[String],
+    ) -> (Self, Vec<String>) {
+        let fields = known_fields();
+        let mut overrides = Self::default();
+        let mut problems = Vec::new();
+
+        for (name, raw) in vars {
+            let Some(field) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let field = field.to_ascii_lowercase();
+            if let Err(problem) = overrides.set(&fields, &field, Some(&raw)) {
+                problems.push(format!("{}：{}", name, problem));
+            }
+        }
+
+        let mut iter = args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let Some(flag) = arg.strip_prefix("--") else {
+                continue;
+            };
+            let (field, inline) = match flag.split_once('=') {
+                Some((field, value)) => (field.replace('-', "_"), Some(value.to_string())),
+                None => (flag.replace('-', "_"), None),
+            };
+            // 不是字段名的参数（比如 --connect）留给别人处理
+            let Some(current) = fields.get(&field) else {
+                continue;
+            };
+            let raw = inline.or_else(|| {
+                let takes_value = |next: &&String| {
+                    !next.starts_with("--")
+                        && !next.starts_with("n2n-maid://")
+                        && (!current.is_boolean() || parse_bool(next).is_some())
+                };
+                iter.next_if(takes_value).cloned()
+            });
+            if let Err(problem) = overrides.set(&fields, &field, raw.as_deref()) {
+                problems.push(format!("{}：{}", arg, problem));
+            }
+        }
+
+        (overrides, problems)
+    }
+
+    /// 有没有任何外部指示
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// 被覆盖的字段名
+    pub fn fields(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+
+    /// 叠加到一份指示上
+    pub fn apply(&self, config: &N2NConfig) -> N2NConfig {
+        if self.is_empty() {
+            return config.clone();
+        }
+        let Value::Object(mut table) = to_value(config) else {
+            return config.clone();
+        };
+        table.extend(self.values.clone());
+        serde_json::from_value(Value::Object(table)).unwrap_or_else(|e| {
+            log::warn!("外部指示没能叠加上去：{}", e);
+            config.clone()
+        })
+    }
+
+    /// 保存前调用：被覆盖的字段换回指示簿里原来的值（`stored`），免得把外部指示写进指示簿
+    pub fn restore(&self, edited: &N2NConfig, stored: &N2NConfig) -> N2NConfig {
+        if self.is_empty() {
+            return edited.clone();
+        }
+        let (Value::Object(mut table), Value::Object(original)) = (to_value(edited), to_value(stored)) else {
+            return edited.clone();
+        };
+        for field in self.values.keys() {
+            match original.get(field) {
+                Some(value) => table.insert(field.clone(), value.clone()),
+                None => table.remove(field),
+            };
+        }
+        serde_json::from_value(Value::Object(table)).unwrap_or_else(|_| edited.clone())
+    }
+
+    /// 按字段本来的类型解析并记下一条（`raw` 为 None 表示只写了开关）
+    fn set(&mut self, fields: &Map<String, Value>, field: &str, raw: Option<&str>) -> Result<(), String> {
+        let current = fields.get(field).ok_or_else(|| "没有这个字段".to_string())?;
+        let value = match (current, raw) {
+            (Value::Bool(_), None) => Value::Bool(true),
+            (_, None) => return Err("少了值".to_string()),
+            (_, Some(raw)) => parse_value(current, raw),
+        };
+
+        // 借默认指示试一试，确认这个值能放进这个字段
+        let mut probe = fields.clone();
+        probe.insert(field.to_string(), value.clone());
+        serde_json::from_value::<N2NConfig>(Value::Object(probe))
+            .map_err(|e| format!("值不合格（{}）", e))?;
+        self.values.insert(field.to_string(), value);
+        Ok(())
+    }
+}
+
+/// 全部可以覆盖的字段（连同默认值，用来推断类型）
+fn known_fields() -> Map<String, Value> {
+    match to_value(&N2NConfig::default()) {
+        Value::Object(mut table) => {
+            // 保险箱取件单是恩兔自己管的，不让外面改
+            table.remove("encryption_key_ref");
+            table
+        }
+        _ => Map::new(),
+    }
+}
+
+fn to_value(config: &N2NConfig) -> Value {
+    serde_json::to_value(config).unwrap_or(Value::Null)
+}
+
+/// 照着字段现在的类型解析字符串
+fn parse_value(current: &Value, raw: &str) -> Value {
+    let raw = raw.trim();
+    match current {
+        Value::String(_) => Value::String(raw.to_string()),
+        Value::Bool(_) => parse_bool(raw).map(Value::Bool).unwrap_or_else(|| Value::String(raw.to_string())),
+        // 列表：可以写 JSON 数组，也可以用逗号分隔
+        Value::Array(_) if !raw.starts_with('[') => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        // 可选字段：空值表示清掉；数字先按数字试，不行再当字符串
+        Value::Null if raw.is_empty() => Value::Null,
+        Value::Null => match serde_json::from_str::<Value>(raw) {
+            Ok(Value::Number(n)) => Value::Number(n),
+            _ => Value::String(raw.to_string()),
+        },
+        _ => serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string())),
+    }
+}
+
+fn parse_bool(raw: &str) -> Option<bool> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_env_and_args_override_fields() {
+        let vars = vec![
+            ("N2N_MAID_SUPERNODE".to_string(), "env.example.com:7777".to_string()),
+            ("N2N_MAID_COMMUNITY".to_string(), "12345".to_string()),
+            ("N2N_MAID_MTU".to_string(), "1400".to_string()),
+            ("N2N_MAID_NO_SUCH_FIELD".to_string(), "x".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ];
+        let args = strings(&[
+            "--supernode=cli.example.com:7777",
+            "--static-ip",
+            "10.0.0.2/24",
+            "--important-peers",
+            "10.0.0.1, 10.0.0.3",
+            "--auto-connect",
+            "--connect",
+            "office",
+            "--reconnect-max-attempts=lots",
+        ]);
+        let (overrides, problems) = ConfigOverrides::collect(vars, &args);
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+
+        let stored = N2NConfig {
+            supernode: "file.example.com:7777".to_string(),
+            community: "home".to_string(),
+            ..Default::default()
+        };
+        let merged = overrides.apply(&stored);
+        assert_eq!(merged.supernode, "cli.example.com:7777");
+        assert_eq!(merged.community, "12345");
+        assert_eq!(merged.mtu, Some(1400));
+        assert_eq!(merged.static_ip.as_deref(), Some("10.0.0.2/24"));
+        assert_eq!(merged.important_peers, strings(&["10.0.0.1", "10.0.0.3"]));
+        assert!(merged.auto_connect);
+        assert_eq!(merged.reconnect_max_attempts, stored.reconnect_max_attempts);
+
+        // 保存时外部指示不会写进指示簿，主人改的其他字段照常保留
+        let edited = N2NConfig {
+            username: "laptop".to_string(),
+            ..merged
+        };
+        let saved = overrides.restore(&edited, &stored);
+        assert_eq!(saved.supernode, "file.example.com:7777");
+        assert_eq!(saved.static_ip, None);
+        assert!(!saved.auto_connect);
+        assert_eq!(saved.username, "laptop");
+    }
+}
@@ -0,0 +1,239 @@
+//! 一次照顾所有同伴（全体 ping、导出名单、叫醒全部、查全部名字）
+//!
+//! 同伴一多，面板要一个个 ping、一个个叫醒，前端得连发几十个命令，还得自己拼结果。
+//! 这里在后端把整批活儿分给几个小工人同时干（最多 `MAX_WORKERS` 个，慢的同伴不会拖住别人），
+//! 干完汇总成一份 `BulkReport`：每位同伴一条结果，外加成功、失败各多少，一共花了多久。
+//! - `ping_all`：按虚拟地址各 ping 一下；
+//! - `resolve_all`：按虚拟地址反查 DNS 名字（小组里有自己的 DNS 时才查得到）；
+//! - `wake_all`：方案里启用着的唤醒规则全部一起叫，等它们上线；
+//! - `export`：把点名册写成文件（`.csv` 写表格，其余写 JSON）。
+use anyhow::{Context, Result};
+use hickory_resolver::Resolver;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::n2n_process::{self, PeerNodeInfo};
+use crate::wake::{self, WakeRecord, WakeRule};
+
+/// 最多几个小工人同时干活
+const MAX_WORKERS: usize = 16;
+/// 每位同伴 ping 多久算没回应（毫秒）
+const PING_TIMEOUT_MS: u64 = 1000;
+
+/// 一批活儿的汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkReport<T> {
+    pub results: Vec<T>,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 整批一共花了多久（毫秒）
+    pub elapsed_ms: u64,
+}
+
+impl<T> BulkReport<T> {
+    fn new(results: Vec<T>, started: Instant, ok: impl Fn(&T) -> bool) -> Self {
+        let succeeded = results.iter().filter(|r| ok(r)).count();
+        BulkReport {
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// 一位同伴的 ping 结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingOutcome {
+    pub vpn_ip: String,
+    pub name: Option<String>,
+    /// 回应的延迟（毫秒，没回应为 None）
+    pub latency_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+/// 一位同伴的反查结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameOutcome {
+    pub vpn_ip: String,
+    /// edge 报来的昵称
+    pub name: Option<String>,
+    /// DNS 里登记的名字（查不到为 None）
+    pub hostname: Option<String>,
+    pub error: Option<String>,
+}
+
+/// 把 `items` 分给几个小工人同时干，结果按原来的顺序排好
+///
+/// 每个小工人开工时用 `init` 备一份自己的家伙（比如 DNS 查询器），之后每件活儿都用它。
+fn run_concurrently<T, R, S>(items: &[T], init: impl Fn() -> S + Sync, work: impl Fn(&mut S, &T) -> R + Sync) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new((0..items.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..items.len().min(MAX_WORKERS) {
+            scope.spawn(|| {
+                let mut tools = init();
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(index) else { break };
+                    let result = work(&mut tools, item);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+/// 点名册里有虚拟地址的同伴（地址, 昵称）
+fn targets(peers: &[PeerNodeInfo]) -> Vec<(String, Option<String>)> {
+    peers
+        .iter()
+        .filter(|p| p.is_local != Some(true))
+        .filter_map(|p| Some((p.vpn_ip.clone()?, p.name.clone())))
+        .collect()
+}
+
+/// 全体 ping 一下
+pub fn ping_all(peers: &[PeerNodeInfo]) -> BulkReport<PingOutcome> {
+    let started = Instant::now();
+    let results = run_concurrently(&targets(peers), || (), |_, (ip, name)| {
+        let (latency_ms, error) = match n2n_process::ping_once(ip, PING_TIMEOUT_MS) {
+            Ok(latency) => (latency, None),
+            Err(e) => (None, Some(format!("{:#}", e))),
+        };
+        PingOutcome { vpn_ip: ip.clone(), name: name.clone(), latency_ms, error }
+    });
+    BulkReport::new(results, started, |r| r.latency_ms.is_some())
+}
+
+/// 全体反查 DNS 名字
+pub fn resolve_all(peers: &[PeerNodeInfo]) -> BulkReport<NameOutcome> {
+    let started = Instant::now();
+    // 同步查询器里面有把锁，大家共用一个就成了排队，所以每个小工人各备一个
+    let results = run_concurrently(&targets(peers), Resolver::from_system_conf, |resolver, (ip, name)| {
+        let mut outcome = NameOutcome { vpn_ip: ip.clone(), name: name.clone(), hostname: None, error: None };
+        let lookup = match (resolver.as_ref(), ip.parse::<Ipv4Addr>()) {
+            (Ok(resolver), Ok(addr)) => resolver.reverse_lookup(IpAddr::V4(addr)).map_err(|e| e.to_string()),
+            (Err(e), _) => Err(format!("读取系统的 DNS 设置失败：{}", e)),
+            (_, Err(e)) => Err(e.to_string()),
+        };
+        match lookup {
+            Ok(names) => {
+                outcome.hostname = names.iter().next().map(|ptr| ptr.0.to_utf8().trim_end_matches('.').to_string())
+            }
+            Err(e) => outcome.error = Some(e),
+        }
+        outcome
+    });
+    BulkReport::new(results, started, |r| r.hostname.is_some())
+}
+
+/// 启用着的唤醒规则全部一起叫（会等它们上线，最多 `wake::VERIFY_TIMEOUT`）
+pub fn wake_all(rules: &[WakeRule], virtual_broadcast: Option<Ipv4Addr>) -> BulkReport<WakeRecord> {
+    let started = Instant::now();
+    let rules: Vec<WakeRule> = rules.iter().filter(|rule| rule.enabled).cloned().collect();
+    let results = run_concurrently(&rules, || (), |_, rule| wake::wake_and_verify(rule, virtual_broadcast, false));
+    BulkReport::new(results, started, |r| r.error.is_none() && r.online_after_secs.is_some())
+}
+
+/// 点名册写成 CSV（逗号、引号、换行都按 CSV 的规矩包起来）
+fn to_csv(peers: &[PeerNodeInfo]) -> String {
+    let field = |value: Option<String>| {
+        let value = value.unwrap_or_default();
+        if value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value
+        }
+    };
+    let mut out = String::from("name,vpn_ip,mac,public_addr,link,last_seen,latency_ms\n");
+    for p in peers {
+        let row = [
+            field(p.name.clone()),
+            field(p.vpn_ip.clone()),
+            field(p.mac.clone()),
+            field(p.public_addr.clone()),
+            field(serde_json::to_value(p.link).ok().and_then(|v| v.as_str().map(str::to_string))),
+            field(p.last_seen.map(|t| t.to_string())),
+            field(p.latency_ms.map(|ms| format!("{:.1}", ms))),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// 把点名册写进文件（`.csv` 写表格，其余写 JSON），返回写了几位
+pub fn export(peers: &[PeerNodeInfo], path: &Path) -> Result<usize> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let content = if is_csv {
+        to_csv(peers)
+    } else {
+        serde_json::to_string_pretty(peers).context("整理点名册失败")?
+    };
+    std::fs::write(path, content).with_context(|| format!("写入 {} 失败", path.display()))?;
+    Ok(peers.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::n2n_process::PeerLink;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_run_concurrently_and_export() {
+        // 顺序不乱，每件活儿只干一次
+        let items: Vec<u32> = (0..50).collect();
+        let doubled = run_concurrently(&items, || 0u32, |count, n| {
+            *count += 1;
+            n * 2
+        });
+        assert_eq!(doubled, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+
+        let peer = |name: &str, ip: Option<&str>, local: bool| PeerNodeInfo {
+            name: Some(name.to_string()),
+            vpn_addr: ip.map(|ip| format!("{}/24", ip)),
+            vpn_ip: ip.map(str::to_string),
+            public_addr: Some("203.0.113.7:50123".to_string()),
+            mac: Some("02:ab:00:10:fe:01".to_string()),
+            mode: Some("p2p".to_string()),
+            link: PeerLink::Direct,
+            last_seen: Some(1700000000),
+            is_local: Some(local),
+            latency_ms: Some(3.5),
+            last_ping: None,
+        };
+        let peers = vec![peer("nas, home", Some("10.0.0.5"), false), peer("me", Some("10.0.0.2"), true), peer("new", None, false)];
+        assert_eq!(targets(&peers), vec![("10.0.0.5".to_string(), Some("nas, home".to_string()))]);
+
+        let csv = to_csv(&peers[..1]);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("\"nas, home\",10.0.0.5,02:ab:00:10:fe:01,203.0.113.7:50123,direct,1700000000,3.5")
+        );
+
+        let dir = TempDir::new("peer-bulk");
+        assert_eq!(export(&peers, &dir.join("peers.json")).unwrap(), 3);
+        let back: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("peers.json")).unwrap()).unwrap();
+        assert_eq!(back[0]["vpnIp"], "10.0.0.5");
+
+        let empty = ping_all(&[]);
+        assert_eq!((empty.succeeded, empty.failed, empty.results.len()), (0, 0, 0));
+    }
+}
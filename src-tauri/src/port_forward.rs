@@ -0,0 +1,292 @@
+//! 传话筒（通道连上后，在本机和同伴之间转发 TCP 端口）
+//!
+//! 想连同伴电脑上的服务，或者把自己的服务开放给同伴，以前要么改防火墙，要么另外装一个转发工具。
+//! 现在在方案里写几条规则，恩兔连上通道时自动开始传话，断开时一起收起来：
+//! - `listen` 是本机监听的地址，`target` 是要转过去的地址，都写成 `host:port`；
+//! - 访问同伴：`127.0.0.1:8080 → 10.0.0.5:80`；
+//! - 开放给同伴：`10.0.0.2:8080 → 127.0.0.1:80`（监听自己的虚拟地址，别的网卡上看不到）。
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 连接目标时最多等多久
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// 监听小工人多久看一次有没有被叫停
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 一条转发规则（记在方案里）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortForward {
+    /// 备注（给主人看的）
+    #[serde(default)]
+    pub name: String,
+    /// 本机监听地址（`host:port`）
+    pub listen: String,
+    /// 转发目标（`host:port`）
+    pub target: String,
+    /// 是否启用
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// 一条规则现在的情况
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardStatus {
+    pub rule: PortForward,
+    /// 是否正在监听
+    pub listening: bool,
+    /// 监听失败（或最近一次连不上目标）的原因
+    pub error: Option<String>,
+    /// 正在传话的连接数
+    pub active: usize,
+    /// 本次一共传过多少个连接
+    pub total: u64,
+}
+
+/// 一条规则的计数板
+#[derive(Default)]
+struct RuleState {
+    listening: AtomicBool,
+    error: Mutex<Option<String>>,
+    active: AtomicUsize,
+    total: AtomicU64,
+}
+
+/// 正在工作的一批规则
+#[derive(Default)]
+struct Running {
+    rules: Vec<PortForward>,
+    states: Vec<Arc<RuleState>>,
+    stop: Arc<AtomicBool>,
+    /// 正在传话的连接（叫停时统统挂断）
+    streams: Arc<Mutex<HashMap<u64, TcpStream>>>,
+}
+
+/// 传话筒
+#[derive(Default)]
+pub struct PortForwarder {
+    running: Mutex<Running>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl PortForwarder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按通道状态调整：`Some(规则)` 表示通道连着，规则变了就重新开张；`None` 表示全部收起来
+    pub fn sync(&self, desired: Option<&[PortForward]>) {
+        let desired: Vec<PortForward> = desired
+            .unwrap_or_default()
+            .iter()
+            .filter(|rule| rule.enabled)
+            .cloned()
+            .collect();
+        let mut running = self.running.lock().unwrap();
+        if running.rules == desired {
+            return;
+        }
+        Self::stop_running(&running);
+        *running = Running::default();
+        if desired.is_empty() {
+            return;
+        }
+
+        log::info!("传话筒开张：{} 条规则", desired.len());
+        for rule in &desired {
+            let state = Arc::new(RuleState::default());
+            self.start_rule(rule, &state, &running);
+            running.states.push(state);
+        }
+        running.rules = desired;
+    }
+
+    /// 每条规则的情况
+    pub fn statuses(&self) -> Vec<ForwardStatus> {
+        let running = self.running.lock().unwrap();
+        running
+            .rules
+            .iter()
+            .zip(&running.states)
+            .map(|(rule, state)| ForwardStatus {
+                rule: rule.clone(),
+                listening: state.listening.load(Ordering::SeqCst),
+                error: state.error.lock().unwrap().clone(),
+                active: state.active.load(Ordering::SeqCst),
+                total: state.total.load(Ordering::SeqCst),
+            })
+            .collect()
+    }
+
+    fn stop_running(running: &Running) {
+        if running.rules.is_empty() {
+            return;
+        }
+        running.stop.store(true, Ordering::SeqCst);
+        for (_, stream) in running.streams.lock().unwrap().drain() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        log::info!("传话筒收起来了");
+    }
+
+    fn start_rule(&self, rule: &PortForward, state: &Arc<RuleState>, running: &Running) {
+        let listener = match TcpListener::bind(rule.listen.trim()).and_then(|l| {
+            l.set_nonblocking(true)?;
+            Ok(l)
+        }) {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::warn!("传话筒没能在 {} 上监听：{}", rule.listen, e);
+                *state.error.lock().unwrap() = Some(e.to_string());
+                return;
+            }
+        };
+        state.listening.store(true, Ordering::SeqCst);
+
+        let target = rule.target.trim().to_string();
+        let state = Arc::clone(state);
+        let stop = Arc::clone(&running.stop);
+        let streams = Arc::clone(&running.streams);
+        let next_id = Arc::clone(&self.next_id);
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((client, _)) => {
+                        let id = next_id.fetch_add(1, Ordering::SeqCst);
+                        let connection = Connection {
+                            id,
+                            target: target.clone(),
+                            state: Arc::clone(&state),
+                            streams: Arc::clone(&streams),
+                        };
+                        thread::spawn(move || connection.relay(client));
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+                    Err(e) => {
+                        log::debug!("传话筒接电话失败：{}", e);
+                        thread::sleep(ACCEPT_POLL);
+                    }
+                }
+            }
+            state.listening.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+impl Drop for PortForwarder {
+    fn drop(&mut self) {
+        Self::stop_running(&self.running.lock().unwrap());
+    }
+}
+
+/// 一通正在传的话
+struct Connection {
+    id: u64,
+    target: String,
+    state: Arc<RuleState>,
+    streams: Arc<Mutex<HashMap<u64, TcpStream>>>,
+}
+
+impl Connection {
+    fn relay(self, client: TcpStream) {
+        let upstream = match client.set_nonblocking(false).and_then(|_| connect(&self.target)) {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                log::debug!("传话筒连不上 {}：{}", self.target, e);
+                *self.state.error.lock().unwrap() = Some(format!("{}: {}", self.target, e));
+                return;
+            }
+        };
+        *self.state.error.lock().unwrap() = None;
+        self.state.total.fetch_add(1, Ordering::SeqCst);
+        self.state.active.fetch_add(1, Ordering::SeqCst);
+
+        if let Ok(clone) = client.try_clone() {
+            self.streams.lock().unwrap().insert(self.id, clone);
+        }
+        let pump = match (client.try_clone(), upstream.try_clone()) {
+            (Ok(mut from_client), Ok(mut to_upstream)) => Some(thread::spawn(move || {
+                let _ = io::copy(&mut from_client, &mut to_upstream);
+                let _ = to_upstream.shutdown(Shutdown::Write);
+            })),
+            _ => None,
+        };
+        let (mut from_upstream, mut to_client) = (upstream, client);
+        let _ = io::copy(&mut from_upstream, &mut to_client);
+        let _ = to_client.shutdown(Shutdown::Write);
+        if let Some(pump) = pump {
+            let _ = pump.join();
+        }
+
+        self.streams.lock().unwrap().remove(&self.id);
+        self.state.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 连接目标（域名的话挨个地址试）
+fn connect(target: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(ErrorKind::NotFound, "解析不到地址");
+    for addr in target.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_forwarder_relays_and_stops() {
+        // 目标：一个回声服务
+        let echo = TcpListener::bind("127.0.0.1:0").unwrap();
+        let target = echo.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for mut stream in echo.incoming().flatten() {
+                let mut buf = [0u8; 64];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let _ = stream.write_all(&buf[..n]);
+            }
+        });
+        let listen = {
+            let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+            probe.local_addr().unwrap().to_string()
+        };
+
+        let forwarder = PortForwarder::new();
+        let rules = vec![PortForward {
+            name: "echo".to_string(),
+            listen: listen.clone(),
+            target,
+            enabled: true,
+        }];
+        forwarder.sync(Some(&rules));
+        assert!(forwarder.statuses()[0].listening);
+
+        let mut client = TcpStream::connect(&listen).unwrap();
+        client.write_all(b"hello").unwrap();
+        let mut buf = [0u8; 5];
+        client.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+        assert_eq!(forwarder.statuses()[0].total, 1);
+
+        forwarder.sync(None);
+        assert!(forwarder.statuses().is_empty());
+        thread::sleep(ACCEPT_POLL * 3);
+        assert!(TcpStream::connect(&listen).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+//! 敲门（总部藏在端口敲门 / 单包授权后面时，出门前先按暗号敲一遍）
+//!
+//! 有的主人自己架的总部平时把端口关着，要先按顺序敲几下（port knocking），
+//! 或者先递一个带口令的 UDP 包（single packet authorization），防火墙才给来源地址开门。
+//! 方案里记一串敲门步骤（`port_knock`），每次 edge 出门前（包括自动重连）恩兔照着敲一遍：
+//! - UDP：往总部的这个端口发一个包，内容是 `payload`（`hex:` 开头按十六进制写，留空发空包）；
+//! - TCP：朝这个端口发起一次连接，只为了让对方看到这一下，连不上是正常的；
+//! - 每敲一下等 `delay_ms` 毫秒再敲下一下，最后一下之后也等一等再让 edge 出门。
+//!
+//! 敲门失败（比如总部的域名查不到）只记一笔，edge 照常出门，真进不去会由它自己报错。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+/// 每一下之后默认等多久（毫秒）
+const DEFAULT_DELAY_MS: u64 = 200;
+/// 每一下之后最多等多久（毫秒）
+const MAX_DELAY_MS: u64 = 10_000;
+/// UDP 包最多带多少字节
+const MAX_PAYLOAD_LEN: usize = 1024;
+/// TCP 敲门不等回应，发出去就走
+const TCP_KNOCK_TIMEOUT: Duration = Duration::from_millis(500);
+
+fn default_delay_ms() -> u64 {
+    DEFAULT_DELAY_MS
+}
+
+/// 用什么敲
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KnockProtocol {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+/// 敲门的一步（记在方案里）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnockStep {
+    #[serde(default)]
+    pub protocol: KnockProtocol,
+    /// 敲总部的哪个端口
+    pub port: u16,
+    /// UDP 包的内容（`hex:` 开头按十六进制写，其余按原文发；TCP 不带内容）
+    #[serde(default)]
+    pub payload: String,
+    /// 敲完这一下等多久（毫秒）
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+}
+
+/// 把 `payload` 换成要发的字节（十六进制写错时为 None）
+pub fn payload_bytes(payload: &str) -> Option<Vec<u8>> {
+    let Some(hex) = payload.strip_prefix("hex:") else {
+        return Some(payload.as_bytes().to_vec());
+    };
+    let hex: String = hex.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// 保存前检查敲门步骤（有问题时返回错误码）
+pub fn check(steps: &[KnockStep]) -> Option<&'static str> {
+    let bad = |step: &KnockStep| {
+        let payload_ok = match step.protocol {
+            KnockProtocol::Udp => payload_bytes(&step.payload).is_some_and(|bytes| bytes.len() <= MAX_PAYLOAD_LEN),
+            KnockProtocol::Tcp => step.payload.is_empty(),
+        };
+        !(step.port != 0 && payload_ok && step.delay_ms <= MAX_DELAY_MS)
+    };
+    steps.iter().any(bad).then_some("error_invalid_port_knock")
+}
+
+/// 照着步骤敲 `host` 的门（会等每一步的 `delay_ms`，别放在界面线程里）
+pub fn knock(host: &str, steps: &[KnockStep]) -> Result<()> {
+    let ip = (host, 0)
+        .to_socket_addrs()
+        .with_context(|| format!("查不到总部 {} 的地址", host))?
+        .next()
+        .with_context(|| format!("查不到总部 {} 的地址", host))?
+        .ip();
+
+    for step in steps {
+        let target = SocketAddr::new(ip, step.port);
+        match step.protocol {
+            KnockProtocol::Udp => {
+                let payload = payload_bytes(&step.payload).context("敲门内容的十六进制写错了")?;
+                let local: SocketAddr = if ip.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+                let socket = UdpSocket::bind(local).context("准备敲门的 UDP 口失败")?;
+                socket
+                    .send_to(&payload, target)
+                    .with_context(|| format!("往 {} 敲门失败", target))?;
+            }
+            KnockProtocol::Tcp => {
+                // 敲门端口本来就关着：被拒绝、超时都算敲过了
+                let _ = TcpStream::connect_timeout(&target, TCP_KNOCK_TIMEOUT);
+            }
+        }
+        log::debug!("敲了一下 {:?} {}", step.protocol, target);
+        thread::sleep(Duration::from_millis(step.delay_ms.min(MAX_DELAY_MS)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_and_knock() {
+        assert_eq!(payload_bytes("hex:de ad BE ef"), Some(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(payload_bytes("open sesame").as_deref(), Some(&b"open sesame"[..]));
+        assert_eq!(payload_bytes("hex:abc"), None);
+
+        let udp = |port: u16, payload: &str| KnockStep {
+            protocol: KnockProtocol::Udp,
+            port,
+            payload: payload.to_string(),
+            delay_ms: 0,
+        };
+        let tcp = KnockStep { protocol: KnockProtocol::Tcp, port: 1, payload: String::new(), delay_ms: 0 };
+        assert_eq!(check(&[udp(7000, "hex:01"), tcp.clone()]), None);
+        assert_eq!(check(&[udp(0, "")]), Some("error_invalid_port_knock"));
+        assert_eq!(check(&[KnockStep { payload: "x".to_string(), ..tcp.clone() }]), Some("error_invalid_port_knock"));
+
+        // 本机开一个 UDP 口当总部，看看暗号送到了没有
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let port = server.local_addr().unwrap().port();
+        knock("127.0.0.1", &[tcp, udp(port, "hex:c0ffee")]).unwrap();
+        let mut buf = [0u8; 16];
+        let (n, _) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], &[0xc0, 0xff, 0xee]);
+    }
+}
@@ -0,0 +1,197 @@
+//! 便携方案文件（把方案打包成文件发给同伴，对方一键导入）
+//!
+//! 给新同伴开通网络时，以前只能一项一项地把总部、暗号、密语抄过去。
+//! 现在可以把一份（或全部）方案导出成 TOML / JSON 文件（按扩展名决定），对方导入即可：
+//! - 导出时总部地址按地址簿展开、去掉地址簿引用和本机的 edge 路径，文件自己就能用；
+//! - 导入时既认打包好的方案文件，也认一份单独的 `N2NConfig`（名字取文件名）；
+//! - 每份方案导入前都要过一遍检查，有一份不合格就整份文件都不导入。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::config::N2NConfig;
+
+/// 当前的方案文件格式版本
+const BUNDLE_VERSION: u32 = 1;
+
+/// 方案文件里的一份方案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortableProfile {
+    pub name: String,
+    pub config: N2NConfig,
+    /// 换密语的邀请里约好的换用时间（Unix 秒；方案文件里不会有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub switch_at: Option<u64>,
+}
+
+/// 方案文件的完整内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    /// 格式版本（以后改格式时用来认旧文件）
+    #[serde(default = "default_version")]
+    pub version: u32,
+    pub profiles: Vec<PortableProfile>,
+}
+
+fn default_version() -> u32 {
+    BUNDLE_VERSION
+}
+
+/// 文件格式（按扩展名决定，.json 以外一律按 TOML）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Toml,
+    Json,
+}
+
+impl FileFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => FileFormat::Json,
+            _ => FileFormat::Toml,
+        }
+    }
+}
+
+impl ProfileBundle {
+    /// 把要导出的方案打包（去掉只在本机有意义的部分）
+    pub fn new(profiles: impl IntoIterator<Item = (String, N2NConfig)>) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            profiles: profiles
+                .into_iter()
+                .map(|(name, mut config)| {
+                    config.supernode_id = None;
+                    config.edge_path = None;
+                    config.encryption_key_ref = None;
+                    PortableProfile {
+                        name,
+                        config,
+                        switch_at: None,
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// 写成文件内容
+    pub fn encode(&self, format: FileFormat) -> Result<String> {
+        match format {
+            FileFormat::Toml => toml::to_string_pretty(self).context("序列化方案文件失败"),
+            FileFormat::Json => serde_json::to_string_pretty(self).context("序列化方案文件失败"),
+        }
+    }
+
+    /// 读出文件内容并逐份检查；单独的一份 N2NConfig 用 `fallback_name` 当名字
+    pub fn decode(content: &str, format: FileFormat, fallback_name: &str) -> Result<Self> {
+        let mut bundle = match format {
+            FileFormat::Toml => {
+                let value: toml::Value = toml::from_str(content).context("方案文件不是有效的 TOML")?;
+                if value.get("profiles").is_some() {
+                    value.try_into::<ProfileBundle>().context("解析方案文件失败")?
+                } else {
+                    let config: N2NConfig = value.try_into().context("解析方案文件失败")?;
+                    Self::single(fallback_name, config)
+                }
+            }
+            FileFormat::Json => {
+                let value: serde_json::Value = serde_json::from_str(content).context("方案文件不是有效的 JSON")?;
+                if value.get("profiles").is_some() {
+                    serde_json::from_value::<ProfileBundle>(value).context("解析方案文件失败")?
+                } else {
+                    let config: N2NConfig = serde_json::from_value(value).context("解析方案文件失败")?;
+                    Self::single(fallback_name, config)
+                }
+            }
+        };
+
+        if bundle.version > BUNDLE_VERSION {
+            anyhow::bail!("这份方案文件来自更新版本的恩兔（格式版本 {}），请先升级", bundle.version);
+        }
+        if bundle.profiles.is_empty() {
+            anyhow::bail!("方案文件里一份方案都没有");
+        }
+        for profile in &bundle.profiles {
+            validate(&profile.name, &profile.config)?;
+        }
+        // 保险箱取件单只在本机有效，别人文件里带来的一律不认
+        for profile in &mut bundle.profiles {
+            profile.config.encryption_key_ref = None;
+        }
+        Ok(bundle)
+    }
+
+    fn single(name: &str, config: N2NConfig) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            profiles: vec![PortableProfile {
+                name: name.to_string(),
+                config,
+                switch_at: None,
+            }],
+        }
+    }
+}
+
+/// 检查一份导入的方案能不能直接用
+fn validate(name: &str, config: &N2NConfig) -> Result<()> {
+    if name.trim().is_empty() {
+        anyhow::bail!("方案文件里有一份方案没有名字");
+    }
+    // 按域名找总部的方案：总部地址和暗号到建立通道时才从 DNS 里查出来
+    let by_dns = config.supernode_dns.as_deref().is_some_and(|d| !d.trim().is_empty());
+    let (host, port) = config.supernode.rsplit_once(':').unwrap_or(("", ""));
+    if !by_dns && (host.is_empty() || port.parse::<u16>().is_err()) {
+        anyhow::bail!("方案“{}”的总部地址“{}”不对，必须是 host:port 的格式", name, config.supernode);
+    }
+    if !by_dns && config.community.trim().is_empty() {
+        anyhow::bail!("方案“{}”没有填写工作暗号", name);
+    }
+    match config.ip_mode.as_str() {
+        "dhcp" => {}
+        "static" if config.static_ip.as_deref().is_some_and(|ip| !ip.trim().is_empty()) => {}
+        "static" => anyhow::bail!("方案“{}”是手动指定地址，但没有填写地址", name),
+        other => anyhow::bail!("方案“{}”的地址分配方式“{}”不认识", name, other),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip_and_validation() {
+        let config = N2NConfig {
+            supernode: "n2n.example.com:7777".to_string(),
+            community: "home".to_string(),
+            encryption_key: "secret".to_string(),
+            supernode_id: Some("sn-1".to_string()),
+            edge_path: Some("/opt/n2n/edge".to_string()),
+            ..Default::default()
+        };
+        let bundle = ProfileBundle::new([("Home".to_string(), config)]);
+
+        for format in [FileFormat::Toml, FileFormat::Json] {
+            let content = bundle.encode(format).unwrap();
+            let decoded = ProfileBundle::decode(&content, format, "ignored").unwrap();
+            assert_eq!(decoded.profiles.len(), 1);
+            assert_eq!(decoded.profiles[0].name, "Home");
+            assert_eq!(decoded.profiles[0].config.encryption_key, "secret");
+            assert_eq!(decoded.profiles[0].config.supernode_id, None);
+            assert_eq!(decoded.profiles[0].config.edge_path, None);
+        }
+
+        // 单独的一份 N2NConfig 也认，名字取文件名
+        let single = r#"{"supernode":"10.1.1.1:7654","community":"lab","username":"","encryption_key":"","ip_mode":"dhcp"}"#;
+        let decoded = ProfileBundle::decode(single, FileFormat::Json, "lab-config").unwrap();
+        assert_eq!(decoded.profiles[0].name, "lab-config");
+
+        // 总部没写端口：整份拒收
+        let bad = "supernode = \"n2n.example.com\"\ncommunity = \"c\"\nusername = \"\"\nencryption_key = \"\"\nip_mode = \"dhcp\"\n";
+        assert!(ProfileBundle::decode(bad, FileFormat::Toml, "bad").is_err());
+
+        assert_eq!(FileFormat::from_path(Path::new("team.JSON")), FileFormat::Json);
+        assert_eq!(FileFormat::from_path(Path::new("team.toml")), FileFormat::Toml);
+    }
+}
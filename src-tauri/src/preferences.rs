@@ -0,0 +1,125 @@
+//! 恩兔自己的偏好（配色、开机自启、提醒气泡、关窗口时躲进托盘、同步文件夹）
+//!
+//! 这些以前和工作指示挤在同一个 `N2NConfig` 里：配色跟着方案走，保存网络设置时会把刚换的配色盖回去，
+//! 换配色时又会把表单里还没保存的网络设置一起写进去。现在单独记在配置目录的 `preferences.toml`：
+//! - 和方案无关，换方案、导入方案都不会动它；
+//! - 有自己的读写命令（`get_preferences` / `save_preferences`），和保存网络设置互不干扰；
+//! - 第一次读的时候，从指示簿当前方案里把旧的配色搬过来（见 `N2NConfig::theme`）。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 工作台的配色偏好
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemePreference {
+    /// 跟随系统（默认，系统切换时实时跟随）
+    #[default]
+    System,
+    /// 明亮
+    Light,
+    /// 暗色
+    Dark,
+}
+
+/// 恩兔自己的偏好
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppPreferences {
+    /// 工作台配色（跟随系统 / 明亮 / 暗色）
+    pub theme: ThemePreference,
+    /// 登录系统后自动打开恩兔
+    pub autostart: bool,
+    /// 弹出恩兔的提醒气泡（关掉后只弹警告和错误）
+    pub notifications: bool,
+    /// 点窗口的关闭按钮时躲进托盘，而不是收工退出
+    pub close_to_tray: bool,
+    /// 同步文件夹（Dropbox/Syncthing 之类，几台机器共用一套方案；None 表示不同步）
+    pub sync_dir: Option<String>,
+}
+
+impl Default for AppPreferences {
+    fn default() -> Self {
+        Self {
+            theme: ThemePreference::System,
+            autostart: false,
+            notifications: true,
+            close_to_tray: false,
+            sync_dir: None,
+        }
+    }
+}
+
+/// 偏好本
+pub struct PreferencesStore {
+    path: PathBuf,
+}
+
+impl PreferencesStore {
+    /// 翻开偏好本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("preferences.toml")))
+    }
+
+    /// 指定偏好本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 读出偏好；还没有偏好本时用 `legacy` 垫底（旧版记在方案里的配色），并马上记下来
+    pub fn load_or_migrate(&self, legacy: impl FnOnce() -> AppPreferences) -> Result<AppPreferences> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => toml::from_str(&content).context("偏好本已经损坏"),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let preferences = legacy();
+                self.save(&preferences)?;
+                Ok(preferences)
+            }
+            Err(e) => Err(e).context("读取偏好本失败"),
+        }
+    }
+
+    /// 记下偏好
+    pub fn save(&self, preferences: &AppPreferences) -> Result<()> {
+        let content = toml::to_string_pretty(preferences).context("序列化偏好失败")?;
+        fs::write(&self.path, content).context("写入偏好本失败")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_preferences_migrate_once_and_fill_defaults() {
+        let dir = TempDir::new("prefs");
+        let store = PreferencesStore::at(dir.join("preferences.toml"));
+
+        let legacy = || AppPreferences {
+            theme: ThemePreference::Dark,
+            ..Default::default()
+        };
+        let migrated = store.load_or_migrate(legacy).unwrap();
+        assert_eq!(migrated.theme, ThemePreference::Dark);
+        assert!(migrated.notifications);
+
+        // 已经有偏好本了就不再看旧配色
+        let saved = AppPreferences {
+            close_to_tray: true,
+            ..migrated
+        };
+        store.save(&saved).unwrap();
+        assert_eq!(store.load_or_migrate(AppPreferences::default).unwrap(), saved);
+
+        // 缺的字段用默认值
+        fs::write(dir.join("preferences.toml"), "theme = \"light\"\n").unwrap();
+        let partial = store.load_or_migrate(AppPreferences::default).unwrap();
+        assert_eq!((partial.theme, partial.notifications, partial.autostart), (ThemePreference::Light, true, false));
+    }
+}
@@ -0,0 +1,136 @@
+//! 出门前的快速检查（建立通道前先看看路通不通）
+//!
+//! 有些问题 edge 自己要转好几圈超时才会放弃，主人只能干等着“连接中”。
+//! 恩兔在真正启动 edge 之前花一两秒先看一眼，发现下面这些情况就直接带着编号回来：
+//! - `error_tap_busy`：同名虚拟网卡已经被别的程序（别的 VPN / 另一个 edge）占着；
+//! - `error_no_network`：没有任何可用的网络（飞行模式、网线拔了）；
+//! - `error_clock_skew`：开了包头加密（-H）但本机时间和标准时间差太多，总部会拒收。
+//!
+//! 检查本身出问题（比如对时服务器连不上）一律放行，交给 edge 自己去试。
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::N2NConfig;
+use crate::local_port;
+use crate::n2n_process;
+
+/// 包头加密能容忍的时间偏差（n2n 按时间戳拒收过期/超前的数据包）
+const MAX_CLOCK_SKEW_SECS: f64 = 16.0;
+/// 对时服务器
+const NTP_SERVER: &str = "pool.ntp.org:123";
+const NTP_TIMEOUT: Duration = Duration::from_millis(1500);
+/// NTP 纪元（1900 年）到 Unix 纪元（1970 年）的秒数
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// 出门前检查一遍，有问题就返回对应的 error_ 编号
+pub fn check(config: &N2NConfig) -> Result<(), &'static str> {
+    if tap_busy(config) {
+        return Err("error_tap_busy");
+    }
+    if config.local_port.is_some_and(|port| !local_port::is_free(port)) {
+        return Err("error_local_port_in_use");
+    }
+    if !network_available(n2n_process::supernode_host(&config.supernode), &config.supernode) {
+        return Err("error_no_network");
+    }
+    if config.header_encryption {
+        if let Some(skew) = clock_skew_secs() {
+            if skew.abs() > MAX_CLOCK_SKEW_SECS {
+                log::warn!("本机时间和标准时间差了 {:.1} 秒，开启包头加密时总部会拒收", skew);
+                return Err("error_clock_skew");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 同名 TAP 网卡是不是已经被别人接上了（tun/tap 有进程占用时 carrier 为 1）
+#[cfg(target_os = "linux")]
+fn tap_busy(config: &N2NConfig) -> bool {
+    let name = config.tap_device.as_deref().unwrap_or("edge0");
+    std::fs::read_to_string(format!("/sys/class/net/{}/carrier", name))
+        .map(|carrier| carrier.trim() == "1")
+        .unwrap_or(false)
+}
+
+/// 其他平台的网卡占用由 edge 自己报告
+#[cfg(not(target_os = "linux"))]
+fn tap_busy(_config: &N2NConfig) -> bool {
+    false
+}
+
+/// 有没有路能通到总部（UDP connect 只查路由表，不会真的发包）
+fn network_available(host: &str, address: &str) -> bool {
+    // 总部直接写的是 IP：看有没有到它的路由就够了（端口不影响选路）
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return route_exists(SocketAddr::new(ip, 7654));
+    }
+    // 写的是域名：先看有没有通往公网的路由，有就放行（解析失败交给 edge 报告）
+    let public_probes = [
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)), 53),
+        SocketAddr::new(IpAddr::V6(Ipv6Addr::new(0x2606, 0x4700, 0x4700, 0, 0, 0, 0, 0x1111)), 53),
+    ];
+    if public_probes.into_iter().any(route_exists) {
+        return true;
+    }
+    // 没有公网路由：总部可能在局域网里，解析出来看看能不能到
+    address
+        .to_socket_addrs()
+        .map(|mut addrs| addrs.any(route_exists))
+        .unwrap_or(false)
+}
+
+fn route_exists(addr: SocketAddr) -> bool {
+    let bind: SocketAddr = match addr {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    UdpSocket::bind(bind)
+        .and_then(|socket| socket.connect(addr))
+        .is_ok()
+}
+
+/// 问一下对时服务器：本机时间比标准时间快多少秒（问不到就返回 None）
+fn clock_skew_secs() -> Option<f64> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.set_read_timeout(Some(NTP_TIMEOUT)).ok()?;
+    socket.connect(NTP_SERVER).ok()?;
+
+    // SNTP 请求：LI=0, VN=3, Mode=3（客户端）
+    let mut request = [0u8; 48];
+    request[0] = 0x1b;
+    socket.send(&request).ok()?;
+
+    let mut reply = [0u8; 48];
+    let len = socket.recv(&mut reply).ok()?;
+    let server = parse_sntp_transmit_time(&reply[..len])?;
+    let local = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs_f64();
+    Some(local - server)
+}
+
+/// 从 SNTP 回复里取出服务器的发送时间（Unix 秒）
+fn parse_sntp_transmit_time(reply: &[u8]) -> Option<f64> {
+    if reply.len() < 48 {
+        return None;
+    }
+    let secs = u32::from_be_bytes(reply[40..44].try_into().ok()?) as u64;
+    let frac = u32::from_be_bytes(reply[44..48].try_into().ok()?) as f64 / (u32::MAX as f64 + 1.0);
+    let unix = secs.checked_sub(NTP_UNIX_OFFSET)?;
+    Some(unix as f64 + frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sntp_parsing_and_route_probe() {
+        let mut reply = [0u8; 48];
+        reply[40..44].copy_from_slice(&((NTP_UNIX_OFFSET + 1_700_000_000) as u32).to_be_bytes());
+        reply[44..48].copy_from_slice(&(1u32 << 31).to_be_bytes());
+        assert_eq!(parse_sntp_transmit_time(&reply), Some(1_700_000_000.5));
+        assert_eq!(parse_sntp_transmit_time(&reply[..20]), None);
+
+        assert!(route_exists(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 7654)));
+    }
+}
@@ -0,0 +1,530 @@
+//! Linux 上的常驻小帮手（以 root 身份守在一个 Unix 门铃后面，替恩兔做需要管理员权限的小事）
+//!
+//! edge 建好网卡后会降权，恩兔直接发不动信号，以前每次断开都要 `pkexec kill` 弹一次授权框；
+//! 清网卡残留、改 MTU、打开 IP 转发也是各弹各的。现在第一次需要时用 pkexec 请出小帮手
+//! （就是恩兔自己，带着 `--privileged-helper` 启动），之后都按门铃递纸条，不再打扰主人：
+//! - 门铃在 `/run/n2n-maid/helper-<uid>.sock`，只有请它出来的那位主人（和 root）按得响：
+//!   门铃文件只给主人读写，每次有人按门铃还会核对对方的 uid；
+//! - 一行一个 JSON 请求、一行一个回答；
+//! - 只替恩兔自己带出门的 edge 办事：每次放 edge 出门后恩兔都来报个到（进程号和网卡名），小帮手当场核对
+//!   ——这只 edge 是不是按门铃的那位恩兔亲手带出门的、命令行里的网卡名对不对得上——再记进名册；
+//!   同一位主人的其他程序也按得响门铃，但冒充不了恩兔，就动不了别人的 edge 和网卡；
+//! - 只做白名单里的事：给名册里的 edge 发停止信号、清名册里虚拟网卡（只认 tun/tap）上的地址和路由、改它的 MTU，
+//!   名册里有 edge 在跑时打开 IP 转发（关的时候只关自己打开的）；
+//! - 网卡和路由本来就是 edge 带着 capabilities 自己建、自己加的（`-d`、`-n`），小帮手不插手；
+//!   给 edge 授予 capabilities 也不归它管（那等于能给任何程序提权），还是第一次用某个 edge 时 `pkexec setcap` 一次；
+//! - 小帮手一直守到关机；恩兔升级后版本对不上，就请旧的下班再换新的。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 带着这个参数启动就是小帮手
+pub const HELPER_ARG: &str = "--privileged-helper";
+/// 门铃所在的目录
+const RUN_DIR: &str = "/run/n2n-maid";
+/// 请小帮手出来后最多等多久（包括主人在授权框里输密码的时间）
+const LAUNCH_WAIT: Duration = Duration::from_secs(120);
+/// 一次问答最多等多久
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// 小帮手的版本（和恩兔一致才用）
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 同一时间只请一次小帮手（免得几件事一起来时弹出好几个授权框）
+static LAUNCH: Mutex<()> = Mutex::new(());
+/// 恩兔带出门、还没走的 edge（小帮手后来才请出来、或者换了新的，都要重新报到）
+static CLAIMS: Mutex<Vec<(u32, String)>> = Mutex::new(Vec::new());
+/// edge 在 Linux 上默认的网卡名（没有 `-d` 时）
+const DEFAULT_DEV: &str = "edge0";
+
+/// 递给小帮手的纸条
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    /// 在吗（回答里带版本号）
+    Ping,
+    /// 请（旧版本的）小帮手下班
+    Quit,
+    /// 恩兔刚带出门的 edge 来报到（进程号和它的网卡名）
+    Claim { pid: u32, dev: String },
+    /// 给 edge 发停止信号（`force` 为 true 时是 SIGKILL，否则 SIGINT；`group` 为 true 时连它拉起的程序整组一起）
+    Signal {
+        pid: u32,
+        force: bool,
+        #[serde(default)]
+        group: bool,
+    },
+    /// 清空网卡上的地址或路由
+    Flush { dev: String, what: FlushWhat },
+    /// 修改网卡的 MTU
+    SetMtu { dev: String, mtu: u16 },
+    /// 打开或关闭 IPv4 转发
+    IpForward { enabled: bool },
+}
+
+/// 清空网卡上的什么
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushWhat {
+    Addr,
+    Route,
+}
+
+impl FlushWhat {
+    fn as_str(self) -> &'static str {
+        match self {
+            FlushWhat::Addr => "addr",
+            FlushWhat::Route => "route",
+        }
+    }
+}
+
+/// 小帮手的回答
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Reply {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+fn socket_path(uid: u32) -> PathBuf {
+    PathBuf::from(RUN_DIR).join(format!("helper-{}.sock", uid))
+}
+
+/// 网卡名核对：1~15 个字符，只能是字母、数字和 `-_.`（和内核的规矩一致，顺便挡住奇怪的参数）
+fn valid_dev(dev: &str) -> bool {
+    (1..=15).contains(&dev.len())
+        && !dev.starts_with('-')
+        && dev.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// 这个进程是不是 edge（小帮手只给 edge 发信号）
+fn is_edge(pid: u32) -> bool {
+    pid > 1
+        && std::fs::read_to_string(format!("/proc/{}/comm", pid)).is_ok_and(|comm| comm.trim() == "edge")
+}
+
+/// `/proc/<pid>/stat` 里的父进程号和启动时刻（启动时刻用来认出进程号被别人重用了）
+fn parse_stat(stat: &str) -> Option<(u32, u64)> {
+    // 程序名可能带空格和括号：从最后一个 `)` 后面数
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+    let ppid = fields.nth(1)?.parse().ok()?;
+    let started = fields.nth(17)?.parse().ok()?;
+    Some((ppid, started))
+}
+
+fn stat_of(pid: u32) -> Option<(u32, u64)> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+/// edge 命令行里的网卡名（`-d` 后面那个，没写就是默认的 edge0）
+fn dev_in_args<'a>(args: impl IntoIterator<Item = &'a str>) -> String {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-d" {
+            return args.next().unwrap_or_default().to_string();
+        }
+        if let Some(dev) = arg.strip_prefix("-d").filter(|dev| !dev.is_empty()) {
+            return dev.to_string();
+        }
+    }
+    DEFAULT_DEV.to_string()
+}
+
+/// 这块网卡是不是 tun/tap（只有虚拟网卡才有 tun_flags；eth0、wlan0 这些小帮手碰都不碰）
+fn is_tun(dev: &str) -> bool {
+    Path::new("/sys/class/net").join(dev).join("tun_flags").exists()
+}
+
+// ---------------------------------------------------------------------------
+// 恩兔这边：按门铃
+// ---------------------------------------------------------------------------
+
+/// 请小帮手做一件事（还没请出来、或者版本对不上，就先用 pkexec 请一次）
+pub fn call(request: &Request) -> Result<()> {
+    let uid = nix::unistd::getuid().as_raw();
+    ensure(uid)?;
+    let reply = send(uid, request)?;
+    if reply.ok {
+        Ok(())
+    } else {
+        anyhow::bail!("小帮手没办成：{}", reply.error.unwrap_or_default())
+    }
+}
+
+/// 刚带出门的 edge 报个到：小帮手已经上岗就马上登记，还没请出来的话等请它出来时一起登记（不会为此去请它）
+pub fn claim(pid: u32, dev: &str) {
+    {
+        let mut claims = CLAIMS.lock().unwrap_or_else(|e| e.into_inner());
+        claims.retain(|(pid, _)| crate::session_state::process_alive(*pid));
+        claims.push((pid, dev.to_string()));
+    }
+    let uid = nix::unistd::getuid().as_raw();
+    if let Ok(reply) = send(uid, &Request::Claim { pid, dev: dev.to_string() }) {
+        if !reply.ok {
+            log::warn!("小帮手没收下 edge（PID: {}）的报到：{}", pid, reply.error.unwrap_or_default());
+        }
+    }
+}
+
+/// 把还没走的 edge 都重新报一遍到（小帮手刚请出来、或者换了新的）
+fn replay_claims(uid: u32) {
+    let claims = CLAIMS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    for (pid, dev) in claims {
+        if !crate::session_state::process_alive(pid) {
+            continue;
+        }
+        match send(uid, &Request::Claim { pid, dev }) {
+            Ok(reply) if !reply.ok => {
+                log::warn!("小帮手没收下 edge（PID: {}）的报到：{}", pid, reply.error.unwrap_or_default())
+            }
+            Err(e) => log::warn!("给 edge（PID: {}）报到失败：{:#}", pid, e),
+            Ok(_) => {}
+        }
+    }
+}
+
+/// 确认门铃那头是同一个版本的小帮手，不在就请出来（请出来以后把恩兔的 edge 报到一遍）
+fn ensure(uid: u32) -> Result<()> {
+    let _launch = LAUNCH.lock().unwrap_or_else(|e| e.into_inner());
+    match send(uid, &Request::Ping) {
+        Ok(reply) if reply.version.as_deref() == Some(VERSION) => return Ok(()),
+        Ok(reply) => {
+            log::info!("小帮手的版本（{:?}）和恩兔对不上，请它下班换个新的", reply.version);
+            let _ = send(uid, &Request::Quit);
+            // 等旧的把门铃拆走，免得它走时顺手拆了新装的
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while socket_path(uid).exists() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+        Err(_) => {}
+    }
+    launch(uid)?;
+    replay_claims(uid);
+    Ok(())
+}
+
+/// 用 pkexec 请出小帮手，等它按得响门铃
+fn launch(uid: u32) -> Result<()> {
+    let exe = std::env::current_exe().context("找不到恩兔自己的位置")?;
+    let pkexec = which::which("pkexec").context("未找到 pkexec，请安装 polkit（KDE 可用 polkit-kde-agent）")?;
+    log::info!("请出常驻小帮手（pkexec），之后断开、清理网卡都不用再授权");
+    // pkexec 会变身成小帮手本身，一直跑到关机；恩兔这边只在后台等着收尸
+    let mut child = Command::new(pkexec)
+        .arg(exe)
+        .arg(HELPER_ARG)
+        .arg(uid.to_string())
+        .stdin(Stdio::null())
+        .spawn()
+        .context("执行 pkexec 失败")?;
+
+    let deadline = Instant::now() + LAUNCH_WAIT;
+    loop {
+        if let Some(status) = child.try_wait().context("等小帮手失败")? {
+            anyhow::bail!("小帮手没请出来（主人取消了授权？退出码：{:?}）", status.code());
+        }
+        if send(uid, &Request::Ping).is_ok_and(|reply| reply.version.as_deref() == Some(VERSION)) {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+            anyhow::bail!("等了 {} 秒小帮手还没上岗", LAUNCH_WAIT.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// 按一次门铃：递一张纸条，等一个回答
+fn send(uid: u32, request: &Request) -> Result<Reply> {
+    let mut stream = UnixStream::connect(socket_path(uid)).context("小帮手不在")?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).context("把纸条递给小帮手失败")?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer).context("等小帮手回答失败")?;
+    serde_json::from_str(&answer).context("看不懂小帮手的回答")
+}
+
+// ---------------------------------------------------------------------------
+// 小帮手这边：守门铃（以 root 身份运行）
+// ---------------------------------------------------------------------------
+
+/// 小帮手上岗，返回进程退出码（`args` 是 `--privileged-helper` 后面的参数）
+pub fn serve(args: &[String]) -> i32 {
+    match serve_inner(args) {
+        Ok(()) => 0,
+        Err(e) => {
+            // 小帮手没有日志系统，错误交给 pkexec 转给恩兔的标准错误
+            eprintln!("n2n-maid 小帮手：{:#}", e);
+            1
+        }
+    }
+}
+
+fn serve_inner(args: &[String]) -> Result<()> {
+    if !nix::unistd::Uid::effective().is_root() {
+        anyhow::bail!("小帮手要以 root 身份运行（请通过 pkexec 启动）");
+    }
+    // 主人是谁以 pkexec 记下的为准，别人冒充不了；直接以 root 启动时才看参数
+    let owner: u32 = std::env::var("PKEXEC_UID")
+        .ok()
+        .or_else(|| args.first().cloned())
+        .context("不知道替哪位主人干活")?
+        .parse()
+        .context("主人的 uid 写得不对")?;
+    // 自立门户：恩兔那边的终端收到 Ctrl+C 也不连带小帮手
+    let _ = nix::unistd::setsid();
+
+    std::fs::create_dir_all(RUN_DIR).with_context(|| format!("创建 {} 失败", RUN_DIR))?;
+    std::fs::set_permissions(RUN_DIR, std::fs::Permissions::from_mode(0o755))?;
+    let path = socket_path(owner);
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).with_context(|| format!("装门铃 {} 失败", path.display()))?;
+    std::os::unix::fs::chown(&path, Some(owner), None)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    let mut book = Registry::default();
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let Some(peer) = peer_pid(&stream, owner) else {
+            continue;
+        };
+        if !answer(stream, peer, &mut book) {
+            break;
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+/// 按门铃的是不是主人（或者 root）：是的话返回对方的进程号
+fn peer_pid(stream: &UnixStream, owner: u32) -> Option<u32> {
+    use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+    let cred = getsockopt(stream, PeerCredentials).ok()?;
+    (cred.uid() == owner || cred.uid() == 0).then_some(cred.pid() as u32)
+}
+
+/// 名册里的一只 edge
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edge {
+    pid: u32,
+    /// 启动时刻（进程号被重用时对不上）
+    started: u64,
+}
+
+/// 小帮手的名册：恩兔带出门的 edge 和它们的网卡，以及 IP 转发是不是小帮手打开的
+#[derive(Debug, Default)]
+struct Registry {
+    edges: Vec<Edge>,
+    devs: Vec<String>,
+    forwarding: bool,
+}
+
+impl Registry {
+    /// 收下报到：这只 edge 得是按门铃的那位（和小帮手是同一个程序）亲手带出门的，网卡名也得和命令行对得上
+    fn claim(&mut self, peer: u32, pid: u32, dev: &str) -> Result<()> {
+        check_dev(dev)?;
+        if !is_edge(pid) {
+            anyhow::bail!("PID {} 不是 edge，不收报到", pid);
+        }
+        let (ppid, started) = stat_of(pid).context("读不到 edge 的进程信息")?;
+        if ppid != peer {
+            anyhow::bail!("edge（PID: {}）不是按门铃的这位带出门的", pid);
+        }
+        let me = std::env::current_exe().context("找不到小帮手自己的位置")?;
+        if std::fs::read_link(format!("/proc/{}/exe", peer)).ok() != Some(me) {
+            anyhow::bail!("按门铃的不是恩兔，不收报到");
+        }
+        let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).context("读不到 edge 的命令行")?;
+        let cmdline = String::from_utf8_lossy(&cmdline);
+        let actual = dev_in_args(cmdline.split('\0').skip(1));
+        if actual != dev {
+            anyhow::bail!("edge（PID: {}）用的网卡是 {}，不是 {}", pid, actual, dev);
+        }
+        self.edges.retain(|e| stat_of(e.pid).map(|(_, s)| s) == Some(e.started));
+        if !self.edges.iter().any(|e| e.pid == pid) {
+            self.edges.push(Edge { pid, started });
+        }
+        if !self.devs.iter().any(|d| d == dev) {
+            self.devs.push(dev.to_string());
+        }
+        Ok(())
+    }
+
+    /// 这只 edge 是不是名册里的那一只（进程号没被别人重用）
+    fn owns(&self, pid: u32) -> bool {
+        self.edges.iter().any(|e| e.pid == pid && stat_of(pid).map(|(_, s)| s) == Some(e.started))
+    }
+
+    /// 这块网卡是不是名册里 edge 用的虚拟网卡
+    fn check_dev(&self, dev: &str) -> Result<()> {
+        check_dev(dev)?;
+        if !self.devs.iter().any(|d| d == dev) || !is_tun(dev) {
+            anyhow::bail!("网卡 {} 不是恩兔的 edge 建的虚拟网卡，小帮手不管", dev);
+        }
+        Ok(())
+    }
+}
+
+/// 回答这一位的纸条（一行一张），收到 Quit 时返回 false
+fn answer(stream: UnixStream, peer: u32, book: &mut Registry) -> bool {
+    let _ = stream.set_read_timeout(Some(REPLY_TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else {
+        return true;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let request = serde_json::from_str::<Request>(&line);
+        let quit = matches!(request, Ok(Request::Quit));
+        let reply = match request.context("看不懂的纸条").and_then(|r| execute(&r, peer, book)) {
+            Ok(version) => Reply { ok: true, version, error: None },
+            Err(e) => Reply { ok: false, version: None, error: Some(format!("{:#}", e)) },
+        };
+        let Ok(mut text) = serde_json::to_string(&reply) else { break };
+        text.push('\n');
+        if writer.write_all(text.as_bytes()).is_err() || quit {
+            return !quit;
+        }
+    }
+    true
+}
+
+/// 照纸条办事（Ping 时返回版本号；`peer` 是按门铃那位的进程号）
+fn execute(request: &Request, peer: u32, book: &mut Registry) -> Result<Option<String>> {
+    match request {
+        Request::Ping => return Ok(Some(VERSION.to_string())),
+        Request::Quit => {}
+        Request::Claim { pid, dev } => book.claim(peer, *pid, dev)?,
+        Request::Signal { pid, force, group } => {
+            use nix::sys::signal::{kill, killpg, Signal};
+            use nix::unistd::{getpgid, Pid};
+
+            if !is_edge(*pid) || !book.owns(*pid) {
+                anyhow::bail!("PID {} 不是恩兔带出门的 edge，小帮手不管", pid);
+            }
+            let target = Pid::from_raw(*pid as i32);
+            let sig = if *force { Signal::SIGKILL } else { Signal::SIGINT };
+            if *group {
+                // 只有自立门户的 edge（自己是组长）才整组发，免得连带别的程序
+                if getpgid(Some(target)).ok() != Some(target) {
+                    anyhow::bail!("edge（PID: {}）不是进程组组长，小帮手不整组发信号", pid);
+                }
+                killpg(target, sig).with_context(|| format!("给 edge（PID: {}）整组发 {} 失败", pid, sig))?;
+            } else {
+                kill(target, sig).with_context(|| format!("给 edge（PID: {}）发 {} 失败", pid, sig))?;
+            }
+        }
+        Request::Flush { dev, what } => {
+            book.check_dev(dev)?;
+            run("ip", &[what.as_str(), "flush", "dev", dev])?;
+        }
+        Request::SetMtu { dev, mtu } => {
+            book.check_dev(dev)?;
+            if *mtu < 68 {
+                anyhow::bail!("MTU {} 太小了", mtu);
+            }
+            run("ip", &["link", "set", "dev", dev, "mtu", &mtu.to_string()])?;
+        }
+        Request::IpForward { enabled } => {
+            // 打开：名册里得有 edge 在跑（当中转节点时才需要）；关闭：只关小帮手自己打开的
+            const IP_FORWARD: &str = "/proc/sys/net/ipv4/ip_forward";
+            let on = std::fs::read_to_string(IP_FORWARD).context("读取 ip_forward 失败")?.trim() == "1";
+            if *enabled && !on {
+                if !book.edges.iter().any(|e| book.owns(e.pid)) {
+                    anyhow::bail!("恩兔的 edge 都不在，小帮手不打开 IP 转发");
+                }
+                std::fs::write(IP_FORWARD, "1").context("打开 ip_forward 失败")?;
+                book.forwarding = true;
+            } else if !*enabled && on {
+                if !book.forwarding {
+                    anyhow::bail!("IP 转发不是小帮手打开的，小帮手不关");
+                }
+                std::fs::write(IP_FORWARD, "0").context("关闭 ip_forward 失败")?;
+                book.forwarding = false;
+            } else if !*enabled {
+                book.forwarding = false;
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn check_dev(dev: &str) -> Result<()> {
+    if !valid_dev(dev) {
+        anyhow::bail!("网卡名“{}”不对", dev);
+    }
+    Ok(())
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program).args(args).status().with_context(|| format!("执行 {} 失败", program))?;
+    if !status.success() {
+        anyhow::bail!("{} {} 失败（{:?}）", program, args.join(" "), status.code());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_and_checks() {
+        let request = Request::Signal { pid: 42, force: false, group: false };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(line, r#"{"op":"signal","pid":42,"force":false,"group":false}"#);
+        assert_eq!(serde_json::from_str::<Request>(&line).unwrap(), request);
+        // 旧版恩兔递来的纸条没有 group：当成只发给 edge 本身
+        let old: Request = serde_json::from_str(r#"{"op":"signal","pid":42,"force":true}"#).unwrap();
+        assert_eq!(old, Request::Signal { pid: 42, force: true, group: false });
+        let flush: Request = serde_json::from_str(r#"{"op":"flush","dev":"edge0","what":"route"}"#).unwrap();
+        assert_eq!(flush, Request::Flush { dev: "edge0".to_string(), what: FlushWhat::Route });
+        assert!(serde_json::from_str::<Request>(r#"{"op":"rm","path":"/"}"#).is_err());
+
+        assert!(valid_dev("edge0"));
+        assert!(valid_dev("n2n-home.1"));
+        assert!(!valid_dev(""));
+        assert!(!valid_dev("-edge"));
+        assert!(!valid_dev("edge0; rm -rf /"));
+        assert!(!valid_dev("a-very-long-device-name"));
+
+        // 小帮手只给恩兔带出门的 edge 发信号：测试进程自己、init 都不行
+        let me = std::process::id();
+        let mut book = Registry::default();
+        assert!(!is_edge(me));
+        assert!(!is_edge(1));
+        assert!(execute(&Request::Signal { pid: me, force: true, group: true }, me, &mut book).is_err());
+        assert!(execute(&Request::Claim { pid: me, dev: "edge0".to_string() }, me, &mut book).is_err());
+        assert_eq!(execute(&Request::Ping, me, &mut book).unwrap().as_deref(), Some(VERSION));
+        // 名册里没有的网卡、不是虚拟网卡的都不碰；IP 转发没 edge 在跑不开，不是自己开的不关
+        assert!(execute(&Request::Flush { dev: "lo".to_string(), what: FlushWhat::Addr }, me, &mut book).is_err());
+        book.devs.push("lo".to_string());
+        assert!(!is_tun("lo"));
+        assert!(execute(&Request::SetMtu { dev: "lo".to_string(), mtu: 1400 }, me, &mut book).is_err());
+        let on = std::fs::read_to_string("/proc/sys/net/ipv4/ip_forward").unwrap().trim() == "1";
+        assert!(execute(&Request::IpForward { enabled: !on }, me, &mut book).is_err());
+
+        // 名册靠 /proc 认人：父进程号、启动时刻，命令行里的网卡名
+        let stat = "4242 (edge (x) y) S 100 4242 4242 0 -1 4194560 1 0 0 0 0 0 0 0 20 0 1 0 987654 0 0";
+        assert_eq!(parse_stat(stat), Some((100, 987654)));
+        let (ppid, _) = stat_of(me).unwrap();
+        assert_eq!(ppid, nix::unistd::getppid().as_raw() as u32);
+        assert_eq!(dev_in_args(["-c", "home", "-l", "sn:7777"]), "edge0");
+        assert_eq!(dev_in_args(["-c", "home", "-d", "n2n1"]), "n2n1");
+        assert_eq!(dev_in_args(["-dn2n2", "-c", "home"]), "n2n2");
+    }
+}
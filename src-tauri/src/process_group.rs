@@ -0,0 +1,161 @@
+//! 一家子一起收拾（edge 和它拉起的程序）
+//!
+//! 主人在额外参数里让 edge 跑脚本、或者 edge 自己拉起辅助程序时，这些“孙子进程”不是恩兔带出门的：
+//! 以前强制清理只放倒 edge 本身，它们留下来继续占着虚拟网卡，下次建立通道就打不开网卡了。现在：
+//! - Unix 上 edge 出门时自立门户（`setsid`），自己当进程组的组长，它拉起的程序默认都在这个组里；
+//!   强制清理和恩兔退出时对整个组发 SIGKILL（`killpg`），edge 降权后发不动就请小帮手代发；
+//! - Windows 上没有进程组可以一起放倒，就把 edge 装进一个 Job Object（“句柄关上就清空”）：
+//!   强制清理时 `TerminateJobObject`；edge 退出后、恩兔退出时把句柄关上，剩下的也一起清走；
+//! - 接手来的 edge 不是恩兔带出门的，不一定自己当组长，只清它本身，免得误伤同组的其他程序。
+use anyhow::Result;
+
+/// 放 edge 出门前：让它自立门户（新的会话和进程组，组号就是它的进程号）
+#[cfg(unix)]
+pub fn isolate(cmd: &mut std::process::Command) {
+    use std::os::unix::process::CommandExt;
+
+    // pre_exec 在子进程里、exec 之前执行；setsid 是异步信号安全的
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// 对进程组 `pgid`（自立门户的 edge 的进程号）整组发 SIGKILL
+/// - 返回 false 表示组里已经没人了
+#[cfg(unix)]
+pub fn kill(pgid: u32) -> Result<bool> {
+    use nix::errno::Errno;
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    // 0 是恩兔自己的组、1 是 init：绝对不能整组放倒
+    if pgid <= 1 {
+        anyhow::bail!("进程组 {} 不能整组清理", pgid);
+    }
+    match killpg(Pid::from_raw(pgid as i32), Signal::SIGKILL) {
+        Ok(()) => Ok(true),
+        Err(Errno::ESRCH) => Ok(false),
+        // edge 降权了：组长还在的话请小帮手代发（组长已经走了，小帮手认不出这是 edge 的组，就不去麻烦它）
+        #[cfg(target_os = "linux")]
+        Err(Errno::EPERM) if crate::session_state::process_alive(pgid) => {
+            log::info!("直接整组发 SIGKILL 失败（edge 可能已降权），请小帮手代发");
+            crate::priv_helper::call(&crate::priv_helper::Request::Signal {
+                pid: pgid,
+                force: true,
+                group: true,
+            })?;
+            Ok(true)
+        }
+        Err(e) => Err(anyhow::anyhow!("给进程组 {} 发 SIGKILL 失败：{}", pgid, e)),
+    }
+}
+
+/// 装着 edge 的 Job Object（句柄关上时里面还没走的进程会被一起清走）
+#[cfg(windows)]
+pub struct Job(windows_sys::Win32::Foundation::HANDLE);
+
+// 句柄只是个编号，哪个线程拿着用都行
+#[cfg(windows)]
+unsafe impl Send for Job {}
+#[cfg(windows)]
+unsafe impl Sync for Job {}
+
+#[cfg(windows)]
+impl Job {
+    /// 新建一个“句柄关上就清空”的 Job Object
+    pub fn new() -> Result<Job> {
+        use windows_sys::Win32::System::JobObjects::{
+            CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        unsafe {
+            let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if handle.is_null() {
+                anyhow::bail!("新建 Job Object 失败：{}", std::io::Error::last_os_error());
+            }
+            let job = Job(handle);
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            let ok = SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            if ok == 0 {
+                anyhow::bail!("设置 Job Object 失败：{}", std::io::Error::last_os_error());
+            }
+            Ok(job)
+        }
+    }
+
+    /// 把刚出门的 edge 装进来（之后它拉起的程序也会自动进来）
+    pub fn assign(&self, process: std::os::windows::io::RawHandle) -> Result<()> {
+        use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+
+        if unsafe { AssignProcessToJobObject(self.0, process as _) } == 0 {
+            anyhow::bail!("把 edge 装进 Job Object 失败：{}", std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// 一家子一起强制清理
+    pub fn terminate(&self) -> Result<()> {
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+            anyhow::bail!("清理 Job Object 失败：{}", std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Job {
+    fn drop(&mut self) {
+        unsafe { windows_sys::Win32::Foundation::CloseHandle(self.0) };
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kill_takes_the_whole_group() {
+        use std::io::{BufRead, BufReader};
+        use std::process::{Command, Stdio};
+        use std::time::{Duration, Instant};
+
+        assert!(kill(0).is_err());
+        assert!(kill(1).is_err());
+
+        // sh 再拉起一个 sleep：sh 就是 edge，sleep 就是它拉起的程序
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 30 & echo $!; wait"]).stdout(Stdio::piped());
+        isolate(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let grandchild: u32 = line.trim().parse().unwrap();
+
+        assert!(kill(child.id()).unwrap());
+        child.wait().unwrap();
+        // sleep 被放倒后交给 init 回收，收走前会短暂挂着 <defunct>
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", grandchild)).unwrap_or_default();
+            if stat.is_empty() || stat.contains(") Z") {
+                break;
+            }
+            assert!(Instant::now() < deadline, "sleep 还活着：{}", stat);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! 收尸小工（回收退出的子进程，别让它们变成僵尸）
+//!
+//! Unix 上子进程退出后要有人 `waitpid` 一下，系统才会把它的进程表项收走；没人收就一直挂着 `<defunct>`。
+//! 以前都靠监控线程 `try_wait`，但停止流程和监控线程会抢着收：
+//! - edge 被小帮手代发的信号放倒时可能已经被别人收过了，`try_wait` 只会报 ECHILD，
+//!   监控线程一直当成“检查失败”，状态卡在原地；
+//! - 句柄在 edge 还没退出时就被换掉（强制停止等不及、新的一班接手），旧进程退出后就没人收了。
+//!
+//! 这里统一处理：`poll` 把 ECHILD 当成“已经退出（被别人收走了）”；来不及等的进程交给 `abandon`，
+//! 由收尸小工拿着句柄 `wait` 到它退出，长时间挂着的会话也不会越攒越多僵尸。
+//! （子进程是 `tokio::process::Child`：`try_wait` 不用等，在哪个线程里调都行。）
+use std::io;
+use std::process::ExitStatus;
+use tokio::process::Child;
+use tokio::runtime::Handle;
+
+/// 子进程现在的样子
+#[derive(Debug)]
+pub enum ChildState {
+    /// 还在干活
+    Running,
+    /// 已经退出；None 表示被别人先收走了，不知道退出码
+    Exited(Option<ExitStatus>),
+}
+
+/// 看一眼子进程退出了没有（退出了就顺手收走）
+pub fn poll(child: &mut Child) -> io::Result<ChildState> {
+    match child.try_wait() {
+        Ok(Some(status)) => Ok(ChildState::Exited(Some(status))),
+        Ok(None) => Ok(ChildState::Running),
+        Err(e) if is_already_reaped(&e) => {
+            log::warn!("子进程 {:?} 已经被别人回收了，当它已经退出", child.id());
+            Ok(ChildState::Exited(None))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// ECHILD：这个进程不是（或者已经不是）恩兔的孩子了
+fn is_already_reaped(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        e.raw_os_error() == Some(libc::ECHILD)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = e;
+        false
+    }
+}
+
+/// 等不及它退出的子进程：交给收尸小工（`runtime` 上的一个任务）拿着句柄等它退出再收
+/// - 只认句柄不认 PID：进程号收走后可能马上分给下一班 edge，按 PID `waitpid` 会把新 edge 的退出码抢走；
+/// - Windows 没有僵尸，这里只是顺手把句柄放下。
+pub fn abandon(mut child: Child, runtime: &Handle) {
+    if !matches!(poll(&mut child), Ok(ChildState::Running)) {
+        return;
+    }
+    log::debug!("子进程 {:?} 还没退出，交给收尸小工", child.id());
+    runtime.spawn(async move {
+        match child.wait().await {
+            Ok(status) => log::debug!("收走了子进程 {:?}：{:?}", child.id(), status),
+            Err(e) => log::warn!("回收子进程失败：{}", e),
+        }
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use nix::sys::wait::waitpid;
+    use nix::unistd::Pid;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tokio::process::Command;
+
+    fn wait_exited(child: &mut Child) -> Option<ExitStatus> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            match poll(child).unwrap() {
+                ChildState::Exited(status) => return status,
+                ChildState::Running if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+                ChildState::Running => panic!("子进程一直没退出"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reaping_survives_races() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let _guard = runtime.enter();
+
+        // 正常退出：拿得到退出码
+        let mut child = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        assert_eq!(wait_exited(&mut child).and_then(|s| s.code()), Some(3));
+
+        // 被别人抢先收走了：不再报错，当成已经退出
+        let mut child = Command::new("sh").args(["-c", "exit 0"]).spawn().unwrap();
+        waitpid(Pid::from_raw(child.id().unwrap() as i32), None).unwrap();
+        assert!(wait_exited(&mut child).is_none());
+
+        // 等不及的进程交给收尸小工，退出后不会留下僵尸
+        let child = Command::new("sleep").arg("0.1").spawn().unwrap();
+        let pid = child.id().unwrap() as i32;
+        abandon(child, runtime.handle());
+        runtime.block_on(tokio::time::sleep(Duration::from_millis(300)));
+        assert!(waitpid(Pid::from_raw(pid), None).is_err(), "进程 {} 还没被收走", pid);
+    }
+}
@@ -0,0 +1,253 @@
+//! 花名册（给管小组的主人用：记下每位成员分到的固定地址）
+//!
+//! 小组一大，谁用了 10.0.0.7 就只能靠记性，发邀请时手一抖就撞了地址，接下来就是两台电脑轮流掉线。
+//! 这里按小组暗号各记一本花名册（配置目录下的 `rosters.json`）：
+//! - 每本写明小组的网段和成员分到的地址，保存时检查地址合法、在网段内、没有重复；
+//! - 可以导出成花名册文件交给其他管理员（.json 为 JSON，其余为 TOML），也可以导入；
+//! - 发邀请链接时拿邀请里的固定地址对一下，撞上别的成员就提醒主人。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+
+use crate::config::N2NConfig;
+use crate::portable::FileFormat;
+
+/// 一位成员
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RosterMember {
+    /// 成员名字（发邀请时就是方案名字）
+    pub name: String,
+    /// 分到的地址（可以带前缀长度，例如 10.0.0.7/24）
+    pub ip: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// 一个小组的花名册
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Roster {
+    /// 小组暗号
+    pub community: String,
+    /// 小组网段（CIDR，例如 10.0.0.0/24）
+    pub subnet: String,
+    pub members: Vec<RosterMember>,
+}
+
+impl Default for Roster {
+    fn default() -> Self {
+        Self {
+            community: String::new(),
+            subnet: "10.0.0.0/24".to_string(),
+            members: Vec::new(),
+        }
+    }
+}
+
+impl Roster {
+    /// 检查整本花名册（有问题时返回 error_ 编号）
+    pub fn check(&self) -> Result<()> {
+        if self.community.trim().is_empty() {
+            anyhow::bail!("error_community_empty");
+        }
+        let (network, prefix) = parse_subnet(&self.subnet).context("error_roster_invalid_subnet")?;
+        let mut seen = Vec::new();
+        for member in &self.members {
+            let ip = host_ip(&member.ip).context("error_roster_invalid_ip")?;
+            if !in_subnet(ip, network, prefix) {
+                anyhow::bail!("error_roster_ip_outside_subnet");
+            }
+            if seen.contains(&ip) {
+                anyhow::bail!("error_roster_duplicate_ip");
+            }
+            seen.push(ip);
+        }
+        Ok(())
+    }
+
+    /// 网段里下一个还没分出去的地址（跳过网络号和广播地址）
+    pub fn next_free(&self) -> Option<String> {
+        let (network, prefix) = parse_subnet(&self.subnet)?;
+        let taken: Vec<Ipv4Addr> = self.members.iter().filter_map(|m| host_ip(&m.ip)).collect();
+        let size = 1u64 << (32 - prefix as u64);
+        let base = u32::from(network) as u64;
+        (1..size.saturating_sub(1))
+            .map(|offset| Ipv4Addr::from((base + offset) as u32))
+            .find(|ip| !taken.contains(ip))
+            .map(|ip| format!("{}/{}", ip, prefix))
+    }
+
+    /// 以 `name` 的名义用这个地址会撞上谁（同名成员不算）
+    pub fn conflict(&self, name: &str, ip: &str) -> Option<&RosterMember> {
+        let ip = host_ip(ip)?;
+        self.members
+            .iter()
+            .find(|m| m.name != name && host_ip(&m.ip) == Some(ip))
+    }
+
+    /// 一份方案发出去会不会撞地址（只看固定地址的方案）
+    pub fn invite_conflict(&self, name: &str, config: &N2NConfig) -> Option<&RosterMember> {
+        if config.ip_mode != "static" {
+            return None;
+        }
+        self.conflict(name, config.static_ip.as_deref()?)
+    }
+}
+
+/// 发出去的邀请（链接，外加撞上的成员）
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareInvite {
+    pub link: String,
+    /// 邀请里的固定地址已经分给了这位成员
+    pub conflict: Option<RosterMember>,
+}
+
+/// 花名册柜子
+pub struct RosterStore {
+    path: PathBuf,
+}
+
+impl RosterStore {
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("rosters.json")))
+    }
+
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load_all(&self) -> Result<Vec<Roster>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path).context("读取花名册失败")?;
+        serde_json::from_str(&content).context("花名册写坏了")
+    }
+
+    fn save_all(&self, rosters: &[Roster]) -> Result<()> {
+        let content = serde_json::to_string_pretty(rosters).context("序列化花名册失败")?;
+        fs::write(&self.path, content).context("写入花名册失败")
+    }
+
+    /// 翻出某个小组的花名册（还没有的话给一本空的）
+    pub fn get(&self, community: &str) -> Result<Roster> {
+        Ok(self
+            .load_all()?
+            .into_iter()
+            .find(|r| r.community == community)
+            .unwrap_or_else(|| Roster {
+                community: community.to_string(),
+                ..Default::default()
+            }))
+    }
+
+    /// 检查后整本替换
+    pub fn save(&self, roster: Roster) -> Result<()> {
+        roster.check()?;
+        let mut rosters = self.load_all()?;
+        rosters.retain(|r| r.community != roster.community);
+        rosters.push(roster);
+        self.save_all(&rosters)
+    }
+
+    /// 从花名册文件导入（同一个小组的会被整本替换），返回导入的花名册
+    pub fn import(&self, path: &Path) -> Result<Roster> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("读取花名册文件 {} 失败", path.display()))?;
+        let roster: Roster = match FileFormat::from_path(path) {
+            FileFormat::Toml => toml::from_str(&content).context("花名册文件不是有效的 TOML")?,
+            FileFormat::Json => serde_json::from_str(&content).context("花名册文件不是有效的 JSON")?,
+        };
+        self.save(roster.clone())?;
+        log::info!("恩兔导入了小组“{}”的花名册（{} 位成员）", roster.community, roster.members.len());
+        Ok(roster)
+    }
+
+    /// 导出某个小组的花名册文件
+    pub fn export(&self, community: &str, path: &Path) -> Result<()> {
+        let roster = self.get(community)?;
+        let content = match FileFormat::from_path(path) {
+            FileFormat::Toml => toml::to_string_pretty(&roster).context("序列化花名册失败")?,
+            FileFormat::Json => serde_json::to_string_pretty(&roster).context("序列化花名册失败")?,
+        };
+        fs::write(path, content).with_context(|| format!("写入花名册文件 {} 失败", path.display()))
+    }
+}
+
+/// `10.0.0.7`、`10.0.0.7/24`、`static:10.0.0.7` 里的地址
+fn host_ip(value: &str) -> Option<Ipv4Addr> {
+    let value = value.trim();
+    let value = value.strip_prefix("static:").unwrap_or(value);
+    value.split('/').next()?.parse().ok()
+}
+
+/// 网段的网络号和前缀长度（`10.0.0.5/24` 也认，按 10.0.0.0/24 算）
+fn parse_subnet(value: &str) -> Option<(Ipv4Addr, u8)> {
+    let (ip, prefix) = value.trim().split_once('/')?;
+    let prefix: u8 = prefix.parse().ok().filter(|p| *p <= 30)?;
+    let ip: Ipv4Addr = ip.parse().ok()?;
+    Some((Ipv4Addr::from(u32::from(ip) & mask(prefix)), prefix))
+}
+
+fn in_subnet(ip: Ipv4Addr, network: Ipv4Addr, prefix: u8) -> bool {
+    u32::from(ip) & mask(prefix) == u32::from(network)
+}
+
+fn mask(prefix: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn member(name: &str, ip: &str) -> RosterMember {
+        RosterMember {
+            name: name.to_string(),
+            ip: ip.to_string(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_roster_checks_and_conflicts() {
+        let mut roster = Roster {
+            community: "home".to_string(),
+            subnet: "10.0.0.0/24".to_string(),
+            members: vec![member("alice", "10.0.0.1"), member("bob", "10.0.0.2/24")],
+        };
+        roster.check().unwrap();
+        assert_eq!(roster.next_free().as_deref(), Some("10.0.0.3/24"));
+
+        let invite = N2NConfig {
+            ip_mode: "static".to_string(),
+            static_ip: Some("10.0.0.2/24".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(roster.invite_conflict("carol", &invite).map(|m| m.name.as_str()), Some("bob"));
+        assert_eq!(roster.invite_conflict("bob", &invite), None);
+
+        roster.members.push(member("carol", "10.0.0.2"));
+        assert_eq!(roster.check().unwrap_err().to_string(), "error_roster_duplicate_ip");
+        roster.members.pop();
+        roster.members.push(member("dave", "10.0.1.9"));
+        assert_eq!(roster.check().unwrap_err().to_string(), "error_roster_ip_outside_subnet");
+
+        // 存进柜子再导出、导入，内容不变
+        let dir = TempDir::new("roster");
+        let store = RosterStore::at(dir.join("rosters.json"));
+        roster.members.pop();
+        store.save(roster.clone()).unwrap();
+        store.export("home", &dir.join("home.toml")).unwrap();
+        let other = RosterStore::at(dir.join("other.json"));
+        assert_eq!(other.import(&dir.join("home.toml")).unwrap(), roster);
+        assert_eq!(other.get("home").unwrap(), roster);
+    }
+}
@@ -0,0 +1,91 @@
+//! 路由（经由虚拟网里的同伴去往别的网段，交给 edge 的 `-n`）
+//!
+//! 把两边的局域网连起来时，要告诉 edge“去 192.168.2.0/24 请找 10.0.0.2”。以前只能在奇妙指令里手写
+//! `-n 192.168.2.0/24:10.0.0.2`，写错了也要等 edge 启动失败才知道。现在方案里单独记一张路由表：
+//! - 每条写明目标网段、子网掩码（`255.255.255.0` 或者前缀长度 `24`）和网关（对面 edge 的虚拟地址）；
+//! - 保存前检查写法，网段里带了主机位（比如 `192.168.2.5/24`）也会指出来；
+//! - 建立通道时逐条翻译成 `-n 网段/前缀:网关`，工作台上能看到本次实际加上的路由。
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+/// 一条路由（记在方案里）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    /// 目标网段（例如 `192.168.2.0`）
+    pub network: String,
+    /// 子网掩码（`255.255.255.0`，也可以直接写前缀长度 `24`）
+    pub netmask: String,
+    /// 网关：负责转发的同伴在虚拟网里的地址
+    pub gateway: String,
+}
+
+impl Route {
+    /// 掩码换算成前缀长度（写法不对、掩码不连续时为 None）
+    pub fn prefix_len(&self) -> Option<u8> {
+        let mask = self.netmask.trim();
+        let mask = mask.strip_prefix('/').unwrap_or(mask);
+        if let Ok(len) = mask.parse::<u8>() {
+            return (len <= 32).then_some(len);
+        }
+        let bits = u32::from(mask.parse::<Ipv4Addr>().ok()?);
+        let len = bits.leading_ones();
+        (bits.checked_shl(len).unwrap_or(0) == 0).then_some(len as u8)
+    }
+
+    /// 翻译成 `-n` 后面的值（`网段/前缀:网关`），写法不对时为 None
+    pub fn to_arg(&self) -> Option<String> {
+        let network = self.network.trim().parse::<Ipv4Addr>().ok()?;
+        let gateway = self.gateway.trim().parse::<Ipv4Addr>().ok()?;
+        let len = self.prefix_len()?;
+        let host_bits = u32::MAX.checked_shr(u32::from(len)).unwrap_or(0);
+        (u32::from(network) & host_bits == 0).then(|| format!("{}/{}:{}", network, len, gateway))
+    }
+}
+
+/// 检查路由表，有写错的返回 `error_` 编号
+pub fn check(routes: &[Route]) -> Option<&'static str> {
+    routes.iter().any(|r| r.to_arg().is_none()).then_some("error_invalid_route")
+}
+
+/// 翻译成 edge 的参数（每条一个 `-n`，写错的跳过，保存时已经拦过）
+pub fn to_args(routes: &[Route]) -> Vec<String> {
+    routes
+        .iter()
+        .filter_map(Route::to_arg)
+        .flat_map(|arg| ["-n".to_string(), arg])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(network: &str, netmask: &str, gateway: &str) -> Route {
+        Route {
+            network: network.to_string(),
+            netmask: netmask.to_string(),
+            gateway: gateway.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_routes_become_edge_args() {
+        let routes = vec![
+            route("192.168.2.0", "255.255.255.0", "10.0.0.2"),
+            route("172.16.0.0", "/12", "10.0.0.3"),
+            route("0.0.0.0", "0", "10.0.0.1"),
+        ];
+        assert_eq!(check(&routes), None);
+        assert_eq!(
+            to_args(&routes),
+            vec!["-n", "192.168.2.0/24:10.0.0.2", "-n", "172.16.0.0/12:10.0.0.3", "-n", "0.0.0.0/0:10.0.0.1"]
+        );
+        assert_eq!(route("10.1.1.1", "255.255.255.255", "10.0.0.2").prefix_len(), Some(32));
+
+        // 掩码不连续、网段带主机位、网关不是地址都算写错
+        assert_eq!(check(&[route("192.168.2.0", "255.0.255.0", "10.0.0.2")]), Some("error_invalid_route"));
+        assert_eq!(check(&[route("192.168.2.5", "24", "10.0.0.2")]), Some("error_invalid_route"));
+        assert_eq!(check(&[route("192.168.2.0", "33", "10.0.0.2")]), Some("error_invalid_route"));
+        assert_eq!(check(&[route("192.168.2.0", "24", "peer")]), Some("error_invalid_route"));
+    }
+}
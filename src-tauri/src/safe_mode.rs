@@ -0,0 +1,143 @@
+//! 安全模式（接连几次没好好收工后，先别急着自动上班）
+//!
+//! 指示写坏了的话，恩兔可能每次一打开就自动连接、edge 一出门就摔倒（或者恩兔自己跟着摔倒），
+//! 主人连打开设置改回来的机会都没有。所以配置目录里的 `launch.json` 记着：
+//! - `running`：上岗时写 true，好好收工时写 false；下次上岗还看到 true，说明上次是意外退出；
+//! - `abnormal_exits`：接连意外退出了几次（恩兔自己意外退出、edge 意外退出都算），连上一次就清零。
+//!
+//! 接连意外退出达到 `THRESHOLD` 次，这次上岗就进安全模式：不自动建立通道、日志放到最详细、
+//! 开门体检单全部摊开给主人看，并用 `safe-mode` 事件说明原因。主人确认没事后可以手动退出安全模式。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 接连意外退出几次后进安全模式
+pub const THRESHOLD: u32 = 3;
+
+/// 上岗登记
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LaunchRecord {
+    running: bool,
+    abnormal_exits: u32,
+}
+
+/// 这次上岗进了安全模式（给前端看原因）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeMode {
+    /// 接连意外退出了几次
+    pub abnormal_exits: u32,
+}
+
+fn safe_mode_for(record: &LaunchRecord) -> Option<SafeMode> {
+    (record.abnormal_exits >= THRESHOLD).then_some(SafeMode {
+        abnormal_exits: record.abnormal_exits,
+    })
+}
+
+/// 上岗登记本
+pub struct LaunchGuard {
+    path: PathBuf,
+}
+
+impl LaunchGuard {
+    /// 翻开登记本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("launch.json")))
+    }
+
+    /// 指定登记本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn load(&self) -> LaunchRecord {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, record: &LaunchRecord) -> Result<()> {
+        let content = serde_json::to_string(record).context("序列化上岗登记失败")?;
+        fs::write(&self.path, content).context("写入上岗登记失败")
+    }
+
+    /// 这次上岗后的登记（上次没好好收工就多记一次意外）
+    fn next(&self) -> LaunchRecord {
+        let last = self.load();
+        LaunchRecord {
+            running: true,
+            abnormal_exits: last.abnormal_exits + u32::from(last.running),
+        }
+    }
+
+    /// 先看一眼这次要不要进安全模式（不写登记本；日志系统准备好之前用）
+    pub fn peek(&self) -> Option<SafeMode> {
+        safe_mode_for(&self.next())
+    }
+
+    /// 上岗登记，返回这次要不要进安全模式
+    pub fn begin(&self) -> Result<Option<SafeMode>> {
+        let record = self.next();
+        self.save(&record)?;
+        Ok(safe_mode_for(&record))
+    }
+
+    /// edge 意外退出：也记一次
+    pub fn record_edge_crash(&self) -> Result<()> {
+        let mut record = self.load();
+        record.abnormal_exits = record.abnormal_exits.saturating_add(1);
+        self.save(&record)
+    }
+
+    /// 连上了，或者主人确认没事了：清零
+    pub fn reset(&self) -> Result<()> {
+        let record = self.load();
+        if record.abnormal_exits == 0 {
+            return Ok(());
+        }
+        self.save(&LaunchRecord { abnormal_exits: 0, ..record })
+    }
+
+    /// 好好收工（意外次数留着，要连上一次才清零）
+    pub fn finish(&self) -> Result<()> {
+        self.save(&LaunchRecord { running: false, ..self.load() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_counts_abnormal_exits_until_connected() {
+        let dir = TempDir::new("safe-mode");
+        let guard = LaunchGuard::at(dir.join("launch.json"));
+
+        // 第一次上岗、好好收工：不算意外
+        assert_eq!(guard.begin().unwrap(), None);
+        guard.finish().unwrap();
+        assert_eq!(guard.begin().unwrap(), None);
+
+        // 恩兔摔倒两次（没来得及 finish），edge 再摔一次：下次上岗进安全模式
+        assert_eq!(guard.begin().unwrap(), None);
+        guard.record_edge_crash().unwrap();
+        assert_eq!(guard.peek(), Some(SafeMode { abnormal_exits: 3 }));
+        assert_eq!(guard.begin().unwrap(), Some(SafeMode { abnormal_exits: 3 }));
+
+        // 好好收工也不清零，连上一次才清零
+        guard.finish().unwrap();
+        assert_eq!(guard.peek(), Some(SafeMode { abnormal_exits: 3 }));
+        guard.reset().unwrap();
+        guard.finish().unwrap();
+        assert_eq!(guard.begin().unwrap(), None);
+    }
+}
@@ -0,0 +1,142 @@
+//! 给 edge 上锁（Linux 下可选的加固措施）
+//!
+//! edge 是第三方的网络程序，却带着 cap_net_admin 之类的能力（甚至直接以 root 身份）在跑。
+//! 主人打开“加固 edge”后，恩兔在放 edge 出门前先把不必要的门都关上，万一它出了问题也闯不了大祸：
+//! - 环境变量只留白名单里的几项，`LD_PRELOAD` 之类能改变程序行为的统统不带；
+//! - 恩兔自己打开的文件、套接字不让 edge 继承（exec 时自动关闭）；
+//! - 以 root 身份启动时设置 `no_new_privs`，edge 以后再执行别的程序也拿不到新权限。
+//!   靠文件 capabilities 运行时不能设：no_new_privs 会让 exec 时的文件 capabilities 失效；
+//! - 指定了“降权用户”时，让 edge 建好网卡后用 `-u/-g` 切换到这个用户继续工作
+//!   （恩兔还没有常驻的提权小助手，所以交给 edge 自己降权）。
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// 允许带给 edge 的环境变量
+const ENV_ALLOWLIST: &[&str] = &["LANG", "LC_ALL", "LC_MESSAGES", "TZ"];
+/// 给 edge 用的固定 PATH（不沿用恩兔的 PATH，免得被人塞进奇怪的目录）
+const SAFE_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// 从当前环境变量里挑出允许带给 edge 的部分
+pub fn restricted_env(vars: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(key, _)| ENV_ALLOWLIST.contains(&key.as_str()))
+        .collect();
+    env.push(("PATH".to_string(), SAFE_PATH.to_string()));
+    env
+}
+
+/// 把降权用户（用户名或 `uid[:gid]`）换成 edge 的 `-u <uid> -g <gid>` 参数
+#[cfg(unix)]
+pub fn privilege_drop_args(user: &str) -> Result<Vec<String>> {
+    let user = user.trim();
+    let (uid, gid) = match user.split_once(':') {
+        Some((uid, gid)) => (
+            uid.parse::<u32>().context("降权用户的 uid 必须是数字")?,
+            gid.parse::<u32>().context("降权用户的 gid 必须是数字")?,
+        ),
+        None => match user.parse::<u32>() {
+            // 只写了 uid：组沿用同名用户的主组，查不到就用同样的编号
+            Ok(uid) => {
+                let gid = nix::unistd::User::from_uid(nix::unistd::Uid::from_raw(uid))
+                    .ok()
+                    .flatten()
+                    .map(|u| u.gid.as_raw())
+                    .unwrap_or(uid);
+                (uid, gid)
+            }
+            Err(_) => {
+                let found = nix::unistd::User::from_name(user)
+                    .with_context(|| format!("查询用户“{}”失败", user))?
+                    .with_context(|| format!("系统里没有用户“{}”", user))?;
+                (found.uid.as_raw(), found.gid.as_raw())
+            }
+        },
+    };
+    if uid == 0 {
+        anyhow::bail!("降权用户不能是 root");
+    }
+    Ok(vec!["-u".to_string(), uid.to_string(), "-g".to_string(), gid.to_string()])
+}
+
+/// 给即将启动的 edge 上锁：收窄环境变量、不继承多余的文件、必要时设置 no_new_privs
+#[cfg(target_os = "linux")]
+pub fn harden(cmd: &mut Command, running_as_root: bool) {
+    use std::os::unix::process::CommandExt;
+
+    cmd.env_clear().envs(restricted_env(std::env::vars()));
+
+    // pre_exec 在子进程里、exec 之前执行，只能做异步信号安全的系统调用
+    unsafe {
+        cmd.pre_exec(move || {
+            mark_inherited_fds_cloexec();
+            if running_as_root && libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// 其他平台暂时不上锁
+#[cfg(not(target_os = "linux"))]
+pub fn harden(_cmd: &mut Command, _running_as_root: bool) {}
+
+/// 把 3 号以后的文件描述符都标成 exec 时关闭
+///
+/// 不直接关掉：标准库自己还靠一根 CLOEXEC 管子回报 exec 失败，关了就收不到错误了。
+#[cfg(target_os = "linux")]
+fn mark_inherited_fds_cloexec() {
+    // Linux 5.11+ 一次搞定；老内核逐个设置（只扫前 1024 个，足够覆盖恩兔会打开的文件）
+    let ret = unsafe { libc::syscall(libc::SYS_close_range, 3u32, u32::MAX, libc::CLOSE_RANGE_CLOEXEC) };
+    if ret == 0 {
+        return;
+    }
+    for fd in 3..1024 {
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFD);
+            if flags >= 0 {
+                libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restricted_env_and_privilege_drop() {
+        let env = restricted_env([
+            ("LANG".to_string(), "zh_CN.UTF-8".to_string()),
+            ("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string()),
+            ("PATH".to_string(), "/tmp:/usr/bin".to_string()),
+        ]);
+        assert_eq!(
+            env,
+            vec![
+                ("LANG".to_string(), "zh_CN.UTF-8".to_string()),
+                ("PATH".to_string(), SAFE_PATH.to_string()),
+            ]
+        );
+
+        #[cfg(unix)]
+        {
+            assert_eq!(privilege_drop_args("1000:100").unwrap(), vec!["-u", "1000", "-g", "100"]);
+            assert!(privilege_drop_args("0:0").is_err());
+            assert!(privilege_drop_args("root").is_err());
+            assert!(privilege_drop_args("no-such-user-for-n2n-maid").is_err());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_harden_strips_environment() {
+        let mut cmd = Command::new("/bin/sh");
+        cmd.args(["-c", "echo \"[$LD_PRELOAD][$PATH]\""]).env("LD_PRELOAD", "/tmp/evil.so");
+        harden(&mut cmd, false);
+        let out = cmd.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), format!("[][{}]", SAFE_PATH));
+    }
+}
@@ -0,0 +1,157 @@
+//! 保险箱（把保密密语交给操作系统的钥匙串保管）
+//!
+//! 密语原本明文写在 `config.toml` 里，谁能读到这个文件谁就能混进主人的网络。
+//! 现在恩兔把密语放进系统自带的保险箱，指示簿里只记一张“取件单”（`keyring:<账户>`）：
+//! - Windows：凭据管理器；macOS：钥匙串；Linux：Secret Service（GNOME Keyring / KWallet）；
+//! - 保险箱用不了（比如没有桌面环境的服务器）时，照旧明文保存，并在日志里提醒一句；
+//! - 取过、放过的密语由指示簿管理器在内存里记一份（`Cache`）：后台小工人每隔十几秒就翻一次指示簿，
+//!   每次都去敲保险箱的话，Linux 上每次都是一趟 D-Bus，钥匙串锁着时还会一遍遍弹解锁框。
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 保险箱里的服务名
+const SERVICE: &str = "n2n-maid";
+/// 取件单的前缀
+const REFERENCE_PREFIX: &str = "keyring:";
+
+/// 某份方案的密语在保险箱里的账户名
+pub fn profile_account(profile: &str) -> String {
+    format!("profile:{}", profile)
+}
+
+/// 某份方案约好要换的新密语在保险箱里的账户名
+pub fn rotation_account(profile: &str) -> String {
+    format!("profile:{}:next", profile)
+}
+
+/// 账户名 → 写进指示簿的取件单
+pub fn reference(account: &str) -> String {
+    format!("{}{}", REFERENCE_PREFIX, account)
+}
+
+/// 取件单 → 账户名（不是取件单就返回 None）
+pub fn parse_reference(reference: &str) -> Option<&str> {
+    reference.strip_prefix(REFERENCE_PREFIX).filter(|account| !account.is_empty())
+}
+
+/// 把密语放进保险箱（已经是同一份就不重复写）
+pub fn store(account: &str, secret: &str) -> Result<()> {
+    if backend::get(account)?.as_deref() == Some(secret) {
+        return Ok(());
+    }
+    backend::set(account, secret).with_context(|| format!("把密语放进系统保险箱失败（{}）", account))
+}
+
+/// 从保险箱取出密语（没有这一项时返回 None）
+pub fn load(account: &str) -> Result<Option<String>> {
+    backend::get(account).with_context(|| format!("从系统保险箱取密语失败（{}）", account))
+}
+
+/// 从保险箱里删掉（本来就没有也算成功）
+pub fn delete(account: &str) -> Result<()> {
+    backend::delete(account).with_context(|| format!("从系统保险箱删除密语失败（{}）", account))
+}
+
+/// 取过、放过的密语（账户名 → 密语，None 表示保险箱里没有这一项；取失败的不记，下次再试）
+#[derive(Debug, Default)]
+pub struct Cache(Mutex<HashMap<String, Option<String>>>);
+
+impl Cache {
+    fn cached(&self, account: &str) -> Option<Option<String>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).get(account).cloned()
+    }
+
+    fn remember(&self, account: &str, secret: Option<&str>) {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(account.to_string(), secret.map(str::to_string));
+    }
+
+    /// 同 `store`，记得保险箱里已经是这一份时就不去敲门
+    pub fn store(&self, account: &str, secret: &str) -> Result<()> {
+        if self.cached(account).flatten().as_deref() == Some(secret) {
+            return Ok(());
+        }
+        store(account, secret)?;
+        self.remember(account, Some(secret));
+        Ok(())
+    }
+
+    /// 同 `load`，取过一次就不再去敲门
+    pub fn load(&self, account: &str) -> Result<Option<String>> {
+        if let Some(secret) = self.cached(account) {
+            return Ok(secret);
+        }
+        let secret = load(account)?;
+        self.remember(account, secret.as_deref());
+        Ok(secret)
+    }
+
+    /// 同 `delete`
+    pub fn delete(&self, account: &str) -> Result<()> {
+        delete(account)?;
+        self.remember(account, None);
+        Ok(())
+    }
+}
+
+/// 真正的系统保险箱
+#[cfg(not(test))]
+mod backend {
+    use super::SERVICE;
+    use keyring::{Entry, Error};
+
+    pub fn get(account: &str) -> keyring::Result<Option<String>> {
+        match Entry::new(SERVICE, account)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(Error::NoEntry) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn set(account: &str, secret: &str) -> keyring::Result<()> {
+        Entry::new(SERVICE, account)?.set_password(secret)
+    }
+
+    pub fn delete(account: &str) -> keyring::Result<()> {
+        match Entry::new(SERVICE, account)?.delete_credential() {
+            Ok(()) | Err(Error::NoEntry) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// 测试时不碰主人真正的保险箱，放在内存里（每个测试线程一个，互不干扰）
+#[cfg(test)]
+pub(crate) mod backend {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    thread_local! {
+        static VAULT: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+        static KNOCKS: RefCell<usize> = const { RefCell::new(0) };
+    }
+
+    /// 这个测试线程一共敲了几次保险箱的门（取、放都算）
+    pub fn knocks() -> usize {
+        KNOCKS.with(|knocks| *knocks.borrow())
+    }
+
+    pub fn get(account: &str) -> anyhow::Result<Option<String>> {
+        KNOCKS.with(|knocks| *knocks.borrow_mut() += 1);
+        Ok(VAULT.with(|vault| vault.borrow().get(account).cloned()))
+    }
+
+    pub fn set(account: &str, secret: &str) -> anyhow::Result<()> {
+        KNOCKS.with(|knocks| *knocks.borrow_mut() += 1);
+        VAULT.with(|vault| vault.borrow_mut().insert(account.to_string(), secret.to_string()));
+        Ok(())
+    }
+
+    pub fn delete(account: &str) -> anyhow::Result<()> {
+        VAULT.with(|vault| vault.borrow_mut().remove(account));
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+//! 网关模式（无人值守的站点网关，一个开关配齐）
+//!
+//! 把两边局域网连起来的网关机器没人守着，以前要自己凑齐好几样：开机就建立通道、掉线总是重连、
+//! 替同伴转发（让对面的路由能经过这里）、大事写进系统日志、再写一份开机启动的服务配置。
+//! 现在方案里打开 `server_mode` 就行：
+//! - 建立通道时在主人的指示上叠加这几项（`effective`），关掉开关就回到主人自己的设置；
+//! - 恩兔启动时自动用这份方案建立通道；
+//! - 可以导出一份服务配置（Linux 的 systemd 用户服务、Windows 的计划任务、macOS 的 LaunchAgent），
+//!   用 `--headless` 启动时不弹工作台，只留托盘；
+//! - 在 systemd 下（`Type=notify`）恩兔会报到并定期报平安，卡死了 systemd 会把它重新拉起来。
+use crate::config::{N2NConfig, ReconnectPolicy};
+
+/// 启动参数：不弹工作台，只在后台干活
+pub const HEADLESS_FLAG: &str = "--headless";
+
+/// 网关模式下掉线后重连的等待上限（秒），比默认的更急一点
+const SERVER_BACKOFF_MAX_SECS: u64 = 30;
+
+/// 启动参数里有没有 `--headless`
+pub fn is_headless(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == HEADLESS_FLAG)
+}
+
+/// 建立通道时真正用的指示（打开了网关模式就叠加上网关需要的几项）
+pub fn effective(config: &N2NConfig) -> N2NConfig {
+    if !config.server_mode {
+        return config.clone();
+    }
+    N2NConfig {
+        auto_connect: true,
+        reconnect_policy: ReconnectPolicy::Always,
+        reconnect_max_attempts: 0,
+        reconnect_backoff_max_secs: config.reconnect_backoff_max_secs.min(SERVER_BACKOFF_MAX_SECS),
+        packet_forwarding: true,
+        system_log: true,
+        supernode_recheck_secs: match config.supernode_recheck_secs {
+            0 => N2NConfig::default().supernode_recheck_secs,
+            secs => secs,
+        },
+        ..config.clone()
+    }
+}
+
+/// 一份开机启动的服务配置
+#[derive(Debug, Clone)]
+pub struct ServiceSetup {
+    /// 文件名
+    pub file_name: String,
+    /// 文件内容
+    pub content: String,
+    /// 装上它要执行的命令（`{path}` 换成导出的位置）
+    pub install: String,
+}
+
+/// 启动参数：不弹工作台，连上指定方案
+fn launch_args(profile: &str) -> [String; 3] {
+    [HEADLESS_FLAG.to_string(), "--connect".to_string(), profile.to_string()]
+}
+
+/// systemd 用户服务（恩兔要用到桌面会话里的托盘和系统保险箱，所以是用户服务）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn systemd_unit(exe: &str, profile: &str) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let args: Vec<String> = launch_args(profile).iter().map(|a| quote(a)).collect();
+    format!(
+        "[Unit]\n\
+         Description=N2N Maid gateway ({profile})\n\
+         After=graphical-session.target network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=notify\n\
+         ExecStart={exe} {args}\n\
+         Restart=always\n\
+         RestartSec=5\n\
+         WatchdogSec=60\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        profile = profile,
+        exe = quote(exe),
+        args = args.join(" "),
+    )
+}
+
+/// 给这份方案生成开机启动的服务配置
+#[cfg(target_os = "linux")]
+pub fn service_setup(exe: &str, profile: &str) -> ServiceSetup {
+    ServiceSetup {
+        file_name: "n2n-maid.service".to_string(),
+        content: systemd_unit(exe, profile),
+        install: "mkdir -p ~/.config/systemd/user && cp \"{path}\" ~/.config/systemd/user/n2n-maid.service \
+                  && systemctl --user daemon-reload && systemctl --user enable --now n2n-maid.service \
+                  && loginctl enable-linger $USER"
+            .to_string(),
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn service_setup(exe: &str, profile: &str) -> ServiceSetup {
+    // 登录后以最高权限启动（edge 要管理员权限才能建网卡）；配合系统的自动登录就是无人值守
+    let command = std::iter::once(exe.to_string())
+        .chain(launch_args(profile))
+        .map(|a| format!("\\\"{}\\\"", a))
+        .collect::<Vec<_>>()
+        .join(" ");
+    ServiceSetup {
+        file_name: "n2n-maid-gateway.cmd".to_string(),
+        content: format!(
+            "@echo off\r\nschtasks /Create /F /TN \"N2N Maid Gateway\" /SC ONLOGON /RL HIGHEST /TR \"{}\"\r\n",
+            command
+        ),
+        install: "\"{path}\"".to_string(),
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub fn service_setup(exe: &str, profile: &str) -> ServiceSetup {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;");
+    let args: String = std::iter::once(exe.to_string())
+        .chain(launch_args(profile))
+        .map(|a| format!("<string>{}</string>", escape(&a)))
+        .collect();
+    ServiceSetup {
+        file_name: "com.n2n.maid.gateway.plist".to_string(),
+        content: format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+             <plist version=\"1.0\"><dict>\n\
+             <key>Label</key><string>com.n2n.maid.gateway</string>\n\
+             <key>ProgramArguments</key><array>{}</array>\n\
+             <key>RunAtLoad</key><true/>\n\
+             <key>KeepAlive</key><true/>\n\
+             </dict></plist>\n",
+            args
+        ),
+        install: "cp \"{path}\" ~/Library/LaunchAgents/ && launchctl load -w ~/Library/LaunchAgents/com.n2n.maid.gateway.plist"
+            .to_string(),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub fn service_setup(_exe: &str, _profile: &str) -> ServiceSetup {
+    ServiceSetup {
+        file_name: String::new(),
+        content: String::new(),
+        install: String::new(),
+    }
+}
+
+/// 告诉 systemd 一声（`READY=1` 报到、`WATCHDOG=1` 报平安）；不是 systemd 拉起来的就什么也不做
+#[cfg(target_os = "linux")]
+pub fn notify(state: &str) {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let path = path.to_string_lossy().to_string();
+    let result = (|| -> std::io::Result<()> {
+        let socket = UnixDatagram::unbound()?;
+        // `@` 开头的是抽象命名空间里的套接字
+        match path.strip_prefix('@') {
+            Some(name) => socket.send_to_addr(state.as_bytes(), &SocketAddr::from_abstract_name(name)?),
+            None => socket.send_to(state.as_bytes(), &path),
+        }?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        log::debug!("告诉 systemd “{}”失败：{}", state, e);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn notify(_state: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_mode_overlay_and_unit() {
+        let mine = N2NConfig {
+            reconnect_max_attempts: 3,
+            supernode_recheck_secs: 0,
+            ..Default::default()
+        };
+        // 没打开时原样
+        assert_eq!(effective(&mine).reconnect_max_attempts, 3);
+
+        let gateway = effective(&N2NConfig { server_mode: true, ..mine });
+        assert!(gateway.auto_connect && gateway.packet_forwarding && gateway.system_log);
+        assert_eq!(gateway.reconnect_policy, ReconnectPolicy::Always);
+        assert_eq!((gateway.reconnect_max_attempts, gateway.reconnect_backoff_max_secs), (0, 30));
+        assert_eq!(gateway.supernode_recheck_secs, 300);
+
+        let unit = systemd_unit("/opt/N2N Maid/n2n-maid", "site \"A\"");
+        assert!(unit.contains("ExecStart=\"/opt/N2N Maid/n2n-maid\" \"--headless\" \"--connect\" \"site \\\"A\\\"\"\n"));
+        assert!(unit.contains("Type=notify\n") && unit.contains("WatchdogSec=60\n"));
+        assert!(is_headless(&["--headless".to_string()]));
+    }
+}
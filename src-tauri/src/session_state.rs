@@ -0,0 +1,164 @@
+//! 交班记录（把当前工作状态记在本子上，恩兔重启后第一眼就知道上次的情况）
+//!
+//! 恩兔崩溃或者升级重启后，以前总是从“待命”开始，主人完全不知道 edge 是还在跑、还是早就摔倒了。
+//! 现在工作期间会把状态、edge 的 PID、总部、暗号、虚拟地址记进配置目录的 `session.json`：
+//! - 正在工作时定期刷新（`updated_at` 就是恩兔最后一次确认的时间）；
+//! - 好好收工后删掉这一页，所以重启时还能读到记录，就说明上次没来得及收尾；
+//! - 重启时再看一眼记录里的 edge 是不是还活着，写进开门体检单交给前端。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 一页交班记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecord {
+    /// 最后确认的状态（connecting / connected / paused）
+    pub status: String,
+    /// edge 的进程号（暂停时为 None）
+    pub pid: Option<u32>,
+    pub supernode: String,
+    pub community: String,
+    /// 拿到的虚拟地址（还没连上时为 None）
+    pub virtual_ip: Option<String>,
+    /// 本次工作开始的时间（Unix 秒）
+    pub started_at: u64,
+    /// 最后一次刷新记录的时间（Unix 秒）
+    pub updated_at: u64,
+}
+
+/// 重启时看到的上一次工作
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastSession {
+    #[serde(flatten)]
+    pub record: SessionRecord,
+    /// 记录里的 edge 现在还在不在
+    pub still_running: bool,
+}
+
+/// 交班本
+pub struct SessionStateStore {
+    path: PathBuf,
+}
+
+impl SessionStateStore {
+    /// 翻开交班本（和指示簿放在同一个目录）
+    pub fn new() -> Result<Self> {
+        let dir = dirs::config_dir()
+            .context("无法获取配置目录")?
+            .join("n2n-maid");
+        fs::create_dir_all(&dir)?;
+        Ok(Self::at(dir.join("session.json")))
+    }
+
+    /// 指定交班本的位置
+    pub fn at(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 读出上一页记录（没有或者写坏了都当没有）
+    pub fn load(&self) -> Option<SessionRecord> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 记下当前工作
+    pub fn save(&self, record: &SessionRecord) -> Result<()> {
+        let content = serde_json::to_string_pretty(record).context("序列化交班记录失败")?;
+        fs::write(&self.path, content).context("写入交班记录失败")
+    }
+
+    /// 好好收工了：撕掉这一页
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).context("删除交班记录失败"),
+        }
+    }
+}
+
+impl SessionRecord {
+    /// 除了刷新时间以外有没有变化（没变化就不用频繁写盘）
+    pub fn same_state(&self, other: &SessionRecord) -> bool {
+        SessionRecord { updated_at: 0, ..self.clone() } == SessionRecord { updated_at: 0, ..other.clone() }
+    }
+
+    /// 重启时检查记录里的 edge 还在不在（`mgmt_alive`：管理口上有没有 edge 应答）
+    pub fn inspect(self, mgmt_alive: bool) -> LastSession {
+        let still_running = mgmt_alive || self.pid.is_some_and(process_alive);
+        LastSession {
+            record: self,
+            still_running,
+        }
+    }
+}
+
+/// 这个进程号现在有没有人在用
+#[cfg(unix)]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    use nix::sys::signal::kill;
+    use nix::unistd::Pid;
+    // 信号 0 只检查进程在不在；EPERM 说明进程在，只是不归我们管（比如 root 的 edge）
+    match kill(Pid::from_raw(pid as i32), None) {
+        Ok(()) => true,
+        Err(e) => e == nix::errno::Errno::EPERM,
+    }
+}
+
+/// Windows：打开进程看一眼退出码（还是 STILL_ACTIVE 就是还在）
+#[cfg(windows)]
+pub(crate) fn process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, STILL_ACTIVE};
+    use windows_sys::Win32::System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return false;
+        }
+        let mut code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut code) != 0;
+        CloseHandle(handle);
+        ok && code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_record_round_trip_and_inspect() {
+        let dir = TempDir::new("session");
+        let path = dir.join("session.json");
+        let store = SessionStateStore::at(path.clone());
+        let _ = store.clear();
+        assert_eq!(store.load(), None);
+
+        let record = SessionRecord {
+            status: "connected".to_string(),
+            pid: Some(std::process::id()),
+            supernode: "vpn.example.com:7777".to_string(),
+            community: "home".to_string(),
+            virtual_ip: Some("10.0.0.2".to_string()),
+            started_at: 100,
+            updated_at: 160,
+        };
+        store.save(&record).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded, record);
+        assert!(loaded.same_state(&SessionRecord { updated_at: 999, ..record.clone() }));
+
+        // 记录里的进程就是测试进程自己，当然还活着
+        #[cfg(unix)]
+        assert!(loaded.clone().inspect(false).still_running);
+        assert!(!SessionRecord { pid: None, ..loaded }.inspect(false).still_running);
+
+        store.clear().unwrap();
+        assert_eq!(store.load(), None);
+    }
+}
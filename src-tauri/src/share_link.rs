@@ -0,0 +1,197 @@
+//! 邀请链接（把一份方案压成一行字，扫码或粘贴就能用）
+//!
+//! 格式和启动参数里认的邀请链接一样，都是 `n2n-maid://join?...`：
+//! - `name`：方案名字（可省略）
+//! - `supernode`：总部地址（host:port）
+//! - `dns`：按域名找总部时的域名（有它时可以不带 `supernode` 和 `community`）
+//! - `community`：工作暗号
+//! - `key`：保密密语（可省略）
+//! - `ip`：`dhcp` 或者手动指定的地址（如 `10.0.0.2/24`），省略时为 `dhcp`
+//! - `at`：换密语的邀请才有，约好换用 `key` 的时间（Unix 秒）
+//!
+//! 全部字段都做百分号编码，整行字可以直接塞进二维码，也可以贴进聊天软件。
+//! 只分享建立通道必需的几项，edge 路径、网卡名这些本机设置不会跟着走。
+use anyhow::{Context, Result};
+
+use crate::config::N2NConfig;
+use crate::portable::PortableProfile;
+
+/// 邀请链接的开头
+const LINK_PREFIX: &str = "n2n-maid://join?";
+
+/// 把一份方案压成邀请链接
+pub fn encode(name: &str, config: &N2NConfig) -> String {
+    encode_with(name, config, None)
+}
+
+/// 换密语的邀请：带着新密语和约好换用的时间，对方收下后也是到点才换
+pub fn encode_rotation(name: &str, config: &N2NConfig, new_key: &str, switch_at: u64) -> String {
+    let config = N2NConfig {
+        encryption_key: new_key.to_string(),
+        ..config.clone()
+    };
+    encode_with(name, &config, Some(switch_at))
+}
+
+fn encode_with(name: &str, config: &N2NConfig, switch_at: Option<u64>) -> String {
+    let ip = match config.ip_mode.as_str() {
+        "static" => config.static_ip.clone().unwrap_or_default(),
+        _ => "dhcp".to_string(),
+    };
+    let mut params = vec![
+        ("name", name.to_string()),
+        ("supernode", config.supernode.clone()),
+        ("community", config.community.clone()),
+    ];
+    if let Some(domain) = config.supernode_dns.as_deref().filter(|d| !d.trim().is_empty()) {
+        params.push(("dns", domain.trim().to_string()));
+    }
+    if !config.encryption_key.is_empty() {
+        params.push(("key", config.encryption_key.clone()));
+    }
+    params.push(("ip", ip));
+    if let Some(switch_at) = switch_at {
+        params.push(("at", switch_at.to_string()));
+    }
+
+    let query: Vec<String> = params
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| format!("{}={}", key, percent_encode(value)))
+        .collect();
+    format!("{}{}", LINK_PREFIX, query.join("&"))
+}
+
+/// 把邀请链接还原成一份方案（也认不带 `n2n-maid://join?` 开头、直接粘贴的参数部分）
+pub fn decode(link: &str) -> Result<PortableProfile> {
+    let link = link.trim();
+    let query = link
+        .strip_prefix(LINK_PREFIX)
+        .or_else(|| link.strip_prefix("n2n-maid://join/?"))
+        .unwrap_or(link);
+
+    let mut name = None;
+    let mut switch_at = None;
+    let mut config = N2NConfig::default();
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value).with_context(|| format!("邀请链接里的“{}”编码不对", key))?;
+        match key {
+            "name" => name = Some(value),
+            "supernode" => config.supernode = value,
+            "community" => config.community = value,
+            "dns" => config.supernode_dns = Some(value).filter(|v| !v.trim().is_empty()),
+            "key" => config.encryption_key = value,
+            "ip" if value.is_empty() || value == "dhcp" => config.ip_mode = "dhcp".to_string(),
+            "ip" => {
+                config.ip_mode = "static".to_string();
+                config.static_ip = Some(value);
+            }
+            "at" => switch_at = Some(value.parse().with_context(|| format!("邀请链接里的换用时间“{}”不对", value))?),
+            // 以后的版本可能会加新字段，不认识的先跳过
+            _ => {}
+        }
+    }
+
+    let by_dns = config.supernode_dns.is_some();
+    let (host, port) = config.supernode.rsplit_once(':').unwrap_or(("", ""));
+    if !by_dns && (host.is_empty() || port.parse::<u16>().is_err()) {
+        anyhow::bail!("邀请链接里的总部地址不对（需要 host:port）");
+    }
+    if !by_dns && config.community.is_empty() {
+        anyhow::bail!("邀请链接里没有工作暗号");
+    }
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| Some(config.community.clone()).filter(|c| !c.is_empty()))
+        .or_else(|| config.supernode_dns.clone())
+        .unwrap_or_default();
+    Ok(PortableProfile { name, config, switch_at })
+}
+
+/// 百分号编码（只留下 URL 里不用转义的字符）
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// 百分号解码（`+` 也当成空格，兼容表单风格的链接）
+fn percent_decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value.get(i + 1..i + 3).context("% 后面少了两位十六进制数")?;
+                out.push(u8::from_str_radix(hex, 16).context("% 后面不是十六进制数")?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).context("解码后不是有效的 UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_link_round_trip() {
+        let config = N2NConfig {
+            supernode: "vpn.example.com:7777".to_string(),
+            community: "办公室".to_string(),
+            encryption_key: "p&ss=w0rd".to_string(),
+            ip_mode: "static".to_string(),
+            static_ip: Some("10.0.0.2/24".to_string()),
+            edge_path: Some("/opt/edge".to_string()),
+            ..Default::default()
+        };
+        let link = encode("Office", &config);
+        assert!(link.starts_with("n2n-maid://join?name=Office&supernode=vpn.example.com%3A7777"));
+        assert!(!link.contains("/opt/edge"));
+
+        let decoded = decode(&link).unwrap();
+        assert_eq!(decoded.name, "Office");
+        assert_eq!(decoded.config.supernode, config.supernode);
+        assert_eq!(decoded.config.community, config.community);
+        assert_eq!(decoded.config.encryption_key, config.encryption_key);
+        assert_eq!(decoded.config.static_ip, config.static_ip);
+        assert_eq!(decoded.config.edge_path, None);
+
+        // 直接粘贴参数部分也认，缺名字时用暗号当名字
+        let pasted = decode("  supernode=1.2.3.4%3A7654&community=lab&ip=dhcp\n").unwrap();
+        assert_eq!(pasted.name, "lab");
+        assert_eq!(pasted.config.ip_mode, "dhcp");
+
+        assert!(decode("n2n-maid://join?community=lab").is_err());
+        assert_eq!(decoded.switch_at, None);
+
+        // 换密语的邀请：带着新密语和换用时间
+        let rotation = decode(&encode_rotation("Office", &config, "n3w key", 1_900_000_000)).unwrap();
+        assert_eq!(rotation.config.encryption_key, "n3w key");
+        assert_eq!(rotation.switch_at, Some(1_900_000_000));
+        assert!(decode("supernode=a%3A1&community=lab&at=soon").is_err());
+
+        // 按域名找总部的邀请：可以不带总部地址和暗号
+        let by_dns = decode("dns=vpn.example.com").unwrap();
+        assert_eq!(by_dns.name, "vpn.example.com");
+        assert_eq!(by_dns.config.supernode_dns.as_deref(), Some("vpn.example.com"));
+        assert!(decode("n2n-maid://join?supernode=a%3A1&community=%E4").is_err());
+    }
+}
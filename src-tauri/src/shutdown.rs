@@ -0,0 +1,115 @@
+//! 关门流程（主人要走时，先等 edge 收拾好工具再关门）
+//!
+//! 以前只有窗口的 CloseRequested 会拦一下：托盘选“退出”要先把窗口叫出来再关，窗口不在就直接
+//! `app.exit`，edge 被留在后台没人管。现在窗口关闭、托盘退出、系统要求退出（`RunEvent::ExitRequested`）
+//! 都走 `begin`，整个流程在后端跑完，界面只收到 `app-exit-waiting` 显示等待提示，它卡住了也不耽误关门：
+//! - 只开工一次：主人连点几下退出，也只收拾一遍；
+//! - 先温柔地请主通道停下，过了 `stop_timeout_secs` 还不走就强制清理，副通道一起收起；
+//! - 收拾完把 IP 转发恢复原样，再真的退出；
+//! - 兜底：超过 `stop_timeout_secs + HARD_DEADLINE_GRACE` 还没收拾完（比如哪把锁卡住了），
+//!   按进程号强制清理 edge，然后直接退出。
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::events::{AssistantMessage, Severity};
+use crate::operation::OpError;
+use crate::AppState;
+
+/// 正常流程之外再多等多久就启用兜底
+const HARD_DEADLINE_GRACE: Duration = Duration::from_secs(10);
+
+/// 关门流程已经开始了
+static STARTED: AtomicBool = AtomicBool::new(false);
+/// 收拾完了，可以真的退出
+static READY: AtomicBool = AtomicBool::new(false);
+
+/// 还有 edge 在工作吗（主通道或者副通道）
+fn busy(app: &AppHandle) -> bool {
+    let state = app.state::<AppState>();
+    let running = state.process.lock().unwrap().is_running();
+    running || state.connections.any_running()
+}
+
+/// 现在能不能直接退出（已经收拾完了，或者本来就没在工作）
+pub fn may_exit(app: &AppHandle) -> bool {
+    READY.load(Ordering::SeqCst) || (!STARTED.load(Ordering::SeqCst) && !busy(app))
+}
+
+/// 开始关门：收拾好 edge 后退出（重复调用只算一次）
+pub fn begin(app: &AppHandle) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let state = app.state::<AppState>();
+    // 说好是真的要退出，免得被“关窗口躲进托盘”拦下来
+    state.quitting.store(true, Ordering::SeqCst);
+    if !busy(app) {
+        finish(app);
+        return;
+    }
+
+    log::info!("主人要关门啦，恩兔先把 edge 收拾好");
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit("app-exit-waiting", ());
+    }
+    let _ = app.emit("assistant-message", AssistantMessage::new("maid_exit_waiting", Severity::Info));
+
+    let (pid, timeout) = {
+        let process = state.process.lock().unwrap();
+        if !process.is_running() {
+            // 只剩副通道在工作，下面的收拾线程会一起收起
+        } else if let Err(e) = process.stop() {
+            // 已经在收拾工具的话就安心等它收完，下面的收拾线程超时后会再强制清理
+            if !matches!(e.downcast_ref::<OpError>(), Some(OpError::AlreadyStopping)) {
+                let _ = app.emit(
+                    "assistant-message",
+                    AssistantMessage::new("maid_graceful_stop_failed", Severity::Warning).with("error", e.to_string()),
+                );
+                let _ = process.stop_force();
+            }
+        }
+        (process.pid(), process.stop_timeout())
+    };
+
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let deadline_app = app.clone();
+    thread::spawn(move || {
+        if done_rx.recv_timeout(timeout + HARD_DEADLINE_GRACE).is_ok() {
+            return;
+        }
+        log::error!("关门迟迟收拾不完，恩兔按进程号强制清理 edge 后直接退出");
+        if let Some(pid) = pid {
+            if let Err(e) = crate::orphans::terminate(pid) {
+                log::warn!("强制清理 edge（PID: {}）失败：{}", pid, e);
+            }
+        }
+        finish(&deadline_app);
+    });
+
+    let app = app.clone();
+    thread::spawn(move || {
+        // 给 edge 一点时间收尾（stop_timeout_secs），不肯走就强制清理，别留下残影进程
+        let state = app.state::<AppState>();
+        let process = state.process.lock().unwrap().clone();
+        process.wait_stopped_or_force();
+        state.connections.stop_all();
+        // 中转小工人可能来不及收尾，走之前把 IP 转发恢复原样
+        state.ip_forwarding.sync(None);
+        let _ = done_tx.send(());
+        finish(&app);
+    });
+}
+
+/// 真的退出（只退一次）
+fn finish(app: &AppHandle) {
+    if READY.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    log::info!("收拾完毕，恩兔下班啦");
+    app.exit(0);
+}
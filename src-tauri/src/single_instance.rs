@@ -0,0 +1,232 @@
+//! 恩兔的“门铃”（单实例 + 启动参数转交）
+//!
+//! 同一时间只该有一位恩兔在管 edge：第二次启动（比如点了邀请链接、或者脚本里带了 `--connect`）时，
+//! 新进程不会再开一个工作台，而是按门铃把启动参数交给已经在岗的恩兔，然后自己安静退场。
+//!
+//! 实现：在岗的实例监听 127.0.0.1 上的随机端口，把“端口 + 口令”写进配置目录的 `instance.lock`；
+//! 后来的实例读到它就连过去，先报口令，再递上一行 JSON 参数列表。
+//! 口令文件只有主人自己能读，其他本机用户没法冒充主人按门铃。
+use anyhow::{Context, Result};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// 门铃连接/读取的超时
+const BELL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 启动参数里恩兔看得懂的动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchAction {
+    /// `--connect [方案名]`：（换到指定方案并）建立通道
+    Connect { profile: Option<String> },
+    /// `n2n-maid://...` 邀请链接
+    DeepLink(String),
+}
+
+/// 在岗恩兔守着的门铃
+pub struct Doorbell {
+    listener: TcpListener,
+    token: String,
+}
+
+/// 抢门铃的结果
+pub enum Claim {
+    /// 恩兔是第一位：拿着门铃等后来者来按
+    Primary(Doorbell),
+    /// 已经有恩兔在岗，参数已经转交过去了
+    Forwarded,
+}
+
+/// 门铃记录文件的位置
+fn lock_path() -> Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .context("无法获取配置目录")?
+        .join("n2n-maid");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join("instance.lock"))
+}
+
+/// 生成一次性的门铃口令
+fn new_token() -> String {
+    let a = RandomState::new().build_hasher().finish();
+    let mut h = RandomState::new().build_hasher();
+    h.write_u32(std::process::id());
+    format!("{:016x}{:016x}", a, h.finish())
+}
+
+/// 启动时先按一下门铃：有人在岗就把参数交过去，否则自己上岗
+pub fn claim(args: &[String]) -> Result<Claim> {
+    claim_at(&lock_path()?, args)
+}
+
+fn claim_at(path: &Path, args: &[String]) -> Result<Claim> {
+    if let Ok(content) = fs::read_to_string(path) {
+        if let Some((port, token)) = parse_lock(&content) {
+            if forward(port, token, args).is_ok() {
+                return Ok(Claim::Forwarded);
+            }
+            // 记录还在但门铃没人应：上一位恩兔大概是摔倒了没收拾，直接接班
+        }
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).context("无法监听本机门铃端口")?;
+    let port = listener.local_addr()?.port();
+    let token = new_token();
+    write_lock(path, &format!("{} {}\n", port, token))?;
+    Ok(Claim::Primary(Doorbell { listener, token }))
+}
+
+fn parse_lock(content: &str) -> Option<(u16, &str)> {
+    let mut parts = content.split_whitespace();
+    let port = parts.next()?.parse().ok()?;
+    let token = parts.next()?;
+    Some((port, token))
+}
+
+#[cfg(unix)]
+fn write_lock(path: &Path, content: &str) -> Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .context("写入门铃记录失败")?;
+    // `mode` 只管新建的文件：以前留下的那份也要收紧，门铃口令别让别人看见
+    file.set_permissions(fs::Permissions::from_mode(0o600)).context("收紧门铃记录的权限失败")?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_lock(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).context("写入门铃记录失败")
+}
+
+/// 把参数交给在岗的恩兔
+fn forward(port: u16, token: &str, args: &[String]) -> Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, BELL_TIMEOUT)?;
+    stream.set_write_timeout(Some(BELL_TIMEOUT))?;
+    stream.set_read_timeout(Some(BELL_TIMEOUT))?;
+    writeln!(stream, "{}", token)?;
+    writeln!(stream, "{}", serde_json::to_string(args)?)?;
+    stream.flush()?;
+
+    // 等对方回一声“收到”，确认按的确实是恩兔家的门铃
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    if reply.trim() != "ok" {
+        anyhow::bail!("门铃那头没有确认收到");
+    }
+    Ok(())
+}
+
+/// 派一位小工人守着门铃：每收到一份参数就交给 `on_args`
+pub fn serve<F>(bell: Doorbell, on_args: F)
+where
+    F: Fn(Vec<String>) + Send + 'static,
+{
+    let Doorbell { listener, token } = bell;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            match read_request(&stream, &token) {
+                Ok(args) => {
+                    let _ = (&stream).write_all(b"ok\n");
+                    log::info!("另一个恩兔把启动参数交过来了：{:?}", args);
+                    on_args(args);
+                }
+                Err(e) => log::warn!("门铃响了但来意不明，已忽略：{}", e),
+            }
+        }
+    });
+}
+
+fn read_request(stream: &TcpStream, token: &str) -> Result<Vec<String>> {
+    stream.set_read_timeout(Some(BELL_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if line.trim() != token {
+        anyhow::bail!("口令不对");
+    }
+    line.clear();
+    reader.read_line(&mut line)?;
+    Ok(serde_json::from_str(line.trim())?)
+}
+
+/// 从启动参数里挑出恩兔看得懂的动作（不认识的参数直接忽略）
+pub fn parse_launch_args(args: &[String]) -> Vec<LaunchAction> {
+    let mut actions = Vec::new();
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if arg == "--connect" {
+            let profile = iter
+                .next_if(|next| !next.starts_with("--") && !next.starts_with("n2n-maid://"))
+                .cloned();
+            actions.push(LaunchAction::Connect { profile });
+        } else if let Some(profile) = arg.strip_prefix("--connect=") {
+            actions.push(LaunchAction::Connect {
+                profile: Some(profile.to_string()).filter(|p| !p.is_empty()),
+            });
+        } else if arg.starts_with("n2n-maid://") {
+            actions.push(LaunchAction::DeepLink(arg.clone()));
+        }
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_launch_args() {
+        assert_eq!(
+            parse_launch_args(&args(&["--connect", "office", "n2n-maid://join?community=c"])),
+            vec![
+                LaunchAction::Connect { profile: Some("office".to_string()) },
+                LaunchAction::DeepLink("n2n-maid://join?community=c".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_launch_args(&args(&["--connect", "--verbose"])),
+            vec![LaunchAction::Connect { profile: None }]
+        );
+        assert_eq!(
+            parse_launch_args(&args(&["--connect=home"])),
+            vec![LaunchAction::Connect { profile: Some("home".to_string()) }]
+        );
+        assert!(parse_launch_args(&args(&["--unknown"])).is_empty());
+    }
+
+    #[test]
+    fn test_second_claim_forwards_to_primary() {
+        let dir = TempDir::new("instance");
+        let path = dir.join("instance.lock");
+
+        let Claim::Primary(bell) = claim_at(&path, &[]).unwrap() else {
+            panic!("第一位应该上岗");
+        };
+        let server = thread::spawn(move || {
+            let (stream, _) = bell.listener.accept().unwrap();
+            let got = read_request(&stream, &bell.token).unwrap();
+            (&stream).write_all(b"ok\n").unwrap();
+            got
+        });
+
+        assert!(matches!(claim_at(&path, &args(&["--connect"])).unwrap(), Claim::Forwarded));
+        assert_eq!(server.join().unwrap(), args(&["--connect"]));
+    }
+}
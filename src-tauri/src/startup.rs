@@ -0,0 +1,125 @@
+//! 开门体检单（启动时的一次性汇总报告）
+//!
+//! 恩兔每次上岗都会把“指示簿读没读好、工具齐不齐、权限够不够、edge 是哪个版本、
+//! 有没有上次留下的 edge 还在值班”写成一张体检单，通过 `startup-report` 事件递给前端，
+//! 前端只要画一条状态横幅就好，不用再挨个调五六个命令。
+use serde::Serialize;
+use std::path::Path;
+
+use crate::config::ConfigManager;
+use crate::elevation;
+use crate::mgmt;
+use crate::n2n_process::{self, N2NProcess};
+use crate::session_state::{LastSession, SessionRecord};
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessCheck {
+    /// 检查项编号（edge_found / tap_driver / elevation）
+    pub id: String,
+    pub ok: bool,
+    /// 补充说明（i18n 编号或原始信息）
+    pub detail: Option<String>,
+}
+
+/// 开门体检单
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReport {
+    /// 指示簿是否读取成功
+    pub config_ok: bool,
+    /// 读取/迁移失败时的原因
+    pub config_error: Option<String>,
+    /// 这次启动是否把旧版配置迁移成了方案
+    pub config_migrated: bool,
+    /// 当前方案名字
+    pub active_profile: Option<String>,
+    /// 方案数量
+    pub profile_count: usize,
+    /// 各项就绪检查
+    pub checks: Vec<ReadinessCheck>,
+    /// 开机自启状态（None 表示当前平台/版本还不支持）
+    pub autostart: Option<bool>,
+    /// 实际会使用的 edge 路径
+    pub edge_path: String,
+    /// 检测到的 edge 版本
+    pub edge_version: Option<String>,
+    /// 管理口上是否已有上次留下的 edge 在值班
+    pub previous_session_found: bool,
+    /// 上次没来得及收尾的工作（交班本里还留着记录时才有）
+    pub last_session: Option<LastSession>,
+}
+
+/// 填写开门体检单（会调用 `edge -h`，可能要等一两秒，别放在界面线程里）
+pub fn collect(
+    manager: &ConfigManager,
+    migration: Result<bool, String>,
+    last_session: Option<SessionRecord>,
+) -> StartupReport {
+    let (config_migrated, mut config_error) = match migration {
+        Ok(migrated) => (migrated, None),
+        Err(e) => (false, Some(e)),
+    };
+
+    let store = manager.load_store();
+    let (active_profile, profile_count, config) = match store {
+        Ok(store) => (
+            Some(store.active_profile.clone()),
+            store.profiles.len(),
+            store.active_config(),
+        ),
+        Err(e) => {
+            config_error.get_or_insert(e.to_string());
+            (None, 0, Default::default())
+        }
+    };
+
+    let edge_path = config
+        .edge_path
+        .clone()
+        .unwrap_or_else(N2NProcess::default_edge_path);
+    let edge_version = n2n_process::detect_edge_version(&edge_path);
+
+    let mut checks = vec![ReadinessCheck {
+        id: "edge_found".to_string(),
+        ok: edge_version.is_some() || edge_exists(&edge_path),
+        detail: Some(edge_path.clone()),
+    }];
+
+    #[cfg(target_os = "windows")]
+    checks.push(ReadinessCheck {
+        id: "tap_driver".to_string(),
+        ok: crate::windows_ready::is_tap_windows_installed().unwrap_or(false),
+        detail: None,
+    });
+
+    let report = elevation::check(&config);
+    checks.push(ReadinessCheck {
+        id: "elevation".to_string(),
+        ok: !report.required || report.elevated,
+        detail: report.advice.first().cloned(),
+    });
+
+    let previous_session_found =
+        mgmt::edge_already_listening(config.management_port.unwrap_or(n2n_process::DEFAULT_MGMT_PORT));
+
+    StartupReport {
+        config_ok: config_error.is_none(),
+        config_error,
+        config_migrated,
+        active_profile,
+        profile_count,
+        checks,
+        autostart: crate::autostart::is_enabled(),
+        edge_path,
+        edge_version,
+        previous_session_found,
+        last_session: last_session.map(|record| record.inspect(previous_session_found)),
+    }
+}
+
+/// edge 是否存在（显式路径看文件，裸命令名去 PATH 里找）
+fn edge_exists(edge_path: &str) -> bool {
+    Path::new(edge_path).exists() || which::which(edge_path).is_ok()
+}
@@ -0,0 +1,130 @@
+//! 状态快照（定期把当前状态写进一个文件，给外面的脚本和监控程序看）
+//!
+//! 遥控口要开 HTTP 接口、发令牌，有些环境不方便（或者监控程序只会读文件）。
+//! 主人在指示里填上 `status_snapshot_path` 后，恩兔每隔 `status_snapshot_interval_secs` 秒把状态写进去：
+//! - 文件名以 `.prom` 结尾时写成 Prometheus 文本格式（可以直接交给 node_exporter 的 textfile collector）；
+//! - 其余一律写成 JSON，内容和遥控口 `GET /v1/status` 一样，另外带上 `updatedAt`（Unix 秒）。
+//!
+//! 先写到旁边的临时文件再改名，读的一方不会读到写了一半的内容。
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 快照的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Prometheus,
+}
+
+impl Format {
+    /// 按文件名挑写法（`.prom` 写成 Prometheus 文本格式，其余写 JSON）
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("prom") => Format::Prometheus,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// 把状态（`status_json` 的结果）写进快照文件
+pub fn write(path: &Path, status: &serde_json::Value, now: u64) -> Result<()> {
+    let content = render(Format::for_path(path), status, now)?;
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("无法创建快照目录 {}", dir.display()))?;
+    }
+    let tmp = temp_path(path);
+    fs::write(&tmp, content).with_context(|| format!("写入状态快照失败：{}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("写入状态快照失败：{}", path.display()))
+}
+
+/// 按写法整理出快照内容
+pub fn render(format: Format, status: &serde_json::Value, now: u64) -> Result<String> {
+    match format {
+        Format::Json => {
+            let mut snapshot = status.clone();
+            snapshot["updatedAt"] = serde_json::json!(now);
+            serde_json::to_string_pretty(&snapshot).context("序列化状态快照失败")
+        }
+        Format::Prometheus => Ok(prometheus(status, now)),
+    }
+}
+
+/// 写到一半时用的临时文件（和快照放在同一个目录，改名才是原子的）
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Prometheus 文本格式
+fn prometheus(status: &serde_json::Value, now: u64) -> String {
+    const STATES: &[&str] =
+        &["disconnected", "connecting", "connected", "degraded", "disconnecting", "paused", "reconnecting", "error"];
+    let current = status["status"].as_str().unwrap_or("disconnected");
+    let mut out = String::new();
+
+    out.push_str("# HELP n2n_maid_up Whether the n2n tunnel is connected.\n# TYPE n2n_maid_up gauge\n");
+    out.push_str(&format!("n2n_maid_up {}\n", u8::from(current == "connected")));
+
+    out.push_str("# HELP n2n_maid_status Current connection state (1 for the active state).\n# TYPE n2n_maid_status gauge\n");
+    for state in STATES {
+        out.push_str(&format!("n2n_maid_status{{status=\"{}\"}} {}\n", state, u8::from(*state == current)));
+    }
+
+    out.push_str("# HELP n2n_maid_info Node name and virtual address of this edge.\n# TYPE n2n_maid_info gauge\n");
+    out.push_str(&format!(
+        "n2n_maid_info{{node_name=\"{}\",ip=\"{}\"}} 1\n",
+        escape_label(status["nodeName"].as_str().unwrap_or("")),
+        escape_label(status["networkInfo"]["ip"].as_str().unwrap_or(""))
+    ));
+
+    out.push_str("# HELP n2n_maid_reconnect_suspended Whether automatic reconnects are suspended.\n# TYPE n2n_maid_reconnect_suspended gauge\n");
+    out.push_str(&format!(
+        "n2n_maid_reconnect_suspended {}\n",
+        u8::from(!status["reconnectSuspendedUntil"].is_null())
+    ));
+
+    out.push_str("# HELP n2n_maid_snapshot_timestamp_seconds When this snapshot was written.\n# TYPE n2n_maid_snapshot_timestamp_seconds gauge\n");
+    out.push_str(&format!("n2n_maid_snapshot_timestamp_seconds {}\n", now));
+    out
+}
+
+/// 标签值里的反斜杠、引号和换行要转义
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_snapshot_formats() {
+        let status = serde_json::json!({
+            "status": "connected",
+            "error": null,
+            "networkInfo": { "ip": "10.0.0.2", "mask": "255.255.255.0", "mac": "" },
+            "nodeName": "lab \"pc\"",
+            "reconnectSuspendedUntil": null
+        });
+        assert_eq!(Format::for_path(Path::new("/var/lib/node_exporter/n2n.PROM")), Format::Prometheus);
+        assert_eq!(Format::for_path(Path::new("status.json")), Format::Json);
+
+        let text = render(Format::Prometheus, &status, 1_700_000_000).unwrap();
+        assert!(text.contains("n2n_maid_up 1\n"));
+        assert!(text.contains("n2n_maid_status{status=\"connected\"} 1\n"));
+        assert!(text.contains("n2n_maid_status{status=\"error\"} 0\n"));
+        assert!(text.contains("n2n_maid_info{node_name=\"lab \\\"pc\\\"\",ip=\"10.0.0.2\"} 1\n"));
+        assert!(text.contains("n2n_maid_snapshot_timestamp_seconds 1700000000\n"));
+
+        let dir = TempDir::new("snapshot");
+        let path = dir.join("status.json");
+        write(&path, &status, 42).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["status"], "connected");
+        assert_eq!(saved["updatedAt"], 42);
+        assert!(!temp_path(&path).exists());
+    }
+}
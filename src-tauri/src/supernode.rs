@@ -0,0 +1,157 @@
+//! 自家总部值班记录（本机 supernode 的使用统计）
+//!
+//! 恩兔酱目前还不会亲自“开分店”（没有内置 supernode），但很多主人会在同一台机器上自己跑一个 supernode。
+//! 这里借用和 edge 一样的 UDP Management API（supernode 默认门牌号 5645），把各小组的在岗人数、
+//! 收发包统计整理成一份“值班记录”，交给托管面板展示。
+//!
+//! 备注：supernode 的管理口只提供全局的收发包计数，没有按小组拆分的流量；
+//! 所以 `communities` 里只有在岗人数，流量统一放在 `packets` 里。
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::mgmt::query_rows_json_at;
+
+/// supernode 管理口的默认门牌号
+pub const DEFAULT_SUPERNODE_MGMT_PORT: u16 = 5645;
+
+/// 单个小组（community）的在岗情况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunityStats {
+    /// 小组暗号
+    pub name: String,
+    /// 当前登记在册的 edge 数量
+    pub edge_count: u32,
+    /// 是否为联邦（federation）内部小组
+    pub is_federation: bool,
+}
+
+/// 一类包的收发计数（forward / broadcast / reg_super / errors）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PacketStats {
+    pub kind: String,
+    pub tx_pkt: u64,
+    pub rx_pkt: u64,
+}
+
+/// 总部值班记录（交给前端的完整快照）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupernodeStats {
+    /// 查询的管理口端口
+    pub port: u16,
+    /// 各小组在岗人数
+    pub communities: Vec<CommunityStats>,
+    /// 收发包统计（全局）
+    pub packets: Vec<PacketStats>,
+    /// supernode 启动时间（Unix 秒，如果能拿到的话）
+    pub start_time: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SnCommunityRow {
+    community: String,
+    is_federation: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SnEdgeRow {
+    community: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SnPacketRow {
+    #[serde(rename = "type")]
+    kind: String,
+    tx_pkt: Option<u64>,
+    rx_pkt: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SnTimestampsRow {
+    start_time: u64,
+}
+
+/// 去本机 supernode 的管理口抄一份值班记录
+pub fn query_supernode_stats(port: u16, password: Option<&str>) -> Result<SupernodeStats> {
+    let addr = ("127.0.0.1", port);
+
+    let communities: Vec<SnCommunityRow> = parse_rows(query_rows_json_at(addr, "communities", password)?);
+    let edges: Vec<SnEdgeRow> = parse_rows(query_rows_json_at(addr, "edges", password)?);
+    let packets: Vec<SnPacketRow> = parse_rows(query_rows_json_at(addr, "packetstats", password)?);
+    // 有些旧版 supernode 没有 timestamps，拿不到也不算失败
+    let start_time = query_rows_json_at(addr, "timestamps", password)
+        .ok()
+        .and_then(|rows| parse_rows::<SnTimestampsRow>(rows).pop())
+        .map(|t| t.start_time);
+
+    Ok(SupernodeStats {
+        port,
+        communities: aggregate_communities(&communities, &edges),
+        packets: packets
+            .into_iter()
+            .map(|p| PacketStats {
+                kind: p.kind,
+                tx_pkt: p.tx_pkt.unwrap_or(0),
+                rx_pkt: p.rx_pkt.unwrap_or(0),
+            })
+            .collect(),
+        start_time,
+    })
+}
+
+fn parse_rows<T: serde::de::DeserializeOwned>(rows: Vec<serde_json::Value>) -> Vec<T> {
+    rows.into_iter()
+        .filter_map(|row| serde_json::from_value(row).ok())
+        .collect()
+}
+
+/// 按小组点名：communities 给出小组名册，edges 给出每位 edge 所属的小组
+fn aggregate_communities(communities: &[SnCommunityRow], edges: &[SnEdgeRow]) -> Vec<CommunityStats> {
+    let mut by_name: BTreeMap<String, CommunityStats> = BTreeMap::new();
+    for c in communities {
+        by_name.entry(c.community.clone()).or_insert_with(|| CommunityStats {
+            name: c.community.clone(),
+            edge_count: 0,
+            is_federation: c.is_federation.unwrap_or(0) != 0,
+        });
+    }
+    for e in edges {
+        let Some(ref name) = e.community else { continue };
+        let entry = by_name.entry(name.clone()).or_insert_with(|| CommunityStats {
+            name: name.clone(),
+            edge_count: 0,
+            is_federation: false,
+        });
+        entry.edge_count = entry.edge_count.saturating_add(1);
+    }
+    by_name.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_communities_counts_edges() {
+        let communities: Vec<SnCommunityRow> = parse_rows(vec![
+            serde_json::json!({"community": "home", "is_federation": 0}),
+            serde_json::json!({"community": "*Federation", "is_federation": 1}),
+        ]);
+        let edges: Vec<SnEdgeRow> = parse_rows(vec![
+            serde_json::json!({"community": "home", "ip4addr": "10.0.0.2/24"}),
+            serde_json::json!({"community": "home", "ip4addr": "10.0.0.3/24"}),
+            serde_json::json!({"community": "work"}),
+        ]);
+
+        let stats = aggregate_communities(&communities, &edges);
+        let home = stats.iter().find(|c| c.name == "home").unwrap();
+        assert_eq!(home.edge_count, 2);
+        let fed = stats.iter().find(|c| c.name == "*Federation").unwrap();
+        assert!(fed.is_federation);
+        assert_eq!(fed.edge_count, 0);
+        assert_eq!(stats.iter().find(|c| c.name == "work").unwrap().edge_count, 1);
+    }
+}
@@ -0,0 +1,270 @@
+//! 恩兔的“值班记录”（把大事写进操作系统自己的日志）
+//!
+//! 管着一大批机器的管理员不会挨个打开恩兔看日志面板，他们习惯用现成的工具收集系统日志。
+//! 主人在设置里打开 `system_log` 后，恩兔会把几件大事（建立通道、意外退出、出错）
+//! 另外抄一份到系统日志里：
+//! - Windows：事件查看器 → 应用程序，来源为 `n2n-maid`
+//! - Linux：直接交给 journald（带 PROFILE / STATUS / ERROR_CODE 结构化字段），
+//!   并且平时的工作日志也会同时抄一份过去，`journalctl -t n2n-maid` 就能看到
+//!
+//! 写不进去也不影响正常工作，只在本地日志里留一句。
+
+/// 事件的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Error,
+}
+
+/// 要抄进系统日志的一件大事
+#[derive(Debug, Clone)]
+pub struct SystemEvent {
+    /// 事件编号（Windows 事件 ID）
+    pub id: u32,
+    pub severity: Severity,
+    /// 给人看的一句话
+    pub message: String,
+    /// 当时使用的方案
+    pub profile: Option<String>,
+    /// 当时的连接状态（connected / disconnected / error ……）
+    pub status: &'static str,
+    /// 已识别的错误编号（error_ / op_）
+    pub error_code: Option<String>,
+}
+
+/// 开始建立通道
+pub const EVENT_CONNECT: u32 = 1;
+/// 建立通道失败（edge 没能启动）
+pub const EVENT_CONNECT_FAILED: u32 = 2;
+/// 工作正常结束（主人断开）
+pub const EVENT_SESSION_ENDED: u32 = 3;
+/// edge 意外退出
+pub const EVENT_UNEXPECTED_EXIT: u32 = 4;
+
+impl SystemEvent {
+    /// 拼成一行完整的记录（没有结构化字段的平台用它）
+    #[cfg(not(target_os = "linux"))]
+    fn full_message(&self) -> String {
+        let mut text = self.message.clone();
+        if let Some(ref profile) = self.profile {
+            text.push_str(&format!(" [profile={}]", profile));
+        }
+        text.push_str(&format!(" [status={}]", self.status));
+        if let Some(ref code) = self.error_code {
+            text.push_str(&format!(" [error={}]", code));
+        }
+        text
+    }
+}
+
+/// 把一件大事抄进系统日志
+pub fn write(event: &SystemEvent) {
+    if let Err(e) = write_platform(event) {
+        log::warn!("没能写进系统日志：{}", e);
+    }
+}
+
+/// Windows：写进事件查看器（应用程序日志）
+#[cfg(target_os = "windows")]
+fn write_platform(event: &SystemEvent) -> anyhow::Result<()> {
+    use windows_sys::Win32::System::EventLog::{
+        DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+        EVENTLOG_INFORMATION_TYPE,
+    };
+
+    /// 系统日志里的来源名字
+    const SOURCE_NAME: &str = "n2n-maid";
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    let source = wide(SOURCE_NAME);
+    let message = wide(&event.full_message());
+    let kind = match event.severity {
+        Severity::Info => EVENTLOG_INFORMATION_TYPE,
+        Severity::Error => EVENTLOG_ERROR_TYPE,
+    };
+
+    unsafe {
+        let handle = RegisterEventSourceW(std::ptr::null(), source.as_ptr());
+        if handle.is_null() {
+            anyhow::bail!("RegisterEventSourceW 失败：{}", std::io::Error::last_os_error());
+        }
+        let strings = [message.as_ptr()];
+        let ok = ReportEventW(
+            handle,
+            kind,
+            0,
+            event.id,
+            std::ptr::null_mut(),
+            strings.len() as u16,
+            0,
+            strings.as_ptr(),
+            std::ptr::null(),
+        );
+        DeregisterEventSource(handle);
+        if ok == 0 {
+            anyhow::bail!("ReportEventW 失败：{}", std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Linux：交给 journald，方便 `journalctl -t n2n-maid` 按字段筛选
+#[cfg(target_os = "linux")]
+fn write_platform(event: &SystemEvent) -> anyhow::Result<()> {
+    let id = event.id.to_string();
+    let mut fields = vec![
+        ("MESSAGE", event.message.as_str()),
+        (
+            "PRIORITY",
+            match event.severity {
+                Severity::Info => journald::PRIORITY_INFO,
+                Severity::Error => journald::PRIORITY_ERR,
+            },
+        ),
+        ("N2N_EVENT", id.as_str()),
+        ("STATUS", event.status),
+    ];
+    if let Some(ref profile) = event.profile {
+        fields.push(("PROFILE", profile));
+    }
+    if let Some(ref code) = event.error_code {
+        fields.push(("ERROR_CODE", code));
+    }
+    journald::send(&fields)?;
+    Ok(())
+}
+
+/// 准备日志系统：平时照旧输出到终端；
+/// 在 Linux 上打开了 `system_log` 并且 journald 在岗时，同时把日志抄一份给 journald
+/// - `verbose`：没用 RUST_LOG 指定时也记到 debug（安全模式下用）
+pub fn init_logging(system_log: bool, verbose: bool) {
+    let default_filter = if verbose { "debug" } else { "info" };
+    let logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter)).build();
+
+    #[cfg(target_os = "linux")]
+    if system_log && journald::available() {
+        let max_level = logger.filter();
+        if log::set_boxed_logger(Box::new(journald::JournalLogger { inner: logger })).is_ok() {
+            log::set_max_level(max_level);
+        }
+        return;
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = system_log;
+
+    let max_level = logger.filter();
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// journald 原生协议（往 `/run/systemd/journal/socket` 发一个数据报）
+#[cfg(target_os = "linux")]
+mod journald {
+    use std::io;
+    use std::os::unix::net::UnixDatagram;
+    use std::path::Path;
+
+    const SOCKET_PATH: &str = "/run/systemd/journal/socket";
+    const SYSLOG_IDENTIFIER: &str = "n2n-maid";
+
+    pub const PRIORITY_ERR: &str = "3";
+    pub const PRIORITY_WARNING: &str = "4";
+    pub const PRIORITY_INFO: &str = "6";
+    pub const PRIORITY_DEBUG: &str = "7";
+
+    /// journald 在不在岗
+    pub fn available() -> bool {
+        Path::new(SOCKET_PATH).exists()
+    }
+
+    /// 发一条带字段的记录（自动带上 SYSLOG_IDENTIFIER）
+    pub fn send(fields: &[(&str, &str)]) -> io::Result<()> {
+        let mut payload = encode(&[("SYSLOG_IDENTIFIER", SYSLOG_IDENTIFIER)]);
+        payload.extend(encode(fields));
+        UnixDatagram::unbound()?.send_to(&payload, SOCKET_PATH)?;
+        Ok(())
+    }
+
+    /// 按原生协议编码：单行值写成 `KEY=value\n`，多行值写成 `KEY\n` + 8 字节小端长度 + 值 + `\n`
+    pub(super) fn encode(fields: &[(&str, &str)]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        for (key, value) in fields {
+            buf.extend_from_slice(key.as_bytes());
+            if value.contains('\n') {
+                buf.push(b'\n');
+                buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            } else {
+                buf.push(b'=');
+            }
+            buf.extend_from_slice(value.as_bytes());
+            buf.push(b'\n');
+        }
+        buf
+    }
+
+    /// 照常输出到终端，同时把每条日志抄给 journald
+    pub struct JournalLogger {
+        pub inner: env_logger::Logger,
+    }
+
+    impl log::Log for JournalLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            self.inner.enabled(metadata)
+        }
+
+        fn log(&self, record: &log::Record) {
+            if !self.inner.matches(record) {
+                return;
+            }
+            self.inner.log(record);
+
+            let priority = match record.level() {
+                log::Level::Error => PRIORITY_ERR,
+                log::Level::Warn => PRIORITY_WARNING,
+                log::Level::Info => PRIORITY_INFO,
+                log::Level::Debug | log::Level::Trace => PRIORITY_DEBUG,
+            };
+            let message = record.args().to_string();
+            // 这里不能再用 log:: 宏报错，不然会绕回自己身上
+            let _ = send(&[
+                ("MESSAGE", &message),
+                ("PRIORITY", priority),
+                ("CODE_MODULE", record.target()),
+            ]);
+        }
+
+        fn flush(&self) {
+            self.inner.flush();
+        }
+    }
+}
+
+/// 其他平台暂时没有对接的系统日志
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn write_platform(event: &SystemEvent) -> anyhow::Result<()> {
+    log::debug!(
+        "当前平台没有对接系统日志，跳过事件 {}（{:?}）：{}",
+        event.id,
+        event.severity,
+        event.full_message()
+    );
+    Ok(())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journald_encoding() {
+        let encoded = journald::encode(&[("MESSAGE", "恩兔上岗"), ("STATUS", "connected"), ("ERROR_CODE", "a\nb")]);
+        let mut expected = "MESSAGE=恩兔上岗\nSTATUS=connected\nERROR_CODE\n".as_bytes().to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(encoded, expected);
+    }
+}
@@ -0,0 +1,561 @@
+//! 找到虚拟网卡在系统里的名字和编号
+//!
+//! edge 只汇报虚拟网卡的 MAC，可 Windows 的网络设置、防火墙、`netsh`/`route` 都认“网卡名称”和“接口编号”。
+//! 恩兔按 MAC 去系统里对一下，把这两样一起递给主人（以后设置 DNS、跃点数、防火墙规则也靠它们）：
+//! - Windows：`GetAdaptersAddresses` 里的 FriendlyName 和 IfIndex；
+//! - Linux：`/sys/class/net/<网卡>/address` 对上 MAC，名字就是网卡名，编号读 `ifindex`。
+//!
+//! edge 走后还会回头看一眼这块网卡：有的 Windows TAP 会一直挂着上次的地址和路由，
+//! 下次建立通道时就撞地址了。`leftover` 负责看，`reset` 负责把残留清掉。
+//!
+//! 主人反复调整通道宽度（MTU）时，`set_mtu` 直接改网卡，不用断开重连。
+//!
+//! 连着的时候恩兔也会定期用 `state` 看一眼网卡还在不在、有没有被停用、地址还挂没挂着：
+//! 睡眠唤醒、驱动抽风都可能把网卡弄没，edge 自己却浑然不觉。
+use anyhow::Result;
+use serde::Serialize;
+use std::net::Ipv4Addr;
+
+/// 虚拟网卡在系统里的身份
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TapAdapter {
+    /// 用来对照的 MAC（统一成小写冒号格式）
+    pub mac: String,
+    /// 系统里显示的网卡名称（Windows 的“以太网 2”、Linux 的 edge0）
+    pub friendly_name: String,
+    /// 接口编号（route/netsh 用的 ifIndex）
+    pub if_index: u32,
+}
+
+/// 把 `AA-BB-CC-DD-EE-FF` / `aa:bb:cc:dd:ee:ff` 统一解析成 6 个字节
+pub(crate) fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let mut bytes = [0u8; 6];
+    let mut parts = mac.trim().split([':', '-']);
+    for byte in bytes.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    parts.next().is_none().then_some(bytes)
+}
+
+fn format_mac(bytes: &[u8; 6]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+/// 主人指定的 MAC 能不能交给 edge（写法要对，而且必须是单播、不能全是 0）
+pub fn is_assignable_mac(mac: &str) -> bool {
+    parse_mac(mac).is_some_and(|bytes| bytes[0] & 0x01 == 0 && bytes != [0; 6])
+}
+
+/// 随机挑一个 MAC（本地管理的单播地址，不会和网卡厂商分配的撞）
+pub fn random_mac() -> Result<String> {
+    let mut bytes = [0u8; 6];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow::anyhow!("生成随机 MAC 失败：{}", e))?;
+    bytes[0] = (bytes[0] & 0xfc) | 0x02;
+    Ok(format_mac(&bytes))
+}
+
+/// 按 MAC 找虚拟网卡（找不到或当前平台不支持时返回 None）
+pub fn resolve(mac: &str) -> Option<TapAdapter> {
+    let bytes = parse_mac(mac)?;
+    let (friendly_name, if_index) = find_by_mac(&bytes)?;
+    Some(TapAdapter {
+        mac: format_mac(&bytes),
+        friendly_name,
+        if_index,
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn find_by_mac(mac: &[u8; 6]) -> Option<(String, u32)> {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        GAA_FLAG_SKIP_UNICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
+
+    let flags = GAA_FLAG_SKIP_UNICAST | GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    // 结构体要求 8 字节对齐，所以用 u64 数组当缓冲区
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64>;
+    loop {
+        buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as u32,
+                flags,
+                std::ptr::null(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                &mut size,
+            )
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_BUFFER_OVERFLOW => continue,
+            other => {
+                log::debug!("GetAdaptersAddresses 失败：{}", other);
+                return None;
+            }
+        }
+    }
+
+    let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !current.is_null() {
+        let adapter = unsafe { &*current };
+        let len = (adapter.PhysicalAddressLength as usize).min(adapter.PhysicalAddress.len());
+        if adapter.PhysicalAddress[..len] == mac[..] {
+            let name = unsafe {
+                let mut n = 0;
+                while *adapter.FriendlyName.add(n) != 0 {
+                    n += 1;
+                }
+                String::from_utf16_lossy(std::slice::from_raw_parts(adapter.FriendlyName, n))
+            };
+            let if_index = unsafe { adapter.Anonymous1.Anonymous.IfIndex };
+            return Some((name, if_index));
+        }
+        current = adapter.Next;
+    }
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn find_by_mac(mac: &[u8; 6]) -> Option<(String, u32)> {
+    std::fs::read_dir("/sys/class/net").ok()?.flatten().find_map(|entry| {
+        let path = entry.path();
+        let address = std::fs::read_to_string(path.join("address")).ok()?;
+        if parse_mac(&address)? != *mac {
+            return None;
+        }
+        let if_index = std::fs::read_to_string(path.join("ifindex")).ok()?.trim().parse().ok()?;
+        Some((entry.file_name().to_string_lossy().to_string(), if_index))
+    })
+}
+
+/// 其他平台暂时不去对照
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn find_by_mac(_mac: &[u8; 6]) -> Option<(String, u32)> {
+    None
+}
+
+/// edge 走后网卡上还挂着的东西
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Leftover {
+    /// 还挂着的 IPv4 地址
+    pub addresses: Vec<String>,
+    /// 还指向这块网卡的 IPv4 路由（`网段/前缀`）
+    pub routes: Vec<String>,
+}
+
+impl Leftover {
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.routes.is_empty()
+    }
+
+    /// 给主人看的一句话
+    pub fn describe(&self) -> String {
+        self.addresses.iter().chain(self.routes.iter()).cloned().collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// 这些路由每块网卡都可能有，不算残留（组播、广播）
+fn is_ambient_route(dest: Ipv4Addr, prefix: u8) -> bool {
+    dest.is_multicast() || (dest.is_broadcast() && prefix == 32)
+}
+
+/// 看看 edge 走后网卡上还挂着什么（网卡已经不在了也算收拾干净）
+#[cfg(target_os = "windows")]
+pub fn leftover(adapter: &TapAdapter) -> Leftover {
+    use windows_sys::Win32::NetworkManagement::IpHelper::{FreeMibTable, GetIpForwardTable2, MIB_IPFORWARD_TABLE2};
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, SOCKADDR_IN};
+
+    let mut found = Leftover {
+        addresses: windows_addresses(adapter.if_index),
+        routes: Vec::new(),
+    };
+
+    let mut table: *mut MIB_IPFORWARD_TABLE2 = std::ptr::null_mut();
+    if unsafe { GetIpForwardTable2(AF_INET, &mut table) } != 0 || table.is_null() {
+        return found;
+    }
+    unsafe {
+        let rows = std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+        for row in rows.iter().filter(|row| row.InterfaceIndex == adapter.if_index) {
+            let sin: SOCKADDR_IN = row.DestinationPrefix.Prefix.Ipv4;
+            let dest = Ipv4Addr::from(sin.sin_addr.S_un.S_addr.to_ne_bytes());
+            let prefix = row.DestinationPrefix.PrefixLength;
+            if !is_ambient_route(dest, prefix) {
+                found.routes.push(format!("{}/{}", dest, prefix));
+            }
+        }
+        FreeMibTable(table as *const _);
+    }
+    found
+}
+
+/// Windows：这块网卡上还绑着的 IPv4 地址（自动私有地址 169.254.x.x 不算）
+#[cfg(target_os = "windows")]
+fn windows_addresses(if_index: u32) -> Vec<String> {
+    windows_adapter(if_index).map(|(_, addresses)| addresses).unwrap_or_default()
+}
+
+/// Windows：这块网卡是不是启用着，以及上面绑着的 IPv4 地址（查不到时为 None）
+#[cfg(target_os = "windows")]
+fn windows_adapter(if_index: u32) -> Option<(bool, Vec<String>)> {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows_sys::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, SOCKADDR_IN};
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64>;
+    loop {
+        buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                AF_INET as u32,
+                flags,
+                std::ptr::null(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                &mut size,
+            )
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_BUFFER_OVERFLOW => continue,
+            _ => return None,
+        }
+    }
+
+    let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !current.is_null() {
+        let adapter = unsafe { &*current };
+        if unsafe { adapter.Anonymous1.Anonymous.IfIndex } == if_index {
+            let mut addresses = Vec::new();
+            let mut unicast = adapter.FirstUnicastAddress;
+            while !unicast.is_null() {
+                let entry = unsafe { &*unicast };
+                let sockaddr = entry.Address.lpSockaddr;
+                if !sockaddr.is_null() && unsafe { (*sockaddr).sa_family } == AF_INET {
+                    let sin = unsafe { &*(sockaddr as *const SOCKADDR_IN) };
+                    let ip = Ipv4Addr::from(unsafe { sin.sin_addr.S_un.S_addr }.to_ne_bytes());
+                    if !ip.is_link_local() {
+                        addresses.push(ip.to_string());
+                    }
+                }
+                unicast = entry.Next;
+            }
+            return Some((adapter.OperStatus == IfOperStatusUp, addresses));
+        }
+        current = adapter.Next;
+    }
+    None
+}
+
+/// 看看 edge 走后网卡上还挂着什么（网卡已经不在了也算收拾干净）
+#[cfg(target_os = "linux")]
+pub fn leftover(adapter: &TapAdapter) -> Leftover {
+    let name = adapter.friendly_name.as_str();
+    if !std::path::Path::new("/sys/class/net").join(name).exists() {
+        return Leftover::default();
+    }
+    let addresses = std::process::Command::new("ip")
+        .args(["-o", "-4", "addr", "show", "dev", name])
+        .output()
+        .map(|out| parse_ip_addr(&String::from_utf8_lossy(&out.stdout)))
+        .unwrap_or_default();
+    let routes = std::fs::read_to_string("/proc/net/route")
+        .map(|table| parse_proc_route(&table, name))
+        .unwrap_or_default();
+    Leftover { addresses, routes }
+}
+
+/// 其他平台暂时不去核对
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn leftover(_adapter: &TapAdapter) -> Leftover {
+    Leftover::default()
+}
+
+/// 重置网卡：把残留的地址和路由清掉
+/// - Windows：地址改回自动获取，再把网卡停用后重新启用；
+/// - Linux：清空网卡上的地址和路由（权限不够时请常驻小帮手代劳）。
+#[cfg(target_os = "windows")]
+pub fn reset(adapter: &TapAdapter) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let name = format!("name={}", adapter.friendly_name);
+    for args in [
+        vec!["interface", "ip", "set", "address", name.as_str(), "source=dhcp"],
+        vec!["interface", "set", "interface", name.as_str(), "admin=disabled"],
+        vec!["interface", "set", "interface", name.as_str(), "admin=enabled"],
+    ] {
+        let status = std::process::Command::new("netsh")
+            .args(&args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .status()?;
+        if !status.success() {
+            anyhow::bail!("netsh {} 失败（{:?}）", args.join(" "), status.code());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn reset(adapter: &TapAdapter) -> Result<()> {
+    use crate::priv_helper::{self, FlushWhat, Request};
+    use std::process::Command;
+
+    let name = adapter.friendly_name.as_str();
+    for what in [FlushWhat::Addr, FlushWhat::Route] {
+        let word = if what == FlushWhat::Addr { "addr" } else { "route" };
+        let direct = Command::new("ip").args([word, "flush", "dev", name]).status()?;
+        if direct.success() {
+            continue;
+        }
+        log::info!("直接清理网卡 {} 的 {} 失败，请小帮手代劳", name, word);
+        priv_helper::call(&Request::Flush { dev: name.to_string(), what })?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn reset(_adapter: &TapAdapter) -> Result<()> {
+    anyhow::bail!("这个平台上恩兔还不会重置网卡")
+}
+
+/// 直接修改网卡的通道宽度（MTU），通道不用断开
+/// - Windows：`netsh interface ipv4 set subinterface <网卡> mtu=<值> store=active`（只改这一次，重启后由 edge 重新设置）；
+/// - Linux：`ip link set dev <网卡> mtu <值>`（权限不够时请常驻小帮手代劳）。
+#[cfg(target_os = "windows")]
+pub fn set_mtu(adapter: &TapAdapter, mtu: u16) -> Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let mtu = format!("mtu={}", mtu);
+    let status = std::process::Command::new("netsh")
+        .args(["interface", "ipv4", "set", "subinterface", adapter.friendly_name.as_str(), mtu.as_str(), "store=active"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("netsh 修改 {} 的 {} 失败（{:?}）", adapter.friendly_name, mtu, status.code());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_mtu(adapter: &TapAdapter, mtu: u16) -> Result<()> {
+    use std::process::Command;
+
+    let value = mtu.to_string();
+    let args = ["link", "set", "dev", adapter.friendly_name.as_str(), "mtu", value.as_str()];
+    if Command::new("ip").args(args).status()?.success() {
+        return Ok(());
+    }
+    log::info!("直接修改网卡 {} 的 MTU 失败，请小帮手代劳", adapter.friendly_name);
+    crate::priv_helper::call(&crate::priv_helper::Request::SetMtu {
+        dev: adapter.friendly_name.clone(),
+        mtu,
+    })
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn set_mtu(_adapter: &TapAdapter, _mtu: u16) -> Result<()> {
+    anyhow::bail!("这个平台上恩兔还不会直接修改网卡的 MTU")
+}
+
+/// 虚拟网卡现在的样子（连着时巡查用）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TapState {
+    /// 当前平台看不了
+    Unknown,
+    /// 系统里已经找不到这块网卡了
+    Missing,
+    Present {
+        /// 网卡是启用的
+        up: bool,
+        /// 网卡上挂着的 IPv4 地址（Linux 带前缀长度，Windows 不带）
+        addresses: Vec<String>,
+    },
+}
+
+/// 网卡出的毛病
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TapProblem {
+    /// 网卡不见了
+    Missing,
+    /// 网卡被停用了
+    Down,
+    /// edge 领到的地址从网卡上掉了
+    AddressLost,
+}
+
+impl TapProblem {
+    /// 给主人看的提示编号
+    pub fn code(&self) -> &'static str {
+        match self {
+            TapProblem::Missing => "error_tap_missing",
+            TapProblem::Down => "error_tap_down",
+            TapProblem::AddressLost => "error_tap_address_lost",
+        }
+    }
+}
+
+impl TapState {
+    /// 对照 edge 领到的地址，看看网卡有没有毛病（看不了的时候不算有毛病）
+    pub fn problem(&self, expected_ip: &str) -> Option<TapProblem> {
+        match self {
+            TapState::Unknown => None,
+            TapState::Missing => Some(TapProblem::Missing),
+            TapState::Present { up: false, .. } => Some(TapProblem::Down),
+            TapState::Present { addresses, .. } => {
+                let held = addresses.iter().any(|a| a.split('/').next() == Some(expected_ip));
+                (!held).then_some(TapProblem::AddressLost)
+            }
+        }
+    }
+}
+
+/// 按 MAC 看一眼虚拟网卡现在的样子
+#[cfg(target_os = "windows")]
+pub fn state(mac: &str) -> TapState {
+    let Some(adapter) = resolve(mac) else {
+        return TapState::Missing;
+    };
+    match windows_adapter(adapter.if_index) {
+        Some((up, addresses)) => TapState::Present { up, addresses },
+        None => TapState::Unknown,
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn state(mac: &str) -> TapState {
+    let Some(adapter) = resolve(mac) else {
+        return TapState::Missing;
+    };
+    let dir = std::path::Path::new("/sys/class/net").join(&adapter.friendly_name);
+    // operstate 对 TAP 常常是 unknown，看 flags 里的 IFF_UP 才靠谱
+    let Some(flags) = std::fs::read_to_string(dir.join("flags"))
+        .ok()
+        .and_then(|f| u32::from_str_radix(f.trim().trim_start_matches("0x"), 16).ok())
+    else {
+        return TapState::Unknown;
+    };
+    let Ok(out) = std::process::Command::new("ip")
+        .args(["-o", "-4", "addr", "show", "dev", adapter.friendly_name.as_str()])
+        .output()
+    else {
+        return TapState::Unknown;
+    };
+    TapState::Present {
+        up: flags & 0x1 != 0,
+        addresses: parse_ip_addr(&String::from_utf8_lossy(&out.stdout)),
+    }
+}
+
+/// 其他平台暂时不去巡查
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn state(_mac: &str) -> TapState {
+    TapState::Unknown
+}
+
+/// `ip -o -4 addr show` 里的 `inet 10.0.0.2/24` 那一截
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_ip_addr(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            words.find(|word| *word == "inet")?;
+            words.next().map(|cidr| cidr.to_string())
+        })
+        .collect()
+}
+
+/// `/proc/net/route` 里属于 `iface` 的路由（地址和面具都是小端十六进制）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_proc_route(table: &str, iface: &str) -> Vec<String> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 8 || cols[0] != iface {
+                return None;
+            }
+            let dest = Ipv4Addr::from(u32::from_str_radix(cols[1], 16).ok()?.to_le_bytes());
+            let prefix = u32::from_str_radix(cols[7], 16).ok()?.count_ones() as u8;
+            (!is_ambient_route(dest, prefix)).then(|| format!("{}/{}", dest, prefix))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mac() {
+        let expected = [0x02, 0xab, 0x00, 0x10, 0xfe, 0x01];
+        assert_eq!(parse_mac("02:AB:00:10:FE:01"), Some(expected));
+        assert_eq!(parse_mac("02-ab-00-10-fe-01\n"), Some(expected));
+        assert_eq!(format_mac(&expected), "02:ab:00:10:fe:01");
+        assert_eq!(parse_mac("02:ab:00:10:fe"), None);
+        assert_eq!(parse_mac("02:ab:00:10:fe:01:02"), None);
+        assert_eq!(parse_mac("zz:ab:00:10:fe:01"), None);
+        assert_eq!(resolve("not a mac"), None);
+
+        assert!(is_assignable_mac("02-AB-00-10-FE-01"));
+        assert!(!is_assignable_mac("01:00:5e:00:00:01"));
+        assert!(!is_assignable_mac("00:00:00:00:00:00"));
+        let random = random_mac().unwrap();
+        assert!(is_assignable_mac(&random));
+        assert_eq!(parse_mac(&random).unwrap()[0] & 0x03, 0x02);
+    }
+
+    #[test]
+    fn test_parse_leftover() {
+        let addr = "7: edge0    inet 10.0.0.2/24 brd 10.0.0.255 scope global edge0\\       valid_lft forever preferred_lft forever\n";
+        assert_eq!(parse_ip_addr(addr), vec!["10.0.0.2/24"]);
+        assert!(parse_ip_addr("").is_empty());
+
+        let table = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+edge0\t0000000A\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+edge0\t000000E0\t00000000\t0001\t0\t0\t0\t000000F0\t0\t0\t0
+";
+        assert_eq!(parse_proc_route(table, "edge0"), vec!["10.0.0.0/24"]);
+        assert!(parse_proc_route(table, "edge1").is_empty());
+
+        let leftover = Leftover {
+            addresses: vec!["10.0.0.2".to_string()],
+            routes: vec!["10.0.0.0/24".to_string()],
+        };
+        assert!(!leftover.is_empty());
+        assert_eq!(leftover.describe(), "10.0.0.2, 10.0.0.0/24");
+        assert!(Leftover::default().is_empty());
+    }
+
+    #[test]
+    fn test_tap_state_problem() {
+        let present = |up, addresses: &[&str]| TapState::Present {
+            up,
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+        };
+        assert_eq!(present(true, &["10.0.0.2/24"]).problem("10.0.0.2"), None);
+        assert_eq!(present(true, &["192.168.1.5", "10.0.0.2"]).problem("10.0.0.2"), None);
+        assert_eq!(present(true, &["10.0.0.20/24"]).problem("10.0.0.2"), Some(TapProblem::AddressLost));
+        assert_eq!(present(true, &[]).problem("10.0.0.2"), Some(TapProblem::AddressLost));
+        assert_eq!(present(false, &["10.0.0.2/24"]).problem("10.0.0.2"), Some(TapProblem::Down));
+        assert_eq!(TapState::Missing.problem("10.0.0.2"), Some(TapProblem::Missing));
+        assert_eq!(TapState::Unknown.problem("10.0.0.2"), None);
+        assert_eq!(TapProblem::AddressLost.code(), "error_tap_address_lost");
+    }
+}
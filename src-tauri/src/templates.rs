@@ -0,0 +1,133 @@
+//! 方案模板（第一次用恩兔时，不用对着一排空白格子发呆）
+//!
+//! 常见的几种用法先替主人填好，选一个再补上总部地址、暗号就能用：
+//! - `public_test`：ntop 提供的公共测试总部，先试试 n2n 能不能通；
+//! - `lan_gaming`：自己搭的总部，和朋友联机打游戏（自动分配地址、断线总是重连）；
+//! - `site_to_site`：把两边的局域网连起来（固定地址，替对面的网段加路由并开启转发）。
+//!
+//! 模板只改和组网有关的几项，edge 路径、网卡名这些本机设置保持原样；
+//! 名字和说明由前端按 `template_<id>` / `template_<id>_desc` 翻译。
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::config::{N2NConfig, ReconnectPolicy};
+use crate::edge_options::EdgeOption;
+use crate::routes::Route;
+
+/// 一份模板
+#[derive(Debug, Clone, Serialize)]
+pub struct Template {
+    /// 模板编号（稳定，前端按它翻译名字）
+    pub id: &'static str,
+    /// 模板里的指示（只看组网相关的几项）
+    pub config: N2NConfig,
+}
+
+impl Template {
+    /// 把模板盖到 `base` 上：组网相关的几项换成模板的，其余保持原样
+    pub fn apply(&self, base: &N2NConfig) -> N2NConfig {
+        let t = &self.config;
+        N2NConfig {
+            supernode: t.supernode.clone(),
+            supernode_id: None,
+            community: t.community.clone(),
+            encryption_key: String::new(),
+            encryption_key_ref: None,
+            ip_mode: t.ip_mode.clone(),
+            static_ip: t.static_ip.clone(),
+            extra_args: None,
+            advanced_options: t.advanced_options.clone(),
+            routes: t.routes.clone(),
+            mtu: t.mtu,
+            important_peers: t.important_peers.clone(),
+            reconnect_policy: t.reconnect_policy,
+            port_forwards: Vec::new(),
+            ..base.clone()
+        }
+    }
+}
+
+/// 恩兔自带的全部模板
+pub fn builtin() -> Vec<Template> {
+    let base = N2NConfig {
+        supernode: String::new(),
+        community: String::new(),
+        encryption_key: String::new(),
+        ..N2NConfig::default()
+    };
+    vec![
+        Template {
+            id: "public_test",
+            config: N2NConfig {
+                supernode: "supernode.ntop.org:7777".to_string(),
+                ..base.clone()
+            },
+        },
+        Template {
+            id: "lan_gaming",
+            config: N2NConfig {
+                community: "lan-party".to_string(),
+                reconnect_policy: ReconnectPolicy::Always,
+                ..base.clone()
+            },
+        },
+        Template {
+            id: "site_to_site",
+            config: N2NConfig {
+                community: "site-link".to_string(),
+                ip_mode: "static".to_string(),
+                static_ip: Some("10.0.0.1/24".to_string()),
+                // 对面的局域网经由对面的 edge（10.0.0.2）转发；-r 让本机也能替对面转发
+                routes: vec![Route {
+                    network: "192.168.2.0".to_string(),
+                    netmask: "255.255.255.0".to_string(),
+                    gateway: "10.0.0.2".to_string(),
+                }],
+                advanced_options: vec![EdgeOption::new("-r", None)],
+                reconnect_policy: ReconnectPolicy::Always,
+                ..base
+            },
+        },
+    ]
+}
+
+/// 按编号找模板
+pub fn find(id: &str) -> Result<Template> {
+    builtin()
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| anyhow::anyhow!("恩兔没有叫“{}”的模板", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_template_keeps_local_settings() {
+        let mine = N2NConfig {
+            supernode: "old.example.com:7777".to_string(),
+            community: "old".to_string(),
+            encryption_key: "secret".to_string(),
+            supernode_id: Some("sn-1".to_string()),
+            edge_path: Some("/opt/n2n/edge".to_string()),
+            tap_device: Some("n2n0".to_string()),
+            ..N2NConfig::default()
+        };
+
+        let applied = find("site_to_site").unwrap().apply(&mine);
+        assert_eq!(applied.community, "site-link");
+        assert_eq!(applied.ip_mode, "static");
+        assert_eq!(applied.static_ip.as_deref(), Some("10.0.0.1/24"));
+        assert_eq!(crate::routes::to_args(&applied.routes), vec!["-n", "192.168.2.0/24:10.0.0.2"]);
+        assert_eq!(applied.supernode_id, None);
+        assert!(applied.encryption_key.is_empty());
+        assert_eq!(applied.edge_path, mine.edge_path);
+        assert_eq!(applied.tap_device, mine.tap_device);
+
+        let public = find("public_test").unwrap().apply(&mine);
+        assert_eq!(public.supernode, "supernode.ntop.org:7777");
+        assert!(public.community.is_empty());
+        assert!(find("nope").is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! 测试用的小工具（只在跑测试时编译）
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// 测试用的临时目录：建好时是空的，离开作用域就连同里面的东西一起删掉（断言没过、测试半路摔倒也会删）
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// 在系统临时目录下建一个 `n2n-maid-<name>-<进程号>`（上次没删干净的先清掉）
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("n2n-maid-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("创建测试用的临时目录失败");
+        Self(dir)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
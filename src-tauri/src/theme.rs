@@ -0,0 +1,133 @@
+/// 工作台配色模块
+/// 负责记住主人的配色偏好，并在“跟随系统”时实时跟着系统明暗切换
+///
+/// - Windows / macOS：系统切换明暗时窗口会收到 `ThemeChanged`，由 main.rs 转交给 `on_os_theme_changed`；
+/// - Linux：GTK 窗口不一定能感知桌面门户（portal）的 color-scheme 变化，
+///   所以额外派一个小工人定期看一眼 `gsettings` 里的 color-scheme。
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Theme};
+
+use crate::preferences::ThemePreference;
+
+/// 前端监听的事件名（载荷为 "light" / "dark"）
+pub const THEME_CHANGED_EVENT: &str = "theme-changed";
+
+/// 当前的配色偏好与最近一次广播出去的实际配色
+pub struct ThemeState {
+    preference: Mutex<ThemePreference>,
+    effective: Mutex<Option<&'static str>>,
+}
+
+impl ThemeState {
+    pub fn new(preference: ThemePreference) -> Self {
+        Self {
+            preference: Mutex::new(preference),
+            effective: Mutex::new(None),
+        }
+    }
+
+    pub fn preference(&self) -> ThemePreference {
+        *self.preference.lock().unwrap()
+    }
+}
+
+/// 把 Tauri 的 Theme 翻译成前端用的名字
+fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+/// 看一眼系统现在是明是暗
+fn os_theme(app: &AppHandle) -> Theme {
+    #[cfg(target_os = "linux")]
+    if let Some(theme) = linux_color_scheme() {
+        return theme;
+    }
+    app.get_webview_window("main")
+        .and_then(|w| w.theme().ok())
+        .unwrap_or(Theme::Light)
+}
+
+/// 结合偏好算出实际使用的配色
+pub fn effective_theme(app: &AppHandle) -> &'static str {
+    let state = app.state::<ThemeState>();
+    match state.preference() {
+        ThemePreference::Light => "light",
+        ThemePreference::Dark => "dark",
+        ThemePreference::System => theme_name(os_theme(app)),
+    }
+}
+
+/// 实际配色有变化时才广播，避免前端被重复的通知刷屏
+fn broadcast(app: &AppHandle, effective: &'static str) {
+    let state = app.state::<ThemeState>();
+    let mut last = state.effective.lock().unwrap();
+    if *last == Some(effective) {
+        return;
+    }
+    *last = Some(effective);
+    drop(last);
+    log::info!("工作台换成{}配色啦", if effective == "dark" { "暗色" } else { "明亮" });
+    let _ = app.emit(THEME_CHANGED_EVENT, effective);
+}
+
+/// 换上主人选的配色（窗口原生部分 + 通知前端）
+pub fn apply_preference(app: &AppHandle, preference: ThemePreference) {
+    *app.state::<ThemeState>().preference.lock().unwrap() = preference;
+    app.set_theme(match preference {
+        ThemePreference::System => None,
+        ThemePreference::Light => Some(Theme::Light),
+        ThemePreference::Dark => Some(Theme::Dark),
+    });
+    broadcast(app, effective_theme(app));
+}
+
+/// 系统明暗变了（窗口的 ThemeChanged）
+pub fn on_os_theme_changed(app: &AppHandle, theme: Theme) {
+    if app.state::<ThemeState>().preference() != ThemePreference::System {
+        return;
+    }
+    // Linux 以 gsettings 为准，窗口事件只当作“该去看一眼了”的提醒
+    #[cfg(target_os = "linux")]
+    let theme = linux_color_scheme().unwrap_or(theme);
+    broadcast(app, theme_name(theme));
+}
+
+/// 派出系统配色巡视小工人（目前只有 Linux 需要）
+pub fn start_os_theme_watcher(app: &AppHandle) {
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+    let app = app.clone();
+    thread::spawn(move || loop {
+        thread::sleep(Duration::from_secs(3));
+        if app.state::<ThemeState>().preference() == ThemePreference::System {
+            broadcast(&app, theme_name(os_theme(&app)));
+        }
+    });
+}
+
+/// 读取桌面门户同款的 color-scheme 设置（GNOME 及兼容桌面）
+#[cfg(target_os = "linux")]
+fn linux_color_scheme() -> Option<Theme> {
+    let output = std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 'default' 表示桌面没有明确偏好，交给窗口自己判断
+    let value = String::from_utf8_lossy(&output.stdout);
+    if value.contains("prefer-dark") {
+        Some(Theme::Dark)
+    } else if value.contains("prefer-light") {
+        Some(Theme::Light)
+    } else {
+        None
+    }
+}
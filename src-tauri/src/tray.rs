@@ -5,31 +5,32 @@ use tauri::{
     tray::{TrayIconBuilder, TrayIconEvent},
     menu::{Menu, MenuItem},
 };
+use std::time::Duration;
+
+use crate::messages;
 use crate::n2n_process::ConnectionStatus;
 
+/// 托盘上“先别自动重连”叫停多久（一个晚上）
+const TRAY_SUSPEND_RECONNECT: Duration = Duration::from_secs(8 * 60 * 60);
+
 /// 创建系统托盘
 pub fn create_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
     let show_i = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
     let connect_i = MenuItem::with_id(app, "connect", "连接", true, None::<&str>)?;
     let disconnect_i = MenuItem::with_id(app, "disconnect", "断开", false, None::<&str>)?;
+    let cancel_i = MenuItem::with_id(app, "cancel_reconnect", "取消排队中的重连", true, None::<&str>)?;
+    let suspend_i = MenuItem::with_id(app, "suspend_reconnect", "今晚先别自动重连（8 小时）", true, None::<&str>)?;
     
-    let menu = Menu::with_items(app, &[&show_i, &connect_i, &disconnect_i, &quit_i])?;
+    let menu = Menu::with_items(app, &[&show_i, &connect_i, &disconnect_i, &cancel_i, &suspend_i, &quit_i])?;
 
     let _ = TrayIconBuilder::with_id("main")
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .on_menu_event(move |app, event| match event.id.as_ref() {
             "quit" => {
-                // 主人从托盘选择“退出”时，也尽量走一遍“温柔收拾工具”的流程
-                // 交给窗口的 CloseRequested 处理：它会提示主人等待，并在 edge 退出后再关门
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    let _ = window.close();
-                } else {
-                    app.exit(0);
-                }
+                // 主人从托盘选择“退出”时，也走一遍“温柔收拾工具”的关门流程（不经过窗口，界面卡住也能退出）
+                crate::shutdown::begin(app);
             }
             "show" => {
                 if let Some(window) = app.get_webview_window("main") {
@@ -49,6 +50,19 @@ pub fn create_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
                     let _ = window.emit("tray-disconnect", ());
                 }
             }
+            "cancel_reconnect" => {
+                // edge 摔倒后正等着重连：这次先不连了，回到待命
+                let state = app.state::<crate::AppState>();
+                let process = state.process.lock().unwrap();
+                if process.cancel_pending_reconnect() {
+                    let _ = update_tray_menu(app, &process.status());
+                }
+            }
+            "suspend_reconnect" => {
+                // 总部要维护：不改方案，临时叫停自动重连和自我巡查
+                let state = app.state::<crate::AppState>();
+                state.process.lock().unwrap().suspend_reconnect(TRAY_SUSPEND_RECONNECT);
+            }
             _ => {}
         })
         .on_tray_icon_event(|tray, event| {
@@ -83,10 +97,19 @@ pub fn update_tray_menu(app: &AppHandle, _status: &ConnectionStatus) -> Result<(
         ConnectionStatus::Connecting => "N2N UI - 连接中...".to_string(),
         ConnectionStatus::Disconnecting => "N2N UI - 断开中...".to_string(),
         ConnectionStatus::Connected(_) => "N2N UI - 已连接".to_string(),
-        ConnectionStatus::Error(msg) => format!("N2N UI - 错误: {}", msg),
+        ConnectionStatus::Degraded(_) => "N2N UI - 已连接（通道不太通）".to_string(),
+        ConnectionStatus::Paused => "N2N UI - 暂停中".to_string(),
+        ConnectionStatus::Reconnecting(attempt) => format!("N2N UI - 等待第 {} 次重连", attempt),
+        ConnectionStatus::Error(msg, _) => format!(
+            "N2N UI - 错误: {}",
+            messages::describe_or_raw(msg, messages::Locale::Zh)
+        ),
     };
 
     tray.set_tooltip(Some(&tooltip))?;
+    // Linux 的 AppIndicator 不显示提示文本，在图标旁边挂个短标签，桌面上一眼就能看出通道通着
+    #[cfg(target_os = "linux")]
+    tray.set_title(crate::desktop_indicator::indicator_label(_status))?;
     
     Ok(())
 }
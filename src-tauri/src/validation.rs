@@ -0,0 +1,322 @@
+//! 指示检查（保存/连接前先把填错的地方指出来）
+//!
+//! 以前填错了总部端口、地址写成 `10.0.0.300`，要等 edge 启动失败、在日志里留下一行看不懂的报错才发现。
+//! 这里在交给 edge 之前逐项检查，每个问题都带上字段名和稳定的 `error_` 编号，工作台可以直接标在对应的输入框下面。
+use serde::Serialize;
+use std::net::Ipv4Addr;
+use std::path::Path;
+
+use crate::config::N2NConfig;
+use crate::edge_caps::EdgeCaps;
+use crate::edge_integrity;
+use crate::edge_options;
+use crate::n2n_process;
+use crate::port_knock;
+use crate::routes;
+use crate::tap_adapter;
+use crate::wake;
+
+/// 小组暗号最长字节数（edge 的 N2N_COMMUNITY_SIZE 是 20，要留一个 `\0`）
+pub const COMMUNITY_MAX_LEN: usize = 19;
+/// 通道宽度（MTU）的允许范围
+pub const MTU_RANGE: std::ops::RangeInclusive<u16> = 576..=1500;
+/// 温柔停止等待时间的允许范围（秒）
+pub const STOP_TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 1..=300;
+/// 报到（打洞保活）间隔的允许范围（秒）
+pub const REGISTER_INTERVAL_RANGE: std::ops::RangeInclusive<u32> = 1..=3600;
+/// 状态快照间隔的允许范围（秒）
+pub const SNAPSHOT_INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 5..=3600;
+/// 重新查总部域名间隔的允许范围（秒，另外 0 表示不查）
+pub const SUPERNODE_RECHECK_RANGE: std::ops::RangeInclusive<u64> = 30..=86400;
+
+/// 某个字段的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    /// 出问题的字段（和 N2NConfig 的字段名一致）
+    pub field: &'static str,
+    /// 问题编号（`error_` 开头，话术见 messages.rs / i18n.ts）
+    pub code: &'static str,
+}
+
+/// 逐项检查一份指示，没问题时返回空列表
+pub fn validate(config: &N2NConfig) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut push = |field, code| errors.push(FieldError { field, code });
+
+    // 填了总部域名时总部地址（和没填的暗号）到建立通道时才从 DNS 里查出来
+    let supernode_dns = config.supernode_dns.as_deref().map(str::trim).filter(|d| !d.is_empty());
+    match supernode_dns {
+        Some(domain) if !is_domain(domain) => push("supernode_dns", "error_invalid_supernode_dns"),
+        Some(_) => {}
+        None if !is_host_port(&config.supernode) => push("supernode", "error_invalid_supernode"),
+        None => {}
+    }
+
+    let community = config.community.trim();
+    if community.is_empty() {
+        if supernode_dns.is_none() {
+            push("community", "error_community_empty");
+        }
+    } else if community.len() > COMMUNITY_MAX_LEN {
+        push("community", "error_community_too_long");
+    }
+
+    match config.ip_mode.as_str() {
+        "dhcp" => {}
+        "static" => {
+            if !config.static_ip.as_deref().is_some_and(is_ipv4_cidr) {
+                push("static_ip", "error_invalid_static_ip");
+            }
+        }
+        _ => push("ip_mode", "error_invalid_ip_mode"),
+    }
+
+    if config.cipher.needs_key() && config.encryption_key.is_empty() {
+        push("cipher", "error_cipher_needs_key");
+    }
+
+    if config.mtu.is_some_and(|mtu| !MTU_RANGE.contains(&mtu)) {
+        push("mtu", "error_invalid_mtu");
+    }
+
+    if config
+        .mac_address
+        .as_deref()
+        .map(str::trim)
+        .is_some_and(|mac| !mac.is_empty() && !tap_adapter::is_assignable_mac(mac))
+    {
+        push("mac_address", "error_invalid_mac");
+    }
+
+    // 0 交给 edge 等于没固定；本机端口也不能和 edge 的管理口撞
+    let mgmt_port = config.management_port.unwrap_or(n2n_process::DEFAULT_MGMT_PORT);
+    if config.management_port == Some(0) {
+        push("management_port", "error_invalid_management_port");
+    }
+    if config.local_port.is_some_and(|port| port == 0 || port == mgmt_port) {
+        push("local_port", "error_invalid_local_port");
+    }
+
+    if config.register_interval_secs.is_some_and(|secs| !REGISTER_INTERVAL_RANGE.contains(&secs)) {
+        push("register_interval_secs", "error_invalid_register_interval");
+    }
+
+    if !STOP_TIMEOUT_RANGE.contains(&config.stop_timeout_secs) {
+        push("stop_timeout_secs", "error_invalid_stop_timeout");
+    }
+
+    if !SNAPSHOT_INTERVAL_RANGE.contains(&config.status_snapshot_interval_secs) {
+        push("status_snapshot_interval_secs", "error_invalid_snapshot_interval");
+    }
+
+    if config.supernode_recheck_secs != 0 && !SUPERNODE_RECHECK_RANGE.contains(&config.supernode_recheck_secs) {
+        push("supernode_recheck_secs", "error_invalid_supernode_recheck");
+    }
+
+    if config.supernode_failover.iter().any(|address| !is_host_port(address)) {
+        push("supernode_failover", "error_invalid_supernode_failover");
+    }
+
+    if config
+        .port_forwards
+        .iter()
+        .any(|rule| !is_host_port(&rule.listen) || !is_host_port(&rule.target))
+    {
+        push("port_forwards", "error_invalid_port_forward");
+    }
+
+    if let Some(code) = wake::check(&config.wake_rules) {
+        push("wake_rules", code);
+    }
+
+    if let Some(code) = port_knock::check(&config.port_knock) {
+        push("port_knock", code);
+    }
+
+    if let Some(code) = routes::check(&config.routes) {
+        push("routes", code);
+    }
+
+    if let Some(code) = edge_options::check(&config.advanced_options) {
+        push("advanced_options", code);
+    }
+
+    if let Some(edge_path) = config.edge_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        if !edge_exists(edge_path) {
+            push("edge_path", "error_edge_path_missing");
+        }
+    }
+
+    if !config.edge_sha256.iter().all(|hash| edge_integrity::is_sha256(hash)) {
+        push("edge_sha256", "error_invalid_edge_sha256");
+    }
+
+    errors
+}
+
+/// 对照 edge 会的本事再查一遍（加密方式、包头加密、压缩方式），没问题时返回空列表
+pub fn validate_capabilities(config: &N2NConfig, caps: &EdgeCaps) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if !caps.supports_cipher(config.cipher) {
+        errors.push(FieldError {
+            field: "cipher",
+            code: "error_cipher_unsupported",
+        });
+    }
+    if config.header_encryption && !caps.header_encryption {
+        errors.push(FieldError {
+            field: "header_encryption",
+            code: "error_header_encryption_unsupported",
+        });
+    }
+    if !caps.supports_compression(config.compression) {
+        errors.push(FieldError {
+            field: "compression",
+            code: "error_compression_unsupported",
+        });
+    }
+    errors
+}
+
+/// 能拿去查 SRV 的域名（至少两段，每段是字母、数字和 `-`）
+fn is_domain(value: &str) -> bool {
+    let labels: Vec<&str> = value.trim_end_matches('.').split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.len() <= 63 && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+/// `host:port`（IPv6 要写成 `[::1]:7777`）
+pub fn is_host_port(value: &str) -> bool {
+    let Some((host, port)) = value.trim().rsplit_once(':') else {
+        return false;
+    };
+    let host_ok = match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(v6) => v6.parse::<std::net::Ipv6Addr>().is_ok(),
+        None => {
+            !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        }
+    };
+    host_ok && port.parse::<u16>().is_ok_and(|p| p != 0)
+}
+
+/// `10.0.0.2`、`10.0.0.2/24`，也认 edge 的 `static:` 前缀
+fn is_ipv4_cidr(value: &str) -> bool {
+    let value = value.trim();
+    let value = value.strip_prefix("static:").unwrap_or(value);
+    let (ip, prefix) = match value.split_once('/') {
+        Some((ip, prefix)) => (ip, Some(prefix)),
+        None => (value, None),
+    };
+    ip.parse::<Ipv4Addr>().is_ok() && prefix.is_none_or(|p| p.parse::<u8>().is_ok_and(|p| p <= 32))
+}
+
+/// 写了路径就看文件在不在；只写了名字就去 PATH 里找
+fn edge_exists(edge_path: &str) -> bool {
+    if edge_path.contains('/') || edge_path.contains('\\') {
+        Path::new(edge_path).is_file()
+    } else {
+        which::which(edge_path).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Cipher, Compression};
+
+    #[test]
+    fn test_validate_reports_each_field() {
+        let good = N2NConfig {
+            supernode: "vpn.example.com:7777".to_string(),
+            community: "home".to_string(),
+            ip_mode: "static".to_string(),
+            static_ip: Some("10.0.0.2/24".to_string()),
+            ..Default::default()
+        };
+        assert!(validate(&good).is_empty());
+        assert!(is_host_port("[fd00::1]:7654"));
+        assert!(is_ipv4_cidr("static:10.0.0.2"));
+
+        let bad = N2NConfig {
+            supernode: "vpn.example.com".to_string(),
+            community: "a-very-long-community-name".to_string(),
+            ip_mode: "static".to_string(),
+            static_ip: Some("10.0.0.300/24".to_string()),
+            mtu: Some(9000),
+            management_port: Some(0),
+            register_interval_secs: Some(0),
+            cipher: Cipher::Chacha20,
+            edge_path: Some("/no/such/dir/edge".to_string()),
+            edge_sha256: vec!["sha256:abc".to_string()],
+            supernode_failover: vec!["backup.example.com:7777".to_string(), "backup.example.com".to_string()],
+            ..Default::default()
+        };
+        let fields: Vec<_> = validate(&bad).iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("supernode", "error_invalid_supernode"),
+                ("community", "error_community_too_long"),
+                ("static_ip", "error_invalid_static_ip"),
+                ("cipher", "error_cipher_needs_key"),
+                ("mtu", "error_invalid_mtu"),
+                ("management_port", "error_invalid_management_port"),
+                ("register_interval_secs", "error_invalid_register_interval"),
+                ("supernode_failover", "error_invalid_supernode_failover"),
+                ("edge_path", "error_edge_path_missing"),
+                ("edge_sha256", "error_invalid_edge_sha256"),
+            ]
+        );
+        // 管理口换了门牌，本机端口就不能再用新的门牌
+        let moved = N2NConfig {
+            management_port: Some(5645),
+            local_port: Some(5645),
+            ..good.clone()
+        };
+        assert_eq!(validate(&moved)[0].code, "error_invalid_local_port");
+
+        // 按域名找总部时，总部地址和暗号可以先空着
+        let by_dns = N2NConfig {
+            supernode_dns: Some("vpn.example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(validate(&by_dns).is_empty());
+        let typo = N2NConfig {
+            supernode_dns: Some("vpn.example.com:7777".to_string()),
+            ..by_dns
+        };
+        assert_eq!(validate(&typo)[0].code, "error_invalid_supernode_dns");
+        assert!(validate(&N2NConfig { local_port: Some(5644), ..moved }).is_empty());
+
+        // 没带 OpenSSL、zstd 的老 edge 不会 ChaCha20、包头加密和 zstd 压缩
+        let caps = EdgeCaps {
+            version: Some("2.7.0".to_string()),
+            generation: Some(2),
+            foreground: true,
+            cipher_levels: true,
+            ciphers: vec![Cipher::Null, Cipher::Twofish, Cipher::Aes],
+            header_encryption: false,
+            compressions: vec![Compression::Lzo],
+        };
+        assert!(validate_capabilities(&good, &caps).is_empty());
+        let wants = N2NConfig {
+            header_encryption: true,
+            compression: Compression::Zstd,
+            ..bad
+        };
+        let fields: Vec<_> = validate_capabilities(&wants, &caps).iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("cipher", "error_cipher_unsupported"),
+                ("header_encryption", "error_header_encryption_unsupported"),
+                ("compression", "error_compression_unsupported"),
+            ]
+        );
+    }
+}
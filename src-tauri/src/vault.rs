@@ -0,0 +1,167 @@
+//! 上锁的指示簿（整本 config.toml 用主人的密码加密后再落盘）
+//!
+//! 几个人合用一台电脑时，别的本地用户也能翻到配置目录：密语虽然已经收进系统保险箱，
+//! 小组暗号、总部地址、方案名字还是明文躺在那里。打开“加密指示簿”后：
+//! - 用 Argon2id 把主人的密码揉成钥匙，再用 AES-256-GCM 把整本指示簿封起来；
+//! - 文件第一行是 `N2N-MAID-VAULT/1`，旧版恩兔读到会直接报解析失败，不会把它当成旧版配置去“迁移”；
+//! - 解锁后只有钥匙留在内存里（不留密码本身），锁上或者退出就忘掉；
+//! - 备份柜直接复制磁盘上的文件，所以开启之后的备份也是加密的；开启、换密码时，柜子里更早的备份
+//!   也用新钥匙重新封一遍（打不开的就扔掉），取消加密时再一起改回明文。
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+
+/// 上锁文件的第一行
+const MAGIC: &str = "N2N-MAID-VAULT/1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 指示簿上了锁、还没用密码打开
+pub const ERROR_LOCKED: &str = "error_config_locked";
+/// 密码不对（或者文件被人改过）
+pub const ERROR_WRONG_PASSWORD: &str = "error_config_wrong_password";
+/// 密码是空的
+pub const ERROR_PASSWORD_EMPTY: &str = "error_config_password_empty";
+
+/// 这段内容是不是上了锁的指示簿
+pub fn is_sealed(content: &str) -> bool {
+    content.starts_with(MAGIC)
+}
+
+/// 用主人的密码揉出来的钥匙（连同揉的时候用的盐）
+pub struct VaultKey {
+    salt: [u8; SALT_LEN],
+    key: [u8; 32],
+}
+
+impl VaultKey {
+    /// 换一把新盐揉钥匙（开启加密或者换密码时用）
+    pub fn generate(password: &str) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).map_err(|e| anyhow!("生成随机盐失败：{}", e))?;
+        Self::derive(password, salt)
+    }
+
+    /// 用密码打开一本上了锁的指示簿，返回钥匙和里面的内容
+    pub fn unlock(content: &str, password: &str) -> Result<(Self, String)> {
+        let (salt, _, _) = parse(content)?;
+        let key = Self::derive(password, salt)?;
+        let plaintext = key.open(content)?;
+        Ok((key, plaintext))
+    }
+
+    fn derive(password: &str, salt: [u8; SALT_LEN]) -> Result<Self> {
+        if password.is_empty() {
+            bail!(ERROR_PASSWORD_EMPTY);
+        }
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(password.as_bytes(), &salt, &mut key)
+            .map_err(|e| anyhow!("揉钥匙失败：{}", e))?;
+        Ok(Self { salt, key })
+    }
+
+    /// 把指示簿封起来（每次都换一个新的 nonce）
+    pub fn seal(&self, plaintext: &str) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| anyhow!("生成随机数失败：{}", e))?;
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .map_err(|_| anyhow!("加密指示簿失败"))?;
+        Ok(format!(
+            "{}\n{}\n{}\n{}\n",
+            MAGIC,
+            to_hex(&self.salt),
+            to_hex(&nonce),
+            to_hex(&ciphertext)
+        ))
+    }
+
+    /// 用这把钥匙打开（盐不一样说明是用别的密码封的，比如换密码之前的备份）
+    pub fn open(&self, content: &str) -> Result<String> {
+        let (salt, nonce, ciphertext) = parse(content)?;
+        if salt != self.salt {
+            bail!(ERROR_WRONG_PASSWORD);
+        }
+        let plaintext = self
+            .cipher()
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| anyhow!(ERROR_WRONG_PASSWORD))?;
+        String::from_utf8(plaintext).context("解开的指示簿不是 UTF-8 文本")
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.key.into())
+    }
+}
+
+impl Drop for VaultKey {
+    fn drop(&mut self) {
+        // 忘掉钥匙时顺手把内存抹干净
+        self.key.fill(0);
+    }
+}
+
+/// 拆开上锁文件：盐、nonce、密文
+fn parse(content: &str) -> Result<([u8; SALT_LEN], [u8; NONCE_LEN], Vec<u8>)> {
+    let mut lines = content.lines();
+    if lines.next() != Some(MAGIC) {
+        bail!("这不是上了锁的指示簿");
+    }
+    let mut field = |name: &str| -> Result<Vec<u8>> {
+        from_hex(lines.next().with_context(|| format!("上锁的指示簿少了{}", name))?.trim())
+            .with_context(|| format!("上锁的指示簿里的{}写坏了", name))
+    };
+    let salt = field("盐")?
+        .try_into()
+        .map_err(|_| anyhow!("上锁的指示簿里的盐长度不对"))?;
+    let nonce = field("nonce")?
+        .try_into()
+        .map_err(|_| anyhow!("上锁的指示簿里的 nonce 长度不对"))?;
+    let ciphertext = field("密文")?;
+    Ok((salt, nonce, ciphertext))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        bail!("十六进制长度不是偶数");
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| {
+            let pair = value.get(i..i + 2).context("不是十六进制")?;
+            u8::from_str_radix(pair, 16).context("不是十六进制")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_unlock() {
+        let plaintext = "active_profile = \"Default\"\n";
+        let key = VaultKey::generate("hunter2").unwrap();
+        let sealed = key.seal(plaintext).unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("Default"));
+        assert_eq!(key.open(&sealed).unwrap(), plaintext);
+
+        let (unlocked, opened) = VaultKey::unlock(&sealed, "hunter2").unwrap();
+        assert_eq!(opened, plaintext);
+        assert_eq!(unlocked.open(&key.seal("again").unwrap()).unwrap(), "again");
+
+        let wrong = VaultKey::unlock(&sealed, "hunter3").err().unwrap();
+        assert_eq!(wrong.to_string(), ERROR_WRONG_PASSWORD);
+        // 换过密码（换了盐）的钥匙打不开旧文件
+        let other = VaultKey::generate("hunter2").unwrap();
+        assert_eq!(other.open(&sealed).err().unwrap().to_string(), ERROR_WRONG_PASSWORD);
+        assert_eq!(VaultKey::generate("").err().unwrap().to_string(), ERROR_PASSWORD_EMPTY);
+    }
+}
@@ -0,0 +1,395 @@
+//! 看看有没有别的 VPN 占着同一片网段
+//!
+//! “显示已连接，可就是 ping 不通”的报告里，很多是主人电脑上还开着 WireGuard、Tailscale、OpenVPN，
+//! 它们的地址或路由和 n2n 小组的网段叠在一起（大家都爱用 192.168.x / 10.x），包被别人的网卡截走了。
+//! 建立通道前恩兔先看一眼：
+//! - 我们的网段：手动地址所在的子网（没写前缀按 edge 的默认 /24），加上主人配的路由；
+//! - 别人的网段：认得出是 VPN 的网卡（按名字和说明认）上的地址和路由；
+//! - 两边有重叠就提醒主人是哪个 VPN、哪块网卡、哪一段，连接照常继续。
+//!
+//! 自动分配地址时事先不知道网段，只看主人配的路由。默认路由（0.0.0.0/0）两边都不算，
+//! 不然开着全局代理的 VPN 会和谁都“撞”。目前会看 Linux 和 Windows。
+use serde::Serialize;
+use std::net::Ipv4Addr;
+
+use crate::config::N2NConfig;
+
+/// edge 没写前缀时用的子网宽度
+const DEFAULT_PREFIX: u8 = 24;
+
+/// 恩兔认得的 VPN
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VpnKind {
+    WireGuard,
+    Tailscale,
+    OpenVpn,
+    ZeroTier,
+}
+
+impl VpnKind {
+    /// 给主人看的名字
+    pub fn label(self) -> &'static str {
+        match self {
+            VpnKind::WireGuard => "WireGuard",
+            VpnKind::Tailscale => "Tailscale",
+            VpnKind::OpenVpn => "OpenVPN",
+            VpnKind::ZeroTier => "ZeroTier",
+        }
+    }
+}
+
+/// 按网卡名字（Linux 的 wg0、tailscale0）和说明（Windows 的 “WireGuard Tunnel”）认 VPN
+///
+/// Windows 上 n2n 自己也用 TAP-Windows，所以只有说明里写明 OpenVPN 的才算 OpenVPN。
+pub fn classify(name: &str, description: &str) -> Option<VpnKind> {
+    let name = name.to_ascii_lowercase();
+    let description = description.to_ascii_lowercase();
+    if name.starts_with("tailscale") || description.contains("tailscale") {
+        Some(VpnKind::Tailscale)
+    } else if name.starts_with("wg") || name == "nordlynx" || description.contains("wireguard") {
+        Some(VpnKind::WireGuard)
+    } else if name.starts_with("zt") || description.contains("zerotier") {
+        Some(VpnKind::ZeroTier)
+    } else if name.starts_with("tun") || description.contains("openvpn") {
+        Some(VpnKind::OpenVpn)
+    } else {
+        None
+    }
+}
+
+/// 一块启用着的网卡和它占着的网段
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    /// 网卡说明（Windows 的驱动名称，Linux 留空）
+    pub description: String,
+    /// 地址所在的子网和经过它的路由
+    pub networks: Vec<(Ipv4Addr, u8)>,
+}
+
+/// 一处撞车
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    pub vpn: VpnKind,
+    /// 对方的网卡
+    pub interface: String,
+    /// 对方占着的网段
+    pub network: String,
+    /// 被它盖住的我们的网段
+    pub ours: String,
+}
+
+/// 两段网络有没有重叠（短前缀的那段盖住了另一段的开头就算）
+pub fn overlaps(a: (Ipv4Addr, u8), b: (Ipv4Addr, u8)) -> bool {
+    let prefix = a.1.min(b.1).min(32);
+    let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    u32::from(a.0) & mask == u32::from(b.0) & mask
+}
+
+/// `10.0.0.2/24`、`static:10.0.0.2`、`192.168.2.0` 这种写法拆成网段
+fn parse_cidr(value: &str, default_prefix: u8) -> Option<(Ipv4Addr, u8)> {
+    let value = value.trim();
+    let value = value.strip_prefix("static:").unwrap_or(value);
+    let (ip, prefix) = match value.split_once('/') {
+        Some((ip, prefix)) => (ip, prefix.parse::<u8>().ok().filter(|p| *p <= 32)?),
+        None => (value, default_prefix),
+    };
+    Some((ip.parse().ok()?, prefix))
+}
+
+fn format_cidr((ip, prefix): (Ipv4Addr, u8)) -> String {
+    let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    format!("{}/{}", Ipv4Addr::from(u32::from(ip) & mask), prefix)
+}
+
+/// 这份指示簿会占用的网段
+pub fn our_networks(config: &N2NConfig) -> Vec<(Ipv4Addr, u8)> {
+    let mut networks = Vec::new();
+    if config.ip_mode == "static" {
+        networks.extend(config.static_ip.as_deref().and_then(|ip| parse_cidr(ip, DEFAULT_PREFIX)));
+    }
+    for route in &config.routes {
+        if let (Ok(ip), Some(prefix)) = (route.network.trim().parse::<Ipv4Addr>(), route.prefix_len()) {
+            networks.push((ip, prefix));
+        }
+    }
+    networks.retain(|(_, prefix)| *prefix > 0);
+    networks
+}
+
+/// 在启用着的网卡里找和我们撞车的 VPN（自己的虚拟网卡 `own_device` 不算）
+pub fn find_conflicts(ours: &[(Ipv4Addr, u8)], interfaces: &[Interface], own_device: &str) -> Vec<Conflict> {
+    let mut conflicts: Vec<Conflict> = Vec::new();
+    for iface in interfaces {
+        if iface.name.eq_ignore_ascii_case(own_device) {
+            continue;
+        }
+        let Some(vpn) = classify(&iface.name, &iface.description) else {
+            continue;
+        };
+        for &theirs in iface.networks.iter().filter(|(ip, prefix)| *prefix > 0 && !ip.is_multicast()) {
+            let Some(&hit) = ours.iter().find(|&&ours| overlaps(ours, theirs)) else {
+                continue;
+            };
+            let conflict = Conflict {
+                vpn,
+                interface: iface.name.clone(),
+                network: format_cidr(theirs),
+                ours: format_cidr(hit),
+            };
+            if !conflicts.contains(&conflict) {
+                conflicts.push(conflict);
+            }
+        }
+    }
+    conflicts
+}
+
+/// 建立通道前看一眼（会调用 `ip` 或系统接口，别放在界面线程里）
+pub fn check(config: &N2NConfig) -> Vec<Conflict> {
+    let ours = our_networks(config);
+    if ours.is_empty() {
+        return Vec::new();
+    }
+    let own_device = config.tap_device.as_deref().unwrap_or("edge0");
+    find_conflicts(&ours, &interfaces(), own_device)
+}
+
+/// `ip -o -4 addr show up` 的每一行：`3: wg0    inet 10.8.0.2/24 scope global wg0 ...`
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_ip_addr(output: &str, interfaces: &mut Vec<Interface>) {
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(_), Some(name)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let Some(network) = words.skip_while(|w| *w != "inet").nth(1).and_then(|c| parse_cidr(c, 32)) else {
+            continue;
+        };
+        push_network(interfaces, name, network);
+    }
+}
+
+/// `ip -4 route show table all` 的每一行：`192.168.1.0/24 dev tailscale0 table 52`
+/// （local/broadcast 之类开头的系统路由和默认路由不看）
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_ip_route(output: &str, interfaces: &mut Vec<Interface>) {
+    for line in output.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some(network) = words.first().and_then(|dest| parse_cidr(dest, 32)) else {
+            continue;
+        };
+        if let Some(pos) = words.iter().position(|w| *w == "dev") {
+            if let Some(name) = words.get(pos + 1) {
+                push_network(interfaces, name, network);
+            }
+        }
+    }
+}
+
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn push_network(interfaces: &mut Vec<Interface>, name: &str, network: (Ipv4Addr, u8)) {
+    match interfaces.iter_mut().find(|iface| iface.name == name) {
+        Some(iface) => iface.networks.push(network),
+        None => interfaces.push(Interface {
+            name: name.to_string(),
+            description: String::new(),
+            networks: vec![network],
+        }),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn interfaces() -> Vec<Interface> {
+    let run = |args: &[&str]| {
+        std::process::Command::new("ip")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+    };
+    let mut interfaces = Vec::new();
+    if let Some(out) = run(&["-o", "-4", "addr", "show", "up"]) {
+        parse_ip_addr(&out, &mut interfaces);
+    }
+    // Tailscale 的子网路由放在 52 号表里，所以要看全部路由表
+    if let Some(out) = run(&["-4", "route", "show", "table", "all"]) {
+        // 只算启用着的网卡上的路由
+        let mut routes = Vec::new();
+        parse_ip_route(&out, &mut routes);
+        for route in routes {
+            if let Some(iface) = interfaces.iter_mut().find(|iface| iface.name == route.name) {
+                iface.networks.extend(route.networks);
+            }
+        }
+    }
+    interfaces
+}
+
+/// Windows：启用着的网卡的 IPv4 地址（带前缀）和挂在上面的路由
+#[cfg(target_os = "windows")]
+fn interfaces() -> Vec<Interface> {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        FreeMibTable, GetAdaptersAddresses, GetIpForwardTable2, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+        GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH, MIB_IPFORWARD_TABLE2,
+    };
+    use windows_sys::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, SOCKADDR_IN};
+
+    unsafe fn wide(ptr: *const u16) -> String {
+        if ptr.is_null() {
+            return String::new();
+        }
+        let mut n = 0;
+        while *ptr.add(n) != 0 {
+            n += 1;
+        }
+        String::from_utf16_lossy(std::slice::from_raw_parts(ptr, n))
+    }
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64>;
+    loop {
+        buffer = vec![0u64; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                AF_INET as u32,
+                flags,
+                std::ptr::null(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES_LH,
+                &mut size,
+            )
+        };
+        match ret {
+            NO_ERROR => break,
+            ERROR_BUFFER_OVERFLOW => continue,
+            other => {
+                log::debug!("GetAdaptersAddresses 失败：{}", other);
+                return Vec::new();
+            }
+        }
+    }
+
+    // (接口编号, 网卡)
+    let mut adapters: Vec<(u32, Interface)> = Vec::new();
+    let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while !current.is_null() {
+        let adapter = unsafe { &*current };
+        if adapter.OperStatus == IfOperStatusUp {
+            let mut iface = Interface {
+                name: unsafe { wide(adapter.FriendlyName) },
+                description: unsafe { wide(adapter.Description) },
+                networks: Vec::new(),
+            };
+            let mut unicast = adapter.FirstUnicastAddress;
+            while !unicast.is_null() {
+                let entry = unsafe { &*unicast };
+                let sockaddr = entry.Address.lpSockaddr;
+                if !sockaddr.is_null() && unsafe { (*sockaddr).sa_family } == AF_INET {
+                    let sin = unsafe { &*(sockaddr as *const SOCKADDR_IN) };
+                    let ip = Ipv4Addr::from(unsafe { sin.sin_addr.S_un.S_addr }.to_ne_bytes());
+                    if !ip.is_link_local() {
+                        iface.networks.push((ip, entry.OnLinkPrefixLength));
+                    }
+                }
+                unicast = entry.Next;
+            }
+            adapters.push((unsafe { adapter.Anonymous1.Anonymous.IfIndex }, iface));
+        }
+        current = adapter.Next;
+    }
+
+    let mut table: *mut MIB_IPFORWARD_TABLE2 = std::ptr::null_mut();
+    if unsafe { GetIpForwardTable2(AF_INET, &mut table) } == 0 && !table.is_null() {
+        unsafe {
+            let rows = std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+            for row in rows {
+                if let Some((_, iface)) = adapters.iter_mut().find(|(index, _)| *index == row.InterfaceIndex) {
+                    let sin: SOCKADDR_IN = row.DestinationPrefix.Prefix.Ipv4;
+                    let dest = Ipv4Addr::from(sin.sin_addr.S_un.S_addr.to_ne_bytes());
+                    if !dest.is_broadcast() {
+                        iface.networks.push((dest, row.DestinationPrefix.PrefixLength));
+                    }
+                }
+            }
+            FreeMibTable(table as *const _);
+        }
+    }
+    adapters.into_iter().map(|(_, iface)| iface).collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn interfaces() -> Vec<Interface> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routes::Route;
+
+    #[test]
+    fn test_find_conflicts() {
+        assert_eq!(classify("wg0", ""), Some(VpnKind::WireGuard));
+        assert_eq!(classify("tailscale0", ""), Some(VpnKind::Tailscale));
+        assert_eq!(classify("以太网 3", "WireGuard Tunnel"), Some(VpnKind::WireGuard));
+        assert_eq!(classify("以太网 2", "TAP-Windows Adapter V9"), None);
+        assert_eq!(classify("eth0", ""), None);
+
+        let net = |s: &str| parse_cidr(s, 32).unwrap();
+        assert!(overlaps(net("192.168.1.0/24"), net("192.168.0.0/16")));
+        assert!(!overlaps(net("192.168.1.0/24"), net("192.168.2.0/24")));
+
+        let config = N2NConfig {
+            ip_mode: "static".to_string(),
+            static_ip: Some("static:192.168.100.5".to_string()),
+            routes: vec![Route {
+                network: "10.20.0.0".to_string(),
+                netmask: "255.255.0.0".to_string(),
+                gateway: "192.168.100.1".to_string(),
+            }],
+            ..Default::default()
+        };
+        let ours = our_networks(&config);
+        assert_eq!(ours, vec![net("192.168.100.5/24"), net("10.20.0.0/16")]);
+
+        let mut interfaces = Vec::new();
+        parse_ip_addr(
+            "1: lo    inet 127.0.0.1/8 scope host lo\n\
+             3: wg0    inet 192.168.100.77/24 brd 192.168.100.255 scope global wg0\n\
+             4: edge0    inet 192.168.100.5/24 scope global edge0\n\
+             5: tailscale0    inet 100.90.1.2/32 scope global tailscale0\n",
+            &mut interfaces,
+        );
+        parse_ip_route(
+            "default dev wg0 table 51820 scope link\n\
+             10.20.30.0/24 dev tailscale0 table 52\n\
+             local 100.90.1.2 dev tailscale0 table local proto kernel scope host src 100.90.1.2\n",
+            &mut interfaces,
+        );
+        let conflicts = find_conflicts(&ours, &interfaces, "edge0");
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    vpn: VpnKind::WireGuard,
+                    interface: "wg0".to_string(),
+                    network: "192.168.100.0/24".to_string(),
+                    ours: "192.168.100.0/24".to_string(),
+                },
+                Conflict {
+                    vpn: VpnKind::Tailscale,
+                    interface: "tailscale0".to_string(),
+                    network: "10.20.30.0/24".to_string(),
+                    ours: "10.20.0.0/16".to_string(),
+                },
+            ]
+        );
+        assert!(check(&N2NConfig::default()).is_empty());
+    }
+}
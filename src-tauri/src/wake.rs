@@ -0,0 +1,242 @@
+//! 唤醒同伴（网络唤醒 Wake-on-LAN，可以定时）
+//!
+//! 家里的 NAS 平时睡着省电，晚上备份前得有人把它叫起来。主人在方案里记几条唤醒规则：
+//! - 每条写明同伴网卡的 MAC、它在虚拟网里的地址，以及每天几点叫它（本地时间 `HH:MM`，留空就只能手动叫）；
+//! - 到点后恩兔把魔术包同时发到本机局域网和虚拟网的广播地址（UDP 9 端口），然后每隔几秒 ping 一下，
+//!   最多等 `VERIFY_TIMEOUT`，确认它真的上线了；
+//! - 每次的结果（几秒后上线、还是没叫醒）记进 `wake-history.jsonl`，并告诉主人一声。
+//!
+//! 恩兔没开着的时候错过的时间点不会补叫。
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::n2n_process;
+use crate::tap_adapter;
+
+/// 叫完之后最多等同伴多久
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(180);
+/// 等待期间多久 ping 一次
+const VERIFY_INTERVAL: Duration = Duration::from_secs(5);
+/// 魔术包发往的端口（discard）
+const WOL_PORT: u16 = 9;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 一条唤醒规则（记在方案里）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WakeRule {
+    /// 备注（例如“NAS”）
+    #[serde(default)]
+    pub name: String,
+    /// 同伴网卡的 MAC
+    pub mac: String,
+    /// 同伴在虚拟网里的地址（叫完之后用它确认上线）
+    pub peer_ip: String,
+    /// 每天几点叫（本地时间 `HH:MM`，留空只能手动叫）
+    #[serde(default)]
+    pub at: Option<String>,
+    /// 是否按时叫
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// 一次唤醒的结果（记进唤醒流水账）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WakeRecord {
+    pub name: String,
+    pub mac: String,
+    pub peer_ip: String,
+    /// 按时叫的（false 表示主人手动叫的）
+    pub scheduled: bool,
+    /// 发出魔术包的时间（Unix 秒）
+    pub sent_at: u64,
+    /// 同伴过了几秒上线（等到超时还没上线为 None）
+    pub online_after_secs: Option<u64>,
+    /// 魔术包没发出去时的原因
+    pub error: Option<String>,
+}
+
+/// `HH:MM` 换算成一天中的第几分钟
+pub fn parse_at(at: &str) -> Option<u16> {
+    let (hour, minute) = at.trim().split_once(':')?;
+    let (hour, minute) = (hour.parse::<u16>().ok()?, minute.parse::<u16>().ok()?);
+    (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+}
+
+/// 检查唤醒规则，有写错的返回 `error_` 编号
+pub fn check(rules: &[WakeRule]) -> Option<&'static str> {
+    let bad = |rule: &WakeRule| {
+        let mac_ok = tap_adapter::is_assignable_mac(&rule.mac);
+        let ip_ok = rule.peer_ip.trim().parse::<Ipv4Addr>().is_ok();
+        let at_ok = rule
+            .at
+            .as_deref()
+            .filter(|at| !at.trim().is_empty())
+            .is_none_or(|at| parse_at(at).is_some());
+        !(mac_ok && ip_ok && at_ok)
+    };
+    rules.iter().any(bad).then_some("error_invalid_wake_rule")
+}
+
+/// 魔术包：6 个 0xFF，后面跟 16 遍 MAC
+pub fn magic_packet(mac: &str) -> Option<Vec<u8>> {
+    let mac = tap_adapter::parse_mac(mac)?;
+    let mut packet = vec![0xff; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(&mac);
+    }
+    Some(packet)
+}
+
+/// 虚拟网的广播地址（按拿到的地址和掩码算）
+pub fn broadcast_of(ip: &str, mask: &str) -> Option<Ipv4Addr> {
+    let ip = u32::from(ip.trim().parse::<Ipv4Addr>().ok()?);
+    let mask = u32::from(mask.trim().parse::<Ipv4Addr>().ok()?);
+    Some(Ipv4Addr::from(ip | !mask))
+}
+
+/// 把魔术包发到本机局域网，以及（连着时）虚拟网的广播地址
+pub fn send(mac: &str, virtual_broadcast: Option<Ipv4Addr>) -> Result<()> {
+    let packet = magic_packet(mac).with_context(|| format!("MAC 写法不对：{}", mac))?;
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).context("打开 UDP 套接字失败")?;
+    socket.set_broadcast(true).context("打开广播失败")?;
+    socket
+        .send_to(&packet, (Ipv4Addr::BROADCAST, WOL_PORT))
+        .context("发送魔术包失败")?;
+    if let Some(broadcast) = virtual_broadcast {
+        if let Err(e) = socket.send_to(&packet, (broadcast, WOL_PORT)) {
+            log::debug!("魔术包没发进虚拟网（{}）：{}", broadcast, e);
+        }
+    }
+    Ok(())
+}
+
+/// 叫醒同伴并等它上线（会阻塞最多 `VERIFY_TIMEOUT`，放到后台线程里用）
+pub fn wake_and_verify(rule: &WakeRule, virtual_broadcast: Option<Ipv4Addr>, scheduled: bool) -> WakeRecord {
+    let mut record = WakeRecord {
+        name: rule.name.clone(),
+        mac: rule.mac.clone(),
+        peer_ip: rule.peer_ip.clone(),
+        scheduled,
+        sent_at: n2n_process::unix_now_seconds(),
+        online_after_secs: None,
+        error: None,
+    };
+    if let Err(e) = send(&rule.mac, virtual_broadcast) {
+        record.error = Some(format!("{:#}", e));
+        return record;
+    }
+    log::info!("已经发出唤醒魔术包：{}（{}）", rule.name, rule.mac);
+
+    let started = Instant::now();
+    while started.elapsed() < VERIFY_TIMEOUT {
+        if matches!(n2n_process::ping_once(rule.peer_ip.trim(), 1000), Ok(Some(_))) {
+            record.online_after_secs = Some(started.elapsed().as_secs());
+            break;
+        }
+        thread::sleep(VERIFY_INTERVAL);
+    }
+    record
+}
+
+/// 现在的本地日期编号和一天中的第几分钟
+#[cfg(unix)]
+pub fn local_clock() -> Option<(u32, u16)> {
+    let now = unsafe { libc::time(std::ptr::null_mut()) };
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return None;
+    }
+    let day = (tm.tm_year as u32) * 1000 + tm.tm_yday as u32;
+    Some((day, (tm.tm_hour * 60 + tm.tm_min) as u16))
+}
+
+#[cfg(target_os = "windows")]
+pub fn local_clock() -> Option<(u32, u16)> {
+    use windows_sys::Win32::System::SystemInformation::GetLocalTime;
+
+    let mut st = unsafe { std::mem::zeroed() };
+    unsafe { GetLocalTime(&mut st) };
+    let day = st.wYear as u32 * 10000 + st.wMonth as u32 * 100 + st.wDay as u32;
+    Some((day, st.wHour * 60 + st.wMinute))
+}
+
+#[cfg(not(any(unix, target_os = "windows")))]
+pub fn local_clock() -> Option<(u32, u16)> {
+    None
+}
+
+/// 闹钟（记着每条规则今天叫过没有）
+#[derive(Debug, Default)]
+pub struct Schedule {
+    fired: HashMap<String, u32>,
+}
+
+impl Schedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 到点该叫的规则（同一条规则一天只叫一次）
+    pub fn due(&mut self, rules: &[WakeRule], day: u32, minute: u16) -> Vec<WakeRule> {
+        rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter(|rule| rule.at.as_deref().and_then(parse_at) == Some(minute))
+            .filter(|rule| {
+                let key = format!("{}|{}|{}", rule.name, rule.mac, minute);
+                self.fired.insert(key, day) != Some(day)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(at: Option<&str>) -> WakeRule {
+        WakeRule {
+            name: "NAS".to_string(),
+            mac: "00:11:32:aa:bb:cc".to_string(),
+            peer_ip: "10.0.0.5".to_string(),
+            at: at.map(str::to_string),
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_wake_rules_and_schedule() {
+        let packet = magic_packet("00-11-32-AA-BB-CC").unwrap();
+        assert_eq!(packet.len(), 102);
+        assert_eq!(&packet[..6], &[0xff; 6]);
+        assert_eq!(&packet[96..], &[0x00, 0x11, 0x32, 0xaa, 0xbb, 0xcc]);
+        assert_eq!(broadcast_of("10.0.0.2", "255.255.255.0"), Some(Ipv4Addr::new(10, 0, 0, 255)));
+
+        assert_eq!(parse_at("22:00"), Some(1320));
+        assert_eq!(parse_at("24:00"), None);
+        assert_eq!(check(&[rule(Some("22:00")), rule(None), rule(Some(""))]), None);
+        assert_eq!(check(&[rule(Some("22:60"))]), Some("error_invalid_wake_rule"));
+        let mut bad_ip = rule(None);
+        bad_ip.peer_ip = "nas.local".to_string();
+        assert_eq!(check(&[bad_ip]), Some("error_invalid_wake_rule"));
+
+        // 到点叫一次，同一天不再叫，第二天照叫；手动规则和停用的规则不按时叫
+        let mut off = rule(Some("22:00"));
+        off.enabled = false;
+        let rules = vec![rule(Some("22:00")), rule(None), off];
+        let mut schedule = Schedule::new();
+        assert!(schedule.due(&rules, 1, 1319).is_empty());
+        assert_eq!(schedule.due(&rules, 1, 1320), vec![rule(Some("22:00"))]);
+        assert!(schedule.due(&rules, 1, 1320).is_empty());
+        assert_eq!(schedule.due(&rules, 2, 1320).len(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+//! 自我巡查（定期看一眼恩兔自己的账本和 edge 的实际情况对不对得上）
+//!
+//! 状态锁、进程句柄和看护员是分开的，万一哪里卡住（看护员意外收工、停止流程没收尾），
+//! 以前只能重启恩兔。现在后台每隔几秒巡查一次，发现下面这些对不上的情况就自己修好并告诉主人：
+//! - 手里还攥着 edge 的句柄，但进程其实已经退出了（看护员没接住）；
+//! - 状态说在连接/已连接/收拾中，手里却没有 edge，也没有排队中的重连；
+//! - “收拾工具中”超过 60 秒还没收拾完。
+//!
+//! 为了不和正常的状态切换撞车，同一个问题要连续两次巡查都看到才动手。
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::n2n_process::ConnectionStatus;
+
+/// 多久巡查一次
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// “收拾工具中”最多容忍多久
+pub const STUCK_DISCONNECTING: Duration = Duration::from_secs(60);
+
+/// 巡查发现的对不上的情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateIssue {
+    /// 句柄还在，进程已经退出了
+    ChildGone,
+    /// 状态说在工作，手里却没有 edge
+    NoChild,
+    /// 收拾工具太久了
+    StuckDisconnecting,
+}
+
+impl StateIssue {
+    /// 稳定的机器可读编号（事件和日志里用）
+    pub fn code(&self) -> &'static str {
+        match self {
+            StateIssue::ChildGone => "child_gone",
+            StateIssue::NoChild => "no_child",
+            StateIssue::StuckDisconnecting => "stuck_disconnecting",
+        }
+    }
+}
+
+/// 一次巡查时看到的样子
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// 手里有没有 edge 的句柄
+    pub has_child: bool,
+    /// 句柄对应的进程是不是已经退出了
+    pub child_exited: bool,
+    /// 账本上的状态
+    pub status: ConnectionStatus,
+    /// 是否正在等待下一次自动重连（这时没有 edge 是正常的）
+    pub reconnect_pending: bool,
+}
+
+/// 巡查员（记着上一次的怀疑和“收拾中”是从什么时候开始的）
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    disconnecting_since: Option<Instant>,
+    suspect: Option<StateIssue>,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 看一眼：确认有问题（连续两次都看到）时返回它
+    pub fn inspect(&mut self, obs: &Observation, now: Instant) -> Option<StateIssue> {
+        if obs.status == ConnectionStatus::Disconnecting {
+            self.disconnecting_since.get_or_insert(now);
+        } else {
+            self.disconnecting_since = None;
+        }
+
+        let found = self.find(obs, now);
+        let confirmed = found.filter(|issue| self.suspect == Some(*issue));
+        self.suspect = if confirmed.is_some() { None } else { found };
+        if confirmed == Some(StateIssue::StuckDisconnecting) {
+            self.disconnecting_since = None;
+        }
+        confirmed
+    }
+
+    fn find(&self, obs: &Observation, now: Instant) -> Option<StateIssue> {
+        if obs.has_child {
+            if obs.child_exited {
+                return Some(StateIssue::ChildGone);
+            }
+            let stuck = self
+                .disconnecting_since
+                .is_some_and(|since| now.duration_since(since) >= STUCK_DISCONNECTING);
+            return stuck.then_some(StateIssue::StuckDisconnecting);
+        }
+        let busy = matches!(
+            obs.status,
+            ConnectionStatus::Connecting
+                | ConnectionStatus::Connected(_)
+                | ConnectionStatus::Disconnecting
+                | ConnectionStatus::Reconnecting(_)
+        );
+        (busy && !obs.reconnect_pending).then_some(StateIssue::NoChild)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obs(has_child: bool, child_exited: bool, status: ConnectionStatus) -> Observation {
+        Observation {
+            has_child,
+            child_exited,
+            status,
+            reconnect_pending: false,
+        }
+    }
+
+    #[test]
+    fn test_watchdog_confirms_issues_twice() {
+        let start = Instant::now();
+        let mut dog = Watchdog::new();
+
+        // 正常工作、正常待命、排队重连时都不吭声
+        assert_eq!(dog.inspect(&obs(true, false, ConnectionStatus::Connected(None)), start), None);
+        assert_eq!(dog.inspect(&obs(false, false, ConnectionStatus::Disconnected), start), None);
+        let mut waiting = obs(false, false, ConnectionStatus::Connecting);
+        waiting.reconnect_pending = true;
+        assert_eq!(dog.inspect(&waiting, start), None);
+        assert_eq!(dog.inspect(&waiting, start), None);
+
+        // 一次看到可能只是正在切换，连续两次才算数
+        let gone = obs(true, true, ConnectionStatus::Connected(None));
+        assert_eq!(dog.inspect(&gone, start), None);
+        assert_eq!(dog.inspect(&gone, start), Some(StateIssue::ChildGone));
+
+        let orphan = obs(false, false, ConnectionStatus::Connected(None));
+        assert_eq!(dog.inspect(&orphan, start), None);
+        assert_eq!(dog.inspect(&obs(false, false, ConnectionStatus::Disconnected), start), None);
+        assert_eq!(dog.inspect(&orphan, start), None);
+        assert_eq!(dog.inspect(&orphan, start), Some(StateIssue::NoChild));
+
+        // 收拾中：60 秒内不算卡住
+        let stopping = obs(true, false, ConnectionStatus::Disconnecting);
+        assert_eq!(dog.inspect(&stopping, start), None);
+        assert_eq!(dog.inspect(&stopping, start + Duration::from_secs(59)), None);
+        assert_eq!(dog.inspect(&stopping, start + Duration::from_secs(60)), None);
+        assert_eq!(
+            dog.inspect(&stopping, start + Duration::from_secs(65)),
+            Some(StateIssue::StuckDisconnecting)
+        );
+        assert_eq!(StateIssue::StuckDisconnecting.code(), "stuck_disconnecting");
+    }
+}
@@ -130,7 +130,7 @@ fn launch_installer(installer: &PathBuf) -> Result<()> {
 }
 
 /// 检查系统里是否已安装 TAP-Windows（常见 ComponentId 为 tap0901）
-fn is_tap_windows_installed() -> Result<bool> {
+pub(crate) fn is_tap_windows_installed() -> Result<bool> {
     let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
 
     // 方式 1：检查经典服务名（OpenVPN TAP 9）
@@ -2,11 +2,12 @@
  * N2N Maid 主界面 - 恩兔酱的工作台 💖
  * 横向布局：左侧背景立绘，右侧卡片化操作面板
  */
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { downloadDir, join } from '@tauri-apps/api/path';
 import { useTranslation } from 'react-i18next';
-import { N2NConfig, ConnectionStatus, StatusResponse, NetworkInfo, PeerNodeInfo, defaultConfig } from './types';
+import { N2NConfig, ConnectionStatus, StatusResponse, SideConnection, NetworkInfo, TransportFeatures, TapAdapter, Route, PeerNodeInfo, BulkReport, PingOutcome, NameOutcome, EdgeStats, EdgeWarning, AssistantMessage, AssistantSeverity, ConfigEncryption, ForwardStatus, SessionSummary, ProfileSummary, ElevationReport, StartupReport, SafeMode, OrphanEdge, SupernodeKeyChange, EdgeBinaryAlert, PortableProfile, ShareInvite, AppPreferences, defaultConfig } from './types';
 import LogViewer from './components/LogViewer';
 import Settings from './components/Settings';
 
@@ -16,6 +17,8 @@ const getBackgroundImage = (status: ConnectionStatus): string => {
     case 'connected':
       return '/assets/bg-connected.png';
     case 'connecting':
+    case 'degraded':
+    case 'reconnecting':
     case 'disconnecting':
       return '/assets/bg-connecting.png';
     case 'error':
@@ -25,14 +28,75 @@ const getBackgroundImage = (status: ConnectionStatus): string => {
   }
 };
 
+// 换上后端广播的配色（样式表按 data-theme 切换）
+const applyTheme = (theme: string) => {
+  document.documentElement.dataset.theme = theme === 'dark' ? 'dark' : 'light';
+};
+
+// 把秒数整理成“1h 2m 3s”这样好读的样子
+const formatDuration = (secs: number): string => {
+  const h = Math.floor(secs / 3600);
+  const m = Math.floor((secs % 3600) / 60);
+  const s = secs % 60;
+  if (h > 0) return `${h}h ${m}m ${s}s`;
+  if (m > 0) return `${m}m ${s}s`;
+  return `${s}s`;
+};
+
+// 把字节数整理成“1.5 MiB”这样好读的样子
+const formatBytes = (bytes: number): string => {
+  const units = ['B', 'KiB', 'MiB', 'GiB', 'TiB'];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return unit === 0 ? `${value} B` : `${value.toFixed(1)} ${units[unit]}`;
+};
+
 function App() {
   const { t, i18n } = useTranslation();
   const [config, setConfig] = useState<N2NConfig>(defaultConfig);
   const [status, setStatus] = useState<ConnectionStatus>('disconnected');
   const [errorMessage, setErrorMessage] = useState<string | null>(null);
+  const [exitReason, setExitReason] = useState<string | null>(null);
   const [networkInfo, setNetworkInfo] = useState<NetworkInfo | null>(null);
+  const [transport, setTransport] = useState<TransportFeatures | null>(null);
+  const [tapAdapter, setTapAdapter] = useState<TapAdapter | null>(null);
+  const [nodeName, setNodeName] = useState<string | null>(null);
+  const [routes, setRoutes] = useState<Route[]>([]);
+  const [localPort, setLocalPort] = useState<number | null>(null);
+  const [registerInterval, setRegisterInterval] = useState<number | null>(null);
+  const [reconnectAttempt, setReconnectAttempt] = useState(0);
+  const [forwards, setForwards] = useState<ForwardStatus[]>([]);
   const [peers, setPeers] = useState<PeerNodeInfo[]>([]);
+  const [peerHostnames, setPeerHostnames] = useState<Record<string, string>>({});
+  const [peerBulkBusy, setPeerBulkBusy] = useState(false);
+  const [edgeStats, setEdgeStats] = useState<EdgeStats | null>(null);
+  const [warnings, setWarnings] = useState<EdgeWarning[]>([]);
+  const [lastSession, setLastSession] = useState<SessionSummary | null>(null);
+  const [maidMessages, setMaidMessages] = useState<AssistantMessage[]>([]);
+  // 主人关掉了提醒气泡的话，只留警告和错误（监听器里读，所以放在 ref 里）
+  const notificationsRef = useRef(true);
+  const [profiles, setProfiles] = useState<ProfileSummary[]>([]);
+  const [sideConnections, setSideConnections] = useState<SideConnection[]>([]);
+  const [elevationAdvice, setElevationAdvice] = useState<string[]>([]);
+  const [keyChange, setKeyChange] = useState<SupernodeKeyChange | null>(null);
+  const [edgeAlert, setEdgeAlert] = useState<EdgeBinaryAlert | null>(null);
+  // 正在分享的邀请链接（可复制，也可以交给二维码工具）
+  const [shareLink, setShareLink] = useState<string | null>(null);
+  const [startupReport, setStartupReport] = useState<StartupReport | null>(null);
+  const [startupDismissed, setStartupDismissed] = useState(false);
+  const [safeMode, setSafeMode] = useState<SafeMode | null>(null);
+  const [orphans, setOrphans] = useState<OrphanEdge[]>([]);
+  // 指示簿加密了、还没解锁
+  const [configLocked, setConfigLocked] = useState(false);
+  const [unlockPassword, setUnlockPassword] = useState('');
+  const [unlockError, setUnlockError] = useState<string | null>(null);
   const [showSettings, setShowSettings] = useState(false);
+  // 自动重连被临时叫停到什么时候（Unix 秒）
+  const [reconnectSuspendedUntil, setReconnectSuspendedUntil] = useState<number | null>(null);
   const [loading, setLoading] = useState(false);
 
   // 读取主人的指示
@@ -45,27 +109,485 @@ function App() {
         setStatus('disconnecting');
         setErrorMessage(null);
       });
+    // edge 的非致命提醒：只记下来给主人看，不改变工作状态
+    const unlistenWarning = listen<EdgeWarning>('warning', (event) => {
+      setWarnings((prev) => [...prev, event.payload].slice(-5));
+    });
+    // 恩兔想说的话：和原始日志分开，只留最近几句画成气泡
+    const unlistenAssistant = listen<AssistantMessage>('assistant-message', (event) => {
+      const important = event.payload.severity === 'warning' || event.payload.severity === 'error';
+      if (!notificationsRef.current && !important) return;
+      setMaidMessages((prev) => [...prev, event.payload].slice(-5));
+    });
+    void invoke<AppPreferences>('get_preferences')
+      .then((preferences) => (notificationsRef.current = preferences.notifications))
+      .catch(() => {});
+    const unlistenPreferences = listen<AppPreferences>('preferences-changed', (event) => {
+      notificationsRef.current = event.payload.notifications;
+    });
+    // 配色由后端统一管理：先问一次当前配色，之后跟着 theme-changed 切换
+    void invoke<string>('get_effective_theme').then(applyTheme).catch(() => {});
+    const unlistenTheme = listen<string>('theme-changed', (event) => applyTheme(event.payload));
+    // 权限体检：只把需要主人动手的建议摆出来（“后端本来就需要管理员”这类就不啰嗦了）
+    const showElevation = (report: ElevationReport) =>
+      setElevationAdvice(report.advice.filter((a) => a !== 'elevation_required_by_backend'));
+    void invoke<ElevationReport>('get_elevation_status').then(showElevation).catch(() => {});
+    const unlistenElevation = listen<ElevationReport>('elevation-advice', (event) => showElevation(event.payload));
+    // 开门体检单：后台填好后会广播；如果广播时界面还没准备好，就主动要一份
+    void invoke<StartupReport | null>('get_startup_report')
+      .then((report) => report && setStartupReport(report))
+      .catch(() => {});
+    const unlistenStartup = listen<StartupReport>('startup-report', (event) => setStartupReport(event.payload));
+    // 安全模式：接连几次没好好收工，这次不自动连接（广播时界面可能还没准备好，也主动问一次）
+    void invoke<SafeMode | null>('get_safe_mode').then(setSafeMode).catch(() => {});
+    const unlistenSafeMode = listen<SafeMode>('safe-mode', (event) => setSafeMode(event.payload));
+    // 没人照看的 edge：开门时后端找过一遍会广播，界面准备好后也主动找一次
+    void invoke<OrphanEdge[]>('find_orphan_edges').then(setOrphans).catch(() => {});
+    const unlistenOrphans = listen<OrphanEdge[]>('orphan-edges', (event) => setOrphans(event.payload));
+    // 本次工作结束：记下总结，待命时给主人看一眼
+    const unlistenSession = listen<SessionSummary>('session-ended', (event) => {
+      setLastSession(event.payload);
+    });
+    // 启动时自动建立通道：马上刷新一次状态，失败的话把原因摆出来
+    const unlistenAutoConnect = listen<{ error: string | null }>('auto-connect', (event) => {
+      void checkStatus().then(() => {
+        if (event.payload.error) setErrorMessage(event.payload.error);
+      });
+    });
+    // 网络变了（按认路对照表换了方案）或者到点换了密语：重新读一遍指示
+    const unlistenProfileSwitched = listen<string>('profile-switched', () => {
+      void loadConfig();
+    });
+    // 主人用编辑器直接改了 config.toml（正在填的设置表单不受影响，保存时才会覆盖）
+    const unlistenConfigChanged = listen('config-changed', () => {
+      void loadConfig();
+    });
+    // 点了 n2n-maid:// 邀请链接：后端先收着，这里取走后请主人确认（刚打开时也主动取一次）
+    const takeDeepLink = () =>
+      void invoke<string | null>('take_deep_link')
+        .then((link) => link && joinWithLink(link))
+        .catch(() => {});
+    takeDeepLink();
+    const unlistenDeepLink = listen('deep-link', takeDeepLink);
+    // 总部公钥和记下的不一样：拦下来大声提醒主人
+    const unlistenKeyChange = listen<SupernodeKeyChange>('supernode-key-changed', (event) => setKeyChange(event.payload));
+    // edge 的指纹对不上：同样拦下来提醒主人
+    const unlistenEdgeAlert = listen<EdgeBinaryAlert>('edge-binary-rejected', (event) => setEdgeAlert(event.payload));
     return () => {
       clearInterval(interval);
       void unlistenPromise.then((unlisten) => unlisten());
+      void unlistenWarning.then((unlisten) => unlisten());
+      void unlistenAssistant.then((unlisten) => unlisten());
+      void unlistenSession.then((unlisten) => unlisten());
+      void unlistenTheme.then((unlisten) => unlisten());
+      void unlistenElevation.then((unlisten) => unlisten());
+      void unlistenStartup.then((unlisten) => unlisten());
+      void unlistenSafeMode.then((unlisten) => unlisten());
+      void unlistenOrphans.then((unlisten) => unlisten());
+      void unlistenKeyChange.then((unlisten) => unlisten());
+      void unlistenEdgeAlert.then((unlisten) => unlisten());
+      void unlistenAutoConnect.then((unlisten) => unlisten());
+      void unlistenProfileSwitched.then((unlisten) => unlisten());
+      void unlistenConfigChanged.then((unlisten) => unlisten());
+      void unlistenPreferences.then((unlisten) => unlisten());
+      void unlistenDeepLink.then((unlisten) => unlisten());
     };
   }, []);
 
+  // 聊天气泡按轻重换颜色
+  const bubbleStyles: Record<AssistantSeverity, string> = {
+    info: 'border-gray-200 bg-gray-50 text-gray-700',
+    success: 'border-green-200 bg-green-50 text-green-700',
+    warning: 'border-amber-200 bg-amber-50 text-amber-800',
+    error: 'border-red-200 bg-red-50 text-red-700',
+  };
+
+  // 气泡里的原因码（exit_/error_ 开头）先翻译成主人看得懂的话
+  const translateReason = (params: AssistantMessage['params']) => {
+    const reason = params.reason;
+    return typeof reason === 'string' && /^(exit|error)_/.test(reason) ? { ...params, reason: t(reason) } : params;
+  };
+
+  // 开门体检单里值得主人看一眼的条目
+  const startupNotes = (): string[] => {
+    if (!startupReport) return [];
+    const notes: string[] = [];
+    // 上了锁的指示簿由解锁卡片负责提醒
+    if (!startupReport.configOk && startupReport.configError !== 'error_config_locked') notes.push(`${t('startup_config_error')}: ${startupReport.configError ?? ''}`);
+    if (startupReport.configMigrated) notes.push(t('startup_config_migrated'));
+    startupReport.checks
+      .filter((c) => !c.ok && c.id !== 'elevation')
+      .forEach((c) => notes.push(t(`startup_check_${c.id}`, { detail: c.detail ?? '' })));
+    // 安全模式下体检单全部摊开：通过的项目也列出来
+    if (safeMode) {
+      startupReport.checks.filter((c) => c.ok).forEach((c) => notes.push(t('startup_check_passed', { id: c.id })));
+    }
+    const last = startupReport.lastSession;
+    if (last) {
+      const time = new Date(last.updatedAt * 1000).toLocaleString();
+      notes.push(
+        last.stillRunning
+          ? t('startup_last_session_running', { community: last.community, pid: last.pid ?? '?' })
+          : t('startup_last_session_ended', { time, community: last.community })
+      );
+    }
+    if (startupReport.previousSessionFound && !last?.stillRunning) notes.push(t('startup_previous_session'));
+    return notes;
+  };
+
+  // 主人确认没事了：退出安全模式（下次打开恩兔照常自动连接）
+  const handleLeaveSafeMode = async () => {
+    try {
+      await invoke('leave_safe_mode');
+      setSafeMode(null);
+    } catch (error) {
+      alert(`${t('safe_mode_leave_failed')}: ${error}`);
+    }
+  };
+
+  // 没人照看的 edge：接手（对得上当前方案时）或者请它下班，处理完从列表里划掉
+  const handleOrphan = async (orphan: OrphanEdge, action: 'adopt' | 'terminate') => {
+    try {
+      await invoke(action === 'adopt' ? 'adopt_orphan_edge' : 'terminate_orphan_edge', { pid: orphan.pid });
+      setOrphans((list) => list.filter((o) => o.pid !== orphan.pid));
+      await checkStatus();
+    } catch (error) {
+      alert(`${t('orphan_failed')}: ${t(String(error))}`);
+    }
+  };
+
   const loadConfig = async () => {
     try {
+      const encryption = await invoke<ConfigEncryption>('get_config_encryption');
+      setConfigLocked(encryption.locked);
+      if (encryption.locked) return;
       const loadedConfig = await invoke<N2NConfig>('get_config');
       setConfig(loadedConfig);
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
     } catch (error) {
       console.error('读取主人指示失败：', error);
     }
   };
 
+  // 用密码打开加密的指示簿
+  const handleUnlockConfig = async () => {
+    try {
+      await invoke('unlock_config', { password: unlockPassword });
+      setUnlockPassword('');
+      setUnlockError(null);
+      await loadConfig();
+    } catch (error) {
+      setUnlockError(String(error));
+    }
+  };
+
+  // 换用另一份工作方案
+  const handleSwitchProfile = async (name: string) => {
+    try {
+      const switched = await invoke<N2NConfig>('switch_profile', { name });
+      setConfig(switched);
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+    } catch (error) {
+      alert(`${t('profile_switch_failed')}: ${error}`);
+    }
+  };
+
+  // 把当前指示另存为一份新方案
+  const handleNewProfile = async () => {
+    const name = prompt(t('profile_new_prompt'))?.trim();
+    if (!name) return;
+    try {
+      await invoke('save_profile', { name, config });
+      await handleSwitchProfile(name);
+    } catch (error) {
+      alert(`${t('save_failed')}: ${error}`);
+    }
+  };
+
+  // 给当前方案记点备注、贴几个标签
+  const handleEditProfileMeta = async () => {
+    const active = profiles.find((p) => p.active);
+    if (!active) return;
+    const notes = prompt(t('profile_notes_prompt'), active.notes);
+    if (notes === null) return;
+    const tags = prompt(t('profile_tags_prompt'), active.tags.join(', '));
+    if (tags === null) return;
+    try {
+      await invoke('set_profile_meta', { name: active.name, notes, tags: tags.split(/[,，]/) });
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+    } catch (error) {
+      alert(`${t('save_failed')}: ${error}`);
+    }
+  };
+
+  // 删除当前方案
+  const handleDeleteProfile = async () => {
+    const active = profiles.find((p) => p.active);
+    if (!active || !confirm(t('profile_delete_confirm', { name: active.name }))) return;
+    try {
+      await invoke('delete_profile', { name: active.name });
+      await loadConfig();
+    } catch (error) {
+      alert(`${t('profile_delete_failed')}: ${error}`);
+    }
+  };
+
+  // 把方案导出成文件，发给同伴导入（.json 为 JSON，其余为 TOML）
+  const handleExportProfiles = async () => {
+    let suggested = 'n2n-maid-profiles.toml';
+    try {
+      suggested = await join(await downloadDir(), suggested);
+    } catch (error) {
+      console.error('找不到下载目录：', error);
+    }
+    const path = prompt(t('profile_export_path_prompt'), suggested)?.trim();
+    if (!path) return;
+    const all = profiles.length > 1 && confirm(t('profile_export_all_confirm'));
+    try {
+      const count = await invoke<number>('export_config', { path, all });
+      alert(t('profile_export_done', { count, path }));
+    } catch (error) {
+      alert(`${t('profile_export_failed')}: ${error}`);
+    }
+  };
+
+  // 按另一份方案同时再开一条通道（或收起它）
+  const handleToggleSideConnection = async (name: string) => {
+    const side = sideConnections.find((c) => c.id === name);
+    const running = side && side.status !== 'disconnected' && side.status !== 'error';
+    try {
+      await invoke(running ? 'disconnect' : 'connect', { connectionId: name });
+      setSideConnections(await invoke<SideConnection[]>('list_connections'));
+    } catch (error) {
+      alert(`${t(running ? 'disconnect_failed' : 'connect_failed')}: ${t(String(error))}`);
+    }
+  };
+
+  // 全体同伴 ping 一下，延迟直接填进表里
+  const handlePingAllPeers = async () => {
+    setPeerBulkBusy(true);
+    try {
+      const report = await invoke<BulkReport<PingOutcome>>('ping_all_peers');
+      const latency = new Map(report.results.map((r) => [r.vpnIp, r.latencyMs]));
+      setPeers((prev) =>
+        prev.map((p) => (p.vpnIp && latency.has(p.vpnIp) ? { ...p, latencyMs: latency.get(p.vpnIp) } : p))
+      );
+      alert(t('peer_bulk_done', { succeeded: report.succeeded, failed: report.failed, ms: report.elapsedMs }));
+    } catch (error) {
+      alert(`${t('peer_ping_all_failed')}: ${t(String(error))}`);
+    } finally {
+      setPeerBulkBusy(false);
+    }
+  };
+
+  // 按虚拟地址反查全体同伴的 DNS 名字
+  const handleResolvePeerNames = async () => {
+    setPeerBulkBusy(true);
+    try {
+      const report = await invoke<BulkReport<NameOutcome>>('resolve_peer_names');
+      const names: Record<string, string> = {};
+      for (const r of report.results) {
+        if (r.hostname) names[r.vpnIp] = r.hostname;
+      }
+      setPeerHostnames(names);
+      alert(t('peer_bulk_done', { succeeded: report.succeeded, failed: report.failed, ms: report.elapsedMs }));
+    } catch (error) {
+      alert(`${t('peer_resolve_failed')}: ${t(String(error))}`);
+    } finally {
+      setPeerBulkBusy(false);
+    }
+  };
+
+  // 把点名册导出成文件（.csv 为表格，其余为 JSON）
+  const handleExportPeers = async () => {
+    let suggested = 'n2n-maid-peers.csv';
+    try {
+      suggested = await join(await downloadDir(), suggested);
+    } catch (error) {
+      console.error('找不到下载目录：', error);
+    }
+    const path = prompt(t('peer_export_path_prompt'), suggested)?.trim();
+    if (!path) return;
+    try {
+      const count = await invoke<number>('export_peers', { path });
+      alert(t('peer_export_done', { count, path }));
+    } catch (error) {
+      alert(`${t('peer_export_failed')}: ${error}`);
+    }
+  };
+
+  // 从同伴发来的方案文件导入
+  const handleImportProfiles = async () => {
+    const path = prompt(t('profile_import_path_prompt'))?.trim();
+    if (!path) return;
+    try {
+      const names = await invoke<string[]>('import_config', { path });
+      await loadConfig();
+      alert(t('profile_import_done', { names: names.join(', ') }));
+    } catch (error) {
+      alert(`${t('profile_import_failed')}: ${error}`);
+    }
+  };
+
+  // 把当前方案压成邀请链接
+  const handleShareProfile = async () => {
+    try {
+      const invite = await invoke<ShareInvite>('encode_share_link');
+      setShareLink(invite.link);
+      if (invite.conflict) {
+        alert(t('share_link_ip_conflict', { ip: invite.conflict.ip, name: invite.conflict.name }));
+      }
+    } catch (error) {
+      alert(`${t('share_link_failed')}: ${error}`);
+    }
+  };
+
+  // 换密语：和小伙伴约好时间，生成带着新密语的邀请链接
+  const handleRotateKey = async () => {
+    const active = profiles.find((p) => p.active);
+    if (!active) return;
+    const tomorrow = new Date(Date.now() + 24 * 3600 * 1000);
+    const pad = (n: number) => String(n).padStart(2, '0');
+    const suggested = `${tomorrow.getFullYear()}-${pad(tomorrow.getMonth() + 1)}-${pad(tomorrow.getDate())} ${pad(tomorrow.getHours())}:00`;
+    const input = prompt(t('key_rotation_time_prompt'), suggested)?.trim();
+    if (!input) return;
+    const switchAt = new Date(input.replace(' ', 'T'));
+    if (Number.isNaN(switchAt.getTime())) {
+      alert(t('key_rotation_time_invalid'));
+      return;
+    }
+    try {
+      const invite = await invoke<ShareInvite>('start_key_rotation', {
+        name: active.name,
+        switchAt: Math.floor(switchAt.getTime() / 1000),
+      });
+      setShareLink(invite.link);
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+      alert(t('key_rotation_desc', { time: switchAt.toLocaleString() }));
+    } catch (error) {
+      alert(`${t('key_rotation_failed')}: ${t(String(error))}`);
+    }
+  };
+
+  // 不换密语了
+  const handleCancelKeyRotation = async () => {
+    const active = profiles.find((p) => p.active);
+    if (!active || !confirm(t('key_rotation_cancel_confirm', { name: active.name }))) return;
+    try {
+      await invoke('cancel_key_rotation', { name: active.name });
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+    } catch (error) {
+      alert(`${t('key_rotation_failed')}: ${error}`);
+    }
+  };
+
+  const handleCopyShareLink = async () => {
+    if (!shareLink) return;
+    try {
+      await navigator.clipboard.writeText(shareLink);
+      alert(t('share_link_copied'));
+    } catch (error) {
+      console.error('复制邀请链接失败：', error);
+    }
+  };
+
+  // 同伴发来的邀请链接：给主人过目，确认后收下并换用
+  const joinWithLink = async (link: string) => {
+    try {
+      const shared = await invoke<PortableProfile>('decode_share_link', { link });
+      // 换密语的邀请：已经有这份方案的话只记下新密语，到点再换
+      const switchAt = shared.switch_at ?? null;
+      if (switchAt && switchAt * 1000 > Date.now() && profiles.some((p) => p.name === shared.name)) {
+        const time = new Date(switchAt * 1000).toLocaleString();
+        if (!confirm(t('key_rotation_import_confirm', { name: shared.name, time }))) return;
+        await invoke('import_profile', { name: shared.name, config: shared.config, switchAt });
+        setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+        return;
+      }
+      const summary = t('share_link_import_confirm', {
+        name: shared.name,
+        supernode: shared.config.supernode,
+        community: shared.config.community,
+      });
+      if (!confirm(summary)) return;
+      await invoke('import_profile', { name: shared.name, config: shared.config });
+      await handleSwitchProfile(shared.name);
+    } catch (error) {
+      alert(`${t('share_link_invalid')}: ${error}`);
+    }
+  };
+
+  // 粘贴同伴发来的邀请链接
+  const handleJoinWithLink = async () => {
+    const link = prompt(t('share_link_paste_prompt'))?.trim();
+    if (link) await joinWithLink(link);
+  };
+
+  // 解锁管理员下发的只读方案
+  const handleUnlockProfile = async () => {
+    const active = profiles.find((p) => p.active);
+    if (!active || !confirm(t('profile_unlock_confirm', { name: active.name }))) return;
+    try {
+      await invoke('unlock_profile', { name: active.name });
+      setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+    } catch (error) {
+      alert(`${t('profile_unlock_failed')}: ${error}`);
+    }
+  };
+
+  // 主人确认总部的新公钥可信
+  const handleTrustSupernodeKey = async () => {
+    if (!confirm(t('supernode_key_trust_confirm'))) return;
+    try {
+      await invoke('trust_supernode_key');
+      setKeyChange(null);
+    } catch (error) {
+      alert(`${t('supernode_key_trust_failed')}: ${error}`);
+    }
+  };
+
+  const handleTrustEdgeBinary = async () => {
+    if (!edgeAlert || !confirm(t('edge_binary_trust_confirm'))) return;
+    try {
+      await invoke('trust_edge_binary', { sha256: edgeAlert.actual });
+      setEdgeAlert(null);
+    } catch (error) {
+      alert(`${t('edge_binary_trust_failed')}: ${t(String(error))}`);
+    }
+  };
+
+  // 总部要维护：临时叫停自动重连（不改方案），小时数为 0 表示马上恢复
+  const handleSuspendReconnect = async (hours: number) => {
+    try {
+      const until = await invoke<number | null>('suspend_auto_reconnect', { durationSecs: Math.round(hours * 3600) });
+      setReconnectSuspendedUntil(until);
+    } catch (error) {
+      console.error('叫停自动重连失败：', error);
+    }
+  };
+
+  const handleAskSuspendReconnect = () => {
+    const hours = Number(prompt(t('reconnect_suspend_prompt'), '8'));
+    if (hours > 0) void handleSuspendReconnect(hours);
+  };
+
   const checkStatus = async () => {
     try {
       const response = await invoke<StatusResponse>('get_status');
       setStatus(response.status);
       setErrorMessage(response.error);
+      setExitReason(response.exitReason ?? null);
       setNetworkInfo(response.networkInfo || null);
+      setTransport(response.transport || null);
+      setTapAdapter(response.tapAdapter || null);
+      setNodeName(response.nodeName || null);
+      setRoutes(response.routes || []);
+      setLocalPort(response.localPort ?? null);
+      setRegisterInterval(response.registerIntervalSecs ?? null);
+      setReconnectAttempt(response.reconnectAttempt ?? 0);
+      setReconnectSuspendedUntil(response.reconnectSuspendedUntil ?? null);
+      setForwards(await invoke<ForwardStatus[]>('get_port_forwards'));
+      setSideConnections(await invoke<SideConnection[]>('list_connections'));
     } catch (error) {
       console.error('查看恩兔工作状态失败：', error);
     }
@@ -97,14 +619,44 @@ function App() {
     };
   }, [status]);
 
+  // 连上后每隔几秒问一次 edge 的实时统计（管理口）
+  useEffect(() => {
+    if (status !== 'connected') {
+      setEdgeStats(null);
+      return;
+    }
+
+    let disposed = false;
+
+    const refreshStats = async () => {
+      try {
+        const result = await invoke<EdgeStats | null>('get_edge_stats');
+        if (!disposed) setEdgeStats(result);
+      } catch (error) {
+        console.error('获取 edge 实时统计失败：', error);
+      }
+    };
+
+    void refreshStats();
+    const interval = setInterval(refreshStats, 5000);
+    return () => {
+      disposed = true;
+      clearInterval(interval);
+    };
+  }, [status]);
+
   const handleConnect = async () => {
     setLoading(true);
+    setWarnings([]);
+    setLastSession(null);
     try {
       await invoke('connect', { config });
       console.log('恩兔开始工作啦');
     } catch (error) {
       console.error('启动工作失败：', error);
-      alert(`${t('connect_failed')}: ${error}`);
+      // 出门前检查没通过时，后端会带回 error_ 编号
+      const reason = String(error);
+      alert(`${t('connect_failed')}: ${reason.startsWith('error_') ? t(reason) : reason}`);
     } finally {
       setLoading(false);
     }
@@ -124,6 +676,59 @@ function App() {
     }
   };
 
+  // edge 摔倒后正等着重连：这次先不连了
+  const handleCancelReconnect = async () => {
+    setLoading(true);
+    try {
+      await invoke<boolean>('cancel_reconnect');
+      await checkStatus();
+    } catch (error) {
+      console.error('取消重连失败：', error);
+      alert(`${t('cancel_reconnect_failed')}: ${error}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handlePause = async () => {
+    setLoading(true);
+    try {
+      await invoke('pause');
+    } catch (error) {
+      console.error('让恩兔歇一会儿失败：', error);
+      alert(`${t('pause_failed')}: ${error}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleResume = async () => {
+    setLoading(true);
+    try {
+      await invoke('resume');
+    } catch (error) {
+      console.error('让恩兔继续工作失败：', error);
+      alert(`${t('connect_failed')}: ${error}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // 换总部：不用断开再连，尽量保住现在的虚拟地址
+  const handleSwitchSupernode = async () => {
+    const address = prompt(t('switch_supernode_prompt'), config.supernode)?.trim();
+    if (!address || address === config.supernode) return;
+    setLoading(true);
+    try {
+      await invoke('switch_supernode', { address });
+      void loadConfig();
+    } catch (error) {
+      alert(`${t('switch_supernode_failed')}: ${t(String(error))}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
   const handleForceDisconnect = async () => {
     setLoading(true);
     try {
@@ -141,11 +746,24 @@ function App() {
       await invoke('save_config', { config: newConfig });
       setConfig(newConfig);
       setShowSettings(false);
-      alert(t('save_success'));
     } catch (error) {
       console.error('保存配置失败：', error);
       alert(`${t('save_failed')}: ${error}`);
+      return;
     }
+    // 通道开着的话问一声：按新的指示重新出门（后端一次做完停下、等待、出门）
+    if ((status === 'connected' || status === 'connecting') && confirm(t('save_reconnect_confirm'))) {
+      try {
+        setWarnings([]);
+        await invoke('reconnect', { config: newConfig });
+      } catch (error) {
+        console.error('重新出门失败：', error);
+        const reason = String(error);
+        alert(`${t('reconnect_failed')}: ${reason.startsWith('error_') ? t(reason) : reason}`);
+      }
+      return;
+    }
+    alert(t('save_success'));
   };
 
   const toggleLanguage = () => {
@@ -167,6 +785,8 @@ function App() {
       case 'connected':
         return 'status-connected';
       case 'connecting':
+      case 'degraded':
+      case 'reconnecting':
       case 'disconnecting':
         return 'status-connecting';
       case 'error':
@@ -185,6 +805,12 @@ function App() {
         return '(｡•̀ᴗ-)✧';
       case 'disconnecting':
         return '(｡•́︿•̀｡)';
+      case 'degraded':
+        return '(・_・;)';
+      case 'paused':
+        return '(｡･ω･)☕';
+      case 'reconnecting':
+        return '(๑•̀ㅂ•́)و';
       case 'error':
         return '(´；ω；`)';
       default:
@@ -229,6 +855,96 @@ function App() {
           <main className="flex-1 min-h-0 overflow-y-auto pb-14">
             {!showSettings ? (
               <div className="flex flex-col min-h-full gap-5">
+                {/* 指示簿解锁 */}
+                {configLocked && (
+                  <div className="p-4 maid-card">
+                    <p className="text-sm font-semibold text-gray-700">🔐 {t('config_locked_title')}</p>
+                    <p className="mt-1 text-xs text-gray-500">{t('config_locked_desc')}</p>
+                    <form
+                      className="flex gap-2 mt-3"
+                      onSubmit={(e) => {
+                        e.preventDefault();
+                        void handleUnlockConfig();
+                      }}
+                    >
+                      <input
+                        type="password"
+                        autoFocus
+                        value={unlockPassword}
+                        onChange={(e) => setUnlockPassword(e.target.value)}
+                        placeholder={t('config_password')}
+                        className="flex-1 maid-input"
+                      />
+                      <button type="submit" disabled={!unlockPassword} className="text-sm maid-button-secondary">
+                        {t('config_unlock')}
+                      </button>
+                    </form>
+                    {unlockError && (
+                      <p className="mt-2 text-xs text-red-600">
+                        {unlockError.startsWith('error_') ? t(unlockError) : unlockError}
+                      </p>
+                    )}
+                  </div>
+                )}
+
+                {/* 安全模式横幅（接连几次没好好收工） */}
+                {safeMode && (
+                  <div className="p-3 border rounded-lg border-amber-200 bg-amber-50">
+                    <p className="text-sm font-medium text-amber-800">🛟 {t('safe_mode_title')}</p>
+                    <p className="text-xs text-amber-800">{t('safe_mode_desc', { count: safeMode.abnormalExits })}</p>
+                    <button onClick={() => void handleLeaveSafeMode()} className="mt-2 text-xs maid-button-secondary">
+                      {t('safe_mode_leave')}
+                    </button>
+                  </div>
+                )}
+
+                {/* 没人照看的 edge */}
+                {orphans.length > 0 && (
+                  <div className="p-3 border rounded-lg border-amber-200 bg-amber-50">
+                    <p className="text-sm font-medium text-amber-800">🧟 {t('orphan_title')}</p>
+                    <p className="text-xs text-amber-800">{t('orphan_desc')}</p>
+                    {orphans.map((orphan) => (
+                      <div key={orphan.pid} className="flex items-center justify-between gap-2 mt-2">
+                        <span className="font-mono text-xs text-amber-800">
+                          {t('orphan_item', {
+                            pid: orphan.pid,
+                            community: orphan.community ?? t('orphan_unknown'),
+                            supernode: orphan.supernode ?? t('orphan_unknown'),
+                            port: orphan.managementPort,
+                          })}
+                        </span>
+                        <div className="flex gap-1 shrink-0">
+                          {orphan.matchesConfig && (
+                            <button onClick={() => void handleOrphan(orphan, 'adopt')} className="text-xs maid-button-secondary">
+                              {t('orphan_adopt')}
+                            </button>
+                          )}
+                          <button onClick={() => void handleOrphan(orphan, 'terminate')} className="text-xs maid-button-secondary">
+                            {t('orphan_terminate')}
+                          </button>
+                        </div>
+                      </div>
+                    ))}
+                  </div>
+                )}
+
+                {/* 开门体检横幅 */}
+                {!startupDismissed && startupNotes().length > 0 && (
+                  <div className="p-3 border rounded-lg border-amber-200 bg-amber-50">
+                    <div className="flex items-start justify-between gap-2">
+                      <div>
+                        {startupNotes().map((note) => (
+                          <p key={note} className="text-xs text-amber-800">📋 {note}</p>
+                        ))}
+                        {startupReport?.edgeVersion && (
+                          <p className="text-xs text-gray-500">{t('startup_edge_version', { version: startupReport.edgeVersion })}</p>
+                        )}
+                      </div>
+                      <button onClick={() => setStartupDismissed(true)} className="text-xs text-gray-500">✕</button>
+                    </div>
+                  </div>
+                )}
+
                 {/* 状态卡片 */}
                 <div className="p-5 maid-card">
                   <div className="flex items-center justify-between mb-4">
@@ -236,15 +952,128 @@ function App() {
                       {t('status')}
                     </h2>
                     <span className={`status-badge ${getStatusBadgeClass()}`}>
-                      {getStatusIcon()} {t(status)}
+                      {getStatusIcon()} {t(status, { attempt: reconnectAttempt })}
                     </span>
                   </div>
 
+                  {/* 工作方案切换（工作中不能换） */}
+                  <div className="flex items-center gap-2 mb-3 text-sm">
+                    <span className="text-gray-500">{t('profile')}</span>
+                    <select
+                      value={profiles.find((p) => p.active)?.name || ''}
+                      onChange={(e) => handleSwitchProfile(e.target.value)}
+                      disabled={status !== 'disconnected' && status !== 'error'}
+                      className="flex-1 py-1 maid-input"
+                    >
+                      {profiles.map((p) => (
+                        <option key={p.name} value={p.name}>{p.read_only ? `🔒 ${p.name}` : p.name}</option>
+                      ))}
+                    </select>
+                    <button onClick={handleNewProfile} className="text-xs maid-button-secondary" title={t('profile_new')}>＋</button>
+                    <button onClick={handleEditProfileMeta} className="text-xs maid-button-secondary" title={t('profile_notes')}>📝</button>
+                    <button
+                      onClick={handleDeleteProfile}
+                      disabled={profiles.length <= 1 || (status !== 'disconnected' && status !== 'error')}
+                      className="text-xs maid-button-secondary"
+                      title={t('profile_delete')}
+                    >
+                      －
+                    </button>
+                    <button onClick={handleExportProfiles} className="text-xs maid-button-secondary" title={t('profile_export')}>⇪</button>
+                    <button onClick={handleImportProfiles} className="text-xs maid-button-secondary" title={t('profile_import')}>⇩</button>
+                    <button onClick={handleShareProfile} className="text-xs maid-button-secondary" title={t('share_link')}>🔗</button>
+                    <button onClick={handleJoinWithLink} className="text-xs maid-button-secondary" title={t('share_link_join')}>📥</button>
+                    <button onClick={handleRotateKey} className="text-xs maid-button-secondary" title={t('key_rotation')}>🔑</button>
+                  </div>
+
+                  {/* 当前方案的标签和备注 */}
+                  {(() => {
+                    const active = profiles.find((p) => p.active);
+                    if (!active || (!active.notes && active.tags.length === 0 && !active.modified_at && !active.key_rotation_at)) return null;
+                    return (
+                      <div className="mb-3 text-xs text-gray-500">
+                        {active.tags.map((tag) => (
+                          <span key={tag} className="inline-block px-2 mr-1 rounded-full bg-maid-pink/30">{tag}</span>
+                        ))}
+                        {active.notes && <p className="mt-1 whitespace-pre-wrap">{active.notes}</p>}
+                        {active.modified_at && (
+                          <p className="mt-1 text-gray-400">
+                            {t('profile_modified_at', { time: new Date(active.modified_at * 1000).toLocaleString() })}
+                          </p>
+                        )}
+                        {active.key_rotation_at && (
+                          <p className="mt-1 text-amber-600">
+                            🔑 {t('key_rotation_pending', { time: new Date(active.key_rotation_at * 1000).toLocaleString() })}
+                            <button onClick={handleCancelKeyRotation} className="ml-2 text-xs maid-button-secondary">
+                              {t('key_rotation_cancel')}
+                            </button>
+                          </p>
+                        )}
+                      </div>
+                    );
+                  })()}
+
+                  {/* 副通道：别的方案同时连着（比如家里的小组和客户的小组） */}
+                  {profiles.length > 1 && (
+                    <div className="mb-3 text-xs">
+                      <p className="mb-1 text-gray-500">{t('side_connections')}</p>
+                      {profiles
+                        .filter((p) => !p.active)
+                        .map((p) => {
+                          const side = sideConnections.find((c) => c.id === p.name);
+                          const sideStatus = side?.status ?? 'disconnected';
+                          const running = sideStatus !== 'disconnected' && sideStatus !== 'error';
+                          return (
+                            <div key={p.name} className="flex items-center justify-between gap-2 mb-1">
+                              <span className="text-gray-700">{p.name}</span>
+                              <span className="flex-1 text-gray-500">
+                                {t(sideStatus, { attempt: side?.reconnectAttempt ?? 0 })}
+                                {side?.networkInfo && <span className="ml-1 font-mono">{side.networkInfo.ip}</span>}
+                                {sideStatus === 'error' && side?.error && <span className="ml-1 text-red-600">{t(side.error)}</span>}
+                              </span>
+                              <button onClick={() => void handleToggleSideConnection(p.name)} className="text-xs maid-button-secondary">
+                                {running ? t('disconnect') : t('connect')}
+                              </button>
+                            </div>
+                          );
+                        })}
+                    </div>
+                  )}
+
+                  {/* 邀请链接（交给同伴粘贴或生成二维码） */}
+                  {shareLink && (
+                    <div className="p-3 mb-3 border rounded-lg border-maid-pink bg-white/70">
+                      <p className="text-xs text-gray-500">{t('share_link_desc')}</p>
+                      <textarea
+                        readOnly
+                        value={shareLink}
+                        rows={3}
+                        onFocus={(e) => e.target.select()}
+                        className="mt-2 font-mono text-xs resize-none maid-input"
+                      />
+                      <div className="flex gap-2 mt-2">
+                        <button onClick={handleCopyShareLink} className="text-xs maid-button-secondary">
+                          {t('share_link_copy')}
+                        </button>
+                        <button onClick={() => setShareLink(null)} className="text-xs maid-button-secondary">
+                          {t('close')}
+                        </button>
+                      </div>
+                    </div>
+                  )}
+
                   {/* 当前配置信息 */}
                   <div className="space-y-2 text-sm">
                     <div className="flex justify-between">
                       <span className="text-gray-500">{t('supernode')}</span>
-                      <span className="font-mono text-gray-700">{config.supernode || '-'}</span>
+                      <span className="font-mono text-gray-700">
+                        {config.supernode || '-'}
+                        {status === 'connected' && (
+                          <button onClick={handleSwitchSupernode} disabled={loading} className="ml-2 text-xs maid-button-secondary">
+                            {t('switch_supernode')}
+                          </button>
+                        )}
+                      </span>
                       <span className="text-gray-500">{t('community')}</span>
                       <span className="font-mono text-gray-700">{config.community || '-'}</span>
                     </div>
@@ -256,11 +1085,16 @@ function App() {
                       <p className="text-sm text-red-700">
                         {getErrorMessage()}
                       </p>
+                      {exitReason && (
+                        <p className="mt-1 text-xs text-red-600">
+                          {t('exit_reason', { reason: t(exitReason) })} <span className="font-mono">({exitReason})</span>
+                        </p>
+                      )}
                     </div>
                   )}
 
-                  {/* 连接中提示（edge 可能在持续重试，不一定会退出） */}
-                  {status === 'connecting' && errorMessage && (
+                  {/* 连接中提示（edge 可能在持续重试，不一定会退出；等着重连时是上次摔倒的原因；降级时是总部那边的情况） */}
+                  {(status === 'connecting' || status === 'reconnecting' || status === 'degraded') && errorMessage && (
                     <div className="p-3 mt-4 border rounded-lg border-amber-200 bg-amber-50">
                       <p className="text-sm text-amber-800">
                         {getErrorMessage()}
@@ -268,6 +1102,106 @@ function App() {
                     </div>
                   )}
 
+                  {/* edge 的非致命提醒 */}
+                  {(status === 'connected' || status === 'connecting') && warnings.length > 0 && (
+                    <div className="p-3 mt-4 border rounded-lg border-amber-200 bg-amber-50">
+                      {warnings.map((w, idx) => (
+                        <p key={`${w.at}-${idx}`} className="text-xs text-amber-800">
+                          ⚠ {w.code ? t(w.code) : w.message}
+                        </p>
+                      ))}
+                    </div>
+                  )}
+
+                  {/* 恩兔的聊天气泡 */}
+                  {maidMessages.length > 0 && (
+                    <div className="mt-4 space-y-1">
+                      {maidMessages.map((m, idx) => (
+                        <div key={`${m.at}-${m.id}-${idx}`} className={`px-3 py-1.5 text-xs border rounded-2xl rounded-bl-sm w-fit max-w-full ${bubbleStyles[m.severity]}`}>
+                          {t(m.id, translateReason(m.params))}
+                        </div>
+                      ))}
+                    </div>
+                  )}
+
+                  {/* 总部公钥变了 */}
+                  {keyChange && (
+                    <div className="p-3 mt-4 border border-red-200 rounded-lg bg-red-50">
+                      <p className="text-sm font-semibold text-red-700">{t('supernode_key_changed_title')}</p>
+                      <p className="mt-1 text-xs text-red-700">{t('error_supernode_key_changed')}</p>
+                      <p className="mt-1 font-mono text-xs text-red-600 whitespace-pre-line break-all">
+                        {t('supernode_key_changed_detail', { pinned: keyChange.pinned, presented: keyChange.presented })}
+                      </p>
+                      <button onClick={handleTrustSupernodeKey} className="mt-2 text-xs maid-button-secondary">
+                        {t('supernode_key_trust')}
+                      </button>
+                    </div>
+                  )}
+
+                  {/* edge 的指纹对不上（不在白名单里的要去设置里改白名单，这里不给信任按钮） */}
+                  {edgeAlert && (
+                    <div className="p-3 mt-4 border border-red-200 rounded-lg bg-red-50">
+                      <p className="text-sm font-semibold text-red-700">{t('edge_binary_rejected_title')}</p>
+                      <p className="mt-1 text-xs text-red-700">{t(edgeAlert.code)}</p>
+                      <p className="mt-1 font-mono text-xs text-red-600 whitespace-pre-line break-all">
+                        {edgeAlert.previous && `${t('edge_binary_previous', { previous: edgeAlert.previous })}\n`}
+                        {t('edge_binary_rejected_detail', { path: edgeAlert.path, actual: edgeAlert.actual })}
+                      </p>
+                      {edgeAlert.previous && (
+                        <button onClick={handleTrustEdgeBinary} className="mt-2 text-xs maid-button-secondary">
+                          {t('edge_binary_trust')}
+                        </button>
+                      )}
+                    </div>
+                  )}
+
+                  {/* 权限建议 */}
+                  {status !== 'connected' && elevationAdvice.length > 0 && (
+                    <div className="p-3 mt-4 border rounded-lg border-amber-200 bg-amber-50">
+                      {elevationAdvice.map((a) => (
+                        <p key={a} className="text-xs text-amber-800">🔑 {t(a)}</p>
+                      ))}
+                    </div>
+                  )}
+
+                  {/* 自动重连临时叫停 */}
+                  {config.reconnect_policy && config.reconnect_policy !== 'never' && (
+                    <p className="mt-3 text-xs text-center text-gray-500">
+                      {reconnectSuspendedUntil ? (
+                        <>
+                          {t('reconnect_suspended_until', { time: new Date(reconnectSuspendedUntil * 1000).toLocaleString() })}{' '}
+                          <button onClick={() => void handleSuspendReconnect(0)} className="underline">
+                            {t('reconnect_suspend_cancel')}
+                          </button>
+                        </>
+                      ) : (
+                        <button onClick={handleAskSuspendReconnect} className="underline">
+                          {t('reconnect_suspend')}
+                        </button>
+                      )}
+                    </p>
+                  )}
+
+                  {/* 上一次工作的总结 */}
+                  {(status === 'disconnected' || status === 'error') && lastSession && (
+                    <p className="mt-3 text-xs text-center text-gray-500">
+                      {t('session_summary', {
+                        duration: formatDuration(lastSession.durationSecs),
+                        tx: lastSession.txPackets,
+                        rx: lastSession.rxPackets,
+                        reconnects: lastSession.reconnectCount,
+                      })}
+                      {' · '}
+                      {t(`session_reason_${lastSession.reason}`)}
+                      {lastSession.exitReason && ` · ${t('exit_reason', { reason: t(lastSession.exitReason) })}`}
+                      {lastSession.outputFile && (
+                        <span className="block mt-1 break-all select-all text-gray-400">
+                          {t('session_output_file', { path: lastSession.outputFile })}
+                        </span>
+                      )}
+                    </p>
+                  )}
+
                   {/* 断开等待提示 */}
                   {status === 'disconnecting' && (
                     <p className="mt-3 text-sm text-center text-gray-500">
@@ -305,11 +1239,138 @@ function App() {
                         </p>
                       )}
 
+                      {/* 虚拟网卡在系统里的名字和接口编号 */}
+                      {tapAdapter && (
+                        <p className="mt-2 font-mono text-xs">
+                          <span className="text-gray-500">{t('tap_adapter')}:</span>
+                          <span className="ml-1 text-gray-700">
+                            {t('tap_adapter_detail', { name: tapAdapter.friendlyName, index: tapAdapter.ifIndex })}
+                          </span>
+                        </p>
+                      )}
+
+                      {/* 实际交给 edge 的工号 */}
+                      {nodeName && (
+                        <p className="mt-2 font-mono text-xs">
+                          <span className="text-gray-500">{t('node_name')}:</span>
+                          <span className="ml-1 text-gray-700">{nodeName}</span>
+                        </p>
+                      )}
+
+                      {/* edge 实际在用的本机 UDP 端口 */}
+                      {localPort != null && (
+                        <p className="mt-2 font-mono text-xs">
+                          <span className="text-gray-500">{t('local_port')}:</span>
+                          <span className="ml-1 text-gray-700">UDP {localPort}</span>
+                        </p>
+                      )}
+
+                      {/* edge 实际在用的报到间隔 */}
+                      {registerInterval != null && (
+                        <p className="mt-2 font-mono text-xs">
+                          <span className="text-gray-500">{t('register_interval_secs')}:</span>
+                          <span className="ml-1 text-gray-700">{t('register_interval_value', { secs: registerInterval })}</span>
+                        </p>
+                      )}
+
+                      {/* edge 管理口报来的实时统计 */}
+                      {edgeStats && (
+                        <div className="mt-2 font-mono text-xs">
+                          <p>
+                            <span className="text-gray-500">{t('edge_stats_registration')}:</span>
+                            <span className={`ml-1 ${edgeStats.registered ? 'text-green-700' : 'text-amber-600'}`}>
+                              {edgeStats.registered ? t('edge_stats_registered') : t('edge_stats_not_registered')}
+                            </span>
+                            {edgeStats.supernode && <span className="ml-1 text-gray-700">({edgeStats.supernode})</span>}
+                          </p>
+                          <p>
+                            <span className="text-gray-500">{t('edge_stats_uptime')}:</span>
+                            <span className="ml-1 text-gray-700">{formatDuration(edgeStats.uptimeSecs)}</span>
+                          </p>
+                          <p>
+                            <span className="text-gray-500">{t('edge_stats_packets')}:</span>
+                            <span className="ml-1 text-gray-700">
+                              ↑ {edgeStats.txPackets} / ↓ {edgeStats.rxPackets}
+                            </span>
+                          </p>
+                          {edgeStats.txBytes != null && edgeStats.rxBytes != null && (
+                            <p>
+                              <span className="text-gray-500">{t('edge_stats_bytes')}:</span>
+                              <span className="ml-1 text-gray-700">
+                                ↑ {formatBytes(edgeStats.txBytes)} / ↓ {formatBytes(edgeStats.rxBytes)}
+                              </span>
+                            </p>
+                          )}
+                        </div>
+                      )}
+
+                      {/* 本次通道加上的路由 */}
+                      {routes.length > 0 && (
+                        <div className="mt-2 font-mono text-xs">
+                          <span className="text-gray-500">{t('routes_applied')}:</span>
+                          {routes.map((route, index) => (
+                            <p key={index} className="ml-1 text-gray-700">
+                              {route.network}/{route.netmask} → {route.gateway}
+                            </p>
+                          ))}
+                        </div>
+                      )}
+
+                      {/* 实际生效的加密/压缩方式 */}
+                      {transport && (
+                        <div className="grid grid-cols-3 gap-2 mt-2 font-mono text-xs">
+                          <div>
+                            <span className="text-gray-500">{t('transport_cipher')}:</span>
+                            <br />
+                            <span className="ml-1 text-gray-700">{transport.cipher ?? '-'}</span>
+                          </div>
+                          <div>
+                            <span className="text-gray-500">{t('transport_compression')}:</span>
+                            <br />
+                            <span className="ml-1 text-gray-700">{transport.compression ?? '-'}</span>
+                          </div>
+                          <div>
+                            <span className="text-gray-500">{t('transport_header_encryption')}:</span>
+                            <br />
+                            <span className="ml-1 text-gray-700">
+                              {transport.headerEncryption == null ? '-' : t(transport.headerEncryption ? 'transport_on' : 'transport_off')}
+                            </span>
+                          </div>
+                        </div>
+                      )}
+
+                      {/* 传话筒 */}
+                      {forwards.length > 0 && (
+                        <div className="mt-2 space-y-1 font-mono text-xs">
+                          {forwards.map((f, idx) => (
+                            <p key={`${f.rule.listen}-${idx}`} className={f.error ? 'text-red-600' : 'text-gray-700'}>
+                              {f.listening ? '⇄' : '✕'} {f.rule.name || t('port_forwards')}: {f.rule.listen} → {f.rule.target}
+                              <span className="ml-1 text-gray-500">
+                                {f.error ?? t('port_forward_stats', { active: f.active, total: f.total })}
+                              </span>
+                            </p>
+                          ))}
+                        </div>
+                      )}
+
                       {/* 同伴节点 */}
                       <div className="pt-3 mt-3 border-t border-white/50">
-                        <p className="mb-2 text-sm font-medium text-gray-700">
-                          {t('peer_list')}
-                        </p>
+                        <div className="flex items-center justify-between mb-2">
+                          <p className="text-sm font-medium text-gray-700">{t('peer_list')}</p>
+                          {peers.length > 0 && (
+                            <div className="flex gap-2">
+                              <button onClick={handlePingAllPeers} disabled={peerBulkBusy} className="text-xs maid-button-secondary">
+                                {t('peer_ping_all')}
+                              </button>
+                              <button onClick={handleResolvePeerNames} disabled={peerBulkBusy} className="text-xs maid-button-secondary">
+                                {t('peer_resolve_names')}
+                              </button>
+                              <button onClick={handleExportPeers} className="text-xs maid-button-secondary">
+                                {t('peer_export')}
+                              </button>
+                            </div>
+                          )}
+                        </div>
                         {peers.length === 0 ? (
                           <p className="text-xs text-gray-500">
                             {t('peer_list_empty')}
@@ -323,6 +1384,7 @@ function App() {
                                   <th className="pb-1 pr-2 font-medium text-left">{t('peer_vpn_ip')}</th>
                                   <th className="pb-1 pr-2 font-medium text-left">{t('peer_mode')}</th>
                                   <th className="pb-1 pr-2 font-medium text-left">{t('peer_public_addr')}</th>
+                                  <th className="pb-1 pr-2 font-medium text-left">{t('peer_mac')}</th>
                                   <th className="pb-1 pr-2 font-medium text-left">{t('peer_latency')}</th>
                                   <th className="pb-1 pr-2 font-medium text-left">{t('peer_last_seen')}</th>
                                 </tr>
@@ -339,10 +1401,18 @@ function App() {
                                     lastSeenAgo != null ? `${lastSeenAgo}s` : '-';
                                   return (
                                     <tr key={`${p.vpnIp || p.vpnAddr || idx}-${idx}`} className="border-t border-white/40">
-                                      <td className="py-1 pr-2 whitespace-nowrap">{p.name || '-'}</td>
+                                      <td className="py-1 pr-2 whitespace-nowrap">
+                                        {p.name || '-'}
+                                        {p.vpnIp && peerHostnames[p.vpnIp] && (
+                                          <span className="text-gray-500"> ({peerHostnames[p.vpnIp]})</span>
+                                        )}
+                                      </td>
                                       <td className="py-1 pr-2 whitespace-nowrap">{p.vpnIp || p.vpnAddr || '-'}</td>
-                                      <td className="py-1 pr-2 whitespace-nowrap">{p.mode || '-'}</td>
+                                      <td className="py-1 pr-2 whitespace-nowrap" title={p.mode || undefined}>
+                                        {p.link === 'unknown' ? p.mode || '-' : t(`peer_link_${p.link}`)}
+                                      </td>
                                       <td className="py-1 pr-2 whitespace-nowrap">{p.publicAddr || '-'}</td>
+                                      <td className="py-1 pr-2 whitespace-nowrap">{p.mac || '-'}</td>
                                       <td className="py-1 pr-2 whitespace-nowrap">{latencyText}</td>
                                       <td className="py-1 pr-2 whitespace-nowrap">{lastSeenText}</td>
                                     </tr>
@@ -366,6 +1436,31 @@ function App() {
                     >
                       {loading ? t('connecting') : t('connect')}
                     </button>
+                  ) : status === 'paused' ? (
+                    <>
+                      <button
+                        onClick={handleResume}
+                        disabled={loading}
+                        className="flex-1 py-4 text-lg maid-button-primary"
+                      >
+                        {t('resume')}
+                      </button>
+                      <button
+                        onClick={handleDisconnect}
+                        disabled={loading}
+                        className="flex-1 py-4 text-lg maid-button-disconnect"
+                      >
+                        {t('disconnect')}
+                      </button>
+                    </>
+                  ) : status === 'reconnecting' ? (
+                    <button
+                      onClick={handleCancelReconnect}
+                      disabled={loading}
+                      className="flex-1 py-4 text-lg maid-button-disconnect"
+                    >
+                      {t('cancel_reconnect')}
+                    </button>
                   ) : status === 'disconnecting' ? (
                     <button
                       onClick={handleForceDisconnect}
@@ -375,13 +1470,24 @@ function App() {
                       {t('force_disconnect')}
                     </button>
                   ) : (
-                    <button
-                      onClick={handleDisconnect}
-                      disabled={loading}
-                      className="flex-1 py-4 text-lg maid-button-disconnect"
-                    >
-                      {t('disconnect')}
-                    </button>
+                    <>
+                      {status === 'connected' && (
+                        <button
+                          onClick={handlePause}
+                          disabled={loading}
+                          className="flex-1 py-4 text-lg maid-button-secondary"
+                        >
+                          {t('pause')}
+                        </button>
+                      )}
+                      <button
+                        onClick={handleDisconnect}
+                        disabled={loading}
+                        className="flex-1 py-4 text-lg maid-button-disconnect"
+                      >
+                        {t('disconnect')}
+                      </button>
+                    </>
                   )}
                 </div>
               </div>
@@ -390,6 +1496,16 @@ function App() {
                 config={config}
                 onSave={handleSaveConfig}
                 onCancel={() => setShowSettings(false)}
+                readOnly={profiles.find((p) => p.active)?.read_only ?? false}
+                onUnlock={handleUnlockProfile}
+                onLocked={() => {
+                  void loadConfig();
+                  setShowSettings(false);
+                }}
+                onRestored={() => {
+                  void loadConfig();
+                  setShowSettings(false);
+                }}
               />
             )}
           </main>
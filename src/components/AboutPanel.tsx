@@ -0,0 +1,83 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { AppInfo, EdgeInfo } from '../types';
+
+/**
+ * 关于恩兔 🪪
+ * 版本、构建指纹、edge 版本和运行环境都由后端填好，报告问题时整张复制给维护者
+ */
+const AboutPanel: React.FC = () => {
+  const { t } = useTranslation();
+  const [info, setInfo] = useState<AppInfo | null>(null);
+  const [edgeInfo, setEdgeInfo] = useState<EdgeInfo | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  // 要问 edge 版本，可能要等一两秒：点开时才去填
+  const handleShow = async () => {
+    setLoading(true);
+    try {
+      setInfo(await invoke<AppInfo>('get_app_info'));
+      setEdgeInfo(await invoke<EdgeInfo>('get_edge_info').catch(() => null));
+    } catch (error) {
+      alert(`${t('about_failed')}: ${t(String(error))}`);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleCopy = async () => {
+    if (!info) return;
+    await navigator.clipboard.writeText(JSON.stringify({ ...info, edge: edgeInfo?.caps ?? null }, null, 2));
+    alert(t('about_copied'));
+  };
+
+  const unknown = t('about_unknown');
+
+  return (
+    <div>
+      <div className="flex items-center justify-between mb-1.5">
+        <span className="text-sm font-medium text-gray-600">{t('about')}</span>
+        {info ? (
+          <button type="button" onClick={() => void handleCopy()} className="text-xs maid-button-secondary">
+            {t('about_copy')}
+          </button>
+        ) : (
+          <button type="button" onClick={() => void handleShow()} disabled={loading} className="text-xs maid-button-secondary">
+            {loading ? t('about_loading') : t('about_show')}
+          </button>
+        )}
+      </div>
+      {info && (
+        <div className="font-mono text-xs text-gray-600 space-y-0.5">
+          <p>
+            {t('about_version')}: {info.version} ({info.git_hash}, {info.build_profile})
+          </p>
+          <p>
+            {t('about_runtime')}: Tauri {info.tauri_version} · {info.rust_version}
+          </p>
+          <p>
+            {t('about_edge')}: {info.edge_version ?? unknown} · {info.edge_path}
+          </p>
+          {edgeInfo?.caps && (
+            <p>
+              {t('about_edge_features')}: v{edgeInfo.caps.generation ?? '?'} · {edgeInfo.caps.ciphers.join('/') || unknown}
+              {edgeInfo.caps.headerEncryption && ' · -H'}
+              {edgeInfo.caps.compressions.length > 0 && ` · ${edgeInfo.caps.compressions.join('/')}`}
+              {!edgeInfo.caps.foreground && ` · ${t('about_edge_no_foreground')}`}
+            </p>
+          )}
+          <p>
+            {t('about_system')}: {info.os_version ?? info.os} · {info.arch}
+          </p>
+          <p>
+            {t('about_webview')}: {info.webview_version ?? unknown}
+          </p>
+        </div>
+      )}
+      <p className="mt-1 text-xs text-gray-400">{t('about_desc')}</p>
+    </div>
+  );
+};
+
+export default AboutPanel;
@@ -0,0 +1,132 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { ConfigEncryption } from '../types';
+
+interface ConfigVaultPanelProps {
+  /// 指示簿刚被锁上（主界面要换成解锁卡片）
+  onLocked?: () => void;
+}
+
+/**
+ * 加密指示簿面板 🔐
+ * 设置/更换/取消指示簿密码，或者马上锁上（锁上后要重新输入密码才能读写指示簿）
+ */
+const ConfigVaultPanel: React.FC<ConfigVaultPanelProps> = ({ onLocked }) => {
+  const { t } = useTranslation();
+  const [encryption, setEncryption] = useState<ConfigEncryption>({ encrypted: false, locked: false });
+  const [password, setPassword] = useState('');
+  const [confirmPassword, setConfirmPassword] = useState('');
+
+  const load = async () => {
+    try {
+      setEncryption(await invoke<ConfigEncryption>('get_config_encryption'));
+    } catch (error) {
+      console.error('读取指示簿加密状态失败：', error);
+    }
+  };
+
+  useEffect(() => {
+    void load();
+  }, []);
+
+  const describe = (error: unknown) => {
+    const message = String(error);
+    return message.startsWith('error_') ? t(message) : message;
+  };
+
+  const reset = async () => {
+    setPassword('');
+    setConfirmPassword('');
+    await load();
+  };
+
+  const handleSetPassword = async () => {
+    if (password !== confirmPassword) {
+      alert(t('config_password_mismatch'));
+      return;
+    }
+    try {
+      await invoke('set_config_password', { password });
+      await reset();
+      alert(t('config_encrypted_done'));
+    } catch (error) {
+      alert(`${t('config_encryption_failed')}: ${describe(error)}`);
+    }
+  };
+
+  const handleRemovePassword = async () => {
+    if (!confirm(t('config_decrypt_confirm'))) return;
+    try {
+      await invoke('remove_config_password', { password });
+      await reset();
+    } catch (error) {
+      alert(`${t('config_encryption_failed')}: ${describe(error)}`);
+    }
+  };
+
+  const handleLock = async () => {
+    try {
+      await invoke('lock_config');
+      onLocked?.();
+    } catch (error) {
+      alert(`${t('config_encryption_failed')}: ${describe(error)}`);
+    }
+  };
+
+  return (
+    <div>
+      <label className="block text-sm font-medium text-gray-600 mb-1.5">
+        {t('config_encryption')}
+        <span className="ml-2 text-xs text-gray-400">
+          {encryption.encrypted ? t('config_encryption_on') : t('config_encryption_off')}
+        </span>
+      </label>
+      <div className="flex gap-2">
+        <input
+          type="password"
+          value={password}
+          onChange={e => setPassword(e.target.value)}
+          placeholder={encryption.encrypted ? t('config_password_new') : t('config_password')}
+          className="flex-1 maid-input"
+        />
+        <input
+          type="password"
+          value={confirmPassword}
+          onChange={e => setConfirmPassword(e.target.value)}
+          placeholder={t('config_password_confirm')}
+          className="flex-1 maid-input"
+        />
+      </div>
+      <div className="flex gap-2 mt-2">
+        <button
+          type="button"
+          onClick={() => void handleSetPassword()}
+          disabled={!password}
+          className="text-xs maid-button-secondary"
+        >
+          {encryption.encrypted ? t('config_password_change') : t('config_encrypt')}
+        </button>
+        {encryption.encrypted && (
+          <>
+            <button
+              type="button"
+              onClick={() => void handleRemovePassword()}
+              disabled={!password}
+              className="text-xs maid-button-secondary"
+              title={t('config_decrypt_hint')}
+            >
+              {t('config_decrypt')}
+            </button>
+            <button type="button" onClick={() => void handleLock()} className="text-xs maid-button-secondary">
+              {t('config_lock')}
+            </button>
+          </>
+        )}
+      </div>
+      <p className="mt-1 text-xs text-gray-400">{t('config_encryption_desc')}</p>
+    </div>
+  );
+};
+
+export default ConfigVaultPanel;
@@ -0,0 +1,171 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { ApiScope, ApiSettings, ApiTokenInfo, IssuedApiToken } from '../types';
+
+/**
+ * 遥控口面板 🔑
+ * 开关本机 HTTP 遥控口、发放/轮换/吊销令牌（设置改动后重启恩兔生效，令牌改动立即生效）
+ */
+const ControlApiPanel: React.FC = () => {
+  const { t } = useTranslation();
+  const [settings, setSettings] = useState<ApiSettings>({ enabled: false, port: 5645, socketPath: null });
+  const [tokens, setTokens] = useState<ApiTokenInfo[]>([]);
+  const [newName, setNewName] = useState('');
+  const [newScope, setNewScope] = useState<ApiScope>('status');
+  // 刚发出的令牌只显示这一次
+  const [issued, setIssued] = useState<IssuedApiToken | null>(null);
+
+  const load = async () => {
+    try {
+      const result = await invoke<{ settings: ApiSettings; tokens: ApiTokenInfo[] }>('get_control_api');
+      setSettings(result.settings);
+      setTokens(result.tokens);
+    } catch (error) {
+      console.error('读取遥控口设置失败：', error);
+    }
+  };
+
+  useEffect(() => {
+    void load();
+  }, []);
+
+  const saveSettings = async (next: ApiSettings) => {
+    setSettings(next);
+    try {
+      await invoke('save_control_api_settings', { settings: next });
+    } catch (error) {
+      alert(`${t('save_failed')}: ${error}`);
+    }
+  };
+
+  const handleCreate = async () => {
+    try {
+      setIssued(await invoke<IssuedApiToken>('create_api_token', { name: newName, scope: newScope }));
+      setNewName('');
+      await load();
+    } catch (error) {
+      alert(`${t('control_api_failed')}: ${error}`);
+    }
+  };
+
+  const handleRotate = async (token: ApiTokenInfo) => {
+    if (!confirm(t('control_api_rotate_confirm', { name: token.name }))) return;
+    try {
+      setIssued(await invoke<IssuedApiToken>('rotate_api_token', { id: token.id }));
+      await load();
+    } catch (error) {
+      alert(`${t('control_api_failed')}: ${error}`);
+    }
+  };
+
+  const handleRevoke = async (token: ApiTokenInfo) => {
+    if (!confirm(t('control_api_revoke_confirm', { name: token.name }))) return;
+    try {
+      await invoke('revoke_api_token', { id: token.id });
+      if (issued?.info.id === token.id) setIssued(null);
+      await load();
+    } catch (error) {
+      alert(`${t('control_api_failed')}: ${error}`);
+    }
+  };
+
+  return (
+    <div>
+      <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+        <input
+          type="checkbox"
+          checked={settings.enabled}
+          onChange={e => void saveSettings({ ...settings, enabled: e.target.checked })}
+        />
+        {t('control_api')}
+      </label>
+
+      {settings.enabled && (
+        <div className="mt-2 space-y-2">
+          <div className="flex gap-2">
+            <div className="w-24">
+              <label htmlFor="control_api_port" className="block text-xs text-gray-500 mb-1">
+                {t('control_api_port')}
+              </label>
+              <input
+                id="control_api_port"
+                type="number"
+                min="1"
+                max="65535"
+                value={settings.port}
+                onChange={e => setSettings({ ...settings, port: parseInt(e.target.value) || 0 })}
+                onBlur={() => void saveSettings(settings)}
+                className="maid-input"
+              />
+            </div>
+            <div className="flex-1">
+              <label htmlFor="control_api_socket" className="block text-xs text-gray-500 mb-1">
+                {t('control_api_socket')}
+              </label>
+              <input
+                id="control_api_socket"
+                type="text"
+                value={settings.socketPath || ''}
+                onChange={e => setSettings({ ...settings, socketPath: e.target.value || null })}
+                onBlur={() => void saveSettings(settings)}
+                placeholder="/run/user/1000/n2n-maid.sock"
+                className="font-mono text-sm maid-input"
+              />
+            </div>
+          </div>
+
+          {tokens.length === 0 && <p className="text-xs text-gray-400">{t('control_api_no_tokens')}</p>}
+          {tokens.map((token) => (
+            <div key={token.id} className="flex items-center gap-2 text-sm text-gray-600">
+              <span className="flex-1 truncate">
+                {token.name}
+                <span className="ml-2 text-xs text-gray-400">
+                  {t(`control_api_scope_${token.scope}`)} · {new Date(token.createdAt * 1000).toLocaleDateString()}
+                </span>
+              </span>
+              <button type="button" onClick={() => void handleRotate(token)} className="text-xs maid-button-secondary">
+                {t('control_api_rotate')}
+              </button>
+              <button type="button" onClick={() => void handleRevoke(token)} className="text-xs maid-button-secondary">
+                {t('control_api_revoke')}
+              </button>
+            </div>
+          ))}
+
+          <div className="flex gap-2">
+            <input
+              type="text"
+              value={newName}
+              onChange={e => setNewName(e.target.value)}
+              placeholder={t('control_api_token_name')}
+              className="flex-1 maid-input"
+            />
+            <select value={newScope} onChange={e => setNewScope(e.target.value as ApiScope)} className="maid-input">
+              <option value="status">{t('control_api_scope_status')}</option>
+              <option value="control">{t('control_api_scope_control')}</option>
+            </select>
+            <button
+              type="button"
+              onClick={() => void handleCreate()}
+              disabled={!newName.trim()}
+              className="text-xs maid-button-secondary"
+            >
+              {t('control_api_create')}
+            </button>
+          </div>
+
+          {issued && (
+            <div className="p-2 text-xs text-gray-600 border rounded-lg border-amber-200 bg-amber-50">
+              <p className="mb-1">{t('control_api_issued', { name: issued.info.name })}</p>
+              <code className="block font-mono break-all select-all">{issued.secret}</code>
+            </div>
+          )}
+        </div>
+      )}
+      <p className="mt-1 text-xs text-gray-400">{t('control_api_desc')}</p>
+    </div>
+  );
+};
+
+export default ControlApiPanel;
@@ -1,6 +1,7 @@
 import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useTranslation } from 'react-i18next';
+import { LogStats, SideConnection } from '../types';
 
 /**
  * 工作日志查看器组件 - 稿纸风格 📝
@@ -11,6 +12,10 @@ const LogViewer: React.FC = () => {
   const { t } = useTranslation();
   const [logs, setLogs] = useState<string[]>([]);
   const [isCollapsed, setIsCollapsed] = useState(true);
+  const [logStats, setLogStats] = useState<LogStats | null>(null);
+  // 副通道各有各的汇报本（key 是方案名）；source 为空时看主通道
+  const [sideLogs, setSideLogs] = useState<Record<string, string[]>>({});
+  const [source, setSource] = useState('');
   const logEndRef = useRef<HTMLDivElement>(null);
 
   useEffect(() => {
@@ -21,6 +26,11 @@ const LogViewer: React.FC = () => {
         if (newLogs.length > 0) {
           setLogs(prev => [...prev, ...newLogs]);
         }
+        const sides = await invoke<SideConnection[]>('list_connections');
+        for (const side of sides) {
+          const lines = await invoke<string[]>('get_logs', { connectionId: side.id });
+          setSideLogs(prev => ({ ...prev, [side.id]: [...(prev[side.id] ?? []), ...lines] }));
+        }
       } catch (error) {
         console.error('接收工作汇报失败：', error);
       }
@@ -29,13 +39,43 @@ const LogViewer: React.FC = () => {
     return () => clearInterval(interval);
   }, []);
 
+  // 顺便看看汇报本有没有写满撕页（诊断用，不用太勤）
+  useEffect(() => {
+    const interval = setInterval(async () => {
+      try {
+        setLogStats(await invoke<LogStats | null>('get_log_stats'));
+      } catch (error) {
+        console.error('查看汇报本使用情况失败：', error);
+      }
+    }, 5000);
+
+    return () => clearInterval(interval);
+  }, []);
+
+  const shown = source ? sideLogs[source] ?? [] : logs;
+
   // 自动滚动到最新的汇报
   useEffect(() => {
     logEndRef.current?.scrollIntoView({ behavior: 'smooth' });
-  }, [logs]);
+  }, [shown.length]);
 
   const handleClearLogs = () => {
-    setLogs([]);
+    if (source) {
+      setSideLogs(prev => ({ ...prev, [source]: [] }));
+    } else {
+      setLogs([]);
+    }
+  };
+
+  // 导出工作汇报（默认先打码，方便主人放心分享给别人排错）
+  const handleExportLogs = async () => {
+    const anonymize = confirm(t('export_logs_anonymize_confirm'));
+    try {
+      const path = await invoke<string>('export_logs', { lines: shown, anonymize });
+      alert(t('export_logs_done', { path }));
+    } catch (error) {
+      alert(`${t('export_logs_failed')}: ${error}`);
+    }
   };
 
   /**
@@ -68,10 +108,39 @@ const LogViewer: React.FC = () => {
             {isCollapsed ? '▶' : '▼'} {t('logs')}
           </span>
           <span className="text-xs px-2 py-0.5 bg-white/60 rounded-full text-gray-500">
-            {logs.length} {t('log_lines')}
+            {shown.length} {t('log_lines')}
           </span>
+          {Object.keys(sideLogs).length > 0 && (
+            <select
+              value={source}
+              onChange={(e) => setSource(e.target.value)}
+              onClick={(e) => e.stopPropagation()}
+              className="text-xs px-2 py-0.5 bg-white/60 rounded-full text-gray-500"
+            >
+              <option value="">{t('log_source_main')}</option>
+              {Object.keys(sideLogs).map((id) => (
+                <option key={id} value={id}>{id}</option>
+              ))}
+            </select>
+          )}
+          {logStats && logStats.dropped > 0 && (
+            <span
+              className="text-xs px-2 py-0.5 bg-amber-100/80 rounded-full text-amber-700"
+              title={t('log_dropped_desc', { capacity: logStats.capacity })}
+            >
+              {t('log_dropped', { count: logStats.dropped })}
+            </span>
+          )}
         </div>
         <div className="flex items-center gap-2" onClick={(e) => e.stopPropagation()}>
+          <button
+            onClick={handleExportLogs}
+            disabled={shown.length === 0}
+            className="px-3 py-1 text-xs text-gray-500 bg-white/70 rounded-lg hover:bg-white transition-colors border border-gray-200"
+            title={t('export_logs')}
+          >
+            {t('export')}
+          </button>
           <button
             onClick={handleClearLogs}
             className="px-3 py-1 text-xs text-gray-500 bg-white/70 rounded-lg hover:bg-white transition-colors border border-gray-200"
@@ -85,10 +154,10 @@ const LogViewer: React.FC = () => {
       {/* 稿纸内容区域（可折叠） */}
       {!isCollapsed && (
         <div className="maid-log-content h-40 p-3 overflow-y-auto font-mono text-xs">
-          {logs.length === 0 ? (
+          {shown.length === 0 ? (
             <p className="text-gray-400 italic log-line">{t('no_logs')}</p>
           ) : (
-            logs.map((log, index) => (
+            shown.map((log, index) => (
               <div key={index} className="log-line leading-6">
                 {renderLogLine(log)}
               </div>
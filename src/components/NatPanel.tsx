@@ -0,0 +1,64 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { EdgeOption, NatReport } from '../types';
+
+interface NatPanelProps {
+  /// 当前表单里的高级选项（打开中转就是加一条 -S1）
+  options: EdgeOption[];
+  onChange: (options: EdgeOption[]) => void;
+}
+
+/**
+ * NAT 侦察面板 🔍
+ * 看看隔了几层 NAT，两层 NAT / 运营商级 NAT 时建议打开中转
+ */
+const NatPanel: React.FC<NatPanelProps> = ({ options, onChange }) => {
+  const { t } = useTranslation();
+  const [report, setReport] = useState<NatReport | null>(null);
+  const [checking, setChecking] = useState(false);
+
+  const relayEnabled = options.some((option) => option.enabled && option.flag.trim() === '-S1');
+
+  const handleDiagnose = async () => {
+    setChecking(true);
+    try {
+      setReport(await invoke<NatReport>('diagnose_nat'));
+    } catch (err) {
+      alert(`${t('nat_check_failed')}: ${t(String(err))}`);
+    } finally {
+      setChecking(false);
+    }
+  };
+
+  const enableRelay = () => onChange([...options, { flag: '-S1', value: null, enabled: true }]);
+
+  return (
+    <div>
+      <div className="flex items-center justify-between mb-1.5">
+        <span className="text-sm font-medium text-gray-600">{t('nat_check')}</span>
+        <button type="button" onClick={() => void handleDiagnose()} disabled={checking} className="text-xs maid-button-secondary">
+          {checking ? t('nat_checking') : t('nat_check_run')}
+        </button>
+      </div>
+      {report && (
+        <div className="text-xs text-gray-600">
+          <p className="font-mono">
+            {t('nat_local_ip')}: {report.localIp ?? '-'} · {t('nat_router_wan_ip')}: {report.routerWanIp ?? '-'} · {t('nat_external_ip')}: {report.externalIp ?? '-'}
+          </p>
+          <p className={`mt-1 ${report.relayRecommended ? 'text-amber-600' : 'text-gray-500'}`}>
+            {t(`nat_verdict_${report.verdict}`)}
+          </p>
+          {report.relayRecommended && !relayEnabled && (
+            <button type="button" onClick={enableRelay} className="mt-1 text-xs maid-button-secondary">
+              {t('nat_enable_relay')}
+            </button>
+          )}
+        </div>
+      )}
+      <p className="mt-1 text-xs text-gray-400">{t('nat_check_desc')}</p>
+    </div>
+  );
+};
+
+export default NatPanel;
@@ -0,0 +1,107 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { NetworkRule, NetworkSnapshot, ProfileSummary } from '../types';
+
+/**
+ * 认路面板 🧭
+ * 编辑“连着哪个网络就换用哪份方案”的对照表
+ */
+const NetworkRulesPanel: React.FC = () => {
+  const { t } = useTranslation();
+  const [rules, setRules] = useState<NetworkRule[]>([]);
+  const [profiles, setProfiles] = useState<ProfileSummary[]>([]);
+  const [network, setNetwork] = useState<NetworkSnapshot | null>(null);
+
+  useEffect(() => {
+    void (async () => {
+      try {
+        setRules(await invoke<NetworkRule[]>('get_network_rules'));
+        setProfiles(await invoke<ProfileSummary[]>('list_profiles'));
+        setNetwork(await invoke<NetworkSnapshot>('get_network_snapshot'));
+      } catch (error) {
+        console.error('读取认路对照表失败：', error);
+      }
+    })();
+  }, []);
+
+  const updateRule = (index: number, patch: Partial<NetworkRule>) => {
+    setRules(rules.map((rule, i) => (i === index ? { ...rule, ...patch } : rule)));
+  };
+
+  const removeRule = (index: number) => {
+    setRules(rules.filter((_, i) => i !== index));
+  };
+
+  // 新规则默认填上现在的 Wi-Fi（没有 Wi-Fi 时填网卡）
+  const addRule = () => {
+    const ssid = network?.ssids[0] ?? '';
+    const iface = ssid ? '' : network?.interfaces[0] ?? '';
+    setRules([...rules, { ssid, interface: iface, profile: profiles[0]?.name ?? '' }]);
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke('save_network_rules', { rules });
+      alert(t('network_rules_saved'));
+    } catch (error) {
+      alert(`${t('network_rules_failed')}: ${error}`);
+    }
+  };
+
+  const describeNetwork = () => {
+    if (!network) return '';
+    const parts = [...network.ssids, ...network.interfaces];
+    return parts.length > 0 ? t('network_current', { network: parts.join(', ') }) : t('network_none');
+  };
+
+  return (
+    <div>
+      <label className="block text-sm font-medium text-gray-600 mb-1.5">
+        {t('network_rules')}
+        <span className="ml-2 text-xs text-gray-400">{describeNetwork()}</span>
+      </label>
+      {rules.map((rule, index) => (
+        <div key={index} className="flex gap-2 mb-2">
+          <input
+            type="text"
+            value={rule.ssid}
+            onChange={e => updateRule(index, { ssid: e.target.value })}
+            placeholder={t('network_rule_ssid')}
+            className="flex-1 maid-input"
+          />
+          <input
+            type="text"
+            value={rule.interface}
+            onChange={e => updateRule(index, { interface: e.target.value })}
+            placeholder={t('network_rule_interface')}
+            className="flex-1 maid-input"
+          />
+          <select
+            value={rule.profile}
+            onChange={e => updateRule(index, { profile: e.target.value })}
+            className="flex-1 maid-input"
+          >
+            {profiles.map(profile => (
+              <option key={profile.name} value={profile.name}>{profile.name}</option>
+            ))}
+          </select>
+          <button type="button" onClick={() => removeRule(index)} className="text-xs maid-button-secondary">
+            ✕
+          </button>
+        </div>
+      ))}
+      <div className="flex gap-2">
+        <button type="button" onClick={addRule} className="text-xs maid-button-secondary" title={t('network_rule_use_current')}>
+          {t('network_rule_add')}
+        </button>
+        <button type="button" onClick={() => void handleSave()} className="text-xs maid-button-secondary">
+          {t('network_rules_save')}
+        </button>
+      </div>
+      <p className="mt-1 text-xs text-gray-400">{t('network_rules_desc')}</p>
+    </div>
+  );
+};
+
+export default NetworkRulesPanel;
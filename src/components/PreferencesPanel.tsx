@@ -0,0 +1,87 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useTranslation } from 'react-i18next';
+import { AppPreferences, ConfigEncryption } from '../types';
+
+/**
+ * 恩兔的偏好面板 🎀
+ * 配色、开机自启、提醒气泡、关窗口躲进托盘：不属于任何方案，改了马上单独保存
+ */
+const PreferencesPanel: React.FC = () => {
+  const { t } = useTranslation();
+  const [preferences, setPreferences] = useState<AppPreferences | null>(null);
+  // 同步文件夹边输边改，离开输入框时才保存
+  const [syncDir, setSyncDir] = useState('');
+  // 指示簿加了密码就不能同步（同步文件夹里只能放明文）
+  const [encrypted, setEncrypted] = useState(false);
+
+  useEffect(() => {
+    void invoke<AppPreferences>('get_preferences')
+      .then((loaded) => {
+        setPreferences(loaded);
+        setSyncDir(loaded.sync_dir || '');
+      })
+      .catch((error) => console.error('读取偏好失败：', error));
+    void invoke<ConfigEncryption>('get_config_encryption')
+      .then((encryption) => setEncrypted(encryption.encrypted))
+      .catch((error) => console.error('读取加密状态失败：', error));
+  }, []);
+
+  const update = async <K extends keyof AppPreferences>(field: K, value: AppPreferences[K]) => {
+    if (!preferences) return;
+    const next = { ...preferences, [field]: value };
+    try {
+      await invoke('save_preferences', { preferences: next });
+      setPreferences(next);
+    } catch (error) {
+      alert(`${t('preferences_save_failed')}: ${t(String(error))}`);
+    }
+  };
+
+  if (!preferences) return null;
+
+  return (
+    <div>
+      <span className="block text-sm font-medium text-gray-600 mb-1.5">{t('preferences')}</span>
+      <label htmlFor="theme" className="block text-xs text-gray-500 mb-1">
+        {t('theme')}
+      </label>
+      <select
+        id="theme"
+        value={preferences.theme}
+        onChange={(e) => void update('theme', e.target.value as AppPreferences['theme'])}
+        className="maid-input"
+      >
+        <option value="system">{t('theme_system')}</option>
+        <option value="light">{t('theme_light')}</option>
+        <option value="dark">{t('theme_dark')}</option>
+      </select>
+      {(['autostart', 'notifications', 'close_to_tray'] as const).map((field) => (
+        <label key={field} className="flex items-center gap-2 mt-2 text-sm text-gray-600">
+          <input type="checkbox" checked={preferences[field]} onChange={(e) => void update(field, e.target.checked)} />
+          {t(field)}
+        </label>
+      ))}
+      <label htmlFor="sync_dir" className="block mt-2 text-xs text-gray-500 mb-1">
+        {t('sync_dir')}
+      </label>
+      <input
+        id="sync_dir"
+        type="text"
+        value={syncDir}
+        onChange={(e) => setSyncDir(e.target.value)}
+        onBlur={() => {
+          if (syncDir.trim() !== (preferences.sync_dir || '')) void update('sync_dir', syncDir.trim() || null);
+        }}
+        placeholder={t('sync_dir_placeholder')}
+        disabled={encrypted && !preferences.sync_dir}
+        className="font-mono text-sm maid-input"
+      />
+      <p className="mt-1 text-xs text-gray-400">{t('sync_dir_desc')}</p>
+      {encrypted && <p className="mt-1 text-xs text-amber-600">{t('sync_dir_encrypted')}</p>}
+      <p className="mt-1 text-xs text-gray-400">{t('preferences_desc')}</p>
+    </div>
+  );
+};
+
+export default PreferencesPanel;
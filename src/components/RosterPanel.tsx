@@ -0,0 +1,173 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { downloadDir, join } from '@tauri-apps/api/path';
+import { useTranslation } from 'react-i18next';
+import { Roster, RosterMember } from '../types';
+
+interface RosterPanelProps {
+  /// 当前表单里的小组暗号（花名册按暗号分本）
+  community: string;
+}
+
+/**
+ * 花名册面板 📒
+ * 记下小组成员分到的固定地址，可以导入/导出花名册文件
+ */
+const RosterPanel: React.FC<RosterPanelProps> = ({ community }) => {
+  const { t } = useTranslation();
+  const [roster, setRoster] = useState<Roster | null>(null);
+
+  const load = async () => {
+    if (!community.trim()) {
+      setRoster(null);
+      return;
+    }
+    try {
+      setRoster(await invoke<Roster>('get_roster', { community }));
+    } catch (error) {
+      console.error('读取花名册失败：', error);
+    }
+  };
+
+  useEffect(() => {
+    void load();
+  }, [community]);
+
+  const describe = (error: unknown) => {
+    const message = String(error);
+    return message.startsWith('error_') ? t(message) : message;
+  };
+
+  const updateMember = (index: number, patch: Partial<RosterMember>) => {
+    if (!roster) return;
+    setRoster({
+      ...roster,
+      members: roster.members.map((member, i) => (i === index ? { ...member, ...patch } : member)),
+    });
+  };
+
+  const removeMember = (index: number) => {
+    if (!roster) return;
+    setRoster({ ...roster, members: roster.members.filter((_, i) => i !== index) });
+  };
+
+  // 先存一下再问下一个空地址（没存的改动也算在内）
+  const handleAddMember = async () => {
+    if (!roster) return;
+    try {
+      await invoke('save_roster', { roster });
+      const ip = await invoke<string | null>('suggest_roster_ip', { community });
+      if (!ip) {
+        alert(t('roster_full'));
+        return;
+      }
+      setRoster({ ...roster, members: [...roster.members, { name: '', ip, notes: '' }] });
+    } catch (error) {
+      alert(`${t('roster_failed')}: ${describe(error)}`);
+    }
+  };
+
+  const handleSave = async () => {
+    if (!roster) return;
+    try {
+      await invoke('save_roster', { roster });
+      alert(t('roster_saved'));
+    } catch (error) {
+      alert(`${t('roster_failed')}: ${describe(error)}`);
+    }
+  };
+
+  const handleImport = async () => {
+    const path = prompt(t('roster_import_path_prompt'))?.trim();
+    if (!path) return;
+    try {
+      const imported = await invoke<Roster>('import_roster', { path });
+      alert(t('roster_imported', { community: imported.community, count: imported.members.length }));
+      await load();
+    } catch (error) {
+      alert(`${t('roster_failed')}: ${describe(error)}`);
+    }
+  };
+
+  const handleExport = async () => {
+    let suggested = `n2n-maid-roster-${community}.toml`;
+    try {
+      suggested = await join(await downloadDir(), suggested);
+    } catch (error) {
+      console.error('找不到下载目录：', error);
+    }
+    const path = prompt(t('roster_export_path_prompt'), suggested)?.trim();
+    if (!path) return;
+    try {
+      await invoke('export_roster', { community, path });
+      alert(t('roster_exported', { path }));
+    } catch (error) {
+      alert(`${t('roster_failed')}: ${describe(error)}`);
+    }
+  };
+
+  return (
+    <div>
+      <label className="block text-sm font-medium text-gray-600 mb-1.5">{t('roster')}</label>
+      {roster ? (
+        <>
+          <input
+            type="text"
+            value={roster.subnet}
+            onChange={e => setRoster({ ...roster, subnet: e.target.value })}
+            placeholder={t('roster_subnet')}
+            title={t('roster_subnet')}
+            className="w-full maid-input mb-2"
+          />
+          {roster.members.map((member, index) => (
+            <div key={index} className="flex gap-2 mb-2">
+              <input
+                type="text"
+                value={member.name}
+                onChange={e => updateMember(index, { name: e.target.value })}
+                placeholder={t('roster_member_name')}
+                className="flex-1 maid-input"
+              />
+              <input
+                type="text"
+                value={member.ip}
+                onChange={e => updateMember(index, { ip: e.target.value })}
+                placeholder={t('roster_member_ip')}
+                className="flex-1 maid-input"
+              />
+              <input
+                type="text"
+                value={member.notes}
+                onChange={e => updateMember(index, { notes: e.target.value })}
+                placeholder={t('roster_member_notes')}
+                className="flex-1 maid-input"
+              />
+              <button type="button" onClick={() => removeMember(index)} className="text-xs maid-button-secondary">
+                ✕
+              </button>
+            </div>
+          ))}
+          <div className="flex gap-2">
+            <button type="button" onClick={() => void handleAddMember()} className="text-xs maid-button-secondary" title={t('roster_suggest')}>
+              {t('roster_add')}
+            </button>
+            <button type="button" onClick={() => void handleSave()} className="text-xs maid-button-secondary">
+              {t('roster_save')}
+            </button>
+            <button type="button" onClick={() => void handleImport()} className="text-xs maid-button-secondary">
+              {t('roster_import')}
+            </button>
+            <button type="button" onClick={() => void handleExport()} className="text-xs maid-button-secondary">
+              {t('roster_export')}
+            </button>
+          </div>
+        </>
+      ) : (
+        <p className="text-xs text-gray-400">{t('roster_need_community')}</p>
+      )}
+      <p className="mt-1 text-xs text-gray-400">{t('roster_desc')}</p>
+    </div>
+  );
+};
+
+export default RosterPanel;
@@ -1,6 +1,16 @@
-import { useState } from 'react';
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { downloadDir } from '@tauri-apps/api/path';
 import { useTranslation } from 'react-i18next';
-import { N2NConfig } from '../types';
+import { BackupInfo, Cipher, Compression, ConfigTemplate, EdgeCandidate, EdgeCaps, EdgeOption, FieldError, KnockStep, N2NConfig, PortForward, Route, SupernodeEntry } from '../types';
+import ControlApiPanel from './ControlApiPanel';
+import ConfigVaultPanel from './ConfigVaultPanel';
+import RosterPanel from './RosterPanel';
+import NetworkRulesPanel from './NetworkRulesPanel';
+import WakePanel from './WakePanel';
+import NatPanel from './NatPanel';
+import PreferencesPanel from './PreferencesPanel';
+import AboutPanel from './AboutPanel';
 
 /**
  * 服务准备面板组件 - 粉色卡片风格 💖
@@ -10,12 +20,147 @@ interface SettingsProps {
   config: N2NConfig;
   onSave: (config: N2NConfig) => void;
   onCancel: () => void;
+  /// 当前方案是管理员下发的只读方案
+  readOnly?: boolean;
+  /// 解锁只读方案
+  onUnlock?: () => void;
+  /// 指示簿已从备份恢复（需要重新加载）
+  onRestored?: () => void;
+  /// 指示簿被锁上了（需要重新解锁）
+  onLocked?: () => void;
 }
 
-const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
+const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel, readOnly = false, onUnlock, onRestored, onLocked }) => {
   const { t } = useTranslation();
   const [formData, setFormData] = useState<N2NConfig>(config);
   const [showAdvanced, setShowAdvanced] = useState(false);
+  const [addressBook, setAddressBook] = useState<SupernodeEntry[]>([]);
+  const [backups, setBackups] = useState<BackupInfo[]>([]);
+  const [selectedBackup, setSelectedBackup] = useState<number | null>(null);
+  // 保存前检查出的问题（字段名 → error_ 编号）
+  const [fieldErrors, setFieldErrors] = useState<Record<string, string>>({});
+  // 被环境变量/启动参数覆盖的字段（改了也不会写进指示簿）
+  const [overridden, setOverridden] = useState<string[]>([]);
+  // 重要同伴按文本编辑，保存时再拆成列表（逗号或空白分隔）
+  const [importantPeersText, setImportantPeersText] = useState((config.important_peers || []).join(', '));
+  const [edgeSha256Text, setEdgeSha256Text] = useState((config.edge_sha256 || []).join(', '));
+  const [failoverText, setFailoverText] = useState((config.supernode_failover || []).join(', '));
+  const [templates, setTemplates] = useState<ConfigTemplate[]>([]);
+  // 当前 edge 会的本事（问不出来时为 null，这时不拦着）
+  const [edgeCaps, setEdgeCaps] = useState<EdgeCaps | null>(null);
+  // 机器上找到的 edge（点了“找找看”才会有）
+  const [edgeCandidates, setEdgeCandidates] = useState<EdgeCandidate[] | null>(null);
+
+  // 翻开总部地址簿
+  const loadAddressBook = async () => {
+    try {
+      setAddressBook(await invoke<SupernodeEntry[]>('list_supernodes'));
+    } catch (error) {
+      console.error('读取总部地址簿失败：', error);
+    }
+  };
+
+  // 翻看指示簿的备份柜
+  const loadBackups = async () => {
+    try {
+      const list = await invoke<BackupInfo[]>('list_backups');
+      setBackups(list);
+      setSelectedBackup(list.length > 0 ? list[0].timestamp : null);
+    } catch (error) {
+      console.error('读取备份柜失败：', error);
+    }
+  };
+
+  useEffect(() => {
+    void loadAddressBook();
+    void loadBackups();
+    void invoke<string[]>('get_overridden_fields').then(setOverridden).catch(() => {});
+    void invoke<ConfigTemplate[]>('list_templates').then(setTemplates).catch(() => {});
+  }, []);
+
+  // 换了 edge 就重新读一遍它的说明书
+  useEffect(() => {
+    void invoke<EdgeCaps | null>('get_edge_capabilities', { config: formData })
+      .then(setEdgeCaps)
+      .catch(() => setEdgeCaps(null));
+  }, [formData.edge_path]);
+
+  const cipherSupported = (cipher: Cipher) => !edgeCaps || cipher === 'default' || edgeCaps.ciphers.includes(cipher);
+  const compressionSupported = (compression: Compression) =>
+    !edgeCaps || compression === 'none' || edgeCaps.compressions.includes(compression);
+
+  // 把指示簿恢复到选中的备份
+  const handleRestoreBackup = async () => {
+    if (selectedBackup === null) return;
+    const time = new Date(selectedBackup * 1000).toLocaleString();
+    if (!confirm(t('backup_restore_confirm', { time }))) return;
+    try {
+      await invoke('restore_backup', { timestamp: selectedBackup });
+      onRestored?.();
+    } catch (error) {
+      alert(`${t('backup_restore_failed')}: ${error}`);
+    }
+  };
+
+  // 把机器上装着的 edge 都找出来给主人挑
+  const handleDiscoverEdges = async () => {
+    try {
+      setEdgeCandidates(await invoke<EdgeCandidate[]>('discover_edge_binaries'));
+    } catch (error) {
+      alert(`${t('edge_discover_failed')}: ${error}`);
+    }
+  };
+
+  // 从地址簿选总部：记下引用，地址跟着地址簿走
+  const handlePickSupernode = (id: string) => {
+    const entry = addressBook.find((s) => s.id === id);
+    setFormData(prev => ({
+      ...prev,
+      supernode_id: entry ? entry.id : null,
+      supernode: entry ? entry.address : prev.supernode,
+    }));
+  };
+
+  // 把当前填写的地址收进地址簿
+  const handleAddToAddressBook = async () => {
+    const name = prompt(t('address_book_name_prompt'))?.trim();
+    if (!name) return;
+    try {
+      const id = await invoke<string>('save_supernode', {
+        entry: { id: '', name, address: formData.supernode, notes: '' },
+      });
+      await loadAddressBook();
+      setFormData(prev => ({ ...prev, supernode_id: id }));
+    } catch (error) {
+      alert(`${t('save_failed')}: ${error}`);
+    }
+  };
+
+  // 量一量到选中总部的距离
+  const handleMeasureRtt = async () => {
+    if (!formData.supernode_id) return;
+    try {
+      await invoke('measure_supernode_rtt', { id: formData.supernode_id });
+      await loadAddressBook();
+    } catch (error) {
+      alert(`${t('address_book_measure_failed')}: ${error}`);
+    }
+  };
+
+  // 用模板预填组网相关的几项（本机设置不动，保存前还可以再改）
+  const handleApplyTemplate = async (id: string) => {
+    if (!id) return;
+    const name = t(`template_${id}`);
+    if (!confirm(t('template_apply_confirm', { name, desc: t(`template_${id}_desc`) }))) return;
+    try {
+      const filled = await invoke<N2NConfig>('apply_template', { id, config: formData });
+      setFormData(filled);
+      setImportantPeersText((filled.important_peers || []).join(', '));
+      setFieldErrors({});
+    } catch (error) {
+      alert(`${t('save_failed')}: ${error}`);
+    }
+  };
 
   const handleChange = (field: keyof N2NConfig, value: any) => {
     setFormData(prev => ({
@@ -24,11 +169,115 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
     }));
   };
 
-  const handleSubmit = (e: React.FormEvent) => {
+  // 传话筒规则：改一条 / 加一条 / 删一条
+  const forwards = formData.port_forwards || [];
+  const updateForward = (index: number, patch: Partial<PortForward>) =>
+    handleChange('port_forwards', forwards.map((rule, i) => (i === index ? { ...rule, ...patch } : rule)));
+  const addForward = () =>
+    handleChange('port_forwards', [...forwards, { name: '', listen: '127.0.0.1:8080', target: '', enabled: true }]);
+  const removeForward = (index: number) =>
+    handleChange('port_forwards', forwards.filter((_, i) => i !== index));
+
+  // 敲门步骤：改一步 / 加一步 / 删一步
+  const knocks = formData.port_knock || [];
+  const updateKnock = (index: number, patch: Partial<KnockStep>) =>
+    handleChange('port_knock', knocks.map((step, i) => (i === index ? { ...step, ...patch } : step)));
+  const addKnock = () =>
+    handleChange('port_knock', [...knocks, { protocol: 'udp', port: 7000, payload: '', delay_ms: 200 }]);
+  const removeKnock = (index: number) =>
+    handleChange('port_knock', knocks.filter((_, i) => i !== index));
+
+  // 路由表：改一条 / 加一条 / 删一条
+  const routes = formData.routes || [];
+  const updateRoute = (index: number, patch: Partial<Route>) =>
+    handleChange('routes', routes.map((route, i) => (i === index ? { ...route, ...patch } : route)));
+  const addRoute = () =>
+    handleChange('routes', [...routes, { network: '', netmask: '255.255.255.0', gateway: '' }]);
+  const removeRoute = (index: number) =>
+    handleChange('routes', routes.filter((_, i) => i !== index));
+
+  // 高级选项：改一条 / 加一条 / 删一条
+  const edgeOptions = formData.advanced_options || [];
+  const updateOption = (index: number, patch: Partial<EdgeOption>) =>
+    handleChange('advanced_options', edgeOptions.map((option, i) => (i === index ? { ...option, ...patch } : option)));
+  const addOption = () =>
+    handleChange('advanced_options', [...edgeOptions, { flag: '', value: null, enabled: true }]);
+  const removeOption = (index: number) =>
+    handleChange('advanced_options', edgeOptions.filter((_, i) => i !== index));
+
+  // 按表单里的指示拼出 edge 命令行，复制给主人（默认把密语打码）
+  const handleExportCommandLine = async () => {
+    const maskSecrets = !confirm(t('command_line_include_secrets'));
+    try {
+      const commandLine = await invoke<string>('export_command_line', { config: formData, maskSecrets });
+      await navigator.clipboard.writeText(commandLine);
+      alert(t('command_line_copied', { commandLine }));
+    } catch (error) {
+      alert(`${t('command_line_failed')}: ${error}`);
+    }
+  };
+
+  // 网关模式：把开机启动的服务配置导出到主人选的文件夹，告诉主人怎么装上
+  const handleExportServiceSetup = async () => {
+    let suggested = '';
+    try {
+      suggested = await downloadDir();
+    } catch (error) {
+      console.error('找不到下载目录：', error);
+    }
+    const dir = prompt(t('service_export_dir_prompt'), suggested)?.trim();
+    if (!dir) return;
+    try {
+      const install = await invoke<string>('export_service_setup', { dir });
+      alert(t('service_exported', { install }));
+    } catch (error) {
+      alert(`${t('service_export_failed')}: ${error}`);
+    }
+  };
+
+  // 随机挑一个 MAC（克隆的虚拟机撞了 MAC 时用）
+  const handleRandomMac = async () => {
+    try {
+      handleChange('mac_address', await invoke<string>('random_mac_address'));
+    } catch (error) {
+      console.error('生成 MAC 失败：', error);
+    }
+  };
+
+  // 只改通道宽度时不用重连：直接改虚拟网卡（后端同时记进当前方案）
+  const handleApplyMtu = async () => {
+    try {
+      await invoke('apply_mtu', { mtu: formData.mtu || 1290 });
+      setFieldErrors((prev) => ({ ...prev, mtu: '' }));
+    } catch (error) {
+      alert(`${t('mtu_apply_failed')}: ${t(String(error))}`);
+    }
+  };
+
+  const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
-    onSave(formData);
+    const important_peers = importantPeersText.split(/[\s,]+/).filter(Boolean);
+    const edge_sha256 = edgeSha256Text.split(/[\s,]+/).filter(Boolean);
+    const supernode_failover = failoverText.split(/[\s,]+/).filter(Boolean);
+    const next = { ...formData, important_peers, edge_sha256, supernode_failover };
+    try {
+      const errors = await invoke<FieldError[]>('validate_config', { config: next });
+      setFieldErrors(Object.fromEntries(errors.map((err) => [err.field, err.code])));
+      if (errors.length > 0) {
+        // 问题在高级设置里的话，把它展开给主人看
+        if (errors.some((err) => ['edge_path', 'mtu', 'local_port', 'register_interval_secs', 'mac_address', 'management_port', 'port_forwards', 'routes', 'wake_rules', 'port_knock', 'advanced_options', 'status_snapshot_interval_secs', 'supernode_recheck_secs', 'supernode_failover', 'edge_sha256'].includes(err.field))) setShowAdvanced(true);
+        return;
+      }
+    } catch (error) {
+      console.error('检查指示失败：', error);
+    }
+    onSave(next);
   };
 
+  // 某个字段检查出的问题
+  const fieldError = (field: keyof N2NConfig) =>
+    fieldErrors[field] && <p className="mt-1 text-xs text-red-600">{t(fieldErrors[field])}</p>;
+
   return (
     <div className="flex flex-col h-full min-h-0">
       <form onSubmit={handleSubmit} className="flex flex-col flex-1 min-h-0">
@@ -38,22 +287,95 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
             <h2 className="text-lg font-bold text-gray-700">
               {t('settings')}
             </h2>
+
+          {/* 只读方案提示 */}
+          {readOnly && (
+            <div className="flex items-center justify-between gap-2 p-2 text-xs text-gray-600 border rounded-lg border-amber-200 bg-amber-50">
+              <span>🔒 {t('profile_read_only_hint')}</span>
+              {onUnlock && (
+                <button type="button" onClick={onUnlock} className="text-xs maid-button-secondary">
+                  {t('profile_unlock')}
+                </button>
+              )}
+            </div>
+          )}
+
+          {overridden.length > 0 && (
+            <div className="p-2 text-xs text-gray-600 border rounded-lg border-amber-200 bg-amber-50">
+              ⚙ {t('config_overridden_hint', { fields: overridden.join(', ') })}
+            </div>
+          )}
           
+          {/* 方案模板 */}
+          {!readOnly && templates.length > 0 && (
+            <select value="" onChange={e => void handleApplyTemplate(e.target.value)} className="maid-input">
+              <option value="">{t('template_pick')}</option>
+              {templates.map((tpl) => (
+                <option key={tpl.id} value={tpl.id} title={t(`template_${tpl.id}_desc`)}>
+                  {t(`template_${tpl.id}`)}
+                </option>
+              ))}
+            </select>
+          )}
+
           {/* 总部地址 */}
           <div>
             <label htmlFor="supernode" className="block text-sm font-medium text-gray-600 mb-1.5">
               {t('supernode')} *
             </label>
+            <div className="flex gap-2 mb-2">
+              <select
+                value={formData.supernode_id || ''}
+                onChange={e => handlePickSupernode(e.target.value)}
+                disabled={readOnly}
+                className="flex-1 maid-input"
+              >
+                <option value="">{t('address_book_manual')}</option>
+                {addressBook.map((s) => (
+                  <option key={s.id} value={s.id}>
+                    {s.name} ({s.address}){s.last_rtt_ms != null ? ` · ${Math.round(s.last_rtt_ms)}ms` : ''}
+                  </option>
+                ))}
+              </select>
+              {formData.supernode_id ? (
+                <button type="button" onClick={handleMeasureRtt} className="text-xs maid-button-secondary">
+                  {t('address_book_measure')}
+                </button>
+              ) : (
+                <button
+                  type="button"
+                  onClick={handleAddToAddressBook}
+                  disabled={readOnly || !formData.supernode.trim()}
+                  className="text-xs maid-button-secondary"
+                >
+                  {t('address_book_add')}
+                </button>
+              )}
+            </div>
             <input
               id="supernode"
               type="text"
               value={formData.supernode}
-              onChange={e => handleChange('supernode', e.target.value)}
+              onChange={e => setFormData(prev => ({ ...prev, supernode: e.target.value, supernode_id: null }))}
               placeholder="example.com:7777"
               className="maid-input"
-              required
+              disabled={readOnly}
+              required={!formData.supernode_dns}
             />
             <p className="mt-1 text-xs text-gray-400">{t('supernode_desc')}</p>
+            {fieldError('supernode')}
+            {/* 按域名找总部（DNS SRV/TXT），管理员改 DNS 就能让大家换总部 */}
+            <input
+              id="supernode_dns"
+              type="text"
+              value={formData.supernode_dns || ''}
+              onChange={e => handleChange('supernode_dns', e.target.value || null)}
+              placeholder={t('supernode_dns_placeholder')}
+              className="mt-2 maid-input"
+              disabled={readOnly}
+            />
+            <p className="mt-1 text-xs text-gray-400">{t('supernode_dns_desc')}</p>
+            {fieldError('supernode_dns')}
           </div>
 
           {/* 工作暗号 */}
@@ -68,9 +390,11 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
               onChange={e => handleChange('community', e.target.value)}
               placeholder="my_community"
               className="maid-input"
-              required
+              disabled={readOnly}
+              required={!formData.supernode_dns}
             />
             <p className="mt-1 text-xs text-gray-400">{t('community_desc')}</p>
+            {fieldError('community')}
           </div>
 
           {/* 我的工号 */}
@@ -100,10 +424,64 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
               onChange={e => handleChange('encryption_key', e.target.value)}
               placeholder="********"
               className="maid-input"
+              disabled={readOnly}
             />
             <p className="mt-1 text-xs text-gray-400">{t('encryption_key_desc')}</p>
           </div>
 
+          {/* 加密方式和包头加密 */}
+          <div>
+            <label htmlFor="cipher" className="block text-sm font-medium text-gray-600 mb-1.5">
+              {t('cipher')}
+            </label>
+            <select
+              id="cipher"
+              value={formData.cipher || 'default'}
+              onChange={e => handleChange('cipher', e.target.value)}
+              className="maid-input"
+            >
+              {(['default', 'aes', 'chacha20', 'speck', 'twofish', 'null'] as Cipher[]).map((cipher) => (
+                <option key={cipher} value={cipher} disabled={!cipherSupported(cipher)}>
+                  {t(`cipher_${cipher}`)}{cipherSupported(cipher) ? '' : ` (${t('edge_unsupported')})`}
+                </option>
+              ))}
+            </select>
+            <label className="flex items-center gap-2 mt-2 text-sm text-gray-600">
+              <input
+                type="checkbox"
+                checked={formData.header_encryption ?? false}
+                onChange={e => handleChange('header_encryption', e.target.checked)}
+                disabled={edgeCaps !== null && !edgeCaps.headerEncryption && !formData.header_encryption}
+              />
+              {t('header_encryption')}
+              {edgeCaps && !edgeCaps.headerEncryption && <span className="text-xs text-gray-400">({t('edge_unsupported')})</span>}
+            </label>
+            <p className="mt-1 text-xs text-gray-400">{t('cipher_desc')}</p>
+            {fieldError('cipher')}
+            {fieldError('header_encryption')}
+          </div>
+
+          {/* 数据压缩 */}
+          <div>
+            <label htmlFor="compression" className="block text-sm font-medium text-gray-600 mb-1.5">
+              {t('compression')}
+            </label>
+            <select
+              id="compression"
+              value={formData.compression || 'none'}
+              onChange={e => handleChange('compression', e.target.value)}
+              className="maid-input"
+            >
+              {(['none', 'lzo', 'zstd'] as Compression[]).map((compression) => (
+                <option key={compression} value={compression} disabled={!compressionSupported(compression)}>
+                  {t(`compression_${compression}`)}{compressionSupported(compression) ? '' : ` (${t('edge_unsupported')})`}
+                </option>
+              ))}
+            </select>
+            <p className="mt-1 text-xs text-gray-400">{t('compression_desc')}</p>
+            {fieldError('compression')}
+          </div>
+
           {/* 地址分配模式 */}
           <div>
             <label htmlFor="ip_mode" className="block text-sm font-medium text-gray-600 mb-1.5">
@@ -119,6 +497,7 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
               <option value="static">{t('static')}</option>
             </select>
             <p className="mt-1 text-xs text-gray-400">{t('ip_mode_desc')}</p>
+            {fieldError('ip_mode')}
           </div>
 
           {/* 静态 IP（仅在手动模式时显示） */}
@@ -136,8 +515,22 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
                 className="maid-input"
               />
               <p className="mt-1 text-xs text-gray-400">{t('static_ip_desc')}</p>
+              {fieldError('static_ip')}
             </div>
           )}
+
+          {/* 开门就上班 */}
+          <div>
+            <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+              <input
+                type="checkbox"
+                checked={formData.auto_connect ?? false}
+                onChange={e => handleChange('auto_connect', e.target.checked)}
+              />
+              {t('auto_connect')}
+            </label>
+            <p className="mt-1 text-xs text-gray-400">{t('auto_connect_desc')}</p>
+          </div>
           </div>
 
           {/* 高级设置卡片 */}
@@ -160,15 +553,43 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
                 <label htmlFor="edge_path" className="block text-sm font-medium text-gray-600 mb-1.5">
                   {t('edge_path')}
                 </label>
-                <input
-                  id="edge_path"
-                  type="text"
-                  value={formData.edge_path || ''}
-                  onChange={e => handleChange('edge_path', e.target.value)}
-                  placeholder="/usr/bin/edge"
-                  className="font-mono text-sm maid-input"
-                />
+                <div className="flex gap-2">
+                  <input
+                    id="edge_path"
+                    type="text"
+                    value={formData.edge_path || ''}
+                    onChange={e => handleChange('edge_path', e.target.value)}
+                    placeholder="/usr/bin/edge"
+                    className="flex-1 font-mono text-sm maid-input"
+                  />
+                  <button type="button" onClick={handleDiscoverEdges} className="text-xs maid-button-secondary">
+                    {t('edge_discover')}
+                  </button>
+                </div>
+                {edgeCandidates && (
+                  <div className="mt-2 space-y-1">
+                    {edgeCandidates.length === 0 && <p className="text-xs text-gray-400">{t('edge_discover_none')}</p>}
+                    {edgeCandidates.map((c) => (
+                      <button
+                        key={c.path}
+                        type="button"
+                        onClick={() => {
+                          handleChange('edge_path', c.path);
+                          setEdgeCandidates(null);
+                        }}
+                        className="block w-full px-2 py-1 text-xs text-left rounded hover:bg-maid-pink"
+                      >
+                        <span className="font-mono">{c.path}</span>
+                        <span className="ml-2 text-gray-400">
+                          {t(`edge_source_${c.source}`)} · {c.version || t('edge_version_unknown')}
+                          {c.arch ? ` · ${c.arch}` : ''}
+                        </span>
+                      </button>
+                    ))}
+                  </div>
+                )}
                 <p className="mt-1 text-xs text-gray-400">{t('edge_path_desc')}</p>
+                {fieldError('edge_path')}
               </div>
 
               {/* 设备名称 */}
@@ -192,31 +613,673 @@ const Settings: React.FC<SettingsProps> = ({ config, onSave, onCancel }) => {
                 <label htmlFor="mtu" className="block text-sm font-medium text-gray-600 mb-1.5">
                   {t('mtu')}
                 </label>
+                <div className="flex items-center gap-2">
+                  <input
+                    id="mtu"
+                    type="number"
+                    value={formData.mtu || 1290}
+                    onChange={e => handleChange('mtu', parseInt(e.target.value))}
+                    className="flex-1 maid-input"
+                  />
+                  <button
+                    type="button"
+                    onClick={() => void handleApplyMtu()}
+                    title={t('mtu_apply_live_hint')}
+                    className="text-xs maid-button-secondary"
+                  >
+                    {t('mtu_apply_live')}
+                  </button>
+                </div>
+                <p className="mt-1 text-xs text-gray-400">{t('mtu_desc')}</p>
+                {fieldError('mtu')}
+              </div>
+
+              {/* 固定本机端口 */}
+              <div>
+                <label htmlFor="local_port" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('local_port')}
+                </label>
                 <input
-                  id="mtu"
+                  id="local_port"
                   type="number"
-                  value={formData.mtu || 1290}
-                  onChange={e => handleChange('mtu', parseInt(e.target.value))}
+                  min={1}
+                  max={65535}
+                  value={formData.local_port ?? ''}
+                  onChange={e => handleChange('local_port', e.target.value ? parseInt(e.target.value) : null)}
+                  placeholder={t('local_port_placeholder')}
                   className="maid-input"
                 />
-                <p className="mt-1 text-xs text-gray-400">{t('mtu_desc')}</p>
+                {fieldError('local_port')}
+                <p className="mt-1 text-xs text-gray-400">{t('local_port_desc')}</p>
+              </div>
+
+              {/* 报到间隔（打洞保活） */}
+              <div>
+                <label htmlFor="register_interval_secs" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('register_interval_secs')}
+                </label>
+                <input
+                  id="register_interval_secs"
+                  type="number"
+                  min={1}
+                  max={3600}
+                  value={formData.register_interval_secs ?? ''}
+                  onChange={e => handleChange('register_interval_secs', e.target.value ? parseInt(e.target.value) : null)}
+                  placeholder={t('register_interval_placeholder')}
+                  className="maid-input"
+                />
+                {fieldError('register_interval_secs')}
+                <p className="mt-1 text-xs text-gray-400">{t('register_interval_desc')}</p>
+              </div>
+
+              {/* 指定 MAC */}
+              <div>
+                <label htmlFor="mac_address" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('mac_address')}
+                </label>
+                <div className="flex items-center gap-2">
+                  <input
+                    id="mac_address"
+                    type="text"
+                    value={formData.mac_address || ''}
+                    onChange={e => handleChange('mac_address', e.target.value || null)}
+                    placeholder={t('mac_address_placeholder')}
+                    className="flex-1 font-mono text-sm maid-input"
+                  />
+                  <button type="button" onClick={() => void handleRandomMac()} className="text-xs maid-button-secondary">
+                    {t('mac_address_randomize')}
+                  </button>
+                </div>
+                {fieldError('mac_address')}
+                <p className="mt-1 text-xs text-gray-400">{t('mac_address_desc')}</p>
+              </div>
+
+              {/* edge 管理口和口令 */}
+              <div>
+                <div className="flex items-center gap-2">
+                  <div className="w-32">
+                    <label htmlFor="management_port" className="block text-sm font-medium text-gray-600 mb-1.5">
+                      {t('management_port')}
+                    </label>
+                    <input
+                      id="management_port"
+                      type="number"
+                      min={1}
+                      max={65535}
+                      value={formData.management_port ?? ''}
+                      onChange={e => handleChange('management_port', e.target.value ? parseInt(e.target.value) : null)}
+                      placeholder="5644"
+                      title={t('management_port_placeholder')}
+                      className="maid-input"
+                    />
+                  </div>
+                  <div className="flex-1">
+                    <label htmlFor="management_password" className="block text-sm font-medium text-gray-600 mb-1.5">
+                      {t('management_password')}
+                    </label>
+                    <input
+                      id="management_password"
+                      type="password"
+                      value={formData.management_password || ''}
+                      onChange={e => handleChange('management_password', e.target.value || null)}
+                      placeholder={t('management_password_placeholder')}
+                      className="maid-input"
+                    />
+                  </div>
+                </div>
+                {fieldError('management_port')}
+                <p className="mt-1 text-xs text-gray-400">{t('management_desc')}</p>
+              </div>
+
+              {/* 工作汇报转递范围 */}
+              <div>
+                <label htmlFor="log_forward" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('log_forward')}
+                </label>
+                <select
+                  id="log_forward"
+                  value={formData.log_forward || 'all'}
+                  onChange={e => handleChange('log_forward', e.target.value)}
+                  className="maid-input"
+                >
+                  <option value="all">{t('log_forward_all')}</option>
+                  <option value="warnings_and_errors">{t('log_forward_warnings_and_errors')}</option>
+                  <option value="connection_events">{t('log_forward_connection_events')}</option>
+                </select>
+                <p className="mt-1 text-xs text-gray-400">{t('log_forward_desc')}</p>
+              </div>
+
+              {/* 恩兔的偏好（配色、开机自启……不属于方案，单独保存） */}
+              <PreferencesPanel />
+
+              {/* 断线重连策略 */}
+              <div>
+                <label htmlFor="reconnect_policy" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('reconnect_policy')}
+                </label>
+                <select
+                  id="reconnect_policy"
+                  value={formData.reconnect_policy || 'never'}
+                  onChange={e => handleChange('reconnect_policy', e.target.value)}
+                  className="maid-input"
+                >
+                  <option value="never">{t('reconnect_policy_never')}</option>
+                  <option value="on_failure">{t('reconnect_policy_on_failure')}</option>
+                  <option value="always">{t('reconnect_policy_always')}</option>
+                </select>
+                {(formData.reconnect_policy ?? 'never') !== 'never' && (
+                  <div className="flex gap-2 mt-2">
+                    <div className="flex-1">
+                      <label htmlFor="reconnect_max_attempts" className="block text-xs text-gray-500 mb-1">
+                        {t('reconnect_max_attempts')}
+                      </label>
+                      <input
+                        id="reconnect_max_attempts"
+                        type="number"
+                        min={0}
+                        value={formData.reconnect_max_attempts ?? 5}
+                        onChange={e => handleChange('reconnect_max_attempts', parseInt(e.target.value) || 0)}
+                        className="maid-input"
+                      />
+                    </div>
+                    <div className="flex-1">
+                      <label htmlFor="reconnect_backoff_max_secs" className="block text-xs text-gray-500 mb-1">
+                        {t('reconnect_backoff_max_secs')}
+                      </label>
+                      <input
+                        id="reconnect_backoff_max_secs"
+                        type="number"
+                        min={1}
+                        value={formData.reconnect_backoff_max_secs ?? 60}
+                        onChange={e => handleChange('reconnect_backoff_max_secs', parseInt(e.target.value) || 1)}
+                        className="maid-input"
+                      />
+                    </div>
+                  </div>
+                )}
+                <p className="mt-1 text-xs text-gray-400">{t('reconnect_policy_desc')}</p>
+              </div>
+
+              {/* 停止等待时间 */}
+              <div>
+                <label htmlFor="stop_timeout_secs" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('stop_timeout_secs')}
+                </label>
+                <input
+                  id="stop_timeout_secs"
+                  type="number"
+                  min={1}
+                  max={300}
+                  value={formData.stop_timeout_secs ?? 10}
+                  onChange={e => handleChange('stop_timeout_secs', parseInt(e.target.value) || 1)}
+                  className="maid-input"
+                />
+                <p className="mt-1 text-xs text-gray-400">{t('stop_timeout_desc')}</p>
+              </div>
+
+              {/* edge 走后重置虚拟网卡 */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.tap_reset_on_teardown ?? false}
+                    onChange={e => handleChange('tap_reset_on_teardown', e.target.checked)}
+                  />
+                  {t('tap_reset_on_teardown')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('tap_reset_on_teardown_desc')}</p>
+              </div>
+
+              {/* 网卡坏了就重新出门 */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.tap_restart_on_loss ?? false}
+                    onChange={e => handleChange('tap_restart_on_loss', e.target.checked)}
+                  />
+                  {t('tap_restart_on_loss')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('tap_restart_on_loss_desc')}</p>
+              </div>
+
+              {/* 状态快照（给外面的脚本/监控程序读） */}
+              <div>
+                <label htmlFor="status_snapshot_path" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('status_snapshot_path')}
+                </label>
+                <div className="flex items-center gap-2">
+                  <input
+                    id="status_snapshot_path"
+                    type="text"
+                    value={formData.status_snapshot_path || ''}
+                    onChange={e => handleChange('status_snapshot_path', e.target.value || null)}
+                    placeholder="/var/lib/node_exporter/textfile/n2n.prom"
+                    className="flex-1 font-mono text-sm maid-input"
+                  />
+                  <input
+                    id="status_snapshot_interval_secs"
+                    type="number"
+                    min={5}
+                    max={3600}
+                    value={formData.status_snapshot_interval_secs ?? 30}
+                    onChange={e => handleChange('status_snapshot_interval_secs', parseInt(e.target.value) || 5)}
+                    title={t('status_snapshot_interval_secs')}
+                    className="w-24 maid-input"
+                  />
+                </div>
+                {fieldError('status_snapshot_interval_secs')}
+                <p className="mt-1 text-xs text-gray-400">{t('status_snapshot_desc')}</p>
+              </div>
+
+              {/* 动态域名的总部：连着的时候定期重新查地址 */}
+              <div>
+                <label htmlFor="supernode_recheck_secs" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('supernode_recheck_secs')}
+                </label>
+                <input
+                  id="supernode_recheck_secs"
+                  type="number"
+                  min={0}
+                  max={86400}
+                  value={formData.supernode_recheck_secs ?? 300}
+                  onChange={e => handleChange('supernode_recheck_secs', parseInt(e.target.value) || 0)}
+                  className="w-32 maid-input"
+                />
+                <p className="mt-1 text-xs text-gray-400">{t('supernode_recheck_desc')}</p>
+                {fieldError('supernode_recheck_secs')}
+              </div>
+
+              {/* 备用总部：总部一直摸不到时按顺序换下一家 */}
+              <div>
+                <label htmlFor="supernode_failover" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('supernode_failover')}
+                </label>
+                <input
+                  id="supernode_failover"
+                  type="text"
+                  value={failoverText}
+                  onChange={e => setFailoverText(e.target.value)}
+                  placeholder="backup1.example.com:7777, backup2.example.com:7777"
+                  className="font-mono text-sm maid-input"
+                />
+                <p className="mt-1 text-xs text-gray-400">{t('supernode_failover_desc')}</p>
+                {fieldError('supernode_failover')}
+              </div>
+
+              {/* 路由表（经由同伴去往别的网段） */}
+              <div>
+                <div className="flex items-center justify-between mb-1.5">
+                  <span className="text-sm font-medium text-gray-600">{t('routes')}</span>
+                  <button type="button" onClick={addRoute} className="text-xs maid-button-secondary">
+                    {t('route_add')}
+                  </button>
+                </div>
+                {routes.map((route, index) => (
+                  <div key={index} className="flex items-center gap-2 mb-2">
+                    <input
+                      type="text"
+                      value={route.network}
+                      onChange={e => updateRoute(index, { network: e.target.value })}
+                      placeholder="192.168.2.0"
+                      className="flex-1 font-mono text-sm maid-input"
+                    />
+                    <span className="text-gray-400">/</span>
+                    <input
+                      type="text"
+                      value={route.netmask}
+                      onChange={e => updateRoute(index, { netmask: e.target.value })}
+                      placeholder="255.255.255.0"
+                      title={t('route_netmask')}
+                      className="w-32 font-mono text-sm maid-input"
+                    />
+                    <span className="text-gray-400">→</span>
+                    <input
+                      type="text"
+                      value={route.gateway}
+                      onChange={e => updateRoute(index, { gateway: e.target.value })}
+                      placeholder="10.0.0.2"
+                      title={t('route_gateway')}
+                      className="w-28 font-mono text-sm maid-input"
+                    />
+                    <button type="button" onClick={() => removeRoute(index)} className="text-xs maid-button-secondary">
+                      －
+                    </button>
+                  </div>
+                ))}
+                {fieldError('routes')}
+                <p className="mt-1 text-xs text-gray-400">{t('routes_desc')}</p>
+              </div>
+
+              {/* 中转节点（当网关） */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.packet_forwarding ?? false}
+                    onChange={e => handleChange('packet_forwarding', e.target.checked)}
+                  />
+                  {t('packet_forwarding')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('packet_forwarding_desc')}</p>
+              </div>
+
+              {/* 网关模式（无人值守的站点网关） */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.server_mode ?? false}
+                    onChange={e => handleChange('server_mode', e.target.checked)}
+                  />
+                  {t('server_mode')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('server_mode_desc')}</p>
+                {formData.server_mode && (
+                  <button type="button" onClick={() => void handleExportServiceSetup()} className="mt-2 text-xs maid-button-secondary">
+                    {t('service_export')}
+                  </button>
+                )}
+              </div>
+
+              {/* 高级选项（一条一条交给 edge 的开关） */}
+              <div>
+                <div className="flex items-center justify-between mb-1.5">
+                  <span className="text-sm font-medium text-gray-600">{t('advanced_options')}</span>
+                  <button type="button" onClick={addOption} className="text-xs maid-button-secondary">
+                    {t('advanced_option_add')}
+                  </button>
+                </div>
+                {edgeOptions.map((option, index) => (
+                  <div key={index} className="flex items-center gap-2 mb-2">
+                    <input
+                      type="checkbox"
+                      checked={option.enabled}
+                      onChange={e => updateOption(index, { enabled: e.target.checked })}
+                      title={t('advanced_option_enabled')}
+                    />
+                    <input
+                      type="text"
+                      value={option.flag}
+                      onChange={e => updateOption(index, { flag: e.target.value })}
+                      placeholder="-E"
+                      className="w-40 font-mono text-sm maid-input"
+                    />
+                    <input
+                      type="text"
+                      value={option.value || ''}
+                      onChange={e => updateOption(index, { value: e.target.value || null })}
+                      placeholder={t('advanced_option_value')}
+                      className="flex-1 font-mono text-sm maid-input"
+                    />
+                    <button type="button" onClick={() => removeOption(index)} className="text-xs maid-button-secondary">
+                      －
+                    </button>
+                  </div>
+                ))}
+                {fieldError('advanced_options')}
+                <p className="mt-1 text-xs text-gray-400">{t('advanced_options_desc')}</p>
+                <button type="button" onClick={() => void handleExportCommandLine()} className="mt-2 text-xs maid-button-secondary">
+                  {t('command_line_export')}
+                </button>
+              </div>
+
+              {/* NAT 侦察（两层 NAT / 运营商级 NAT 时建议打开中转） */}
+              <NatPanel options={edgeOptions} onChange={options => handleChange('advanced_options', options)} />
+
+              {/* 重要同伴（连上后预热邻居缓存） */}
+              <div>
+                <label htmlFor="important_peers" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('important_peers')}
+                </label>
+                <input
+                  id="important_peers"
+                  type="text"
+                  value={importantPeersText}
+                  onChange={e => setImportantPeersText(e.target.value)}
+                  placeholder="10.0.0.2, 10.0.0.5"
+                  className="font-mono text-sm maid-input"
+                />
+                <p className="mt-1 text-xs text-gray-400">{t('important_peers_desc')}</p>
+              </div>
+
+              {/* 传话筒（通道连上后转发 TCP 端口） */}
+              <div>
+                <div className="flex items-center justify-between mb-1.5">
+                  <span className="text-sm font-medium text-gray-600">{t('port_forwards')}</span>
+                  <button type="button" onClick={addForward} className="text-xs maid-button-secondary">
+                    {t('port_forward_add')}
+                  </button>
+                </div>
+                {forwards.map((rule, index) => (
+                  <div key={index} className="flex items-center gap-2 mb-2">
+                    <input
+                      type="checkbox"
+                      checked={rule.enabled}
+                      onChange={e => updateForward(index, { enabled: e.target.checked })}
+                      title={t('port_forward_enabled')}
+                    />
+                    <input
+                      type="text"
+                      value={rule.name}
+                      onChange={e => updateForward(index, { name: e.target.value })}
+                      placeholder={t('port_forward_name')}
+                      className="w-24 text-sm maid-input"
+                    />
+                    <input
+                      type="text"
+                      value={rule.listen}
+                      onChange={e => updateForward(index, { listen: e.target.value })}
+                      placeholder="127.0.0.1:8080"
+                      className="flex-1 font-mono text-sm maid-input"
+                    />
+                    <span className="text-gray-400">→</span>
+                    <input
+                      type="text"
+                      value={rule.target}
+                      onChange={e => updateForward(index, { target: e.target.value })}
+                      placeholder="10.0.0.5:80"
+                      className="flex-1 font-mono text-sm maid-input"
+                    />
+                    <button type="button" onClick={() => removeForward(index)} className="text-xs maid-button-secondary">
+                      －
+                    </button>
+                  </div>
+                ))}
+                {fieldError('port_forwards')}
+                <p className="mt-1 text-xs text-gray-400">{t('port_forwards_desc')}</p>
+              </div>
+
+              {/* 敲门（总部藏在端口敲门/单包授权后面时） */}
+              <div>
+                <div className="flex items-center justify-between mb-1.5">
+                  <span className="text-sm font-medium text-gray-600">{t('port_knock')}</span>
+                  <button type="button" onClick={addKnock} className="text-xs maid-button-secondary">
+                    {t('port_knock_add')}
+                  </button>
+                </div>
+                {knocks.map((step, index) => (
+                  <div key={index} className="flex items-center gap-2 mb-2">
+                    <select
+                      value={step.protocol}
+                      onChange={e =>
+                        updateKnock(index, {
+                          protocol: e.target.value as KnockStep['protocol'],
+                          payload: e.target.value === 'tcp' ? '' : step.payload,
+                        })
+                      }
+                      className="w-20 text-sm maid-input"
+                    >
+                      <option value="udp">UDP</option>
+                      <option value="tcp">TCP</option>
+                    </select>
+                    <input
+                      type="number"
+                      min={1}
+                      max={65535}
+                      value={step.port}
+                      onChange={e => updateKnock(index, { port: parseInt(e.target.value, 10) || 0 })}
+                      title={t('port_knock_port')}
+                      className="w-24 font-mono text-sm maid-input"
+                    />
+                    <input
+                      type="text"
+                      value={step.payload}
+                      onChange={e => updateKnock(index, { payload: e.target.value })}
+                      disabled={step.protocol === 'tcp'}
+                      placeholder={t('port_knock_payload')}
+                      className="flex-1 font-mono text-sm maid-input"
+                    />
+                    <input
+                      type="number"
+                      min={0}
+                      max={10000}
+                      value={step.delay_ms}
+                      onChange={e => updateKnock(index, { delay_ms: parseInt(e.target.value, 10) || 0 })}
+                      title={t('port_knock_delay')}
+                      className="w-24 font-mono text-sm maid-input"
+                    />
+                    <button type="button" onClick={() => removeKnock(index)} className="text-xs maid-button-secondary">
+                      －
+                    </button>
+                  </div>
+                ))}
+                {fieldError('port_knock')}
+                <p className="mt-1 text-xs text-gray-400">{t('port_knock_desc')}</p>
+              </div>
+
+              {/* 唤醒同伴（按时叫醒 NAS 之类） */}
+              <WakePanel
+                rules={formData.wake_rules || []}
+                onChange={rules => handleChange('wake_rules', rules)}
+                error={fieldErrors.wake_rules}
+              />
+
+              {/* 写入系统日志 */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.system_log ?? false}
+                    onChange={e => handleChange('system_log', e.target.checked)}
+                  />
+                  {t('system_log')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('system_log_desc')}</p>
               </div>
 
-              {/* 额外参数 */}
+              {/* 桌面的 VPN 提示（仅 Linux） */}
               <div>
-                <label htmlFor="extra_args" className="block text-sm font-medium text-gray-600 mb-1.5">
-                  {t('extra_args')}
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.desktop_vpn_hint ?? false}
+                    onChange={e => handleChange('desktop_vpn_hint', e.target.checked)}
+                  />
+                  {t('desktop_vpn_hint')}
                 </label>
-                <textarea
-                  id="extra_args"
-                  value={formData.extra_args || ''}
-                  onChange={e => handleChange('extra_args', e.target.value)}
-                  placeholder="-v -r -E"
-                  rows={2}
-                  className="font-mono text-sm resize-none maid-input"
+                <p className="mt-1 text-xs text-gray-400">{t('desktop_vpn_hint_desc')}</p>
+              </div>
+
+              {/* 给 edge 上锁（仅 Linux） */}
+              <div>
+                <label className="flex items-center gap-2 text-sm font-medium text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.edge_hardening ?? false}
+                    onChange={e => handleChange('edge_hardening', e.target.checked)}
+                  />
+                  {t('edge_hardening')}
+                </label>
+                {formData.edge_hardening && (
+                  <div className="mt-2">
+                    <label htmlFor="edge_run_as" className="block text-xs text-gray-500 mb-1">
+                      {t('edge_run_as')}
+                    </label>
+                    <input
+                      id="edge_run_as"
+                      type="text"
+                      value={formData.edge_run_as || ''}
+                      onChange={e => handleChange('edge_run_as', e.target.value || null)}
+                      placeholder="nobody"
+                      className="font-mono text-sm maid-input"
+                    />
+                  </div>
+                )}
+                <p className="mt-1 text-xs text-gray-400">{t('edge_hardening_desc')}</p>
+              </div>
+
+              {/* 核对 edge 的指纹 */}
+              <div>
+                <label htmlFor="edge_sha256" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('edge_sha256')}
+                </label>
+                <input
+                  id="edge_sha256"
+                  type="text"
+                  value={edgeSha256Text}
+                  onChange={e => setEdgeSha256Text(e.target.value)}
+                  className="font-mono text-sm maid-input"
                 />
-                <p className="mt-1 text-xs text-gray-400">{t('extra_args_desc')}</p>
+                {fieldError('edge_sha256')}
+                <p className="mt-1 text-xs text-gray-400">{t('edge_sha256_desc')}</p>
+                <label className="flex items-center gap-2 mt-2 text-sm text-gray-600">
+                  <input
+                    type="checkbox"
+                    checked={formData.edge_integrity_bypass ?? false}
+                    onChange={e => handleChange('edge_integrity_bypass', e.target.checked)}
+                  />
+                  {t('edge_integrity_bypass')}
+                </label>
+                <p className="mt-1 text-xs text-gray-400">{t('edge_integrity_bypass_desc')}</p>
+              </div>
+
+              {/* 指示簿备份 */}
+              <div>
+                <label htmlFor="backup" className="block text-sm font-medium text-gray-600 mb-1.5">
+                  {t('backup')}
+                </label>
+                <div className="flex gap-2">
+                  <select
+                    id="backup"
+                    value={selectedBackup ?? ''}
+                    onChange={e => setSelectedBackup(e.target.value ? Number(e.target.value) : null)}
+                    disabled={backups.length === 0}
+                    className="flex-1 maid-input"
+                  >
+                    {backups.length === 0 && <option value="">{t('backup_none')}</option>}
+                    {backups.map((b) => (
+                      <option key={b.timestamp} value={b.timestamp}>
+                        {new Date(b.timestamp * 1000).toLocaleString()} ({(b.size / 1024).toFixed(1)} KB)
+                      </option>
+                    ))}
+                  </select>
+                  <button
+                    type="button"
+                    onClick={handleRestoreBackup}
+                    disabled={selectedBackup === null}
+                    className="text-xs maid-button-secondary"
+                  >
+                    {t('backup_restore')}
+                  </button>
+                </div>
+                <p className="mt-1 text-xs text-gray-400">{t('backup_desc')}</p>
               </div>
+
+              {/* 认路（按网络自动换方案） */}
+              <NetworkRulesPanel />
+
+              {/* 花名册（管小组时记下成员的固定地址） */}
+              <RosterPanel community={formData.community} />
+
+              {/* 加密指示簿 */}
+              <ConfigVaultPanel onLocked={onLocked} />
+
+              {/* 遥控口（给脚本用的本机 HTTP 接口） */}
+              <ControlApiPanel />
+
+              {/* 关于恩兔（版本和运行环境，报告问题时用） */}
+              <AboutPanel />
               </div>
             )}
           </div>
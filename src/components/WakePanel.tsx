@@ -0,0 +1,147 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useTranslation } from 'react-i18next';
+import { BulkReport, WakeRecord, WakeRule } from '../types';
+
+interface WakePanelProps {
+  /// 当前表单里的唤醒规则（跟着方案一起保存）
+  rules: WakeRule[];
+  onChange: (rules: WakeRule[]) => void;
+  /// 保存时检查出来的问题（error_ 编号）
+  error?: string;
+}
+
+/**
+ * 唤醒面板 ⏰
+ * 按时叫醒同伴（比如备份前的 NAS），并翻看最近几次叫没叫醒
+ */
+const WakePanel: React.FC<WakePanelProps> = ({ rules, onChange, error }) => {
+  const { t } = useTranslation();
+  const [history, setHistory] = useState<WakeRecord[]>([]);
+  const [wakingAll, setWakingAll] = useState(false);
+
+  const loadHistory = async () => {
+    try {
+      setHistory(await invoke<WakeRecord[]>('get_wake_history', { limit: 5 }));
+    } catch (err) {
+      console.error('读取唤醒流水账失败：', err);
+    }
+  };
+
+  useEffect(() => {
+    void loadHistory();
+    const unlisten = listen('wake-finished', () => void loadHistory());
+    return () => {
+      void unlisten.then((off) => off());
+    };
+  }, []);
+
+  const updateRule = (index: number, patch: Partial<WakeRule>) =>
+    onChange(rules.map((rule, i) => (i === index ? { ...rule, ...patch } : rule)));
+  const addRule = () => onChange([...rules, { name: '', mac: '', peer_ip: '', at: '22:00', enabled: true }]);
+  const removeRule = (index: number) => onChange(rules.filter((_, i) => i !== index));
+
+  const handleWakeNow = async (rule: WakeRule) => {
+    try {
+      await invoke('wake_peer', { rule });
+    } catch (err) {
+      alert(`${t('wake_failed')}: ${t(String(err))}`);
+    }
+  };
+
+  // 启用着的规则一起叫，等大家上线后报个总数
+  const handleWakeAll = async () => {
+    setWakingAll(true);
+    try {
+      const report = await invoke<BulkReport<WakeRecord>>('wake_all_peers', { rules });
+      alert(t('wake_all_done', { succeeded: report.succeeded, failed: report.failed }));
+    } catch (err) {
+      alert(`${t('wake_failed')}: ${t(String(err))}`);
+    } finally {
+      setWakingAll(false);
+    }
+  };
+
+  const describeRecord = (record: WakeRecord) => {
+    const name = record.name || record.peer_ip;
+    if (record.error) return t('wake_history_failed', { name });
+    if (record.onlineAfterSecs != null) return t('wake_history_online', { name, secs: record.onlineAfterSecs });
+    return t('wake_history_no_answer', { name });
+  };
+
+  return (
+    <div>
+      <div className="flex items-center justify-between mb-1.5">
+        <span className="text-sm font-medium text-gray-600">{t('wake_rules')}</span>
+        <div className="flex gap-2">
+          {rules.some((rule) => rule.enabled) && (
+            <button type="button" onClick={() => void handleWakeAll()} disabled={wakingAll} className="text-xs maid-button-secondary">
+              {wakingAll ? t('wake_all_running') : t('wake_all')}
+            </button>
+          )}
+          <button type="button" onClick={addRule} className="text-xs maid-button-secondary">
+            {t('wake_rule_add')}
+          </button>
+        </div>
+      </div>
+      {rules.map((rule, index) => (
+        <div key={index} className="flex items-center gap-2 mb-2">
+          <input
+            type="checkbox"
+            checked={rule.enabled}
+            onChange={e => updateRule(index, { enabled: e.target.checked })}
+            title={t('wake_rule_enabled')}
+          />
+          <input
+            type="text"
+            value={rule.name}
+            onChange={e => updateRule(index, { name: e.target.value })}
+            placeholder={t('wake_rule_name')}
+            className="w-20 text-sm maid-input"
+          />
+          <input
+            type="text"
+            value={rule.mac}
+            onChange={e => updateRule(index, { mac: e.target.value })}
+            placeholder="00:11:32:aa:bb:cc"
+            className="flex-1 font-mono text-sm maid-input"
+          />
+          <input
+            type="text"
+            value={rule.peer_ip}
+            onChange={e => updateRule(index, { peer_ip: e.target.value })}
+            placeholder="10.0.0.5"
+            className="w-24 font-mono text-sm maid-input"
+          />
+          <input
+            type="time"
+            value={rule.at || ''}
+            onChange={e => updateRule(index, { at: e.target.value || null })}
+            title={t('wake_rule_at')}
+            className="w-28 text-sm maid-input"
+          />
+          <button type="button" onClick={() => void handleWakeNow(rule)} className="text-xs maid-button-secondary">
+            {t('wake_now')}
+          </button>
+          <button type="button" onClick={() => removeRule(index)} className="text-xs maid-button-secondary">
+            －
+          </button>
+        </div>
+      ))}
+      {error && <p className="mt-1 text-xs text-red-600">{t(error)}</p>}
+      <p className="mt-1 text-xs text-gray-400">{t('wake_rules_desc')}</p>
+      {history.length > 0 && (
+        <ul className="mt-2 text-xs text-gray-500">
+          {history.map((record) => (
+            <li key={`${record.sentAt}-${record.mac}`}>
+              {new Date(record.sentAt * 1000).toLocaleString()} · {record.scheduled ? t('wake_history_scheduled') : t('wake_history_manual')} · {describeRecord(record)}
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+};
+
+export default WakePanel;
@@ -10,6 +10,10 @@ const resources = {
       "connecting": "正在铺设通道...",
       "connected": "扫清障碍，通道已建立完毕！",
       "disconnected": "恩兔在待命中",
+      "reconnecting": "摔了一跤，等着第 {{attempt}} 次重连...",
+      "degraded": "通道还开着，但好像不太通了，恩兔正盯着...",
+      "cancel_reconnect": "这次先不连了",
+      "cancel_reconnect_failed": "取消重连失败",
       "error": "呜呜，出错了",
       "settings": "服务准备",
       "logs": "工作日志",
@@ -26,10 +30,237 @@ const resources = {
       "dhcp": "自动的 (dhcp)",
       "static": "不动的 (static)",
       "advanced_settings": "高级的设置",
-      "extra_args": "奇妙指令",
+      "routes": "路由表",
+      "route_add": "＋ 加一条",
+      "route_netmask": "子网掩码（或前缀长度）",
+      "route_gateway": "网关（负责转发的同伴的虚拟地址）",
+      "routes_desc": "经由虚拟网里的同伴去往别的网段：左边写网段和子网掩码（也可以写前缀长度 24），右边写对面 edge 的虚拟地址。建立通道时交给 edge 的 -n",
+      "packet_forwarding": "当中转节点（网关）",
+      "server_mode": "网关模式（无人值守）",
+      "server_mode_desc": "给没人守着的站点网关用，一个开关配齐：恩兔启动就用这份方案建立通道、掉线总是重连（不限次数，最多等 30 秒）、当中转节点、大事写进系统日志、定期重新查总部地址。只在建立通道时叠加，不会改掉你自己的设置。再导出一份开机启动的服务配置就能让机器开机上岗（会用到刚保存的当前方案）",
+      "service_export": "导出开机启动服务…",
+      "service_export_dir_prompt": "把服务配置导出到哪个文件夹？",
+      "service_exported": "已导出。在终端里执行下面的命令装上（Windows 请以管理员身份运行）：\n{{install}}",
+      "service_export_failed": "导出服务配置失败",
+      "packet_forwarding_desc": "让这台机器替同伴转发流量：交给 edge 的 -r，连上后再打开系统的 IP 转发（Linux 打开 net.ipv4.ip_forward，会影响所有网卡；Windows 打开虚拟网卡的转发），断开后恢复原样。同伴那边要在路由表里把这台机器的虚拟地址填成网关",
+      "routes_applied": "已加上的路由",
+      "error_invalid_route": "路由要写成 IPv4 网段、子网掩码（或前缀长度）和网关，网段里不能带主机位",
+      "advanced_options": "奇妙指令",
+      "advanced_option_add": "＋ 加一条",
+      "advanced_option_enabled": "启用这条指令",
+      "advanced_option_value": "值（不需要就留空）",
       "edge_path": "工具箱路径",
       "tap_device": "设备名称",
       "mtu": "通道宽度",
+      "log_forward": "汇报范围",
+      "log_forward_all": "事无巨细都汇报",
+      "log_forward_warnings_and_errors": "只汇报警告和错误",
+      "log_forward_connection_events": "只汇报连接大事",
+      "theme": "工作台配色",
+      "profile": "工作方案",
+      "startup_config_error": "指示簿没读好",
+      "startup_config_migrated": "恩兔把旧版指示簿搬进了“Default”方案（原件备份为 config.legacy.toml）",
+      "startup_check_edge_found": "没找到 edge 工具箱：{{detail}}",
+      "startup_check_tap_driver": "还没有安装 TAP-Windows 网卡驱动",
+      "startup_check_passed": "✓ {{id}} 检查通过",
+      "safe_mode_title": "安全模式",
+      "safe_mode_desc": "恩兔接连 {{count}} 次没好好收工，这次先不自动建立通道，日志也放到了最详细。看看下面的体检单和日志，把指示改好后再手动连接吧～",
+      "safe_mode_leave": "没事了，退出安全模式",
+      "safe_mode_leave_failed": "退出安全模式失败",
+      "orphan_title": "发现没人照看的 edge",
+      "orphan_desc": "上次没来得及收拾、或者在别处启动的 edge 还在跑，可能占着网卡和管理口。接手后恩兔会照看它；也可以请它下班。",
+      "orphan_item": "PID {{pid}} · 小组 {{community}} · 总部 {{supernode}} · 管理口 {{port}}",
+      "orphan_unknown": "未知",
+      "orphan_adopt": "接手",
+      "orphan_terminate": "请它下班",
+      "orphan_failed": "处理没人照看的 edge 失败",
+      "startup_previous_session": "发现上次留下的 edge 还在管理口值班，可能需要先把它请走",
+      "startup_last_session_running": "恩兔重启前留下的 edge 还在工作（小组 {{community}}，PID {{pid}}），通道可能还通着，但恩兔现在管不到它；重新建立通道前请先结束这个进程",
+      "startup_last_session_ended": "上次工作（小组 {{community}}）没来得及收尾，最后一次确认是在 {{time}}，之后恩兔或 edge 意外停下了",
+      "startup_edge_version": "edge 版本：{{version}}",
+      "elevation_missing": "恩兔现在没有管理员权限，但网卡驱动需要它。请右键“以管理员身份运行”再建立通道～",
+      "elevation_caps_missing": "edge 还没有网卡权限（capabilities），建立通道时恩兔会弹窗申请授权",
+      "elevation_can_drop": "当前后端其实不需要管理员权限，可以用普通身份运行恩兔，更安全哦",
+      "elevation_backend_unknown": "恩兔没找到可用的网卡驱动（TAP-Windows 或 Wintun），通道可能建不起来",
+      "address_book_manual": "手动填写地址",
+      "template_pick": "从模板填写…",
+      "template_apply_confirm": "用模板“{{name}}”预填吗？总部、暗号、地址这些会被换掉（保存前还可以再改）\n{{desc}}",
+      "template_public_test": "公共测试总部",
+      "template_public_test_desc": "ntop 提供的公共测试总部，适合先试试能不能通；暗号请起一个别人猜不到的",
+      "template_lan_gaming": "自建总部 · 局域网联机",
+      "template_lan_gaming_desc": "填上自己搭的总部地址，和朋友们用同一个暗号，地址自动分配，断线总是重连",
+      "template_site_to_site": "两地局域网互联",
+      "template_site_to_site_desc": "固定地址 10.0.0.1，替对面的 192.168.2.0/24 加路由并开启转发；请按实际网段修改路由表",
+      "address_book_add": "收进地址簿",
+      "address_book_name_prompt": "给这个总部起个名字吧",
+      "address_book_measure": "测距离",
+      "address_book_measure_failed": "测量延迟失败",
+      "profile_new": "另存为新方案",
+      "profile_notes": "备注和标签",
+      "profile_notes_prompt": "给这份方案记点备注（负责人、用途、到期时间……）",
+      "profile_tags_prompt": "标签（用逗号分开）",
+      "profile_modified_at": "最后修改：{{time}}",
+      "profile_new_prompt": "给新方案起个名字吧（会复制当前的指示）",
+      "profile_delete": "删除当前方案",
+      "profile_delete_confirm": "真的要撕掉“{{name}}”这份方案吗？",
+      "profile_switch_failed": "切换方案失败",
+      "profile_delete_failed": "删除方案失败",
+      "profile_export": "导出方案到文件",
+      "profile_export_path_prompt": "导出到哪里？（以 .json 结尾导出为 JSON，否则为 TOML；文件里包含密语，请只发给信任的同伴）",
+      "profile_export_all_confirm": "要把全部方案一起导出吗？（取消则只导出当前方案）",
+      "profile_export_done": "已导出 {{count}} 份方案到 {{path}}",
+      "profile_export_failed": "导出方案失败",
+      "profile_import": "从文件导入方案",
+      "profile_import_path_prompt": "方案文件的位置（.toml 或 .json）：",
+      "profile_import_done": "已导入方案：{{names}}（导入的方案为只读，需要时可以解锁修改）",
+      "profile_import_failed": "导入方案失败",
+      "share_link": "生成邀请链接",
+      "share_link_desc": "把这行邀请链接发给同伴（或交给二维码工具生成二维码），对方在恩兔里粘贴就能用。链接里包含密语，请只发给信任的人",
+      "share_link_copy": "复制",
+      "share_link_copied": "邀请链接已复制",
+      "share_link_failed": "生成邀请链接失败",
+      "share_link_ip_conflict": "注意：邀请里的地址 {{ip}} 在花名册上已经分给了“{{name}}”，两台电脑同时在线会抢地址哦",
+      "share_link_join": "粘贴邀请链接",
+      "share_link_paste_prompt": "请粘贴同伴发来的邀请链接（n2n-maid://join?...）：",
+      "share_link_import_confirm": "要收下这份方案吗？\n名字：{{name}}\n总部：{{supernode}}\n暗号：{{community}}",
+      "share_link_invalid": "邀请链接无效",
+      "key_rotation": "换密语",
+      "key_rotation_time_prompt": "和小伙伴们约好什么时候换上新密语？（格式：YYYY-MM-DD HH:MM，到点之前照旧用旧密语）",
+      "key_rotation_time_invalid": "时间格式不对，要写成 YYYY-MM-DD HH:MM",
+      "key_rotation_failed": "换密语失败",
+      "key_rotation_desc": "新密语已经记下，{{time}} 自动换上。把下面的邀请链接发给小伙伴们，他们收下后也会在同一时间换",
+      "key_rotation_pending": "约好了 {{time}} 换新密语",
+      "key_rotation_cancel": "不换了",
+      "key_rotation_cancel_confirm": "不换“{{name}}”的密语了吗？已经发出去的邀请也要通知小伙伴作废哦",
+      "key_rotation_import_confirm": "“{{name}}”的管理员约好了换密语，要记下新密语并在 {{time}} 自动换上吗？",
+      "close": "关闭",
+      "profile_read_only_hint": "这是管理员下发的方案，总部、暗号和密语已锁定",
+      "profile_unlock": "解锁",
+      "profile_unlock_confirm": "解锁“{{name}}”后就可以随意修改了，改错了可能连不上哦，确定吗？",
+      "profile_unlock_failed": "解锁方案失败",
+      "theme_system": "跟随系统",
+      "theme_light": "明亮",
+      "theme_dark": "暗色",
+      "preferences": "恩兔的偏好",
+      "preferences_desc": "这些设置不属于任何方案，改了马上生效，和上面的工作指示分开保存",
+      "preferences_save_failed": "保存偏好失败",
+      "autostart": "登录系统后自动打开恩兔",
+      "notifications": "弹出提醒气泡（关掉后只弹警告和错误）",
+      "close_to_tray": "点关闭按钮时躲进托盘（从托盘选“退出”才真的收工）",
+      "sync_dir": "同步文件夹",
+      "sync_dir_placeholder": "例如 ~/Dropbox/n2n-maid（留空表示不同步）",
+      "sync_dir_encrypted": "指示簿加了密码，同步先停着：同步文件夹里只能放明文方案，要同步就先取消加密",
+      "sync_dir_desc": "选一个 Dropbox、Syncthing 之类会自动同步的文件夹，几台机器上的恩兔就共用一套方案；密语不会放进去",
+      "important_peers": "重要同伴",
+      "system_log": "写入系统日志",
+      "system_log_desc": "把建立通道、意外退出、出错这些大事另外抄一份到系统日志（Windows 事件查看器 → 应用程序，来源 n2n-maid；Linux 交给 journald，可用 journalctl -t n2n-maid 查看），方便管理员统一收集。重启恩兔后完整生效",
+      "desktop_vpn_hint": "在桌面网络面板里显示（仅 Linux）",
+      "desktop_vpn_hint_desc": "连上后请 NetworkManager 把虚拟网卡当作外部连接显示出来，GNOME / KDE 的网络面板就能看到 VPN 正在使用。NetworkManager 只负责显示，不会改动地址和路由；没有 NetworkManager 时不起作用",
+      "edge_hardening": "给 edge 上锁（仅 Linux）",
+      "edge_run_as": "建好网卡后切换到的用户（用户名或 uid:gid，留空不切换）",
+      "edge_hardening_desc": "启动 edge 时只带必要的环境变量、不让它继承恩兔打开的文件，以 root 运行时再加上 no_new_privs；填写用户后 edge 建好网卡就降权到这个用户继续工作。万一 edge 出了问题，能闯的祸也小一些",
+      "edge_sha256": "edge 指纹白名单（SHA-256）",
+      "edge_sha256_desc": "每次出门前恩兔都会算一遍 edge 的指纹。填了白名单（多个用逗号或空格隔开）就只放行名单里的 edge；留空则记住第一次见到的指纹，之后变了就拦下来问主人",
+      "edge_integrity_bypass": "指纹对不上也照常出门",
+      "edge_integrity_bypass_desc": "自己编译、经常换 edge 时可以打开：对不上只记日志，不再拦下。edge 带着网卡权限在跑，平时请别打开",
+      "control_api": "打开遥控口（给脚本用的本机 HTTP 接口）",
+      "control_api_port": "端口",
+      "control_api_socket": "改用 Unix socket（留空则监听 127.0.0.1）",
+      "control_api_no_tokens": "还没有发过令牌",
+      "control_api_token_name": "令牌名字（比如“备份脚本”）",
+      "control_api_scope_status": "只读",
+      "control_api_scope_control": "遥控",
+      "control_api_create": "发放令牌",
+      "control_api_rotate": "轮换",
+      "control_api_revoke": "吊销",
+      "control_api_rotate_confirm": "给“{{name}}”换一枚新令牌吗？旧令牌会立即失效",
+      "control_api_revoke_confirm": "吊销“{{name}}”的令牌吗？",
+      "control_api_issued": "“{{name}}”的令牌（只显示这一次，请马上复制）：",
+      "control_api_failed": "遥控口操作失败",
+      "control_api_desc": "脚本带上 Authorization: Bearer <令牌> 就能 GET /v1/status（只读令牌即可）、POST /v1/connect?profile=方案名 或 /v1/disconnect（需要遥控令牌）。开关、端口和 socket 重启恩兔后生效；令牌的发放、轮换和吊销立即生效",
+      "backup": "指示簿备份",
+      "backup_desc": "每次修改指示簿前，恩兔都会自动留一份带时间的备份（默认最多 10 份、保留 30 天），改坏了可以从这里找回",
+      "backup_none": "还没有备份",
+      "backup_restore": "恢复",
+      "backup_restore_confirm": "要把整本指示簿（全部方案和地址簿）恢复到 {{time}} 的样子吗？当前内容会先另存一份备份",
+      "backup_restore_failed": "恢复备份失败",
+      "reconnect_policy": "断线重连",
+      "reconnect_policy_never": "从不自动重连",
+      "reconnect_policy_on_failure": "出错退出时重连",
+      "reconnect_policy_always": "总是重连（除非主人断开）",
+      "reconnect_max_attempts": "最多连续重连次数（0 为不限）",
+      "reconnect_backoff_max_secs": "最长等待（秒）",
+      "stop_timeout_secs": "停止等待时间（秒）",
+      "status_snapshot_path": "状态快照文件",
+      "status_snapshot_interval_secs": "多久写一次（秒）",
+      "supernode_recheck_secs": "重新查总部地址（秒）",
+      "supernode_recheck_desc": "总部用的是动态域名时，连着的期间每隔这么久重新查一次，IP 换了就重新建立通道（0 表示不查）",
+      "supernode_failover": "备用总部",
+      "supernode_failover_desc": "总部一直摸不到时，按顺序换到下一家重新出门（host:port，用逗号或空格分隔）",
+      "maid_supernode_moved": "总部的地址换成了 {{address}}，恩兔重新建立一下通道",
+      "maid_vpn_conflict": "主人，{{vpn}} 的网卡 {{interface}} 占着 {{network}}，和小组的 {{ours}} 叠在一起了。连上后如果不通，请先关掉 {{vpn}} 或者给小组换个网段",
+      "status_snapshot_desc": "填上路径后，恩兔每隔几秒把状态写进这个文件，给不方便用遥控口的脚本或监控程序读；以 .prom 结尾时写成 Prometheus 文本格式（可交给 node_exporter 的 textfile collector），其余写成 JSON。留空不写",
+      "stop_timeout_desc": "断开、退出恩兔或换方案重连时，先请 edge 自己收拾，过了这么久还没走就强制清理。慢的电脑可以调长一点",
+      "tap_reset_on_teardown": "edge 走后重置残留的虚拟网卡",
+      "tap_restart_on_loss": "虚拟网卡坏了就重新建立通道",
+      "tap_restart_on_loss_desc": "连着的时候恩兔每隔几秒看一眼虚拟网卡，睡眠唤醒、驱动抽风把它弄没、停用或者把地址冲掉时，edge 自己发觉不了。默认只提醒主人，勾上后恩兔直接重新建立通道",
+      "tap_reset_on_teardown_desc": "断开后恩兔会看一眼虚拟网卡，地址或路由还挂着（有些 Windows TAP 会这样）就提醒主人；勾上后恩兔直接重置网卡，免得下次建立通道撞地址。重置可能需要管理员权限",
+      "command_line_export": "复制等价的 edge 命令行",
+      "command_line_include_secrets": "命令行里要带上真实的密语和口令吗？（取消则打码）",
+      "command_line_copied": "已复制到剪贴板：\n{{commandLine}}",
+      "command_line_failed": "拼命令行失败",
+      "reconnect_policy_desc": "edge 意外退出后恩兔要不要自己再去打扫。每次失败等待时间翻倍（从 2 秒起），连上后重新计数；按流量计费的笔记本建议从不，无人值守的网关建议总是",
+      "important_peers_desc": "连上后恩兔会先和这些同伴打个招呼，提前备好邻居缓存，第一次 SSH/远程桌面就不用干等啦（留空则跳过）",
+      "port_forwards": "传话筒",
+      "wake_rules": "唤醒同伴",
+      "wake_rule_add": "＋ 加一条",
+      "wake_rule_enabled": "按时叫醒",
+      "wake_rule_name": "备注",
+      "wake_rule_at": "每天几点叫（留空只能手动叫）",
+      "wake_now": "现在叫",
+      "side_connections": "同时连着的其它方案",
+      "log_source_main": "主通道",
+      "switch_supernode": "换总部",
+      "switch_supernode_prompt": "换到哪个总部？（host:port，恩兔会尽量保住现在的地址）",
+      "switch_supernode_failed": "换总部失败",
+      "wake_all": "全部叫醒",
+      "wake_all_running": "正在叫大家…",
+      "wake_all_done": "叫完啦：{{succeeded}} 位上线，{{failed}} 位没叫醒",
+      "wake_failed": "没能叫醒",
+      "wake_rules_desc": "用网络唤醒（Wake-on-LAN）叫醒同伴：填上它网卡的 MAC、虚拟地址和时间，恩兔到点发魔术包，再等它最多 3 分钟，确认上线后记下结果。恩兔没开着时错过的时间不会补叫",
+      "wake_history_scheduled": "按时",
+      "wake_history_manual": "手动",
+      "wake_history_online": "{{name}} {{secs}} 秒后上线",
+      "wake_history_no_answer": "{{name}} 没有上线",
+      "wake_history_failed": "{{name}} 魔术包没发出去",
+      "error_invalid_wake_rule": "唤醒规则要写明同伴的 MAC 和虚拟地址，时间写成 22:00 这样（留空只能手动叫）",
+      "port_forwards_desc": "通道连上后转发 TCP 端口：左边是本机监听地址，右边是转发目标。访问同伴写 127.0.0.1:8080 → 10.0.0.5:80；把自己的服务开放给同伴写 自己的虚拟地址:8080 → 127.0.0.1:80",
+      "port_forward_add": "添加规则",
+      "port_forward_name": "备注",
+      "port_forward_enabled": "启用这条规则",
+      "port_forward_stats": "（{{active}} 个连接中，共 {{total}} 个）",
+      "error_invalid_port_forward": "传话筒规则的监听地址和目标都要写成 host:port",
+      "port_knock": "敲门",
+      "port_knock_add": "添加一步",
+      "port_knock_port": "总部的端口",
+      "port_knock_payload": "UDP 内容（hex: 开头写十六进制，可留空）",
+      "port_knock_delay": "敲完等多久（毫秒）",
+      "port_knock_desc": "总部藏在端口敲门或单包授权后面时用：每次建立通道（包括自动重连）前，恩兔按顺序敲总部的这些端口，每一下之后等一会儿再敲下一下。UDP 会发出填写的内容，TCP 只发起一次连接。敲不成只记在工作汇报里，不会拦着连接",
+      "error_connection_tap_clash": "另一条通道已经在用同一块虚拟网卡了，请给这份方案换一个网卡名（设备名称）",
+      "error_connection_mgmt_port_clash": "另一条通道的 edge 已经占着同一个管理口了，请给这份方案换一个管理口",
+      "error_connection_local_port_clash": "另一条通道的 edge 已经占着同一个本机端口了，请换一个或留空",
+      "error_orphan_not_found": "这只 edge 已经不在了（可能自己下班了）",
+      "error_orphan_mismatch": "这只 edge 的小组或总部和当前方案对不上，不能接手，只能请它下班",
+      "error_orphan_terminate_failed": "这只 edge 不肯下班（可能需要管理员权限）",
+      "error_invalid_port_knock": "敲门步骤要写端口（1 到 65535），等待不超过 10000 毫秒；UDP 内容用 hex: 开头时要是成对的十六进制，TCP 敲门不带内容",
+      "error_edge_option_managed": "高级选项里有基本设置已经管着的开关（-c、-l、-k、-a 这些），请在基本设置里改",
+      "error_edge_option_unknown": "高级选项里有 edge 不认识的开关，检查一下拼写吧",
+      "error_edge_option_needs_value": "高级选项里有开关需要填一个值",
+      "error_edge_option_no_value": "高级选项里有开关不需要值，请把值清空",
+      "error_roster_invalid_subnet": "花名册的网段要写成 CIDR，例如 10.0.0.0/24",
+      "error_roster_invalid_ip": "花名册里有成员的地址不是有效的 IPv4 地址",
+      "error_roster_ip_outside_subnet": "花名册里有成员的地址不在小组网段内",
+      "error_roster_duplicate_ip": "花名册里有两位成员分到了同一个地址",
       
       // 状态信息
       "virtual_ip": "虚拟 IP",
@@ -38,6 +269,8 @@ const resources = {
       // 提示信息 - 女仆口吻
       "save_success": "主人的指示已记下！",
       "save_failed": "哎呀，记录失败了...",
+      "save_reconnect_confirm": "通道正开着，要让恩兔按新的指示马上重新出门吗？",
+      "reconnect_failed": "重新出门失败",
       "connect_success": "走廊已经扫干净啦，主人可以随时通行！",
       "connect_failed": "抱歉主人，通道出故障了",
       "disconnect_success": "恩兔去休息咯~",
@@ -48,8 +281,30 @@ const resources = {
       "hide_logs": "收起日志",
       "clear_logs": "清空记录",
       "clear": "清空",
+      "export": "导出",
+      "export_logs": "导出工作汇报",
+      "export_logs_anonymize_confirm": "要先给汇报打码吗？（公网地址、MAC、暗号、密语会换成占位符，适合发给别人排错）\n选“取消”则原样导出。",
+      "export_logs_done": "工作汇报已保存到：{{path}}",
+      "about": "关于恩兔",
+      "about_show": "查看",
+      "about_loading": "正在填写身份卡…",
+      "about_version": "版本",
+      "about_runtime": "运行环境",
+      "about_edge": "工具箱",
+      "about_edge_features": "工具箱本事",
+      "about_edge_no_foreground": "不认得 -f",
+      "about_system": "系统",
+      "about_webview": "网页引擎",
+      "about_unknown": "不知道",
+      "about_copy": "复制给维护者",
+      "about_copied": "身份卡已复制，报告问题时贴上就好",
+      "about_failed": "读取身份卡失败",
+      "about_desc": "报告问题时附上这些，维护者就不用再追问版本和系统了（导出的工作汇报开头也会带上）",
+      "export_logs_failed": "导出工作汇报失败",
       "no_logs": "还没有工作记录呢",
       "log_lines": "条记录",
+      "log_dropped": "已丢弃 {{count}} 条",
+      "log_dropped_desc": "没人查看时汇报本最多只留最近 {{capacity}} 条，更早的汇报已经丢弃",
       
       // 网卡信息
       "network_info": "通道详情",
@@ -57,6 +312,14 @@ const resources = {
       "ip": "IP 地址",
       "mask": "子网面具",
       "mac": "MAC 地址",
+      "transport_cipher": "加密方式",
+      "transport_compression": "压缩方式",
+      "transport_header_encryption": "包头加密",
+      "transport_on": "已开启",
+      "transport_off": "未开启",
+      "tap_adapter": "网卡",
+      "tap_adapter_detail": "{{name}}（接口编号 {{index}}）",
+      "node_name": "工号",
 
       // 同伴节点
       "peer_list": "同伴列表",
@@ -65,21 +328,227 @@ const resources = {
       "peer_vpn_ip": "VPN 地址",
       "peer_mode": "模式",
       "peer_public_addr": "公网地址",
+      "peer_mac": "MAC",
+      "peer_ping_all": "全部 ping",
+      "peer_ping_all_failed": "ping 同伴失败",
+      "peer_resolve_names": "查名字",
+      "peer_resolve_failed": "查同伴名字失败",
+      "peer_bulk_done": "办好啦：{{succeeded}} 位成功，{{failed}} 位失败，用了 {{ms}} ms",
+      "peer_export": "导出",
+      "peer_export_path_prompt": "导出到哪个文件？（.csv 为表格，其余为 JSON）",
+      "peer_export_done": "已导出 {{count}} 位同伴到 {{path}}",
+      "peer_export_failed": "导出同伴失败",
+      "peer_link_direct": "直连",
+      "peer_link_relayed": "总部中转",
       "peer_latency": "延迟",
       "peer_last_seen": "再见",
       "latency_unknown": "—",
       
       // 参数说明 - 简化且亲切
       "supernode_desc": "总部服务器的地址，告诉恩兔去哪里领任务呀（格式：vpn.example.com:7777）",
+      "supernode_dns_placeholder": "或者只填总部域名（如 example.com）",
+      "supernode_dns_desc": "填了域名的话，恩兔每次建立通道前查 _n2n._udp.<域名> 的 SRV 记录找总部，没填暗号时也用 _n2n.<域名> 的 TXT 记录（community=...）",
       "community_desc": "工作团队的暗号，只有知道暗号的伙伴才能进入同一个通道哦",
-      "username_desc": "可选。给设备起个昵称，方便主人辨认；留空就用电脑的名字啦",
+      "username_desc": "可选。给设备起个昵称，方便主人辨认；留空就用电脑的名字啦（会去掉 edge 不认识的字符，最长 15 个字节）",
       "encryption_key_desc": "保密用的密语（可选），设置后通道会更安全。留空就不加密，但不太推荐呢",
+      "cipher": "加密方式",
+      "cipher_default": "交给 edge 决定（推荐）",
+      "cipher_null": "不加密（-A1）",
+      "cipher_twofish": "Twofish（-A2）",
+      "cipher_aes": "AES（-A3）",
+      "cipher_chacha20": "ChaCha20（-A4）",
+      "cipher_speck": "Speck（-A5）",
+      "header_encryption": "包头加密（-H，连小组暗号也藏起来）",
+      "cipher_desc": "没有 AES 硬件加速的小设备选 ChaCha20 或 Speck 会更快；包头加密需要总部用固定的小组清单。这只 edge 不会的选项会标出来",
+      "edge_unsupported": "这只 edge 不会",
+      "compression": "数据压缩",
+      "compression_none": "不压缩",
+      "compression_lzo": "LZO（-z1，省 CPU）",
+      "compression_zstd": "zstd（-z2，压得更小）",
+      "compression_desc": "上行带宽很慢时打开，用一点 CPU 换更少的流量；已经压缩过的内容（视频、压缩包）压不了多少",
       "ip_mode_desc": "选择恩兔怎么获取地址：自动分配就交给总部，手动指定就由主人决定",
       "static_ip_desc": "手动指定的地址，格式像这样：192.168.125.xxx",
-      "extra_args_desc": "给恩兔的魔法掸子加点特技，比如 -v（多话模式）、-r（帮忙转发）、-E（接收广播）",
-      "edge_path_desc": "恩兔的工具箱放在哪里，留空就用默认位置（bin/edge）",
+      "auto_connect": "打开恩兔就开始工作",
+      "auto_connect_desc": "恩兔启动（比如开机自启）后自动用这份方案建立通道，不用再点“开始工作”",
+      "maid_connected": "通道打扫好啦！主人可以出发了～",
+      "maid_connected_ip": "通道打扫好啦！恩兔领到的地址是 {{ip}}",
+      "maid_network_switched": "主人换到了网络“{{network}}”，恩兔换用了方案“{{profile}}”",
+      "maid_key_rotated": "到了约好的时间，方案“{{profile}}”换上了新密语",
+      "maid_config_reloaded": "主人在外面改了指示簿，恩兔已经重新读过了",
+      "maid_config_reload_failed": "主人在外面改了指示簿，可是恩兔看不懂：{{error}}。改好之前保存设置会失败哦",
+      "maid_sync_pulled": "从同步文件夹拿到了别的机器上的方案：{{profiles}}（密语不跟着同步，新方案要再填一遍哦）",
+      "maid_sync_conflict": "这几份方案在两台机器上都改过了，恩兔留着这边的，把对面的另存成了：{{profiles}}，主人挑一份吧",
+      "maid_sync_failed": "和同步文件夹对账失败了：{{error}}",
+      "maid_edge_exited": "edge 摔倒了：{{reason}}",
+      "exit_reason": "摔倒的原因：{{reason}}",
+      "exit_bad_args": "edge 不认识交给它的参数（检查高级选项，或者 edge 版本太老）",
+      "exit_tap_failed": "虚拟网卡打不开（可能被占用，或者驱动没装好）",
+      "exit_permission_denied": "权限不够",
+      "exit_auth_failed": "认证没通过（检查密语、用户名和口令）",
+      "exit_killed": "被系统或别的程序叫停了（比如内存不够）",
+      "exit_crashed": "edge 自己出了故障（程序崩溃）",
+      "exit_clean": "edge 自己收工了（没有说明原因）",
+      "exit_failed": "edge 报错退出，没有留下线索，请看工作汇报",
+      "exit_unknown": "不知道（没能拿到退出状态）",
+      "maid_reconnecting": "恩兔摔了一跤（{{reason}}），{{delay}} 秒后再去打扫（第 {{attempt}} 次重连）",
+      "maid_reconnect_gave_up": "恩兔重连了 {{attempts}} 次都没成功，先停下来等主人指示",
+      "maid_reconnect_cancelled": "主人叫停了，恩兔不再重连啦",
+      "maid_reconnect_suspended": "好的，接下来 {{minutes}} 分钟恩兔不自动重连，也先不做自我巡查",
+      "maid_reconnect_unsuspended": "自动重连恢复啦，断线了恩兔会照方案重新打扫",
+      "reconnect_suspend": "总部要维护？先别自动重连",
+      "reconnect_suspend_prompt": "先别自动重连几个小时？（不会改动方案）",
+      "reconnect_suspended_until": "自动重连已暂停到 {{time}}",
+      "reconnect_suspend_cancel": "现在恢复",
+      "maid_paused": "恩兔先歇一会儿～工作便签已经记好，主人随时可以叫恩兔继续",
+      "maid_resumed": "恩兔回来啦，按便签继续打扫通道～",
+      "maid_safe_mode": "恩兔接连 {{count}} 次没好好收工，这次进了安全模式：先不自动建立通道哦",
+      "maid_orphan_edges": "机器上有 {{count}} 只没人照看的 edge 在跑，主人看看是接手还是请它下班～",
+      "maid_edge_adopted": "恩兔接手了已经在跑的 edge（PID {{pid}}），之后由恩兔照看它",
+      "maid_supernode_switching": "恩兔收一收工具，这就换去总部 {{address}}～",
+      "maid_supernode_switched": "已经朝新总部 {{address}} 出发啦，原来的地址尽量给主人留着",
+      "maid_supernode_failover": "总部 {{previous}} 一直摸不到，恩兔换备用总部 {{address}} 试试～",
+      "maid_supernode_failover_ok": "备用总部 {{address}} 接待成功，通道连上啦",
+      "maid_supernode_failover_exhausted": "备用总部都试过了，还是一家都摸不到……主人看看网络或者总部那边吧",
+      "maid_exit_waiting": "主人要关门啦，恩兔先把 edge 温柔地收拾好（正在等待退出）…",
+      "maid_restarting": "恩兔按新的指示重新出门，先把旧的通道收拾好～",
+      "maid_registration_lost": "edge 还在，可已经 {{secs}} 秒没在总部报上到了，通道可能已经不通，恩兔继续盯着",
+      "maid_registration_restored": "重新在总部报上到啦，通道恢复正常～",
+      "maid_tap_broken": "虚拟网卡“{{adapter}}”出毛病了（不见了、被停用或者地址掉了），edge 自己还没发觉",
+      "maid_tap_restored": "虚拟网卡“{{adapter}}”恢复正常啦～",
+      "maid_graceful_stop_failed": "优雅断开失败：{{error}}（将尝试强制停止）",
+      "maid_force_stop_timeout": "等得有点久了…恩兔要改用强制停止来清理残影进程了",
+      "maid_stop_in_progress": "恩兔已经在请 edge 停下了，不用再点啦（授权框弹出来的话，确认一次就好）",
+      "maid_stop_cooling_down": "恩兔刚请 edge 停下，先看看它走不走，{{secs}} 秒后再试吧",
+      "maid_tap_leftover": "edge 走了，可虚拟网卡“{{adapter}}”上还挂着 {{leftover}}，下次建立通道可能会撞地址（可以在高级设置里让恩兔自动重置网卡）",
+      "maid_tap_reset": "虚拟网卡“{{adapter}}”上还有残留，恩兔已经把它重置好啦",
+      "maid_mtu_applied": "通道宽度已经改成 {{mtu}} 啦，通道没有断开",
+      "maid_wake_sent": "恩兔去叫醒“{{name}}”啦，等它上线……",
+      "maid_wake_online": "“{{name}}”醒过来了（{{secs}} 秒后上线）",
+      "maid_wake_no_answer": "“{{name}}”等了 {{minutes}} 分钟还没上线，可能没叫醒，或者它的 edge 还没连上",
+      "maid_wake_failed": "叫醒“{{name}}”的魔术包没发出去：{{error}}",
+      "maid_tap_reset_failed": "恩兔想重置虚拟网卡“{{adapter}}”，但是失败了：{{error}}",
+      "maid_state_child_gone": "恩兔巡查时发现 edge 早就停工了却没人接手，已经把状态收拾好啦",
+      "maid_state_no_child": "恩兔巡查时发现状态说在工作，其实手里没有 edge，已经改回待命啦",
+      "maid_state_stuck_disconnecting": "收拾工具超过 60 秒还没收拾完，恩兔已经强制清理好了",
+      "maid_ready": "开门体检都通过啦，恩兔随时可以开工～",
+      "maid_not_ready": "开门体检有 {{count}} 项没通过，主人看一眼再开工吧",
+      "config_locked_title": "指示簿上了锁",
+      "config_locked_desc": "主人的指示簿是加密保存的，输入密码后恩兔才能读到方案",
+      "config_password": "密码",
+      "config_password_new": "新密码",
+      "config_password_confirm": "再输入一次",
+      "config_password_mismatch": "两次输入的密码不一样",
+      "config_unlock": "解锁",
+      "config_encryption": "加密指示簿",
+      "config_encryption_on": "已加密",
+      "config_encryption_off": "未加密",
+      "config_encryption_desc": "用密码加密整本指示簿，合用电脑的其他用户就看不到暗号和总部地址了；备份柜里的旧备份也会一起加密。每次启动恩兔都要输入密码。忘了密码就只能重新填写指示簿哦",
+      "network_rules": "认路（按网络换方案）",
+      "network_rules_desc": "连着某个 Wi-Fi 或某块网卡时，恩兔自动换用对应的方案；通道开着的话会用新方案重新建立。从上往下第一条对上的规则生效",
+      "network_rule_ssid": "Wi-Fi 名称",
+      "network_rule_interface": "网卡名",
+      "network_rule_add": "添加规则",
+      "network_rule_use_current": "用现在的网络",
+      "network_rules_save": "保存对照表",
+      "network_rules_saved": "对照表已保存",
+      "network_rules_failed": "保存对照表失败",
+      "network_current": "现在：{{network}}",
+      "network_none": "没看到连着的网络",
+      "roster": "花名册",
+      "roster_desc": "管小组时记下每位成员分到的固定地址，发邀请时恩兔会帮主人检查有没有撞地址。可以导出成文件交给其他管理员",
+      "roster_subnet": "小组网段（CIDR）",
+      "roster_member_name": "成员名字",
+      "roster_member_ip": "地址",
+      "roster_member_notes": "备注",
+      "roster_add": "添加成员",
+      "roster_suggest": "下一个空地址",
+      "roster_full": "网段里已经没有空地址了",
+      "roster_save": "保存花名册",
+      "roster_saved": "花名册已保存",
+      "roster_import": "导入花名册",
+      "roster_export": "导出花名册",
+      "roster_import_path_prompt": "花名册文件的路径（.toml 或 .json）",
+      "roster_export_path_prompt": "把花名册保存到哪里？（.json 结尾为 JSON，其余为 TOML）",
+      "roster_imported": "已导入小组“{{community}}”的花名册（{{count}} 位成员）",
+      "roster_exported": "花名册已导出到 {{path}}",
+      "roster_failed": "花名册操作失败",
+      "roster_need_community": "先填写小组暗号，才知道是哪个小组的花名册哦",
+      "config_encrypt": "加密",
+      "config_password_change": "更换密码",
+      "config_decrypt": "取消加密",
+      "config_decrypt_hint": "在第一个输入框里填当前密码",
+      "config_decrypt_confirm": "取消后指示簿和备份柜里的备份都会改回明文保存，确定吗？",
+      "config_lock": "立即锁上",
+      "config_encrypted_done": "指示簿已经加密保存，备份柜里的旧备份也一起用新密码封好了（打不开的旧备份已经清掉）",
+      "config_encryption_failed": "加密设置失败",
+      "config_overridden_hint": "这些字段由 N2N_MAID_* 环境变量或启动参数指定，在这里修改不会写进指示簿：{{fields}}",
+      "error_config_locked": "指示簿上了锁，请主人先输入密码打开",
+      "error_config_wrong_password": "密码不对（或者指示簿被改动过），恩兔打不开",
+      "error_config_password_empty": "密码不能是空的哦",
+      "advanced_options_desc": "给恩兔的魔法掸子加点特技，一行一个开关，比如 -v（多话模式）、-r（帮忙转发）、-E（接收广播）；值里带空格也没关系",
+      "nat_check": "NAT 侦察",
+      "nat_check_run": "侦察一下",
+      "nat_checking": "侦察中…",
+      "nat_check_failed": "NAT 侦察失败",
+      "nat_local_ip": "本机",
+      "nat_router_wan_ip": "路由器外网口",
+      "nat_external_ip": "出口",
+      "nat_verdict_public": "这台电脑直接就是公网地址，同伴可以直接连过来",
+      "nat_verdict_nat": "只隔着自家路由器一层 NAT，打洞通常没问题",
+      "nat_verdict_double_nat": "路由器外面还套着一层路由器（两层 NAT），同伴之间基本打不通洞，建议打开中转模式，让数据经总部转交",
+      "nat_verdict_cgnat": "运营商给的是共享地址（运营商级 NAT，路由器外网口和真正的出口对不上或落在 100.64.0.0/10），同伴之间打不通洞，建议打开中转模式，让数据经总部转交",
+      "nat_verdict_unknown": "问不到出口地址（STUN 服务器连不上），没法判断",
+      "nat_enable_relay": "打开中转模式（-S1）",
+      "nat_check_desc": "用 STUN 问出口地址，再用 NAT-PMP / UPnP 问路由器的外网口地址，看看隔了几层 NAT。结果也会记进工作日志",
+      "edge_path_desc": "恩兔的工具箱放在哪里，留空就用默认位置（bin/edge）；不知道装在哪里的话点“找找看”",
+      "edge_discover": "🔍 找找看",
+      "edge_discover_failed": "找工具箱失败了",
+      "edge_discover_none": "恩兔翻遍了 PATH、资源目录和常见的安装位置，都没找到 edge 呢",
+      "edge_source_path": "PATH",
+      "edge_source_bundled": "恩兔自带",
+      "edge_source_common": "常见位置",
+      "edge_version_unknown": "版本不明",
       "tap_device_desc": "虚拟网卡的名字，留空恩兔会自动取名（比如 edge0）",
       "mtu_desc": "通道的宽度，默认 1290。太小会影响速度，太大可能卡住",
+      "mtu_apply_live": "立即生效",
+      "local_port": "本机端口",
+      "local_port_placeholder": "留空由 edge 随便挑",
+      "local_port_desc": "固定 edge 和总部、同伴通信用的本机 UDP 端口（交给 edge 的 -p），防火墙只放行固定端口时使用。建立通道前恩兔会先检查端口有没有被占用",
+      "error_local_port_in_use": "固定的本机端口已经被别的程序占用了，换一个端口或者先关掉占用它的程序吧",
+      "error_invalid_local_port": "本机端口要在 1 到 65535 之间，而且不能和 edge 的管理口（默认 5644）相同",
+      "register_interval_secs": "报到间隔",
+      "register_interval_placeholder": "留空用 edge 默认的 20 秒",
+      "register_interval_desc": "edge 多久向总部和同伴报到一次，顺便让 NAT 记住打好的洞（交给 edge 的 -i，秒）。NAT 很快就忘掉映射、同伴时不时失联时调短一点（比如 10），流量按包计费时可以调长",
+      "register_interval_value": "每 {{secs}} 秒",
+      "edge_stats_registration": "总部报到",
+      "edge_stats_registered": "已报到",
+      "edge_stats_not_registered": "最近没和总部通上话",
+      "edge_stats_uptime": "edge 在岗",
+      "edge_stats_packets": "收发包数",
+      "edge_stats_bytes": "收发流量",
+      "error_invalid_register_interval": "报到间隔要在 1 到 3600 秒之间（留空用 edge 默认的 20 秒）",
+      "mac_address": "硬件编号（MAC）",
+      "mac_address_placeholder": "留空由 edge 随机挑",
+      "mac_address_randomize": "随机换一个",
+      "management_port": "管理口",
+      "management_port_placeholder": "留空用默认的 5644",
+      "management_password": "管理口令",
+      "management_password_placeholder": "留空用 edge 的默认口令",
+      "management_desc": "edge 的管理口（交给 edge 的 -t）和门禁口令（--management-password）。恩兔通过管理口查看心跳、同伴和流量；同一台机器上要开第二只 edge 时把端口错开",
+      "error_invalid_management_port": "管理口要在 1 到 65535 之间",
+      "mac_address_desc": "固定虚拟网卡的 MAC，交给 edge 的 -m。克隆出来的虚拟机提示硬件编号已被占用时，点“随机换一个”再保存即可",
+      "error_invalid_mac": "硬件编号（MAC）要写成 02:ab:cd:ef:12:34 这样，而且不能是组播地址或全 0",
+      "error_cipher_needs_key": "选了加密方式就要填保密密语（只有“不加密”可以不填）",
+      "error_key_rotation_time_passed": "换密语的时间要约在以后",
+      "error_sync_config_encrypted": "加密指示簿和同步文件夹只能二选一：同步文件夹里只能放明文方案。先关掉其中一个吧",
+      "error_sync_dir_missing": "找不到这个同步文件夹，先确认它已经建好（网盘客户端已经同步下来）吧",
+      "error_cipher_unsupported": "这个版本的 edge 不会这种加密方式，换一种或者换个新一点的 edge 吧",
+      "error_header_encryption_unsupported": "这个版本的 edge 不会包头加密（-H 要 2.8 以上）",
+      "error_compression_unsupported": "这只 edge 不会这种压缩方式（zstd 要 edge 编译时带上才有），换成 LZO 吧",
+      "mtu_apply_live_hint": "通道连着时直接修改虚拟网卡，不用断开重连（同时记进当前方案）",
+      "mtu_apply_failed": "通道宽度没改成",
+      "error_mtu_live_not_connected": "通道连上之后才能直接修改通道宽度；没连上时保存指示就好",
+      "log_forward_desc": "开了 -v -v -v 这种多话模式时，可以让恩兔只挑重点汇报，界面会更轻快（下次建立通道时生效）",
       
       // 错误提示 - 安慰式
       "error_mac_in_use": "这个硬件编号已经有人用了，可能是其他设备还在工作，或者总部还没注销旧记录",
@@ -87,11 +556,57 @@ const resources = {
       "error_mac_or_ip_in_use": "总部说这台设备的硬件编号/地址还没释放呢（可能上次断开太匆忙）。主人可以先等一小会儿让总部清理，或者点“断开”让恩兔发出 SIGINT 再重新连接。",
       "error_tap_create_failed": "恩兔没权限创建设备，需要主人帮忙授权呢",
       "error_tap_busy": "虚拟网卡被占用了（Device or resource busy）。可能已经有别的 edge 在工作，或者 TAP 名称冲突；请先断开其它连接/重启相关服务，或在设置里换个 TAP 设备名再试。",
+      "error_no_network": "恩兔找不到任何可以出门的路（没有网络，可能开了飞行模式或者网线松了），等网络恢复再试吧",
+      "error_clock_skew": "这台电脑的时间和标准时间差得太多了，开了包头加密（-H）时总部会拒收；请先同步一下系统时间再连接",
+      "error_supernode_key_changed": "总部的公钥和上次记下的不一样！可能有人在冒充总部，恩兔已经拒绝出门。请先向管理员确认新公钥是否可信",
+      "error_invalid_supernode": "总部地址要写成 host:port 的样子（比如 vpn.example.com:7777，IPv6 写成 [::1]:7777）",
+      "error_invalid_supernode_dns": "总部域名只填域名就好（比如 example.com，不带端口），恩兔会去查它的 SRV 记录",
+      "error_community_empty": "还没有填写小组暗号哦",
+      "error_community_too_long": "小组暗号最多 19 个字节（中文一个字占 3 个字节），再长 edge 会把它截短",
+      "error_invalid_static_ip": "指定地址要写成 10.0.0.2 或 10.0.0.2/24 的样子",
+      "error_invalid_ip_mode": "地址分配方式只能是自动分配或手动指定",
+      "error_invalid_mtu": "通道宽度（MTU）要在 576 到 1500 之间",
+      "error_invalid_stop_timeout": "停止等待时间要在 1 到 300 秒之间",
+      "error_invalid_snapshot_interval": "状态快照间隔要在 5 到 3600 秒之间",
+      "error_invalid_supernode_recheck": "重新查总部地址的间隔要在 30 到 86400 秒之间（填 0 表示不查）",
+      "error_invalid_supernode_failover": "备用总部的每一条都要写成 host:port",
+      "error_edge_path_missing": "在这个位置找不到工具箱（edge 可执行文件），检查一下路径吧",
+      "error_edge_binary_changed": "工具箱（edge）和上次记下的不一样！可能被人掉包了，恩兔已经拒绝出门。如果是主人自己升级的，确认指纹后再信任它",
+      "error_edge_not_allowlisted": "工具箱（edge）的指纹不在白名单里，恩兔已经拒绝出门。请核对 edge 的来源，或者把它的指纹加进白名单",
+      "error_edge_integrity_unverifiable": "没能核对工具箱（edge）的指纹（读不了 edge 或者指纹本），恩兔宁可先不出门。检查一下文件权限，或者在设置里打开“指纹对不上也照常出门”",
+      "error_invalid_edge_sha256": "edge 指纹白名单里每一条都要是 64 位十六进制的 SHA-256",
+      "supernode_key_changed_title": "⚠️ 总部公钥变了",
+      "supernode_key_changed_detail": "记下的公钥：{{pinned}}\n这次的公钥：{{presented}}",
+      "supernode_key_trust": "我确认新公钥可信",
+      "supernode_key_trust_confirm": "只有在管理员确认总部确实换了公钥时才应该信任它。确定要信任新公钥吗？",
+      "supernode_key_trust_failed": "信任新公钥失败",
+      "edge_binary_rejected_title": "⚠️ edge 的指纹对不上",
+      "edge_binary_rejected_detail": "位置：{{path}}\n这次的指纹：{{actual}}",
+      "edge_binary_previous": "记下的指纹：{{previous}}",
+      "edge_binary_trust": "是我自己换的，信任它",
+      "edge_binary_trust_confirm": "只有在确认 edge 是你自己升级或替换的时候才应该信任它。确定要记下新的指纹吗？",
+      "edge_binary_trust_failed": "信任新的 edge 失败",
       "error_supernode_unreachable": "联系不上总部了，是不是地址写错了，或者网络断了？",
+      "error_tap_missing": "虚拟网卡不见了（可能是睡眠唤醒或者驱动出了问题）",
+      "error_tap_down": "虚拟网卡被停用了",
+      "error_tap_address_lost": "虚拟网卡上的地址掉了",
       "error_auth_failed": "门被锁住了，检查一下暗号和密语对不对吧",
       "error_permission_denied": "权限不够呀，Linux 系统需要给工具箱特殊权限才行",
       "error_edge_exited": "呜呜…恩兔的工具箱突然停工了（edge 意外退出）。请打开“工作汇报”看看最后一条报错线索。",
       "error_wsagetlasterror": "Windows 环境下奇怪的错误发生了，但是似乎不影响使用哦",
+      "error_version_mismatch": "恩兔和总部说的不是同一种“方言”（edge 与 supernode 的 n2n 大版本不一致，比如 v2 对 v3）。请让 edge 和 supernode 使用相同的大版本（可以用 edge -h 查看版本），或者换一个匹配的总部地址～",
+
+      // 非致命提醒 - 小声提醒
+      "warning_community_truncated": "小组暗号太长啦，edge 把它截短了；请确认伙伴们用的是同一个暗号哦",
+      "warning_unknown_option": "edge 不认识某个奇妙指令，已经跳过了；可以检查一下高级设置里的参数",
+      "warning_mtu": "通道宽度（MTU）好像不太合适，edge 给出了提醒",
+      "session_summary": "上次工作 {{duration}}，收发 {{tx}}/{{rx}} 个包，重连 {{reconnects}} 次",
+      "session_reason_user_stop": "主人让恩兔收工的",
+      "session_reason_force_stop": "强制停止的",
+      "session_reason_restart": "按新的指示重新出门了",
+      "session_reason_edge_exited": "edge 意外摔倒了",
+      "session_reason_state_repaired": "巡查时发现状态对不上，恩兔收拾掉的",
+      "session_output_file": "edge 的原始输出录在 {{path}}",
       
       // 快捷配置
       "quick_config": "当前配置",
@@ -100,6 +615,12 @@ const resources = {
       "disconnecting": "正在收拾工具...",
       "force_disconnect": "立即停止",
       "disconnect_waiting": "恩兔在温柔地关闭通道，可能需要一小会儿。如果一直等不来，可以选择强制停止哦。",
+
+      // 暂停相关
+      "paused": "恩兔在歇一会儿，工作便签还留着呢",
+      "pause": "歇一会儿",
+      "resume": "继续打扫",
+      "pause_failed": "唔...恩兔没能停下来歇息",
     }
   },
   en: {
@@ -110,6 +631,10 @@ const resources = {
       "connecting": "Preparing your path...",
       "connected": "Master, your path is ready!",
       "disconnected": "N-Too is on standby",
+      "reconnecting": "Tripped over, waiting for reconnect attempt {{attempt}}...",
+      "degraded": "Tunnel is up, but something is wrong with it. Watching it...",
+      "cancel_reconnect": "Stop reconnecting",
+      "cancel_reconnect_failed": "Failed to cancel the reconnect",
       "error": "Oopsy! Something went wrong",
       "settings": "Mission Prep",
       "logs": "Work Report",
@@ -126,10 +651,237 @@ const resources = {
       "dhcp": "Auto Assign",
       "static": "Manual",
       "advanced_settings": "Advanced",
-      "extra_args": "Special Orders",
+      "routes": "Routes",
+      "route_add": "+ Add",
+      "route_netmask": "Netmask (or prefix length)",
+      "route_gateway": "Gateway (virtual address of the forwarding peer)",
+      "routes_desc": "Reach other subnets through a peer in the virtual network: network and netmask on the left (a prefix length like 24 also works), the peer edge's virtual address on the right. Passed to edge as -n",
+      "packet_forwarding": "Act as a forwarding node (gateway)",
+      "server_mode": "Server mode (unattended)",
+      "server_mode_desc": "One switch for an unattended site gateway: connect with this profile when N2N Maid starts, always reconnect (no attempt limit, waiting at most 30 seconds), act as a forwarding node, copy major events to the system log and re-check the supernode address periodically. Applied only when connecting, so your own settings stay untouched. Export a startup service to bring the machine online at boot (it uses the saved active profile)",
+      "service_export": "Export startup service…",
+      "service_export_dir_prompt": "Which folder should the service setup be exported to?",
+      "service_exported": "Exported. Run this in a terminal to install it (as administrator on Windows):\n{{install}}",
+      "service_export_failed": "Failed to export the service setup",
+      "packet_forwarding_desc": "Let this machine route traffic for peers: passes -r to edge and, once connected, turns on OS IP forwarding (net.ipv4.ip_forward on Linux, which affects every adapter; forwarding on the virtual adapter on Windows), restoring it after disconnecting. Peers should use this machine's virtual address as the gateway in their routes",
+      "routes_applied": "Routes added",
+      "error_invalid_route": "Routes need an IPv4 network, a netmask (or prefix length) and a gateway, and the network must not have host bits set",
+      "advanced_options": "Special Orders",
+      "advanced_option_add": "+ Add",
+      "advanced_option_enabled": "Use this order",
+      "advanced_option_value": "Value (leave empty if none)",
       "edge_path": "Toolbox Path",
       "tap_device": "Device Name",
       "mtu": "Tunnel Width",
+      "log_forward": "Report Scope",
+      "log_forward_all": "Everything",
+      "log_forward_warnings_and_errors": "Warnings and errors only",
+      "log_forward_connection_events": "Connection events only",
+      "theme": "Theme",
+      "profile": "Profile",
+      "startup_config_error": "Failed to load settings",
+      "startup_config_migrated": "Your old settings were moved into the \"Default\" profile (backup: config.legacy.toml)",
+      "startup_check_edge_found": "edge executable not found: {{detail}}",
+      "startup_check_tap_driver": "TAP-Windows driver is not installed yet",
+      "startup_check_passed": "✓ {{id}} check passed",
+      "safe_mode_title": "Safe mode",
+      "safe_mode_desc": "The app did not shut down cleanly {{count}} times in a row, so auto-connect is off and logging is at its most verbose for this launch. Check the readiness report and logs below, fix the settings, then connect manually.",
+      "safe_mode_leave": "All good, leave safe mode",
+      "safe_mode_leave_failed": "Failed to leave safe mode",
+      "orphan_title": "Unmanaged edge found",
+      "orphan_desc": "An edge left over from last time or started elsewhere is still running and may hold the TAP device and management port. Adopt it to let the app manage it, or stop it.",
+      "orphan_item": "PID {{pid}} · community {{community}} · supernode {{supernode}} · management port {{port}}",
+      "orphan_unknown": "unknown",
+      "orphan_adopt": "Adopt",
+      "orphan_terminate": "Stop it",
+      "orphan_failed": "Failed to handle the unmanaged edge",
+      "startup_previous_session": "An edge from a previous session is still answering on the management port; you may need to stop it first",
+      "startup_last_session_running": "The edge left from before the restart is still running (community {{community}}, PID {{pid}}), so the tunnel may still be up, but N-Too can't control it; stop that process before connecting again",
+      "startup_last_session_ended": "The last session (community {{community}}) didn't end cleanly; it was last confirmed at {{time}}, after which the app or edge stopped unexpectedly",
+      "startup_edge_version": "edge version: {{version}}",
+      "elevation_missing": "N-Too is not running as administrator, but the network driver needs it. Right-click and \"Run as administrator\" before connecting~",
+      "elevation_caps_missing": "edge lacks network capabilities yet; N-Too will ask for authorization when connecting",
+      "elevation_can_drop": "The current backend does not need administrator rights; running N-Too as a normal user is safer",
+      "elevation_backend_unknown": "No usable network driver found (TAP-Windows or Wintun); the tunnel may fail to come up",
+      "address_book_manual": "Enter address manually",
+      "template_pick": "Fill from a template…",
+      "template_apply_confirm": "Pre-fill with the \"{{name}}\" template? Supernode, community and address will be replaced (you can still edit before saving)\n{{desc}}",
+      "template_public_test": "Public test supernode",
+      "template_public_test_desc": "The public test supernode run by ntop, good for a first try; pick a community name nobody else will guess",
+      "template_lan_gaming": "Self-hosted supernode · LAN gaming",
+      "template_lan_gaming_desc": "Enter your own supernode, share one community with friends; addresses are assigned automatically and it always reconnects",
+      "template_site_to_site": "Site-to-site with routes",
+      "template_site_to_site_desc": "Static address 10.0.0.1, routes the remote 192.168.2.0/24 and enables forwarding; adjust the routes to your subnets",
+      "address_book_add": "Add to address book",
+      "address_book_name_prompt": "Name this supernode",
+      "address_book_measure": "Measure RTT",
+      "address_book_measure_failed": "Failed to measure latency",
+      "profile_new": "Save as new profile",
+      "profile_notes": "Notes and tags",
+      "profile_notes_prompt": "Notes for this profile (owner, purpose, expiry…)",
+      "profile_tags_prompt": "Tags (comma separated)",
+      "profile_modified_at": "Last modified: {{time}}",
+      "profile_new_prompt": "Name the new profile (current settings will be copied)",
+      "profile_delete": "Delete current profile",
+      "profile_delete_confirm": "Really delete the profile \"{{name}}\"?",
+      "profile_switch_failed": "Failed to switch profile",
+      "profile_delete_failed": "Failed to delete profile",
+      "profile_export": "Export profiles to file",
+      "profile_export_path_prompt": "Export to which file? (.json exports JSON, anything else TOML; the file contains the encryption key, so only share it with people you trust)",
+      "profile_export_all_confirm": "Export all profiles? (Cancel exports only the current one)",
+      "profile_export_done": "Exported {{count}} profile(s) to {{path}}",
+      "profile_export_failed": "Failed to export profiles",
+      "profile_import": "Import profiles from file",
+      "profile_import_path_prompt": "Path of the profile file (.toml or .json):",
+      "profile_import_done": "Imported: {{names}} (imported profiles are read-only; unlock them to edit)",
+      "profile_import_failed": "Failed to import profiles",
+      "share_link": "Create share link",
+      "share_link_desc": "Send this link to a teammate (or turn it into a QR code) and they can paste it into the app. It contains the encryption key, so only share it with people you trust",
+      "share_link_copy": "Copy",
+      "share_link_copied": "Share link copied",
+      "share_link_failed": "Failed to create share link",
+      "share_link_ip_conflict": "Heads up: the address {{ip}} in this invite is already assigned to \"{{name}}\" in the roster. Both machines online at once will fight over it",
+      "share_link_join": "Paste share link",
+      "share_link_paste_prompt": "Paste the share link you received (n2n-maid://join?...):",
+      "share_link_import_confirm": "Add this profile?\nName: {{name}}\nSupernode: {{supernode}}\nCommunity: {{community}}",
+      "share_link_invalid": "Invalid share link",
+      "key_rotation": "Rotate key",
+      "key_rotation_time_prompt": "When should everyone switch to the new key? (YYYY-MM-DD HH:MM; the old key stays in use until then)",
+      "key_rotation_time_invalid": "Invalid time, use YYYY-MM-DD HH:MM",
+      "key_rotation_failed": "Failed to rotate key",
+      "key_rotation_desc": "The new key is saved and will be used from {{time}}. Send the link below to your group; they'll switch at the same time once they add it",
+      "key_rotation_pending": "Switching to a new key at {{time}}",
+      "key_rotation_cancel": "Cancel",
+      "key_rotation_cancel_confirm": "Cancel the key rotation for \"{{name}}\"? Let your group know the invite you sent is void",
+      "key_rotation_import_confirm": "The admin of \"{{name}}\" scheduled a key rotation. Save the new key and switch to it at {{time}}?",
+      "close": "Close",
+      "profile_read_only_hint": "This profile is managed by an administrator; supernode, community and key are locked",
+      "profile_unlock": "Unlock",
+      "profile_unlock_confirm": "After unlocking \"{{name}}\" you can edit everything, but a wrong change may break the connection. Continue?",
+      "profile_unlock_failed": "Failed to unlock profile",
+      "theme_system": "Follow system",
+      "theme_light": "Light",
+      "theme_dark": "Dark",
+      "preferences": "App preferences",
+      "preferences_desc": "These settings don't belong to any profile; they apply right away and are saved separately from the settings above",
+      "preferences_save_failed": "Failed to save preferences",
+      "autostart": "Open N2N Maid when I log in",
+      "notifications": "Show notification bubbles (warnings and errors are always shown)",
+      "close_to_tray": "Hide to the tray when closing the window (choose Quit in the tray to exit)",
+      "sync_dir": "Sync folder",
+      "sync_dir_placeholder": "e.g. ~/Dropbox/n2n-maid (leave empty to turn syncing off)",
+      "sync_dir_encrypted": "Syncing is off while the config is encrypted: the sync folder copy can only be plain text. Remove the password to sync",
+      "sync_dir_desc": "Pick a folder synced by Dropbox, Syncthing or similar and every machine shares the same profiles; keys are never written there",
+      "important_peers": "Important peers",
+      "system_log": "Write to system log",
+      "system_log_desc": "Also record connects, unexpected exits and errors in the system log (Windows Event Viewer → Application, source n2n-maid; journald on Linux, see journalctl -t n2n-maid) so admins can collect them with their usual tools. Fully applies after restarting the app",
+      "desktop_vpn_hint": "Show in the desktop network panel (Linux only)",
+      "desktop_vpn_hint_desc": "Once connected, ask NetworkManager to show the virtual adapter as an external connection so the GNOME / KDE network panels reflect that the VPN is active. NetworkManager only displays it and leaves addresses and routes alone; has no effect without NetworkManager",
+      "edge_hardening": "Harden edge (Linux only)",
+      "edge_run_as": "User to switch to after the adapter is up (name or uid:gid, empty to stay)",
+      "edge_hardening_desc": "Start edge with a minimal environment, without inheriting the app's open files, and with no_new_privs when running as root. With a user set, edge drops to that user once the adapter is up. This limits the damage if edge misbehaves",
+      "edge_sha256": "edge fingerprint allow-list (SHA-256)",
+      "edge_sha256_desc": "N-Too hashes edge before every start. With an allow-list (separate entries with commas or spaces), only listed edge binaries may run; when empty, the first fingerprint seen is remembered and any change is stopped and reported",
+      "edge_integrity_bypass": "Start even if the fingerprint does not match",
+      "edge_integrity_bypass_desc": "For people who build edge themselves and swap it often: mismatches are only logged. edge runs with network privileges, so keep this off otherwise",
+      "control_api": "Enable control API (local HTTP interface for scripts)",
+      "control_api_port": "Port",
+      "control_api_socket": "Use a Unix socket instead (empty = listen on 127.0.0.1)",
+      "control_api_no_tokens": "No tokens issued yet",
+      "control_api_token_name": "Token name (e.g. \"backup script\")",
+      "control_api_scope_status": "Status only",
+      "control_api_scope_control": "Control",
+      "control_api_create": "Issue token",
+      "control_api_rotate": "Rotate",
+      "control_api_revoke": "Revoke",
+      "control_api_rotate_confirm": "Issue a new token for \"{{name}}\"? The old token stops working immediately",
+      "control_api_revoke_confirm": "Revoke the token for \"{{name}}\"?",
+      "control_api_issued": "Token for \"{{name}}\" (shown only once, copy it now):",
+      "control_api_failed": "Control API operation failed",
+      "control_api_desc": "Scripts send Authorization: Bearer <token> to GET /v1/status (status-only tokens work) or POST /v1/connect?profile=name and /v1/disconnect (control tokens only). The switch, port and socket apply after restarting the app; issuing, rotating and revoking tokens apply immediately",
+      "backup": "Profile backups",
+      "backup_desc": "A timestamped backup is kept automatically before every change (up to 10, for 30 days by default), so a bad edit can be undone here",
+      "backup_none": "No backups yet",
+      "backup_restore": "Restore",
+      "backup_restore_confirm": "Restore all profiles and the address book to the backup from {{time}}? The current contents will be backed up first",
+      "backup_restore_failed": "Failed to restore backup",
+      "reconnect_policy": "Reconnect",
+      "reconnect_policy_never": "Never reconnect automatically",
+      "reconnect_policy_on_failure": "Reconnect when edge fails",
+      "reconnect_policy_always": "Always reconnect (unless you disconnect)",
+      "reconnect_max_attempts": "Max attempts in a row (0 = unlimited)",
+      "reconnect_backoff_max_secs": "Max wait (seconds)",
+      "stop_timeout_secs": "Stop timeout (seconds)",
+      "status_snapshot_path": "Status snapshot file",
+      "status_snapshot_interval_secs": "Write every (seconds)",
+      "supernode_recheck_secs": "Re-check supernode address (seconds)",
+      "supernode_recheck_desc": "For supernodes on dynamic DNS: while connected, look the address up again this often and reconnect when the IP changes (0 turns it off)",
+      "supernode_failover": "Backup supernodes",
+      "supernode_failover_desc": "When the supernode stays unreachable, reconnect to the next one in order (host:port, separated by commas or spaces)",
+      "maid_supernode_moved": "The supernode moved to {{address}}; reconnecting",
+      "maid_vpn_conflict": "{{vpn}} interface {{interface}} holds {{network}}, which overlaps the community's {{ours}}. If peers are unreachable once connected, turn off {{vpn}} or move the community to another subnet",
+      "status_snapshot_desc": "When set, the current status is written to this file every few seconds for scripts or monitoring agents that can't use the control API. Paths ending in .prom get the Prometheus text format (ready for node_exporter's textfile collector); anything else gets JSON. Leave empty to turn it off",
+      "stop_timeout_desc": "When disconnecting, quitting or reconnecting with another profile, edge is asked to stop first and force-killed if it is still running after this long. Raise it on slow machines",
+      "tap_reset_on_teardown": "Reset the virtual adapter if edge leaves it dirty",
+      "tap_restart_on_loss": "Reconnect when the virtual adapter breaks",
+      "tap_restart_on_loss_desc": "While connected, the virtual adapter is checked every few seconds. Sleep/resume or a driver hiccup can remove it, disable it or drop its address without edge noticing. You are warned by default; with this enabled the tunnel is re-established automatically",
+      "tap_reset_on_teardown_desc": "After disconnecting, the virtual adapter is checked for addresses or routes that were not released (some Windows TAP drivers do this). You are warned by default; with this enabled the adapter is reset so the next connection does not hit an address conflict. Resetting may need administrator rights",
+      "command_line_export": "Copy equivalent edge command line",
+      "command_line_include_secrets": "Include the real encryption key and passwords in the command line? (Cancel to mask them)",
+      "command_line_copied": "Copied to clipboard:\n{{commandLine}}",
+      "command_line_failed": "Failed to build the command line",
+      "reconnect_policy_desc": "Whether to restart edge after it exits unexpectedly. The wait doubles after each failure (starting at 2 seconds) and the count resets once connected. Use never on metered laptops and always on unattended gateways",
+      "important_peers_desc": "After connecting, N-Too greets these peers once to warm up the ARP/neighbor cache so your first SSH/RDP isn't delayed (leave empty to skip)",
+      "port_forwards": "Port forwarding",
+      "wake_rules": "Wake peers",
+      "wake_rule_add": "+ Add",
+      "wake_rule_enabled": "Wake on schedule",
+      "wake_rule_name": "Label",
+      "wake_rule_at": "Daily wake time (leave empty for manual only)",
+      "wake_now": "Wake now",
+      "side_connections": "Other profiles connected at the same time",
+      "log_source_main": "Main tunnel",
+      "switch_supernode": "Switch",
+      "switch_supernode_prompt": "Switch to which supernode? (host:port; the current virtual IP is kept where possible)",
+      "switch_supernode_failed": "Failed to switch supernode",
+      "wake_all": "Wake all",
+      "wake_all_running": "Waking everyone…",
+      "wake_all_done": "Done: {{succeeded}} came online, {{failed}} did not",
+      "wake_failed": "Could not wake",
+      "wake_rules_desc": "Wake peers with Wake-on-LAN: enter the peer's MAC, virtual address and a time. At that time a magic packet is sent, then the peer gets up to 3 minutes to come online and the result is recorded. Times missed while N2N Maid isn't running are skipped",
+      "wake_history_scheduled": "Scheduled",
+      "wake_history_manual": "Manual",
+      "wake_history_online": "{{name}} online after {{secs}} s",
+      "wake_history_no_answer": "{{name}} did not come online",
+      "wake_history_failed": "{{name}} packet not sent",
+      "error_invalid_wake_rule": "Wake rules need the peer's MAC and virtual address, and a time like 22:00 (leave it empty for manual wake only)",
+      "port_forwards_desc": "Forward TCP ports while connected: the left side is the local listen address, the right side is the target. To reach a peer use 127.0.0.1:8080 → 10.0.0.5:80; to expose your own service use your-virtual-ip:8080 → 127.0.0.1:80",
+      "port_forward_add": "Add rule",
+      "port_forward_name": "Label",
+      "port_forward_enabled": "Enable this rule",
+      "port_forward_stats": "({{active}} active, {{total}} total)",
+      "error_invalid_port_forward": "Port forwarding rules need a listen address and a target in host:port form",
+      "port_knock": "Port knocking",
+      "port_knock_add": "Add step",
+      "port_knock_port": "Supernode port",
+      "port_knock_payload": "UDP payload (prefix with hex: for hex bytes, optional)",
+      "port_knock_delay": "Wait afterwards (ms)",
+      "port_knock_desc": "For supernodes behind port knocking or single-packet authorization: before every connect (including automatic reconnects) these supernode ports are knocked in order, pausing after each step. UDP steps send the given payload; TCP steps just open a connection attempt. A failed knock is only logged and doesn't block the connection",
+      "error_connection_tap_clash": "Another tunnel is already using the same virtual adapter; give this profile a different device name",
+      "error_connection_mgmt_port_clash": "Another tunnel's edge already uses the same management port; give this profile a different one",
+      "error_connection_local_port_clash": "Another tunnel's edge already uses the same local port; pick another or leave it empty",
+      "error_orphan_not_found": "That edge is no longer running",
+      "error_orphan_mismatch": "That edge's community or supernode doesn't match the current profile; it can only be stopped",
+      "error_orphan_terminate_failed": "That edge refused to stop (administrator rights may be needed)",
+      "error_invalid_port_knock": "Knock steps need a port (1-65535) and a wait of at most 10000 ms; UDP payloads starting with hex: must be whole hex bytes, and TCP knocks carry no payload",
+      "error_edge_option_managed": "An advanced option duplicates a basic setting (-c, -l, -k, -a, ...); change it in the basic settings instead",
+      "error_edge_option_unknown": "An advanced option is not a flag edge knows; check the spelling",
+      "error_edge_option_needs_value": "An advanced option needs a value",
+      "error_edge_option_no_value": "An advanced option does not take a value; please clear it",
+      "error_roster_invalid_subnet": "The roster subnet must be in CIDR form, e.g. 10.0.0.0/24",
+      "error_roster_invalid_ip": "A roster member's address is not a valid IPv4 address",
+      "error_roster_ip_outside_subnet": "A roster member's address is outside the community subnet",
+      "error_roster_duplicate_ip": "Two roster members share the same address",
       
       // Status
       "virtual_ip": "My Address",
@@ -138,6 +890,8 @@ const resources = {
       // Messages - Maid tone
       "save_success": "Master's orders noted!",
       "save_failed": "Oops, failed to record...",
+      "save_reconnect_confirm": "The tunnel is up. Reconnect now to apply the new settings?",
+      "reconnect_failed": "Reconnect failed",
       "connect_success": "The hallway is sparkling clean, Master can pass anytime!",
       "connect_failed": "Sorry Master, the tunnel is broken",
       "disconnect_success": "N-Too is off to rest~",
@@ -148,8 +902,30 @@ const resources = {
       "hide_logs": "Hide Logs",
       "clear_logs": "Clear Records",
       "clear": "Clear",
+      "export": "Export",
+      "export_logs": "Export work report",
+      "export_logs_anonymize_confirm": "Anonymize the report first? (Public IPs, MACs, community and key are replaced with placeholders, safe for sharing.)\nChoose Cancel to export as-is.",
+      "export_logs_done": "Work report saved to: {{path}}",
+      "about": "About N-Too",
+      "about_show": "Show",
+      "about_loading": "Filling in the ID card…",
+      "about_version": "Version",
+      "about_runtime": "Runtime",
+      "about_edge": "Toolbox",
+      "about_edge_features": "Toolbox features",
+      "about_edge_no_foreground": "no -f",
+      "about_system": "System",
+      "about_webview": "Web engine",
+      "about_unknown": "unknown",
+      "about_copy": "Copy for maintainers",
+      "about_copied": "ID card copied; paste it into your bug report",
+      "about_failed": "Failed to read the ID card",
+      "about_desc": "Attach this to bug reports so maintainers don't have to ask for versions and the system (exported work reports start with it too)",
+      "export_logs_failed": "Failed to export work report",
       "no_logs": "No work records yet",
       "log_lines": "records",
+      "log_dropped": "{{count}} dropped",
+      "log_dropped_desc": "Only the latest {{capacity}} lines are kept while nobody is reading; older lines were dropped",
       
       // Network info
       "network_info": "Tunnel Details",
@@ -157,6 +933,14 @@ const resources = {
       "ip": "Address",
       "mask": "Subnet Mask",
       "mac": "Hardware ID",
+      "transport_cipher": "Cipher",
+      "transport_compression": "Compression",
+      "transport_header_encryption": "Header encryption",
+      "transport_on": "On",
+      "transport_off": "Off",
+      "tap_adapter": "Adapter",
+      "tap_adapter_detail": "{{name}} (ifIndex {{index}})",
+      "node_name": "Node name",
 
       // Peers
       "peer_list": "Peers",
@@ -165,21 +949,227 @@ const resources = {
       "peer_vpn_ip": "VPN IP",
       "peer_mode": "Mode",
       "peer_public_addr": "Public",
+      "peer_mac": "MAC",
+      "peer_ping_all": "Ping all",
+      "peer_ping_all_failed": "Failed to ping peers",
+      "peer_resolve_names": "Resolve names",
+      "peer_resolve_failed": "Failed to resolve peer names",
+      "peer_bulk_done": "Done: {{succeeded}} succeeded, {{failed}} failed in {{ms}} ms",
+      "peer_export": "Export",
+      "peer_export_path_prompt": "Export to which file? (.csv for a table, anything else for JSON)",
+      "peer_export_done": "Exported {{count}} peer(s) to {{path}}",
+      "peer_export_failed": "Failed to export peers",
+      "peer_link_direct": "Direct",
+      "peer_link_relayed": "Relayed",
       "peer_latency": "Latency",
       "peer_last_seen": "Last seen",
       "latency_unknown": "—",
       
       // Parameter descriptions - Simplified and friendly
       "supernode_desc": "The head office address where N-Too gets her tasks (format: vpn.example.com:7777)",
+      "supernode_dns_placeholder": "Or just a supernode domain (e.g. example.com)",
+      "supernode_dns_desc": "With a domain set, N-Too looks up the SRV record _n2n._udp.<domain> before every connection, and the TXT record _n2n.<domain> (community=...) when no community is filled in",
       "community_desc": "Team secret code - only friends who know it can enter the same tunnel",
-      "username_desc": "Optional. Give your device a nickname for easy recognition; leave empty to use computer name",
+      "username_desc": "Optional. Give your device a nickname for easy recognition; leave empty to use the computer name (characters edge cannot handle are removed, max 15 bytes)",
       "encryption_key_desc": "Secret password (optional) to make the tunnel safer. Leave empty means no encryption, but not recommended",
+      "cipher": "Cipher",
+      "cipher_default": "Let edge decide (recommended)",
+      "cipher_null": "No encryption (-A1)",
+      "cipher_twofish": "Twofish (-A2)",
+      "cipher_aes": "AES (-A3)",
+      "cipher_chacha20": "ChaCha20 (-A4)",
+      "cipher_speck": "Speck (-A5)",
+      "header_encryption": "Header encryption (-H, hides the community name too)",
+      "cipher_desc": "ChaCha20 or Speck are faster on small devices without AES acceleration; header encryption needs a fixed community list on the supernode. Options this edge doesn't support are marked",
+      "edge_unsupported": "not supported by this edge",
+      "compression": "Compression",
+      "compression_none": "None",
+      "compression_lzo": "LZO (-z1, light on CPU)",
+      "compression_zstd": "zstd (-z2, smaller output)",
+      "compression_desc": "Turn on for slow uplinks: trades a little CPU for less traffic. Already-compressed data (video, archives) won't shrink much",
       "ip_mode_desc": "How N-Too gets the address: Auto means head office assigns, Manual means Master decides",
       "static_ip_desc": "Manually specified address, like this: 10.0.0.2 or 10.0.0.2/24",
-      "extra_args_desc": "Special skills for N-Too's magic duster, like -v (chatty mode), -r (help forward), -E (receive broadcast)",
-      "edge_path_desc": "Where N-Too's toolbox is stored, leave empty for default location (bin/edge)",
+      "auto_connect": "Connect when N-Too starts",
+      "auto_connect_desc": "Connect with this profile automatically when the app starts (e.g. on login), so you don't have to click Start",
+      "maid_connected": "The tunnel is ready, Master!",
+      "maid_connected_ip": "The tunnel is ready! My address is {{ip}}",
+      "maid_network_switched": "You're on \"{{network}}\" now, so I switched to the \"{{profile}}\" profile",
+      "maid_key_rotated": "It's time: the \"{{profile}}\" profile now uses its new key",
+      "maid_config_reloaded": "config.toml was edited outside the app; I've reloaded it",
+      "maid_config_reload_failed": "config.toml was edited outside the app, but I can't read it: {{error}}. Saving settings will fail until it's fixed",
+      "maid_sync_pulled": "Got profiles from the sync folder: {{profiles}} (keys aren't synced, so enter them again for new profiles)",
+      "maid_sync_conflict": "These profiles were changed on two machines; I kept this one and saved the other copy as: {{profiles}}. Please pick one",
+      "maid_sync_failed": "Failed to sync with the sync folder: {{error}}",
+      "maid_edge_exited": "edge stopped unexpectedly: {{reason}}",
+      "exit_reason": "Reason: {{reason}}",
+      "exit_bad_args": "edge did not accept its arguments (check the advanced options, or edge may be too old)",
+      "exit_tap_failed": "The virtual adapter could not be opened (in use, or the driver is missing)",
+      "exit_permission_denied": "Not enough permissions",
+      "exit_auth_failed": "Authentication failed (check the key, username and password)",
+      "exit_killed": "Stopped by the system or another program (for example out of memory)",
+      "exit_crashed": "edge crashed",
+      "exit_clean": "edge exited on its own without an error",
+      "exit_failed": "edge exited with an error but left no clue; see the work report",
+      "exit_unknown": "Unknown (the exit status was not available)",
+      "maid_reconnecting": "Connection dropped ({{reason}}), retrying in {{delay}}s (attempt {{attempt}})",
+      "maid_reconnect_gave_up": "Still failing after {{attempts}} reconnect attempts. Waiting for your instructions",
+      "maid_reconnect_cancelled": "Reconnect cancelled",
+      "maid_reconnect_suspended": "Auto-reconnect and self-checks are suspended for the next {{minutes}} minutes",
+      "maid_reconnect_unsuspended": "Auto-reconnect is back on",
+      "reconnect_suspend": "Supernode maintenance? Pause auto-reconnect",
+      "reconnect_suspend_prompt": "Pause auto-reconnect for how many hours? (the profile is not changed)",
+      "reconnect_suspended_until": "Auto-reconnect paused until {{time}}",
+      "reconnect_suspend_cancel": "Resume now",
+      "maid_paused": "Taking a short break. Your settings are saved, resume any time",
+      "maid_resumed": "Back to work, resuming the tunnel",
+      "maid_safe_mode": "{{count}} unclean shutdowns in a row, starting in safe mode without auto-connect",
+      "maid_orphan_edges": "{{count}} unmanaged edge process(es) are running; adopt or stop them",
+      "maid_edge_adopted": "Adopted the running edge (PID {{pid}}); the app now manages it",
+      "maid_supernode_switching": "Packing up to switch to supernode {{address}}",
+      "maid_supernode_switched": "Heading to supernode {{address}}, keeping the current address where possible",
+      "maid_supernode_failover": "Supernode {{previous}} is unreachable, trying backup supernode {{address}}",
+      "maid_supernode_failover_ok": "Backup supernode {{address}} answered, the tunnel is up",
+      "maid_supernode_failover_exhausted": "Every backup supernode has been tried and none could be reached; please check the network or the supernodes",
+      "maid_exit_waiting": "Closing: waiting for edge to shut down cleanly…",
+      "maid_restarting": "Reconnecting with the new settings, closing the current tunnel first",
+      "maid_registration_lost": "edge is running but hasn't registered with the supernode for {{secs}}s. The tunnel may be down, still watching",
+      "maid_registration_restored": "Registered with the supernode again, tunnel is back to normal",
+      "maid_tap_broken": "The virtual adapter \"{{adapter}}\" is broken (gone, disabled or lost its address) and edge hasn't noticed",
+      "maid_tap_restored": "The virtual adapter \"{{adapter}}\" is back to normal",
+      "maid_graceful_stop_failed": "Graceful stop failed: {{error}} (forcing stop)",
+      "maid_force_stop_timeout": "edge is taking too long, forcing it to stop",
+      "maid_stop_in_progress": "Already asking edge to stop, no need to click again (if an authorization prompt shows up, confirming it once is enough)",
+      "maid_stop_cooling_down": "Just asked edge to stop; give it {{secs}} seconds before trying again",
+      "maid_tap_leftover": "edge has exited but the virtual adapter \"{{adapter}}\" still holds {{leftover}}; the next connection may hit an address conflict (automatic reset can be enabled in advanced settings)",
+      "maid_tap_reset": "The virtual adapter \"{{adapter}}\" had leftovers and has been reset",
+      "maid_mtu_applied": "Tunnel width changed to {{mtu}} without disconnecting",
+      "maid_wake_sent": "Waking \"{{name}}\", waiting for it to come online...",
+      "maid_wake_online": "\"{{name}}\" is awake (online after {{secs}} seconds)",
+      "maid_wake_no_answer": "\"{{name}}\" did not come online within {{minutes}} minutes; it may still be asleep or its edge isn't connected yet",
+      "maid_wake_failed": "Could not send the wake packet for \"{{name}}\": {{error}}",
+      "maid_tap_reset_failed": "Could not reset the virtual adapter \"{{adapter}}\": {{error}}",
+      "maid_state_child_gone": "Self-check found edge had already exited unnoticed; the state has been cleaned up",
+      "maid_state_no_child": "Self-check found the status claimed a connection with no edge running; reset to disconnected",
+      "maid_state_stuck_disconnecting": "Disconnecting took more than 60 seconds; edge was force stopped",
+      "maid_ready": "All startup checks passed, ready when you are",
+      "maid_not_ready": "{{count}} startup check(s) failed, please take a look before connecting",
+      "config_locked_title": "Configuration locked",
+      "config_locked_desc": "Your configuration is encrypted. Enter the password to load your profiles",
+      "config_password": "Password",
+      "config_password_new": "New password",
+      "config_password_confirm": "Confirm password",
+      "config_password_mismatch": "The passwords do not match",
+      "config_unlock": "Unlock",
+      "config_encryption": "Encrypted configuration",
+      "config_encryption_on": "On",
+      "config_encryption_off": "Off",
+      "config_encryption_desc": "Encrypt the whole configuration with a password so other users of this computer can't read communities or supernode addresses; existing backups are encrypted too. You'll be asked for it on every launch. If you forget it, the configuration has to be set up again",
+      "network_rules": "Network-based profiles",
+      "network_rules_desc": "Switch to a profile automatically when a given Wi-Fi or network interface is connected. A running tunnel is reconnected with the new profile. The first matching rule wins",
+      "network_rule_ssid": "Wi-Fi name",
+      "network_rule_interface": "Interface",
+      "network_rule_add": "Add rule",
+      "network_rule_use_current": "Use current network",
+      "network_rules_save": "Save rules",
+      "network_rules_saved": "Rules saved",
+      "network_rules_failed": "Failed to save rules",
+      "network_current": "Now: {{network}}",
+      "network_none": "No connected network found",
+      "roster": "Roster",
+      "roster_desc": "For community hosts: track the static address assigned to each member. Invites are checked against it for collisions. Export it to share with other admins",
+      "roster_subnet": "Community subnet (CIDR)",
+      "roster_member_name": "Member name",
+      "roster_member_ip": "Address",
+      "roster_member_notes": "Notes",
+      "roster_add": "Add member",
+      "roster_suggest": "Next free address",
+      "roster_full": "No free addresses left in the subnet",
+      "roster_save": "Save roster",
+      "roster_saved": "Roster saved",
+      "roster_import": "Import roster",
+      "roster_export": "Export roster",
+      "roster_import_path_prompt": "Path to the roster file (.toml or .json)",
+      "roster_export_path_prompt": "Where should the roster be saved? (.json for JSON, anything else for TOML)",
+      "roster_imported": "Imported the roster for \"{{community}}\" ({{count}} members)",
+      "roster_exported": "Roster exported to {{path}}",
+      "roster_failed": "Roster operation failed",
+      "roster_need_community": "Fill in the community first so N-Too knows whose roster this is",
+      "config_encrypt": "Encrypt",
+      "config_password_change": "Change password",
+      "config_decrypt": "Remove encryption",
+      "config_decrypt_hint": "Enter the current password in the first field",
+      "config_decrypt_confirm": "The configuration and its backups will be stored in plain text again. Continue?",
+      "config_lock": "Lock now",
+      "config_encrypted_done": "The configuration is now encrypted, and older backups were re-encrypted with the new password (any that couldn't be opened were deleted)",
+      "config_encryption_failed": "Encryption setting failed",
+      "config_overridden_hint": "These fields are set by N2N_MAID_* environment variables or command-line flags and are not saved to the configuration: {{fields}}",
+      "error_config_locked": "The configuration is encrypted; please enter the password to unlock it",
+      "error_config_wrong_password": "Wrong password (or the configuration file was modified)",
+      "error_config_password_empty": "The password cannot be empty",
+      "advanced_options_desc": "Special skills for N-Too's magic duster, one flag per row, like -v (chatty mode), -r (help forward), -E (receive broadcast); values may contain spaces",
+      "nat_check": "NAT check",
+      "nat_check_run": "Check",
+      "nat_checking": "Checking…",
+      "nat_check_failed": "NAT check failed",
+      "nat_local_ip": "This computer",
+      "nat_router_wan_ip": "Router WAN",
+      "nat_external_ip": "Public",
+      "nat_verdict_public": "This computer has a public address, so peers can reach it directly",
+      "nat_verdict_nat": "Only your own router's NAT is in the way; hole punching usually works",
+      "nat_verdict_double_nat": "There is another router outside yours (double NAT). Peers will almost never connect directly; turn on relay mode so traffic goes through the supernode",
+      "nat_verdict_cgnat": "Your ISP shares one address between customers (carrier-grade NAT: the router's WAN address doesn't match the public one or is in 100.64.0.0/10). Peers can't connect directly; turn on relay mode so traffic goes through the supernode",
+      "nat_verdict_unknown": "Couldn't find the public address (STUN server unreachable), so N-Too can't tell",
+      "nat_enable_relay": "Turn on relay mode (-S1)",
+      "nat_check_desc": "Asks a STUN server for the public address and the router (NAT-PMP / UPnP) for its WAN address to see how many NAT layers there are. The result is also written to the log",
+      "edge_path_desc": "Where N-Too's toolbox is stored, leave empty for default location (bin/edge); click \"Find\" if you're not sure where it's installed",
+      "edge_discover": "🔍 Find",
+      "edge_discover_failed": "Failed to look for the toolbox",
+      "edge_discover_none": "N-Too searched PATH, her resource folder and the usual install locations but found no edge",
+      "edge_source_path": "PATH",
+      "edge_source_bundled": "Bundled",
+      "edge_source_common": "Common location",
+      "edge_version_unknown": "unknown version",
       "tap_device_desc": "Virtual network card name, leave empty and N-Too will pick one (like edge0)",
       "mtu_desc": "Tunnel width, default 1290. Too small affects speed, too large might get stuck",
+      "mtu_apply_live": "Apply now",
+      "local_port": "Local port",
+      "local_port_placeholder": "Leave empty to let edge pick one",
+      "local_port_desc": "Pin the local UDP port edge uses to talk to head office and peers (passed to edge as -p), for firewalls that only allow fixed ports. The port is checked for conflicts before connecting",
+      "error_local_port_in_use": "The pinned local port is already used by another program; pick another port or close that program first",
+      "error_invalid_local_port": "The local port must be between 1 and 65535 and can't be the same as edge's management port (5644 by default)",
+      "register_interval_secs": "Registration interval",
+      "register_interval_placeholder": "Leave empty for edge's default of 20 seconds",
+      "register_interval_desc": "How often edge registers with head office and peers, which also keeps NAT mappings open (passed to edge as -i, in seconds). Shorten it (say, 10) behind NATs that forget mappings quickly and make peers drop out now and then; lengthen it on metered links",
+      "register_interval_value": "every {{secs}} s",
+      "edge_stats_registration": "Supernode registration",
+      "edge_stats_registered": "Registered",
+      "edge_stats_not_registered": "No recent contact with the supernode",
+      "edge_stats_uptime": "edge uptime",
+      "edge_stats_packets": "Packets sent / received",
+      "edge_stats_bytes": "Traffic sent / received",
+      "error_invalid_register_interval": "The registration interval must be between 1 and 3600 seconds (leave it empty for edge's default of 20)",
+      "mac_address": "Hardware ID (MAC)",
+      "mac_address_placeholder": "Leave empty to let edge pick one",
+      "mac_address_randomize": "Randomize",
+      "management_port": "Management port",
+      "management_port_placeholder": "Leave empty for the default 5644",
+      "management_password": "Management password",
+      "management_password_placeholder": "Leave empty for edge's default password",
+      "management_desc": "edge's management port (passed to edge as -t) and its password (--management-password). The app reads heartbeats, peers and traffic through it; move the port when running a second edge on the same machine",
+      "error_invalid_management_port": "The management port must be between 1 and 65535",
+      "mac_address_desc": "Pin the virtual adapter's MAC, passed to edge as -m. If a cloned VM reports the hardware ID is taken, click Randomize and save",
+      "error_invalid_mac": "The hardware ID (MAC) must look like 02:ab:cd:ef:12:34 and can't be a multicast or all-zero address",
+      "error_cipher_needs_key": "An encryption key is required for this cipher (only \"no encryption\" works without one)",
+      "error_key_rotation_time_passed": "The key switch time must be in the future",
+      "error_sync_config_encrypted": "Config encryption and the sync folder can't be used together: the sync folder copy can only be plain text. Turn one of them off first",
+      "error_sync_dir_missing": "The sync folder doesn't exist; make sure it has been created (and synced by your cloud client) first",
+      "error_cipher_unsupported": "This edge build doesn't support that cipher; pick another one or use a newer edge",
+      "error_header_encryption_unsupported": "This edge build doesn't support header encryption (-H needs 2.8 or later)",
+      "error_compression_unsupported": "This edge build doesn't support that compression (zstd must be compiled in); try LZO instead",
+      "mtu_apply_live_hint": "Change the virtual adapter directly while connected, without reconnecting (also saved to the current profile)",
+      "mtu_apply_failed": "Failed to change the tunnel width",
+      "error_mtu_live_not_connected": "The tunnel width can only be changed live while connected; otherwise just save the settings",
+      "log_forward_desc": "With chatty -v -v -v logging, let N-Too report only the important lines to keep the UI snappy (applies on next connect)",
       
       // Error messages - Comforting style
       "error_mac_in_use": "This hardware ID is already taken, maybe another device is using it or head office hasn't cleared the old record",
@@ -187,11 +1177,57 @@ const resources = {
       "error_mac_or_ip_in_use": "Head office says your MAC/IP is still marked as in-use (maybe the last session wasn't released yet). You can wait a bit, or click “Take a Break” to send SIGINT and try again.",
       "error_tap_create_failed": "N-Too doesn't have permission to create device, Master needs to grant authorization",
       "error_tap_busy": "The virtual adapter is busy (Device or resource busy). Another edge may still be running, or the TAP name conflicts. Disconnect other sessions or change the TAP device name and retry.",
+      "error_no_network": "N-Too can't find any way out (no network; airplane mode or a loose cable?). Try again once the network is back",
+      "error_clock_skew": "This computer's clock is too far off; with header encryption (-H) head office will reject the packets. Sync the system time and connect again",
+      "error_supernode_key_changed": "The supernode's public key differs from the one remembered last time! Someone may be impersonating head office, so N-Too refused to connect. Check with your administrator before trusting the new key",
+      "error_invalid_supernode": "The supernode must look like host:port (e.g. vpn.example.com:7777, or [::1]:7777 for IPv6)",
+      "error_invalid_supernode_dns": "Enter just a domain for supernode discovery (e.g. example.com, no port); its SRV records will be looked up",
+      "error_community_empty": "The secret code is empty",
+      "error_community_too_long": "The secret code can be at most 19 bytes (a Chinese character takes 3); edge would truncate anything longer",
+      "error_invalid_static_ip": "The static address must look like 10.0.0.2 or 10.0.0.2/24",
+      "error_invalid_ip_mode": "The address mode must be DHCP or static",
+      "error_invalid_mtu": "The tunnel width (MTU) must be between 576 and 1500",
+      "error_invalid_stop_timeout": "The stop timeout must be between 1 and 300 seconds",
+      "error_invalid_snapshot_interval": "The status snapshot interval must be between 5 and 3600 seconds",
+      "error_invalid_supernode_recheck": "The supernode re-check interval must be between 30 and 86400 seconds (0 turns it off)",
+      "error_invalid_supernode_failover": "Every backup supernode must be in host:port form",
+      "error_edge_path_missing": "No toolbox (edge executable) was found at this path; please check it",
+      "error_edge_binary_changed": "The toolbox (edge) differs from the one remembered last time! It may have been replaced, so N-Too refused to connect. If you upgraded it yourself, check the fingerprint before trusting it",
+      "error_edge_not_allowlisted": "The toolbox (edge) fingerprint is not on the allow-list, so N-Too refused to connect. Check where this edge came from, or add its fingerprint to the allow-list",
+      "error_edge_integrity_unverifiable": "The toolbox (edge) fingerprint could not be checked (edge or the fingerprint book is unreadable), so N-Too refused to connect. Check the file permissions, or turn on \"Start even if the fingerprint does not match\" in the settings",
+      "error_invalid_edge_sha256": "Every entry in the edge allow-list must be a 64-digit hexadecimal SHA-256",
+      "supernode_key_changed_title": "⚠️ Supernode key changed",
+      "supernode_key_changed_detail": "Remembered key: {{pinned}}\nPresented key: {{presented}}",
+      "supernode_key_trust": "I trust the new key",
+      "supernode_key_trust_confirm": "Only trust the new key if your administrator confirmed the supernode's key really changed. Trust it?",
+      "supernode_key_trust_failed": "Failed to trust the new key",
+      "edge_binary_rejected_title": "⚠️ edge fingerprint mismatch",
+      "edge_binary_rejected_detail": "Location: {{path}}\nCurrent fingerprint: {{actual}}",
+      "edge_binary_previous": "Remembered fingerprint: {{previous}}",
+      "edge_binary_trust": "I replaced it myself, trust it",
+      "edge_binary_trust_confirm": "Only trust it if you upgraded or replaced edge yourself. Remember the new fingerprint?",
+      "edge_binary_trust_failed": "Failed to trust the new edge",
       "error_supernode_unreachable": "Can't reach head office, is the address wrong or network disconnected?",
+      "error_tap_missing": "The virtual adapter is gone (sleep/resume or a driver problem?)",
+      "error_tap_down": "The virtual adapter has been disabled",
+      "error_tap_address_lost": "The virtual adapter lost its address",
       "error_auth_failed": "The door is locked, please check if the code and password are correct",
       "error_permission_denied": "Not enough permission, Linux systems need special capabilities for the toolbox",
       "error_edge_exited": "Oops… the toolbox stopped unexpectedly (edge exited). Check the “Work Report” for the last error line.",
       "error_wsagetlasterror": "A strange error occurred in Windows environment, but it seems not to affect usage",
+      "error_version_mismatch": "N-Too and head office speak different dialects (edge and supernode run different n2n major versions, e.g. v2 vs v3). Use the same major version on both sides (check with edge -h), or pick a matching supernode~",
+
+      // Non-fatal warnings
+      "warning_community_truncated": "The secret code is too long and edge truncated it; make sure all peers use the same one",
+      "warning_unknown_option": "edge didn't recognize one of the special orders and skipped it; check the advanced settings",
+      "warning_mtu": "edge complained about the tunnel width (MTU)",
+      "session_summary": "Last session {{duration}}, {{tx}}/{{rx}} packets sent/received, {{reconnects}} reconnects",
+      "session_reason_user_stop": "stopped by you",
+      "session_reason_force_stop": "force stopped",
+      "session_reason_restart": "reconnected with new settings",
+      "session_reason_edge_exited": "edge exited unexpectedly",
+      "session_reason_state_repaired": "cleaned up by the self-check",
+      "session_output_file": "Raw edge output saved to {{path}}",
 
       // Quick config
       "quick_config": "Current Config",
@@ -200,6 +1236,12 @@ const resources = {
       "disconnecting": "Packing up tools...",
       "force_disconnect": "Force Stop",
       "disconnect_waiting": "N-Too is gently closing the tunnel, might take a moment. If it takes too long, you can force stop.",
+
+      // Pause
+      "paused": "N-Too is taking a short break, the session is kept",
+      "pause": "Short Break",
+      "resume": "Resume",
+      "pause_failed": "Um... N-Too couldn't pause",
     }
   }
 };
@@ -8,37 +8,593 @@ export interface NetworkInfo {
 }
 
 /// 同伴节点信息（来自 n2n Management API）
+/// 和同伴之间的通道怎么走
+export type PeerLink = 'direct' | 'relayed' | 'unknown';
+
 export interface PeerNodeInfo {
   name?: string | null;
   vpnAddr?: string | null;
   vpnIp?: string | null;
   publicAddr?: string | null;
+  mac?: string | null;
   mode?: string | null;
+  /// 直连还是经总部中转（按 mode 归好类）
+  link: PeerLink;
   lastSeen?: number | null;
   isLocal?: boolean | null;
   latencyMs?: number | null;
   lastPing?: number | null;
 }
 
+/// 一批同伴操作的汇总（ping_all_peers / resolve_peer_names / wake_all_peers）
+export interface BulkReport<T> {
+  results: T[];
+  succeeded: number;
+  failed: number;
+  elapsedMs: number;
+}
+
+/// 一位同伴的 ping 结果
+export interface PingOutcome {
+  vpnIp: string;
+  name?: string | null;
+  latencyMs?: number | null;
+  error?: string | null;
+}
+
+/// 一位同伴的反查结果（hostname 是 DNS 里登记的名字）
+export interface NameOutcome {
+  vpnIp: string;
+  name?: string | null;
+  hostname?: string | null;
+  error?: string | null;
+}
+
+/// 一类包的收发计数（transop：全部数据包；p2p：直连；super：经总部中转……）
+export interface PacketCounter {
+  kind: string;
+  tx: number;
+  rx: number;
+}
+
+/// edge 的实时统计（来自管理口）
+export interface EdgeStats {
+  startedAt: number;
+  uptimeSecs: number;
+  lastSuper: number;
+  lastP2p: number;
+  registered: boolean;
+  supernode?: string | null;
+  txPackets: number;
+  rxPackets: number;
+  txBytes?: number | null;
+  rxBytes?: number | null;
+  packets: PacketCounter[];
+}
+
+/// edge 的非致命提醒（WARNING 行）
+export interface EdgeWarning {
+  code?: string | null;
+  message: string;
+  at: number;
+}
+
+/// 指示簿的加密状态（get_config_encryption）
+export interface ConfigEncryption {
+  encrypted: boolean;
+  locked: boolean;
+}
+
+/// 恩兔对主人说的一句话（assistant-message 事件，按 id 翻译成聊天气泡）
+export type AssistantSeverity = 'info' | 'success' | 'warning' | 'error';
+
+export interface AssistantMessage {
+  id: string;
+  severity: AssistantSeverity;
+  params: Record<string, string | number | boolean | null>;
+  at: number;
+}
+
+/// 总部地址簿里的一条记录（多份方案共用）
+export interface SupernodeEntry {
+  id: string;
+  name: string;
+  address: string;
+  notes: string;
+  last_rtt_ms?: number | null;
+  last_measured?: number | null;
+}
+
+/// 开门体检单里的单项检查
+export interface ReadinessCheck {
+  id: 'edge_found' | 'tap_driver' | 'elevation' | string;
+  ok: boolean;
+  detail?: string | null;
+}
+
+/// 开门体检单（startup-report 事件 / get_startup_report）
+/// 这次上岗进了安全模式（get_safe_mode / safe-mode）
+export interface SafeMode {
+  /// 接连意外退出了几次
+  abnormalExits: number;
+}
+
+export interface StartupReport {
+  configOk: boolean;
+  configError?: string | null;
+  configMigrated: boolean;
+  activeProfile?: string | null;
+  profileCount: number;
+  checks: ReadinessCheck[];
+  autostart?: boolean | null;
+  edgePath: string;
+  edgeVersion?: string | null;
+  previousSessionFound: boolean;
+  /// 上次没来得及收尾的工作（交班本里还留着记录时才有）
+  lastSession?: LastSession | null;
+}
+
+/// 交班本里上一次工作的记录
+export interface LastSession {
+  /// 最后确认的状态（connecting / connected / paused）
+  status: string;
+  pid?: number | null;
+  supernode: string;
+  community: string;
+  virtualIp?: string | null;
+  startedAt: number;
+  /// 最后一次确认的时间（Unix 秒）
+  updatedAt: number;
+  /// 记录里的 edge 现在还在不在
+  stillRunning: boolean;
+}
+
+/// 权限体检报告（get_elevation_status / elevation-advice 事件）
+export interface ElevationReport {
+  elevated: boolean;
+  required: boolean;
+  backend: 'tap' | 'wintun' | 'linux_capabilities' | 'root' | 'unknown';
+  advice: string[];
+}
+
+/// 总部公钥和记下的不一致（supernode-key-changed 事件）
+export interface SupernodeKeyChange {
+  pinned: string;
+  presented: string;
+}
+
+/// edge 的指纹对不上，被拦下了（edge-binary-rejected 事件；不在白名单里时没有 previous）
+export interface EdgeBinaryAlert {
+  code: string;
+  path: string;
+  previous: string | null;
+  actual: string;
+}
+
+/// 工作方案概要（list_profiles 返回）
+/// 方案文件 / 邀请链接里的一份方案
+export interface PortableProfile {
+  name: string;
+  config: N2NConfig;
+  /// 换密语的邀请里约好的换用时间（Unix 秒）
+  switch_at?: number | null;
+}
+
+/// 恩兔自带的方案模板（名字和说明按 template_<id> 翻译）
+export interface ConfigTemplate {
+  id: string;
+  config: N2NConfig;
+}
+
+/// 指示簿的一份自动备份
+/// 指示检查出的某个字段的问题
+export interface FieldError {
+  /// 出问题的字段（和 N2NConfig 的字段名一致）
+  field: keyof N2NConfig;
+  /// 问题编号（error_ 开头，对应 i18n）
+  code: string;
+}
+
+/// 遥控口令牌的权限范围（只读 / 遥控）
+export type ApiScope = 'status' | 'control';
+
+/// 遥控口设置（改动后重启恩兔生效）
+export interface ApiSettings {
+  enabled: boolean;
+  port: number;
+  /// 改为监听 Unix socket（仅 Unix）
+  socketPath?: string | null;
+}
+
+/// 遥控令牌概要（令牌本身只在发放/轮换时给出一次）
+export interface ApiTokenInfo {
+  id: string;
+  name: string;
+  scope: ApiScope;
+  createdAt: number;
+}
+
+/// 刚发放（或轮换）的令牌
+export interface IssuedApiToken {
+  info: ApiTokenInfo;
+  secret: string;
+}
+
+export interface BackupInfo {
+  /// 备份时间（Unix 秒，也是恢复时用的编号）
+  timestamp: number;
+  /// 文件大小（字节）
+  size: number;
+}
+
+export interface ProfileSummary {
+  name: string;
+  active: boolean;
+  supernode: string;
+  community: string;
+  /// 管理员下发的只读方案（总部、暗号、密语要先解锁才能改）
+  read_only: boolean;
+  /// 随手记（负责人、用途、到期时间……）
+  notes: string;
+  tags: string[];
+  /// 创建/最后修改时间（Unix 秒，老方案可能没有）
+  created_at?: number | null;
+  modified_at?: number | null;
+  /// 约好换密语的时间（Unix 秒，没约时为空）
+  key_rotation_at?: number | null;
+}
+
+/// 一次工作结束后的总结（session-ended 事件 / 工作日志本）
+export interface SessionSummary {
+  startedAt: number;
+  endedAt: number;
+  durationSecs: number;
+  supernode: string;
+  community: string;
+  txPackets: number;
+  rxPackets: number;
+  txBytes?: number | null;
+  rxBytes?: number | null;
+  reconnectCount: number;
+  pauseCount: number;
+  reason: 'user_stop' | 'force_stop' | 'edge_exited' | 'state_repaired' | 'restart' | string;
+  error?: string | null;
+  /// 最近一次 edge 意外摔倒的原因码（exit_ 开头，见 exit_reason.rs）
+  exitReason?: string | null;
+  /// 本次工作的 edge 原始输出录音
+  outputFile?: string | null;
+}
+
+/// 自家总部的值班记录（本机 supernode 统计）
+export interface CommunityStats {
+  name: string;
+  edgeCount: number;
+  isFederation: boolean;
+}
+
+export interface PacketStats {
+  kind: string;
+  txPkt: number;
+  rxPkt: number;
+}
+
+export interface SupernodeStats {
+  port: number;
+  communities: CommunityStats[];
+  packets: PacketStats[];
+  startTime?: number | null;
+}
+
 export interface N2NConfig {
   supernode: string;
   community: string;
   username: string;
   encryption_key: string;
+  /// 密语在系统保险箱里的取件单（后端自动维护）
+  encryption_key_ref?: string | null;
   ip_mode: string;
   static_ip?: string | null;
-  extra_args?: string | null;
+  /// 数据加密方式（-A<n>，default 交给 edge 决定）
+  cipher?: Cipher;
+  /// 包头加密（-H）
+  header_encryption?: boolean;
+  /// 数据压缩（-z<n>）
+  compression?: Compression;
+  /// 路由表（建立通道时翻译成 -n）
+  routes?: Route[];
+  /// 中转节点（-r，并打开系统的 IP 转发）
+  packet_forwarding?: boolean;
+  /// 高级选项（一条一条交给 edge 的开关）
+  advanced_options?: EdgeOption[];
   edge_path?: string | null;
   tap_device?: string | null;
   mtu?: number | null;
+  /// 固定 edge 的本机 UDP 端口（-p，留空由 edge 随便挑）
+  local_port?: number | null;
+  /// 报到（打洞保活）间隔（-i，秒；留空用 edge 默认的 20 秒）
+  register_interval_secs?: number | null;
+  /// 指定虚拟网卡的 MAC（-m，留空由 edge 随机挑）
+  mac_address?: string | null;
+  /// edge 管理口（-t，留空用默认的 5644）和门禁口令（--management-password）
+  management_port?: number | null;
+  management_password?: string | null;
+  log_forward?: LogForwardFilter;
+  supernode_id?: string | null;
+  /// 按域名找总部（建立通道前查 _n2n._udp.<域名> 的 SRV 记录）
+  supernode_dns?: string | null;
+  important_peers?: string[];
+  system_log?: boolean;
+  desktop_vpn_hint?: boolean;
+  reconnect_policy?: ReconnectPolicy;
+  reconnect_max_attempts?: number;
+  reconnect_backoff_max_secs?: number;
+  edge_hardening?: boolean;
+  edge_run_as?: string | null;
+  /// edge 指纹白名单（SHA-256，留空则记住第一次见到的指纹）
+  edge_sha256?: string[];
+  edge_integrity_bypass?: boolean;
+  auto_connect?: boolean;
+  port_forwards?: PortForward[];
+  /// 唤醒规则（按时叫醒同伴）
+  wake_rules?: WakeRule[];
+  /// 敲门步骤（edge 每次出门前照着敲一遍总部的门）
+  port_knock?: KnockStep[];
+  /// 温柔停止后最多等多久（秒），超时就强制清理
+  stop_timeout_secs?: number;
+  /// edge 走后虚拟网卡还挂着地址/路由时，顺手重置网卡
+  tap_reset_on_teardown?: boolean;
+  /// 连着时虚拟网卡坏了（不见了、被停用、地址掉了）就重新出门
+  tap_restart_on_loss?: boolean;
+  /// 定期写状态快照的文件（.prom 写 Prometheus 文本格式，其余写 JSON）
+  status_snapshot_path?: string | null;
+  status_snapshot_interval_secs?: number;
+  /// 连着的时候多久重新查一次总部域名（秒，0 表示不查）
+  supernode_recheck_secs?: number;
+  /// 备用总部（host:port）：总部一直摸不到时按顺序换下一家
+  supernode_failover?: string[];
+  /// 网关模式：无人值守的站点网关（开机建立通道、总是重连、替同伴转发、写系统日志）
+  server_mode?: boolean;
+}
+
+/// 一条路由（netmask 可以写 255.255.255.0 或前缀长度 24）
+export interface Route {
+  network: string;
+  netmask: string;
+  gateway: string;
+}
+
+/// 一条高级选项（不需要值的开关 value 留空）
+export interface EdgeOption {
+  flag: string;
+  value?: string | null;
+  enabled: boolean;
+}
+
+/// 一条唤醒规则（at 是本地时间 HH:MM，留空只能手动叫）
+export interface WakeRule {
+  name: string;
+  mac: string;
+  peer_ip: string;
+  at?: string | null;
+  enabled: boolean;
+}
+
+/// 一次唤醒的结果（get_wake_history / wake-finished）
+export interface WakeRecord {
+  name: string;
+  mac: string;
+  peerIp: string;
+  scheduled: boolean;
+  sentAt: number;
+  onlineAfterSecs?: number | null;
+  error?: string | null;
+}
+
+/// 传话筒规则（listen / target 都写成 host:port）
+export interface PortForward {
+  name: string;
+  listen: string;
+  target: string;
+  enabled: boolean;
+}
+
+/// 敲门的一步（payload 以 hex: 开头时按十六进制发；TCP 不带内容）
+export interface KnockStep {
+  protocol: 'udp' | 'tcp';
+  port: number;
+  payload: string;
+  delay_ms: number;
+}
+
+/// 传话筒规则现在的情况（get_port_forwards）
+export interface ForwardStatus {
+  rule: PortForward;
+  listening: boolean;
+  error: string | null;
+  active: number;
+  total: number;
+}
+
+/// 花名册里的一位成员（ip 可以带前缀长度）
+export interface RosterMember {
+  name: string;
+  ip: string;
+  notes: string;
+}
+
+/// 一个小组的花名册（get_roster / save_roster）
+export interface Roster {
+  community: string;
+  /// 小组网段（CIDR）
+  subnet: string;
+  members: RosterMember[];
 }
 
-export type ConnectionStatus = 'disconnected' | 'connecting' | 'disconnecting' | 'connected' | 'error';
+/// 邀请链接，外加撞上的成员（encode_share_link）
+export interface ShareInvite {
+  link: string;
+  conflict: RosterMember | null;
+}
+
+/// 认路对照表里的一条规则（ssid / interface 留空表示不看，都写时要同时满足）
+export interface NetworkRule {
+  ssid: string;
+  interface: string;
+  profile: string;
+}
+
+/// 恩兔眼里现在的网络（get_network_snapshot）
+export interface NetworkSnapshot {
+  ssids: string[];
+  interfaces: string[];
+}
+
+/// 数据加密方式（null 是不加密）
+export type Cipher = 'default' | 'null' | 'twofish' | 'aes' | 'chacha20' | 'speck';
+
+/// 数据压缩方式
+export type Compression = 'none' | 'lzo' | 'zstd';
+
+/// edge 会的本事（从 edge -h 的说明书里看出来）
+export interface EdgeCaps {
+  version?: string | null;
+  /** 大版本（2 或 3） */
+  generation?: number | null;
+  /** 认得 -f（前台运行） */
+  foreground: boolean;
+  /** 加密方式按 -A<n> 编号选（2.8 以前只有一个 -A） */
+  cipherLevels: boolean;
+  ciphers: Cipher[];
+  headerEncryption: boolean;
+  compressions: Compression[];
+}
+
+/// 机器上没人照看的 edge
+export interface OrphanEdge {
+  pid: number;
+  community?: string | null;
+  supernode?: string | null;
+  managementPort: number;
+  tapDevice?: string | null;
+  /** 和当前方案对得上，可以接手 */
+  matchesConfig: boolean;
+}
+
+/// 当前方案实际会用的 edge 的底细
+export interface EdgeInfo {
+  path: string;
+  found: boolean;
+  caps?: EdgeCaps | null;
+}
+
+/// 机器上找到的一个 edge（工具箱路径的候选）
+export interface EdgeCandidate {
+  path: string;
+  source: 'path' | 'bundled' | 'common';
+  version?: string | null;
+  arch?: string | null;
+}
+
+/// 恩兔的身份卡（版本、构建指纹、运行环境）
+export interface AppInfo {
+  version: string;
+  git_hash: string;
+  build_profile: string;
+  tauri_version: string;
+  rust_version: string;
+  edge_path: string;
+  edge_version?: string | null;
+  os: string;
+  os_version?: string | null;
+  arch: string;
+  webview_version?: string | null;
+}
+
+/// NAT 侦察结论
+export type NatVerdict = 'public' | 'nat' | 'double_nat' | 'cgnat' | 'unknown';
+
+/// NAT 侦察报告
+export interface NatReport {
+  localIp?: string | null;
+  gateway?: string | null;
+  routerWanIp?: string | null;
+  /// 外网口地址是怎么问到的（nat-pmp / upnp）
+  routerWanSource?: string | null;
+  externalIp?: string | null;
+  verdict: NatVerdict;
+  relayRecommended: boolean;
+}
+
+/// 断线重连策略（从不 / 出错时 / 总是）
+export type ReconnectPolicy = 'never' | 'on_failure' | 'always';
+
+/// 工作汇报的转递范围
+export type LogForwardFilter = 'all' | 'warnings_and_errors' | 'connection_events';
+
+/// 工作台配色偏好（system 会实时跟随系统明暗）
+export type ThemePreference = 'system' | 'light' | 'dark';
+
+/// 恩兔自己的偏好（get_preferences / save_preferences，和工作指示分开保存）
+export interface AppPreferences {
+  theme: ThemePreference;
+  /// 登录系统后自动打开恩兔
+  autostart: boolean;
+  /// 弹出提醒气泡（关掉后只弹警告和错误）
+  notifications: boolean;
+  /// 点关闭按钮时躲进托盘
+  close_to_tray: boolean;
+  /// 同步文件夹（几台机器共用一套方案；null 表示不同步）
+  sync_dir?: string | null;
+}
+
+export type ConnectionStatus = 'disconnected' | 'connecting' | 'disconnecting' | 'connected' | 'degraded' | 'paused' | 'reconnecting' | 'error';
+
+/// edge 实际生效的通道特性（加密/压缩/包头加密）
+export interface TransportFeatures {
+  cipher?: string | null;
+  compression?: string | null;
+  headerEncryption?: boolean | null;
+}
+
+/// 汇报本的使用情况（写满时会撕掉最旧的汇报）
+export interface LogStats {
+  buffered: number;
+  capacity: number;
+  dropped: number;
+}
+
+/// 虚拟网卡在系统里的身份（按 MAC 对照得到）
+export interface TapAdapter {
+  mac: string;
+  friendlyName: string;
+  ifIndex: number;
+}
 
 export interface StatusResponse {
   status: ConnectionStatus;
   error: string | null;
   networkInfo?: NetworkInfo | null;
+  transport?: TransportFeatures | null;
+  tapAdapter?: TapAdapter | null;
+  /// 自动重连被临时叫停到什么时候（Unix 秒）
+  reconnectSuspendedUntil?: number | null;
+  /// 实际交给 edge 的工号（没填时由主机名整理而来）
+  nodeName?: string | null;
+  /// 本次通道加上的路由（没连上时为空）
+  routes?: Route[];
+  /// edge 实际在用的本机 UDP 端口
+  localPort?: number | null;
+  /// edge 实际在用的报到间隔（秒）
+  registerIntervalSecs?: number | null;
+  /// 正等着第几次自动重连（status 为 reconnecting 时才有）
+  reconnectAttempt?: number;
+  /// edge 摔倒的原因码（status 为 error 时才有，比如 exit_tap_failed）
+  exitReason?: string | null;
+}
+
+/// 一条副通道（按别的方案另开的连接，id 是方案名）
+export interface SideConnection extends StatusResponse {
+  id: string;
 }
 
 export const defaultConfig: N2NConfig = {
@@ -48,8 +604,43 @@ export const defaultConfig: N2NConfig = {
   encryption_key: '',
   ip_mode: 'dhcp',
   static_ip: null,
-  extra_args: null,
+  cipher: 'default',
+  header_encryption: false,
+  compression: 'none',
+  routes: [],
+  packet_forwarding: false,
+  advanced_options: [],
   edge_path: null,
   tap_device: null,
   mtu: 1290,
+  local_port: null,
+  register_interval_secs: null,
+  mac_address: null,
+  management_port: null,
+  management_password: null,
+  log_forward: 'all',
+  supernode_id: null,
+  supernode_dns: null,
+  important_peers: [],
+  system_log: false,
+  desktop_vpn_hint: false,
+  reconnect_policy: 'never',
+  reconnect_max_attempts: 5,
+  reconnect_backoff_max_secs: 60,
+  edge_hardening: false,
+  edge_run_as: null,
+  edge_sha256: [],
+  edge_integrity_bypass: false,
+  auto_connect: false,
+  port_forwards: [],
+  wake_rules: [],
+  port_knock: [],
+  stop_timeout_secs: 10,
+  tap_reset_on_teardown: false,
+  tap_restart_on_loss: false,
+  status_snapshot_path: null,
+  status_snapshot_interval_secs: 30,
+  supernode_recheck_secs: 300,
+  supernode_failover: [],
+  server_mode: false,
 };